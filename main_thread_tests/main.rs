@@ -4,12 +4,21 @@ use pelican::graphics::Rectangle;
 use pelican::graphics::Point;
 use pelican::graphics::Size;
 use pelican::graphics::Image;
+use pelican::graphics::{Context, ImageCache};
 use pelican::ui::ImageView;
 use pelican::ui::{ViewController, ViewControllerBehavior};
 use pelican::ui::run_loop::RunLoop;
 use pelican::ui::timer::Timer;
 use pelican::ui::application::Application;
 use pelican::platform::thread;
+use pelican::testing::{assert_view_matches_snapshot, TestDriver};
+use pelican::ui::TextField;
+use pelican::ui::key::{KeyCode, ModifierFlag};
+use pelican::ui::press::Press;
+use pelican::ui::KeyCommand;
+use pelican::ui::notification::{self, NotificationCenter};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
 struct ExampleViewController {}
 impl ViewControllerBehavior for ExampleViewController {}
@@ -30,6 +39,24 @@ pub fn main() -> Result<(), String> {
     println!("custom test: scaled_images");
     scaled_images();
 
+    println!("custom test: headless");
+    headless();
+
+    println!("custom test: image_cache");
+    image_cache();
+
+    println!("custom test: layer_clipping");
+    layer_clipping();
+
+    println!("custom test: snapshots");
+    snapshots();
+
+    println!("custom test: test_driver");
+    test_driver();
+
+    println!("custom test: responder_chain");
+    responder_chain();
+
     println!("custom test: application");
     application();
 
@@ -165,6 +192,249 @@ fn scaled_images() {
     assert_ne!(raw1.query().height, raw2.query().height);
 }
 
+fn image_cache() {
+    let path = example_resources_directory().join("tree.png");
+    let path = path.to_str().unwrap();
+    ImageCache::clear();
+
+    let context = Context::new_headless(Size::new(50, 50), 1.0);
+    Image::new(path).layer_for(&context);
+    assert_eq!(ImageCache::layer_count(), 1);
+
+    // Once nothing else holds the first context, its layer is evicted when
+    // the image is drawn into another.
+    drop(context);
+    let other_context = Context::new_headless(Size::new(50, 50), 1.0);
+    Image::new(path).layer_for(&other_context);
+    assert_eq!(ImageCache::layer_count(), 1);
+
+    // An undecodable file shows a placeholder instead.
+    let broken = Image::new(example_resources_directory().join("missing.png").to_str().unwrap());
+    assert_eq!(broken.size(), &Size::new(1, 1));
+}
+
+fn headless() {
+    // Views outside a window are drawn offscreen.
+    let view = View::new(Rectangle::new(0, 0, 20, 10));
+    view.set_background_color(pelican::ui::Color::new(255, 0, 0, 255));
+
+    let child = View::new(Rectangle::new(10, 0, 10, 10));
+    child.set_background_color(pelican::ui::Color::new(0, 0, 255, 255));
+    view.add_subview(child);
+
+    let snapshot = view.snapshot();
+    assert_eq!(snapshot.size(), &Size::new(20, 10));
+    assert_eq!(snapshot.pixel_at(5, 5), Some(pelican::graphics::Color::RGBA(255, 0, 0, 255)));
+    assert_eq!(snapshot.pixel_at(15, 5), Some(pelican::graphics::Color::RGBA(0, 0, 255, 255)));
+    assert_eq!(snapshot.pixel_at(20, 5), None);
+
+    // At a scale of 2, there are twice as many pixels each way.
+    let context = pelican::graphics::Context::new_headless(Size::new(20, 10), 2.0);
+    assert!(context.is_headless());
+    let snapshot = view.snapshot_in_context(&context);
+    assert_eq!(snapshot.size(), &Size::new(20, 10));
+    assert_eq!(snapshot.pixel_size(), Size::new(40, 20));
+    assert_eq!(snapshot.pixel_at(25, 15), Some(pelican::graphics::Color::RGBA(0, 0, 255, 255)));
+
+    // A subtree snapshot grows to fit the view's shadow.
+    view.set_shadow(Some(pelican::ui::Shadow::new(pelican::ui::Color::new(0, 0, 0, 255), Point::new(4, 4), 0, 1.0)));
+    let snapshot = view.snapshot_subtree();
+    assert_eq!(snapshot.size(), &Size::new(24, 14));
+    assert_eq!(snapshot.pixel_at(5, 5), Some(pelican::graphics::Color::RGBA(255, 0, 0, 255)));
+    assert_eq!(snapshot.pixel_at(22, 12), Some(pelican::graphics::Color::RGBA(0, 0, 0, 255)));
+    assert_eq!(snapshot.pixel_at(22, 2).map(|color| color.a), Some(0));
+    view.set_shadow(None);
+
+    // Capturing a window draws everything in it.
+    let view_controller = ViewController::new(ExampleViewController {});
+    let window = Window::new_headless(Rectangle::new(0, 0, 40, 30), view_controller);
+    window.set_hidden(false);
+    window.view.set_background_color(pelican::ui::Color::new(0, 255, 0, 255));
+    window.view.add_subview(view.clone());
+
+    let capture = window.capture();
+    assert_eq!(capture.size(), &Size::new(40, 30));
+    assert_eq!(capture.pixel_at(5, 5), Some(pelican::graphics::Color::RGBA(255, 0, 0, 255)));
+    assert_eq!(capture.pixel_at(30, 20), Some(pelican::graphics::Color::RGBA(0, 255, 0, 255)));
+    view.remove_from_superview();
+}
+
+fn layer_clipping() {
+    use pelican::graphics::{Color, FillRule, Path};
+
+    let context = Context::new_headless(Size::new(20, 10), 1.0);
+    let view = View::new(Rectangle::new(0, 0, 20, 10));
+    view.set_background_color(pelican::ui::Color::new(255, 0, 0, 255));
+    view.snapshot_in_context(&context);
+
+    // The view's layer is drawn already, so snapshotting it again in the
+    // same context shows what's drawn into it below.
+    {
+        let layer = view.layer().unwrap();
+
+        // Clipping to rectangles narrows with each, and restoring goes back
+        // to the clipping saved.
+        layer.save();
+        layer.clip_to_rect(&Rectangle::new(0, 0, 10, 10));
+        layer.save();
+        layer.clip_to_rect(&Rectangle::new(5, 0, 10, 10));
+        layer.fill_rectangle_without_scaling(&Rectangle::new(0, 0, 20, 2), Color::RGBA(0, 255, 0, 255));
+        layer.restore();
+        layer.fill_rectangle_without_scaling(&Rectangle::new(0, 2, 20, 2), Color::RGBA(0, 0, 255, 255));
+        layer.restore();
+        layer.fill_rectangle_without_scaling(&Rectangle::new(0, 8, 20, 2), Color::RGBA(0, 0, 0, 255));
+    }
+
+    let snapshot = view.snapshot_in_context(&context);
+    assert_eq!(snapshot.pixel_at(2, 0), Some(Color::RGBA(255, 0, 0, 255)));
+    assert_eq!(snapshot.pixel_at(7, 0), Some(Color::RGBA(0, 255, 0, 255)));
+    assert_eq!(snapshot.pixel_at(12, 0), Some(Color::RGBA(255, 0, 0, 255)));
+    assert_eq!(snapshot.pixel_at(2, 3), Some(Color::RGBA(0, 0, 255, 255)));
+    assert_eq!(snapshot.pixel_at(12, 3), Some(Color::RGBA(255, 0, 0, 255)));
+    assert_eq!(snapshot.pixel_at(15, 9), Some(Color::RGBA(0, 0, 0, 255)));
+
+    // A semi-transparent clear inside a path blends over what's underneath
+    // in the color it was cleared with.
+    {
+        let layer = view.layer().unwrap();
+        layer.save();
+        layer.clip_to_path(&Path::rectangle(&Rectangle::new(0.0, 4.0, 10.0, 4.0)), FillRule::NonZero);
+        layer.clear_with_color(Color::RGBA(0, 0, 255, 128));
+        layer.restore();
+    }
+
+    let snapshot = view.snapshot_in_context(&context);
+    let blended = snapshot.pixel_at(5, 5).unwrap();
+    assert!((126..=129).contains(&blended.r), "{:?}", blended);
+    assert_eq!(blended.g, 0);
+    assert!((126..=129).contains(&blended.b), "{:?}", blended);
+    assert_eq!(blended.a, 255);
+    assert_eq!(snapshot.pixel_at(15, 5), Some(Color::RGBA(255, 0, 0, 255)));
+}
+
+fn snapshots() {
+    let directory = std::env::temp_dir().join(format!("pelican-snapshots-{}", std::process::id()));
+    std::env::set_var("PELICAN_SNAPSHOT_DIR", &directory);
+
+    let view = View::new(Rectangle::new(0, 0, 20, 10));
+    view.set_background_color(pelican::ui::Color::new(255, 0, 0, 255));
+
+    // A missing snapshot fails, with the drawing saved next to where it
+    // should be.
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| assert_view_matches_snapshot(&view, "red")));
+    assert!(result.is_err());
+    assert!(!directory.join("red.png").is_file());
+    assert!(directory.join("red.actual.png").is_file());
+
+    // Snapshots are recorded when asked to, then match.
+    std::env::set_var("PELICAN_UPDATE_SNAPSHOTS", "1");
+    assert_view_matches_snapshot(&view, "red");
+    std::env::remove_var("PELICAN_UPDATE_SNAPSHOTS");
+    assert!(directory.join("red.png").is_file());
+    assert_view_matches_snapshot(&view, "red");
+    assert!(!directory.join("red.actual.png").is_file());
+
+    // Changes within the tolerance still match.
+    view.set_background_color(pelican::ui::Color::new(254, 0, 0, 255));
+    assert_view_matches_snapshot(&view, "red");
+
+    view.set_background_color(pelican::ui::Color::new(0, 0, 255, 255));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| assert_view_matches_snapshot(&view, "red")));
+    assert!(result.is_err());
+    assert!(directory.join("red.actual.png").is_file());
+    assert!(directory.join("red.diff.png").is_file());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+    std::env::remove_var("PELICAN_SNAPSHOT_DIR");
+}
+
+fn test_driver() {
+    let view_controller = ViewController::new(ExampleViewController {});
+    let window = Window::new_headless(Rectangle::new(0, 0, 300, 200), view_controller);
+    window.make_key_and_visible();
+
+    let text_field = TextField::new(Rectangle::new(10, 10, 200, 40), String::new());
+    window.view.add_subview(text_field.view.clone());
+
+    let driver = TestDriver::new(&window);
+
+    // Tapping the text field gives it focus, so typing goes into it.
+    driver.tap(Point::new(20, 20));
+    assert_eq!(window.focused_view(), Some(text_field.view.clone()));
+
+    driver.type_text("hello");
+    driver.press_key(KeyCode::Backspace, &[]);
+    assert_eq!(text_field.label().text().string(), "hell");
+
+    driver.press_key(KeyCode::Z, &[ModifierFlag::Control]);
+    assert_eq!(text_field.label().text().string(), "hello");
+
+    // Pumping the run loop draws the window offscreen.
+    driver.pump();
+    assert!(window.view.layer().is_some());
+
+    // Timers only fire once the driver's clock reaches them.
+    let fired = std::rc::Rc::new(std::cell::Cell::new(false));
+    let fired_clone = fired.clone();
+    Timer::after(std::time::Duration::from_secs(1), move || fired_clone.set(true));
+    driver.pump();
+    assert!(!fired.get());
+    driver.advance(std::time::Duration::from_millis(1100));
+    assert!(fired.get());
+}
+
+struct PressRecordingViewController {
+    presses: Rc<RefCell<Vec<KeyCode>>>
+}
+
+impl ViewControllerBehavior for PressRecordingViewController {
+    fn press_began(&self, press: &Press) -> bool {
+        self.presses.borrow_mut().push(press.key().key_code());
+        true
+    }
+}
+
+fn responder_chain() {
+    let presses = Rc::new(RefCell::new(Vec::new()));
+    let view_controller = ViewController::new(PressRecordingViewController { presses: presses.clone() });
+    let window = Window::new_headless(Rectangle::new(0, 0, 300, 200), view_controller);
+    window.make_key_and_visible();
+
+    let text_field = TextField::new(Rectangle::new(10, 10, 200, 40), String::new());
+    window.view.add_subview(text_field.view.clone());
+
+    let driver = TestDriver::new(&window);
+
+    // With nothing focused, presses reach the window, which offers them to
+    // its view controller first.
+    driver.press_key(KeyCode::F1, &[]);
+    assert_eq!(*presses.borrow(), vec![KeyCode::F1]);
+
+    // The focused text field handles typing keys itself.
+    driver.tap(Point::new(20, 20));
+    driver.press_key(KeyCode::A, &[]);
+    assert_eq!(*presses.borrow(), vec![KeyCode::F1]);
+
+    // Keyboard shortcuts are matched before the text field sees the press.
+    let saved = Rc::new(Cell::new(false));
+    let saved_clone = saved.clone();
+    window.view.add_key_command(KeyCommand::new(KeyCode::S, &[ModifierFlag::Command], move || saved_clone.set(true)));
+    driver.press_key(KeyCode::S, &[ModifierFlag::Command]);
+    assert!(saved.get());
+
+    // Key command actions can open windows, as a New Window command does.
+    let new_window = Rc::new(RefCell::new(None));
+    let new_window_clone = new_window.clone();
+    window.view.add_key_command(KeyCommand::new(KeyCode::N, &[ModifierFlag::Command], move || {
+        let view_controller = ViewController::new(ExampleViewController {});
+        let window = Window::new("new window", Rectangle::new(0, 0, 100, 100), view_controller);
+        window.make_key_and_visible();
+        new_window_clone.replace(Some(window));
+    }));
+    driver.press_key(KeyCode::N, &[ModifierFlag::Command]);
+    assert!(new_window.borrow().is_some());
+}
+
 fn application() {
     let frame = Rectangle {
         origin: Point { x: 10, y: 10 },
@@ -178,9 +448,21 @@ fn application() {
         start_window_count = application.windows().len();
     }
 
-    let _ = Window::new("test", frame, view_controller);
-    let application = Application::borrow();
-    assert_eq!(application.windows().len(), start_window_count + 1);
+    let window = Window::new("test", frame, view_controller);
+    {
+        let application = Application::borrow();
+        assert_eq!(application.windows().len(), start_window_count + 1);
+    }
+
+    // Making the window key tells observers about it.
+    let became_key = Rc::new(Cell::new(false));
+    let became_key_clone = became_key.clone();
+    let token = NotificationCenter::add_observer(notification::WINDOW_DID_BECOME_KEY, move |notification| {
+        became_key_clone.set(notification.payload::<Window>().is_some());
+    });
+    window.make_key_and_visible();
+    assert!(became_key.get());
+    NotificationCenter::remove_observer(token);
 }
 
 fn example_resources_directory() -> std::path::PathBuf {
@@ -0,0 +1,182 @@
+//! Conversions between the sRGB colors stored in textures and linear light,
+//! where blending is physically correct.
+//!
+//! `Color` components are sRGB encoded: a component of 128 is much less
+//! than half as bright as 255. Averaging encoded components, as blending
+//! with alpha or stepping along a gradient does, gives results that are too
+//! dark in the middle, or washed out where a color fades into transparency.
+//! Converting to `LinearColor` first, and back once done, avoids that.
+
+use crate::graphics::Color;
+
+/// The linear light of an sRGB encoded component.
+pub fn srgb_to_linear(component: u8) -> f32 {
+    let value = component as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// The sRGB encoded component of linear light from 0 to 1.
+pub fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let encoded = if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+/// A color in linear light, with components from 0 to 1 and alpha not
+/// premultiplied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinearColor {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32
+}
+
+impl LinearColor {
+    pub fn new(red: f32, green: f32, blue: f32, alpha: f32) -> LinearColor {
+        LinearColor { red, green, blue, alpha }
+    }
+
+    pub fn from_srgb(color: &Color) -> LinearColor {
+        LinearColor {
+            red: srgb_to_linear(color.r),
+            green: srgb_to_linear(color.g),
+            blue: srgb_to_linear(color.b),
+            alpha: color.a as f32 / 255.0
+        }
+    }
+
+    pub fn to_srgb(&self) -> Color {
+        Color::RGBA(
+            linear_to_srgb(self.red),
+            linear_to_srgb(self.green),
+            linear_to_srgb(self.blue),
+            (self.alpha.clamp(0.0, 1.0) * 255.0).round() as u8
+        )
+    }
+
+    /// The components multiplied by alpha, as blending works with.
+    pub fn premultiplied(&self) -> LinearColor {
+        LinearColor::new(self.red * self.alpha, self.green * self.alpha, self.blue * self.alpha, self.alpha)
+    }
+
+    /// The components divided by alpha again, from premultiplied ones.
+    /// Fully transparent colors have no color left, and are black.
+    pub fn unpremultiplied(&self) -> LinearColor {
+        if self.alpha <= 0.0 {
+            return LinearColor::new(0.0, 0.0, 0.0, 0.0);
+        }
+        LinearColor::new(self.red / self.alpha, self.green / self.alpha, self.blue / self.alpha, self.alpha)
+    }
+
+    /// This color drawn over `background`.
+    pub fn over(&self, background: &LinearColor) -> LinearColor {
+        let source = self.premultiplied();
+        let background = background.premultiplied();
+        let remaining = 1.0 - source.alpha;
+
+        LinearColor::new(
+            source.red + background.red * remaining,
+            source.green + background.green * remaining,
+            source.blue + background.blue * remaining,
+            source.alpha + background.alpha * remaining
+        ).unpremultiplied()
+    }
+
+    /// The color `progress` of the way from this one to `to`. Interpolating
+    /// premultiplied components keeps a color fading into transparency from
+    /// picking up the transparent color's components.
+    pub fn interpolate(&self, to: &LinearColor, progress: f32) -> LinearColor {
+        let from = self.premultiplied();
+        let to = to.premultiplied();
+        let mix = |from: f32, to: f32| from + (to - from) * progress;
+
+        LinearColor::new(
+            mix(from.red, to.red),
+            mix(from.green, to.green),
+            mix(from.blue, to.blue),
+            mix(from.alpha, to.alpha)
+        ).unpremultiplied()
+    }
+}
+
+/// Alpha premultiplication of 8-bit colors, for textures with premultiplied
+/// alpha.
+pub trait Premultiply {
+    /// The color components multiplied by alpha.
+    fn premultiplied(&self) -> Self;
+
+    /// The color components divided by alpha again.
+    fn unpremultiplied(&self) -> Self;
+}
+
+impl Premultiply for Color {
+    fn premultiplied(&self) -> Color {
+        let multiply = |component: u8| ((component as u32 * self.a as u32 + 127) / 255) as u8;
+        Color::RGBA(multiply(self.r), multiply(self.g), multiply(self.b), self.a)
+    }
+
+    fn unpremultiplied(&self) -> Color {
+        if self.a == 0 {
+            return Color::RGBA(0, 0, 0, 0);
+        }
+        let divide = |component: u8| ((component as u32 * 255 + self.a as u32 / 2) / self.a as u32).min(255) as u8;
+        Color::RGBA(divide(self.r), divide(self.g), divide(self.b), self.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srgb_round_trip() {
+        for component in 0..=255 {
+            assert_eq!(linear_to_srgb(srgb_to_linear(component)), component);
+        }
+        assert_eq!(srgb_to_linear(0), 0.0);
+        assert_eq!(srgb_to_linear(255), 1.0);
+
+        // Half the light is much brighter than half the encoded value.
+        assert_eq!(linear_to_srgb(0.5), 188);
+    }
+
+    #[test]
+    fn test_interpolate_in_linear_light() {
+        let black = LinearColor::from_srgb(&Color::RGBA(0, 0, 0, 255));
+        let white = LinearColor::from_srgb(&Color::RGBA(255, 255, 255, 255));
+        assert_eq!(black.interpolate(&white, 0.5).to_srgb(), Color::RGBA(188, 188, 188, 255));
+
+        // Fading red out keeps it red, rather than darkening towards the
+        // transparent black.
+        let red = LinearColor::from_srgb(&Color::RGBA(255, 0, 0, 255));
+        let clear = LinearColor::from_srgb(&Color::RGBA(0, 0, 0, 0));
+        assert_eq!(red.interpolate(&clear, 0.5).to_srgb(), Color::RGBA(255, 0, 0, 128));
+    }
+
+    #[test]
+    fn test_over() {
+        let half_white = LinearColor::from_srgb(&Color::RGBA(255, 255, 255, 128));
+        let black = LinearColor::from_srgb(&Color::RGBA(0, 0, 0, 255));
+        assert_eq!(half_white.over(&black).to_srgb(), Color::RGBA(188, 188, 188, 255));
+
+        let clear = LinearColor::from_srgb(&Color::RGBA(0, 0, 0, 0));
+        assert_eq!(clear.over(&clear).to_srgb(), Color::RGBA(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_premultiply() {
+        let color = Color::RGBA(255, 128, 0, 128);
+        assert_eq!(color.premultiplied(), Color::RGBA(128, 64, 0, 128));
+        assert_eq!(color.premultiplied().unpremultiplied(), Color::RGBA(255, 128, 0, 128));
+        assert_eq!(Color::RGBA(10, 20, 30, 0).unpremultiplied(), Color::RGBA(0, 0, 0, 0));
+    }
+}
@@ -2,17 +2,25 @@ use crate::graphics::Size;
 use crate::graphics::Point;
 use crate::graphics::Rectangle;
 
+use crate::graphics::renderer::{CanvasRenderer, HeadlessRenderer, Renderer, RendererKind, RenderTarget};
+
 use sdl2::rect::Rect;
 use sdl2::render::Texture;
-use sdl2::video::Window;
-use sdl2::video::WindowContext;
-use sdl2::render::TextureCreator;
-use sdl2::render::Canvas;
+use sdl2::render::TextureAccess;
+use sdl2::render::BlendMode;
+use sdl2::surface::Surface;
+use sdl2::video::FullscreenType;
 use sdl2::pixels::Color;
+use sdl2::pixels::PixelFormatEnum;
 
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::convert::TryInto;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Headless contexts have no window to take an id from, so they count down
+/// from the largest id, away from SDL's window ids.
+static NEXT_HEADLESS_ID: AtomicU32 = AtomicU32::new(u32::MAX);
 
 pub struct SdlContainer {
     sdl: Option<Rc<sdl2::Sdl>>,
@@ -48,26 +56,32 @@ struct ContextInner {
     ///
     /// As opposed to the actual pixel size: `pixel_size`. This would be
     /// different to `pixel_size` if the display has has higher DPI.
-    size: Size<u32>,
+    size: RefCell<Size<u32>>,
 
     /// The actual pixel size of the drawable canvas.
     ///
     /// As opposed to the point size: `size`.
-    pixel_size: Size<u32>,
+    pixel_size: RefCell<Size<u32>>,
 
     /// The render scale. This would be different if using a higher density
     /// display.
-    render_scale: f32,
+    render_scale: Cell<f32>,
 
-    /// Internal SDL canvas
-    canvas: Rc<RefCell<Canvas<Window>>>,
+    /// Draws the window's layers, on the graphics card or in software.
+    renderer: RefCell<Box<dyn Renderer>>,
 
-    /// Internal SDL texture creator
-    texture_creator: TextureCreator<WindowContext>
+    /// The part of each texture that drawing is limited to, in pixels. See
+    /// `set_clip_rectangle`.
+    clip_rectangle: Cell<Option<Rect>>
 }
 
 impl Context {
     pub fn new(title: &str, position: Point<i32>, size: Size<u32>) -> Context {
+        Context::new_with_renderer(title, position, size, RendererKind::default())
+    }
+
+    /// A context for a new window, drawn by a renderer of `renderer_kind`.
+    pub fn new_with_renderer(title: &str, position: Point<i32>, size: Size<u32>, renderer_kind: RendererKind) -> Context {
         let sdl: &sdl2::Sdl;
 
         unsafe { sdl = SDL_CONTAINER.lazy(); }
@@ -85,32 +99,48 @@ impl Context {
         let id = window.id();
         let (render_width, render_height) = window.size();
 
-        let mut canvas = window.into_canvas().build().unwrap();
-
-
-        canvas.set_draw_color(Color::RGB(0, 0, 0));
-        canvas.clear();
-        canvas.present();
-
-        let (pixel_width, pixel_height) = canvas.output_size().unwrap();
+        let mut renderer = CanvasRenderer::new(window, renderer_kind);
+        renderer.clear(RenderTarget::Window, Color::RGB(0, 0, 0), None);
+        renderer.present();
 
         let render_size = Size { width: render_width, height: render_height };
-        let pixel_size = Size { width: pixel_width, height: pixel_height  };
+        let pixel_size = renderer.output_size();
 
         assert_eq!(render_size, size);
 
-        let render_scale = pixel_width as f32 / size.width as f32;
-
-        let texture_creator = canvas.texture_creator();
+        let render_scale = pixel_size.width as f32 / size.width as f32;
 
         Context {
             inner: Rc::new(ContextInner {
                 id: id,
-                size: size,
-                render_scale: render_scale,
-                canvas: Rc::new(RefCell::new(canvas)),
-                pixel_size: pixel_size,
-                texture_creator: texture_creator
+                size: RefCell::new(size),
+                render_scale: Cell::new(render_scale),
+                renderer: RefCell::new(Box::new(renderer)),
+                pixel_size: RefCell::new(pixel_size),
+                clip_rectangle: Cell::new(None)
+            })
+        }
+    }
+
+    /// A context drawing into memory rather than a window, `size` points
+    /// at `render_scale`. It needs no display server, so views can be drawn
+    /// in tests, or for snapshots. See `View::snapshot_in_context`.
+    pub fn new_headless(size: Size<u32>, render_scale: f32) -> Context {
+        let pixel_size = Size {
+            width: (size.width as f32 * render_scale).round() as u32,
+            height: (size.height as f32 * render_scale).round() as u32
+        };
+
+        let renderer = HeadlessRenderer::new(&pixel_size);
+
+        Context {
+            inner: Rc::new(ContextInner {
+                id: NEXT_HEADLESS_ID.fetch_sub(1, Ordering::Relaxed),
+                size: RefCell::new(size),
+                render_scale: Cell::new(render_scale),
+                renderer: RefCell::new(Box::new(renderer)),
+                pixel_size: RefCell::new(pixel_size),
+                clip_rectangle: Cell::new(None)
             })
         }
     }
@@ -119,25 +149,173 @@ impl Context {
         self.inner.id
     }
 
-    pub(crate) fn texture_creator(&self) -> &TextureCreator<WindowContext> {
-        &self.inner.texture_creator
+    /// How many clones of the context there are, this one included, such
+    /// as those held by layers drawing into it.
+    pub(crate) fn reference_count(&self) -> usize {
+        Rc::strong_count(&self.inner)
+    }
+
+    /// Whether the context draws into memory rather than a window. See
+    /// `new_headless`.
+    pub fn is_headless(&self) -> bool {
+        self.inner.renderer.borrow().window().is_none()
+    }
+
+    /// A texture `width` by `height` pixels that can be drawn into this
+    /// context's textures.
+    pub(crate) fn create_texture(&self, format: Option<PixelFormatEnum>, access: TextureAccess, width: u32, height: u32) -> Texture {
+        self.inner.renderer.borrow().create_texture(format, access, width, height)
+    }
+
+    pub(crate) fn create_texture_from_surface(&self, surface: &Surface) -> Texture {
+        self.inner.renderer.borrow().create_texture_from_surface(surface)
+    }
+
+    /// The pixels of `texture`, which must be a target texture, as RGBA
+    /// bytes, row by row from the top left.
+    pub(crate) fn read_texture_pixels(&self, texture: &mut Texture) -> Vec<u8> {
+        self.inner.renderer.borrow_mut().read_pixels(RenderTarget::Texture(texture))
+    }
+
+    /// Which renderer draws the window. It's the one asked for when the
+    /// window was made, unless the graphics card couldn't be used.
+    pub fn renderer_kind(&self) -> RendererKind {
+        self.inner.renderer.borrow().kind()
     }
 
     pub fn render_scale(&self) -> f32 {
-        self.inner.render_scale
+        self.inner.render_scale.get()
     }
 
     pub fn size(&self) -> Size<u32> {
-        self.inner.size.clone()
+        self.inner.size.borrow().clone()
     }
 
     pub fn pixel_size(&self) -> Size<u32> {
-        self.inner.pixel_size.clone()
+        self.inner.pixel_size.borrow().clone()
+    }
+
+    /// Reads the size of the window again, after it was resized. Returns the
+    /// new point size.
+    ///
+    /// Layers made before the resize keep their size, so views have to make
+    /// new ones to fill the window.
+    pub(crate) fn update_size(&self) -> Size<u32> {
+        let renderer = self.inner.renderer.borrow();
+        let (width, height) = match renderer.window() {
+            Some(window) => window.size(),
+            None => return self.size()
+        };
+        let pixel_size = renderer.output_size();
+
+        let size = Size { width, height };
+        if width > 0 {
+            self.inner.render_scale.set(pixel_size.width as f32 / width as f32);
+        }
+
+        self.inner.size.replace(size.clone());
+        self.inner.pixel_size.replace(pixel_size);
+        size
+    }
+
+    /// The title in the window's title bar.
+    pub(crate) fn title(&self) -> String {
+        self.inner.renderer.borrow().window().map_or(String::new(), |window| window.title().to_string())
+    }
+
+    pub(crate) fn set_title(&self, title: &str) {
+        // SDL can't take a title with a nul in it, so it's cut off there.
+        let title = title.split('\0').next().unwrap_or("");
+        if let Some(window) = self.inner.renderer.borrow_mut().window_mut() {
+            window.set_title(title).unwrap();
+        }
+    }
+
+    /// The smallest size the user can resize the window to, or zero if
+    /// there's no limit.
+    pub(crate) fn minimum_size(&self) -> Size<u32> {
+        let (width, height) = self.inner.renderer.borrow().window().map_or((0, 0), |window| window.minimum_size());
+        Size { width, height }
+    }
+
+    pub(crate) fn set_minimum_size(&self, size: &Size<u32>) {
+        if let Some(window) = self.inner.renderer.borrow_mut().window_mut() {
+            let (width, height) = clamped_window_size(size);
+            if let Err(error) = window.set_minimum_size(width, height) {
+                println!("Warning: could not set the minimum window size: {}", error);
+            }
+        }
+    }
+
+    /// The largest size the user can resize the window to, or zero if
+    /// there's no limit.
+    pub(crate) fn maximum_size(&self) -> Size<u32> {
+        let (width, height) = self.inner.renderer.borrow().window().map_or((0, 0), |window| window.maximum_size());
+        Size { width, height }
+    }
+
+    pub(crate) fn set_maximum_size(&self, size: &Size<u32>) {
+        if let Some(window) = self.inner.renderer.borrow_mut().window_mut() {
+            let (width, height) = clamped_window_size(size);
+            if let Err(error) = window.set_maximum_size(width, height) {
+                println!("Warning: could not set the maximum window size: {}", error);
+            }
+        }
+    }
+
+    /// Where the window is on screen, in pixels: with its title bar and
+    /// borders, then without. `None` for a headless context.
+    #[cfg(all(feature = "accesskit", target_os = "linux"))]
+    pub(crate) fn screen_frames(&self) -> Option<(Rectangle<i32, u32>, Rectangle<i32, u32>)> {
+        let renderer = self.inner.renderer.borrow();
+        let window = renderer.window()?;
+        let (x, y) = window.position();
+        let (width, height) = window.size();
+        let (top, left, bottom, right) = window.border_size().unwrap_or((0, 0, 0, 0));
+
+        let inner = Rectangle::new(x, y, width, height);
+        let outer = Rectangle::new(
+            x - left as i32,
+            y - top as i32,
+            width + left as u32 + right as u32,
+            height + top as u32 + bottom as u32
+        );
+        Some((outer, inner))
+    }
+
+    pub(crate) fn is_resizable(&self) -> bool {
+        let flags = self.inner.renderer.borrow().window().map_or(0, |window| window.window_flags());
+        flags & sdl2::sys::SDL_WindowFlags::SDL_WINDOW_RESIZABLE as u32 != 0
+    }
+
+    pub(crate) fn set_resizable(&self, resizable: bool) {
+        let renderer = self.inner.renderer.borrow();
+        let window = match renderer.window() {
+            Some(window) => window,
+            None => return
+        };
+        let resizable = if resizable { sdl2::sys::SDL_bool::SDL_TRUE } else { sdl2::sys::SDL_bool::SDL_FALSE };
+
+        // The SDL bindings don't wrap this one.
+        unsafe { sdl2::sys::SDL_SetWindowResizable(window.raw(), resizable) };
+    }
+
+    pub(crate) fn is_fullscreen(&self) -> bool {
+        self.inner.renderer.borrow().window().is_some_and(|window| window.fullscreen_state() != FullscreenType::Off)
+    }
+
+    /// Makes the window fill the screen it's on, at the desktop's
+    /// resolution, or returns it to its size before.
+    pub(crate) fn set_fullscreen(&self, fullscreen: bool) {
+        let fullscreen_type = if fullscreen { FullscreenType::Desktop } else { FullscreenType::Off };
+        let mut renderer = self.inner.renderer.borrow_mut();
+        if let Some(Err(error)) = renderer.window_mut().map(|window| window.set_fullscreen(fullscreen_type)) {
+            println!("Warning: could not change fullscreen: {}", error);
+        }
     }
 
     pub fn draw(&self) {
-        let mut canvas = self.inner.canvas.borrow_mut();
-        canvas.present();
+        self.inner.renderer.borrow_mut().present();
     }
 
     // TODO: pub(crate)
@@ -149,8 +327,8 @@ impl Context {
             destination.size.height
         );
 
-        let mut canvas = self.inner.canvas.borrow_mut();
-        canvas.copy(child, None, destination).unwrap();
+        let mut renderer = self.inner.renderer.borrow_mut();
+        renderer.copy(RenderTarget::Window, child, None, destination, None);
     }
 
     pub(crate) fn draw_texture_in_texture(&self, parent: &mut Texture, child: &Texture, source: Option<&Rectangle<i32, u32>>, destination: &Rectangle<i32, u32>) {
@@ -173,20 +351,120 @@ impl Context {
             destination.size.height
         );
 
-        let mut canvas = self.inner.canvas.borrow_mut();
+        let mut renderer = self.inner.renderer.borrow_mut();
+        renderer.copy(RenderTarget::Texture(parent), child, source_rect, destination, self.inner.clip_rectangle.get());
+    }
+
+    /// Like `draw_texture_in_texture`, but rotating `child` clockwise by
+    /// `angle` degrees about the center of `destination`, after flipping it.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn draw_texture_in_texture_rotated(
+        &self,
+        parent: &mut Texture,
+        child: &Texture,
+        source: Option<&Rectangle<i32, u32>>,
+        destination: &Rectangle<i32, u32>,
+        angle: f64,
+        flip_horizontal: bool,
+        flip_vertical: bool
+    ) {
+        let source_rect = source.map(|source| Rect::new(
+            source.origin.x,
+            source.origin.y,
+            source.size.width,
+            source.size.height
+        ));
+
+        let destination = Rect::new(
+            destination.origin.x,
+            destination.origin.y,
+            destination.size.width,
+            destination.size.height
+        );
+
+        let mut renderer = self.inner.renderer.borrow_mut();
+        renderer.copy_rotated(
+            RenderTarget::Texture(parent),
+            child,
+            source_rect,
+            destination,
+            angle,
+            flip_horizontal,
+            flip_vertical,
+            self.inner.clip_rectangle.get()
+        );
+    }
 
-        canvas.with_texture_canvas(parent, |canvas| {
-            canvas.copy(&child, source_rect, destination).unwrap();
-        }).unwrap();
+    pub(crate) fn fill_rectangle_in_texture(&self, texture: &mut Texture, color: Color, rectangle: &Rectangle<i32, u32>) {
+        let rectangle = Rect::new(
+            rectangle.origin.x,
+            rectangle.origin.y,
+            rectangle.size.width,
+            rectangle.size.height
+        );
+
+        let mut renderer = self.inner.renderer.borrow_mut();
+        renderer.fill_rectangles(RenderTarget::Texture(texture), color, &[rectangle], BlendMode::Blend, self.inner.clip_rectangle.get());
+    }
+
+    /// Fills each of `rectangles` with `color`. With `BlendMode::None`, the
+    /// color replaces what's there, so a transparent color erases.
+    pub(crate) fn fill_rectangles_in_texture(&self, texture: &mut Texture, color: Color, rectangles: &[Rectangle<i32, u32>], blend_mode: BlendMode) {
+        let rectangles: Vec<Rect> = rectangles
+            .iter()
+            .map(|rectangle| Rect::new(
+                rectangle.origin.x,
+                rectangle.origin.y,
+                rectangle.size.width,
+                rectangle.size.height
+            ))
+            .collect();
+
+        let mut renderer = self.inner.renderer.borrow_mut();
+        renderer.fill_rectangles(RenderTarget::Texture(texture), color, &rectangles, blend_mode, self.inner.clip_rectangle.get());
     }
 
     pub(crate) fn clear_texture(&self, texture: &mut Texture, color: Color) {
-        let mut canvas = self.inner.canvas.borrow_mut();
+        let mut renderer = self.inner.renderer.borrow_mut();
+        renderer.clear(RenderTarget::Texture(texture), color, self.inner.clip_rectangle.get());
+    }
+
+    /// The part of each texture drawing is limited to, in pixels, if any.
+    /// See `set_clip_rectangle`.
+    pub(crate) fn clip_rectangle(&self) -> Option<Rectangle<i32, u32>> {
+        self.inner.clip_rectangle.get().map(|rectangle| Rectangle::new(
+            rectangle.x(),
+            rectangle.y(),
+            rectangle.width(),
+            rectangle.height()
+        ))
+    }
+
+    /// Limits drawing into textures to `rectangle`, in pixels, until it's
+    /// set back to `None`. Used to only draw the parts of layers that
+    /// changed.
+    pub(crate) fn set_clip_rectangle(&self, rectangle: Option<&Rectangle<i32, u32>>) {
+        let rectangle = rectangle.map(|rectangle| Rect::new(
+            rectangle.origin.x,
+            rectangle.origin.y,
+            rectangle.size.width,
+            rectangle.size.height
+        ));
+        self.inner.clip_rectangle.set(rectangle);
+    }
 
-        canvas.with_texture_canvas(texture, |canvas| {
-            canvas.set_draw_color(color);
-            canvas.clear();
-        }).unwrap();
+    /// Fills `rectangle` of the window, in pixels, over what's been drawn
+    /// to it, until the next frame is drawn.
+    pub(crate) fn fill_rectangle_in_context(&self, color: Color, rectangle: &Rectangle<i32, u32>) {
+        let rectangle = Rect::new(
+            rectangle.origin.x,
+            rectangle.origin.y,
+            rectangle.size.width,
+            rectangle.size.height
+        );
+
+        let mut renderer = self.inner.renderer.borrow_mut();
+        renderer.fill_rectangles(RenderTarget::Window, color, &[rectangle], BlendMode::Blend, None);
     }
 }
 
@@ -197,3 +475,21 @@ impl Clone for Context {
         }
     }
 }
+
+/// `size` as a window size SDL accepts, whose sides can't be larger than
+/// `i32::MAX`.
+fn clamped_window_size(size: &Size<u32>) -> (u32, u32) {
+    let max = i32::MAX as u32;
+    (size.width.min(max), size.height.min(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamped_window_size() {
+        assert_eq!(clamped_window_size(&Size { width: 640, height: 480 }), (640, 480));
+        assert_eq!(clamped_window_size(&Size { width: u32::MAX, height: 480 }), (i32::MAX as u32, 480));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::graphics::{Rectangle, Size};
+
+/// Distances in from each edge of a rectangle, in points, such as padding
+/// between a view's edges and its content. Negative insets are outsets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EdgeInsets {
+    pub top: i32,
+    pub left: i32,
+    pub bottom: i32,
+    pub right: i32
+}
+
+impl EdgeInsets {
+    pub fn new(top: i32, left: i32, bottom: i32, right: i32) -> EdgeInsets {
+        EdgeInsets { top, left, bottom, right }
+    }
+
+    /// The same inset from every edge.
+    pub fn uniform(inset: i32) -> EdgeInsets {
+        EdgeInsets::new(inset, inset, inset, inset)
+    }
+
+    /// `horizontal` from the left and right edges, and `vertical` from the
+    /// top and bottom ones.
+    pub fn symmetric(horizontal: i32, vertical: i32) -> EdgeInsets {
+        EdgeInsets::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    pub fn zero() -> EdgeInsets {
+        EdgeInsets::default()
+    }
+
+    /// The left and right insets together.
+    pub fn horizontal(&self) -> i32 {
+        self.left + self.right
+    }
+
+    /// The top and bottom insets together.
+    pub fn vertical(&self) -> i32 {
+        self.top + self.bottom
+    }
+
+    /// `size` with the insets taken off, down to nothing.
+    pub fn inset_size(&self, size: &Size<u32>) -> Size<u32> {
+        Size::new(
+            (size.width as i64 - self.horizontal() as i64).max(0) as u32,
+            (size.height as i64 - self.vertical() as i64).max(0) as u32
+        )
+    }
+
+    /// `size` with the insets added around it, such as the size a view
+    /// needs to fit content of `size` inside its insets.
+    pub fn outset_size(&self, size: &Size<u32>) -> Size<u32> {
+        Size::new(
+            (size.width as i64 + self.horizontal() as i64).max(0) as u32,
+            (size.height as i64 + self.vertical() as i64).max(0) as u32
+        )
+    }
+}
+
+impl Rectangle<i32, u32> {
+    /// The part of the rectangle inside `insets`. A rectangle inset past
+    /// nothing is left empty, at its inset top left.
+    pub fn inset_by_edges(&self, insets: &EdgeInsets) -> Rectangle<i32, u32> {
+        let size = insets.inset_size(&self.size);
+        Rectangle::new(self.origin.x + insets.left, self.origin.y + insets.top, size.width, size.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inset_rectangle() {
+        let rectangle = Rectangle::new(10, 10, 100, 50);
+
+        assert_eq!(rectangle.inset_by_edges(&EdgeInsets::zero()), rectangle);
+        assert_eq!(rectangle.inset_by_edges(&EdgeInsets::new(1, 2, 3, 4)), Rectangle::new(12, 11, 94, 46));
+        assert_eq!(rectangle.inset_by_edges(&EdgeInsets::uniform(-5)), Rectangle::new(5, 5, 110, 60));
+        assert_eq!(rectangle.inset_by_edges(&EdgeInsets::symmetric(60, 0)), Rectangle::new(70, 10, 0, 50));
+    }
+
+    #[test]
+    fn test_sizes() {
+        let insets = EdgeInsets::symmetric(8, 4);
+
+        assert_eq!(insets.horizontal(), 16);
+        assert_eq!(insets.vertical(), 8);
+        assert_eq!(insets.inset_size(&Size::new(100, 20)), Size::new(84, 12));
+        assert_eq!(insets.outset_size(&Size::new(84, 12)), Size::new(100, 20));
+        assert_eq!(insets.inset_size(&Size::new(10, 5)), Size::new(0, 0));
+    }
+}
@@ -4,6 +4,8 @@ use crate::graphics::Context;
 use crate::graphics::Layer;
 use crate::graphics::Size;
 use crate::graphics::Color;
+use crate::graphics::{FontDescriptor, FontFace, FontRegistry, FontWeight};
+use sdl2::ttf::FontStyle;
 use std::rc::Rc;
 use std::collections::HashMap;
 use std::cell::RefCell;
@@ -11,22 +13,34 @@ use std::cell::RefCell;
 pub struct Font {
     path: String,
     size: u16,
+    family: String,
+    weight: FontWeight,
+    italic: bool,
+
+    /// Styles SDL needs to apply on top of the face at `path`, when the
+    /// requested traits weren't available as a face of their own.
+    synthetic_style: FontStyle,
 
     /// A new font needs to be constructed for each desired font size. These are
     /// lazily created and cached so as to not need to repeatedly load the same
     /// fonts.
-    font_sizes: Rc<RefCell<HashMap<u16, Rc<sdl2::ttf::Font<'static, 'static>>>>>
+    font_sizes: Rc<RefCell<FontCache>>
 }
 
-const PATHS: &[&str] = &[
+/// Loaded fonts keyed by size, whether kerning is enabled, and the synthetic
+/// style applied. Variants of the same face share a cache.
+type FontCache = HashMap<(u16, bool, FontStyle), Rc<sdl2::ttf::Font<'static, 'static>>>;
+
+pub(crate) const PATHS: &[&str] = &[
     "/System/Library/Fonts",
     "/System/Library/Fonts/Cache",
     "/System/Library/Fonts/Supplemental"
 ];
 
-const TYPES: &[&str] = &[
+pub(crate) const TYPES: &[&str] = &[
     ".ttc",
     ".ttf",
+    ".otf",
     ".fon",
     ""
 ];
@@ -51,17 +65,199 @@ static mut TTF_CONTAINER: SdlTtfContainer = SdlTtfContainer {
 };
 
 impl Font {
+    /// Loads the font named `font_name`. This is first looked up as a file
+    /// name in the system and bundle font directories, and otherwise
+    /// resolved as a descriptor such as "Helvetica Bold" through the shared
+    /// `FontRegistry`.
     pub fn new(font_name: &str, size: u16) -> Font {
-        let path = find_font(font_name);
-        let font_sizes = Rc::new(RefCell::new(HashMap::new()));
-        Font { path, size, font_sizes }
+        if let Some(path) = find_font(font_name) {
+            let face = FontFace::from_name(font_name, &path);
+            return Font::new_with_face(&face, size, FontStyle::NORMAL);
+        }
+
+        let mut descriptor = FontDescriptor::parse(font_name)
+            .unwrap_or_else(|| panic!("Font {} not found", font_name));
+        descriptor.size = size;
+
+        Font::from_descriptor(&descriptor).unwrap_or_else(|| panic!("Font {} not found", font_name))
+    }
+
+    /// Resolves a descriptor string such as "Helvetica Bold 17" to a font.
+    /// Returns `None` if the descriptor can't be parsed or the family isn't
+    /// installed.
+    pub fn from_descriptor_string(descriptor: &str) -> Option<Font> {
+        Font::from_descriptor(&FontDescriptor::parse(descriptor)?)
+    }
+
+    /// Resolves a descriptor to the closest installed face of its family.
+    /// Traits that no installed face provides (e.g. bold for a family with
+    /// only a regular face) are synthesized when rendering.
+    pub fn from_descriptor(descriptor: &FontDescriptor) -> Option<Font> {
+        let registry = FontRegistry::shared();
+        let face = registry.face_matching(&descriptor.family, descriptor.weight, descriptor.italic)?;
+        Some(Font::from_face(face, descriptor.weight, descriptor.italic, descriptor.size))
+    }
+
+    /// A font using `face`, synthesizing whichever of the given traits the
+    /// face doesn't have itself.
+    pub(crate) fn from_face(face: &FontFace, weight: FontWeight, italic: bool, size: u16) -> Font {
+        let mut font = Font::new_with_face(face, size, FontStyle::NORMAL);
+        font.apply_traits(face, weight, italic);
+        font
+    }
+
+    fn new_with_face(face: &FontFace, size: u16, synthetic_style: FontStyle) -> Font {
+        Font {
+            path: face.path.clone(),
+            size,
+            family: face.family.clone(),
+            weight: face.weight,
+            italic: face.italic,
+            synthetic_style,
+            font_sizes: Rc::new(RefCell::new(HashMap::new()))
+        }
+    }
+
+    /// Records the requested traits, synthesizing any that `face` lacks.
+    fn apply_traits(&mut self, face: &FontFace, weight: FontWeight, italic: bool) {
+        let mut synthetic_style = FontStyle::NORMAL;
+
+        if weight.is_bold() && !face.weight.is_bold() {
+            synthetic_style |= FontStyle::BOLD;
+        }
+
+        if italic && !face.italic {
+            synthetic_style |= FontStyle::ITALIC;
+        }
+
+        self.weight = weight;
+        self.italic = italic;
+        self.synthetic_style = synthetic_style;
     }
 
     pub fn default() -> Font {
         Font::new("Helvetica", 16)
     }
 
-    // Get a drawable layer from the font for the given context.
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    pub fn weight(&self) -> FontWeight {
+        self.weight
+    }
+
+    pub fn is_italic(&self) -> bool {
+        self.italic
+    }
+
+    pub fn is_bold(&self) -> bool {
+        self.weight.is_bold()
+    }
+
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+
+    /// A description of this font's family, traits and size.
+    pub fn descriptor(&self) -> FontDescriptor {
+        FontDescriptor::new(&self.family, self.weight, self.italic, self.size)
+    }
+
+    /// The same face at a different size.
+    pub fn with_size(&self, size: u16) -> Font {
+        Font { size, ..self.clone() }
+    }
+
+    /// The closest variant of this font's family with the given weight and
+    /// style, keeping the same size. If the family isn't in the registry
+    /// (e.g. it was loaded from a file in an unusual location), the traits
+    /// are synthesized from this font's face.
+    pub fn with_traits(&self, weight: FontWeight, italic: bool) -> Font {
+        let registry = FontRegistry::shared();
+        let face = registry
+            .face_matching(&self.family, weight, italic)
+            .cloned()
+            .unwrap_or_else(|| FontFace {
+                family: self.family.clone(),
+                weight: if self.synthetic_style.contains(FontStyle::BOLD) { FontWeight::Regular } else { self.weight },
+                italic: self.italic && !self.synthetic_style.contains(FontStyle::ITALIC),
+                path: self.path.clone()
+            });
+
+        let mut font = if face.path == self.path {
+            Font { synthetic_style: FontStyle::NORMAL, ..self.clone() }
+        } else {
+            Font::new_with_face(&face, self.size, FontStyle::NORMAL)
+        };
+        font.apply_traits(&face, weight, italic);
+        font
+    }
+
+    /// The bold variant of this font.
+    pub fn bold(&self) -> Font {
+        self.with_traits(FontWeight::Bold, self.italic)
+    }
+
+    /// The italic variant of this font.
+    pub fn italic(&self) -> Font {
+        self.with_traits(self.weight, true)
+    }
+
+    /// The names of all the installed font families.
+    pub fn families() -> Vec<String> {
+        FontRegistry::shared().families()
+    }
+
+    /// Whether this font has glyphs for every character in `text`.
+    /// Whitespace, control characters and invisible formatting characters
+    /// (such as zero width joiners and variation selectors) don't need
+    /// glyphs.
+    ///
+    /// SDL_ttf can only be asked about characters in the Basic Multilingual
+    /// Plane, so characters outside of it (e.g. most emoji) are assumed to
+    /// be provided.
+    pub fn provides_glyphs_for(&self, text: &str) -> bool {
+        let font = self.load_font_for_size(self.size);
+
+        text.chars().all(|character| {
+            character.is_whitespace() ||
+                character.is_control() ||
+                is_default_ignorable(character) ||
+                character as u32 > 0xFFFF ||
+                font.find_glyph(character).is_some()
+        })
+    }
+
+    /// The font to draw `text` with: this font if it has glyphs for all of
+    /// `text`, otherwise the first font in the shared registry's cascade
+    /// list that does, with the same traits and size. If no font has the
+    /// glyphs, this font is used anyway.
+    pub fn fallback_for(&self, text: &str) -> Font {
+        if self.provides_glyphs_for(text) {
+            return self.clone();
+        }
+
+        let cascade_fonts = FontRegistry::shared().cascade_fonts(self.weight, self.italic, self.size);
+
+        cascade_fonts
+            .into_iter()
+            .find(|font| font.path != self.path && font.provides_glyphs_for(text))
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// The change in width, in points, from the font's kerning pair for
+    /// `first` followed by `second`. This is usually negative, e.g. for
+    /// "AV".
+    pub fn kerning_between(&self, first: &str, second: &str) -> i32 {
+        let pair = format!("{}{}", first, second);
+        let (kerned_width, _) = self.load_font_for_size(self.size).size_of(&pair).unwrap();
+        let (unkerned_width, _) = self.load_font(self.size, false).size_of(&pair).unwrap();
+        kerned_width as i32 - unkerned_width as i32
+    }
+
+    // Get a drawable layer from the font for the given context. The text is
+    // drawn with the font's kerning pairs applied.
     pub fn layer_for(&self, context: &Context, text: &str, color: Color) -> Layer {
         let font_size = (self.size as f32 * context.render_scale()) as u16;
         let font = self.load_font_for_size(font_size);
@@ -73,7 +269,7 @@ impl Font {
             .blended(color)
             .unwrap();
 
-        let texture = surface.as_texture(context.texture_creator()).unwrap();
+        let texture = context.create_texture_from_surface(&surface);
 
         Layer::new_prerendered(
             context.clone(),
@@ -83,38 +279,46 @@ impl Font {
         )
     }
 
-    /// Get the size of the given string for this font.
+    /// Get the size of the given string for this font, without kerning.
+    /// Use `kerning_between` for the adjustment between characters.
     pub fn size_for(&self, text: &str) -> Size<u32> {
-        let font = self.load_font_for_size(self.size);
+        let font = self.load_font(self.size, false);
         let (width, height) = font.size_of(text).unwrap();
         Size { width, height }
     }
 
+    /// Loads a font from the given size, with kerning enabled.
+    fn load_font_for_size(&self, font_size: u16) -> Rc<sdl2::ttf::Font> {
+        self.load_font(font_size, true)
+    }
+
     /// Loads a font from the given size. This is a lazy operation, so the
     /// font will only be loaded if it is not already loaded (using
     /// `self.font_sizes`).
-    fn load_font_for_size(&self, font_size: u16) -> Rc<sdl2::ttf::Font> {
+    fn load_font(&self, font_size: u16, kerning: bool) -> Rc<sdl2::ttf::Font<'_, '_>> {
         let mut font_sizes = self.font_sizes.borrow_mut();
+        let key = (font_size, kerning, self.synthetic_style);
 
-        if font_sizes.get(&font_size).is_none() {
+        if font_sizes.get(&key).is_none() {
             let ttf_context = unsafe { TTF_CONTAINER.lazy() };
             let mut font = ttf_context.load_font(&self.path, font_size).unwrap();
-            font.set_kerning(false);
-            font_sizes.insert(font_size, Rc::new(font));
+            font.set_kerning(kerning);
+            font.set_style(self.synthetic_style);
+            font_sizes.insert(key, Rc::new(font));
         }
 
-        font_sizes.get(&font_size).unwrap().clone()
+        font_sizes.get(&key).unwrap().clone()
     }
 }
 
-fn find_font(font_name: &str) -> String {
+fn find_font(font_name: &str) -> Option<String> {
     // Find the font in system paths.
     for path in PATHS {
         for filetype in TYPES {
             let path = *path;
             let potential = format!("{}/{}{}", path, font_name, filetype);
-            if Path::new(&potential).exists() {
-                return potential;
+            if Path::new(&potential).is_file() {
+                return Some(potential);
             }
         }
     }
@@ -122,24 +326,38 @@ fn find_font(font_name: &str) -> String {
     // If it wasn't found in system paths, try the bundle path.
     for filetype in TYPES {
         let potential = Bundle::path_for_resource(&format!("{}{}", font_name, filetype));
-        if Path::new(&potential).exists() {
-            return potential;
+        if Path::new(&potential).is_file() {
+            return Some(potential);
         }
     }
 
     // If we've still not found it, we're out of ideas.
-    panic!("Font {} not found", font_name);
+    None
+}
+
+/// Characters which are never drawn by themselves, so fonts don't need glyphs
+/// for them.
+fn is_default_ignorable(character: char) -> bool {
+    matches!(
+        character as u32,
+        0x00AD | 0x034F | 0x061C | 0x180B..=0x180E | 0x200B..=0x200F | 0x202A..=0x202E |
+        0x2060..=0x206F | 0xFE00..=0xFE0F | 0xFEFF
+    )
 }
 
 impl PartialEq for Font {
     fn eq(&self, other: &Font) -> bool {
-        self.path == other.path && self.size == other.size
+        self.path == other.path &&
+            self.size == other.size &&
+            self.weight == other.weight &&
+            self.italic == other.italic &&
+            self.synthetic_style == other.synthetic_style
     }
 }
 
 impl std::fmt::Debug for Font {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Font {{ path: {}, size: {} }}", self.path, self.size)
+        write!(f, "Font {{ path: {}, size: {}, descriptor: {} }}", self.path, self.size, self.descriptor())
     }
 }
 
@@ -148,6 +366,10 @@ impl Clone for Font {
         Font {
             path: self.path.clone(),
             size: self.size,
+            family: self.family.clone(),
+            weight: self.weight,
+            italic: self.italic,
+            synthetic_style: self.synthetic_style,
             font_sizes: self.font_sizes.clone()
         }
     }
@@ -171,6 +393,58 @@ mod tests {
         assert_eq!(font.size, 16);
     }
 
+    #[test]
+    fn test_font_new_with_descriptor_name() {
+        let font = Font::new("Helvetica Bold", 17);
+        assert_eq!(font.path, "/System/Library/Fonts/Helvetica.ttc");
+        assert_eq!(font.descriptor(), FontDescriptor::new("Helvetica", FontWeight::Bold, false, 17));
+        assert_eq!(font.synthetic_style, FontStyle::BOLD);
+        assert_eq!(Font::from_descriptor_string("Helvetica Bold 17"), Some(font));
+        assert_eq!(Font::from_descriptor_string("Nonexistent Sans 12"), None);
+    }
+
+    #[test]
+    fn test_font_variants() {
+        let font = Font::new("Arial", 12);
+        assert_eq!(font.family(), "Arial");
+        assert!(!font.is_bold());
+
+        let bold_italic = font.bold().italic();
+        assert!(bold_italic.is_bold());
+        assert!(bold_italic.is_italic());
+        assert_eq!(bold_italic.size(), 12);
+        assert_eq!(bold_italic.synthetic_style, FontStyle::BOLD | FontStyle::ITALIC);
+        assert_ne!(bold_italic, font);
+
+        let regular = bold_italic.with_traits(FontWeight::Regular, false);
+        assert_eq!(regular, font);
+        assert_eq!(font.with_size(20).descriptor().size, 20);
+    }
+
+    #[test]
+    fn test_font_families() {
+        let families = Font::families();
+        assert!(families.contains(&String::from("Helvetica")));
+        assert!(families.contains(&String::from("Arial")));
+    }
+
+    #[test]
+    fn test_font_fallback_for() {
+        let font = Font::new("Helvetica", 16);
+        assert!(font.provides_glyphs_for("Hello\u{200D}\n"));
+        assert_eq!(font.fallback_for("Hello"), font);
+
+        assert!(!font.provides_glyphs_for("日本"));
+        let fallback = font.fallback_for("日本");
+        assert_eq!(fallback.family(), "PingFang");
+        assert_eq!(fallback.size(), 16);
+        assert!(fallback.provides_glyphs_for("日本"));
+
+        let fallback = font.bold().fallback_for("日本");
+        assert_eq!(fallback.family(), "PingFang");
+        assert!(fallback.is_bold());
+    }
+
     #[test]
     fn test_font_size_for() {
         let font = Font::new("Helvetica", 16);
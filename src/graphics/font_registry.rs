@@ -0,0 +1,510 @@
+use crate::platform::bundle::Bundle;
+use crate::graphics::Font;
+use crate::graphics::font::{PATHS, TYPES};
+use crate::macros::*;
+use std::cell::RefMut;
+use std::fmt;
+use std::path::Path;
+
+/// The weight (or thickness) of a font face, ordered from lightest to
+/// heaviest. The numeric values follow the common CSS/OpenType scale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FontWeight {
+    Thin = 100,
+    UltraLight = 200,
+    Light = 300,
+    Regular = 400,
+    Medium = 500,
+    Semibold = 600,
+    Bold = 700,
+    Heavy = 800,
+    Black = 900
+}
+
+impl FontWeight {
+    /// Parses a weight from a single word, such as "Bold" or "semibold".
+    /// Returns `None` if the word doesn't name a weight.
+    pub fn from_name(name: &str) -> Option<FontWeight> {
+        match name.to_lowercase().as_str() {
+            "thin" | "hairline" => Some(FontWeight::Thin),
+            "ultralight" | "extralight" => Some(FontWeight::UltraLight),
+            "light" => Some(FontWeight::Light),
+            "regular" | "normal" | "book" | "roman" => Some(FontWeight::Regular),
+            "medium" => Some(FontWeight::Medium),
+            "semibold" | "demibold" => Some(FontWeight::Semibold),
+            "bold" => Some(FontWeight::Bold),
+            "heavy" | "extrabold" | "ultrabold" => Some(FontWeight::Heavy),
+            "black" => Some(FontWeight::Black),
+            _ => None
+        }
+    }
+
+    /// The name used for this weight in descriptors and face names.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FontWeight::Thin => "Thin",
+            FontWeight::UltraLight => "UltraLight",
+            FontWeight::Light => "Light",
+            FontWeight::Regular => "Regular",
+            FontWeight::Medium => "Medium",
+            FontWeight::Semibold => "Semibold",
+            FontWeight::Bold => "Bold",
+            FontWeight::Heavy => "Heavy",
+            FontWeight::Black => "Black"
+        }
+    }
+
+    /// Whether a face of this weight should be considered bold.
+    pub fn is_bold(&self) -> bool {
+        *self >= FontWeight::Semibold
+    }
+
+    fn distance_to(&self, other: FontWeight) -> i32 {
+        (*self as i32 - other as i32).abs()
+    }
+}
+
+fn is_italic_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "italic" || name == "oblique"
+}
+
+/// Splits trailing weight and style words off `words`, returning the weight
+/// and whether the words named an italic style. `words` is left holding the
+/// family name.
+fn split_traits(words: &mut Vec<String>) -> (FontWeight, bool) {
+    let mut weight = None;
+    let mut italic = false;
+
+    while let Some(word) = words.last() {
+        if words.len() == 1 {
+            // The family name always needs at least one word.
+            break;
+        }
+
+        if is_italic_name(word) && !italic {
+            italic = true;
+        } else if let (None, Some(parsed)) = (weight, FontWeight::from_name(word)) {
+            weight = Some(parsed);
+        } else {
+            break;
+        }
+
+        words.pop();
+    }
+
+    (weight.unwrap_or(FontWeight::Regular), italic)
+}
+
+/// Splits a run of words written in camel case, such as "BoldItalic", into
+/// its component words.
+fn split_camel_case(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+
+    for character in text.chars() {
+        match words.last_mut() {
+            Some(word) if !character.is_uppercase() => word.push(character),
+            _ => words.push(character.to_string())
+        }
+    }
+
+    words
+}
+
+/// Families are compared ignoring case and whitespace, so "Helvetica Neue"
+/// will match a face file named "HelveticaNeue-Bold".
+fn family_key(family: &str) -> String {
+    family.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect()
+}
+
+/// The families tried, in order, when a font doesn't have a glyph for some
+/// text and no cascade list has been set with
+/// `FontRegistry::set_cascade_list`. Families that aren't installed are
+/// skipped.
+const DEFAULT_CASCADE_LIST: &[&str] = &[
+    "PingFang",
+    "Hiragino Sans",
+    "Apple SD Gothic Neo",
+    "Apple Symbols",
+    "Arial Unicode MS",
+    "Noto Sans CJK",
+    "Noto Sans Symbols",
+    "DejaVu Sans"
+];
+
+/// A description of a font in terms of its family and traits rather than a
+/// concrete face on disk, such as "Helvetica Bold Italic 17". Descriptors
+/// are resolved to faces through the `FontRegistry`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontDescriptor {
+    pub family: String,
+    pub weight: FontWeight,
+    pub italic: bool,
+    pub size: u16
+}
+
+impl FontDescriptor {
+    pub fn new(family: &str, weight: FontWeight, italic: bool, size: u16) -> FontDescriptor {
+        FontDescriptor { family: String::from(family), weight, italic, size }
+    }
+
+    /// Parses a descriptor written as a family name followed by optional
+    /// weight and style words and an optional point size, for example
+    /// "Helvetica Bold 17", "Helvetica Neue Light Italic" or "Arial 12".
+    /// The size defaults to 16 when omitted. Returns `None` if no family
+    /// name is present.
+    pub fn parse(descriptor: &str) -> Option<FontDescriptor> {
+        let mut words: Vec<String> = descriptor.split_whitespace().map(String::from).collect();
+
+        let size = match words.last().map(|word| word.parse::<u16>()) {
+            Some(Ok(size)) => {
+                words.pop();
+                size
+            },
+            _ => 16
+        };
+
+        if words.is_empty() {
+            return None;
+        }
+
+        let (weight, italic) = split_traits(&mut words);
+        Some(FontDescriptor { family: words.join(" "), weight, italic, size })
+    }
+}
+
+impl fmt::Display for FontDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.family)?;
+
+        if self.weight != FontWeight::Regular {
+            write!(f, " {}", self.weight.name())?;
+        }
+
+        if self.italic {
+            write!(f, " Italic")?;
+        }
+
+        write!(f, " {}", self.size)
+    }
+}
+
+/// A single installed font file along with the family and traits it
+/// provides.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFace {
+    pub family: String,
+    pub weight: FontWeight,
+    pub italic: bool,
+    pub path: String
+}
+
+impl FontFace {
+    /// Works out the family and traits of a face from its file name, e.g.
+    /// "Arial Bold Italic.ttf" or "HelveticaNeue-BoldItalic.otf". Names
+    /// without recognisable traits are treated as the regular face of a
+    /// family with the same name.
+    pub fn from_name(name: &str, path: &str) -> FontFace {
+        let (family, weight, italic) = match name.rfind('-') {
+            Some(index) => {
+                let mut words = vec![String::from(&name[..index])];
+                words.extend(split_camel_case(&name[index + 1..]));
+                let (weight, italic) = split_traits(&mut words);
+
+                if words.len() == 1 {
+                    (words.remove(0), weight, italic)
+                } else {
+                    (String::from(name), FontWeight::Regular, false)
+                }
+            },
+            None => {
+                let mut words: Vec<String> = name.split_whitespace().map(String::from).collect();
+                let (weight, italic) = split_traits(&mut words);
+                (words.join(" "), weight, italic)
+            }
+        };
+
+        FontFace { family, weight, italic, path: String::from(path) }
+    }
+
+    /// Builds a face from the file at `path`, or returns `None` if the file
+    /// doesn't have a font extension.
+    pub fn from_path(path: &Path) -> Option<FontFace> {
+        let extension = path.extension()?.to_str()?;
+        if !TYPES.iter().any(|filetype| filetype.len() > 1 && filetype[1..].eq_ignore_ascii_case(extension)) {
+            return None;
+        }
+
+        let name = path.file_stem()?.to_str()?;
+        Some(FontFace::from_name(name, path.to_str()?))
+    }
+}
+
+/// Keeps track of the font faces available to the application, grouped by
+/// family. The shared registry is populated lazily from the system font
+/// directories and the bundle's resources the first time it is used, and
+/// additional faces can be registered by hand.
+///
+/// The registry also holds the cascade list of families used for font
+/// fallback, when the font chosen for some text doesn't have glyphs for it.
+pub struct FontRegistry {
+    faces: Vec<FontFace>,
+    scanned: bool,
+
+    /// The families to fall back to, or `None` to use
+    /// `DEFAULT_CASCADE_LIST`.
+    cascade_list: Option<Vec<String>>,
+
+    /// Fonts loaded for fallback, kept so that their faces aren't reloaded
+    /// each time some text needs them.
+    fallback_fonts: Vec<Font>
+}
+
+singleton!(FontRegistry, faces: Vec::new(), scanned: false, cascade_list: None, fallback_fonts: Vec::new());
+
+impl FontRegistry {
+    /// An empty registry, which won't search any directories by itself.
+    pub fn new() -> FontRegistry {
+        FontRegistry {
+            faces: Vec::new(),
+            scanned: true,
+            cascade_list: None,
+            fallback_fonts: Vec::new()
+        }
+    }
+
+    /// The shared registry, having searched the system and bundle font
+    /// directories if it hasn't already.
+    pub fn shared<'a>() -> RefMut<'a, FontRegistry> {
+        let mut registry = FontRegistry::borrow_mut();
+
+        if !registry.scanned {
+            registry.scanned = true;
+
+            for path in PATHS {
+                registry.add_directory(Path::new(path));
+            }
+
+            registry.add_directory(Path::new(&Bundle::path_for_resource("")));
+        }
+
+        registry
+    }
+
+    /// Registers every font file directly inside `directory`. Missing or
+    /// unreadable directories are ignored.
+    pub fn add_directory(&mut self, directory: &Path) {
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return
+        };
+
+        let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+        paths.sort();
+
+        for path in paths {
+            if let Some(face) = FontFace::from_path(&path) {
+                self.add_face(face);
+            }
+        }
+    }
+
+    /// Registers a single face. A face with the same path as one already
+    /// registered replaces it.
+    pub fn add_face(&mut self, face: FontFace) {
+        self.faces.retain(|existing| existing.path != face.path);
+        self.faces.push(face);
+    }
+
+    /// The names of all the registered families, sorted alphabetically.
+    pub fn families(&self) -> Vec<String> {
+        let mut families: Vec<String> = Vec::new();
+
+        for face in &self.faces {
+            if !families.iter().any(|family| family_key(family) == family_key(&face.family)) {
+                families.push(face.family.clone());
+            }
+        }
+
+        families.sort();
+        families
+    }
+
+    /// All of the registered faces in the given family, lightest first.
+    pub fn faces_for_family(&self, family: &str) -> Vec<&FontFace> {
+        let key = family_key(family);
+        let mut faces: Vec<&FontFace> = self.faces
+            .iter()
+            .filter(|face| family_key(&face.family) == key)
+            .collect();
+
+        faces.sort_by_key(|face| (face.weight, face.italic));
+        faces
+    }
+
+    /// The families tried in order when a font is missing glyphs.
+    pub fn cascade_list(&self) -> Vec<String> {
+        match &self.cascade_list {
+            Some(cascade_list) => cascade_list.clone(),
+            None => DEFAULT_CASCADE_LIST.iter().map(|family| String::from(*family)).collect()
+        }
+    }
+
+    /// Sets the families tried in order when a font is missing glyphs, e.g.
+    /// to prefer a particular CJK or symbol font.
+    pub fn set_cascade_list(&mut self, cascade_list: Vec<String>) {
+        self.cascade_list = Some(cascade_list);
+    }
+
+    /// The installed fonts from the cascade list, in order, with the given
+    /// traits and size. Families that aren't installed are left out.
+    pub(crate) fn cascade_fonts(&mut self, weight: FontWeight, italic: bool, size: u16) -> Vec<Font> {
+        let mut fonts = Vec::new();
+
+        for family in self.cascade_list() {
+            let cached = self.fallback_fonts.iter().find(|font| {
+                family_key(font.family()) == family_key(&family) &&
+                    font.weight() == weight &&
+                    font.is_italic() == italic
+            });
+
+            let font = match cached {
+                Some(font) => font.with_size(size),
+                None => {
+                    let face = match self.face_matching(&family, weight, italic) {
+                        Some(face) => face.clone(),
+                        None => continue
+                    };
+
+                    let font = Font::from_face(&face, weight, italic, size);
+                    self.fallback_fonts.push(font.clone());
+                    font
+                }
+            };
+
+            fonts.push(font);
+        }
+
+        fonts
+    }
+
+    /// Finds the face in `family` that best matches the requested traits.
+    /// Faces with the right style are preferred, followed by the closest
+    /// weight (preferring the heavier face for bold requests and the
+    /// lighter one otherwise). Returns `None` if the family isn't known.
+    pub fn face_matching(&self, family: &str, weight: FontWeight, italic: bool) -> Option<&FontFace> {
+        self.faces_for_family(family)
+            .into_iter()
+            .min_by_key(|face| {
+                let leaning = if weight.is_bold() { face.weight < weight } else { face.weight > weight };
+                (face.italic != italic, face.weight.distance_to(weight), leaning)
+            })
+    }
+}
+
+impl Default for FontRegistry {
+    fn default() -> FontRegistry {
+        FontRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> FontRegistry {
+        let mut registry = FontRegistry::new();
+        registry.add_face(FontFace::from_name("Arial", "/fonts/Arial.ttf"));
+        registry.add_face(FontFace::from_name("Arial Bold", "/fonts/Arial Bold.ttf"));
+        registry.add_face(FontFace::from_name("Arial Bold Italic", "/fonts/Arial Bold Italic.ttf"));
+        registry.add_face(FontFace::from_name("HelveticaNeue-Light", "/fonts/HelveticaNeue-Light.otf"));
+        registry.add_face(FontFace::from_name("HelveticaNeue-BoldItalic", "/fonts/HelveticaNeue-BoldItalic.otf"));
+        registry
+    }
+
+    #[test]
+    fn test_parse_descriptor() {
+        let descriptor = FontDescriptor::parse("Helvetica Bold 17").unwrap();
+        assert_eq!(descriptor, FontDescriptor::new("Helvetica", FontWeight::Bold, false, 17));
+
+        let descriptor = FontDescriptor::parse("Helvetica Neue Light Italic").unwrap();
+        assert_eq!(descriptor, FontDescriptor::new("Helvetica Neue", FontWeight::Light, true, 16));
+
+        let descriptor = FontDescriptor::parse("Black 12").unwrap();
+        assert_eq!(descriptor, FontDescriptor::new("Black", FontWeight::Regular, false, 12));
+
+        assert_eq!(FontDescriptor::parse(" 17 "), None);
+    }
+
+    #[test]
+    fn test_descriptor_display_round_trips() {
+        let descriptor = FontDescriptor::new("Helvetica Neue", FontWeight::Semibold, true, 13);
+        assert_eq!(descriptor.to_string(), "Helvetica Neue Semibold Italic 13");
+        assert_eq!(FontDescriptor::parse(&descriptor.to_string()), Some(descriptor));
+    }
+
+    #[test]
+    fn test_face_from_name() {
+        let face = FontFace::from_name("Arial Bold Italic", "a");
+        assert_eq!((face.family.as_str(), face.weight, face.italic), ("Arial", FontWeight::Bold, true));
+
+        let face = FontFace::from_name("HelveticaNeue-BoldItalic", "a");
+        assert_eq!((face.family.as_str(), face.weight, face.italic), ("HelveticaNeue", FontWeight::Bold, true));
+
+        let face = FontFace::from_name("Noto-CJK", "a");
+        assert_eq!((face.family.as_str(), face.weight, face.italic), ("Noto-CJK", FontWeight::Regular, false));
+    }
+
+    #[test]
+    fn test_face_from_path_requires_font_extension() {
+        assert!(FontFace::from_path(Path::new("/fonts/Arial.ttf")).is_some());
+        assert!(FontFace::from_path(Path::new("/fonts/Arial.TTF")).is_some());
+        assert!(FontFace::from_path(Path::new("/fonts/README.txt")).is_none());
+        assert!(FontFace::from_path(Path::new("/fonts/Arial")).is_none());
+    }
+
+    #[test]
+    fn test_families() {
+        assert_eq!(registry().families(), vec!["Arial", "HelveticaNeue"]);
+        assert_eq!(registry().faces_for_family("helvetica neue").len(), 2);
+    }
+
+    #[test]
+    fn test_face_matching() {
+        let registry = registry();
+
+        let face = registry.face_matching("Arial", FontWeight::Bold, false).unwrap();
+        assert_eq!(face.path, "/fonts/Arial Bold.ttf");
+
+        let face = registry.face_matching("Arial", FontWeight::Regular, true).unwrap();
+        assert_eq!(face.path, "/fonts/Arial Bold Italic.ttf");
+
+        let face = registry.face_matching("Arial", FontWeight::Semibold, false).unwrap();
+        assert_eq!(face.path, "/fonts/Arial Bold.ttf");
+
+        let face = registry.face_matching("Arial", FontWeight::Light, false).unwrap();
+        assert_eq!(face.path, "/fonts/Arial.ttf");
+
+        let face = registry.face_matching("Helvetica Neue", FontWeight::Regular, false).unwrap();
+        assert_eq!(face.path, "/fonts/HelveticaNeue-Light.otf");
+
+        assert!(registry.face_matching("Courier", FontWeight::Regular, false).is_none());
+    }
+
+    #[test]
+    fn test_cascade_list() {
+        let mut registry = registry();
+        assert_eq!(registry.cascade_list()[0], "PingFang");
+
+        registry.set_cascade_list(vec![String::from("Helvetica Neue"), String::from("Courier")]);
+        assert_eq!(registry.cascade_list(), vec!["Helvetica Neue", "Courier"]);
+    }
+
+    #[test]
+    fn test_add_face_replaces_same_path() {
+        let mut registry = registry();
+        registry.add_face(FontFace::from_name("Arial Black", "/fonts/Arial.ttf"));
+        assert_eq!(registry.faces_for_family("Arial").len(), 3);
+        assert_eq!(registry.faces_for_family("Arial")[2].weight, FontWeight::Black);
+    }
+}
@@ -0,0 +1,238 @@
+//! Linear and radial gradients, for filling layers without shipping images.
+//!
+//! A gradient is a list of stops, each a color at a location from 0 to 1
+//! along the gradient, and a direction saying how locations are laid out
+//! over the rectangle filled. Colors between stops are interpolated in
+//! linear light, see `color_space`, so a gradient from red to green doesn't
+//! turn muddy brown in the middle.
+//!
+//! ```ignore
+//! let gradient = Gradient::new(
+//!     vec![
+//!         GradientStop::new(0.0, Color::RGBA(90, 160, 250, 255)),
+//!         GradientStop::new(1.0, Color::RGBA(20, 90, 200, 255))
+//!     ],
+//!     GradientDirection::vertical()
+//! );
+//! layer.fill_gradient(&rectangle, gradient.stops(), gradient.direction());
+//! ```
+
+use crate::graphics::{Color, LinearColor, Point, Size};
+
+/// How many colors along a gradient are worked out, before being picked
+/// from for each pixel.
+const RAMP_LENGTH: usize = 1024;
+
+/// A color at a location along a gradient, from 0 at the start to 1 at the
+/// end.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientStop {
+    pub location: f32,
+    pub color: Color
+}
+
+impl GradientStop {
+    pub fn new(location: f32, color: Color) -> GradientStop {
+        GradientStop { location, color }
+    }
+}
+
+/// How the locations of a gradient's stops are laid out over a rectangle.
+/// Points are fractions of the rectangle, from (0, 0) at the top left to
+/// (1, 1) at the bottom right, so a gradient stretches with what it fills.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GradientDirection {
+    /// Along the line from `start` to `end`, with the same color across
+    /// it. Before `start` is the first stop's color, past `end` the last's.
+    Linear { start: Point<f32>, end: Point<f32> },
+
+    /// Out from `center`, reaching the end at `radius`. As the rectangle is
+    /// filled in fractions of its size, the circle is stretched into an
+    /// ellipse in rectangles that aren't square.
+    Radial { center: Point<f32>, radius: f32 }
+}
+
+impl GradientDirection {
+    /// From the top edge down to the bottom edge.
+    pub fn vertical() -> GradientDirection {
+        GradientDirection::Linear {
+            start: Point::new(0.5, 0.0),
+            end: Point::new(0.5, 1.0)
+        }
+    }
+
+    /// From the left edge across to the right edge.
+    pub fn horizontal() -> GradientDirection {
+        GradientDirection::Linear {
+            start: Point::new(0.0, 0.5),
+            end: Point::new(1.0, 0.5)
+        }
+    }
+
+    /// From the center out to the middle of the edges.
+    pub fn radial() -> GradientDirection {
+        GradientDirection::Radial {
+            center: Point::new(0.5, 0.5),
+            radius: 0.5
+        }
+    }
+
+    /// The location along the gradient of the point `x`, `y`, in fractions
+    /// of the rectangle filled.
+    fn location(&self, x: f32, y: f32) -> f32 {
+        match self {
+            GradientDirection::Linear { start, end } => {
+                let dx = end.x - start.x;
+                let dy = end.y - start.y;
+                let length_squared = dx * dx + dy * dy;
+                if length_squared == 0.0 {
+                    return 0.0;
+                }
+                ((x - start.x) * dx + (y - start.y) * dy) / length_squared
+            },
+            GradientDirection::Radial { center, radius } => {
+                if *radius <= 0.0 {
+                    return 1.0;
+                }
+                (x - center.x).hypot(y - center.y) / radius
+            }
+        }
+    }
+}
+
+/// A set of stops laid out in a direction, such as for a view's background.
+/// See `View::set_background_gradient`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    stops: Vec<GradientStop>,
+    direction: GradientDirection
+}
+
+impl Gradient {
+    pub fn new(stops: Vec<GradientStop>, direction: GradientDirection) -> Gradient {
+        Gradient { stops, direction }
+    }
+
+    /// A gradient from `start` to `end`, evenly, in `direction`.
+    pub fn between(start: Color, end: Color, direction: GradientDirection) -> Gradient {
+        Gradient::new(vec![GradientStop::new(0.0, start), GradientStop::new(1.0, end)], direction)
+    }
+
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    pub fn direction(&self) -> &GradientDirection {
+        &self.direction
+    }
+}
+
+/// The color of a gradient with `stops` at `location`.
+fn color_at(stops: &[GradientStop], location: f32) -> LinearColor {
+    let first = &stops[0];
+    if location <= first.location {
+        return LinearColor::from_srgb(&first.color);
+    }
+
+    for pair in stops.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        if location <= to.location {
+            let span = to.location - from.location;
+            let progress = if span > 0.0 { (location - from.location) / span } else { 1.0 };
+            return LinearColor::from_srgb(&from.color).interpolate(&LinearColor::from_srgb(&to.color), progress);
+        }
+    }
+
+    LinearColor::from_srgb(&stops[stops.len() - 1].color)
+}
+
+/// The color of each pixel of a rectangle of `size` pixels filled with a
+/// gradient, in rows of RGBA from the top left, alpha not premultiplied.
+/// Empty if there are no stops.
+pub(crate) fn gradient_pixels(size: &Size<u32>, stops: &[GradientStop], direction: &GradientDirection) -> Vec<u8> {
+    if stops.is_empty() {
+        return Vec::new();
+    }
+
+    let mut stops = stops.to_vec();
+    stops.sort_by(|a, b| a.location.total_cmp(&b.location));
+
+    // Locations past the ends take the end colors, so the ramp only needs
+    // to cover from 0 to 1.
+    let ramp: Vec<Color> = (0..RAMP_LENGTH)
+        .map(|index| color_at(&stops, index as f32 / (RAMP_LENGTH - 1) as f32).to_srgb())
+        .collect();
+
+    let mut pixels = Vec::with_capacity((size.width * size.height * 4) as usize);
+    for y in 0..size.height {
+        let fraction_y = (y as f32 + 0.5) / size.height as f32;
+        for x in 0..size.width {
+            let fraction_x = (x as f32 + 0.5) / size.width as f32;
+            let location = direction.location(fraction_x, fraction_y).clamp(0.0, 1.0);
+            let color = ramp[(location * (RAMP_LENGTH - 1) as f32).round() as usize];
+            pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(pixels: &[u8], size: &Size<u32>, x: u32, y: u32) -> Color {
+        let index = ((y * size.width + x) * 4) as usize;
+        Color::RGBA(pixels[index], pixels[index + 1], pixels[index + 2], pixels[index + 3])
+    }
+
+    #[test]
+    fn test_linear_gradient_pixels() {
+        let size = Size::new(4, 101);
+        let gradient = Gradient::between(Color::RGBA(0, 0, 0, 255), Color::RGBA(255, 255, 255, 255), GradientDirection::vertical());
+        let pixels = gradient_pixels(&size, gradient.stops(), gradient.direction());
+        assert_eq!(pixels.len(), 4 * 101 * 4);
+
+        // The same across each row, and brightening down the rows.
+        assert_eq!(pixel(&pixels, &size, 0, 0), pixel(&pixels, &size, 3, 0));
+        for y in 1..101 {
+            assert!(pixel(&pixels, &size, 0, y).r > pixel(&pixels, &size, 0, y - 1).r);
+        }
+        assert!(pixel(&pixels, &size, 0, 100).r > 250);
+
+        // Halfway is half the light, brighter than half the encoded value.
+        assert_eq!(pixel(&pixels, &size, 2, 50), Color::RGBA(188, 188, 188, 255));
+    }
+
+    #[test]
+    fn test_stops_out_of_order_and_past_ends() {
+        let red = Color::RGBA(255, 0, 0, 255);
+        let blue = Color::RGBA(0, 0, 255, 255);
+        let stops = vec![GradientStop::new(0.75, blue), GradientStop::new(0.25, red)];
+        let size = Size::new(100, 1);
+        let pixels = gradient_pixels(&size, &stops, &GradientDirection::horizontal());
+
+        assert_eq!(pixel(&pixels, &size, 0, 0), red);
+        assert_eq!(pixel(&pixels, &size, 20, 0), red);
+        assert_eq!(pixel(&pixels, &size, 80, 0), blue);
+        assert_eq!(pixel(&pixels, &size, 99, 0), blue);
+
+        assert!(gradient_pixels(&size, &[], &GradientDirection::horizontal()).is_empty());
+    }
+
+    #[test]
+    fn test_radial_gradient_pixels() {
+        let white = Color::RGBA(255, 255, 255, 255);
+        let clear = Color::RGBA(0, 0, 0, 0);
+        let size = Size::new(20, 20);
+        let gradient = Gradient::between(white, clear, GradientDirection::radial());
+        let pixels = gradient_pixels(&size, gradient.stops(), gradient.direction());
+
+        assert!(pixel(&pixels, &size, 10, 10).a > 230);
+        assert_eq!(pixel(&pixels, &size, 0, 0).a, 0);
+
+        // Symmetric about the center.
+        assert_eq!(pixel(&pixels, &size, 4, 10), pixel(&pixels, &size, 15, 10));
+        assert_eq!(pixel(&pixels, &size, 10, 4), pixel(&pixels, &size, 10, 15));
+    }
+}
@@ -1,11 +1,14 @@
 use crate::graphics::Layer;
 use crate::graphics::Size;
 use crate::graphics::Context;
+use crate::graphics::ImageCache;
 use crate::platform::bundle::Bundle;
 use std::collections::HashMap;
 use std::rc::Rc;
-use sdl2::image::LoadSurface;
+use sdl2::image::{ImageRWops, SaveSurface};
+use sdl2::rwops::RWops;
 use sdl2::surface::Surface;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use regex::Regex;
 use std::fs::metadata;
 
@@ -19,11 +22,17 @@ pub struct Image<'a> {
     /// context, so `Image` will lazily create `Layer` objects once per context.
     /// This are lazily populated by `layer_for()`.
     ///
+    /// Images loaded from files use the `ImageCache` instead, so they're
+    /// shared with every other image of the same file.
     layers: HashMap<u32, Rc<Layer>>,
 
-    surface: Surface<'a>,
+    /// The path of the file the surface was decoded from, or empty for
+    /// images created from data or pixels.
+    source: String,
 
-    scale_loaded: u8
+    surface: Rc<Surface<'a>>,
+
+    scale_loaded: f32
 }
 
 impl<'a> Image<'a> {
@@ -35,11 +44,15 @@ impl<'a> Image<'a> {
     /// If an image with a @2x suffix (before the file extension) is found,
     /// it will be loaded and scaled appropriately if the display scale is
     /// greater than 1.
+    ///
+    /// Files are only decoded once, however many images of them are created,
+    /// see `ImageCache`.
     pub fn new(name: &str) -> Image<'a> {
         let image_path_2x = Image::scale_2x_name(name);
         let image_path = Bundle::path_for_resource(name);
 
         let surface;
+        let source;
         let width;
         let height;
         let scale_loaded;
@@ -50,29 +63,31 @@ impl<'a> Image<'a> {
         if Image::is_file(&image_path_2x) {
             // By default, we load the 2x image if there is one. There's just
             // going to be a higher chance that modern displays are scaled.
-            surface = Surface::from_file(&image_path_2x).unwrap();
+            surface = Image::surface_or_placeholder(&image_path_2x, 2.0);
+            source = image_path_2x;
 
             let widthf32 = surface.width() as f32 * 0.5;
             let heightf32 = surface.height() as f32 * 0.5;
 
             if widthf32.floor() != widthf32.ceil() {
-                panic!("An error occurred loading {}. A @2x image must be cleanly divisible by 2 but got {}", &image_path_2x, widthf32);
+                panic!("An error occurred loading {}. A @2x image must be cleanly divisible by 2 but got {}", &source, widthf32);
             }
 
             if heightf32.floor() != heightf32.ceil() {
-                panic!("An error occurred loading {}. A @2x image must be cleanly divisible by 2 but got {}", &image_path_2x, heightf32);
+                panic!("An error occurred loading {}. A @2x image must be cleanly divisible by 2 but got {}", &source, heightf32);
             }
 
             width = widthf32 as u32;
             height = heightf32 as u32;
 
-            scale_loaded = 2;
+            scale_loaded = 2.0;
         } else if Image::is_file(&image_path) {
             // We load the regular image if there is no 2x image.
-            surface = Surface::from_file(image_path).unwrap();
+            surface = Image::surface_or_placeholder(&image_path, 1.0);
+            source = image_path;
             width = surface.width();
             height = surface.height();
-            scale_loaded = 1;
+            scale_loaded = 1.0;
         } else {
             panic!("Image not found: {}. Searched the following paths: [\n  {},\n  {}\n]", name, image_path, image_path_2x);
         }
@@ -81,50 +96,187 @@ impl<'a> Image<'a> {
         let layers = HashMap::new();
         let name = name.to_string();
 
-        Image { name, size, layers, surface, scale_loaded }
+        Image { name, size, layers, source, surface, scale_loaded }
+    }
+
+    /// Decodes an image from the contents of a PNG or JPEG file, such as
+    /// one downloaded or embedded in the binary. The image is at a scale of
+    /// 1, and can't be swapped for a @2x version.
+    pub fn from_data(data: &[u8]) -> Result<Image<'static>, String> {
+        let surface = RWops::from_bytes(data)?.load()?;
+        let size = Size { width: surface.width(), height: surface.height() };
+
+        Ok(Image {
+            name: String::new(),
+            size,
+            layers: HashMap::new(),
+            source: String::new(),
+            surface: Rc::new(surface),
+            scale_loaded: 1.0
+        })
+    }
+
+    /// An image of `pixels`, RGBA bytes row by row from the top left, drawn
+    /// at `scale`. Its size is in points, so smaller than `pixel_size` when
+    /// the scale is greater than 1.
+    pub(crate) fn from_pixels(pixels: &[u8], pixel_size: &Size<u32>, scale: f32) -> Image<'static> {
+        let mut surface = Surface::new(pixel_size.width.max(1), pixel_size.height.max(1), PixelFormatEnum::RGBA32).unwrap();
+        let row_length = pixel_size.width as usize * 4;
+        let pitch = surface.pitch() as usize;
+
+        surface.with_lock_mut(|data| {
+            for (row, source) in pixels.chunks_exact(row_length).enumerate() {
+                data[row * pitch..row * pitch + row_length].copy_from_slice(source);
+            }
+        });
+
+        let size = Size {
+            width: (pixel_size.width as f32 / scale).round() as u32,
+            height: (pixel_size.height as f32 / scale).round() as u32
+        };
+
+        Image {
+            name: String::new(),
+            size,
+            layers: HashMap::new(),
+            source: String::new(),
+            surface: Rc::new(surface),
+            scale_loaded: scale
+        }
     }
 
     pub fn size(&self) -> &Size<u32> {
         &self.size
     }
 
+    /// The scale of the version loaded, 2 for a @2x image.
+    pub fn scale(&self) -> f32 {
+        self.scale_loaded
+    }
+
+    /// The size of the image in pixels, the size scaled by the scale of the
+    /// version loaded.
+    pub fn pixel_size(&self) -> Size<u32> {
+        Size { width: self.surface.width(), height: self.surface.height() }
+    }
+
+    /// The color of the pixel `x` pixels from the left, and `y` from the
+    /// top, or `None` outside the image.
+    pub fn pixel_at(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.surface.width() || y >= self.surface.height() {
+            return None;
+        }
+
+        let offset = (y as usize * self.surface.width() as usize + x as usize) * 4;
+        let pixel = &self.pixels()[offset..offset + 4];
+        Some(Color::RGBA(pixel[0], pixel[1], pixel[2], pixel[3]))
+    }
+
+    /// The image's pixels as RGBA bytes, row by row from the top left,
+    /// `pixel_size` wide.
+    pub fn pixels(&self) -> Vec<u8> {
+        let surface = self.surface.convert_format(PixelFormatEnum::RGBA32).unwrap();
+        let row_length = surface.width() as usize * 4;
+        let pitch = surface.pitch() as usize;
+
+        surface.without_lock().unwrap()
+            .chunks(pitch)
+            .take(surface.height() as usize)
+            .flat_map(|row| row[..row_length].to_vec())
+            .collect()
+    }
+
+    /// Writes the image to a PNG file at `path`, at the scale it was loaded
+    /// or drawn at.
+    pub fn save_png(&self, path: &str) -> Result<(), String> {
+        self.surface.save(path)
+    }
+
     /// Returns the layer to be drawn for the given context.
     ///
     /// Note: Because the scale of the context may change, in the case the
     /// window is moved from one screen to another, this may reload the image
     /// from disk if a more appropriate scale version is found. This may also
     /// happen once because the scale wasn't known at initialization.
+    ///
+    /// Images loaded from the same file share the layer, see `ImageCache`.
     pub fn layer_for(&mut self, context: &Context) -> Rc<Layer> {
         let id = context.id();
         let render_scale = context.render_scale();
 
-        if render_scale == 1.0 && self.scale_loaded != 1 {
+        // Images decoded from data have no file to reload, or share.
+        if !self.name.is_empty() {
+            self.reload_for_scale(render_scale);
+
+            let surface = &self.surface;
+            let size = &self.size;
+            let scale = self.scale_loaded;
+            return ImageCache::layer(&self.source, scale, context, || {
+                let texture = context.create_texture_from_surface(surface);
+                Layer::new_prerendered(context.clone(), size.clone(), texture, scale)
+            });
+        }
+
+        if self.layers.get(&id).is_none() {
+            let texture = context.create_texture_from_surface(&self.surface);
+            let layer = Layer::new_prerendered(context.clone(), self.size.clone(), texture, self.scale_loaded);
+            let layers = &mut self.layers;
+            layers.insert(id, Rc::new(layer));
+        }
+
+        self.layers.get(&id).unwrap().clone()
+    }
+
+    /// Reloads the image from disk if there's a version better suited to
+    /// `render_scale` than the one loaded.
+    fn reload_for_scale(&mut self, render_scale: f32) {
+        if render_scale == 1.0 && self.scale_loaded != 1.0 {
             let image_path = Bundle::path_for_resource(&self.name);
 
-            if Image::is_file(&image_path) {
-                self.scale_loaded = 1;
-                self.surface = Surface::from_file(image_path).unwrap();
+            if let Some(surface) = Image::reloaded_surface(&image_path, 1.0) {
+                self.scale_loaded = 1.0;
+                self.surface = surface;
+                self.source = image_path;
             }
-        } else if render_scale == 2.0 && self.scale_loaded != 2 {
+        } else if render_scale == 2.0 && self.scale_loaded != 2.0 {
             let image_path_2x = Image::scale_2x_name(&self.name);
 
-            if Image::is_file(&image_path_2x) {
-                self.scale_loaded = 2;
-                self.surface = Surface::from_file(image_path_2x).unwrap();
+            if let Some(surface) = Image::reloaded_surface(&image_path_2x, 2.0) {
+                self.scale_loaded = 2.0;
+                self.surface = surface;
+                self.source = image_path_2x;
             }
         }
+    }
 
-        if self.layers.get(&id).is_none() {
-            let texture = self.surface.as_texture(context.texture_creator()).unwrap();
-            let layer = Layer::new_prerendered(context.clone(), self.size.clone(), texture, self.scale_loaded as f32);
-            let layers = &mut self.layers;
-            layers.insert(id, Rc::new(layer));
+    /// The surface decoded from the file at `path`, or if it can't be
+    /// decoded, a clear placeholder a point wide at `scale`, so a broken
+    /// file shows nothing rather than bringing down the application.
+    fn surface_or_placeholder(path: &str, scale: f32) -> Rc<Surface<'static>> {
+        ImageCache::surface(path, scale).unwrap_or_else(|error| {
+            println!("Warning: couldn't decode the image {}. {}", path, error);
+            let side = scale.ceil() as u32;
+            Rc::new(Surface::new(side, side, PixelFormatEnum::RGBA32).unwrap())
+        })
+    }
+
+    /// The surface of the file at `path`, to use at `scale` in place of the
+    /// one loaded, if there's a file there that can be decoded.
+    fn reloaded_surface(path: &str, scale: f32) -> Option<Rc<Surface<'static>>> {
+        if !Image::is_file(path) {
+            return None;
         }
 
-        self.layers.get(&id).unwrap().clone()
+        match ImageCache::surface(path, scale) {
+            Ok(surface) => Some(surface),
+            Err(error) => {
+                println!("Warning: couldn't decode the image {}. {}", path, error);
+                None
+            }
+        }
     }
 
-    fn scale_2x_name(name: &str) -> String {
+    pub(crate) fn scale_2x_name(name: &str) -> String {
         let name2x = name.clone();
         let name2x = Regex::new(r"\.png$").unwrap().replace_all(&name2x, "@2x.png");
         let name2x = Regex::new(r"\.jpg$").unwrap().replace_all(&name2x, "@2x.jpg");
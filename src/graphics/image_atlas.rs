@@ -0,0 +1,191 @@
+//! Packs many small images, such as icons, into one, so drawing them all
+//! uses a single texture instead of one each.
+//!
+//! Each image keeps its name, and is drawn with a layer showing just its
+//! part of the atlas, see `ImageAtlas::layer_for`.
+//!
+//! ```ignore
+//! let mut atlas = ImageAtlas::from_files(&["icons/play.png", "icons/pause.png", "icons/stop.png"]);
+//! let layer = atlas.layer_for("icons/pause.png", view_layer.context()).unwrap();
+//! view_layer.draw_child_layer(&layer, &Rectangle::new(8, 8, 16, 16));
+//! ```
+
+use crate::graphics::{Context, Image, Layer, Point, Rectangle, Size};
+use std::collections::HashMap;
+
+/// How many transparent pixels are left between images, per point, so
+/// filtering while drawing scaled doesn't pick up pixels of a neighbour.
+const PADDING: u32 = 1;
+
+/// Many images packed into one, each to be drawn by name.
+pub struct ImageAtlas {
+    image: Image<'static>,
+
+    /// Where each image is in the atlas, in points.
+    frames: HashMap<String, Rectangle<i32, u32>>
+}
+
+impl ImageAtlas {
+    /// Packs `images`, each given with the name to draw it by.
+    ///
+    /// The images must all be loaded at the same scale, as the atlas has
+    /// just the one. Panics if they aren't.
+    pub fn new(images: &[(&str, &Image)]) -> ImageAtlas {
+        let scale = images.first().map(|(_, image)| image.scale()).unwrap_or(1.0);
+
+        if let Some((name, image)) = images.iter().find(|(_, image)| image.scale() != scale) {
+            panic!("An error occurred packing {} into an image atlas. All images must be at the same scale, expected {} but got {}", name, scale, image.scale());
+        }
+
+        // Images are placed on whole points, so each part of the atlas is in
+        // whole pixels at its scale.
+        let alignment = scale.ceil().max(1.0) as u32;
+        let sizes: Vec<Size<u32>> = images.iter().map(|(_, image)| image.pixel_size()).collect();
+        let (positions, atlas_size) = pack(&sizes, alignment);
+
+        let mut pixels = vec![0; (atlas_size.width * atlas_size.height * 4) as usize];
+        let atlas_row_length = atlas_size.width as usize * 4;
+        for ((_, image), position) in images.iter().zip(positions.iter()) {
+            let row_length = image.pixel_size().width as usize * 4;
+            if row_length == 0 {
+                continue;
+            }
+
+            for (row, source) in image.pixels().chunks_exact(row_length).enumerate() {
+                let start = (position.y as usize + row) * atlas_row_length + position.x as usize * 4;
+                pixels[start..start + row_length].copy_from_slice(source);
+            }
+        }
+
+        let frames = images
+            .iter()
+            .zip(positions.iter())
+            .map(|((name, image), position)| {
+                let frame = Rectangle::new(
+                    (position.x / alignment) as i32,
+                    (position.y / alignment) as i32,
+                    image.size().width,
+                    image.size().height
+                );
+                (name.to_string(), frame)
+            })
+            .collect();
+
+        ImageAtlas {
+            image: Image::from_pixels(&pixels, &atlas_size, scale),
+            frames
+        }
+    }
+
+    /// Loads and packs the images at `names`, as `Image::new` would, each
+    /// drawn by its name.
+    pub fn from_files(names: &[&str]) -> ImageAtlas {
+        let images: Vec<Image> = names.iter().map(|name| Image::new(name)).collect();
+        let named_images: Vec<(&str, &Image)> = names.iter().copied().zip(images.iter()).collect();
+        ImageAtlas::new(&named_images)
+    }
+
+    /// The image everything is packed into.
+    pub fn image(&self) -> &Image<'static> {
+        &self.image
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.frames.contains_key(name)
+    }
+
+    /// Where the image `name` is in the atlas, in points.
+    pub fn frame(&self, name: &str) -> Option<&Rectangle<i32, u32>> {
+        self.frames.get(name)
+    }
+
+    /// A layer drawing just the image `name`, sharing the atlas' texture in
+    /// `context` with every other one, or `None` if there's no such image.
+    pub fn layer_for(&mut self, name: &str, context: &Context) -> Option<Layer> {
+        let frame = self.frames.get(name)?.clone();
+        Some(self.image.layer_for(context).new_partial(frame))
+    }
+}
+
+/// Places rectangles of `sizes` in rows, tallest first, returning where
+/// each goes and the size of them all together. Positions are multiples of
+/// `alignment`, with as much space left between rectangles.
+fn pack(sizes: &[Size<u32>], alignment: u32) -> (Vec<Point<u32>>, Size<u32>) {
+    let aligned = |length: u32| length.div_ceil(alignment) * alignment;
+    let padding = PADDING * alignment;
+
+    // Roughly square, but at least as wide as the widest rectangle.
+    let area: u32 = sizes.iter().map(|size| (aligned(size.width) + padding) * (aligned(size.height) + padding)).sum();
+    let widest = sizes.iter().map(|size| aligned(size.width)).max().unwrap_or(0);
+    let max_width = aligned((area as f32).sqrt().ceil() as u32).max(widest);
+
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|a, b| sizes[*b].height.cmp(&sizes[*a].height));
+
+    let mut positions = vec![Point::new(0, 0); sizes.len()];
+    let mut atlas_size = Size::new(0, 0);
+    let mut x = 0;
+    let mut y = 0;
+    let mut row_height = 0;
+
+    for index in order {
+        let size = &sizes[index];
+
+        if x > 0 && x + aligned(size.width) > max_width {
+            x = 0;
+            y += row_height + padding;
+            row_height = 0;
+        }
+
+        positions[index] = Point::new(x, y);
+        atlas_size.width = atlas_size.width.max(x + size.width);
+        atlas_size.height = atlas_size.height.max(y + size.height);
+
+        x += aligned(size.width) + padding;
+        row_height = row_height.max(aligned(size.height));
+    }
+
+    (positions, atlas_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlap(a: &Point<u32>, a_size: &Size<u32>, b: &Point<u32>, b_size: &Size<u32>) -> bool {
+        a.x < b.x + b_size.width && b.x < a.x + a_size.width && a.y < b.y + b_size.height && b.y < a.y + a_size.height
+    }
+
+    #[test]
+    fn test_pack_without_overlapping() {
+        let sizes: Vec<Size<u32>> = (1..20).map(|index| Size::new(index * 3 % 17 + 1, index * 5 % 13 + 1)).collect();
+        let (positions, atlas_size) = pack(&sizes, 1);
+
+        for (index, (position, size)) in positions.iter().zip(sizes.iter()).enumerate() {
+            assert!(position.x + size.width <= atlas_size.width);
+            assert!(position.y + size.height <= atlas_size.height);
+
+            for (other_position, other_size) in positions.iter().zip(sizes.iter()).skip(index + 1) {
+                assert!(!overlap(position, size, other_position, other_size));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_aligns_positions() {
+        let sizes = vec![Size::new(5, 5), Size::new(3, 7), Size::new(4, 2), Size::new(9, 1)];
+        let (positions, _) = pack(&sizes, 2);
+
+        for position in &positions {
+            assert_eq!(position.x % 2, 0);
+            assert_eq!(position.y % 2, 0);
+        }
+    }
+
+    #[test]
+    fn test_pack_nothing() {
+        let (positions, atlas_size) = pack(&[], 1);
+        assert!(positions.is_empty());
+        assert_eq!(atlas_size, Size::new(0, 0));
+    }
+}
@@ -0,0 +1,173 @@
+//! Keeps decoded images and their textures, so the same image file used in
+//! many places, or drawn every frame, is only decoded and uploaded once.
+//!
+//! Images created with `Image::new` go through the cache by themselves: the
+//! surface decoded from a file is shared by every image of it, and the
+//! layer drawn for it is shared by every image of it drawn in the same
+//! context. Entries are kept until they're removed, so an application
+//! swapping lots of images in and out can remove the ones it's done with.
+//! The layers of a context are removed by themselves once the cache is all
+//! that's left using the context, such as after its window closes.
+//!
+//! ```ignore
+//! // Both views share one decoded surface and one texture.
+//! let first = ImageView::new(frame.clone(), Image::new("icons/star.png"));
+//! let second = ImageView::new(frame, Image::new("icons/star.png"));
+//!
+//! // Done with the icons.
+//! ImageCache::remove("icons/star.png");
+//! ```
+
+use crate::graphics::{Context, Image, Layer};
+use crate::platform::bundle::Bundle;
+use sdl2::image::LoadSurface;
+use sdl2::surface::Surface;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+thread_local! {
+    static IMAGE_CACHE: RefCell<ImageCache> = RefCell::new(ImageCache {
+        surfaces: HashMap::new(),
+        layers: HashMap::new()
+    });
+}
+
+/// What a cached image was loaded from: the path of its file, and the scale
+/// it's drawn at.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct SourceKey {
+    path: String,
+
+    /// The bits of the scale, as floats can't be hashed.
+    scale: u32
+}
+
+impl SourceKey {
+    fn new(path: &str, scale: f32) -> SourceKey {
+        SourceKey { path: path.to_string(), scale: scale.to_bits() }
+    }
+}
+
+/// The decoded images and uploaded textures shared between images loaded
+/// from the same files. Each thread has its own cache.
+pub struct ImageCache {
+    surfaces: HashMap<SourceKey, Rc<Surface<'static>>>,
+
+    /// Textures are unique per context, so layers are kept per context id.
+    layers: HashMap<(u32, SourceKey), Rc<Layer>>
+}
+
+impl ImageCache {
+    /// The surface decoded from the file at `path`, drawn at `scale`,
+    /// decoding it the first time it's asked for.
+    pub(crate) fn surface(path: &str, scale: f32) -> Result<Rc<Surface<'static>>, String> {
+        let key = SourceKey::new(path, scale);
+
+        if let Some(surface) = IMAGE_CACHE.with(|cache| cache.borrow().surfaces.get(&key).cloned()) {
+            return Ok(surface);
+        }
+
+        let surface = Rc::new(Surface::from_file(path)?);
+        IMAGE_CACHE.with(|cache| cache.borrow_mut().surfaces.insert(key, surface.clone()));
+        Ok(surface)
+    }
+
+    /// The layer for the file at `path`, drawn at `scale`, in `context`,
+    /// creating it with `create_layer` the first time it's asked for.
+    pub(crate) fn layer(path: &str, scale: f32, context: &Context, create_layer: impl FnOnce() -> Layer) -> Rc<Layer> {
+        let key = (context.id(), SourceKey::new(path, scale));
+
+        if let Some(layer) = IMAGE_CACHE.with(|cache| cache.borrow().layers.get(&key).cloned()) {
+            return layer;
+        }
+
+        // Creating the layer is kept out of the borrow, in case it loads
+        // anything through the cache itself.
+        let layer = Rc::new(create_layer());
+        let unused = IMAGE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            cache.layers.insert(key, layer.clone());
+            cache.take_unused_layers()
+        });
+
+        // The contexts of the unused layers are freed here, out of the
+        // borrow.
+        drop(unused);
+        layer
+    }
+
+    /// Forgets the textures drawn into the context with `id`, such as when
+    /// its window closes, so the context can be freed.
+    pub(crate) fn remove_context(id: u32) {
+        let removed: Vec<Rc<Layer>> = IMAGE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let keys: Vec<(u32, SourceKey)> = cache.layers.keys().filter(|(context_id, _)| *context_id == id).cloned().collect();
+            keys.iter().filter_map(|key| cache.layers.remove(key)).collect()
+        });
+        drop(removed);
+    }
+
+    /// Takes out the layers of contexts only the cache's layers still use,
+    /// as nothing can draw into those contexts any more.
+    fn take_unused_layers(&mut self) -> Vec<Rc<Layer>> {
+        let mut cached_per_context: HashMap<u32, usize> = HashMap::new();
+        for (context_id, _) in self.layers.keys() {
+            *cached_per_context.entry(*context_id).or_default() += 1;
+        }
+
+        let unused_keys: Vec<(u32, SourceKey)> = self.layers
+            .iter()
+            .filter(|(key, layer)| layer.context().reference_count() <= cached_per_context[&key.0])
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        unused_keys.iter().filter_map(|key| self.layers.remove(key)).collect()
+    }
+
+    /// Forgets the decoded surfaces and textures of the image `name`, at
+    /// every scale, as given to `Image::new`. Images already created keep
+    /// theirs, but new ones will decode the file again.
+    pub fn remove(name: &str) {
+        let path = Bundle::path_for_resource(name);
+        let path_2x = Image::scale_2x_name(name);
+
+        IMAGE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            cache.surfaces.retain(|key, _| key.path != path && key.path != path_2x);
+            cache.layers.retain(|(_, key), _| key.path != path && key.path != path_2x);
+        });
+    }
+
+    /// Forgets every decoded surface and texture, such as when memory runs
+    /// low.
+    pub fn clear() {
+        IMAGE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            cache.surfaces.clear();
+            cache.layers.clear();
+        });
+    }
+
+    /// How many decoded surfaces are kept.
+    pub fn surface_count() -> usize {
+        IMAGE_CACHE.with(|cache| cache.borrow().surfaces.len())
+    }
+
+    /// How many textures are kept, over every context.
+    pub fn layer_count() -> usize {
+        IMAGE_CACHE.with(|cache| cache.borrow().layers.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_keys() {
+        assert_eq!(SourceKey::new("/images/star.png", 2.0), SourceKey::new("/images/star.png", 2.0));
+        assert_ne!(SourceKey::new("/images/star.png", 1.0), SourceKey::new("/images/star.png", 2.0));
+        assert_ne!(SourceKey::new("/images/star.png", 1.0), SourceKey::new("/images/moon.png", 1.0));
+    }
+}
@@ -3,15 +3,76 @@ use crate::graphics::Point;
 use crate::graphics::Size;
 use crate::graphics::Color;
 use crate::graphics::Rectangle;
+use crate::graphics::Transform;
+use crate::graphics::rounded_rectangle;
+use crate::graphics::shadow;
+use crate::graphics::gradient::{self, GradientDirection, GradientStop};
+use crate::graphics::path::{Path, Polyline};
+use crate::graphics::rasterizer::{self, FillRule};
+use crate::graphics::stroke::{self, StrokeStyle};
+use crate::graphics::color_space::Premultiply;
 
 use sdl2::render::Texture;
 use sdl2::render::TextureAccess;
 use sdl2::render::BlendMode;
+use sdl2::pixels::PixelFormatEnum;
 
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::cell::Cell;
 
+/// How far, in pixels, the straight lines paths are drawn with can be
+/// from their curves.
+const PATH_TOLERANCE: f32 = 0.25;
+
+/// The square around the circle of `radius` around `center`.
+fn circle_rectangle(center: Point<f32>, radius: f32) -> Rectangle<f32, f32> {
+    Rectangle::new(center.x - radius, center.y - radius, radius * 2.0, radius * 2.0)
+}
+
+/// A clip rectangle, in pixels, that nothing is drawn inside, for when
+/// clipping leaves nothing to draw. Clip rectangles can't be empty.
+const NOTHING_CLIP_RECTANGLE: Rectangle<i32, u32> = Rectangle {
+    origin: Point { x: -1, y: -1 },
+    size: Size { width: 1, height: 1 }
+};
+
+/// `pixels`, drawn while clipped to a path, cut to the path's `mask`. Drawing
+/// into a transparent texture leaves colors premultiplied by their alpha,
+/// so they're divided out again, to blend back into the layer.
+fn masked_pixels(pixels: &[u8], mask: &[u8]) -> Vec<u8> {
+    pixels
+        .chunks(4)
+        .zip(mask)
+        .flat_map(|(pixel, coverage)| {
+            let color = Color::RGBA(pixel[0], pixel[1], pixel[2], pixel[3]).unpremultiplied();
+            let alpha = (color.a as u32 * *coverage as u32 / 255) as u8;
+            vec![color.r, color.g, color.b, alpha]
+        })
+        .collect()
+}
+
+/// What `Layer::save` keeps for `Layer::restore` to go back to.
+struct GraphicsState {
+    /// The context's clip rectangle, in pixels.
+    clip_rectangle: Option<Rectangle<i32, u32>>,
+
+    /// Set once clipped to a path since being saved.
+    path_clip: Option<PathClip>
+}
+
+/// While clipped to a path, drawing goes into a texture of its own, which
+/// is drawn into the layer's texture through the path's coverage when the
+/// clipping is restored.
+struct PathClip {
+    /// The layer's own texture, swapped out while drawing is clipped.
+    texture: Texture,
+
+    /// How much of each pixel of the layer is inside the path, from 0 to
+    /// 255, in rows from the top left.
+    mask: Vec<u8>
+}
+
 /// To use an analogy, this is a piece of paper that will be drawn on. It'll
 /// then either be glued onto another layer, or onto the `Context` canvas.
 /// These layers then make the full picture.
@@ -34,6 +95,21 @@ pub struct Layer {
 
     needs_display: Cell<bool>,
 
+    /// The part of the layer to draw again when the whole layer doesn't
+    /// need display, such as where a child layer changed. In points,
+    /// relative to the top left of the layer.
+    dirty_rectangle: RefCell<Option<Rectangle<i32, u32>>>,
+
+    /// What the delegate drew, before child layers were drawn over it. Kept
+    /// so drawing part of the layer again, such as where a child layer
+    /// changed, can copy it back rather than asking the delegate to draw
+    /// again. See `cache_content`.
+    content: RefCell<Option<Texture>>,
+
+    /// The clipping saved by `save`, for `restore` to go back to, most
+    /// recent last.
+    graphics_states: RefCell<Vec<GraphicsState>>,
+
     /// This layer's scale.
     ///
     /// If the layer scale is 1.0 and the screen display is 2.0, when the layer
@@ -43,6 +119,11 @@ pub struct Layer {
     /// is drawn, it wont be scaled up at all.
     scale: f32,
 
+    /// The context's render scale when the texture was made. The texture is
+    /// sized for it, so the layer has to be made again if the window moves
+    /// to a screen with a different scale.
+    render_scale: f32,
+
     delegate: Box<dyn LayerDelegate>
 }
 
@@ -71,23 +152,27 @@ impl Layer {
             println!("Warning: Layer height is not an integer. This may cause rendering issues.");
         }
 
-        let mut texture = context.texture_creator()
-            .create_texture(
-                None,
-                TextureAccess::Target,
-                width.round() as u32,
-                height.round() as u32
-            ).unwrap();
+        let mut texture = context.create_texture(
+            None,
+            TextureAccess::Target,
+            width.round() as u32,
+            height.round() as u32
+        );
 
         texture.set_blend_mode(BlendMode::Blend);
 
+        let render_scale = context.render_scale();
         Layer {
             context: context,
             size: size,
             needs_display: Cell::new(true),
+            dirty_rectangle: RefCell::new(None),
+            content: RefCell::new(None),
+            graphics_states: RefCell::new(Vec::new()),
             texture: Rc::new(RefCell::new(texture)),
             delegate: delegate,
             scale: 1.0,
+            render_scale,
             source_rectangle: None
         }
     }
@@ -96,17 +181,53 @@ impl Layer {
     /// passed in at construction, and there is no delegate to handle any draw
     /// instructions. Making `draw()` no-op.
     pub fn new_prerendered(context: Context, size: Size<u32>, texture: Texture, scale: f32) -> Self {
+        let render_scale = context.render_scale();
         Layer {
             context: context,
             size: size,
             needs_display: Cell::new(false),
+            dirty_rectangle: RefCell::new(None),
+            content: RefCell::new(None),
+            graphics_states: RefCell::new(Vec::new()),
             texture: Rc::new(RefCell::new(texture)),
             delegate: Box::new(EmptyLayerDelegate {}),
             scale: scale,
+            render_scale,
             source_rectangle: None
         }
     }
 
+    /// Creates a layer with the blurred shadow of a rounded rectangle of
+    /// `size`. The layer is `blur_radius` larger than `size` on each side,
+    /// for the blur to fade out.
+    pub(crate) fn new_shadow(context: Context, size: Size<u32>, corner_radius: f32, blur_radius: u32, color: Color) -> Self {
+        let scale = context.render_scale();
+        let pixel_size = Size::new(
+            (size.width as f32 * scale).round() as u32,
+            (size.height as f32 * scale).round() as u32
+        );
+        let pixel_blur_radius = (blur_radius as f32 * scale).round() as u32;
+        let mask = shadow::shadow_mask(&pixel_size, corner_radius * scale, pixel_blur_radius);
+
+        let pixels: Vec<u8> = mask
+            .iter()
+            .flat_map(|value| {
+                let alpha = (*value as u32 * color.a as u32 / 255) as u8;
+                vec![color.r, color.g, color.b, alpha]
+            })
+            .collect();
+
+        let width = pixel_size.width + pixel_blur_radius * 2;
+        let height = pixel_size.height + pixel_blur_radius * 2;
+
+        let mut texture = context.create_texture(Some(PixelFormatEnum::RGBA32), TextureAccess::Static, width, height);
+        texture.update(None, &pixels, width as usize * 4).unwrap();
+        texture.set_blend_mode(BlendMode::Blend);
+
+        let size = Size::new(size.width + blur_radius * 2, size.height + blur_radius * 2);
+        Layer::new_prerendered(context, size, texture, scale)
+    }
+
     /// Creates a layer that cannot draw anything on its own. It's useful for
     /// creating a layer that can be used as a container for other layers.
     pub fn new_no_render(context: Context, size: Size<u32>) -> Self {
@@ -125,22 +246,90 @@ impl Layer {
             context: self.context.clone(),
             size: portion.size.clone(),
             needs_display: Cell::new(false),
+            dirty_rectangle: RefCell::new(None),
+            content: RefCell::new(None),
+            graphics_states: RefCell::new(Vec::new()),
             texture: self.texture.clone(),
             delegate: Box::new(EmptyLayerDelegate {}),
             scale: self.scale,
+            render_scale: self.render_scale,
             source_rectangle: Some(scaled_portion)
         }
     }
 
     // Requests for the delegate to draw on this layer.
+    // Any clipping the delegate leaves in place is restored after.
     pub fn draw(&self) {
+        let depth = self.graphics_states.borrow().len();
+        self.save();
         self.delegate.layer_will_draw(self);
         self.delegate.draw_layer(self);
+        while self.graphics_states.borrow().len() > depth {
+            self.restore();
+        }
+        self.needs_display.set(false);
+        self.dirty_rectangle.replace(None);
+    }
+
+    /// Keeps a copy of what's been drawn so far, to restore with
+    /// `restore_content` instead of drawing again. Called after the delegate
+    /// draws, before child layers are drawn over it.
+    pub(crate) fn cache_content(&self) {
+        let pixel_size = self.pixel_size();
+        let mut content = self.content.borrow_mut();
+
+        if content.as_ref().is_none_or(|content| {
+            let query = content.query();
+            query.width != pixel_size.width || query.height != pixel_size.height
+        }) {
+            let mut texture = self.context.create_texture(None, TextureAccess::Target, pixel_size.width, pixel_size.height);
+            texture.set_blend_mode(BlendMode::None);
+            content.replace(texture);
+        }
+
+        // The copy replaces what's in the cache, rather than blending over
+        // it.
+        let mut texture = self.texture.borrow_mut();
+        texture.set_blend_mode(BlendMode::None);
+        let destination = Rectangle { origin: Point { x: 0, y: 0 }, size: pixel_size };
+        self.context.draw_texture_in_texture(content.as_mut().unwrap(), &texture, None, &destination);
+        texture.set_blend_mode(BlendMode::Blend);
+    }
+
+    /// Copies back what `cache_content` kept, within the context's clip
+    /// rectangle, in place of the delegate drawing again, and marks the
+    /// layer drawn as `draw` does. Returns `false` if nothing was cached.
+    pub(crate) fn restore_content(&self) -> bool {
+        let content = self.content.borrow();
+        let content = match content.as_ref() {
+            Some(content) => content,
+            None => return false
+        };
+
+        let mut texture = self.texture.borrow_mut();
+        let destination = Rectangle { origin: Point { x: 0, y: 0 }, size: self.pixel_size() };
+        self.context.draw_texture_in_texture(&mut texture, content, None, &destination);
+
         self.needs_display.set(false);
+        self.dirty_rectangle.replace(None);
+        true
+    }
+
+    /// What's been drawn into the layer, as RGBA bytes row by row from the
+    /// top left, `pixel_size` wide.
+    pub(crate) fn read_pixels(&self) -> Vec<u8> {
+        self.context.read_texture_pixels(&mut self.texture.borrow_mut())
+    }
+
+    /// Drops the copy kept by `cache_content`, such as when the layer no
+    /// longer has child layers drawn over its content.
+    pub(crate) fn clear_content_cache(&self) {
+        self.content.replace(None);
     }
 
     pub(crate) fn skip_draw(&self) {
         self.needs_display.set(false);
+        self.dirty_rectangle.replace(None);
     }
 
     pub(crate) fn get_needs_display(&self) -> bool {
@@ -151,6 +340,34 @@ impl Layer {
         self.needs_display.set(true)
     }
 
+    /// Marks `rectangle` of the layer, in points, to be drawn again along
+    /// with any other part already marked.
+    pub(crate) fn set_needs_display_in_rect(&self, rectangle: &Rectangle<i32, u32>) {
+        let bounds = Rectangle::new(0, 0, self.size.width, self.size.height);
+        let rectangle = match rectangle.intersection(&bounds) {
+            Some(rectangle) => rectangle,
+            None => return
+        };
+
+        let mut dirty_rectangle = self.dirty_rectangle.borrow_mut();
+        let union = match dirty_rectangle.as_ref() {
+            Some(dirty_rectangle) => dirty_rectangle.union(&rectangle),
+            None => rectangle
+        };
+        dirty_rectangle.replace(union);
+    }
+
+    /// The part of the layer to draw again, in points: all of it if it
+    /// needs display, or the union of the rectangles marked since it was
+    /// last drawn.
+    pub(crate) fn dirty_rectangle(&self) -> Option<Rectangle<i32, u32>> {
+        if self.needs_display.get() {
+            Some(Rectangle::new(0, 0, self.size.width, self.size.height))
+        } else {
+            self.dirty_rectangle.borrow().clone()
+        }
+    }
+
     /// Set the color factor of the texture for the next render.
     pub fn set_color_factor(&self, color: Color, blend_factor: f32) {
         let mut texture = self.texture.borrow_mut();
@@ -175,6 +392,13 @@ impl Layer {
         texture.set_color_mod(red, green, blue);
     }
 
+    /// Set the opacity of the texture for the next render, from 0
+    /// (transparent) to 1 (opaque).
+    pub fn set_opacity(&self, opacity: f32) {
+        let alpha = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+        self.texture.borrow_mut().set_alpha_mod(alpha);
+    }
+
     /// Note: The destination at this point is using the (unscaled) point
     /// system, not the real pixel size. The size of the real texture itself is
     /// determined by this method; the difference in quality being the source
@@ -196,6 +420,40 @@ impl Layer {
         context.draw_texture_in_texture(&mut parent_texture, &child_texture, source, &destination);
     }
 
+    /// Like `draw_child_layer`, but applying `transform` about the center of
+    /// `destination`. Skewing isn't supported, and is ignored.
+    pub fn draw_child_layer_transformed(&self, child_layer: &Layer, destination: &Rectangle<i32, u32>, transform: &Transform) {
+        let (sx, sy, angle) = transform.decompose();
+
+        let center_x = destination.origin.x as f32 + destination.size.width as f32 / 2.0 + transform.tx;
+        let center_y = destination.origin.y as f32 + destination.size.height as f32 / 2.0 + transform.ty;
+        let width = destination.size.width as f32 * sx.abs();
+        let height = destination.size.height as f32 * sy.abs();
+
+        let transformed = Rectangle::new(
+            (center_x - width / 2.0).round() as i32,
+            (center_y - height / 2.0).round() as i32,
+            width.round() as u32,
+            height.round() as u32
+        );
+
+        let mut parent_texture = self.texture.borrow_mut();
+        let child_texture = child_layer.texture.borrow();
+        let context = &self.context;
+        let source = child_layer.source_rectangle.as_ref();
+        let destination = &transformed * context.render_scale();
+
+        context.draw_texture_in_texture_rotated(
+            &mut parent_texture,
+            &child_texture,
+            source,
+            &destination,
+            angle.to_degrees() as f64,
+            sx < 0.0,
+            sy < 0.0
+        );
+    }
+
     /// To be used when the layer is already declared at the native resolution.
     /// Used by rendering text (e.g. at twice the font size than specified)
     /// because each character is drawn separately to a layer first.
@@ -207,6 +465,278 @@ impl Layer {
         context.draw_texture_in_texture(&mut parent_texture, &child_texture, None, &destination);
     }
 
+    /// Fills a rectangle of this layer with a solid color. Like
+    /// `draw_child_layer_without_scaling`, the rectangle is expected to
+    /// already be in the layer's native resolution.
+    pub fn fill_rectangle_without_scaling(&self, rectangle: &Rectangle<i32, u32>, color: Color) {
+        let mut texture = self.texture.borrow_mut();
+        let context = &self.context;
+
+        context.fill_rectangle_in_texture(&mut texture, color, rectangle);
+    }
+
+    /// Fills `rectangle` of this layer with a gradient of `stops`, laid out
+    /// in `direction` over the rectangle. Like `draw_child_layer`, the
+    /// rectangle is in points, scaled to the layer's native resolution.
+    /// Transparent parts of the gradient blend with what's underneath.
+    pub fn fill_gradient(&self, rectangle: &Rectangle<i32, u32>, stops: &[GradientStop], direction: &GradientDirection) {
+        let context = &self.context;
+        let destination = rectangle * context.render_scale();
+        let size = &destination.size;
+        if stops.is_empty() || size.width == 0 || size.height == 0 {
+            return;
+        }
+
+        let pixels = gradient::gradient_pixels(size, stops, direction);
+
+        let mut gradient_texture = context.create_texture(Some(PixelFormatEnum::RGBA32), TextureAccess::Static, size.width, size.height);
+        gradient_texture.update(None, &pixels, size.width as usize * 4).unwrap();
+        gradient_texture.set_blend_mode(BlendMode::Blend);
+
+        let mut texture = self.texture.borrow_mut();
+        context.draw_texture_in_texture(&mut texture, &gradient_texture, None, &destination);
+    }
+
+    /// Fills the inside of `path`, in points, with `color`. Which parts of
+    /// overlapping subpaths are inside is decided by `fill_rule`. Edges are
+    /// anti-aliased, blending with what's underneath.
+    pub fn fill_path(&self, path: &Path, color: Color, fill_rule: FillRule) {
+        let scale = self.context.render_scale();
+        let polygons = path.scaled(scale).flattened(PATH_TOLERANCE);
+        self.fill_polygons(&polygons, color, fill_rule);
+    }
+
+    /// Draws the outline of `path`, in points, with `color`, as wide and
+    /// with the caps, joins and dashes of `style`.
+    pub fn stroke_path(&self, path: &Path, color: Color, style: &StrokeStyle) {
+        let scale = self.context.render_scale();
+        let polylines = path.scaled(scale).flattened(PATH_TOLERANCE);
+        let polygons = stroke::stroke_polygons(&polylines, &style.scaled(scale), PATH_TOLERANCE);
+        self.fill_polygons(&polygons, color, FillRule::NonZero);
+    }
+
+    /// Draws an anti-aliased line from `from` to `to`, in points, `line_width`
+    /// wide, with butt ends.
+    pub fn stroke_line(&self, from: Point<f32>, to: Point<f32>, color: Color, line_width: f32) {
+        let mut path = Path::new();
+        path.move_to(from);
+        path.line_to(to);
+        self.stroke_path(&path, color, &StrokeStyle::new(line_width));
+    }
+
+    /// Fills the circle of `radius` around `center`, in points, with
+    /// anti-aliased edges.
+    pub fn fill_circle(&self, center: Point<f32>, radius: f32, color: Color) {
+        self.fill_ellipse(&circle_rectangle(center, radius), color);
+    }
+
+    /// Outlines the circle of `radius` around `center`, in points, with a
+    /// line `line_width` wide centered on it.
+    pub fn stroke_circle(&self, center: Point<f32>, radius: f32, color: Color, line_width: f32) {
+        self.stroke_ellipse(&circle_rectangle(center, radius), color, line_width);
+    }
+
+    /// Fills the ellipse fitting in `rectangle`, in points, with
+    /// anti-aliased edges.
+    pub fn fill_ellipse(&self, rectangle: &Rectangle<f32, f32>, color: Color) {
+        self.fill_path(&Path::ellipse(rectangle), color, FillRule::NonZero);
+    }
+
+    /// Outlines the ellipse fitting in `rectangle`, in points, with a line
+    /// `line_width` wide centered on it.
+    pub fn stroke_ellipse(&self, rectangle: &Rectangle<f32, f32>, color: Color, line_width: f32) {
+        self.stroke_path(&Path::ellipse(rectangle), color, &StrokeStyle::new(line_width));
+    }
+
+    /// Fills `rectangle`, in points, with its corners rounded by
+    /// `corner_radius`, with anti-aliased edges. Unlike
+    /// `fill_rectangle_without_scaling`, the rectangle can be at fractions
+    /// of a pixel.
+    pub fn fill_rounded_rectangle(&self, rectangle: &Rectangle<f32, f32>, corner_radius: f32, color: Color) {
+        self.fill_path(&Path::rounded_rectangle(rectangle, corner_radius), color, FillRule::NonZero);
+    }
+
+    /// Outlines `rectangle`, in points, with its corners rounded by
+    /// `corner_radius`, with a line `line_width` wide centered on its
+    /// edges.
+    pub fn stroke_rounded_rectangle(&self, rectangle: &Rectangle<f32, f32>, corner_radius: f32, color: Color, line_width: f32) {
+        self.stroke_path(&Path::rounded_rectangle(rectangle, corner_radius), color, &StrokeStyle::new(line_width));
+    }
+
+    /// Fills `polygons`, in pixels, with `color`, through a texture of just
+    /// the part of the layer they cover.
+    fn fill_polygons(&self, polygons: &[Polyline], color: Color, fill_rule: FillRule) {
+        let points = polygons.iter().flat_map(|polygon| polygon.points.iter());
+        let (mut left, mut top, mut right, mut bottom) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for (x, y) in points {
+            left = left.min(*x);
+            top = top.min(*y);
+            right = right.max(*x);
+            bottom = bottom.max(*y);
+        }
+
+        let pixel_size = self.pixel_size();
+        let bounds = Rectangle::new(left, top, right - left, bottom - top).rounded_out();
+        let bounds = match bounds.intersection(&Rectangle::new(0, 0, pixel_size.width, pixel_size.height)) {
+            Some(bounds) => bounds,
+            None => return
+        };
+
+        let (left, top, width, height) = (bounds.left(), bounds.top(), bounds.width(), bounds.height());
+        let mask = rasterizer::coverage_mask(polygons, (left, top), width, height, fill_rule);
+        let pixels: Vec<u8> = mask
+            .iter()
+            .flat_map(|coverage| {
+                let alpha = (*coverage as u32 * color.a as u32 / 255) as u8;
+                vec![color.r, color.g, color.b, alpha]
+            })
+            .collect();
+
+        let context = &self.context;
+        let mut path_texture = context.create_texture(Some(PixelFormatEnum::RGBA32), TextureAccess::Static, width, height);
+        path_texture.update(None, &pixels, width as usize * 4).unwrap();
+        path_texture.set_blend_mode(BlendMode::Blend);
+
+        let mut texture = self.texture.borrow_mut();
+        context.draw_texture_in_texture(&mut texture, &path_texture, None, &Rectangle::new(left, top, width, height));
+    }
+
+    /// Saves the current clipping, to go back to with `restore`. Saves can
+    /// be nested, each restore going back to the one before. Clipping left
+    /// in place once the delegate finishes drawing is restored then.
+    ///
+    /// ```ignore
+    /// layer.save();
+    /// layer.clip_to_rect(&Rectangle::new(0, 0, filled_width, height));
+    /// layer.fill_rounded_rectangle(&track, radius, fill_color);
+    /// layer.restore();
+    /// ```
+    pub fn save(&self) {
+        self.graphics_states.borrow_mut().push(GraphicsState {
+            clip_rectangle: self.context.clip_rectangle(),
+            path_clip: None
+        });
+    }
+
+    /// Goes back to the clipping from the last `save`. Anything drawn while
+    /// clipped to a path is drawn into the layer now, cut to the path.
+    pub fn restore(&self) {
+        let state = match self.graphics_states.borrow_mut().pop() {
+            Some(state) => state,
+            None => return
+        };
+
+        if let Some(path_clip) = state.path_clip {
+            let pixels = masked_pixels(&self.read_pixels(), &path_clip.mask);
+            let pixel_size = self.pixel_size();
+
+            let mut clipped = self.context.create_texture(Some(PixelFormatEnum::RGBA32), TextureAccess::Static, pixel_size.width, pixel_size.height);
+            clipped.update(None, &pixels, pixel_size.width as usize * 4).unwrap();
+            clipped.set_blend_mode(BlendMode::Blend);
+
+            let mut texture = self.texture.borrow_mut();
+            *texture = path_clip.texture;
+            let destination = Rectangle { origin: Point { x: 0, y: 0 }, size: pixel_size };
+            self.context.draw_texture_in_texture(&mut texture, &clipped, None, &destination);
+        }
+
+        self.context.set_clip_rectangle(state.clip_rectangle.as_ref());
+    }
+
+    /// Limits drawing to `rectangle` of the layer, in points, within any
+    /// clipping already in place, until the next `restore`.
+    pub fn clip_to_rect(&self, rectangle: &Rectangle<i32, u32>) {
+        let pixel_size = self.pixel_size();
+        let current = self.context.clip_rectangle()
+            .unwrap_or(Rectangle { origin: Point { x: 0, y: 0 }, size: pixel_size });
+        let clip_rectangle = (rectangle * self.context.render_scale()).intersection(&current);
+        self.context.set_clip_rectangle(Some(&clip_rectangle.unwrap_or(NOTHING_CLIP_RECTANGLE)));
+    }
+
+    /// Limits drawing to the inside of `path`, in points, within any
+    /// clipping already in place, until the next `restore`. The path's
+    /// edges are anti-aliased.
+    ///
+    /// Drawing is blended into the layer when restored, so clearing while
+    /// clipped to a path doesn't clear what was drawn before.
+    pub fn clip_to_path(&self, path: &Path, fill_rule: FillRule) {
+        // Drawing clipped to a path is only drawn into the layer once
+        // restored.
+        if self.graphics_states.borrow().is_empty() {
+            self.save();
+        }
+
+        let scale = self.context.render_scale();
+        let path = path.scaled(scale);
+
+        let bounds = path.bounds().map(|bounds| bounds.rounded_out());
+        let current = self.context.clip_rectangle();
+        let clip_rectangle = match (bounds, current.as_ref()) {
+            (Some(bounds), Some(current)) => bounds.intersection(current),
+            (bounds, None) => bounds,
+            (None, Some(_)) => None
+        };
+        self.context.set_clip_rectangle(Some(&clip_rectangle.unwrap_or(NOTHING_CLIP_RECTANGLE)));
+
+        let pixel_size = self.pixel_size();
+        let coverage = rasterizer::coverage_mask(&path.flattened(PATH_TOLERANCE), (0, 0), pixel_size.width, pixel_size.height, fill_rule);
+
+        let mut states = self.graphics_states.borrow_mut();
+        let state = states.last_mut().unwrap();
+
+        match state.path_clip.as_mut() {
+            // Already clipped to a path since saving: only what's inside
+            // both is kept.
+            Some(path_clip) => {
+                for (mask, coverage) in path_clip.mask.iter_mut().zip(coverage) {
+                    *mask = (*mask as u32 * coverage as u32 / 255) as u8;
+                }
+            },
+            None => {
+                let mut clipped = self.context.create_texture(None, TextureAccess::Target, pixel_size.width, pixel_size.height);
+                clipped.set_blend_mode(BlendMode::Blend);
+                self.context.clear_texture(&mut clipped, Color::RGBA(0, 0, 0, 0));
+
+                let texture = std::mem::replace(&mut *self.texture.borrow_mut(), clipped);
+                state.path_clip = Some(PathClip { texture, mask: coverage });
+            }
+        }
+    }
+
+    /// The size of the layer's texture in pixels.
+    pub(crate) fn pixel_size(&self) -> Size<u32> {
+        let scale = self.context.render_scale();
+        Size::new(
+            (self.size.width as f32 * scale).round() as u32,
+            (self.size.height as f32 * scale).round() as u32
+        )
+    }
+
+    /// Makes the parts of the layer outside of rounded corners of `radius`
+    /// transparent.
+    pub(crate) fn clip_to_corner_radius(&self, radius: f32) {
+        let scale = self.context.render_scale();
+        let rectangles = rounded_rectangle::corner_rectangles(&self.pixel_size(), radius * scale);
+        if rectangles.is_empty() {
+            return;
+        }
+
+        let mut texture = self.texture.borrow_mut();
+        let transparent = Color::RGBA(0, 0, 0, 0);
+        self.context.fill_rectangles_in_texture(&mut texture, transparent, &rectangles, BlendMode::None);
+    }
+
+    /// Draws a border of `width` along the inside edge of the layer, rounded
+    /// with `corner_radius`.
+    pub(crate) fn draw_border(&self, width: f32, color: Color, corner_radius: f32) {
+        let scale = self.context.render_scale();
+        let width = (width * scale).round() as u32;
+        let rectangles = rounded_rectangle::border_rectangles(&self.pixel_size(), width, corner_radius * scale);
+
+        let mut texture = self.texture.borrow_mut();
+        self.context.fill_rectangles_in_texture(&mut texture, color, &rectangles, BlendMode::Blend);
+    }
+
     // Actually copies this layer's texture to the context canvas.
     pub fn draw_into_context(&self) {
         let context = &self.context;
@@ -223,16 +753,32 @@ impl Layer {
     }
 
     pub fn clear_with_color(&self, color: Color) {
+        // Clearing replaces pixels rather than blending into them, so while
+        // clipped to a path the color is premultiplied like everything else
+        // drawn there, for `masked_pixels` to divide out again.
+        let color = if self.is_clipped_to_path() { color.premultiplied() } else { color };
+
         let mut texture = self.texture.borrow_mut();
         let context = &self.context;
 
         context.clear_texture(&mut texture, color)
     }
 
+    /// Whether drawing is going into the texture of a `PathClip`.
+    fn is_clipped_to_path(&self) -> bool {
+        self.graphics_states.borrow().iter().any(|state| state.path_clip.is_some())
+    }
+
     pub fn size(&self) -> &Size<u32> {
         &self.size
     }
 
+    /// The context's render scale when the layer was made. See
+    /// `Context::render_scale`.
+    pub(crate) fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
     pub fn context(&self) -> &Context {
         &self.context
     }
@@ -257,3 +803,18 @@ impl std::fmt::Debug for Layer {
 
 struct EmptyLayerDelegate {}
 impl LayerDelegate for EmptyLayerDelegate {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked_pixels() {
+        // Opaque red, and half transparent red as drawn into a transparent
+        // texture.
+        let pixels = [255, 0, 0, 255, 128, 0, 0, 128, 255, 0, 0, 255];
+        let mask = [255, 255, 128];
+
+        assert_eq!(masked_pixels(&pixels, &mask), vec![255, 0, 0, 255, 255, 0, 0, 128, 255, 0, 0, 128]);
+    }
+}
@@ -5,6 +5,9 @@ mod context;
 pub use context::Context;
 pub use context::SDL_CONTAINER;
 
+mod renderer;
+pub use renderer::{CanvasRenderer, HeadlessRenderer, Renderer, RendererKind, RenderTarget};
+
 mod point;
 pub use point::Point;
 
@@ -14,6 +17,28 @@ pub use size::Size;
 mod rectangle;
 pub use rectangle::Rectangle;
 
+mod edge_insets;
+pub use edge_insets::EdgeInsets;
+
+mod transform;
+pub use transform::Transform;
+
+mod rounded_rectangle;
+
+mod shadow;
+
+mod path;
+pub use path::{Path, PathElement};
+
+mod rasterizer;
+pub use rasterizer::FillRule;
+
+mod stroke;
+pub use stroke::{LineCap, LineJoin, StrokeStyle};
+
+mod gradient;
+pub use gradient::{Gradient, GradientDirection, GradientStop};
+
 mod layer;
 pub use layer::Layer;
 
@@ -23,7 +48,19 @@ pub use layer::LayerDelegate;
 mod font;
 pub use font::Font;
 
+mod font_registry;
+pub use font_registry::{FontDescriptor, FontFace, FontRegistry, FontWeight};
+
 mod image;
 pub use image::Image;
 
+mod image_cache;
+pub use image_cache::ImageCache;
+
+mod image_atlas;
+pub use image_atlas::ImageAtlas;
+
 pub use sdl2::pixels::Color;
+
+mod color_space;
+pub use color_space::{linear_to_srgb, srgb_to_linear, LinearColor, Premultiply};
@@ -0,0 +1,554 @@
+//! Paths of lines and curves, for drawing shapes such as charts and icons.
+//!
+//! A path is made of subpaths, each starting with `move_to` and followed by
+//! lines, curves and arcs from wherever the last one ended. It's drawn with
+//! `Layer::fill_path`, or `Layer::stroke_path` for its outline, in points
+//! with the origin at the top left of the layer and y going down.
+//!
+//! ```ignore
+//! let mut path = Path::new();
+//! path.move_to(Point::new(10.0, 40.0));
+//! path.line_to(Point::new(30.0, 10.0));
+//! path.quad_curve_to(Point::new(40.0, 0.0), Point::new(50.0, 25.0));
+//! path.close();
+//!
+//! layer.fill_path(&path, Color::RGBA(2, 117, 227, 255), FillRule::NonZero);
+//! layer.stroke_path(&path, Color::RGBA(0, 0, 0, 255), &StrokeStyle::new(2.0));
+//! ```
+
+use crate::graphics::{Point, Rectangle};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// One step of a path. Curves and lines go from the end of the previous
+/// element.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathElement {
+    /// Starts a new subpath at the point.
+    MoveTo(Point<f32>),
+
+    /// A straight line to the point.
+    LineTo(Point<f32>),
+
+    /// A quadratic Bézier curve to `to`, bent towards `control`.
+    QuadCurveTo { control: Point<f32>, to: Point<f32> },
+
+    /// A cubic Bézier curve to `to`, leaving towards `control1` and
+    /// arriving from `control2`.
+    CubicCurveTo { control1: Point<f32>, control2: Point<f32>, to: Point<f32> },
+
+    /// A straight line back to the start of the subpath, joining up with
+    /// it.
+    Close
+}
+
+/// A path of lines and curves. See the module documentation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    elements: Vec<PathElement>
+}
+
+/// A subpath turned into straight lines between `points`, in the order
+/// they're drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Polyline {
+    pub points: Vec<(f32, f32)>,
+    pub closed: bool
+}
+
+impl Path {
+    pub fn new() -> Path {
+        Path { elements: Vec::new() }
+    }
+
+    /// A closed path around `rectangle`, clockwise from its top left.
+    pub fn rectangle(rectangle: &Rectangle<f32, f32>) -> Path {
+        let mut path = Path::new();
+        path.add_rectangle(rectangle);
+        path
+    }
+
+    /// A closed path around `rectangle`, with its corners rounded by
+    /// `corner_radius`.
+    pub fn rounded_rectangle(rectangle: &Rectangle<f32, f32>, corner_radius: f32) -> Path {
+        let mut path = Path::new();
+        path.add_rounded_rectangle(rectangle, corner_radius);
+        path
+    }
+
+    /// A closed path around the ellipse fitting in `rectangle`.
+    pub fn ellipse(rectangle: &Rectangle<f32, f32>) -> Path {
+        let mut path = Path::new();
+        path.add_ellipse(rectangle);
+        path
+    }
+
+    pub fn elements(&self) -> &[PathElement] {
+        &self.elements
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Where the path has got to, which the next line or curve starts from,
+    /// if anywhere yet.
+    pub fn current_point(&self) -> Option<Point<f32>> {
+        let mut start = None;
+        let mut current = None;
+        for element in &self.elements {
+            match element {
+                PathElement::MoveTo(point) => {
+                    start = Some(point.clone());
+                    current = Some(point.clone());
+                },
+                PathElement::LineTo(to) | PathElement::QuadCurveTo { to, .. } | PathElement::CubicCurveTo { to, .. } => {
+                    current = Some(to.clone());
+                },
+                PathElement::Close => current = start.clone()
+            }
+        }
+        current
+    }
+
+    /// Starts a new subpath at `point`.
+    pub fn move_to(&mut self, point: Point<f32>) {
+        self.elements.push(PathElement::MoveTo(point));
+    }
+
+    /// Adds a straight line to `point`. Starts the path there if it hasn't
+    /// started yet.
+    pub fn line_to(&mut self, point: Point<f32>) {
+        if self.current_point().is_none() {
+            self.move_to(point);
+            return;
+        }
+        self.elements.push(PathElement::LineTo(point));
+    }
+
+    /// Adds a quadratic Bézier curve to `to`, bent towards `control`.
+    pub fn quad_curve_to(&mut self, control: Point<f32>, to: Point<f32>) {
+        self.start_if_needed(&control);
+        self.elements.push(PathElement::QuadCurveTo { control, to });
+    }
+
+    /// Adds a cubic Bézier curve to `to`, leaving towards `control1` and
+    /// arriving from `control2`.
+    pub fn cubic_curve_to(&mut self, control1: Point<f32>, control2: Point<f32>, to: Point<f32>) {
+        self.start_if_needed(&control1);
+        self.elements.push(PathElement::CubicCurveTo { control1, control2, to });
+    }
+
+    /// Adds an arc of the circle around `center`, from `start_angle` to
+    /// `end_angle`, in radians from the positive x axis. As y goes down,
+    /// increasing angles go clockwise on screen; `clockwise` picks which
+    /// way round the circle the arc goes.
+    ///
+    /// A line is added from the current point to the start of the arc, if
+    /// the path has started. The arc is made of cubic curves, a quarter of
+    /// the circle at most each.
+    pub fn arc(&mut self, center: Point<f32>, radius: f32, start_angle: f32, end_angle: f32, clockwise: bool) {
+        let mut sweep = end_angle - start_angle;
+        if clockwise && sweep < 0.0 {
+            sweep = sweep % (2.0 * PI) + 2.0 * PI;
+        } else if !clockwise && sweep > 0.0 {
+            sweep = sweep % (2.0 * PI) - 2.0 * PI;
+        }
+
+        let point_at = |angle: f32| Point::new(center.x + radius * angle.cos(), center.y + radius * angle.sin());
+
+        let start = point_at(start_angle);
+        if self.current_point().is_some() {
+            self.line_to(start);
+        } else {
+            self.move_to(start);
+        }
+
+        let segments = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = sweep / segments as f32;
+        // How far along the tangents the control points go, for a cubic
+        // curve closest to an arc of `step`.
+        let handle = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+
+        for segment in 0..segments {
+            let from = start_angle + step * segment as f32;
+            let to = from + step;
+            let control1 = Point::new(
+                center.x + radius * from.cos() - handle * from.sin(),
+                center.y + radius * from.sin() + handle * from.cos()
+            );
+            let control2 = Point::new(
+                center.x + radius * to.cos() + handle * to.sin(),
+                center.y + radius * to.sin() - handle * to.cos()
+            );
+            self.elements.push(PathElement::CubicCurveTo { control1, control2, to: point_at(to) });
+        }
+    }
+
+    /// Closes the current subpath with a straight line back to its start.
+    pub fn close(&mut self) {
+        if self.current_point().is_some() && self.elements.last() != Some(&PathElement::Close) {
+            self.elements.push(PathElement::Close);
+        }
+    }
+
+    /// Adds a closed subpath around `rectangle`, clockwise from its top
+    /// left.
+    pub fn add_rectangle(&mut self, rectangle: &Rectangle<f32, f32>) {
+        let (x, y) = (rectangle.origin.x, rectangle.origin.y);
+        let (width, height) = (rectangle.size.width, rectangle.size.height);
+
+        self.move_to(Point::new(x, y));
+        self.line_to(Point::new(x + width, y));
+        self.line_to(Point::new(x + width, y + height));
+        self.line_to(Point::new(x, y + height));
+        self.close();
+    }
+
+    /// Adds a closed subpath around `rectangle` with its corners rounded
+    /// by `corner_radius`, clockwise from the top left. The radius is
+    /// limited to half the shorter side, for the corners not to overlap.
+    ///
+    /// Any two rounded rectangles are made of the same elements, so are
+    /// compatible for interpolating between.
+    pub fn add_rounded_rectangle(&mut self, rectangle: &Rectangle<f32, f32>, corner_radius: f32) {
+        let (x, y) = (rectangle.origin.x, rectangle.origin.y);
+        let (width, height) = (rectangle.size.width, rectangle.size.height);
+        let radius = corner_radius.min(width.min(height) / 2.0).max(0.0);
+        let (left, top, right, bottom) = (x + radius, y + radius, x + width - radius, y + height - radius);
+
+        // Each arc adds the straight edge leading up to it.
+        self.move_to(Point::new(left, y));
+        self.arc(Point::new(right, top), radius, -FRAC_PI_2, 0.0, true);
+        self.arc(Point::new(right, bottom), radius, 0.0, FRAC_PI_2, true);
+        self.arc(Point::new(left, bottom), radius, FRAC_PI_2, PI, true);
+        self.arc(Point::new(left, top), radius, PI, PI + FRAC_PI_2, true);
+        self.close();
+    }
+
+    /// Adds a closed subpath around the ellipse fitting in `rectangle`,
+    /// clockwise from its rightmost point.
+    pub fn add_ellipse(&mut self, rectangle: &Rectangle<f32, f32>) {
+        let radius_x = rectangle.size.width / 2.0;
+        let radius_y = rectangle.size.height / 2.0;
+        let center_x = rectangle.origin.x + radius_x;
+        let center_y = rectangle.origin.y + radius_y;
+
+        // The unit circle's arc, stretched to the ellipse.
+        let mut circle = Path::new();
+        circle.arc(Point::new(0.0, 0.0), 1.0, 0.0, 2.0 * PI, true);
+        let stretch = |point: &Point<f32>| Point::new(center_x + point.x * radius_x, center_y + point.y * radius_y);
+
+        for element in circle.elements {
+            self.elements.push(match element {
+                PathElement::MoveTo(point) => PathElement::MoveTo(stretch(&point)),
+                PathElement::CubicCurveTo { control1, control2, to } => PathElement::CubicCurveTo {
+                    control1: stretch(&control1),
+                    control2: stretch(&control2),
+                    to: stretch(&to)
+                },
+                element => element
+            });
+        }
+        self.close();
+    }
+
+    /// The smallest rectangle containing every point of the path,
+    /// including curves' control points, or `None` if it's empty.
+    pub fn bounds(&self) -> Option<Rectangle<f32, f32>> {
+        let mut points = self.elements.iter().flat_map(|element| match element {
+            PathElement::MoveTo(point) | PathElement::LineTo(point) => vec![point],
+            PathElement::QuadCurveTo { control, to } => vec![control, to],
+            PathElement::CubicCurveTo { control1, control2, to } => vec![control1, control2, to],
+            PathElement::Close => vec![]
+        });
+
+        let first = points.next()?;
+        let (mut left, mut top, mut right, mut bottom) = (first.x, first.y, first.x, first.y);
+        for point in points {
+            left = left.min(point.x);
+            top = top.min(point.y);
+            right = right.max(point.x);
+            bottom = bottom.max(point.y);
+        }
+        Some(Rectangle::new(left, top, right - left, bottom - top))
+    }
+
+    /// Whether the path can be interpolated with `other`: both have the
+    /// same elements in the same order, only at different points.
+    pub fn is_compatible(&self, other: &Path) -> bool {
+        self.elements.len() == other.elements.len()
+            && self.elements.iter().zip(&other.elements).all(|(a, b)| std::mem::discriminant(a) == std::mem::discriminant(b))
+    }
+
+    /// The path `progress` of the way from this one to `to`, each point
+    /// moved along a straight line, or `None` if the paths aren't
+    /// compatible. Progress past 1, such as from a springy easing,
+    /// overshoots.
+    pub fn interpolate(&self, to: &Path, progress: f32) -> Option<Path> {
+        if !self.is_compatible(to) {
+            return None;
+        }
+
+        let mix = |from: &Point<f32>, to: &Point<f32>| Point::new(
+            from.x + (to.x - from.x) * progress,
+            from.y + (to.y - from.y) * progress
+        );
+
+        let elements = self.elements.iter().zip(&to.elements).map(|pair| match pair {
+            (PathElement::MoveTo(from), PathElement::MoveTo(to)) => PathElement::MoveTo(mix(from, to)),
+            (PathElement::LineTo(from), PathElement::LineTo(to)) => PathElement::LineTo(mix(from, to)),
+            (PathElement::QuadCurveTo { control: from_control, to: from }, PathElement::QuadCurveTo { control, to }) => {
+                PathElement::QuadCurveTo {
+                    control: mix(from_control, control),
+                    to: mix(from, to)
+                }
+            },
+            (
+                PathElement::CubicCurveTo { control1: from_control1, control2: from_control2, to: from },
+                PathElement::CubicCurveTo { control1, control2, to }
+            ) => PathElement::CubicCurveTo {
+                control1: mix(from_control1, control1),
+                control2: mix(from_control2, control2),
+                to: mix(from, to)
+            },
+            _ => PathElement::Close
+        }).collect();
+
+        Some(Path { elements })
+    }
+
+    /// The path with every point scaled by `scale`, such as from points to
+    /// pixels.
+    pub(crate) fn scaled(&self, scale: f32) -> Path {
+        let scale_point = |point: &Point<f32>| Point::new(point.x * scale, point.y * scale);
+        let elements = self.elements.iter().map(|element| match element {
+            PathElement::MoveTo(point) => PathElement::MoveTo(scale_point(point)),
+            PathElement::LineTo(point) => PathElement::LineTo(scale_point(point)),
+            PathElement::QuadCurveTo { control, to } => PathElement::QuadCurveTo {
+                control: scale_point(control),
+                to: scale_point(to)
+            },
+            PathElement::CubicCurveTo { control1, control2, to } => PathElement::CubicCurveTo {
+                control1: scale_point(control1),
+                control2: scale_point(control2),
+                to: scale_point(to)
+            },
+            PathElement::Close => PathElement::Close
+        }).collect();
+        Path { elements }
+    }
+
+    /// The subpaths, with curves replaced by straight lines no further than
+    /// `tolerance` from them.
+    pub(crate) fn flattened(&self, tolerance: f32) -> Vec<Polyline> {
+        let mut polylines: Vec<Polyline> = Vec::new();
+        let mut current: Option<Polyline> = None;
+
+        for element in &self.elements {
+            match element {
+                PathElement::MoveTo(point) => {
+                    if let Some(polyline) = current.take() {
+                        polylines.push(polyline);
+                    }
+                    current = Some(Polyline { points: vec![(point.x, point.y)], closed: false });
+                },
+                PathElement::LineTo(to) => {
+                    if let Some(polyline) = current.as_mut() {
+                        polyline.points.push((to.x, to.y));
+                    }
+                },
+                PathElement::QuadCurveTo { control, to } => {
+                    if let Some(polyline) = current.as_mut() {
+                        let from = *polyline.points.last().unwrap();
+                        flatten_quad(from, (control.x, control.y), (to.x, to.y), tolerance, &mut polyline.points);
+                    }
+                },
+                PathElement::CubicCurveTo { control1, control2, to } => {
+                    if let Some(polyline) = current.as_mut() {
+                        let from = *polyline.points.last().unwrap();
+                        flatten_cubic(
+                            from,
+                            (control1.x, control1.y),
+                            (control2.x, control2.y),
+                            (to.x, to.y),
+                            tolerance,
+                            &mut polyline.points
+                        );
+                    }
+                },
+                PathElement::Close => {
+                    if let Some(mut polyline) = current.take() {
+                        polyline.closed = true;
+                        // Anything after closing starts where the subpath did.
+                        let start = polyline.points[0];
+                        polylines.push(polyline);
+                        current = Some(Polyline { points: vec![start], closed: false });
+                    }
+                }
+            }
+        }
+
+        if let Some(polyline) = current {
+            polylines.push(polyline);
+        }
+
+        polylines.retain(|polyline| polyline.closed || polyline.points.len() > 1);
+        polylines
+    }
+
+    fn start_if_needed(&mut self, point: &Point<f32>) {
+        if self.current_point().is_none() {
+            self.move_to(point.clone());
+        }
+    }
+}
+
+/// Adds the points of the quadratic curve from `from` to `to` to `points`,
+/// as lines no further than `tolerance` from the curve.
+fn flatten_quad(from: (f32, f32), control: (f32, f32), to: (f32, f32), tolerance: f32, points: &mut Vec<(f32, f32)>) {
+    // The curve strays from a line by at most a quarter of how far its
+    // control point bends from the chord, shrinking with the square of the
+    // number of segments.
+    let bend = distance((from.0 - 2.0 * control.0 + to.0, from.1 - 2.0 * control.1 + to.1));
+    let segments = (bend / (8.0 * tolerance)).sqrt().ceil().max(1.0) as usize;
+
+    for segment in 1..=segments {
+        let t = segment as f32 / segments as f32;
+        let u = 1.0 - t;
+        points.push((
+            u * u * from.0 + 2.0 * u * t * control.0 + t * t * to.0,
+            u * u * from.1 + 2.0 * u * t * control.1 + t * t * to.1
+        ));
+    }
+}
+
+/// Adds the points of the cubic curve from `from` to `to` to `points`, as
+/// lines no further than `tolerance` from the curve.
+fn flatten_cubic(from: (f32, f32), control1: (f32, f32), control2: (f32, f32), to: (f32, f32), tolerance: f32, points: &mut Vec<(f32, f32)>) {
+    let bend1 = distance((from.0 - 2.0 * control1.0 + control2.0, from.1 - 2.0 * control1.1 + control2.1));
+    let bend2 = distance((control1.0 - 2.0 * control2.0 + to.0, control1.1 - 2.0 * control2.1 + to.1));
+    let segments = (3.0 * bend1.max(bend2) / (4.0 * tolerance)).sqrt().ceil().max(1.0) as usize;
+
+    for segment in 1..=segments {
+        let t = segment as f32 / segments as f32;
+        let u = 1.0 - t;
+        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+        points.push((
+            a * from.0 + b * control1.0 + c * control2.0 + d * to.0,
+            a * from.1 + b * control1.1 + c * control2.1 + d * to.1
+        ));
+    }
+}
+
+pub(crate) fn distance(vector: (f32, f32)) -> f32 {
+    vector.0.hypot(vector.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_to(a: (f32, f32), b: (f32, f32)) -> bool {
+        distance((a.0 - b.0, a.1 - b.1)) < 0.01
+    }
+
+    #[test]
+    fn test_building_a_path() {
+        let mut path = Path::new();
+        assert!(path.current_point().is_none());
+
+        // Lines start the path if there's nowhere to start from.
+        path.line_to(Point::new(10.0, 10.0));
+        assert_eq!(path.elements(), &[PathElement::MoveTo(Point::new(10.0, 10.0))]);
+
+        path.line_to(Point::new(20.0, 10.0));
+        path.quad_curve_to(Point::new(30.0, 10.0), Point::new(30.0, 20.0));
+        assert_eq!(path.current_point(), Some(Point::new(30.0, 20.0)));
+
+        path.close();
+        path.close();
+        assert_eq!(path.elements().len(), 4);
+        assert_eq!(path.current_point(), Some(Point::new(10.0, 10.0)));
+        assert_eq!(path.bounds(), Some(Rectangle::new(10.0, 10.0, 20.0, 10.0)));
+    }
+
+    #[test]
+    fn test_flattened_curves_stay_on_the_curve() {
+        let mut path = Path::new();
+        path.arc(Point::new(50.0, 50.0), 40.0, 0.0, PI, true);
+
+        let polylines = path.flattened(0.25);
+        assert_eq!(polylines.len(), 1);
+        let points = &polylines[0].points;
+        assert!(points.len() > 4);
+        assert!(close_to(points[0], (90.0, 50.0)));
+        assert!(close_to(*points.last().unwrap(), (10.0, 50.0)));
+
+        // Clockwise on screen goes down first, through the bottom.
+        assert!(points.iter().all(|point| point.1 >= 49.99));
+        for point in points {
+            assert!((distance((point.0 - 50.0, point.1 - 50.0)) - 40.0).abs() < 0.3);
+        }
+    }
+
+    #[test]
+    fn test_flattened_subpaths() {
+        let mut path = Path::rectangle(&Rectangle::new(0.0, 0.0, 10.0, 5.0));
+        path.move_to(Point::new(20.0, 20.0));
+        path.move_to(Point::new(30.0, 30.0));
+        path.line_to(Point::new(40.0, 30.0));
+
+        let polylines = path.flattened(0.25);
+        assert_eq!(polylines, vec![
+            Polyline { points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (0.0, 5.0)], closed: true },
+            Polyline { points: vec![(30.0, 30.0), (40.0, 30.0)], closed: false }
+        ]);
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let small = Path::rectangle(&Rectangle::new(0.0, 0.0, 10.0, 10.0));
+        let large = Path::rectangle(&Rectangle::new(0.0, 0.0, 30.0, 20.0));
+        assert!(small.is_compatible(&large));
+        assert_eq!(small.interpolate(&large, 0.5), Some(Path::rectangle(&Rectangle::new(0.0, 0.0, 20.0, 15.0))));
+        assert_eq!(small.interpolate(&large, 1.0), Some(large.clone()));
+
+        // An ellipse is made of curves rather than lines.
+        let ellipse = Path::ellipse(&Rectangle::new(0.0, 0.0, 10.0, 10.0));
+        assert!(!small.is_compatible(&ellipse));
+        assert!(small.interpolate(&ellipse, 0.5).is_none());
+    }
+
+    #[test]
+    fn test_rounded_rectangle() {
+        let path = Path::rounded_rectangle(&Rectangle::new(0.0, 0.0, 40.0, 20.0), 5.0);
+        let bounds = |path: &Path| {
+            let bounds = path.bounds().unwrap();
+            let round = |value: f32| (value * 100.0).round() / 100.0;
+            Rectangle::new(round(bounds.origin.x), round(bounds.origin.y), round(bounds.size.width), round(bounds.size.height))
+        };
+        assert_eq!(bounds(&path), Rectangle::new(0.0, 0.0, 40.0, 20.0));
+
+        let points = &path.flattened(0.1)[0].points;
+        assert!(points.iter().all(|(x, y)| (-0.01..=40.01).contains(x) && (-0.01..=20.01).contains(y)));
+        // The corners are cut off.
+        assert!(points.iter().all(|(x, y)| distance((*x, *y)) > 2.0));
+
+        // Radii too big for the rectangle are limited, keeping the same
+        // elements for interpolating.
+        let pill = Path::rounded_rectangle(&Rectangle::new(0.0, 0.0, 40.0, 20.0), 50.0);
+        assert!(pill.is_compatible(&path));
+        assert_eq!(bounds(&pill), Rectangle::new(0.0, 0.0, 40.0, 20.0));
+    }
+
+    #[test]
+    fn test_ellipse() {
+        let path = Path::ellipse(&Rectangle::new(0.0, 0.0, 40.0, 20.0));
+        let polylines = path.flattened(0.1);
+        assert!(polylines[0].closed);
+        for (x, y) in &polylines[0].points {
+            let (dx, dy) = ((x - 20.0) / 20.0, (y - 10.0) / 10.0);
+            assert!((dx * dx + dy * dy - 1.0).abs() < 0.02);
+        }
+    }
+}
@@ -23,6 +23,47 @@ impl<T> Clone for Point<T> where T: Number {
     }
 }
 
+impl Point<i32> {
+    /// The point moved `dx` right and `dy` down.
+    pub fn offset_by(&self, dx: i32, dy: i32) -> Point<i32> {
+        Point::new(self.x + dx, self.y + dy)
+    }
+
+    /// The same point in floats, without losing anything.
+    pub fn to_f32(&self) -> Point<f32> {
+        Point::new(self.x as f32, self.y as f32)
+    }
+}
+
+impl Point<f32> {
+    /// The point moved `dx` right and `dy` down.
+    pub fn offset_by(&self, dx: f32, dy: f32) -> Point<f32> {
+        Point::new(self.x + dx, self.y + dy)
+    }
+
+    /// The nearest point of whole points.
+    pub fn rounded(&self) -> Point<i32> {
+        Point::new(self.x.round() as i32, self.y.round() as i32)
+    }
+}
+
+impl From<&Point<i32>> for Point<f32> {
+    fn from(point: &Point<i32>) -> Point<f32> {
+        point.to_f32()
+    }
+}
+
+impl std::ops::Sub<Point<i32>> for Point<i32> {
+    type Output = Point<i32>;
+
+    fn sub(self, other: Point<i32>) -> Point<i32> {
+        Point {
+            x: self.x - other.x,
+            y: self.y - other.y
+        }
+    }
+}
+
 impl std::ops::Add<Point<i32>> for Point<i32> {
     type Output = Point<i32>;
 
@@ -80,4 +121,12 @@ mod tests {
       let p3 = p1 + p2;
       assert_eq!(p3, Point { x: 4, y: 6 });
     }
+
+    #[test]
+    fn test_sub_and_offset() {
+      let p1 = Point { x: 4, y: 6 };
+      assert_eq!(p1.clone() - Point { x: 3, y: 4 }, Point { x: 1, y: 2 });
+      assert_eq!(p1.offset_by(-4, 1), Point { x: 0, y: 7 });
+      assert_eq!(Point::from(&p1).offset_by(0.4, 0.6).rounded(), Point { x: 4, y: 7 });
+    }
 }
@@ -0,0 +1,169 @@
+//! Turns polygons into how much of each pixel they cover, for filling
+//! paths with anti-aliased edges.
+
+use crate::graphics::path::Polyline;
+
+/// How many rows each row of pixels is sampled at, for vertical
+/// anti-aliasing. Horizontal coverage is worked out exactly.
+const SUBSAMPLES: usize = 4;
+
+/// Which parts of a path crossing over itself, or with subpaths inside
+/// each other, count as inside it and get filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside wherever the path winds around a different number of times
+    /// one way than the other. Subpaths drawn the same way round fill each
+    /// other's holes, drawn opposite ways they cut them out.
+    NonZero,
+
+    /// Inside wherever the path is crossed an odd number of times on the
+    /// way out, so subpaths inside others cut holes in them.
+    EvenOdd
+}
+
+impl FillRule {
+    fn is_inside(&self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0
+        }
+    }
+}
+
+/// An edge of a polygon, going down from `top` to `bottom`, and whether it
+/// went up or down as drawn.
+struct Edge {
+    top: (f32, f32),
+    bottom: (f32, f32),
+    winding: i32
+}
+
+/// How much, from 0 to 255, of each pixel of a `width` by `height`
+/// rectangle of pixels at `origin` the polygons cover, in rows from the
+/// top left. Every polyline counts as closed.
+pub(crate) fn coverage_mask(polygons: &[Polyline], origin: (i32, i32), width: u32, height: u32, fill_rule: FillRule) -> Vec<u8> {
+    let edges: Vec<Edge> = polygons
+        .iter()
+        .flat_map(|polygon| {
+            let points = &polygon.points;
+            (0..points.len()).filter_map(move |index| {
+                let from = points[index];
+                let to = points[(index + 1) % points.len()];
+                let from = (from.0 - origin.0 as f32, from.1 - origin.1 as f32);
+                let to = (to.0 - origin.0 as f32, to.1 - origin.1 as f32);
+
+                if from.1 == to.1 {
+                    None
+                } else if from.1 < to.1 {
+                    Some(Edge { top: from, bottom: to, winding: 1 })
+                } else {
+                    Some(Edge { top: to, bottom: from, winding: -1 })
+                }
+            })
+        })
+        .collect();
+
+    let mut mask = Vec::with_capacity((width * height) as usize);
+    let mut row = vec![0.0_f32; width as usize];
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+
+    for y in 0..height {
+        row.iter_mut().for_each(|value| *value = 0.0);
+
+        for subsample in 0..SUBSAMPLES {
+            let sample_y = y as f32 + (subsample as f32 + 0.5) / SUBSAMPLES as f32;
+
+            crossings.clear();
+            for edge in &edges {
+                if sample_y >= edge.top.1 && sample_y < edge.bottom.1 {
+                    let progress = (sample_y - edge.top.1) / (edge.bottom.1 - edge.top.1);
+                    let x = edge.top.0 + (edge.bottom.0 - edge.top.0) * progress;
+                    crossings.push((x, edge.winding));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if fill_rule.is_inside(winding) {
+                    add_span(&mut row, pair[0].0, pair[1].0, 1.0 / SUBSAMPLES as f32);
+                }
+            }
+        }
+
+        mask.extend(row.iter().map(|value| (value * 255.0).round().clamp(0.0, 255.0) as u8));
+    }
+
+    mask
+}
+
+/// Adds `amount` to `row`, for the part of each pixel between `from` and
+/// `to` along it.
+fn add_span(row: &mut [f32], from: f32, to: f32, amount: f32) {
+    let from = from.max(0.0);
+    let to = to.min(row.len() as f32);
+    if from >= to {
+        return;
+    }
+
+    let first = from.floor() as usize;
+    let last = (to.ceil() as usize - 1).min(row.len() - 1);
+    if first == last {
+        row[first] += (to - from) * amount;
+        return;
+    }
+
+    row[first] += (first as f32 + 1.0 - from) * amount;
+    for value in row[first + 1..last].iter_mut() {
+        *value += amount;
+    }
+    row[last] += (to - last as f32) * amount;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(f32, f32)]) -> Polyline {
+        Polyline { points: points.to_vec(), closed: true }
+    }
+
+    #[test]
+    fn test_pixel_aligned_square() {
+        let square = polygon(&[(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)]);
+        let mask = coverage_mask(&[square], (0, 0), 4, 4, FillRule::NonZero);
+        assert_eq!(mask, vec![
+            0, 0, 0, 0,
+            0, 255, 255, 0,
+            0, 255, 255, 0,
+            0, 0, 0, 0
+        ]);
+    }
+
+    #[test]
+    fn test_partial_coverage() {
+        // Half a pixel over on each side, with the mask moved by its origin.
+        let square = polygon(&[(10.5, 10.5), (12.5, 10.5), (12.5, 12.5), (10.5, 12.5)]);
+        let mask = coverage_mask(&[square], (10, 10), 3, 3, FillRule::NonZero);
+        assert_eq!(mask, vec![
+            64, 128, 64,
+            128, 255, 128,
+            64, 128, 64
+        ]);
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        let outer = polygon(&[(0.0, 0.0), (6.0, 0.0), (6.0, 6.0), (0.0, 6.0)]);
+        let inner = polygon(&[(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)]);
+        let polygons = [outer, inner];
+
+        // Both drawn the same way round: only even-odd cuts a hole.
+        let non_zero = coverage_mask(&polygons, (0, 0), 6, 6, FillRule::NonZero);
+        let even_odd = coverage_mask(&polygons, (0, 0), 6, 6, FillRule::EvenOdd);
+        assert_eq!(non_zero[3 * 6 + 3], 255);
+        assert_eq!(even_odd[3 * 6 + 3], 0);
+        assert_eq!(even_odd[6 + 1], 255);
+    }
+}
@@ -29,6 +29,9 @@ impl Rectangle<i32, u32> {
         }
     }
 
+    /// Whether `point` is inside the rectangle or on any of its edges. See
+    /// `contains_point` for hit testing, where rectangles side by side
+    /// shouldn't both contain the points between them.
     pub fn contains(&self, point: &Point<i32>) -> bool {
         point.x >= self.origin.x && point.y >= self.origin.y &&
             point.x <= self.origin.x + self.size.width as i32 &&
@@ -73,6 +76,177 @@ impl Rectangle<i32, u32> {
     pub fn origin(&self) -> &Point<i32> {
         &self.origin
     }
+
+    /// Whether the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        self.size.width == 0 || self.size.height == 0
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &Rectangle<i32, u32>) -> Rectangle<i32, u32> {
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        Rectangle::new(left, top, (right - left) as u32, (bottom - top) as u32)
+    }
+
+    /// The area covered by both rectangles, or `None` if they don't overlap.
+    pub fn intersection(&self, other: &Rectangle<i32, u32>) -> Option<Rectangle<i32, u32>> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        Some(Rectangle::new(left, top, (right - left) as u32, (bottom - top) as u32))
+    }
+
+    /// Whether the rectangles overlap. Rectangles that only touch don't.
+    pub fn intersects(&self, other: &Rectangle<i32, u32>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Whether `point` is inside the rectangle, counting the left and top
+    /// edges but not the right and bottom ones, so each point is in only one
+    /// of rectangles laid side by side. Used for hit testing.
+    pub fn contains_point(&self, point: &Point<i32>) -> bool {
+        point.x >= self.left() && point.x < self.right() &&
+            point.y >= self.top() && point.y < self.bottom()
+    }
+
+    /// The rectangle moved `dx` right and `dy` down.
+    pub fn offset_by(&self, dx: i32, dy: i32) -> Rectangle<i32, u32> {
+        Rectangle::new(self.origin.x + dx, self.origin.y + dy, self.size.width, self.size.height)
+    }
+
+    /// The rectangle shrunk by `dx` on the left and right, and `dy` on the top
+    /// and bottom, about its center. Negative amounts grow it. A rectangle
+    /// shrunk past nothing is left empty, at its center.
+    pub fn inset_by(&self, dx: i32, dy: i32) -> Rectangle<i32, u32> {
+        let width = (self.size.width as i64 - dx as i64 * 2).max(0) as u32;
+        let height = (self.size.height as i64 - dy as i64 * 2).max(0) as u32;
+        let x = if width == 0 { self.center().x } else { self.origin.x + dx };
+        let y = if height == 0 { self.center().y } else { self.origin.y + dy };
+
+        Rectangle::new(x, y, width, height)
+    }
+
+    /// The same rectangle in floats, such as for drawing paths. Every
+    /// rectangle a view can have converts without losing anything.
+    pub fn to_f32(&self) -> Rectangle<f32, f32> {
+        Rectangle::new(self.origin.x as f32, self.origin.y as f32, self.size.width as f32, self.size.height as f32)
+    }
+}
+
+impl Rectangle<f32, f32> {
+    pub fn left(&self) -> f32 {
+        self.origin.x
+    }
+
+    pub fn top(&self) -> f32 {
+        self.origin.y
+    }
+
+    pub fn right(&self) -> f32 {
+        self.origin.x + self.size.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.origin.y + self.size.height
+    }
+
+    pub fn center(&self) -> Point<f32> {
+        Point::new(self.origin.x + self.size.width / 2.0, self.origin.y + self.size.height / 2.0)
+    }
+
+    /// Whether the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        self.size.width <= 0.0 || self.size.height <= 0.0
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &Rectangle<f32, f32>) -> Rectangle<f32, f32> {
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        Rectangle::new(left, top, right - left, bottom - top)
+    }
+
+    /// The area covered by both rectangles, or `None` if they don't overlap.
+    pub fn intersection(&self, other: &Rectangle<f32, f32>) -> Option<Rectangle<f32, f32>> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        Some(Rectangle::new(left, top, right - left, bottom - top))
+    }
+
+    /// Whether the rectangles overlap. Rectangles that only touch don't.
+    pub fn intersects(&self, other: &Rectangle<f32, f32>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Whether `point` is inside the rectangle, counting the left and top
+    /// edges but not the right and bottom ones, like the integer version.
+    pub fn contains_point(&self, point: &Point<f32>) -> bool {
+        point.x >= self.left() && point.x < self.right() &&
+            point.y >= self.top() && point.y < self.bottom()
+    }
+
+    /// The rectangle moved `dx` right and `dy` down.
+    pub fn offset_by(&self, dx: f32, dy: f32) -> Rectangle<f32, f32> {
+        Rectangle::new(self.origin.x + dx, self.origin.y + dy, self.size.width, self.size.height)
+    }
+
+    /// The rectangle shrunk by `dx` on the left and right, and `dy` on the top
+    /// and bottom, about its center. Negative amounts grow it. A rectangle
+    /// shrunk past nothing is left empty, at its center.
+    pub fn inset_by(&self, dx: f32, dy: f32) -> Rectangle<f32, f32> {
+        let width = (self.size.width - dx * 2.0).max(0.0);
+        let height = (self.size.height - dy * 2.0).max(0.0);
+        let center = self.center();
+
+        Rectangle::new(center.x - width / 2.0, center.y - height / 2.0, width, height)
+    }
+
+    /// The smallest rectangle of whole points containing this one, so
+    /// nothing drawn in it is cut off.
+    pub fn rounded_out(&self) -> Rectangle<i32, u32> {
+        let left = self.left().floor();
+        let top = self.top().floor();
+        let right = self.right().ceil().max(left);
+        let bottom = self.bottom().ceil().max(top);
+
+        Rectangle::new(left as i32, top as i32, (right - left) as u32, (bottom - top) as u32)
+    }
+
+    /// The nearest rectangle of whole points, rounding each edge.
+    pub fn rounded(&self) -> Rectangle<i32, u32> {
+        let left = self.left().round();
+        let top = self.top().round();
+        let right = self.right().round().max(left);
+        let bottom = self.bottom().round().max(top);
+
+        Rectangle::new(left as i32, top as i32, (right - left) as u32, (bottom - top) as u32)
+    }
+}
+
+impl From<&Rectangle<i32, u32>> for Rectangle<f32, f32> {
+    fn from(rectangle: &Rectangle<i32, u32>) -> Rectangle<f32, f32> {
+        rectangle.to_f32()
+    }
 }
 
 impl<T, U> Clone for Rectangle<T, U> where T: Number, U: Number {
@@ -162,6 +336,71 @@ mod tests {
         assert!(!rect.contains(&Point { x: 101, y: -1 }));
     }
 
+    #[test]
+    fn test_union_and_intersection() {
+        let a = Rectangle::new(0, 0, 100, 50);
+        let b = Rectangle::new(50, 25, 100, 100);
+
+        assert_eq!(a.union(&b), Rectangle::new(0, 0, 150, 125));
+        assert_eq!(a.intersection(&b), Some(Rectangle::new(50, 25, 50, 25)));
+        assert_eq!(b.intersection(&a), a.intersection(&b));
+
+        // Rectangles that only touch don't overlap.
+        assert_eq!(a.intersection(&Rectangle::new(100, 0, 10, 10)), None);
+        assert_eq!(a.intersection(&Rectangle::new(-20, -20, 10, 10)), None);
+        assert!(Rectangle::new(10, 10, 0, 5).is_empty());
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let left = Rectangle::new(0, 0, 10, 10);
+        let right = Rectangle::new(10, 0, 10, 10);
+
+        // A point on the edge between them is only in one.
+        assert!(left.contains_point(&Point::new(0, 0)));
+        assert!(!left.contains_point(&Point::new(10, 5)));
+        assert!(right.contains_point(&Point::new(10, 5)));
+        assert!(left.contains(&Point::new(10, 5)));
+
+        let left = left.to_f32();
+        assert!(left.contains_point(&Point::new(9.5, 9.5)));
+        assert!(!left.contains_point(&Point::new(10.0, 5.0)));
+    }
+
+    #[test]
+    fn test_offset_and_inset() {
+        let rect = Rectangle::new(10, 20, 100, 50);
+
+        assert_eq!(rect.offset_by(-5, 5), Rectangle::new(5, 25, 100, 50));
+        assert_eq!(rect.inset_by(10, 5), Rectangle::new(20, 25, 80, 40));
+        assert_eq!(rect.inset_by(-10, 0), Rectangle::new(0, 20, 120, 50));
+        assert_eq!(rect.inset_by(60, 0), Rectangle::new(60, 20, 0, 50));
+        assert!(rect.intersects(&rect.offset_by(99, 49)));
+        assert!(!rect.intersects(&rect.offset_by(100, 0)));
+
+        let rect = rect.to_f32();
+        assert_eq!(rect.inset_by(10.0, 5.0), Rectangle::new(20.0, 25.0, 80.0, 40.0));
+        assert_eq!(rect.inset_by(60.0, 0.0), Rectangle::new(60.0, 20.0, 0.0, 50.0));
+    }
+
+    #[test]
+    fn test_float_rectangles() {
+        let a = Rectangle::new(0.0, 0.0, 10.0, 5.0);
+        let b = Rectangle::new(5.0, 2.5, 10.0, 10.0);
+
+        assert_eq!(a.union(&b), Rectangle::new(0.0, 0.0, 15.0, 12.5));
+        assert_eq!(a.intersection(&b), Some(Rectangle::new(5.0, 2.5, 5.0, 2.5)));
+        assert_eq!(a.intersection(&Rectangle::new(10.0, 0.0, 1.0, 1.0)), None);
+
+        // Converting to floats and back loses nothing.
+        let rect = Rectangle::new(-3, 7, 11, 13);
+        assert_eq!(Rectangle::from(&rect).rounded_out(), rect);
+        assert_eq!(rect.to_f32().rounded(), rect);
+
+        assert_eq!(Rectangle::new(0.5, 0.25, 2.0, 2.0).rounded_out(), Rectangle::new(0, 0, 3, 3));
+        assert_eq!(Rectangle::new(0.4, 0.6, 2.0, 2.0).rounded(), Rectangle::new(0, 1, 2, 2));
+    }
+
     #[test]
     fn test_multiply() {
         let rect: Rectangle<i32, u32> = Rectangle::new(0, 0, 100, 100);
@@ -0,0 +1,337 @@
+use crate::graphics::Size;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureAccess, TextureCreator};
+use sdl2::surface::{Surface, SurfaceContext};
+use sdl2::video::Window;
+
+/// Which `Renderer` a window draws with. See `Window::new_with_renderer`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RendererKind {
+    /// Composites layers on the graphics card, with textures kept in video
+    /// memory. Falls back to `Software` if there's no accelerated renderer.
+    #[default]
+    Gpu,
+
+    /// Composites layers on the CPU. Slower for large windows, but works
+    /// without a graphics card, such as in virtual machines.
+    Software
+}
+
+/// Where a `Renderer` draws: into a layer's texture, or the window itself.
+/// Without a window, the window is the memory a headless renderer draws
+/// into.
+pub enum RenderTarget<'a> {
+    Window,
+    Texture(&'a mut Texture)
+}
+
+/// Does the drawing for a `Context`: copying layer textures into each other,
+/// filling rectangles, and showing the result in the window.
+///
+/// Rectangles are in pixels. When there's a `clip` rectangle, nothing is
+/// drawn outside of it.
+pub trait Renderer {
+    fn kind(&self) -> RendererKind;
+
+    /// The window drawn into, or `None` for a headless renderer.
+    fn window(&self) -> Option<&Window>;
+    fn window_mut(&mut self) -> Option<&mut Window>;
+
+    /// The size of the window in pixels, which is larger than its size in
+    /// points on high density displays.
+    fn output_size(&self) -> Size<u32>;
+
+    /// A texture to draw into or copy, `width` by `height` pixels. Textures
+    /// can only be drawn by the renderer that made them.
+    fn create_texture(&self, format: Option<PixelFormatEnum>, access: TextureAccess, width: u32, height: u32) -> Texture;
+
+    /// A texture with the pixels of `surface`.
+    fn create_texture_from_surface(&self, surface: &Surface) -> Texture;
+
+    /// Copies `source` of `texture`, or all of it, to `destination` of
+    /// `target`, blending with the texture's blend mode.
+    fn copy(&mut self, target: RenderTarget, texture: &Texture, source: Option<Rect>, destination: Rect, clip: Option<Rect>);
+
+    /// Like `copy`, but rotating `texture` clockwise by `angle` degrees
+    /// about the center of `destination`, after flipping it.
+    #[allow(clippy::too_many_arguments)]
+    fn copy_rotated(
+        &mut self,
+        target: RenderTarget,
+        texture: &Texture,
+        source: Option<Rect>,
+        destination: Rect,
+        angle: f64,
+        flip_horizontal: bool,
+        flip_vertical: bool,
+        clip: Option<Rect>
+    );
+
+    /// Fills each of `rectangles` with `color`. With `BlendMode::None`, the
+    /// color replaces what's there, so a transparent color erases.
+    fn fill_rectangles(&mut self, target: RenderTarget, color: Color, rectangles: &[Rect], blend_mode: BlendMode, clip: Option<Rect>);
+
+    /// Replaces all of `target`, or just `clip`, with `color`.
+    fn clear(&mut self, target: RenderTarget, color: Color, clip: Option<Rect>);
+
+    /// The pixels of `target`, as RGBA bytes, row by row from the top left.
+    fn read_pixels(&mut self, target: RenderTarget) -> Vec<u8>;
+
+    /// Shows what's been drawn to the window.
+    fn present(&mut self);
+}
+
+/// A `Renderer` drawing with an SDL canvas, either accelerated, with
+/// textures on the graphics card, or in software.
+pub struct CanvasRenderer {
+    canvas: Canvas<Window>,
+    kind: RendererKind
+}
+
+impl CanvasRenderer {
+    /// A renderer for `window` of `kind`. If the graphics card can't be
+    /// used, the renderer draws in software instead.
+    pub fn new(window: Window, kind: RendererKind) -> CanvasRenderer {
+        if kind == RendererKind::Gpu {
+            // Building the canvas takes the window, so a reference is kept
+            // to get it back if it fails.
+            let window_context = window.context();
+
+            match window.into_canvas().accelerated().present_vsync().build() {
+                Ok(canvas) => return CanvasRenderer { canvas, kind },
+                Err(error) => {
+                    println!("Warning: no GPU renderer, drawing in software instead: {}", error);
+                    let window = unsafe { Window::from_ref(window_context) };
+                    return CanvasRenderer::new(window, RendererKind::Software);
+                }
+            }
+        }
+
+        CanvasRenderer {
+            canvas: window.into_canvas().software().build().unwrap(),
+            kind: RendererKind::Software
+        }
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    fn kind(&self) -> RendererKind {
+        self.kind
+    }
+
+    fn window(&self) -> Option<&Window> {
+        Some(self.canvas.window())
+    }
+
+    fn window_mut(&mut self) -> Option<&mut Window> {
+        Some(self.canvas.window_mut())
+    }
+
+    fn output_size(&self) -> Size<u32> {
+        let (width, height) = self.canvas.output_size().unwrap();
+        Size { width, height }
+    }
+
+    fn create_texture(&self, format: Option<PixelFormatEnum>, access: TextureAccess, width: u32, height: u32) -> Texture {
+        self.canvas.texture_creator().create_texture(format, access, width, height).unwrap()
+    }
+
+    fn create_texture_from_surface(&self, surface: &Surface) -> Texture {
+        self.canvas.texture_creator().create_texture_from_surface(surface).unwrap()
+    }
+
+    fn copy(&mut self, target: RenderTarget, texture: &Texture, source: Option<Rect>, destination: Rect, clip: Option<Rect>) {
+        copy(&mut self.canvas, target, texture, source, destination, clip);
+    }
+
+    fn copy_rotated(
+        &mut self,
+        target: RenderTarget,
+        texture: &Texture,
+        source: Option<Rect>,
+        destination: Rect,
+        angle: f64,
+        flip_horizontal: bool,
+        flip_vertical: bool,
+        clip: Option<Rect>
+    ) {
+        copy_rotated(&mut self.canvas, target, texture, source, destination, angle, flip_horizontal, flip_vertical, clip);
+    }
+
+    fn fill_rectangles(&mut self, target: RenderTarget, color: Color, rectangles: &[Rect], blend_mode: BlendMode, clip: Option<Rect>) {
+        fill_rectangles(&mut self.canvas, target, color, rectangles, blend_mode, clip);
+    }
+
+    fn clear(&mut self, target: RenderTarget, color: Color, clip: Option<Rect>) {
+        clear(&mut self.canvas, target, color, clip);
+    }
+
+    fn read_pixels(&mut self, target: RenderTarget) -> Vec<u8> {
+        read_pixels(&mut self.canvas, target)
+    }
+
+    fn present(&mut self) {
+        self.canvas.present();
+    }
+}
+
+/// A `Renderer` drawing in software into memory, with no window or display
+/// server, such as to test drawing or take snapshots of views. See
+/// `Context::new_headless`.
+pub struct HeadlessRenderer {
+    canvas: Canvas<Surface<'static>>,
+    texture_creator: TextureCreator<SurfaceContext<'static>>
+}
+
+impl HeadlessRenderer {
+    /// A renderer drawing into `size` pixels.
+    pub fn new(size: &Size<u32>) -> HeadlessRenderer {
+        // SDL can't make an empty surface.
+        let surface = Surface::new(size.width.max(1), size.height.max(1), PixelFormatEnum::RGBA32).unwrap();
+        let canvas = surface.into_canvas().unwrap();
+        let texture_creator = canvas.texture_creator();
+
+        HeadlessRenderer {
+            canvas,
+            texture_creator
+        }
+    }
+}
+
+impl Renderer for HeadlessRenderer {
+    fn kind(&self) -> RendererKind {
+        RendererKind::Software
+    }
+
+    fn window(&self) -> Option<&Window> {
+        None
+    }
+
+    fn window_mut(&mut self) -> Option<&mut Window> {
+        None
+    }
+
+    fn output_size(&self) -> Size<u32> {
+        let (width, height) = self.canvas.output_size().unwrap();
+        Size { width, height }
+    }
+
+    fn create_texture(&self, format: Option<PixelFormatEnum>, access: TextureAccess, width: u32, height: u32) -> Texture {
+        self.texture_creator.create_texture(format, access, width, height).unwrap()
+    }
+
+    fn create_texture_from_surface(&self, surface: &Surface) -> Texture {
+        self.texture_creator.create_texture_from_surface(surface).unwrap()
+    }
+
+    fn copy(&mut self, target: RenderTarget, texture: &Texture, source: Option<Rect>, destination: Rect, clip: Option<Rect>) {
+        copy(&mut self.canvas, target, texture, source, destination, clip);
+    }
+
+    fn copy_rotated(
+        &mut self,
+        target: RenderTarget,
+        texture: &Texture,
+        source: Option<Rect>,
+        destination: Rect,
+        angle: f64,
+        flip_horizontal: bool,
+        flip_vertical: bool,
+        clip: Option<Rect>
+    ) {
+        copy_rotated(&mut self.canvas, target, texture, source, destination, angle, flip_horizontal, flip_vertical, clip);
+    }
+
+    fn fill_rectangles(&mut self, target: RenderTarget, color: Color, rectangles: &[Rect], blend_mode: BlendMode, clip: Option<Rect>) {
+        fill_rectangles(&mut self.canvas, target, color, rectangles, blend_mode, clip);
+    }
+
+    fn clear(&mut self, target: RenderTarget, color: Color, clip: Option<Rect>) {
+        clear(&mut self.canvas, target, color, clip);
+    }
+
+    fn read_pixels(&mut self, target: RenderTarget) -> Vec<u8> {
+        read_pixels(&mut self.canvas, target)
+    }
+
+    /// There's no window to show, the pixels stay in memory.
+    fn present(&mut self) {}
+}
+
+// The drawing is the same for any SDL canvas, whether it draws into a window
+// or memory.
+
+/// Draws into `target` of `canvas` with `draw`, within `clip`.
+fn draw<T: sdl2::render::RenderTarget>(canvas: &mut Canvas<T>, target: RenderTarget, clip: Option<Rect>, draw: impl FnOnce(&mut Canvas<T>)) {
+    match target {
+        RenderTarget::Window => {
+            canvas.set_clip_rect(clip);
+            draw(canvas);
+            canvas.set_clip_rect(None);
+        },
+        RenderTarget::Texture(texture) => {
+            canvas.with_texture_canvas(texture, |canvas| {
+                canvas.set_clip_rect(clip);
+                draw(canvas);
+            }).unwrap();
+        }
+    }
+}
+
+fn copy<T: sdl2::render::RenderTarget>(canvas: &mut Canvas<T>, target: RenderTarget, texture: &Texture, source: Option<Rect>, destination: Rect, clip: Option<Rect>) {
+    draw(canvas, target, clip, |canvas| {
+        canvas.copy(texture, source, destination).unwrap();
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn copy_rotated<T: sdl2::render::RenderTarget>(
+    canvas: &mut Canvas<T>,
+    target: RenderTarget,
+    texture: &Texture,
+    source: Option<Rect>,
+    destination: Rect,
+    angle: f64,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    clip: Option<Rect>
+) {
+    draw(canvas, target, clip, |canvas| {
+        canvas.copy_ex(texture, source, destination, angle, None, flip_horizontal, flip_vertical).unwrap();
+    });
+}
+
+fn fill_rectangles<T: sdl2::render::RenderTarget>(canvas: &mut Canvas<T>, target: RenderTarget, color: Color, rectangles: &[Rect], blend_mode: BlendMode, clip: Option<Rect>) {
+    draw(canvas, target, clip, |canvas| {
+        canvas.set_blend_mode(blend_mode);
+        canvas.set_draw_color(color);
+        canvas.fill_rects(rectangles).unwrap();
+        canvas.set_blend_mode(BlendMode::Blend);
+    });
+}
+
+fn clear<T: sdl2::render::RenderTarget>(canvas: &mut Canvas<T>, target: RenderTarget, color: Color, clip: Option<Rect>) {
+    draw(canvas, target, clip, |canvas| {
+        canvas.set_draw_color(color);
+
+        // Clearing ignores the clip rectangle, so the clipped part is
+        // filled instead, replacing what's there.
+        if let Some(clip) = clip {
+            canvas.set_blend_mode(BlendMode::None);
+            canvas.fill_rect(clip).unwrap();
+            canvas.set_blend_mode(BlendMode::Blend);
+        } else {
+            canvas.clear();
+        }
+    });
+}
+
+fn read_pixels<T: sdl2::render::RenderTarget>(canvas: &mut Canvas<T>, target: RenderTarget) -> Vec<u8> {
+    let mut pixels = Vec::new();
+    draw(canvas, target, None, |canvas| {
+        pixels = canvas.read_pixels(None, PixelFormatEnum::RGBA32).unwrap();
+    });
+    pixels
+}
@@ -0,0 +1,125 @@
+//! The rows of pixels making up the corners and borders of rounded
+//! rectangles, as drawn without anti-aliasing.
+
+use crate::graphics::{Rectangle, Size};
+
+/// How far in from the left and right edges the rounded rectangle starts on
+/// `row`, for a rectangle `height` pixels tall.
+fn corner_inset(height: u32, radius: f32, row: u32) -> u32 {
+    let y = row as f32 + 0.5;
+    let bottom_corner_start = height as f32 - radius;
+
+    let distance = if y < radius {
+        radius - y
+    } else if y > bottom_corner_start {
+        y - bottom_corner_start
+    } else {
+        return 0;
+    };
+
+    (radius - (radius * radius - distance * distance).max(0.0).sqrt()).round() as u32
+}
+
+/// The radius clamped so that the corners don't overlap.
+fn clamped_radius(size: &Size<u32>, radius: f32) -> f32 {
+    radius.min(size.width.min(size.height) as f32 / 2.0).max(0.0)
+}
+
+/// Adds a one pixel tall rectangle, merged into the rectangle above it if
+/// that's the same width.
+fn push_row(rectangles: &mut Vec<Rectangle<i32, u32>>, x: u32, y: u32, width: u32) {
+    if width == 0 {
+        return;
+    }
+
+    let above = rectangles.iter_mut().find(|rectangle| {
+        rectangle.origin.x == x as i32 && rectangle.size.width == width && rectangle.bottom() == y as i32
+    });
+
+    match above {
+        Some(rectangle) => rectangle.size.height += 1,
+        None => rectangles.push(Rectangle::new(x as i32, y as i32, width, 1))
+    }
+}
+
+/// The areas outside of the rounded corners of a rectangle of `size`.
+pub(crate) fn corner_rectangles(size: &Size<u32>, radius: f32) -> Vec<Rectangle<i32, u32>> {
+    let radius = clamped_radius(size, radius);
+    let mut rectangles = Vec::new();
+
+    for row in 0..size.height {
+        let inset = corner_inset(size.height, radius, row);
+        push_row(&mut rectangles, 0, row, inset);
+        push_row(&mut rectangles, size.width - inset, row, inset);
+    }
+
+    rectangles
+}
+
+/// The area of a border `width` pixels wide along the inside of a rounded
+/// rectangle of `size`.
+pub(crate) fn border_rectangles(size: &Size<u32>, width: u32, radius: f32) -> Vec<Rectangle<i32, u32>> {
+    let radius = clamped_radius(size, radius);
+    let width = width.min(size.width / 2).min(size.height / 2);
+    let inner_height = size.height - width * 2;
+    let inner_radius = (radius - width as f32).max(0.0);
+    let mut rectangles = Vec::new();
+
+    for row in 0..size.height {
+        let outer = corner_inset(size.height, radius, row);
+
+        if row < width || row >= size.height - width {
+            push_row(&mut rectangles, outer, row, size.width - outer * 2);
+            continue;
+        }
+
+        let inner = width + corner_inset(inner_height, inner_radius, row - width);
+        push_row(&mut rectangles, outer, row, inner - outer);
+        push_row(&mut rectangles, size.width - inner, row, inner - outer);
+    }
+
+    rectangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(rectangles: &[Rectangle<i32, u32>]) -> u32 {
+        rectangles.iter().map(|rectangle| rectangle.size.width * rectangle.size.height).sum()
+    }
+
+    #[test]
+    fn test_corner_rectangles() {
+        let size = Size::new(100, 50);
+        assert!(corner_rectangles(&size, 0.0).is_empty());
+
+        // About the area between the corner and a quarter circle, in each of
+        // the four corners.
+        let corners = corner_rectangles(&size, 10.0);
+        let expected = 4.0 * (100.0 - std::f32::consts::PI * 100.0 / 4.0);
+        assert!((area(&corners) as f32 - expected).abs() < 10.0);
+        assert!(corners.iter().all(|rectangle| rectangle.size.width <= 10));
+
+        // The radius is limited to half the shortest side.
+        assert_eq!(corner_rectangles(&size, 100.0), corner_rectangles(&size, 25.0));
+    }
+
+    #[test]
+    fn test_border_rectangles() {
+        let size = Size::new(10, 10);
+
+        // A square border is merged into the top, bottom and two sides.
+        let border = border_rectangles(&size, 1, 0.0);
+        assert_eq!(border, vec![
+            Rectangle::new(0, 0, 10, 1),
+            Rectangle::new(0, 1, 1, 8),
+            Rectangle::new(9, 1, 1, 8),
+            Rectangle::new(0, 9, 10, 1)
+        ]);
+
+        let rounded = border_rectangles(&Size::new(100, 100), 2, 20.0);
+        assert!(area(&rounded) < area(&border_rectangles(&Size::new(100, 100), 2, 0.0)));
+        assert_eq!(area(&border_rectangles(&size, 20, 0.0)), 100);
+    }
+}
@@ -0,0 +1,102 @@
+//! Blurred masks for drawing shadows.
+
+use crate::graphics::rounded_rectangle;
+use crate::graphics::Size;
+
+/// The opacity, from 0 to 255, of each pixel of the shadow of a rounded
+/// rectangle of `size` pixels, blurred by `blur_radius` pixels. The mask is
+/// `blur_radius` larger than the rectangle on each side, so the blur has
+/// room to fade out, and is in rows from the top left.
+pub(crate) fn shadow_mask(size: &Size<u32>, corner_radius: f32, blur_radius: u32) -> Vec<u8> {
+    let width = (size.width + blur_radius * 2) as usize;
+    let height = (size.height + blur_radius * 2) as usize;
+    let padding = blur_radius as usize;
+
+    let mut mask = vec![0.0; width * height];
+    for y in 0..size.height as usize {
+        let row = (y + padding) * width + padding;
+        for value in mask[row..row + size.width as usize].iter_mut() {
+            *value = 255.0;
+        }
+    }
+
+    for corner in rounded_rectangle::corner_rectangles(size, corner_radius) {
+        for y in corner.top()..corner.bottom() {
+            let row = (y as usize + padding) * width + padding;
+            for value in mask[row + corner.left() as usize..row + corner.right() as usize].iter_mut() {
+                *value = 0.0;
+            }
+        }
+    }
+
+    // Three box blurs are close to a gaussian blur, and together spread over
+    // the blur radius.
+    let box_radius = (blur_radius as f32 / 3.0).ceil() as usize;
+    if box_radius > 0 {
+        for _ in 0..3 {
+            box_blur(&mut mask, width, height, box_radius, 1, width);
+            box_blur(&mut mask, height, width, box_radius, width, 1);
+        }
+    }
+
+    mask.into_iter().map(|value: f32| value.round().clamp(0.0, 255.0) as u8).collect()
+}
+
+/// Averages each value with the `radius` values either side of it along
+/// `lines` lines of `length` values. Consecutive values in a line are
+/// `step` apart, and lines start `line_step` apart.
+fn box_blur(values: &mut [f32], length: usize, lines: usize, radius: usize, step: usize, line_step: usize) {
+    let mut line = vec![0.0; length];
+    let window = (radius * 2 + 1) as f32;
+
+    for line_index in 0..lines {
+        let start = line_index * line_step;
+        for (i, value) in line.iter_mut().enumerate() {
+            *value = values[start + i * step];
+        }
+
+        // A running sum over the window, with the values past the ends as 0.
+        let mut sum: f32 = line.iter().take(radius + 1).sum();
+        for i in 0..length {
+            values[start + i * step] = sum / window;
+
+            if i + radius + 1 < length {
+                sum += line[i + radius + 1];
+            }
+            if i >= radius {
+                sum -= line[i - radius];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shadow_mask() {
+        let size = Size::new(20, 10);
+
+        // Without blur, the mask is the rectangle.
+        let mask = shadow_mask(&size, 0.0, 0);
+        assert_eq!(mask.len(), 200);
+        assert!(mask.iter().all(|value| *value == 255));
+
+        let size = Size::new(40, 30);
+        let mask = shadow_mask(&size, 0.0, 6);
+        let width = 52;
+        assert_eq!(mask.len(), 52 * 42);
+
+        // Opaque in the middle, fading out towards the edges.
+        let middle_row = 21 * width;
+        assert_eq!(mask[middle_row + 26], 255);
+        assert!(mask[middle_row + 6] < 255 && mask[middle_row + 6] > 0);
+        assert!(mask[middle_row + 3] < mask[middle_row + 6]);
+        assert_eq!(mask[0], 0);
+
+        // Rounded corners are more transparent.
+        let rounded = shadow_mask(&size, 5.0, 6);
+        assert!(rounded[6 * width + 6] < mask[6 * width + 6]);
+    }
+}
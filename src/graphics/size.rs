@@ -14,6 +14,27 @@ impl<T> Size<T> where T: Number {
     }
 }
 
+impl Size<u32> {
+    /// The same size in floats, without losing anything.
+    pub fn to_f32(&self) -> Size<f32> {
+        Size::new(self.width as f32, self.height as f32)
+    }
+}
+
+impl Size<f32> {
+    /// The smallest size of whole points at least as big, so nothing in it
+    /// is cut off. Negative sizes become empty.
+    pub fn rounded_up(&self) -> Size<u32> {
+        Size::new(self.width.ceil().max(0.0) as u32, self.height.ceil().max(0.0) as u32)
+    }
+}
+
+impl From<&Size<u32>> for Size<f32> {
+    fn from(size: &Size<u32>) -> Size<f32> {
+        size.to_f32()
+    }
+}
+
 impl<T> Clone for Size<T> where T: Copy {
     fn clone(&self) -> Self {
         Size {
@@ -78,4 +99,12 @@ mod tests {
 
         assert_eq!(format!("{:?}", size), "Size(1, 2)");
     }
+
+    #[test]
+    fn test_conversions() {
+        let size: Size<u32> = Size::new(3, 4);
+        assert_eq!(size.to_f32(), Size::new(3.0, 4.0));
+        assert_eq!(Size::from(&size).rounded_up(), size);
+        assert_eq!(Size::new(2.1, -1.0).rounded_up(), Size::new(3, 0));
+    }
 }
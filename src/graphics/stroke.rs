@@ -0,0 +1,431 @@
+//! Outlining paths: how wide lines are, how they end and meet, and their
+//! dash pattern.
+//!
+//! A stroke is drawn by turning each line into polygons covering it, which
+//! are then filled like any other path. The polygons all go the same way
+//! round, so where they overlap, at joins, they fill each other rather than
+//! cutting holes.
+
+use crate::graphics::path::{distance, Polyline};
+use std::f32::consts::PI;
+
+/// The shape of the ends of open subpaths, and of dashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends square, exactly at the end of the line.
+    Butt,
+
+    /// Ends with a half circle around the end of the line.
+    Round,
+
+    /// Ends square, half the line width past the end of the line.
+    Square
+}
+
+/// The shape of the outside of corners, where lines meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    /// The edges extended to a point, or beveled if that's further than the
+    /// miter limit allows.
+    Miter,
+
+    /// Rounded around the corner.
+    Round,
+
+    /// Cut off straight across the corner.
+    Bevel
+}
+
+/// How a path is outlined by `Layer::stroke_path`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    /// How wide the line is, centered on the path, in points.
+    pub line_width: f32,
+
+    pub line_cap: LineCap,
+
+    pub line_join: LineJoin,
+
+    /// How far, as a multiple of the line width, a mitered corner can
+    /// reach before it's beveled instead. Sharp corners reach furthest.
+    pub miter_limit: f32,
+
+    /// Lengths of alternating dashes and gaps, starting with a dash, in
+    /// points. Empty for a solid line.
+    pub dashes: Vec<f32>,
+
+    /// How far into the dash pattern the line starts.
+    pub dash_phase: f32
+}
+
+impl StrokeStyle {
+    /// A solid line of `line_width`, with butt caps and mitered corners.
+    pub fn new(line_width: f32) -> StrokeStyle {
+        StrokeStyle {
+            line_width,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 10.0,
+            dashes: Vec::new(),
+            dash_phase: 0.0
+        }
+    }
+
+    /// The style, with `line_cap` at the ends of lines.
+    pub fn with_line_cap(mut self, line_cap: LineCap) -> StrokeStyle {
+        self.line_cap = line_cap;
+        self
+    }
+
+    /// The style, with `line_join` at corners.
+    pub fn with_line_join(mut self, line_join: LineJoin) -> StrokeStyle {
+        self.line_join = line_join;
+        self
+    }
+
+    /// The style, dashed with alternating dashes and gaps of `dashes`,
+    /// starting `dash_phase` into the pattern.
+    pub fn with_dashes(mut self, dashes: &[f32], dash_phase: f32) -> StrokeStyle {
+        self.dashes = dashes.to_vec();
+        self.dash_phase = dash_phase;
+        self
+    }
+
+    /// The style, with everything in points multiplied by `scale`, such as
+    /// into pixels.
+    pub(crate) fn scaled(&self, scale: f32) -> StrokeStyle {
+        StrokeStyle {
+            line_width: self.line_width * scale,
+            dashes: self.dashes.iter().map(|length| length * scale).collect(),
+            dash_phase: self.dash_phase * scale,
+            ..self.clone()
+        }
+    }
+
+    fn is_dashed(&self) -> bool {
+        !self.dashes.is_empty()
+            && self.dashes.iter().all(|length| *length >= 0.0)
+            && self.dashes.iter().sum::<f32>() > 0.0
+    }
+}
+
+impl Default for StrokeStyle {
+    fn default() -> StrokeStyle {
+        StrokeStyle::new(1.0)
+    }
+}
+
+/// Polygons covering `polylines` stroked with `style`, all going the same
+/// way round, for filling with `FillRule::NonZero`. Curves of round caps
+/// and joins are no further than `tolerance` from circles.
+pub(crate) fn stroke_polygons(polylines: &[Polyline], style: &StrokeStyle, tolerance: f32) -> Vec<Polyline> {
+    let half_width = style.line_width / 2.0;
+    if half_width <= 0.0 {
+        return Vec::new();
+    }
+
+    let polylines = if style.is_dashed() {
+        dashed(polylines, &style.dashes, style.dash_phase)
+    } else {
+        polylines.to_vec()
+    };
+
+    let mut polygons = Vec::new();
+    for polyline in polylines {
+        let points = without_repeats(&polyline.points, polyline.closed);
+        stroke_polyline(&points, polyline.closed, style, half_width, tolerance, &mut polygons);
+    }
+
+    polygons.into_iter().map(clockwise).collect()
+}
+
+fn stroke_polyline(points: &[(f32, f32)], closed: bool, style: &StrokeStyle, half_width: f32, tolerance: f32, polygons: &mut Vec<Polyline>) {
+    if points.len() == 1 {
+        // A dot, only seen if the caps stick out.
+        let point = points[0];
+        match style.line_cap {
+            LineCap::Butt => {},
+            LineCap::Round => polygons.push(circle(point, half_width, tolerance)),
+            LineCap::Square => polygons.push(polygon(vec![
+                (point.0 - half_width, point.1 - half_width),
+                (point.0 + half_width, point.1 - half_width),
+                (point.0 + half_width, point.1 + half_width),
+                (point.0 - half_width, point.1 + half_width)
+            ]))
+        }
+        return;
+    }
+
+    let segment_count = if closed { points.len() } else { points.len() - 1 };
+    let segment = |index: usize| (points[index % points.len()], points[(index + 1) % points.len()]);
+
+    for index in 0..segment_count {
+        let (from, to) = segment(index);
+        let normal = scaled(normal(from, to), half_width);
+        polygons.push(polygon(vec![
+            add(from, normal),
+            add(to, normal),
+            subtract(to, normal),
+            subtract(from, normal)
+        ]));
+    }
+
+    let joins = if closed { 0..segment_count } else { 1..segment_count };
+    for index in joins {
+        let (previous_from, corner) = segment((index + segment_count - 1) % segment_count);
+        let (_, next_to) = segment(index);
+        join(previous_from, corner, next_to, style, half_width, tolerance, polygons);
+    }
+
+    if !closed {
+        cap(points[1], points[0], style.line_cap, half_width, tolerance, polygons);
+        cap(points[points.len() - 2], points[points.len() - 1], style.line_cap, half_width, tolerance, polygons);
+    }
+}
+
+/// Fills the outside of the corner at `corner`, between the line from
+/// `from` and the line on to `to`.
+fn join(from: (f32, f32), corner: (f32, f32), to: (f32, f32), style: &StrokeStyle, half_width: f32, tolerance: f32, polygons: &mut Vec<Polyline>) {
+    let incoming = subtract(corner, from);
+    let outgoing = subtract(to, corner);
+    let turn = incoming.0 * outgoing.1 - incoming.1 * outgoing.0;
+    if turn == 0.0 && dot(incoming, outgoing) > 0.0 {
+        return;
+    }
+
+    if style.line_join == LineJoin::Round {
+        polygons.push(circle(corner, half_width, tolerance));
+        return;
+    }
+
+    // The outside of the corner is on the opposite side to the turn.
+    let side = if turn > 0.0 { -1.0 } else { 1.0 };
+    let incoming_normal = scaled(normal(from, corner), side);
+    let outgoing_normal = scaled(normal(corner, to), side);
+    let incoming_edge = add(corner, scaled(incoming_normal, half_width));
+    let outgoing_edge = add(corner, scaled(outgoing_normal, half_width));
+
+    let bisector = add(incoming_normal, outgoing_normal);
+    let bisector_length = distance(bisector);
+    // How far the miter's point is from the corner, as a multiple of half
+    // the line width. That's also the length of the miter across the
+    // line, from the inside corner to the point, in line widths.
+    let miter_ratio = if bisector_length > 0.0 { 2.0 / bisector_length } else { f32::INFINITY };
+
+    if style.line_join == LineJoin::Miter && miter_ratio <= style.miter_limit {
+        let tip = add(corner, scaled(bisector, half_width * miter_ratio / bisector_length));
+        polygons.push(polygon(vec![corner, incoming_edge, tip, outgoing_edge]));
+    } else {
+        polygons.push(polygon(vec![corner, incoming_edge, outgoing_edge]));
+    }
+}
+
+/// Adds the cap at `end`, the end of the line coming from `from`.
+fn cap(from: (f32, f32), end: (f32, f32), line_cap: LineCap, half_width: f32, tolerance: f32, polygons: &mut Vec<Polyline>) {
+    match line_cap {
+        LineCap::Butt => {},
+        LineCap::Round => polygons.push(circle(end, half_width, tolerance)),
+        LineCap::Square => {
+            let normal = scaled(normal(from, end), half_width);
+            let direction = subtract(end, from);
+            let extension = scaled(direction, half_width / distance(direction));
+            let beyond = add(end, extension);
+            polygons.push(polygon(vec![
+                add(end, normal),
+                add(beyond, normal),
+                subtract(beyond, normal),
+                subtract(end, normal)
+            ]));
+        }
+    }
+}
+
+/// The pieces of `polylines` that are dashes, with dashes and gaps of
+/// `dashes`, starting `phase` into the pattern.
+fn dashed(polylines: &[Polyline], dashes: &[f32], phase: f32) -> Vec<Polyline> {
+    let pattern_length: f32 = dashes.iter().sum();
+    let mut pieces = Vec::new();
+
+    for polyline in polylines {
+        let mut points = polyline.points.clone();
+        if polyline.closed {
+            points.push(points[0]);
+        }
+
+        // Where in the pattern the start of the line is.
+        let mut offset = phase.rem_euclid(pattern_length);
+        let mut dash_index = 0;
+        while offset >= dashes[dash_index] {
+            offset -= dashes[dash_index];
+            dash_index = (dash_index + 1) % dashes.len();
+        }
+        let mut remaining = dashes[dash_index] - offset;
+        let mut current: Option<Vec<(f32, f32)>> = if dash_index % 2 == 0 { Some(vec![points[0]]) } else { None };
+
+        for pair in points.windows(2) {
+            let (mut from, to) = (pair[0], pair[1]);
+            let mut length = distance(subtract(to, from));
+
+            while length > remaining {
+                let point = add(from, scaled(subtract(to, from), remaining / length));
+                match current.take() {
+                    Some(mut dash) => {
+                        if dash.last() != Some(&point) {
+                            dash.push(point);
+                        }
+                        pieces.push(Polyline { points: dash, closed: false });
+                    },
+                    None => current = Some(vec![point])
+                }
+
+                length -= remaining;
+                from = point;
+                dash_index = (dash_index + 1) % dashes.len();
+                remaining = dashes[dash_index];
+            }
+
+            remaining -= length;
+            if let Some(dash) = current.as_mut() {
+                dash.push(to);
+            }
+        }
+
+        if let Some(dash) = current {
+            pieces.push(Polyline { points: dash, closed: false });
+        }
+    }
+
+    pieces
+}
+
+/// `points`, without any point the same as the one before.
+fn without_repeats(points: &[(f32, f32)], closed: bool) -> Vec<(f32, f32)> {
+    let mut unique: Vec<(f32, f32)> = Vec::with_capacity(points.len());
+    for point in points {
+        if unique.last() != Some(point) {
+            unique.push(*point);
+        }
+    }
+    if closed && unique.len() > 1 && unique.first() == unique.last() {
+        unique.pop();
+    }
+    unique
+}
+
+fn circle(center: (f32, f32), radius: f32, tolerance: f32) -> Polyline {
+    // Enough sides for each to be within the tolerance of the circle.
+    let step = 2.0 * (1.0 - (tolerance / radius).min(1.0)).acos();
+    let sides = ((2.0 * PI / step).ceil() as usize).clamp(8, 256);
+
+    polygon((0..sides).map(|side| {
+        let angle = side as f32 / sides as f32 * 2.0 * PI;
+        (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+    }).collect())
+}
+
+fn polygon(points: Vec<(f32, f32)>) -> Polyline {
+    Polyline { points, closed: true }
+}
+
+/// `polygon`, reversed if needed to go clockwise on screen.
+fn clockwise(mut polygon: Polyline) -> Polyline {
+    let points = &polygon.points;
+    let area: f32 = (0..points.len())
+        .map(|index| {
+            let (a, b) = (points[index], points[(index + 1) % points.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    if area < 0.0 {
+        polygon.points.reverse();
+    }
+    polygon
+}
+
+/// The unit vector at right angles to the line from `from` to `to`.
+fn normal(from: (f32, f32), to: (f32, f32)) -> (f32, f32) {
+    let direction = subtract(to, from);
+    let length = distance(direction);
+    (-direction.1 / length, direction.0 / length)
+}
+
+fn add(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+fn subtract(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn scaled(vector: (f32, f32), scale: f32) -> (f32, f32) {
+    (vector.0 * scale, vector.1 * scale)
+}
+
+fn dot(a: (f32, f32), b: (f32, f32)) -> f32 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::rasterizer::{self, FillRule};
+
+    fn line(points: &[(f32, f32)]) -> Vec<Polyline> {
+        vec![Polyline { points: points.to_vec(), closed: false }]
+    }
+
+    fn stroked_mask(polylines: &[Polyline], style: &StrokeStyle, size: u32) -> Vec<u8> {
+        let polygons = stroke_polygons(polylines, style, 0.1);
+        rasterizer::coverage_mask(&polygons, (0, 0), size, size, FillRule::NonZero)
+    }
+
+    #[test]
+    fn test_line_caps() {
+        let horizontal = line(&[(2.0, 5.0), (8.0, 5.0)]);
+
+        let butt = stroked_mask(&horizontal, &StrokeStyle::new(2.0), 10);
+        assert_eq!(&butt[4 * 10..5 * 10], &[0, 0, 255, 255, 255, 255, 255, 255, 0, 0]);
+        assert_eq!(&butt[5 * 10..6 * 10], &[0, 0, 255, 255, 255, 255, 255, 255, 0, 0]);
+        assert!(butt[3 * 10..4 * 10].iter().all(|value| *value == 0));
+
+        let square = stroked_mask(&horizontal, &StrokeStyle::new(2.0).with_line_cap(LineCap::Square), 10);
+        assert_eq!(&square[4 * 10..5 * 10], &[0, 255, 255, 255, 255, 255, 255, 255, 255, 0]);
+
+        let round = stroked_mask(&horizontal, &StrokeStyle::new(2.0).with_line_cap(LineCap::Round), 10);
+        assert!(round[4 * 10 + 1] > 0 && round[4 * 10 + 1] < 255);
+    }
+
+    #[test]
+    fn test_line_joins() {
+        let corner = line(&[(2.0, 10.0), (10.0, 10.0), (10.0, 18.0)]);
+
+        // The outside of the corner is at the top right, beyond both lines.
+        let miter = stroked_mask(&corner, &StrokeStyle::new(4.0), 20);
+        assert_eq!(miter[8 * 20 + 11], 255);
+
+        let bevel = stroked_mask(&corner, &StrokeStyle::new(4.0).with_line_join(LineJoin::Bevel), 20);
+        assert_eq!(bevel[8 * 20 + 11], 0);
+        assert_eq!(bevel[9 * 20 + 10], 255);
+
+        // Too sharp for the miter limit, so beveled.
+        let limited = stroked_mask(&corner, &StrokeStyle { miter_limit: 1.0, ..StrokeStyle::new(4.0) }, 20);
+        assert_eq!(limited[8 * 20 + 11], 0);
+
+        let round = stroked_mask(&corner, &StrokeStyle::new(4.0).with_line_join(LineJoin::Round), 20);
+        assert!(round[8 * 20 + 11] > 0);
+    }
+
+    #[test]
+    fn test_dashes() {
+        let pieces = dashed(&line(&[(0.0, 0.0), (10.0, 0.0)]), &[3.0, 2.0], 0.0);
+        let spans: Vec<(f32, f32)> = pieces.iter().map(|piece| (piece.points[0].0, piece.points.last().unwrap().0)).collect();
+        assert_eq!(spans, vec![(0.0, 3.0), (5.0, 8.0)]);
+
+        // Starting partway through the pattern, and carrying on round
+        // corners.
+        let pieces = dashed(&line(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)]), &[3.0, 2.0], 4.0);
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].points, vec![(1.0, 0.0), (4.0, 0.0)]);
+        assert_eq!(pieces[1].points, vec![(4.0, 2.0), (4.0, 4.0)]);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::graphics::Point;
+
+/// A 2D affine transform, such as a rotation, scale or translation.
+///
+/// A point (x, y) is transformed to
+/// (`a * x + c * y + tx`, `b * x + d * y + ty`). Angles are in radians, and
+/// since y points down, positive angles rotate clockwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32
+}
+
+impl Transform {
+    /// The transform that leaves points where they are.
+    pub fn identity() -> Transform {
+        Transform::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn new(a: f32, b: f32, c: f32, d: f32, tx: f32, ty: f32) -> Transform {
+        Transform { a, b, c, d, tx, ty }
+    }
+
+    pub fn translation(tx: f32, ty: f32) -> Transform {
+        Transform::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    /// Scales by `sx` horizontally and `sy` vertically. Negative scales
+    /// flip.
+    pub fn scale(sx: f32, sy: f32) -> Transform {
+        Transform::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    /// Rotates clockwise by `angle` radians.
+    pub fn rotation(angle: f32) -> Transform {
+        let (sin, cos) = angle.sin_cos();
+        Transform::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// The transform that applies this transform and then `other`.
+    pub fn concat(&self, other: &Transform) -> Transform {
+        Transform {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            tx: self.tx * other.a + self.ty * other.c + other.tx,
+            ty: self.tx * other.b + self.ty * other.d + other.ty
+        }
+    }
+
+    /// This transform followed by a translation.
+    pub fn translated(&self, tx: f32, ty: f32) -> Transform {
+        self.concat(&Transform::translation(tx, ty))
+    }
+
+    /// This transform followed by a scale.
+    pub fn scaled(&self, sx: f32, sy: f32) -> Transform {
+        self.concat(&Transform::scale(sx, sy))
+    }
+
+    /// This transform followed by a rotation.
+    pub fn rotated(&self, angle: f32) -> Transform {
+        self.concat(&Transform::rotation(angle))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::identity()
+    }
+
+    /// The transform that undoes this one, or `None` if this transform
+    /// collapses points onto a line, as a scale of 0 does.
+    pub fn inverted(&self) -> Option<Transform> {
+        let determinant = self.a * self.d - self.b * self.c;
+        if determinant.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let a = self.d / determinant;
+        let b = -self.b / determinant;
+        let c = -self.c / determinant;
+        let d = self.a / determinant;
+
+        Some(Transform {
+            a,
+            b,
+            c,
+            d,
+            tx: -(self.tx * a + self.ty * c),
+            ty: -(self.tx * b + self.ty * d)
+        })
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y + self.tx, self.b * x + self.d * y + self.ty)
+    }
+
+    /// Transforms `point`, rounding to the nearest whole point.
+    pub fn apply_to_point(&self, point: &Point<i32>) -> Point<i32> {
+        let (x, y) = self.apply(point.x as f32, point.y as f32);
+        Point::new(x.round() as i32, y.round() as i32)
+    }
+
+    /// Splits the transform into a scale, followed by a rotation and then the
+    /// translation: `(sx, sy, angle)`. Skewing can't be represented, and is
+    /// lost.
+    pub fn decompose(&self) -> (f32, f32, f32) {
+        let sx = (self.a * self.a + self.b * self.b).sqrt();
+        if sx == 0.0 {
+            return (0.0, (self.c * self.c + self.d * self.d).sqrt(), 0.0);
+        }
+
+        let angle = self.b.atan2(self.a);
+        let sy = (self.a * self.d - self.b * self.c) / sx;
+        (sx, sy, angle)
+    }
+
+    /// The transform that scales by `sx` and `sy`, rotates by `angle` and
+    /// then translates by `tx` and `ty`. The reverse of `decompose`.
+    pub fn compose(sx: f32, sy: f32, angle: f32, tx: f32, ty: f32) -> Transform {
+        Transform::scale(sx, sy).rotated(angle).translated(tx, ty)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_close(transform: &Transform, expected: &Transform) {
+        let values = [
+            (transform.a, expected.a),
+            (transform.b, expected.b),
+            (transform.c, expected.c),
+            (transform.d, expected.d),
+            (transform.tx, expected.tx),
+            (transform.ty, expected.ty)
+        ];
+        for (value, expected_value) in values.iter() {
+            assert!((value - expected_value).abs() < 0.0001, "{:?} isn't close to {:?}", transform, expected);
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let point = Point::new(10, 0);
+
+        assert_eq!(Transform::identity().apply_to_point(&point), point);
+        assert_eq!(Transform::translation(5.0, -5.0).apply_to_point(&point), Point::new(15, -5));
+        assert_eq!(Transform::scale(2.0, 3.0).apply_to_point(&point), Point::new(20, 0));
+
+        // Clockwise, as y points down.
+        assert_eq!(Transform::rotation(FRAC_PI_2).apply_to_point(&point), Point::new(0, 10));
+
+        let transform = Transform::scale(2.0, 2.0).rotated(FRAC_PI_2).translated(1.0, 1.0);
+        assert_eq!(transform.apply_to_point(&point), Point::new(1, 21));
+    }
+
+    #[test]
+    fn test_inverted() {
+        let transform = Transform::scale(2.0, 0.5).rotated(0.3).translated(7.0, -3.0);
+        let inverted = transform.inverted().unwrap();
+
+        assert_close(&transform.concat(&inverted), &Transform::identity());
+        assert_close(&inverted.concat(&transform), &Transform::identity());
+        assert_eq!(Transform::scale(0.0, 1.0).inverted(), None);
+    }
+
+    #[test]
+    fn test_decompose() {
+        let transform = Transform::compose(2.0, -0.5, 1.2, 7.0, -3.0);
+        let (sx, sy, angle) = transform.decompose();
+
+        assert!((sx - 2.0).abs() < 0.0001);
+        assert!((sy + 0.5).abs() < 0.0001);
+        assert!((angle - 1.2).abs() < 0.0001);
+        assert_close(&Transform::compose(sx, sy, angle, transform.tx, transform.ty), &transform);
+    }
+}
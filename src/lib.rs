@@ -4,3 +4,4 @@ pub mod ui;
 pub mod text;
 pub mod macros;
 pub mod platform;
+pub mod testing;
@@ -37,6 +37,8 @@ macro_rules! custom_view {
         impl $view {
             #![allow(dead_code)]
 
+            // One argument per behavior field, so it can grow with the view.
+            #[allow(clippy::too_many_arguments)]
             pub(crate) fn new_all(frame: $crate::graphics::Rectangle<i32, u32>, $($key: $value),*) -> Self {
                 let super_behavior = $super {
                     view: $crate::ui::WeakView::none()
@@ -108,8 +110,20 @@ macro_rules! custom_view {
     };
 }
 
+/// Enters a `tracing` span at the trace level until the end of the
+/// enclosing block, when the `trace` feature is enabled, such as
+/// `trace_span!("draw_view", view = %name)`. Without the feature, it does
+/// nothing and its arguments aren't evaluated.
+macro_rules! trace_span {
+    ($($arguments:tt)*) => {
+        #[cfg(feature = "trace")]
+        let _span = tracing::trace_span!($($arguments)*).entered();
+    };
+}
+
 pub use singleton;
 pub use custom_view;
+pub(crate) use trace_span;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,81 @@
+//! Connects windows to screen readers through AccessKit, with the
+//! `accesskit` feature. See `Window::accessibility_tree`.
+//!
+//! Only Linux screen readers, such as Orca, are reached so far, over
+//! AT-SPI. There's no adapter for other platforms yet.
+
+use crate::graphics::Rectangle;
+use crate::ui::accessibility::AccessibilityAction;
+use crate::ui::application::Application;
+use crate::ui::run_loop::{MainThreadProxy, RunLoop};
+
+/// A window's connection to the platform's screen readers, which it sends
+/// its accessibility tree to as it changes.
+pub(crate) struct Adapter {
+    adapter: accesskit_unix::Adapter
+}
+
+impl Adapter {
+    /// Connects the window with the context id `window_id`, starting with
+    /// the tree `tree`. Returns `None` if there's nothing to connect to,
+    /// such as when no screen reader has ever run.
+    pub(crate) fn new(window_id: u32, tree: accesskit::TreeUpdate, is_focused: bool) -> Option<Adapter> {
+        let action_handler = ActionHandler {
+            window_id,
+            proxy: RunLoop::main_thread_proxy()
+        };
+
+        let adapter = accesskit_unix::Adapter::new(move || tree, is_focused, Box::new(action_handler))?;
+        Some(Adapter { adapter })
+    }
+
+    /// Sends the changes to the window's tree.
+    pub(crate) fn update(&self, tree: accesskit::TreeUpdate) {
+        self.adapter.update(tree);
+    }
+
+    /// Tells screen readers the window gained or lost focus.
+    pub(crate) fn set_focused(&self, is_focused: bool) {
+        self.adapter.update_window_focus_state(is_focused);
+    }
+
+    /// Tells screen readers where the window is on screen, in pixels: its
+    /// `outer` frame, with its title bar and borders, and its `inner`
+    /// frame, which the tree's bounds are relative to.
+    pub(crate) fn set_screen_frames(&self, outer: &Rectangle<i32, u32>, inner: &Rectangle<i32, u32>) {
+        self.adapter.set_root_window_bounds(accesskit_rect(outer), accesskit_rect(inner));
+    }
+}
+
+fn accesskit_rect(frame: &Rectangle<i32, u32>) -> accesskit::Rect {
+    accesskit::Rect::new(frame.left() as f64, frame.top() as f64, frame.right() as f64, frame.bottom() as f64)
+}
+
+/// Performs what screen readers ask of a window's views. Requests come from
+/// AccessKit's own thread, so they're posted to the main thread.
+struct ActionHandler {
+    window_id: u32,
+    proxy: MainThreadProxy
+}
+
+impl accesskit::ActionHandler for ActionHandler {
+    fn do_action(&mut self, request: accesskit::ActionRequest) {
+        let (view_id, action) = match AccessibilityAction::from_accesskit(request) {
+            Some(action) => action,
+            None => return
+        };
+
+        let window_id = self.window_id;
+        self.proxy.post(move || perform_action(window_id, view_id, &action));
+    }
+}
+
+/// Performs `action` on the view with `view_id`, if it's still in the
+/// window with `window_id`.
+fn perform_action(window_id: u32, view_id: usize, action: &AccessibilityAction) {
+    let window = Application::borrow().get_window(window_id).cloned();
+    let view = window.and_then(|window| window.view.view_with_id(view_id));
+    if let Some(view) = view {
+        view.perform_accessibility_action(action);
+    }
+}
@@ -0,0 +1,104 @@
+use sdl2::mouse::SystemCursor;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// The shape of the pointer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Cursor {
+    Arrow,
+
+    /// For selecting text.
+    IBeam,
+
+    /// For links, and other things to click.
+    PointingHand,
+
+    Crosshair,
+
+    /// For something that can't be clicked or dropped on.
+    NotAllowed,
+
+    /// For resizing left and right.
+    ResizeHorizontal,
+
+    /// For resizing up and down.
+    ResizeVertical,
+
+    /// For resizing from the top left or bottom right corner.
+    ResizeDiagonal,
+
+    /// For resizing from the top right or bottom left corner.
+    ResizeAntiDiagonal,
+
+    /// No pointer is shown, such as while typing.
+    Hidden
+}
+
+impl Cursor {
+    fn system_cursor(&self) -> Option<SystemCursor> {
+        match self {
+            Cursor::Arrow => Some(SystemCursor::Arrow),
+            Cursor::IBeam => Some(SystemCursor::IBeam),
+            Cursor::PointingHand => Some(SystemCursor::Hand),
+            Cursor::Crosshair => Some(SystemCursor::Crosshair),
+            Cursor::NotAllowed => Some(SystemCursor::No),
+            Cursor::ResizeHorizontal => Some(SystemCursor::SizeWE),
+            Cursor::ResizeVertical => Some(SystemCursor::SizeNS),
+            Cursor::ResizeDiagonal => Some(SystemCursor::SizeNWSE),
+            Cursor::ResizeAntiDiagonal => Some(SystemCursor::SizeNESW),
+            Cursor::Hidden => None
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Cursor> = const { Cell::new(Cursor::Arrow) };
+
+    // SDL only keeps a pointer to the cursor set, so each is kept for as
+    // long as the application runs.
+    static SYSTEM_CURSORS: RefCell<HashMap<Cursor, sdl2::mouse::Cursor>> = RefCell::new(HashMap::new());
+}
+
+/// Changes the shape of the pointer, or hides it with `Cursor::Hidden`.
+///
+/// While the pointer is over a window, the event loop sets the cursor that
+/// the view under it prefers as it moves, see `View::set_cursor`; this is
+/// for changing it in between, such as hiding it while typing.
+pub fn set(cursor: Cursor) {
+    let previous = CURRENT.with(|current| current.replace(cursor));
+    if previous == cursor {
+        return;
+    }
+
+    let system_cursor = match cursor.system_cursor() {
+        Some(system_cursor) => system_cursor,
+        None => {
+            unsafe { sdl2::sys::SDL_ShowCursor(sdl2::sys::SDL_DISABLE as i32) };
+            return;
+        }
+    };
+
+    if previous == Cursor::Hidden {
+        unsafe { sdl2::sys::SDL_ShowCursor(sdl2::sys::SDL_ENABLE as i32) };
+    }
+
+    SYSTEM_CURSORS.with(|system_cursors| {
+        let mut system_cursors = system_cursors.borrow_mut();
+        if let std::collections::hash_map::Entry::Vacant(entry) = system_cursors.entry(cursor) {
+            match sdl2::mouse::Cursor::from_system(system_cursor) {
+                Ok(sdl_cursor) => { entry.insert(sdl_cursor); },
+                Err(error) => {
+                    println!("Warning: couldn't create the {:?} cursor: {}", cursor, error);
+                    return;
+                }
+            }
+        }
+
+        system_cursors[&cursor].set();
+    });
+}
+
+/// The shape of the pointer, as last set.
+pub fn current() -> Cursor {
+    CURRENT.with(|current| current.get())
+}
@@ -9,9 +9,65 @@ pub trait Action {
     fn merge(&self, _other: &Box<dyn Action>) -> Option<Box<dyn Action>> {
         None
     }
+    /// Whether the action is a small continuous edit, such as typing a single
+    /// character, that an `UndoManager` may coalesce with the previous action
+    /// (using `merge`) so they're undone together.
+    fn can_coalesce(&self) -> bool {
+        false
+    }
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
+/// Several actions that are undone and redone together, in one step.
+pub struct ActionGroup {
+    name: String,
+    actions: Vec<Box<dyn Action>>
+}
+
+impl ActionGroup {
+    pub fn new(name: &str) -> ActionGroup {
+        ActionGroup {
+            name: name.to_string(),
+            actions: Vec::new()
+        }
+    }
+
+    /// The actions in the group, in the order they were performed.
+    pub fn actions(&self) -> &Vec<Box<dyn Action>> {
+        &self.actions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    fn push(&mut self, action: Box<dyn Action>) {
+        self.actions.push(action);
+    }
+}
+
+impl Action for ActionGroup {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn forward(&mut self) {
+        for action in self.actions.iter_mut() {
+            action.forward();
+        }
+    }
+
+    fn backward(&mut self) {
+        for action in self.actions.iter_mut().rev() {
+            action.backward();
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
 /// A history of actions which can be undone/redone.
 ///
 /// E.g. a `pelican::ui::TextField` would create a `History` for itself.
@@ -66,6 +122,172 @@ impl History {
     }
 }
 
+/// Manages undoing and redoing actions, with a stack of actions to undo and
+/// another of actions to redo.
+///
+/// Actions performed between `begin_group` and `end_group` are undone in one
+/// step. Consecutive actions that `can_coalesce`, such as typing one
+/// character at a time, are merged into one step until `break_coalescing` is
+/// called or a different action is pushed.
+///
+/// E.g. a `pelican::ui::TextField` would create an `UndoManager` for itself.
+pub struct UndoManager {
+    undo_stack: Vec<Box<dyn Action>>,
+    redo_stack: Vec<Box<dyn Action>>,
+
+    // Groups that have begun but not ended, innermost last.
+    open_groups: Vec<ActionGroup>,
+
+    // Whether the next action may be coalesced with the top of the undo
+    // stack.
+    coalescing: bool,
+
+    levels_of_undo: usize
+}
+
+impl UndoManager {
+    /// Create a new undo manager with no limit on the number of steps that
+    /// can be undone.
+    pub fn new() -> UndoManager {
+        UndoManager {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            open_groups: Vec::new(),
+            coalescing: false,
+            levels_of_undo: 0
+        }
+    }
+
+    /// The maximum number of steps that can be undone, or 0 for no limit.
+    pub fn levels_of_undo(&self) -> usize {
+        self.levels_of_undo
+    }
+
+    /// Sets the maximum number of steps that can be undone, or 0 for no
+    /// limit. The oldest steps are discarded when the limit is exceeded.
+    pub fn set_levels_of_undo(&mut self, levels_of_undo: usize) {
+        self.levels_of_undo = levels_of_undo;
+        self.enforce_levels_of_undo();
+    }
+
+    fn enforce_levels_of_undo(&mut self) {
+        if self.levels_of_undo > 0 && self.undo_stack.len() > self.levels_of_undo {
+            let excess = self.undo_stack.len() - self.levels_of_undo;
+            self.undo_stack.drain(0..excess);
+        }
+    }
+
+    /// Adds an action that has already been performed. This clears the redo
+    /// stack.
+    pub fn push(&mut self, action: Box<dyn Action>) {
+        self.redo_stack.clear();
+
+        if let Some(group) = self.open_groups.last_mut() {
+            group.push(action);
+            return;
+        }
+
+        let can_coalesce = action.can_coalesce();
+        if self.coalescing && can_coalesce {
+            if let Some(last) = self.undo_stack.last() {
+                if let Some(merged) = last.merge(&action) {
+                    self.undo_stack.pop();
+                    self.undo_stack.push(merged);
+                    return;
+                }
+            }
+        }
+
+        self.undo_stack.push(action);
+        self.coalescing = can_coalesce;
+        self.enforce_levels_of_undo();
+    }
+
+    /// Begins a group; every action pushed until the matching `end_group`
+    /// is undone in one step. Groups may be nested.
+    pub fn begin_group(&mut self, name: &str) {
+        self.open_groups.push(ActionGroup::new(name));
+    }
+
+    /// Ends the innermost group. Empty groups are discarded.
+    pub fn end_group(&mut self) {
+        if let Some(group) = self.open_groups.pop() {
+            if !group.is_empty() {
+                self.coalescing = false;
+                self.push(Box::new(group));
+                self.coalescing = false;
+            }
+        }
+    }
+
+    /// The number of groups that have begun but not ended.
+    pub fn grouping_level(&self) -> usize {
+        self.open_groups.len()
+    }
+
+    /// Stops the next action from being coalesced with the last, e.g. when
+    /// the carat moves between typing.
+    pub fn break_coalescing(&mut self) {
+        self.coalescing = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// The name of the action that `undo` would undo.
+    pub fn undo_action_name(&self) -> Option<&str> {
+        self.undo_stack.last().map(|action| action.name())
+    }
+
+    /// The name of the action that `redo` would redo.
+    pub fn redo_action_name(&self) -> Option<&str> {
+        self.redo_stack.last().map(|action| action.name())
+    }
+
+    /// Undo the last step. Any open groups are ended first.
+    pub fn undo(&mut self) {
+        while !self.open_groups.is_empty() {
+            self.end_group();
+        }
+
+        if let Some(mut action) = self.undo_stack.pop() {
+            action.backward();
+            self.redo_stack.push(action);
+        }
+
+        self.coalescing = false;
+    }
+
+    /// Redo the last undone step.
+    pub fn redo(&mut self) {
+        if let Some(mut action) = self.redo_stack.pop() {
+            action.forward();
+            self.undo_stack.push(action);
+        }
+
+        self.coalescing = false;
+    }
+
+    /// Removes every action from both stacks.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.open_groups.clear();
+        self.coalescing = false;
+    }
+}
+
+impl Default for UndoManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,6 +339,10 @@ mod tests {
             self
         }
 
+        fn can_coalesce(&self) -> bool {
+            self.mergeable
+        }
+
         fn merge(&self, other: &Box<dyn Action>) -> Option<Box<dyn Action>> {
             let other = other.as_any().downcast_ref::<TestAction>().unwrap();
 
@@ -319,4 +545,135 @@ mod tests {
         assert_eq!(history.actions.len(), 1);
         assert_eq!(history.actions.first().unwrap().as_any().downcast_ref::<TestAction>().unwrap().addition, "HelloWorld");
     }
+
+    fn new_example() -> TestExampleRef {
+        TestExampleRef {
+            test_example: Rc::new(RefCell::new(TestExample {
+                text: Text::from("")
+            }))
+        }
+    }
+
+    /// Performs an action inserting `addition` at the end of the example.
+    fn perform(example: &TestExampleRef, addition: &str, mergeable: bool) -> Box<dyn Action> {
+        let index = example.test_example.borrow().text.len();
+        let mut action = TestAction {
+            example: example.clone(),
+            addition: addition.to_string(),
+            index,
+            mergeable
+        };
+
+        action.forward();
+        Box::new(action)
+    }
+
+    fn text(example: &TestExampleRef) -> String {
+        example.test_example.borrow().text.to_string()
+    }
+
+    #[test]
+    fn test_undo_manager() {
+        let example = new_example();
+        let mut undo_manager = UndoManager::new();
+        assert!(!undo_manager.can_undo());
+
+        undo_manager.push(perform(&example, "Hello", false));
+        undo_manager.push(perform(&example, "World", false));
+        assert_eq!(undo_manager.undo_action_name(), Some("TestAction"));
+
+        undo_manager.undo();
+        assert_eq!(text(&example), "Hello");
+        assert!(undo_manager.can_redo());
+
+        undo_manager.redo();
+        assert_eq!(text(&example), "HelloWorld");
+
+        undo_manager.undo();
+        undo_manager.undo();
+        undo_manager.undo();
+        assert_eq!(text(&example), "");
+        assert!(!undo_manager.can_undo());
+
+        // Pushing a new action clears the redo stack.
+        undo_manager.redo();
+        undo_manager.push(perform(&example, "!", false));
+        assert!(!undo_manager.can_redo());
+        assert_eq!(text(&example), "Hello!");
+    }
+
+    #[test]
+    fn test_undo_manager_coalescing() {
+        let example = new_example();
+        let mut undo_manager = UndoManager::new();
+
+        undo_manager.push(perform(&example, "a", true));
+        undo_manager.push(perform(&example, "b", true));
+        undo_manager.push(perform(&example, "c", false));
+        undo_manager.push(perform(&example, "d", true));
+        undo_manager.break_coalescing();
+        undo_manager.push(perform(&example, "e", true));
+        undo_manager.push(perform(&example, "f", true));
+        assert_eq!(text(&example), "abcdef");
+
+        undo_manager.undo();
+        assert_eq!(text(&example), "abcd");
+        undo_manager.undo();
+        assert_eq!(text(&example), "abc");
+        undo_manager.undo();
+        assert_eq!(text(&example), "ab");
+        undo_manager.undo();
+        assert_eq!(text(&example), "");
+    }
+
+    #[test]
+    fn test_undo_manager_groups() {
+        let example = new_example();
+        let mut undo_manager = UndoManager::new();
+
+        undo_manager.begin_group("Outer");
+        undo_manager.push(perform(&example, "a", false));
+        undo_manager.begin_group("Inner");
+        undo_manager.push(perform(&example, "b", false));
+        undo_manager.end_group();
+        undo_manager.push(perform(&example, "c", false));
+        assert_eq!(undo_manager.grouping_level(), 1);
+        undo_manager.end_group();
+
+        // Empty groups don't add a step.
+        undo_manager.begin_group("Empty");
+        undo_manager.end_group();
+
+        assert_eq!(undo_manager.undo_action_name(), Some("Outer"));
+        undo_manager.undo();
+        assert_eq!(text(&example), "");
+        assert!(!undo_manager.can_undo());
+
+        undo_manager.redo();
+        assert_eq!(text(&example), "abc");
+
+        // Undoing ends any open groups first.
+        undo_manager.begin_group("Unfinished");
+        undo_manager.push(perform(&example, "d", false));
+        undo_manager.push(perform(&example, "e", false));
+        undo_manager.undo();
+        assert_eq!(undo_manager.grouping_level(), 0);
+        assert_eq!(text(&example), "abc");
+    }
+
+    #[test]
+    fn test_undo_manager_levels_of_undo() {
+        let example = new_example();
+        let mut undo_manager = UndoManager::new();
+        undo_manager.set_levels_of_undo(2);
+
+        undo_manager.push(perform(&example, "a", false));
+        undo_manager.push(perform(&example, "b", false));
+        undo_manager.push(perform(&example, "c", false));
+
+        undo_manager.undo();
+        undo_manager.undo();
+        assert!(!undo_manager.can_undo());
+        assert_eq!(text(&example), "a");
+    }
 }
@@ -0,0 +1,126 @@
+use crate::ui::command::Command;
+use crate::ui::menu::{Menu, MenuItem};
+use crate::ui::{View, Window};
+
+/// The application's menus, such as File and Edit, shown along the top of
+/// its windows by a `MenuBarView` given the menu bar.
+///
+/// Items are the same `MenuItem`s shown in context menus. Command items,
+/// such as Edit > Undo, are sent to the first responder of the key window
+/// and passed up the responder chain, so they act on the focused view.
+#[derive(Clone, Debug, Default)]
+pub struct MenuBar {
+    menus: Vec<Menu>
+}
+
+impl MenuBar {
+    pub fn new(menus: Vec<Menu>) -> MenuBar {
+        MenuBar {
+            menus
+        }
+    }
+
+    /// The standard Edit menu, with undo and redo, and the pasteboard
+    /// commands.
+    pub fn edit_menu() -> Menu {
+        Menu::new("Edit", vec![
+            MenuItem::command("Undo", Command::Undo),
+            MenuItem::command("Redo", Command::Redo),
+            MenuItem::separator(),
+            MenuItem::command("Cut", Command::Cut),
+            MenuItem::command("Copy", Command::Copy),
+            MenuItem::command("Paste", Command::Paste),
+            MenuItem::command("Select All", Command::SelectAll)
+        ])
+    }
+
+    pub fn menus(&self) -> &[Menu] {
+        &self.menus
+    }
+
+    /// Adds a menu, after those already added.
+    pub fn add_menu(&mut self, menu: Menu) {
+        self.menus.push(menu);
+    }
+
+    /// The menu titled `title`, if any.
+    pub fn menu(&self, title: &str) -> Option<&Menu> {
+        self.menus.iter().find(|menu| menu.title() == title)
+    }
+
+    /// The item at `path`: the index of a menu, followed by the index of an
+    /// item in it and in each submenu on the way.
+    pub fn item(&self, path: &[usize]) -> Option<&MenuItem> {
+        let (first, rest) = path.split_first()?;
+        let mut menu = self.menus.get(*first)?;
+        let (last, submenus) = rest.split_last()?;
+
+        for index in submenus {
+            menu = menu.items().get(*index)?.submenu_menu()?;
+        }
+
+        menu.items().get(*last)
+    }
+
+    /// Chooses the item at `path`, as `item` finds it, sending commands to
+    /// `target`. Returns whether the item was performed.
+    pub fn perform(&self, path: &[usize], target: Option<&View>) -> bool {
+        match self.item(path) {
+            Some(item) => item.perform(target),
+            None => false
+        }
+    }
+
+    /// Chooses the item at `path`, sending commands to `window`'s first
+    /// responder.
+    pub fn perform_in_window(&self, path: &[usize], window: &Window) -> bool {
+        self.perform(path, Some(&window.first_responder()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::TextField;
+    use crate::ui::view::Behavior;
+    use crate::graphics::Rectangle;
+
+    #[test]
+    fn test_item_paths() {
+        let find = Menu::new("Find", vec![MenuItem::new("Find…", || {})]);
+        let mut menu_bar = MenuBar::new(vec![Menu::new("File", vec![MenuItem::new("Open", || {})])]);
+        let mut edit_menu = MenuBar::edit_menu();
+        edit_menu.add_item(MenuItem::submenu("Find", find));
+        menu_bar.add_menu(edit_menu);
+
+        assert_eq!(menu_bar.item(&[0, 0]).unwrap().title(), "Open");
+        assert_eq!(menu_bar.item(&[1, 7, 0]).unwrap().title(), "Find…");
+        assert!(menu_bar.item(&[1, 0, 0]).is_none());
+        assert!(menu_bar.item(&[1]).is_none());
+        assert!(menu_bar.item(&[2, 0]).is_none());
+        assert_eq!(menu_bar.menu("Edit").unwrap().items().len(), 8);
+    }
+
+    #[test]
+    fn test_edit_menu_reaches_text_field() {
+        let menu_bar = MenuBar::new(vec![MenuBar::edit_menu()]);
+        let container = View::new(Rectangle::new(0, 0, 300, 300));
+        let text_field = TextField::new(Rectangle::new(0, 0, 300, 100), "".to_string());
+        container.add_subview(text_field.view.clone());
+        text_field.behavior().text_input_did_receive("hello");
+
+        // Typing over Select All replaces the text, and Undo and Redo
+        // step through the text field's own history.
+        assert!(menu_bar.perform(&[0, 6], Some(&text_field.view)));
+        text_field.behavior().text_input_did_receive("bye");
+        assert_eq!(text_field.label().text().string(), "bye");
+        assert!(menu_bar.perform(&[0, 0], Some(&text_field.view)));
+        assert_eq!(text_field.label().text().string(), "hello");
+        assert!(menu_bar.perform(&[0, 1], Some(&text_field.view)));
+        assert_eq!(text_field.label().text().string(), "bye");
+
+        // The container can't undo without a window.
+        assert!(!menu_bar.perform(&[0, 0], Some(&container)));
+        assert!(!menu_bar.perform(&[0, 2], Some(&text_field.view)));
+    }
+}
@@ -1,4 +1,9 @@
+#[cfg(all(feature = "accesskit", target_os = "linux"))]
+pub(crate) mod accessibility;
 pub mod bundle;
 pub mod clipboard;
+pub mod cursor;
 pub mod history;
+pub mod menu_bar;
+pub mod pasteboard;
 pub mod thread;
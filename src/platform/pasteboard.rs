@@ -0,0 +1,145 @@
+use crate::platform::clipboard;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The name used to remember the items last written to the general
+/// pasteboard.
+const GENERAL_NAME: &str = "pelican.pasteboard.general";
+
+thread_local! {
+    static CONTENTS: RefCell<HashMap<String, Vec<String>>> = RefCell::new(HashMap::new());
+}
+
+/// A pasteboard holds text for copy and paste.
+///
+/// The general pasteboard is backed by the system clipboard, so text copied
+/// from a `Pasteboard` can be pasted in other applications and vice versa.
+/// Named pasteboards are private to the application.
+///
+/// A pasteboard holds one or more items. Copying with multiple carats writes
+/// one item per carat, which are joined with newlines on the system
+/// clipboard. As long as the clipboard hasn't changed since, reading the
+/// general pasteboard returns the separate items again, so they can be pasted
+/// back one per carat.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pasteboard {
+    name: Option<String>
+}
+
+impl Pasteboard {
+    /// The pasteboard backed by the system clipboard.
+    pub fn general() -> Pasteboard {
+        Pasteboard {
+            name: None
+        }
+    }
+
+    /// A pasteboard private to the application. Pasteboards with the same
+    /// name share their contents.
+    pub fn named(name: &str) -> Pasteboard {
+        Pasteboard {
+            name: Some(name.to_string())
+        }
+    }
+
+    /// The name of the pasteboard, or `None` for the general pasteboard.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn key(&self) -> &str {
+        self.name.as_deref().unwrap_or(GENERAL_NAME)
+    }
+
+    /// Replaces the contents of the pasteboard with `text`.
+    pub fn set_string(&self, text: &str) {
+        self.set_strings(vec![text.to_string()]);
+    }
+
+    /// Replaces the contents of the pasteboard with `items`. Setting no
+    /// items clears the pasteboard.
+    pub fn set_strings(&self, items: Vec<String>) {
+        if self.name.is_none() {
+            clipboard::set_string(&items.join("\n"));
+        }
+
+        CONTENTS.with(|contents| {
+            let mut contents = contents.borrow_mut();
+            if items.is_empty() {
+                contents.remove(self.key());
+            } else {
+                contents.insert(self.key().to_string(), items);
+            }
+        });
+    }
+
+    /// Removes the contents of the pasteboard.
+    pub fn clear(&self) {
+        self.set_strings(Vec::new());
+    }
+
+    /// The items on the pasteboard. For the general pasteboard, if the
+    /// system clipboard was changed by another application this is its text
+    /// as a single item.
+    pub fn strings(&self) -> Vec<String> {
+        let items = CONTENTS.with(|contents| contents.borrow().get(self.key()).cloned());
+
+        if self.name.is_some() {
+            return items.unwrap_or_default();
+        }
+
+        let text = match clipboard::get_string() {
+            Some(text) if !text.is_empty() => text,
+            _ => return Vec::new()
+        };
+
+        match items {
+            Some(items) if items.join("\n") == text => items,
+            _ => vec![text]
+        }
+    }
+
+    /// The contents of the pasteboard as a single string, with multiple
+    /// items joined by newlines.
+    pub fn string(&self) -> Option<String> {
+        let items = self.strings();
+        if items.is_empty() {
+            None
+        } else {
+            Some(items.join("\n"))
+        }
+    }
+
+    /// Whether the pasteboard has any text.
+    pub fn has_strings(&self) -> bool {
+        if self.name.is_none() {
+            return clipboard::contains_text();
+        }
+
+        CONTENTS.with(|contents| contents.borrow().contains_key(self.key()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_pasteboard() {
+        let pasteboard = Pasteboard::named("test_named_pasteboard");
+        assert!(!pasteboard.has_strings());
+        assert_eq!(pasteboard.string(), None);
+
+        pasteboard.set_string("hello");
+        assert!(pasteboard.has_strings());
+        assert_eq!(pasteboard.strings(), vec!["hello".to_string()]);
+
+        pasteboard.set_strings(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(Pasteboard::named("test_named_pasteboard").string(), Some("a\nb".to_string()));
+        assert!(!Pasteboard::named("another").has_strings());
+
+        pasteboard.clear();
+        assert!(!pasteboard.has_strings());
+        assert_eq!(pasteboard.strings(), Vec::<String>::new());
+    }
+}
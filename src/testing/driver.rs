@@ -0,0 +1,280 @@
+use crate::graphics::Point;
+use crate::ui::Window;
+use crate::ui::event_loop;
+use crate::ui::key::{KeyCode, ModifierFlag};
+use crate::ui::run_loop::RunLoop;
+use crate::ui::clock::{Clock, ManualClock};
+use sdl2::event::Event;
+use sdl2::mouse::{MouseButton, MouseState, MouseWheelDirection};
+use std::time::Duration;
+
+/// How far `TestDriver::advance` moves time between passes of the run loop,
+/// about one frame at 60 frames per second.
+const FRAME_DURATION: Duration = Duration::from_millis(16);
+
+/// Drives a window in tests as a person would: touching, typing, pressing
+/// keys and scrolling. Events are synthesized and sent through the same
+/// handling as events from the platform, so gesture recognizers, the first
+/// responder and key repeat all see them.
+///
+/// Nothing else runs until the test asks for it with `pump`, which makes a
+/// single pass of the run loop without waiting, such as to draw the window
+/// or run posted tasks. Time stands still too: the driver gives the run loop
+/// a `ManualClock`, and timers, animations and the carat's blinking only
+/// move on when the test calls `advance`.
+///
+/// ```ignore
+/// let window = Window::new_headless(Rectangle::new(0, 0, 300, 200), view_controller);
+/// let driver = TestDriver::new(&window);
+/// driver.tap(Point::new(20, 20));
+/// driver.type_text("hello");
+/// driver.press_key(KeyCode::A, &[ModifierFlag::Command]);
+/// driver.advance(Duration::from_millis(500));
+/// ```
+pub struct TestDriver {
+    window: Window,
+    clock: ManualClock,
+
+    // The clock the run loop went by before the driver, put back when the
+    // driver is dropped.
+    previous_clock: Option<Box<dyn Clock>>
+}
+
+impl TestDriver {
+    /// A driver sending events to `window`, usually one made with
+    /// `Window::new_headless`. Until the driver is dropped, the run loop
+    /// goes by the driver's clock.
+    pub fn new(window: &Window) -> TestDriver {
+        let clock = ManualClock::new();
+        let previous_clock = RunLoop::borrow().replace_clock(Some(Box::new(clock.clone())));
+
+        TestDriver {
+            window: window.clone(),
+            clock,
+            previous_clock
+        }
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Starts a touch at `point` in the window, as pressing the mouse button.
+    pub fn touch_down(&self, point: Point<i32>) {
+        self.touch_down_with_tap_count(point, 1);
+    }
+
+    /// Starts a touch at `point` counted as the `tap_count`th in quick
+    /// succession, as the platform counts clicks. See `Touch::tap_count`.
+    pub fn touch_down_with_tap_count(&self, point: Point<i32>, tap_count: u8) {
+        self.send(Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: self.window_id(),
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: tap_count,
+            x: point.x,
+            y: point.y
+        });
+    }
+
+    /// Moves the touch started by `touch_down` to `point`.
+    pub fn touch_move(&self, point: Point<i32>) {
+        self.send(Event::MouseMotion {
+            timestamp: 0,
+            window_id: self.window_id(),
+            which: 0,
+            // The left button held.
+            mousestate: MouseState::from_sdl_state(1),
+            x: point.x,
+            y: point.y,
+            xrel: 0,
+            yrel: 0
+        });
+    }
+
+    /// Moves the pointer to `point` without a button held, entering and
+    /// leaving the views' tracking areas.
+    pub fn move_pointer(&self, point: Point<i32>) {
+        self.send(Event::MouseMotion {
+            timestamp: 0,
+            window_id: self.window_id(),
+            which: 0,
+            mousestate: MouseState::from_sdl_state(0),
+            x: point.x,
+            y: point.y,
+            xrel: 0,
+            yrel: 0
+        });
+    }
+
+    /// Ends the touch started by `touch_down` at `point`.
+    pub fn touch_up(&self, point: Point<i32>) {
+        self.send(Event::MouseButtonUp {
+            timestamp: 0,
+            window_id: self.window_id(),
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x: point.x,
+            y: point.y
+        });
+    }
+
+    /// Touches and releases `point`.
+    pub fn tap(&self, point: Point<i32>) {
+        self.touch_down(point.clone());
+        self.touch_up(point);
+    }
+
+    /// Taps `point` twice, the second touch having a tap count of 2.
+    pub fn double_tap(&self, point: Point<i32>) {
+        self.tap(point.clone());
+        self.touch_down_with_tap_count(point.clone(), 2);
+        self.touch_up(point);
+    }
+
+    /// Touches `from`, moves to `to`, and releases there.
+    pub fn drag(&self, from: Point<i32>, to: Point<i32>) {
+        self.touch_down(from);
+        self.touch_move(to.clone());
+        self.touch_up(to);
+    }
+
+    /// Clicks `point` with the secondary button, showing its context menu.
+    pub fn secondary_click(&self, point: Point<i32>) {
+        self.send(Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: self.window_id(),
+            which: 0,
+            mouse_btn: MouseButton::Right,
+            clicks: 1,
+            x: point.x,
+            y: point.y
+        });
+    }
+
+    /// Scrolls by `translation`, in notches with up and right positive, as
+    /// turning a mouse wheel or swiping on a trackpad. The view scrolled is
+    /// the one under where the pointer last moved, see `move_pointer`.
+    pub fn scroll(&self, translation: Point<i32>) {
+        self.send(Event::MouseWheel {
+            timestamp: 0,
+            window_id: self.window_id(),
+            which: 0,
+            x: translation.x,
+            y: translation.y,
+            direction: MouseWheelDirection::Normal
+        });
+    }
+
+    /// Drops the files at `paths` on the window, as dragged from another
+    /// application, where the pointer last moved to.
+    pub fn drop_files(&self, paths: &[&str]) {
+        self.send(Event::DropBegin {
+            timestamp: 0,
+            window_id: self.window_id()
+        });
+
+        for path in paths {
+            self.send(Event::DropFile {
+                timestamp: 0,
+                window_id: self.window_id(),
+                filename: path.to_string()
+            });
+        }
+
+        self.send(Event::DropComplete {
+            timestamp: 0,
+            window_id: self.window_id()
+        });
+    }
+
+    /// Starts pressing `key_code`, with `modifier_flags` held. The press is
+    /// sent to the window's first responder.
+    pub fn key_down(&self, key_code: KeyCode, modifier_flags: &[ModifierFlag]) {
+        self.send(Event::KeyDown {
+            timestamp: 0,
+            window_id: self.window_id(),
+            keycode: Some(key_code),
+            scancode: None,
+            keymod: event_loop::keymod(modifier_flags),
+            repeat: false
+        });
+    }
+
+    /// Releases `key_code`, with `modifier_flags` still held.
+    pub fn key_up(&self, key_code: KeyCode, modifier_flags: &[ModifierFlag]) {
+        self.send(Event::KeyUp {
+            timestamp: 0,
+            window_id: self.window_id(),
+            keycode: Some(key_code),
+            scancode: None,
+            keymod: event_loop::keymod(modifier_flags),
+            repeat: false
+        });
+    }
+
+    /// Presses and releases `key_code`, with `modifier_flags` held.
+    pub fn press_key(&self, key_code: KeyCode, modifier_flags: &[ModifierFlag]) {
+        self.key_down(key_code, modifier_flags);
+        self.key_up(key_code, modifier_flags);
+    }
+
+    /// Types `text` into the window's first responder, as the platform's
+    /// text input does after key presses.
+    pub fn type_text(&self, text: &str) {
+        self.send(Event::TextInput {
+            timestamp: 0,
+            window_id: self.window_id(),
+            text: text.to_string()
+        });
+    }
+
+    /// Makes a single pass of the run loop, without waiting: runs posted
+    /// tasks and woken futures, display links, and the timers that are due,
+    /// including the one drawing the window if it needs display.
+    pub fn pump(&self) {
+        RunLoop::borrow().run_once();
+    }
+
+    /// Makes `passes` passes of the run loop, such as to let tasks posted
+    /// by tasks run.
+    pub fn pump_times(&self, passes: usize) {
+        for _ in 0..passes {
+            self.pump();
+        }
+    }
+
+    /// Moves the run loop's clock forward by `duration`, one frame at a
+    /// time, making a pass of the run loop after each. Timers fire, and
+    /// animations step, as if that much time had passed.
+    pub fn advance(&self, duration: Duration) {
+        let mut remaining = duration;
+        while remaining > Duration::from_secs(0) {
+            let step = remaining.min(FRAME_DURATION);
+            self.clock.advance(step);
+            self.pump();
+            remaining -= step;
+        }
+    }
+
+    /// The clock the run loop goes by while the driver is used.
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    fn window_id(&self) -> u32 {
+        self.window.context().id()
+    }
+
+    fn send(&self, event: Event) {
+        event_loop::handle_event(event);
+    }
+}
+
+impl Drop for TestDriver {
+    fn drop(&mut self) {
+        RunLoop::borrow().replace_clock(self.previous_clock.take());
+    }
+}
@@ -0,0 +1,7 @@
+//! Helpers for testing applications built with pelican.
+
+mod snapshot;
+pub use snapshot::{assert_view_matches_snapshot, assert_view_matches_snapshot_with_tolerance, DEFAULT_TOLERANCE};
+
+mod driver;
+pub use driver::TestDriver;
@@ -0,0 +1,184 @@
+use crate::graphics::{Context, Image};
+use crate::ui::View;
+use std::fs;
+use std::path::PathBuf;
+
+/// Where snapshots are kept, relative to the crate being tested.
+const SNAPSHOT_DIRECTORY: &str = "tests/snapshots";
+
+/// Set to keep snapshots somewhere else than `SNAPSHOT_DIRECTORY`.
+const SNAPSHOT_DIRECTORY_VARIABLE: &str = "PELICAN_SNAPSHOT_DIR";
+
+/// Set to record snapshots again instead of comparing with them, such as
+/// after changing how a view looks on purpose.
+const UPDATE_SNAPSHOTS_VARIABLE: &str = "PELICAN_UPDATE_SNAPSHOTS";
+
+/// How much each channel of a pixel can differ from the snapshot, so small
+/// differences in blending don't fail tests.
+pub const DEFAULT_TOLERANCE: u8 = 2;
+
+/// Pixels that differ are drawn in this color in diff images.
+const DIFFERENCE_COLOR: [u8; 4] = [255, 0, 0, 255];
+
+/// Pixels that match are drawn faded in diff images, with this alpha.
+const MATCHING_ALPHA: u8 = 48;
+
+/// Draws `view` offscreen, at a scale of 1, and checks it looks the same as
+/// the snapshot `name`, a PNG in `tests/snapshots` of the crate tested.
+///
+/// When `PELICAN_UPDATE_SNAPSHOTS` is set, the drawing is saved as the
+/// snapshot instead. Otherwise, if more than `DEFAULT_TOLERANCE` separates
+/// any channel of any pixel, the test fails, and the drawing is saved next to
+/// the snapshot as `name.actual.png`, along with `name.diff.png` showing the
+/// pixels that differ in red.
+///
+/// A missing snapshot fails the test too, with the drawing saved as
+/// `name.actual.png`, so a misspelled name or a snapshot that was never
+/// committed doesn't pass unnoticed. Run the test once with
+/// `PELICAN_UPDATE_SNAPSHOTS=1` to record it.
+///
+/// ```ignore
+/// let button = Button::new(Rectangle::new(0, 0, 120, 44), "OK", || {});
+/// assert_view_matches_snapshot(&button.view, "button");
+/// ```
+pub fn assert_view_matches_snapshot(view: &View, name: &str) {
+    assert_view_matches_snapshot_with_tolerance(view, name, DEFAULT_TOLERANCE);
+}
+
+/// Like `assert_view_matches_snapshot`, allowing each channel of a pixel to
+/// differ by up to `tolerance`.
+pub fn assert_view_matches_snapshot_with_tolerance(view: &View, name: &str, tolerance: u8) {
+    let context = Context::new_headless(view.frame().size, 1.0);
+    let actual = view.snapshot_in_context(&context);
+
+    let path = snapshot_path(name, "png");
+    let actual_path = snapshot_path(name, "actual.png");
+    let diff_path = snapshot_path(name, "diff.png");
+
+    if std::env::var_os(UPDATE_SNAPSHOTS_VARIABLE).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        actual.save_png(path.to_str().unwrap()).unwrap();
+        println!("Recorded snapshot {}", path.display());
+        return;
+    }
+
+    if !path.is_file() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        actual.save_png(actual_path.to_str().unwrap()).unwrap();
+        panic!(
+            "No snapshot {} at {}. The view was drawn to {}; run the test with {}=1 to record it",
+            name,
+            path.display(),
+            actual_path.display(),
+            UPDATE_SNAPSHOTS_VARIABLE
+        );
+    }
+
+    let expected = fs::read(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|data| Image::from_data(&data))
+        .unwrap_or_else(|error| panic!("Could not load snapshot {}: {}", path.display(), error));
+
+    // Failures from before are stale once the view matches again.
+    let _ = fs::remove_file(&actual_path);
+    let _ = fs::remove_file(&diff_path);
+
+    if expected.pixel_size() != actual.pixel_size() {
+        actual.save_png(actual_path.to_str().unwrap()).unwrap();
+        panic!(
+            "View is {}x{} but snapshot {} is {}x{}. It was drawn to {}",
+            actual.pixel_size().width, actual.pixel_size().height,
+            name,
+            expected.pixel_size().width, expected.pixel_size().height,
+            actual_path.display()
+        );
+    }
+
+    if let Some(difference) = compare_pixels(&expected.pixels(), &actual.pixels(), tolerance) {
+        let diff = Image::from_pixels(&difference.diff, &actual.pixel_size(), 1.0);
+        actual.save_png(actual_path.to_str().unwrap()).unwrap();
+        diff.save_png(diff_path.to_str().unwrap()).unwrap();
+        panic!(
+            "View differs from snapshot {} in {} pixels. It was drawn to {}, with the differences in {}",
+            name,
+            difference.pixel_count,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+/// The file for snapshot `name`, ending in `extension`.
+fn snapshot_path(name: &str, extension: &str) -> PathBuf {
+    let mut path = match std::env::var_os(SNAPSHOT_DIRECTORY_VARIABLE) {
+        Some(directory) => PathBuf::from(directory),
+        None => {
+            // Cargo runs tests in the directory of the crate tested, and
+            // tells them where it is.
+            let mut path = std::env::var_os("CARGO_MANIFEST_DIR")
+                .map_or_else(|| PathBuf::from("."), PathBuf::from);
+            path.push(SNAPSHOT_DIRECTORY);
+            path
+        }
+    };
+
+    path.push(format!("{}.{}", name, extension));
+    path
+}
+
+/// How a drawing differs from a snapshot.
+struct Difference {
+    /// The number of pixels differing by more than the tolerance.
+    pixel_count: usize,
+
+    /// The drawing faded, with the pixels that differ in red, as RGBA bytes.
+    diff: Vec<u8>
+}
+
+/// Compares two images of the same size, as RGBA bytes, or returns `None` if
+/// no channel of any pixel differs by more than `tolerance`.
+fn compare_pixels(expected: &[u8], actual: &[u8], tolerance: u8) -> Option<Difference> {
+    let mut pixel_count = 0;
+    let mut diff = Vec::with_capacity(actual.len());
+
+    for (expected, actual) in expected.chunks_exact(4).zip(actual.chunks_exact(4)) {
+        let differs = expected.iter().zip(actual).any(|(expected, actual)| expected.abs_diff(*actual) > tolerance);
+
+        if differs {
+            pixel_count += 1;
+            diff.extend_from_slice(&DIFFERENCE_COLOR);
+        } else {
+            diff.extend_from_slice(&actual[..3]);
+            diff.push(actual[3].min(MATCHING_ALPHA));
+        }
+    }
+
+    if pixel_count == 0 {
+        None
+    } else {
+        Some(Difference { pixel_count, diff })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_pixels() {
+        let expected = [10, 20, 30, 255, 0, 0, 0, 0];
+
+        // Within the tolerance, the images match.
+        assert!(compare_pixels(&expected, &[12, 18, 30, 255, 0, 0, 0, 2], 2).is_none());
+
+        let difference = compare_pixels(&expected, &[10, 20, 30, 255, 0, 9, 0, 255], 2).unwrap();
+        assert_eq!(difference.pixel_count, 1);
+        assert_eq!(difference.diff, vec![10, 20, 30, MATCHING_ALPHA, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_snapshot_path() {
+        let path = snapshot_path("button", "diff.png");
+        assert!(path.ends_with("button.diff.png"));
+    }
+}
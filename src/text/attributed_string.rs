@@ -1,8 +1,46 @@
 use crate::graphics::Color;
 use crate::graphics::Font;
 use std::collections::HashMap;
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, Ref, RefCell};
 use crate::text::text::Text;
+use crate::text::layout::LayoutCache;
+use uuid::Uuid;
+use crate::text::word_boundary::{self, SegmentKind};
+use unicode_segmentation::{Graphemes, UnicodeSegmentation};
+
+/// The style of a line drawn through or under text. Used by the `Underline`
+/// and `Strikethrough` attributes.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum LineStyle {
+    /// No line is drawn.
+    None,
+
+    /// A single thin line.
+    Single,
+
+    /// A single line, twice the thickness of `Single`.
+    Thick,
+
+    /// Two thin lines.
+    Double
+}
+
+/// How characters are spaced relative to each other. Used by the `Kerning`
+/// attribute.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Kerning {
+    /// The font's kerning pairs and ligatures (such as "fi") are used.
+    Default,
+
+    /// Characters are spaced by their own widths only, without kerning
+    /// pairs or ligatures.
+    Disabled,
+
+    /// The font's kerning pairs are used, and the given number of points is
+    /// added after each character. Negative values tighten the spacing.
+    /// Ligatures are not used.
+    Adjusted(i32)
+}
 
 #[derive(PartialEq, Debug)]
 pub enum Attribute {
@@ -11,6 +49,41 @@ pub enum Attribute {
     },
     Font {
         font: Font
+    },
+    Underline {
+        style: LineStyle
+    },
+    Strikethrough {
+        style: LineStyle
+    },
+    /// The highlight color drawn behind the character.
+    BackgroundColor {
+        color: Color
+    },
+    /// The distance, in points, to raise the character above the baseline.
+    /// Negative values lower the character (e.g. for subscript).
+    BaselineOffset {
+        offset: i32
+    },
+    /// Marks the character as part of a link. Has no default value; use
+    /// `get_attribute_if_set_for` to look it up.
+    Link {
+        url: String
+    },
+    /// How the character is spaced from the next one.
+    Kerning {
+        kerning: Kerning
+    },
+    /// Marks the character as part of a misspelled word, drawn with a red
+    /// squiggle underneath. Set by spell checking, see `SpellChecker`.
+    Misspelled {
+        is_misspelled: bool
+    },
+    /// A highlight drawn over the background color, such as the matches of
+    /// a search. Set by a `TextView`'s find bar, so kept apart from
+    /// `BackgroundColor` for the text's own colors.
+    Highlight {
+        color: Color
     }
 }
 
@@ -28,13 +101,77 @@ impl Attribute {
             _ => panic!("Attribute is not a font")
         }
     }
+
+    pub fn underline_style(&self) -> LineStyle {
+        match self {
+            Attribute::Underline { style } => *style,
+            _ => panic!("Attribute is not an underline")
+        }
+    }
+
+    pub fn strikethrough_style(&self) -> LineStyle {
+        match self {
+            Attribute::Strikethrough { style } => *style,
+            _ => panic!("Attribute is not a strikethrough")
+        }
+    }
+
+    pub fn background_color(&self) -> &Color {
+        match self {
+            Attribute::BackgroundColor { color } => color,
+            _ => panic!("Attribute is not a background color")
+        }
+    }
+
+    pub fn baseline_offset(&self) -> i32 {
+        match self {
+            Attribute::BaselineOffset { offset } => *offset,
+            _ => panic!("Attribute is not a baseline offset")
+        }
+    }
+
+    pub fn link(&self) -> &str {
+        match self {
+            Attribute::Link { url } => url,
+            _ => panic!("Attribute is not a link")
+        }
+    }
+
+    pub fn kerning(&self) -> Kerning {
+        match self {
+            Attribute::Kerning { kerning } => *kerning,
+            _ => panic!("Attribute is not a kerning")
+        }
+    }
+
+    pub fn is_misspelled(&self) -> bool {
+        match self {
+            Attribute::Misspelled { is_misspelled } => *is_misspelled,
+            _ => panic!("Attribute is not a misspelling")
+        }
+    }
+
+    pub fn highlight_color(&self) -> &Color {
+        match self {
+            Attribute::Highlight { color } => color,
+            _ => panic!("Attribute is not a highlight")
+        }
+    }
 }
 
 impl Clone for Attribute {
     fn clone(&self) -> Attribute {
         match self {
             Attribute::Color { color } => Attribute::Color { color: color.clone() },
-            Attribute::Font { font } => Attribute::Font { font: font.clone() }
+            Attribute::Font { font } => Attribute::Font { font: font.clone() },
+            Attribute::Underline { style } => Attribute::Underline { style: *style },
+            Attribute::Strikethrough { style } => Attribute::Strikethrough { style: *style },
+            Attribute::BackgroundColor { color } => Attribute::BackgroundColor { color: *color },
+            Attribute::BaselineOffset { offset } => Attribute::BaselineOffset { offset: *offset },
+            Attribute::Link { url } => Attribute::Link { url: url.clone() },
+            Attribute::Kerning { kerning } => Attribute::Kerning { kerning: *kerning },
+            Attribute::Misspelled { is_misspelled } => Attribute::Misspelled { is_misspelled: *is_misspelled },
+            Attribute::Highlight { color } => Attribute::Highlight { color: *color }
         }
     }
 }
@@ -42,7 +179,15 @@ impl Clone for Attribute {
 #[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub enum Key {
     Color,
-    Font
+    Font,
+    Underline,
+    Strikethrough,
+    BackgroundColor,
+    BaselineOffset,
+    Link,
+    Kerning,
+    Misspelled,
+    Highlight
 }
 
 type AttributeContainer = HashMap<Key, Attribute>;
@@ -51,8 +196,8 @@ pub struct AttributedString {
     /// The actual text that this `AttributedString` represents.
     text: Text,
 
-    /// The attributes for each character in the string. The index of the
-    /// character in the string matches the index of the attribute in the
+    /// The attributes for each grapheme cluster in the string. The index of
+    /// the grapheme in the string matches the index of the attribute in the
     /// vec.
     attributes: RefCell<Vec<AttributeContainer>>,
 
@@ -60,7 +205,13 @@ pub struct AttributedString {
     ///
     /// E.g. If any given character do not have the `Color` attribute, then
     /// the default color will be used.
-    default_attributes: RefCell<AttributeContainer>
+    default_attributes: RefCell<AttributeContainer>,
+
+    /// Uniquely identifies this string, e.g. for caching its layout.
+    id: Uuid,
+
+    /// Incremented whenever the text or attributes change.
+    revision: Cell<u64>
 }
 
 pub struct AttributedSubstring<'a> {
@@ -74,28 +225,55 @@ impl AttributedString {
         let mut default_attributes = AttributeContainer::new();
         default_attributes.insert(Key::Color, Attribute::Color { color: Color::BLACK });
         default_attributes.insert(Key::Font, Attribute::Font { font: Font::default() });
+        default_attributes.insert(Key::Underline, Attribute::Underline { style: LineStyle::None });
+        default_attributes.insert(Key::Strikethrough, Attribute::Strikethrough { style: LineStyle::None });
+        default_attributes.insert(Key::BackgroundColor, Attribute::BackgroundColor { color: Color::RGBA(0, 0, 0, 0) });
+        default_attributes.insert(Key::BaselineOffset, Attribute::BaselineOffset { offset: 0 });
+        default_attributes.insert(Key::Kerning, Attribute::Kerning { kerning: Kerning::Default });
+        default_attributes.insert(Key::Misspelled, Attribute::Misspelled { is_misspelled: false });
+        default_attributes.insert(Key::Highlight, Attribute::Highlight { color: Color::RGBA(0, 0, 0, 0) });
+
+        let text = Text::new(text);
 
         let mut attributes = Vec::new();
 
-        for _ in text.chars() {
+        for _ in 0..text.len() {
             attributes.push(AttributeContainer::new());
         }
 
         AttributedString {
-            text: Text::new(text),
+            text,
             attributes: RefCell::new(attributes),
-            default_attributes: RefCell::new(default_attributes)
+            default_attributes: RefCell::new(default_attributes),
+            id: Uuid::new_v4(),
+            revision: Cell::new(0)
         }
     }
 
+    /// A unique identifier for this string. Copies of the string (e.g. from
+    /// `AttributedSubstring::to_owned`) have their own identifiers.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// A counter that changes whenever the text or attributes are mutated,
+    /// so that anything derived from the string can tell it's stale.
+    pub fn revision(&self) -> u64 {
+        self.revision.get()
+    }
+
+    /// Records a mutation, discarding any cached layouts of the string.
+    fn did_change(&self) {
+        self.revision.set(self.revision.get() + 1);
+        LayoutCache::invalidate(self.id);
+    }
+
     pub fn new_matching_default_style(text: String, existing_attributed_string: &AttributedString) -> AttributedString {
         let attributed_string = AttributedString::new(text);
 
-        let existing_color = existing_attributed_string.default_attributes.borrow().get(&Key::Color).unwrap().clone();
-        attributed_string.set_default_attribute(Key::Color, existing_color);
-
-        let existing_font = existing_attributed_string.default_attributes.borrow().get(&Key::Font).unwrap().clone();
-        attributed_string.set_default_attribute(Key::Font, existing_font);
+        for (key, attribute) in existing_attributed_string.default_attributes.borrow().iter() {
+            attributed_string.set_default_attribute(key.clone(), attribute.clone());
+        }
 
         attributed_string
     }
@@ -108,12 +286,27 @@ impl AttributedString {
         self.text.len()
     }
 
+    /// Inserts `string` at the grapheme index `idx`.
+    ///
+    /// The number of attribute containers added is the change in grapheme
+    /// count rather than the grapheme count of `string`, since inserted
+    /// combining characters can merge with their neighbours.
     pub fn insert_str(&mut self, idx: usize, string: &str) {
         self.text.insert_str(idx, string);
-        let text = Text::from(string);
+        self.reconcile_attributes_at(idx);
+        self.did_change();
+    }
+
+    /// Inserts or removes attribute containers at `idx` until there is
+    /// exactly one per grapheme cluster again.
+    fn reconcile_attributes_at(&self, idx: usize) {
         let mut attributes = self.attributes.borrow_mut();
-        for _ in 0..text.len() {
-            attributes.insert(idx, AttributeContainer::new())
+        while attributes.len() < self.text.len() {
+            attributes.insert(idx, AttributeContainer::new());
+        }
+        while attributes.len() > self.text.len() {
+            let last = attributes.len() - 1;
+            attributes.remove(idx.min(last));
         }
     }
 
@@ -121,8 +314,9 @@ impl AttributedString {
         let mut lines = Vec::new();
         let mut start = 0;
 
-        for (i, c) in self.text.string().chars().enumerate() {
-            if c == '\n' {
+        for (i, grapheme) in self.graphemes().enumerate() {
+            // "\r\n" is a single grapheme cluster.
+            if grapheme.ends_with('\n') {
                 lines.push(AttributedSubstring {
                     attributed_string: self,
                     start: start,
@@ -139,6 +333,33 @@ impl AttributedString {
         lines
     }
 
+    /// Iterates the words of the string, as determined by Unicode word
+    /// boundaries. Whitespace and punctuation between words are not
+    /// included. Each substring's `range()` is its position in this string.
+    pub fn words(&self) -> impl Iterator<Item = AttributedSubstring<'_>> {
+        self.substring(0..self.len()).words()
+    }
+
+    /// Iterates the sentences of the string, as determined by Unicode
+    /// sentence boundaries. Trailing whitespace belongs to the preceding
+    /// sentence.
+    pub fn sentences(&self) -> impl Iterator<Item = AttributedSubstring<'_>> {
+        self.substring(0..self.len()).sentences()
+    }
+
+    /// A substring over the given grapheme range.
+    pub fn substring(&self, range: std::ops::Range<usize>) -> AttributedSubstring<'_> {
+        if range.start > range.end || range.end > self.len() {
+            panic!("Range {:?} out of bounds for length {}", range, self.len());
+        }
+
+        AttributedSubstring {
+            attributed_string: self,
+            start: range.start,
+            end: range.end
+        }
+    }
+
     pub fn substring_for_char(&self, char_index: usize) -> AttributedSubstring {
         AttributedSubstring {
             attributed_string: self,
@@ -151,9 +372,15 @@ impl AttributedString {
         self.text.string().chars()
     }
 
+    /// Iterates over the (extended) grapheme clusters of the string. The
+    /// index of each grapheme matches the index used for attributes.
+    pub fn graphemes(&self) -> Graphemes<'_> {
+        self.text.string().graphemes(true)
+    }
+
     pub fn set_default_attribute(&self, key: Key, attribute: Attribute) {
-        let mut default_attributes = self.default_attributes.borrow_mut();
-        default_attributes.insert(key, attribute);
+        self.default_attributes.borrow_mut().insert(key, attribute);
+        self.did_change();
     }
 
     pub fn set_attribute_for(&self, index: usize, key: Key, attribute: Attribute) {
@@ -164,9 +391,56 @@ impl AttributedString {
         }
 
         attributes[index].insert(key, attribute);
+        drop(attributes);
+        self.did_change();
     }
 
+    /// Sets `attribute` for each character in `range`.
+    pub fn set_attribute_in_range(&self, range: std::ops::Range<usize>, key: Key, attribute: Attribute) {
+        let mut attributes = self.attributes.borrow_mut();
+
+        if range.end > self.text.len() {
+            panic!("Range {:?} out of bounds for length {}", range, self.text.len());
+        }
+
+        for index in range {
+            attributes[index].insert(key.clone(), attribute.clone());
+        }
+        drop(attributes);
+        self.did_change();
+    }
+
+    /// Removes `key` from the characters in `range`, so they use the default
+    /// attribute again.
+    pub fn remove_attribute_in_range(&self, range: std::ops::Range<usize>, key: Key) {
+        let mut attributes = self.attributes.borrow_mut();
+
+        if range.end > self.text.len() {
+            panic!("Range {:?} out of bounds for length {}", range, self.text.len());
+        }
+
+        for index in range {
+            attributes[index].remove(&key);
+        }
+        drop(attributes);
+        self.did_change();
+    }
+
+    /// Returns the attribute for the character at `index`, falling back to
+    /// the default attribute.
+    ///
+    /// Panics if there is no default for `key` and the character doesn't
+    /// have it set (e.g. `Key::Link`). See `get_attribute_if_set_for`.
     pub fn get_attribute_for(&self, index: usize, key: Key) -> Ref<'_, Attribute> {
+        match self.get_attribute_if_set_for(index, key.clone()) {
+            Some(attribute) => attribute,
+            None => panic!("No attribute or default attribute for {:?}", key)
+        }
+    }
+
+    /// Like `get_attribute_for`, but returns `None` rather than panicking
+    /// when neither the character nor the defaults have the attribute.
+    pub fn get_attribute_if_set_for(&self, index: usize, key: Key) -> Option<Ref<'_, Attribute>> {
         let attributes = self.attributes.borrow();
 
         if index >= attributes.len() {
@@ -174,50 +448,196 @@ impl AttributedString {
         }
 
         if attributes[index].get(&key).is_some() {
-            Ref::map(attributes, |attrs| attrs[index].get(&key).unwrap())
+            Some(Ref::map(attributes, |attrs| attrs[index].get(&key).unwrap()))
+        } else if self.default_attributes.borrow().get(&key).is_some() {
+            Some(self.default_attribute(key))
         } else {
-            self.default_attribute(key)
+            None
         }
     }
 
+    /// Calls `block` for each run of characters within `range` that share the
+    /// same value for the attribute `key`, along with the longest effective
+    /// range of that value (clipped to `range`).
+    ///
+    /// Characters without the attribute set use the default attribute. If
+    /// there is no default either (e.g. `Key::Link`), those characters are
+    /// skipped.
+    ///
+    /// The runs are calculated before `block` is first called, so it's safe
+    /// for `block` to set attributes on this string.
+    pub fn enumerate_attribute<F>(&self, key: Key, range: std::ops::Range<usize>, mut block: F)
+    where F: FnMut(&Attribute, std::ops::Range<usize>) {
+        if range.end > self.len() {
+            panic!("Range {:?} out of bounds for length {}", range, self.len());
+        }
+
+        let mut runs: Vec<(Attribute, std::ops::Range<usize>)> = Vec::new();
+
+        for index in range {
+            let attribute = match self.get_attribute_if_set_for(index, key.clone()) {
+                Some(attribute) => attribute,
+                None => continue
+            };
+
+            if let Some((value, effective_range)) = runs.last_mut() {
+                if *value == *attribute && effective_range.end == index {
+                    effective_range.end = index + 1;
+                    continue;
+                }
+            }
+
+            runs.push((attribute.clone(), index..index + 1));
+        }
+
+        for (value, effective_range) in runs {
+            block(&value, effective_range);
+        }
+    }
+
+    /// All attributes that apply to the character at `index`; i.e. the
+    /// attributes set on the character, plus any defaults it doesn't
+    /// override.
+    pub fn effective_attributes_for(&self, index: usize) -> HashMap<Key, Attribute> {
+        let attributes = self.attributes.borrow();
+
+        if index >= attributes.len() {
+            panic!("Index out of bounds. Attempted {}, but length is {} / {}", index, attributes.len(), self.text.string());
+        }
+
+        let mut effective_attributes = self.default_attributes.borrow().clone();
+        for (key, attribute) in attributes[index].iter() {
+            effective_attributes.insert(key.clone(), attribute.clone());
+        }
+
+        effective_attributes
+    }
+
     pub fn default_attribute(&self, key: Key) -> Ref<'_, Attribute> {
         let default_attributes = self.default_attributes.borrow();
         Ref::map(default_attributes, |attrs| attrs.get(&key).unwrap())
     }
 
     pub fn replace_range(&mut self, range: std::ops::Range<usize>, string: &str) {
-        let mut attributes = self.attributes.borrow_mut();
         let start = range.start;
         let end = range.end;
 
         self.text.replace_range(range, string);
 
-        for _ in start..end {
-            attributes.remove(start);
+        {
+            let mut attributes = self.attributes.borrow_mut();
+            for _ in start..end {
+                attributes.remove(start);
+            }
         }
 
-        let text = Text::from(string);
-
-        let mut new_attributes = Vec::new();
-        for _ in 0..text.len() {
-            new_attributes.push(AttributeContainer::new());
-        }
+        // As with `insert_str`, the replacement may merge with surrounding
+        // graphemes, so only the difference in length is added.
+        self.reconcile_attributes_at(start);
+        self.did_change();
+    }
+}
 
-        for i in start..start + text.len() {
-            attributes.insert(i, new_attributes.remove(0));
-        }
+impl Drop for AttributedString {
+    fn drop(&mut self) {
+        LayoutCache::invalidate(self.id);
     }
 }
 
-impl AttributedSubstring<'_> {
+/// Iterates the effective attributes of each character in an
+/// `AttributedSubstring`. See `AttributedSubstring::attributes`.
+pub struct Attributes<'a> {
+    attributed_string: &'a AttributedString,
+    index: usize,
+    end: usize
+}
+
+impl<'a> AttributedSubstring<'a> {
     pub fn text(&self) -> &str {
         &self.attributed_string.text[self.start..self.end]
     }
 
-    pub fn chars(&self) -> std::str::Chars {
+    /// The range of the substring within the original `AttributedString`.
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+
+    /// The number of graphemes in the substring.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Iterates the effective attributes (see
+    /// `AttributedString::effective_attributes_for`) for each character of
+    /// the substring, in order. The first item belongs to the first
+    /// character of the substring, rather than of the original string.
+    ///
+    /// Attributes are looked up as the iterator advances, so the iterator
+    /// reflects attributes set after it was created.
+    pub fn attributes(&self) -> Attributes<'a> {
+        Attributes {
+            attributed_string: self.attributed_string,
+            index: self.start,
+            end: self.end
+        }
+    }
+
+    /// See `AttributedString::words`. The substring is segmented on its
+    /// own, so a word cut off by the substring's range is still returned.
+    /// Ranges are relative to the original string.
+    pub fn words(&self) -> impl Iterator<Item = AttributedSubstring<'a>> {
+        let attributed_string = self.attributed_string;
+        let start = self.start;
+
+        word_boundary::word_segments(&Text::from(self.text()))
+            .into_iter()
+            .filter(|(_, kind)| *kind == SegmentKind::Word)
+            .map(move |(range, _)| attributed_string.substring(start + range.start..start + range.end))
+    }
+
+    /// See `AttributedString::sentences`. Ranges are relative to the
+    /// original string.
+    pub fn sentences(&self) -> impl Iterator<Item = AttributedSubstring<'a>> {
+        let attributed_string = self.attributed_string;
+        let start = self.start;
+
+        word_boundary::sentence_ranges(&Text::from(self.text()))
+            .into_iter()
+            .map(move |range| attributed_string.substring(start + range.start..start + range.end))
+    }
+
+    /// Copies the substring, including its attributes and the default
+    /// attributes of the original string, into a new independent
+    /// `AttributedString`.
+    pub fn to_owned(&self) -> AttributedString {
+        let attributed_string = AttributedString::new(self.text().to_string());
+
+        for (key, attribute) in self.attributed_string.default_attributes.borrow().iter() {
+            attributed_string.set_default_attribute(key.clone(), attribute.clone());
+        }
+
+        {
+            let source = self.attributed_string.attributes.borrow();
+            let mut destination = attributed_string.attributes.borrow_mut();
+            destination.clone_from_slice(&source[self.start..self.end]);
+        }
+
+        attributed_string
+    }
+
+    pub fn chars(&self) -> std::str::Chars<'_> {
         self.text().chars()
     }
 
+    /// Iterates over the grapheme clusters of the substring.
+    pub fn graphemes(&self) -> Graphemes<'_> {
+        self.text().graphemes(true)
+    }
+
     pub fn set_attribute_for(&self, index: usize, key: Key, attribute: Attribute) {
         self.attributed_string.set_attribute_for(self.start + index, key, attribute);
     }
@@ -226,9 +646,67 @@ impl AttributedSubstring<'_> {
         self.attributed_string.get_attribute_for(self.start + index, key)
     }
 
-    pub fn substring_for_char(&self, char_index: usize) -> AttributedSubstring {
+    pub fn get_attribute_if_set_for(&self, index: usize, key: Key) -> Option<Ref<'_, Attribute>> {
+        self.attributed_string.get_attribute_if_set_for(self.start + index, key)
+    }
+
+    pub fn effective_attributes_for(&self, index: usize) -> HashMap<Key, Attribute> {
+        self.attributed_string.effective_attributes_for(self.start + index)
+    }
+
+    pub fn substring_for_char(&self, char_index: usize) -> AttributedSubstring<'_> {
         self.attributed_string.substring_for_char(self.start + char_index)
     }
+
+    /// See `AttributedString::enumerate_attribute`. Both `range` and the
+    /// effective ranges are relative to the start of this substring.
+    pub fn enumerate_attribute<F>(&self, key: Key, range: std::ops::Range<usize>, mut block: F)
+    where F: FnMut(&Attribute, std::ops::Range<usize>) {
+        if range.end > self.end - self.start {
+            panic!("Range {:?} out of bounds for substring length {}", range, self.end - self.start);
+        }
+
+        let start = self.start;
+        self.attributed_string.enumerate_attribute(key, start + range.start..start + range.end, |value, effective_range| {
+            block(value, effective_range.start - start..effective_range.end - start);
+        });
+    }
+}
+
+impl Iterator for Attributes<'_> {
+    type Item = HashMap<Key, Attribute>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end {
+            return None;
+        }
+
+        let attributes = self.attributed_string.effective_attributes_for(self.index);
+        self.index += 1;
+        Some(attributes)
+    }
+}
+
+/// Two substrings are equal if they have the same text, and each character
+/// has the same effective attributes. Where the substrings came from, and
+/// whether an attribute was set explicitly or as a default, is ignored.
+impl PartialEq for AttributedSubstring<'_> {
+    fn eq(&self, other: &AttributedSubstring) -> bool {
+        self.text() == other.text() && self.attributes().eq(other.attributes())
+    }
+}
+
+/// See `AttributedSubstring`'s `PartialEq`.
+impl PartialEq for AttributedString {
+    fn eq(&self, other: &AttributedString) -> bool {
+        self.substring(0..self.len()) == other.substring(0..other.len())
+    }
+}
+
+impl std::fmt::Debug for AttributedSubstring<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "AttributedSubstring {{ text: \"{}\", range: {:?} }}", self.text(), self.range())
+    }
 }
 
 impl std::fmt::Debug for AttributedString {
@@ -462,4 +940,266 @@ mod tests {
         assert_eq!(attributed_string.get_attribute_for(12, Key::Color).color(), &Color::BLUE); // l
         assert_eq!(attributed_string.get_attribute_for(13, Key::Color).color(), &Color::BLUE); // d
     }
+
+    #[test]
+    fn test_attributes_are_per_grapheme() {
+        let attributed_string = AttributedString::new("a👨‍👩‍👧é\nb".to_string());
+        assert_eq!(attributed_string.len(), 5);
+        assert_eq!(attributed_string.graphemes().count(), 5);
+        assert_eq!(attributed_string.graphemes().nth(1), Some("👨‍👩‍👧"));
+
+        attributed_string.set_attribute_for(4, Key::Color, Attribute::Color { color: Color::RED });
+        assert_eq!(attributed_string.get_attribute_for(4, Key::Color).color(), &Color::RED);
+        assert_eq!(attributed_string.get_attribute_for(2, Key::Color).color(), &Color::BLACK);
+    }
+
+    #[test]
+    fn test_attribute_in_range() {
+        let attributed_string = AttributedString::new("teh cat".to_string());
+        assert!(!attributed_string.get_attribute_for(0, Key::Misspelled).is_misspelled());
+
+        attributed_string.set_attribute_in_range(0..3, Key::Misspelled, Attribute::Misspelled { is_misspelled: true });
+        assert!(attributed_string.get_attribute_for(2, Key::Misspelled).is_misspelled());
+        assert!(!attributed_string.get_attribute_for(3, Key::Misspelled).is_misspelled());
+
+        let revision = attributed_string.revision();
+        attributed_string.remove_attribute_in_range(1..3, Key::Misspelled);
+        assert!(attributed_string.get_attribute_for(0, Key::Misspelled).is_misspelled());
+        assert!(!attributed_string.get_attribute_for(1, Key::Misspelled).is_misspelled());
+        assert!(attributed_string.revision() > revision);
+    }
+
+    #[test]
+    fn test_lines_with_graphemes() {
+        let attributed_string = AttributedString::new("👨‍👩‍👧\r\nb".to_string());
+        let lines = attributed_string.lines();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text(), "👨‍👩‍👧\r\n");
+        assert_eq!(lines[0].end, 2);
+        assert_eq!(lines[1].text(), "b");
+        assert_eq!(lines[1].graphemes().count(), 1);
+    }
+
+    #[test]
+    fn test_insert_combining_character() {
+        let mut attributed_string = AttributedString::new("ab".to_string());
+        attributed_string.set_attribute_for(1, Key::Color, Attribute::Color { color: Color::RED });
+
+        // A combining acute accent merges with the preceding "a".
+        attributed_string.insert_str(1, "\u{301}");
+        assert_eq!(attributed_string.len(), 2);
+        assert_eq!(attributed_string.get_attribute_for(1, Key::Color).color(), &Color::RED);
+
+        attributed_string.insert_str(1, "👨‍👩‍👧");
+        assert_eq!(attributed_string.len(), 3);
+        assert_eq!(attributed_string.get_attribute_for(1, Key::Color).color(), &Color::BLACK);
+        assert_eq!(attributed_string.get_attribute_for(2, Key::Color).color(), &Color::RED);
+
+        attributed_string.replace_range(1..2, "👩‍👩‍👦x");
+        assert_eq!(attributed_string.text(), &Text::from("a\u{301}👩‍👩‍👦xb"));
+        assert_eq!(attributed_string.len(), 4);
+        assert_eq!(attributed_string.get_attribute_for(3, Key::Color).color(), &Color::RED);
+    }
+
+    #[test]
+    fn test_decoration_attributes() {
+        let attributed_string = AttributedString::new("abc".to_string());
+        assert_eq!(attributed_string.get_attribute_for(0, Key::Underline).underline_style(), LineStyle::None);
+        assert_eq!(attributed_string.get_attribute_for(0, Key::Strikethrough).strikethrough_style(), LineStyle::None);
+        assert_eq!(attributed_string.get_attribute_for(0, Key::BackgroundColor).background_color().a, 0);
+        assert_eq!(attributed_string.get_attribute_for(0, Key::BaselineOffset).baseline_offset(), 0);
+
+        attributed_string.set_attribute_for(1, Key::Underline, Attribute::Underline { style: LineStyle::Double });
+        attributed_string.set_attribute_for(1, Key::Strikethrough, Attribute::Strikethrough { style: LineStyle::Single });
+        attributed_string.set_attribute_for(1, Key::BackgroundColor, Attribute::BackgroundColor { color: Color::YELLOW });
+        attributed_string.set_attribute_for(1, Key::BaselineOffset, Attribute::BaselineOffset { offset: -3 });
+
+        assert_eq!(attributed_string.get_attribute_for(1, Key::Underline).underline_style(), LineStyle::Double);
+        assert_eq!(attributed_string.get_attribute_for(1, Key::Strikethrough).strikethrough_style(), LineStyle::Single);
+        assert_eq!(attributed_string.get_attribute_for(1, Key::BackgroundColor).background_color(), &Color::YELLOW);
+        assert_eq!(attributed_string.get_attribute_for(1, Key::BaselineOffset).baseline_offset(), -3);
+        assert_eq!(attributed_string.get_attribute_for(2, Key::Underline).underline_style(), LineStyle::None);
+    }
+
+    #[test]
+    fn test_new_matching_default_style_copies_all_defaults() {
+        let existing = AttributedString::new("abc".to_string());
+        existing.set_default_attribute(Key::Underline, Attribute::Underline { style: LineStyle::Thick });
+        existing.set_default_attribute(Key::Color, Attribute::Color { color: Color::RED });
+
+        let matching = AttributedString::new_matching_default_style("def".to_string(), &existing);
+        assert_eq!(matching.get_attribute_for(0, Key::Underline).underline_style(), LineStyle::Thick);
+        assert_eq!(matching.get_attribute_for(0, Key::Color).color(), &Color::RED);
+    }
+
+    #[test]
+    fn test_enumerate_attribute() {
+        let attributed_string = AttributedString::new("Hello, world!".to_string());
+        for i in 7..12 {
+            attributed_string.set_attribute_for(i, Key::Color, Attribute::Color { color: Color::RED });
+        }
+        // Explicitly setting the default value merges with the default run.
+        attributed_string.set_attribute_for(1, Key::Color, Attribute::Color { color: Color::BLACK });
+
+        let mut runs = Vec::new();
+        attributed_string.enumerate_attribute(Key::Color, 0..13, |value, range| {
+            runs.push((value.color().clone(), range));
+        });
+
+        assert_eq!(runs, vec![
+            (Color::BLACK, 0..7),
+            (Color::RED, 7..12),
+            (Color::BLACK, 12..13)
+        ]);
+
+        let mut runs = Vec::new();
+        attributed_string.enumerate_attribute(Key::Color, 9..13, |value, range| {
+            runs.push((value.color().clone(), range));
+        });
+        assert_eq!(runs, vec![(Color::RED, 9..12), (Color::BLACK, 12..13)]);
+
+        let mut count = 0;
+        attributed_string.enumerate_attribute(Key::Color, 3..3, |_, _| count += 1);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_enumerate_attribute_allows_mutation() {
+        let attributed_string = AttributedString::new("abcd".to_string());
+        attributed_string.set_attribute_for(2, Key::Underline, Attribute::Underline { style: LineStyle::Single });
+
+        attributed_string.enumerate_attribute(Key::Underline, 0..4, |value, range| {
+            if value.underline_style() == LineStyle::Single {
+                for i in range {
+                    attributed_string.set_attribute_for(i, Key::Color, Attribute::Color { color: Color::BLUE });
+                }
+            }
+        });
+
+        assert_eq!(attributed_string.get_attribute_for(2, Key::Color).color(), &Color::BLUE);
+        assert_eq!(attributed_string.get_attribute_for(3, Key::Color).color(), &Color::BLACK);
+    }
+
+    #[test]
+    fn test_substring_enumerate_attribute() {
+        let attributed_string = AttributedString::new("ab\ncd".to_string());
+        attributed_string.set_attribute_for(4, Key::Color, Attribute::Color { color: Color::RED });
+
+        let lines = attributed_string.lines();
+        let mut runs = Vec::new();
+        lines[1].enumerate_attribute(Key::Color, 0..2, |value, range| {
+            runs.push((value.color().clone(), range));
+        });
+
+        assert_eq!(runs, vec![(Color::BLACK, 0..1), (Color::RED, 1..2)]);
+    }
+
+    #[test]
+    fn test_link_attribute() {
+        let attributed_string = AttributedString::new("see docs here".to_string());
+        for i in 4..8 {
+            attributed_string.set_attribute_for(i, Key::Link, Attribute::Link { url: "https://example.com".to_string() });
+        }
+        attributed_string.set_attribute_for(12, Key::Link, Attribute::Link { url: "https://example.com".to_string() });
+
+        assert!(attributed_string.get_attribute_if_set_for(0, Key::Link).is_none());
+        assert_eq!(attributed_string.get_attribute_if_set_for(4, Key::Link).unwrap().link(), "https://example.com");
+        assert!(attributed_string.get_attribute_if_set_for(0, Key::Color).is_some());
+
+        // Separate ranges with the same value are not merged.
+        let mut runs = Vec::new();
+        attributed_string.enumerate_attribute(Key::Link, 0..13, |value, range| {
+            runs.push((value.link().to_string(), range));
+        });
+        assert_eq!(runs, vec![
+            ("https://example.com".to_string(), 4..8),
+            ("https://example.com".to_string(), 12..13)
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "No attribute or default attribute for Link")]
+    fn test_get_attribute_for_without_default() {
+        let attributed_string = AttributedString::new("abc".to_string());
+        attributed_string.get_attribute_for(0, Key::Link);
+    }
+
+    #[test]
+    fn test_substring_attributes() {
+        let attributed_string = AttributedString::new("ab\ncd".to_string());
+        attributed_string.set_attribute_for(4, Key::Color, Attribute::Color { color: Color::RED });
+
+        let lines = attributed_string.lines();
+        let attributes: Vec<HashMap<Key, Attribute>> = lines[1].attributes().collect();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[0].get(&Key::Color).unwrap().color(), &Color::BLACK);
+        assert_eq!(attributes[1].get(&Key::Color).unwrap().color(), &Color::RED);
+        assert!(attributes[1].get(&Key::Font).is_some());
+        assert!(attributes[1].get(&Key::Link).is_none());
+    }
+
+    #[test]
+    fn test_substring_to_owned() {
+        let attributed_string = AttributedString::new("one two".to_string());
+        attributed_string.set_default_attribute(Key::Color, Attribute::Color { color: Color::GREEN });
+        attributed_string.set_attribute_for(5, Key::Color, Attribute::Color { color: Color::RED });
+
+        let owned = attributed_string.substring(4..7).to_owned();
+        assert_eq!(owned.text(), &Text::from("two"));
+        assert_eq!(owned.get_attribute_for(0, Key::Color).color(), &Color::GREEN);
+        assert_eq!(owned.get_attribute_for(1, Key::Color).color(), &Color::RED);
+
+        // The copy is independent of the original.
+        owned.set_attribute_for(0, Key::Color, Attribute::Color { color: Color::BLUE });
+        assert_eq!(attributed_string.get_attribute_for(4, Key::Color).color(), &Color::GREEN);
+    }
+
+    #[test]
+    fn test_substring_equality() {
+        let attributed_string = AttributedString::new("abab".to_string());
+        assert_eq!(attributed_string.substring(0..2), attributed_string.substring(2..4));
+        assert_ne!(attributed_string.substring(0..2), attributed_string.substring(1..3));
+
+        attributed_string.set_attribute_for(3, Key::Color, Attribute::Color { color: Color::RED });
+        assert_ne!(attributed_string.substring(0..2), attributed_string.substring(2..4));
+
+        // An explicit attribute equal to the default is the same content.
+        let other = AttributedString::new("ab".to_string());
+        other.set_attribute_for(0, Key::Color, Attribute::Color { color: Color::BLACK });
+        assert_eq!(other, attributed_string.substring(0..2).to_owned());
+        assert_ne!(other, attributed_string);
+    }
+
+    #[test]
+    fn test_words() {
+        let attributed_string = AttributedString::new("Hello, wörld! 👨‍👩‍👧 it's".to_string());
+        let words: Vec<(String, std::ops::Range<usize>)> = attributed_string
+            .words()
+            .map(|word| (word.text().to_string(), word.range()))
+            .collect();
+
+        assert_eq!(words, vec![
+            ("Hello".to_string(), 0..5),
+            ("wörld".to_string(), 7..12),
+            ("it's".to_string(), 16..20)
+        ]);
+
+        let lines = attributed_string.lines();
+        let substring = lines[0].substring_for_char(8);
+        assert_eq!(substring.words().map(|word| word.range()).collect::<Vec<_>>(), vec![8..9]);
+    }
+
+    #[test]
+    fn test_sentences() {
+        let attributed_string = AttributedString::new("One. Two?\nThree".to_string());
+        attributed_string.set_attribute_for(5, Key::Color, Attribute::Color { color: Color::RED });
+
+        let sentences: Vec<AttributedSubstring> = attributed_string.sentences().collect();
+        assert_eq!(sentences.len(), 3);
+        assert_eq!(sentences[0].text(), "One. ");
+        assert_eq!(sentences[1].text(), "Two?\n");
+        assert_eq!(sentences[1].range(), 5..10);
+        assert_eq!(sentences[1].get_attribute_for(0, Key::Color).color(), &Color::RED);
+        assert_eq!(sentences[2].text(), "Three");
+    }
 }
@@ -0,0 +1,447 @@
+use std::ops::Range;
+
+/// The bidirectional character types from the Unicode Bidirectional
+/// Algorithm (UAX #9) that affect layout here. Explicit embeddings,
+/// overrides and isolates aren't supported, so their formatting characters
+/// are treated as boundary neutrals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BidiClass {
+    /// Left-to-right, e.g. Latin letters.
+    L,
+    /// Right-to-left, e.g. Hebrew letters.
+    R,
+    /// Right-to-left Arabic letters.
+    AL,
+    /// European numbers, e.g. ASCII digits.
+    EN,
+    /// European number separators, "+" and "-".
+    ES,
+    /// European number terminators, e.g. "%" and currency symbols.
+    ET,
+    /// Arabic-Indic digits.
+    AN,
+    /// Common number separators, e.g. "," and ".".
+    CS,
+    /// Non-spacing (combining) marks.
+    NSM,
+    /// Paragraph separators, e.g. newlines.
+    B,
+    /// Segment separators, i.e. tabs.
+    S,
+    /// Whitespace.
+    WS,
+    /// Other neutrals, e.g. punctuation, symbols and emoji.
+    ON
+}
+
+impl BidiClass {
+    fn is_strong(self) -> bool {
+        matches!(self, BidiClass::L | BidiClass::R | BidiClass::AL)
+    }
+
+    fn is_neutral(self) -> bool {
+        matches!(self, BidiClass::B | BidiClass::S | BidiClass::WS | BidiClass::ON)
+    }
+
+    /// The strong direction used when resolving neutrals (N1), where numbers
+    /// act as right-to-left text.
+    fn direction_for_neutrals(self) -> Option<BidiClass> {
+        match self {
+            BidiClass::L => Some(BidiClass::L),
+            BidiClass::R | BidiClass::AL | BidiClass::EN | BidiClass::AN => Some(BidiClass::R),
+            _ => None
+        }
+    }
+}
+
+/// The bidirectional type of a character. This is an approximation of the
+/// Unicode Character Database covering the common scripts and punctuation.
+pub fn bidi_class(character: char) -> BidiClass {
+    match character as u32 {
+        0x200E => BidiClass::L,
+        0x200F => BidiClass::R,
+        0x061C => BidiClass::AL,
+        0x000A | 0x000D | 0x001C..=0x001E | 0x0085 | 0x2029 => BidiClass::B,
+        0x0009 | 0x000B | 0x001F => BidiClass::S,
+        0x0030..=0x0039 | 0x00B2 | 0x00B3 | 0x00B9 | 0x06F0..=0x06F9 => BidiClass::EN,
+        0x0660..=0x0669 | 0x066B | 0x066C => BidiClass::AN,
+        0x002B | 0x002D => BidiClass::ES,
+        0x0023..=0x0025 | 0x00A2..=0x00A5 | 0x00B0 | 0x066A | 0x20A0..=0x20CF => BidiClass::ET,
+        0x002C | 0x002E | 0x002F | 0x003A | 0x00A0 => BidiClass::CS,
+        0x0300..=0x036F | 0x0591..=0x05BD | 0x05BF | 0x05C1 | 0x05C2 | 0x05C4 | 0x05C5 | 0x05C7 |
+        0x0610..=0x061A | 0x064B..=0x065F | 0x0670 | 0x06D6..=0x06DC | 0x06DF..=0x06E4 |
+        0x06E7 | 0x06E8 | 0x06EA..=0x06ED | 0x20D0..=0x20FF | 0xFE00..=0xFE0F => BidiClass::NSM,
+        0x0590..=0x05FF | 0x07C0..=0x085F | 0xFB1D..=0xFB4F | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF => BidiClass::R,
+        0x0600..=0x07BF | 0x0860..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => BidiClass::AL,
+        _ if character.is_whitespace() => BidiClass::WS,
+        _ if character.is_alphabetic() => BidiClass::L,
+        _ => BidiClass::ON
+    }
+}
+
+/// The type of a grapheme cluster, which is the type of its first character.
+/// Combining marks therefore take the type of the character they're
+/// attached to.
+fn grapheme_class(grapheme: &str) -> BidiClass {
+    match grapheme.chars().next() {
+        Some(character) => bidi_class(character),
+        None => BidiClass::ON
+    }
+}
+
+/// The resolved embedding levels of some text, one per grapheme cluster.
+/// Even levels are left-to-right and odd levels right-to-left.
+///
+/// Paragraphs (separated by newlines) are resolved independently, each with
+/// a base level from its first strong character (rules P2 and P3).
+pub struct BidiInfo {
+    classes: Vec<BidiClass>,
+    levels: Vec<u8>,
+    paragraph_levels: Vec<u8>
+}
+
+impl BidiInfo {
+    pub fn new<'a>(graphemes: impl Iterator<Item = &'a str>) -> BidiInfo {
+        let classes: Vec<BidiClass> = graphemes.map(grapheme_class).collect();
+        let mut levels = Vec::with_capacity(classes.len());
+        let mut paragraph_levels = Vec::with_capacity(classes.len());
+
+        let mut start = 0;
+        while start < classes.len() {
+            let end = classes[start..]
+                .iter()
+                .position(|class| *class == BidiClass::B)
+                .map(|offset| start + offset + 1)
+                .unwrap_or_else(|| classes.len());
+
+            let paragraph = &classes[start..end];
+            let paragraph_level = paragraph_level(paragraph);
+            levels.extend(resolve_paragraph(paragraph, paragraph_level));
+            paragraph_levels.resize(paragraph_levels.len() + paragraph.len(), paragraph_level);
+
+            start = end;
+        }
+
+        BidiInfo { classes, levels, paragraph_levels }
+    }
+
+    pub fn levels(&self) -> &[u8] {
+        &self.levels
+    }
+
+    /// Whether the grapheme at `index` is laid out right-to-left.
+    pub fn is_right_to_left(&self, index: usize) -> bool {
+        self.levels.get(index).map(|level| level % 2 == 1).unwrap_or(false)
+    }
+
+    /// Whether the paragraph containing the grapheme at `index` has a
+    /// right-to-left base direction.
+    pub fn is_paragraph_right_to_left(&self, index: usize) -> bool {
+        self.paragraph_levels.get(index).map(|level| level % 2 == 1).unwrap_or(false)
+    }
+
+    /// Whether any of the text needs reordering at all.
+    pub fn has_right_to_left(&self) -> bool {
+        self.levels.iter().any(|level| *level > 0)
+    }
+
+    /// The levels of the graphemes in `line`, a range of a single visual
+    /// line, after trailing whitespace and separators are reset to the
+    /// paragraph level (rule L1).
+    pub fn line_levels(&self, line: Range<usize>) -> Vec<u8> {
+        let mut levels = self.levels[line.clone()].to_vec();
+        let classes = &self.classes[line.clone()];
+        let paragraph_levels = &self.paragraph_levels[line];
+
+        // Walking backwards, whitespace at the end of the line or before a
+        // separator is reset along with the separator itself.
+        let mut resetting = true;
+        for index in (0..levels.len()).rev() {
+            match classes[index] {
+                BidiClass::B | BidiClass::S => {
+                    levels[index] = paragraph_levels[index];
+                    resetting = true;
+                },
+                BidiClass::WS if resetting => levels[index] = paragraph_levels[index],
+                _ => resetting = false
+            }
+        }
+
+        levels
+    }
+
+    /// The indexes of the graphemes in `line` in the order they appear from
+    /// left to right (rule L2).
+    pub fn visual_order(&self, line: Range<usize>) -> Vec<usize> {
+        let levels = self.line_levels(line.clone());
+        visual_order_for_levels(&levels).into_iter().map(|index| line.start + index).collect()
+    }
+}
+
+/// The base level of a paragraph: 1 if its first strong character is
+/// right-to-left, otherwise 0.
+fn paragraph_level(classes: &[BidiClass]) -> u8 {
+    match classes.iter().find(|class| class.is_strong()) {
+        Some(BidiClass::R) | Some(BidiClass::AL) => 1,
+        _ => 0
+    }
+}
+
+/// Resolves the level of each character in a single paragraph using the
+/// weak type (W1-W7), neutral type (N1, N2) and implicit level (I1, I2)
+/// rules.
+fn resolve_paragraph(classes: &[BidiClass], paragraph_level: u8) -> Vec<u8> {
+    let embedding_direction = if paragraph_level % 2 == 1 { BidiClass::R } else { BidiClass::L };
+    let mut types = classes.to_vec();
+
+    // W1: Non-spacing marks take the type of the previous character.
+    for index in 0..types.len() {
+        if types[index] == BidiClass::NSM {
+            types[index] = if index == 0 { embedding_direction } else { types[index - 1] };
+        }
+    }
+
+    // W2: European numbers after Arabic letters are Arabic numbers. W3:
+    // Arabic letters are then simply right-to-left.
+    let mut last_strong = embedding_direction;
+    for class in types.iter_mut() {
+        match *class {
+            BidiClass::EN if last_strong == BidiClass::AL => *class = BidiClass::AN,
+            strong if strong.is_strong() => last_strong = strong,
+            _ => ()
+        }
+    }
+
+    for class in types.iter_mut() {
+        if *class == BidiClass::AL {
+            *class = BidiClass::R;
+        }
+    }
+
+    // W4: A single separator between two numbers of the same type joins
+    // them.
+    for index in 1..types.len().saturating_sub(1) {
+        let (before, after) = (types[index - 1], types[index + 1]);
+
+        types[index] = match (before, types[index], after) {
+            (BidiClass::EN, BidiClass::ES, BidiClass::EN) => BidiClass::EN,
+            (BidiClass::EN, BidiClass::CS, BidiClass::EN) => BidiClass::EN,
+            (BidiClass::AN, BidiClass::CS, BidiClass::AN) => BidiClass::AN,
+            (_, class, _) => class
+        };
+    }
+
+    // W5: Terminators next to European numbers become part of them.
+    let mut index = 0;
+    while index < types.len() {
+        if types[index] != BidiClass::ET {
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+        while index < types.len() && types[index] == BidiClass::ET {
+            index += 1;
+        }
+
+        let touches_number = (start > 0 && types[start - 1] == BidiClass::EN) ||
+            (index < types.len() && types[index] == BidiClass::EN);
+
+        if touches_number {
+            for class in &mut types[start..index] {
+                *class = BidiClass::EN;
+            }
+        }
+    }
+
+    // W6: Any remaining separators and terminators are neutral.
+    for class in types.iter_mut() {
+        if matches!(*class, BidiClass::ES | BidiClass::ET | BidiClass::CS) {
+            *class = BidiClass::ON;
+        }
+    }
+
+    // W7: European numbers in left-to-right text are left-to-right.
+    let mut last_strong = embedding_direction;
+    for class in types.iter_mut() {
+        match *class {
+            BidiClass::EN if last_strong == BidiClass::L => *class = BidiClass::L,
+            BidiClass::L | BidiClass::R => last_strong = *class,
+            _ => ()
+        }
+    }
+
+    // N1 and N2: Neutrals between text of the same direction take that
+    // direction, otherwise the paragraph's direction.
+    let mut index = 0;
+    while index < types.len() {
+        if !types[index].is_neutral() {
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+        while index < types.len() && types[index].is_neutral() {
+            index += 1;
+        }
+
+        let before = if start == 0 {
+            embedding_direction
+        } else {
+            types[start - 1].direction_for_neutrals().unwrap_or(embedding_direction)
+        };
+
+        let after = if index == types.len() {
+            embedding_direction
+        } else {
+            types[index].direction_for_neutrals().unwrap_or(embedding_direction)
+        };
+
+        let direction = if before == after { before } else { embedding_direction };
+
+        for class in &mut types[start..index] {
+            *class = direction;
+        }
+    }
+
+    // I1 and I2: Levels from the resolved types.
+    types
+        .iter()
+        .map(|class| {
+            match (paragraph_level % 2 != 1, class) {
+                (true, BidiClass::R) => paragraph_level + 1,
+                (true, BidiClass::AN) | (true, BidiClass::EN) => paragraph_level + 2,
+                (false, BidiClass::L) | (false, BidiClass::AN) | (false, BidiClass::EN) => paragraph_level + 1,
+                _ => paragraph_level
+            }
+        })
+        .collect()
+}
+
+/// Reverses every run at each level from the highest down to the lowest odd
+/// level, returning the indexes into `levels` in visual order.
+pub fn visual_order_for_levels(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+
+    let highest = match levels.iter().max() {
+        Some(highest) => *highest,
+        None => return order
+    };
+
+    let lowest_odd = match levels.iter().filter(|level| *level % 2 == 1).min() {
+        Some(lowest_odd) => *lowest_odd,
+        None => return order
+    };
+
+    for level in (lowest_odd..=highest).rev() {
+        let mut index = 0;
+
+        while index < order.len() {
+            if levels[order[index]] < level {
+                index += 1;
+                continue;
+            }
+
+            let start = index;
+            while index < order.len() && levels[order[index]] >= level {
+                index += 1;
+            }
+
+            order[start..index].reverse();
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    fn info(text: &str) -> BidiInfo {
+        BidiInfo::new(text.graphemes(true))
+    }
+
+    fn visual(text: &str) -> String {
+        let graphemes: Vec<&str> = text.graphemes(true).collect();
+        let info = BidiInfo::new(graphemes.iter().copied());
+        info.visual_order(0..graphemes.len()).iter().map(|index| graphemes[*index]).collect()
+    }
+
+    #[test]
+    fn test_bidi_class() {
+        assert_eq!(bidi_class('a'), BidiClass::L);
+        assert_eq!(bidi_class('日'), BidiClass::L);
+        assert_eq!(bidi_class('א'), BidiClass::R);
+        assert_eq!(bidi_class('ب'), BidiClass::AL);
+        assert_eq!(bidi_class('٣'), BidiClass::AN);
+        assert_eq!(bidi_class('3'), BidiClass::EN);
+        assert_eq!(bidi_class(' '), BidiClass::WS);
+        assert_eq!(bidi_class('\n'), BidiClass::B);
+        assert_eq!(bidi_class('!'), BidiClass::ON);
+    }
+
+    #[test]
+    fn test_left_to_right_is_unchanged() {
+        let info = info("Hello, world!");
+        assert!(!info.has_right_to_left());
+        assert_eq!(visual("Hello, world!"), "Hello, world!");
+    }
+
+    #[test]
+    fn test_right_to_left_is_reversed() {
+        let info = info("שלום");
+        assert_eq!(info.levels(), &[1, 1, 1, 1]);
+        assert!(info.is_paragraph_right_to_left(0));
+        assert_eq!(visual("שלום"), "םולש");
+    }
+
+    #[test]
+    fn test_mixed_direction() {
+        // Right-to-left words inside left-to-right text keep their position,
+        // with the space between them taking the surrounding direction.
+        assert_eq!(visual("abc אב גד def"), "abc דג בא def");
+        assert_eq!(info("abc אב גד def").levels()[6], 1);
+
+        // Left-to-right words inside right-to-left text.
+        assert_eq!(visual("אב abc de גד"), "דג abc de בא");
+    }
+
+    #[test]
+    fn test_numbers_in_right_to_left_text() {
+        // Numbers stay left-to-right, with their separators.
+        assert_eq!(visual("אב 12.5 גד"), "דג 12.5 בא");
+        assert_eq!(info("אב 12").levels(), &[1, 1, 1, 2, 2]);
+
+        // After Arabic letters, European digits become Arabic numbers.
+        assert_eq!(info("ب 1").levels(), &[1, 1, 2]);
+    }
+
+    #[test]
+    fn test_paragraphs_have_their_own_direction() {
+        let info = info("אב\nab");
+        assert!(info.is_paragraph_right_to_left(0));
+        assert!(info.is_paragraph_right_to_left(2));
+        assert!(!info.is_paragraph_right_to_left(3));
+        assert_eq!(info.levels(), &[1, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_trailing_whitespace_takes_paragraph_level() {
+        let info = info("ab אב ");
+        assert_eq!(info.levels()[5], 0);
+        assert_eq!(info.line_levels(3..6), vec![1, 1, 0]);
+
+        let info = self::info("אב ab ");
+        assert_eq!(info.line_levels(0..6), vec![1, 1, 1, 2, 2, 1]);
+        assert_eq!(info.visual_order(0..6), vec![5, 3, 4, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_visual_order_for_levels() {
+        assert_eq!(visual_order_for_levels(&[0, 0, 1, 1, 0]), vec![0, 1, 3, 2, 4]);
+        assert_eq!(visual_order_for_levels(&[1, 2, 2, 1]), vec![3, 1, 2, 0]);
+        assert_eq!(visual_order_for_levels(&[]), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,672 @@
+use crate::graphics::{Font, Color, Point, Rectangle, Size};
+use crate::text::attributed_string::{AttributedString, Attribute, Key, Kerning};
+use crate::text::shaping;
+use crate::text::rendering::{self, Character, WholeText};
+use crate::text::{HorizontalAlignment, VerticalAlignment};
+use crate::macros::trace_span;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+use uuid::Uuid;
+
+/// A run of consecutive characters on a single line that share all of their
+/// attributes, so can be drawn in one go.
+pub struct GlyphRun {
+    /// The grapheme range of the run within the laid out string.
+    range: Range<usize>,
+
+    /// The text of the run, in visual (left to right) order. Trailing
+    /// newlines are not included, as there is nothing to draw for them.
+    text: String,
+
+    /// Where the run is drawn, relative to the top-left of the layout.
+    frame: Rectangle<i32, u32>,
+
+    /// The effective attributes shared by every character in the run.
+    attributes: HashMap<Key, Attribute>,
+
+    /// The pieces of text to draw for the run, each with its x offset from
+    /// the run's origin. Runs using the font's kerning are drawn in one
+    /// piece (with ligatures), otherwise each character is placed
+    /// individually.
+    segments: Vec<(String, i32)>
+}
+
+/// A single visual line of text. A line of the string may produce several
+/// line fragments if it wraps.
+pub struct LineFragment {
+    /// The grapheme range of the fragment within the laid out string,
+    /// including any trailing whitespace or newline.
+    range: Range<usize>,
+
+    /// The position and (visual) size of the line, relative to the top-left
+    /// of the layout. The height includes line spacing.
+    frame: Rectangle<i32, u32>,
+
+    glyph_runs: Vec<GlyphRun>,
+
+    ends_with_newline: bool
+}
+
+/// The result of laying out an `AttributedString`: positioned line fragments,
+/// each made up of glyph runs.
+///
+/// Positions and sizes are in the native resolution, i.e. multiplied by
+/// `render_scale`.
+pub struct Layout {
+    line_fragments: Vec<LineFragment>,
+
+    /// Per character positions, used for carat placement and hit testing.
+    rendering: rendering::Result,
+
+    horizontal_alignment: HorizontalAlignment,
+    render_scale: f32
+}
+
+/// Everything a cached layout depends on. The string's revision is included
+/// so that a stale layout is never returned, even if it somehow wasn't
+/// invalidated.
+#[derive(PartialEq)]
+struct LayoutKey {
+    string_id: Uuid,
+    revision: u64,
+    size: Size<u32>,
+    horizontal_alignment: HorizontalAlignment,
+    vertical_alignment: VerticalAlignment,
+    number_of_lines: usize,
+    render_scale: f32
+}
+
+/// A cache of recently generated layouts, so that redrawing unchanged text
+/// skips measuring and line breaking. Entries are keyed by the string's id
+/// along with the size and options the layout was generated with, and are
+/// discarded when their `AttributedString` is mutated or dropped.
+///
+/// Layouts are reference counted and so can't be shared between threads;
+/// each thread has its own cache.
+pub struct LayoutCache {
+    /// Least recently used first.
+    entries: Vec<(LayoutKey, Rc<Layout>)>
+}
+
+/// The most layouts kept before the least recently used are discarded.
+const LAYOUT_CACHE_CAPACITY: usize = 256;
+
+thread_local! {
+    static LAYOUT_CACHE: RefCell<LayoutCache> = const { RefCell::new(LayoutCache { entries: Vec::new() }) };
+}
+
+impl LayoutCache {
+    /// Discards every cached layout of the string with `string_id`. This is
+    /// called by `AttributedString` whenever it changes.
+    pub fn invalidate(string_id: Uuid) {
+        // The cache may already be gone if this is called while the thread
+        // is shutting down.
+        let _ = LAYOUT_CACHE.try_with(|cache| {
+            cache.borrow_mut().entries.retain(|(key, _)| key.string_id != string_id);
+        });
+    }
+
+    /// Discards every cached layout, e.g. after fonts have been changed.
+    pub fn clear() {
+        LAYOUT_CACHE.with(|cache| cache.borrow_mut().entries.clear());
+    }
+
+    /// The number of layouts currently cached on this thread.
+    pub fn len() -> usize {
+        LAYOUT_CACHE.with(|cache| cache.borrow().entries.len())
+    }
+
+    /// Whether there are no layouts cached on this thread.
+    pub fn is_empty() -> bool {
+        LayoutCache::len() == 0
+    }
+
+    fn get(key: &LayoutKey) -> Option<Rc<Layout>> {
+        LAYOUT_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let position = cache.entries.iter().position(|(existing, _)| existing == key)?;
+
+            // Move the entry to the end, as it's now the most recently used.
+            let entry = cache.entries.remove(position);
+            let layout = entry.1.clone();
+            cache.entries.push(entry);
+            Some(layout)
+        })
+    }
+
+    fn insert(key: LayoutKey, layout: Rc<Layout>) {
+        LAYOUT_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if cache.entries.len() >= LAYOUT_CACHE_CAPACITY {
+                cache.entries.remove(0);
+            }
+            cache.entries.push((key, layout));
+        });
+    }
+}
+
+impl GlyphRun {
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn frame(&self) -> &Rectangle<i32, u32> {
+        &self.frame
+    }
+
+    pub fn attribute(&self, key: Key) -> Option<&Attribute> {
+        self.attributes.get(&key)
+    }
+
+    pub fn font(&self) -> &Font {
+        self.attributes.get(&Key::Font).unwrap().font()
+    }
+
+    pub fn color(&self) -> &Color {
+        self.attributes.get(&Key::Color).unwrap().color()
+    }
+
+    pub fn kerning(&self) -> Kerning {
+        self.attributes.get(&Key::Kerning).unwrap().kerning()
+    }
+
+    pub fn segments(&self) -> &[(String, i32)] {
+        &self.segments
+    }
+}
+
+impl LineFragment {
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    pub fn frame(&self) -> &Rectangle<i32, u32> {
+        &self.frame
+    }
+
+    pub fn glyph_runs(&self) -> &[GlyphRun] {
+        &self.glyph_runs
+    }
+
+    pub fn ends_with_newline(&self) -> bool {
+        self.ends_with_newline
+    }
+}
+
+impl Layout {
+    /// Lays out `attributed_string` within `width` points, wrapping at word
+    /// boundaries, or anywhere within a word if a single word doesn't fit.
+    ///
+    /// The text is aligned to the top-left, and the number of lines isn't
+    /// limited.
+    pub fn new(attributed_string: &AttributedString, width: u32, render_scale: f32) -> Layout {
+        Layout::new_in_frame(
+            attributed_string,
+            Rectangle::new(0, 0, width, 0),
+            HorizontalAlignment::Left,
+            VerticalAlignment::Top,
+            0,
+            render_scale
+        )
+    }
+
+    /// Lays out `attributed_string` within the size of `frame` with the given
+    /// alignment. The origin of `frame` is ignored; the layout is relative to
+    /// its own top-left.
+    ///
+    /// Only the first `number_of_lines` line fragments are laid out, unless
+    /// it's 0, in which case all lines are.
+    pub fn new_in_frame(
+        attributed_string: &AttributedString,
+        frame: Rectangle<i32, u32>,
+        horizontal_alignment: HorizontalAlignment,
+        vertical_alignment: VerticalAlignment,
+        number_of_lines: usize,
+        render_scale: f32
+    ) -> Layout {
+        trace_span!("text_layout", width = frame.size.width, number_of_lines);
+
+        let mut whole_text = WholeText::from(attributed_string, frame, render_scale);
+        whole_text.limit_number_of_lines(number_of_lines);
+        whole_text.align_horizontally(horizontal_alignment);
+        whole_text.align_vertically(vertical_alignment);
+
+        let rendering = whole_text.calculate_character_render_positions();
+
+        let mut line_fragments = Vec::new();
+        let mut index = 0;
+
+        for (line_index, line) in whole_text.lines.iter().enumerate() {
+            let line_position = &whole_text.line_positions[line_index];
+            let line_start = index;
+            let characters: Vec<&Character> = line.words.iter().flat_map(|word| word.characters.iter()).collect();
+            let ends_with_newline = characters.last().map(|character| character.is_newline()).unwrap_or(false);
+            index += characters.len();
+
+            // Runs are built from left to right, so in right-to-left text
+            // each run's text is in visual order and its range is walked
+            // backwards.
+            let mut glyph_runs: Vec<GlyphRun> = Vec::new();
+            let mut previous_was_justified = false;
+
+            for character_index in rendering.visual_order_for_line(line_index) {
+                let character = characters[character_index - line_start];
+                if character.is_newline() {
+                    continue;
+                }
+
+                let x = rendering.origin_for_character_at_index(character_index).unwrap().x;
+                let mut attributes = attributed_string.effective_attributes_for(character_index);
+
+                // Characters the font has no glyphs for are drawn
+                // with a font from the fallback cascade, which
+                // starts a new run.
+                let fallback = attributes[&Key::Font].font().fallback_for(&character.character);
+                attributes.insert(Key::Font, Attribute::Font { font: fallback });
+
+                // A run is drawn at its natural width, so it can't continue
+                // past whitespace widened by justification.
+                let continues_run = match glyph_runs.last() {
+                    Some(run) => {
+                        let logically_adjacent = run.range.end == character_index || run.range.start == character_index + 1;
+                        logically_adjacent &&
+                            run.frame.right() == x &&
+                            run.attributes == attributes &&
+                            !previous_was_justified
+                    },
+                    None => false
+                };
+                previous_was_justified = character.justification > 0;
+
+                if continues_run {
+                    let run = glyph_runs.last_mut().unwrap();
+                    run.range.start = run.range.start.min(character_index);
+                    run.range.end = run.range.end.max(character_index + 1);
+                    run.text.push_str(&character.character);
+                    run.segments.push((character.character.clone(), x - run.frame.origin.x));
+                    run.frame.size.width += character.size.width;
+                    run.frame.size.height = run.frame.size.height.max(character.size.height);
+                } else {
+                    glyph_runs.push(GlyphRun {
+                        range: character_index..character_index + 1,
+                        text: character.character.clone(),
+                        frame: Rectangle {
+                            origin: Point { x, y: line_position.y },
+                            size: character.size.clone()
+                        },
+                        attributes,
+                        segments: vec![(character.character.clone(), 0)]
+                    });
+                }
+            }
+
+            for run in glyph_runs.iter_mut() {
+                if run.kerning() == Kerning::Default {
+                    run.segments = vec![(shaping::apply_ligatures(&run.text, run.font()), 0)];
+                }
+            }
+
+            let visual_size = line.visual_size();
+
+            line_fragments.push(LineFragment {
+                range: line_start..index,
+                frame: Rectangle {
+                    origin: line_position.clone(),
+                    size: visual_size
+                },
+                glyph_runs,
+                ends_with_newline
+            });
+        }
+
+        Layout {
+            line_fragments,
+            rendering,
+            horizontal_alignment,
+            render_scale
+        }
+    }
+
+    /// Like `new_in_frame`, but returns a shared layout from the
+    /// `LayoutCache` if the string hasn't changed since it was last laid out
+    /// with the same size and options.
+    pub fn cached_in_frame(
+        attributed_string: &AttributedString,
+        frame: Rectangle<i32, u32>,
+        horizontal_alignment: HorizontalAlignment,
+        vertical_alignment: VerticalAlignment,
+        number_of_lines: usize,
+        render_scale: f32
+    ) -> Rc<Layout> {
+        let key = LayoutKey {
+            string_id: attributed_string.id(),
+            revision: attributed_string.revision(),
+            size: frame.size.clone(),
+            horizontal_alignment,
+            vertical_alignment,
+            number_of_lines,
+            render_scale
+        };
+
+        if let Some(layout) = LayoutCache::get(&key) {
+            return layout;
+        }
+
+        let layout = Rc::new(Layout::new_in_frame(
+            attributed_string,
+            frame,
+            horizontal_alignment,
+            vertical_alignment,
+            number_of_lines,
+            render_scale
+        ));
+
+        LayoutCache::insert(key, layout.clone());
+        layout
+    }
+
+    pub fn line_fragments(&self) -> &[LineFragment] {
+        &self.line_fragments
+    }
+
+    /// The line fragment containing the character at `index`.
+    pub fn line_fragment_for_character_at_index(&self, index: usize) -> Option<&LineFragment> {
+        self.line_fragments.iter().find(|fragment| fragment.range.contains(&index))
+    }
+
+    /// The line fragment whose vertical extent contains `point`, regardless
+    /// of how far left or right of the line's text the point is.
+    pub fn line_fragment_at_point(&self, point: &Point<i32>) -> Option<&LineFragment> {
+        self.line_fragments
+            .iter()
+            .find(|fragment| point.y >= fragment.frame.origin.y && point.y < fragment.frame.bottom())
+    }
+
+    /// The index of the character whose glyph is drawn at `point`, or `None`
+    /// if no glyph covers it.
+    ///
+    /// This is the inverse of `rendering().position_for_character_at_index`
+    /// for hit testing characters themselves. To find where a carat should
+    /// be placed for a point, use `carat_index_at_point`.
+    pub fn character_index_at_point(&self, point: &Point<i32>) -> Option<usize> {
+        self.rendering.character_containing_position(point)
+    }
+
+    /// The index a carat should be placed at for `point`, i.e. the boundary
+    /// between characters closest to it. Points beyond the text resolve to
+    /// the nearest line and its nearest end.
+    pub fn carat_index_at_point(&self, point: &Point<i32>) -> usize {
+        self.rendering.character_at_position(point.clone())
+    }
+
+    /// The range of characters on the line at `point`, for hit testing whole
+    /// lines (e.g. selecting a line with a triple click).
+    pub fn character_range_at_point(&self, point: &Point<i32>) -> Option<Range<usize>> {
+        self.line_fragment_at_point(point).map(LineFragment::range)
+    }
+
+    /// The size of the smallest rectangle, with its origin at the top-left of
+    /// the layout, that contains every line fragment.
+    pub fn size(&self) -> Size<u32> {
+        let mut size = Size::new(0, 0);
+
+        for fragment in self.line_fragments.iter() {
+            let right = fragment.frame.right().max(0) as u32;
+            let bottom = fragment.frame.bottom().max(0) as u32;
+            size.width = size.width.max(right);
+            size.height = size.height.max(bottom);
+        }
+
+        size
+    }
+
+    /// Per character positions and sizes, for placing carats and hit testing.
+    pub fn rendering(&self) -> &rendering::Result {
+        &self.rendering
+    }
+
+    /// The alignment the lines were laid out with.
+    pub fn horizontal_alignment(&self) -> HorizontalAlignment {
+        self.horizontal_alignment
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyph_runs_split_on_attributes() {
+        let attributed_string = AttributedString::new(String::from("Hello world"));
+        for i in 6..11 {
+            attributed_string.set_attribute_for(i, Key::Color, Attribute::Color { color: Color::RED });
+        }
+
+        let layout = Layout::new(&attributed_string, 10000, 1.0);
+        assert_eq!(layout.line_fragments().len(), 1);
+
+        let runs = layout.line_fragments()[0].glyph_runs();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].text(), "Hello ");
+        assert_eq!(runs[0].range(), 0..6);
+        assert_eq!(runs[0].color(), &Color::BLACK);
+        assert_eq!(runs[1].text(), "world");
+        assert_eq!(runs[1].range(), 6..11);
+        assert_eq!(runs[1].color(), &Color::RED);
+        assert_eq!(runs[1].frame().origin.x, runs[0].frame().right());
+    }
+
+    #[test]
+    fn test_newlines_create_line_fragments() {
+        let attributed_string = AttributedString::new(String::from("one\ntwo\n"));
+        let layout = Layout::new(&attributed_string, 10000, 1.0);
+        let fragments = layout.line_fragments();
+
+        assert_eq!(fragments.len(), 3);
+        assert_eq!(fragments[0].range(), 0..4);
+        assert!(fragments[0].ends_with_newline());
+        assert_eq!(fragments[0].glyph_runs()[0].text(), "one");
+        assert_eq!(fragments[1].range(), 4..8);
+        assert_eq!(fragments[2].range(), 8..8);
+        assert!(fragments[1].frame().origin.y > fragments[0].frame().origin.y);
+
+        assert_eq!(layout.line_fragment_for_character_at_index(5).unwrap().range(), 4..8);
+        assert!(layout.size().height >= fragments[1].frame().bottom() as u32);
+    }
+
+    #[test]
+    fn test_word_wrap() {
+        let attributed_string = AttributedString::new(String::from("aaaa bbbb"));
+        let whole_width = Layout::new(&attributed_string, 10000, 1.0).size().width;
+
+        // Just too narrow to fit both words.
+        let layout = Layout::new(&attributed_string, whole_width - 1, 1.0);
+        let fragments = layout.line_fragments();
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[0].range(), 0..5);
+        assert_eq!(fragments[1].range(), 5..9);
+    }
+
+    #[test]
+    fn test_break_anywhere_fallback() {
+        let attributed_string = AttributedString::new(String::from("abcdefghijklmnop"));
+        let whole_width = Layout::new(&attributed_string, 10000, 1.0).size().width;
+        let width = whole_width / 3;
+
+        let layout = Layout::new(&attributed_string, width, 1.0);
+        let fragments = layout.line_fragments();
+        assert!(fragments.len() >= 3);
+
+        let mut expected_start = 0;
+        for fragment in fragments {
+            assert_eq!(fragment.range().start, expected_start);
+            assert!(fragment.frame().size.width <= width);
+            expected_start = fragment.range().end;
+        }
+        assert_eq!(expected_start, 16);
+    }
+
+    #[test]
+    fn test_number_of_lines() {
+        let attributed_string = AttributedString::new(String::from("one\ntwo\nthree"));
+        let frame = Rectangle::new(0, 0, 10000, 10000);
+
+        let unlimited = Layout::new_in_frame(&attributed_string, frame.clone(), HorizontalAlignment::Left, VerticalAlignment::Top, 0, 1.0);
+        assert_eq!(unlimited.line_fragments().len(), 3);
+
+        let limited = Layout::new_in_frame(&attributed_string, frame.clone(), HorizontalAlignment::Left, VerticalAlignment::Top, 2, 1.0);
+        assert_eq!(limited.line_fragments().len(), 2);
+        assert_eq!(limited.line_fragments()[1].range(), 4..8);
+        assert!(limited.size().height < unlimited.size().height);
+
+        let more_than_needed = Layout::new_in_frame(&attributed_string, frame, HorizontalAlignment::Left, VerticalAlignment::Top, 5, 1.0);
+        assert_eq!(more_than_needed.line_fragments().len(), 3);
+    }
+
+    #[test]
+    fn test_justified() {
+        let attributed_string = AttributedString::new(String::from("aaa bb cccc\nd e"));
+        let natural_width = Layout::new(&attributed_string, 10000, 1.0).line_fragments()[0].frame().size.width;
+
+        // Narrow enough for "cccc" to wrap.
+        let width = natural_width - 1;
+        let frame = Rectangle::new(0, 0, width, 1000);
+        let layout = Layout::new_in_frame(&attributed_string, frame, HorizontalAlignment::Justified, VerticalAlignment::Top, 0, 1.0);
+        let fragments = layout.line_fragments();
+        assert_eq!(fragments.len(), 3);
+
+        // The wrapped line fills the width, with a run per word.
+        assert_eq!(fragments[0].range(), 0..7);
+        assert_eq!(fragments[0].frame().size.width, width);
+        let runs = fragments[0].glyph_runs();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].text(), "aaa ");
+        assert_eq!(runs[1].text(), "bb ");
+        assert_eq!(runs[1].frame().origin.x, runs[0].frame().right());
+
+        // Ends of paragraphs are left aligned and not stretched.
+        assert!(fragments[1].frame().size.width < width);
+        assert_eq!(fragments[1].frame().origin.x, 0);
+        assert_eq!(fragments[2].glyph_runs().len(), 1);
+        assert_eq!(fragments[2].glyph_runs()[0].text(), "d e");
+
+        // Carat positions follow the justified spacing.
+        let position = layout.rendering().position_for_character_at_index(4);
+        assert_eq!(position.x, runs[1].frame().origin.x);
+    }
+
+    #[test]
+    fn test_font_fallback_splits_runs() {
+        let attributed_string = AttributedString::new(String::from("Hi 日本 there"));
+        let layout = Layout::new(&attributed_string, 10000, 1.0);
+        let runs = layout.line_fragments()[0].glyph_runs();
+
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].text(), "Hi ");
+        assert_eq!(runs[1].text(), "日本");
+        assert_eq!(runs[1].range(), 3..5);
+        assert_eq!(runs[1].font().family(), "PingFang");
+        assert_eq!(runs[2].text(), " there");
+        assert_eq!(runs[2].font(), &Font::default());
+    }
+
+    #[test]
+    fn test_right_to_left_runs() {
+        let attributed_string = AttributedString::new(String::from("abc שלום"));
+        let layout = Layout::new(&attributed_string, 10000, 1.0);
+        let runs = layout.line_fragments()[0].glyph_runs();
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].text(), "abc ");
+        assert_eq!(runs[0].range(), 0..4);
+        assert_eq!(runs[1].text(), "םולש");
+        assert_eq!(runs[1].range(), 4..8);
+        assert_eq!(runs[1].frame().origin.x, runs[0].frame().right());
+
+        // The first Hebrew letter is drawn at the right end of the line.
+        let rendering = layout.rendering();
+        let first = rendering.origin_for_character_at_index(4).unwrap().x;
+        let last = rendering.origin_for_character_at_index(7).unwrap().x;
+        assert!(first > last);
+    }
+
+    #[test]
+    fn test_hit_testing() {
+        let attributed_string = AttributedString::new(String::from("ab\ncd"));
+        let layout = Layout::new(&attributed_string, 1000, 1.0);
+        let fragments = layout.line_fragments();
+        let first_run = &fragments[0].glyph_runs()[0];
+        let second_line = fragments[1].frame();
+
+        let inside_b = Point { x: first_run.frame().right() - 1, y: first_run.frame().origin.y + 1 };
+        assert_eq!(layout.character_index_at_point(&inside_b), Some(1));
+        assert_eq!(layout.carat_index_at_point(&inside_b), 2);
+
+        let past_end = Point { x: 500, y: second_line.origin.y + 1 };
+        assert_eq!(layout.character_index_at_point(&past_end), None);
+        assert_eq!(layout.carat_index_at_point(&past_end), 5);
+
+        assert_eq!(layout.character_range_at_point(&inside_b), Some(0..3));
+        assert_eq!(layout.character_range_at_point(&past_end), Some(3..5));
+        assert_eq!(layout.character_range_at_point(&Point { x: 0, y: second_line.bottom() + 1 }), None);
+    }
+
+    #[test]
+    fn test_segments_follow_kerning() {
+        let attributed_string = AttributedString::new(String::from("abcd"));
+        attributed_string.set_attribute_for(2, Key::Kerning, Attribute::Kerning { kerning: Kerning::Adjusted(2) });
+        attributed_string.set_attribute_for(3, Key::Kerning, Attribute::Kerning { kerning: Kerning::Adjusted(2) });
+
+        let layout = Layout::new(&attributed_string, 1000, 1.0);
+        let runs = layout.line_fragments()[0].glyph_runs();
+        assert_eq!(runs.len(), 2);
+
+        assert_eq!(runs[0].segments(), &[(String::from("ab"), 0)]);
+
+        let c_width = layout.rendering().character_size_for_character_at_index(2).width as i32;
+        assert_eq!(runs[1].kerning(), Kerning::Adjusted(2));
+        assert_eq!(runs[1].segments(), &[(String::from("c"), 0), (String::from("d"), c_width)]);
+    }
+
+    #[test]
+    fn test_layout_cache() {
+        let mut attributed_string = AttributedString::new(String::from("Hello"));
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let layout = |attributed_string: &AttributedString, frame: Rectangle<i32, u32>| {
+            Layout::cached_in_frame(attributed_string, frame, HorizontalAlignment::Left, VerticalAlignment::Top, 0, 1.0)
+        };
+
+        let first = layout(&attributed_string, frame.clone());
+        assert!(Rc::ptr_eq(&first, &layout(&attributed_string, frame.clone())));
+
+        // The origin doesn't affect the layout, but the size does.
+        assert!(Rc::ptr_eq(&first, &layout(&attributed_string, Rectangle::new(10, 10, 100, 100))));
+        assert!(!Rc::ptr_eq(&first, &layout(&attributed_string, Rectangle::new(0, 0, 50, 100))));
+
+        // Mutations invalidate the string's layouts.
+        let entries = LayoutCache::len();
+        attributed_string.set_attribute_for(0, Key::Color, Attribute::Color { color: Color::RED });
+        assert_eq!(LayoutCache::len(), entries - 2);
+        let recoloured = layout(&attributed_string, frame.clone());
+        assert!(!Rc::ptr_eq(&first, &recoloured));
+        assert_eq!(recoloured.line_fragments()[0].glyph_runs()[0].color(), &Color::RED);
+
+        attributed_string.insert_str(5, " world");
+        assert_eq!(layout(&attributed_string, frame.clone()).line_fragments()[0].range(), 0..11);
+
+        // Dropping the string discards its layouts.
+        let entries = LayoutCache::len();
+        drop(attributed_string);
+        assert_eq!(LayoutCache::len(), entries - 1);
+    }
+}
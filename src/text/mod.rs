@@ -1,5 +1,8 @@
 pub mod attributed_string;
+pub mod bidi;
+pub mod layout;
 pub mod rendering;
+pub mod shaping;
 pub mod word_boundary;
 pub mod text;
 
@@ -7,9 +10,17 @@ pub use text::Text;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum HorizontalAlignment {
+    /// Left aligned in an interface laid out left to right, and right
+    /// aligned in one laid out right to left. Labels resolve it with their
+    /// `View::effective_layout_direction`; text laid out on its own is left
+    /// aligned.
+    Natural,
     Left,
     Center,
-    Right
+    Right,
+    /// Lines that wrap are widened to fill the width by spacing out their
+    /// words. The last line of each paragraph is left aligned.
+    Justified
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
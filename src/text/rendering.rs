@@ -1,8 +1,9 @@
 use crate::graphics::Rectangle;
 use crate::graphics::Point;
 use crate::graphics::Size;
-use crate::text::attributed_string::{AttributedString, AttributedSubstring};
-use crate::text::attributed_string;
+use crate::text::attributed_string::{AttributedString, AttributedSubstring, LineStyle};
+use crate::text::bidi::BidiInfo;
+use crate::text::shaping;
 use crate::text::{VerticalAlignment, HorizontalAlignment};
 
 /// Used for rendering.
@@ -13,11 +14,15 @@ use crate::text::{VerticalAlignment, HorizontalAlignment};
 /// position. This is because the `Word` struct will ensure following characters
 /// are always be sequential / on the same line.
 pub struct Character {
-    /// The character to be rendered.
-    character: char,
+    /// The grapheme cluster to be rendered. This may be made up of more than
+    /// one `char` (e.g. emoji sequences or combining characters).
+    pub(crate) character: String,
 
-    /// The size of the character.
-    size: Size<u32>,
+    /// The size of the character. Includes `justification`.
+    pub(crate) size: Size<u32>,
+
+    /// Extra width added to a whitespace character to justify its line.
+    pub(crate) justification: u32
 }
 
 /// Used for rendering.
@@ -29,10 +34,10 @@ pub struct Character {
 /// If an individual word is too long to fit on a line, it will be broken up
 /// into multiple `Word`s for simplicity of rendering.
 pub struct Word {
-    characters: Vec<Character>,
+    pub(crate) characters: Vec<Character>,
 
     /// The size of the word.
-    size: Size<u32>
+    pub(crate) size: Size<u32>
 }
 
 /// Used for rendering.
@@ -40,7 +45,7 @@ pub struct Word {
 /// A whole line of text, containing `Word`s.
 pub struct LineOfText {
     /// The text to be rendered.
-    words: Vec<Word>,
+    pub(crate) words: Vec<Word>,
 
     /// The size of the line.
     ///
@@ -49,7 +54,7 @@ pub struct LineOfText {
     ///
     /// The width of the line is determined by the sum of the widths of all
     /// characters in the line.
-    size: Size<u32>
+    pub(crate) size: Size<u32>
 }
 
 /// Used for rendering.
@@ -61,13 +66,13 @@ pub struct LineOfText {
 /// control for text-alignment.
 pub struct WholeText<'a> {
     /// The text to be rendered.
-    lines: Vec<LineOfText>,
+    pub(crate) lines: Vec<LineOfText>,
 
     /// Positions of each line of text.
     ///
     /// Note that these positions are relative to the top-left of the
     /// `WholeText`.
-    line_positions: Vec<Point<i32>>,
+    pub(crate) line_positions: Vec<Point<i32>>,
 
     /// The position and size of the text.
     ///
@@ -87,6 +92,12 @@ pub struct WholeText<'a> {
 pub struct LineResult {
     start_index: usize,
     positions: Vec<Point<i32>>,
+
+    /// The line's character indexes (relative to `start_index`) from left
+    /// to right, which differs from the logical order in right-to-left
+    /// text.
+    visual_order: Vec<usize>,
+
     sizes: Vec<Size<u32>>,
     height: u32,
     ends_with_newline: bool
@@ -94,7 +105,19 @@ pub struct LineResult {
 
 pub struct Result {
     lines: Vec<LineResult>,
+
+    /// The top-left of each character as drawn, plus the position after the
+    /// last character if the text doesn't end with a newline.
     positions: Vec<Point<i32>>,
+
+    /// Where a carat is placed before each character (and after the last).
+    /// This is the leading edge of the character, which is its right side
+    /// for right-to-left text.
+    carat_positions: Vec<Point<i32>>,
+
+    /// Whether each character is laid out right-to-left.
+    right_to_left: Vec<bool>,
+
     sizes: Vec<Size<u32>>,
     line_heights: Vec<u32>,
     render_scale: f32,
@@ -103,12 +126,12 @@ pub struct Result {
 }
 
 impl Character {
-    fn is_newline(&self) -> bool {
-        self.character == '\n'
+    pub(crate) fn is_newline(&self) -> bool {
+        self.character.ends_with('\n')
     }
 
     fn is_whitespace(&self) -> bool {
-        self.character.is_whitespace()
+        self.character.chars().all(char::is_whitespace)
     }
 
     pub fn size(&self) -> &Size<u32> {
@@ -116,7 +139,7 @@ impl Character {
     }
 
     pub fn to_string(&self) -> String {
-        self.character.to_string()
+        self.character.clone()
     }
 }
 
@@ -153,7 +176,7 @@ impl Word {
 
 impl std::fmt::Debug for Word {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Word({})", self.characters.iter().map(|c| c.character).collect::<String>())
+        write!(f, "Word({})", self.characters.iter().map(|c| c.character.as_str()).collect::<String>())
     }
 }
 
@@ -185,18 +208,13 @@ impl LineOfText {
         // The current word being formed.
         let mut current_word = Word::new();
 
-        for (char_index, character) in attributed_string.chars().enumerate() {
-            // Calculate the size of the character.
-            let font_attribute = attributed_string
-                .get_attribute_for(char_index, attributed_string::Key::Font);
-            let font = font_attribute.font();
-            let size = font.size_for(&String::from(character));
-            let size = Size::new(
-                (size.width as f32 * render_scale) as u32,
-                (size.height as f32 * render_scale) as u32
-            );
+        // The size of each character, including kerning.
+        let sizes = shaping::character_sizes(attributed_string, render_scale);
 
-            let character = Character { character, size };
+        for (char_index, character) in attributed_string.graphemes().enumerate() {
+            let size = sizes[char_index].clone();
+
+            let character = Character { character: String::from(character), size, justification: 0 };
 
             let potential_word_width = current_word.size.width + character.size.width;
             if potential_word_width > maximum_width {
@@ -278,6 +296,65 @@ impl LineOfText {
         lines
     }
 
+    /// Whether the last character of the line is a newline.
+    fn ends_with_newline(&self) -> bool {
+        match self.words.last().and_then(|word| word.characters.last()) {
+            Some(character) => character.is_newline(),
+            None => false
+        }
+    }
+
+    /// Spreads `extra_width` across the whitespace between words, so that
+    /// the line becomes `extra_width` wider. Trailing whitespace isn't
+    /// widened. Any previous justification is removed first.
+    ///
+    /// Does nothing (other than removing previous justification) if there is
+    /// no whitespace between words.
+    fn justify(&mut self, extra_width: u32) {
+        for word in self.words.iter_mut() {
+            for character in word.characters.iter_mut() {
+                character.size.width -= character.justification;
+                word.size.width -= character.justification;
+                self.size.width -= character.justification;
+                character.justification = 0;
+            }
+        }
+
+        let character_count: usize = self.words.iter().map(|word| word.characters.len()).sum();
+
+        // Whitespace, excluding the very last character of the line.
+        let mut inner_whitespace = Vec::new();
+        let mut index = 0;
+        for (word_index, word) in self.words.iter().enumerate() {
+            for (character_index, character) in word.characters.iter().enumerate() {
+                if character.is_whitespace() && index + 1 < character_count {
+                    inner_whitespace.push((word_index, character_index));
+                }
+                index += 1;
+            }
+        }
+
+        if extra_width == 0 || inner_whitespace.is_empty() {
+            return;
+        }
+
+        let spaces = inner_whitespace.len() as u32;
+        for (space_index, (word_index, character_index)) in inner_whitespace.into_iter().enumerate() {
+            // Any remainder goes to the first spaces.
+            let mut justification = extra_width / spaces;
+            if (space_index as u32) < extra_width % spaces {
+                justification += 1;
+            }
+
+            let word = &mut self.words[word_index];
+            let character = &mut word.characters[character_index];
+            character.justification = justification;
+            character.size.width += justification;
+            word.size.width += justification;
+            self.size.width += justification;
+        }
+    }
+
     /// The size of the line.
     ///
     /// However, if the last character in the line is whitespace, it will not
@@ -286,7 +363,7 @@ impl LineOfText {
     ///
     /// In addition, line height (20% more than the font height) is included
     /// here.
-    fn visual_size(&self) -> Size<u32> {
+    pub(crate) fn visual_size(&self) -> Size<u32> {
         // 20% of height
         let additional_height = self.size.height as f32 * 0.2;
         let height = self.size.height as f32 + additional_height;
@@ -347,6 +424,19 @@ impl WholeText<'_> {
         whole_text
     }
 
+    /// Removes any lines after the first `number_of_lines`. This should be
+    /// done before aligning, so that only the remaining lines are aligned.
+    ///
+    /// A `number_of_lines` of 0 means there is no limit.
+    pub fn limit_number_of_lines(&mut self, number_of_lines: usize) {
+        if number_of_lines == 0 {
+            return;
+        }
+
+        self.lines.truncate(number_of_lines);
+        self.line_positions.truncate(number_of_lines);
+    }
+
     fn lines_total_height(&self) -> u32 {
         self.lines.iter().fold(0, |acc, line| acc + line.visual_size().height)
     }
@@ -358,9 +448,22 @@ impl WholeText<'_> {
     pub fn align_horizontally(&mut self, horizontal_alignment: HorizontalAlignment) {
         // Aligning horizontalling is simple as we don't need to account for
         // other lines of text, as they cannot overlap horizontally.
-        for (index, line) in self.lines.iter().enumerate() {
+        let line_count = self.lines.len();
+        let frame_width = self.frame.size.width;
+
+        for (index, line) in self.lines.iter_mut().enumerate() {
+            // The last line of a paragraph is left aligned when justifying.
+            let is_paragraph_end = index + 1 == line_count || line.ends_with_newline();
+            if horizontal_alignment == HorizontalAlignment::Justified && !is_paragraph_end {
+                line.justify(0);
+                let extra_width = frame_width.saturating_sub(line.visual_size().width);
+                line.justify(extra_width);
+            } else {
+                line.justify(0);
+            }
+
             match horizontal_alignment {
-                HorizontalAlignment::Left => {
+                HorizontalAlignment::Natural | HorizontalAlignment::Left | HorizontalAlignment::Justified => {
                     self.line_positions[index].x = 0;
                 }
                 HorizontalAlignment::Center => {
@@ -434,7 +537,12 @@ impl WholeText<'_> {
     /// Iterate characters of the text with their positions to render.
     ///
     /// Positions of characters are calculated during this iteration.
+    ///
+    /// Characters are positioned in visual order, so right-to-left runs are
+    /// reversed using the Unicode Bidirectional Algorithm, while indexes
+    /// remain in logical order.
     pub fn calculate_character_render_positions(&self) -> Result {
+        let bidi = BidiInfo::new(self.attributed_string.graphemes());
         let mut positions: Vec<Point<i32>> = Vec::new();
         let mut sizes: Vec<Size<u32>> = Vec::new();
         let mut line_heights: Vec<u32> = Vec::new();
@@ -473,8 +581,7 @@ impl WholeText<'_> {
                         y: character_relative_position.y
                     };
 
-                    line_positions.push(absolute_position.clone());
-                    positions.push(absolute_position.clone());
+                    line_positions.push(absolute_position);
                     sizes.push(character.size.clone());
                     line_character_sizes.push(character.size.clone());
                     line_heights.push(line_height);
@@ -486,9 +593,28 @@ impl WholeText<'_> {
                 }
             }
 
+            // Lay the characters out again from left to right in visual
+            // order. A trailing newline stays at the end, as it isn't drawn.
+            let visible_end = if line_ends_with_newline { index - 1 } else { index };
+            let mut visual_order: Vec<usize> = bidi
+                .visual_order(line_start_index..visible_end)
+                .into_iter()
+                .map(|index| index - line_start_index)
+                .collect();
+            visual_order.extend(visible_end - line_start_index..index - line_start_index);
+
+            let mut x = line_relative_position.x;
+            for line_char_index in visual_order.iter() {
+                line_positions[*line_char_index].x = x;
+                x += line_character_sizes[*line_char_index].width as i32;
+            }
+
+            positions.extend(line_positions.iter().cloned());
+
             let line_result = LineResult {
                 start_index: line_start_index,
                 positions: line_positions,
+                visual_order,
                 sizes: line_character_sizes,
                 height: line_height,
                 ends_with_newline: line_ends_with_newline
@@ -498,6 +624,7 @@ impl WholeText<'_> {
         }
 
         let render_scale = self.render_scale;
+        let right_to_left: Vec<bool> = (0..sizes.len()).map(|index| bidi.is_right_to_left(index)).collect();
 
         let fallback_cursor_rectangle: Rectangle<i32, u32>;
         if positions.is_empty() {
@@ -523,9 +650,29 @@ impl WholeText<'_> {
             }
         }
 
+        let carat_positions = positions
+            .iter()
+            .enumerate()
+            .map(|(index, position)| {
+                // After the last character, the carat goes on its trailing
+                // edge.
+                let (character_index, trailing) = if index < sizes.len() { (index, false) } else { (index - 1, true) };
+
+                if right_to_left[character_index] != trailing {
+                    Point { x: positions[character_index].x + sizes[character_index].width as i32, y: position.y }
+                } else if trailing {
+                    Point { x: positions[character_index].x, y: position.y }
+                } else {
+                    position.clone()
+                }
+            })
+            .collect();
+
         Result {
             lines: line_results,
             positions,
+            carat_positions,
+            right_to_left,
             sizes,
             line_heights,
             render_scale,
@@ -536,31 +683,56 @@ impl WholeText<'_> {
 }
 
 impl Result {
-    /// Find the position for a given character.
+    /// Find the position of the carat before a given character, which is on
+    /// the character's leading edge (its right side in right-to-left text).
     ///
-    /// Returns `None` if the character is not found.
+    /// Indexes past the end are clamped to the position after the last
+    /// character.
     pub fn position_for_character_at_index(&self, index: usize) -> &Point<i32> {
         let mut index = index as i32;
-        if index >= self.positions.len() as i32 {
-            index = self.positions.len() as i32 - 1;
+        if index >= self.carat_positions.len() as i32 {
+            index = self.carat_positions.len() as i32 - 1;
         }
 
         if index < 0 {
             index = 0;
         }
 
-        if let Some(position) = self.positions.get(index as usize) {
+        if let Some(position) = self.carat_positions.get(index as usize) {
             position
         } else {
             &self.fallback_cursor_rectangle.origin
         }
     }
 
+    /// The top-left of the character at `index` as drawn.
+    pub(crate) fn origin_for_character_at_index(&self, index: usize) -> Option<&Point<i32>> {
+        if index < self.sizes.len() {
+            self.positions.get(index)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the character at `index` is laid out right-to-left.
+    pub fn is_right_to_left_at_index(&self, index: usize) -> bool {
+        self.right_to_left.get(index).copied().unwrap_or(false)
+    }
+
+    /// The indexes of the characters in the line at `line_index`, from left
+    /// to right.
+    pub(crate) fn visual_order_for_line(&self, line_index: usize) -> Vec<usize> {
+        let line = &self.lines[line_index];
+        line.visual_order.iter().map(|index| line.start_index + index).collect()
+    }
+
     /// Returns the character index for a given position. Intended to be used
     /// for determining where to place the carat from a tap or click.
     ///
     /// If the position is over halfway for a given character, the following
-    /// character index is returned.
+    /// character index is returned. Halfway is measured from the character's
+    /// leading edge, so in right-to-left text clicking on the left half of a
+    /// character places the carat after it.
     ///
     /// Returns the last character index if the character is not found.
     pub fn character_at_position(&self, position: Point<i32>) -> usize {
@@ -579,51 +751,71 @@ impl Result {
                 return self.sizes.len();
             }
 
-            let mut accrued_width: i32 = 0;
-
             if line_result.positions.is_empty() {
                 return self.sizes.len();
             }
 
-            for (line_char_index, character_position) in line_result.positions.iter().enumerate() {
-                // if we're on the last element and it's a newline, then we
-                // don't want to count it.
-                if line_char_index == line_result.sizes.len() - 1 {
-                    if line_result.ends_with_newline {
-                        return line_char_index + line_result.start_index;
+            // A trailing newline isn't drawn, so the carat can only be placed
+            // before it.
+            let mut count = line_result.positions.len();
+            if line_result.ends_with_newline {
+                count -= 1;
+            }
+
+            if count == 0 {
+                return line_result.start_index;
+            }
+
+            // Find the character horizontally closest to the position.
+            let (line_char_index, distance_from_origin) = (0..count)
+                .map(|line_char_index| {
+                    let origin = line_result.positions[line_char_index].x;
+                    (line_char_index, position.x - origin)
+                })
+                .min_by_key(|(line_char_index, from_origin)| {
+                    let width = line_result.sizes[*line_char_index].width as i32;
+                    if *from_origin < 0 {
+                        -from_origin
+                    } else if *from_origin >= width {
+                        from_origin - width + 1
+                    } else {
+                        0
                     }
-                }
+                })
+                .unwrap();
 
-                let width = self.sizes.get(line_char_index).unwrap().width;
-                accrued_width += width as i32;
+            let width = line_result.sizes[line_char_index].width as i32;
+            let before_halfway = distance_from_origin < width / 2;
+            let index = line_char_index + line_result.start_index;
 
-                // If the position is higher than the current character, and
-                // there is another character, then it's definitely not on this
-                // character.
-                //
-                // If not, then this is the best character.
-                if position.x > accrued_width && line_result.positions.len() > line_char_index + 1 {
-                    continue;
-                }
+            if before_halfway != self.is_right_to_left_at_index(index) {
+                return index;
+            } else {
+                return index + 1;
+            }
+        }
 
-                let half_width = width as i32 / 2;
-                let halfway = character_position.x + half_width;
+        unreachable!();
+    }
 
-                let index = line_char_index + line_result.start_index;
+    /// Returns the index of the character drawn at `position`, or `None` if
+    /// no character covers that position. Unlike `character_at_position`,
+    /// this is for hit testing a character itself (e.g. a link), rather than
+    /// placing a carat between characters.
+    pub fn character_containing_position(&self, position: &Point<i32>) -> Option<usize> {
+        for (index, size) in self.sizes.iter().enumerate() {
+            let origin = &self.positions[index];
+            let line_height = self.line_heights[index];
 
-                if position.x < halfway {
-                    return index;
-                } else {
-                    if line_char_index >= line_result.positions.len() {
-                        return index;
-                    } else {
-                        return index + 1;
-                    }
-                }
+            let contains_x = position.x >= origin.x && position.x < origin.x + size.width as i32;
+            let contains_y = position.y >= origin.y && position.y < origin.y + line_height as i32;
+
+            if contains_x && contains_y {
+                return Some(index);
             }
         }
 
-        unreachable!();
+        None
     }
 
     /// Returns the line height for a given character.
@@ -648,7 +840,7 @@ impl Result {
             return self.fallback_cursor_rectangle.clone();
         }
 
-        let mut position = self.positions.get(index).unwrap_or(&self.positions.last().unwrap());
+        let mut position = self.carat_positions.get(index).unwrap_or(&self.carat_positions.last().unwrap());
         let line_height = self.line_heights.get(index).unwrap_or(&self.line_heights.last().unwrap());
         let character_size = self.sizes.get(index).unwrap_or(self.sizes.last().unwrap());
 
@@ -689,6 +881,74 @@ impl Result {
     }
 }
 
+/// A line drawn as part of a character's styling, e.g. from the `Underline`
+/// attribute.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Decoration {
+    Underline,
+    Strikethrough,
+
+    /// A zigzag under the character, such as under misspelled words.
+    Squiggle
+}
+
+impl Decoration {
+    /// Vertical position of the decoration, as a proportion of the character
+    /// height from the top of the character.
+    fn relative_y(&self) -> f32 {
+        match self {
+            // Just below the baseline, above most descenders.
+            Decoration::Underline | Decoration::Squiggle => 0.85,
+            // Roughly through the middle of lowercase letters.
+            Decoration::Strikethrough => 0.55
+        }
+    }
+
+    /// The rectangles to fill in order to draw this decoration for a character
+    /// drawn in `character_frame`.
+    ///
+    /// Both `character_frame` and the returned rectangles are in the native
+    /// resolution (i.e. already multiplied by `render_scale`).
+    pub fn rectangles(&self, style: LineStyle, character_frame: &Rectangle<i32, u32>, render_scale: f32) -> Vec<Rectangle<i32, u32>> {
+        let thickness = render_scale.round().max(1.0) as u32;
+        let x = character_frame.origin.x;
+        let y = character_frame.origin.y + (character_frame.size.height as f32 * self.relative_y()).round() as i32;
+        let width = character_frame.size.width;
+
+        if *self == Decoration::Squiggle && style != LineStyle::None {
+            return squiggle_rectangles(x, y, width, thickness);
+        }
+
+        match style {
+            LineStyle::None => Vec::new(),
+            LineStyle::Single => vec![
+                Rectangle::new(x, y, width, thickness)
+            ],
+            LineStyle::Thick => vec![
+                Rectangle::new(x, y, width, thickness * 2)
+            ],
+            LineStyle::Double => vec![
+                Rectangle::new(x, y, width, thickness),
+                Rectangle::new(x, y + thickness as i32 * 2, width, thickness)
+            ]
+        }
+    }
+}
+
+/// Dots of `thickness` stepping down and back up across `width`, drawing a
+/// zigzag two dots high starting at `y`.
+fn squiggle_rectangles(x: i32, y: i32, width: u32, thickness: u32) -> Vec<Rectangle<i32, u32>> {
+    const HEIGHTS: [i32; 4] = [0, 1, 2, 1];
+
+    (0..width.div_ceil(thickness))
+        .map(|step| {
+            let dot_width = thickness.min(width - step * thickness);
+            let dot_y = y + HEIGHTS[step as usize % HEIGHTS.len()] * thickness as i32;
+            Rectangle::new(x + (step * thickness) as i32, dot_y, dot_width, thickness)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -696,11 +956,12 @@ mod tests {
     #[test]
     fn test_character() {
         let character = Character {
-            character: 'a',
-            size: Size::new(10, 20)
+            character: String::from("a"),
+            size: Size::new(10, 20),
+            justification: 0
         };
 
-        assert_eq!(character.character, 'a');
+        assert_eq!(character.character, "a");
         assert_eq!(character.size, Size::new(10, 20));
         assert_eq!(character.size().width, 10);
         assert_eq!(character.size().height, 20);
@@ -711,12 +972,13 @@ mod tests {
         let mut word = Word::new();
 
         word.add_character(Character {
-            character: 'a',
-            size: Size::new(10, 20)
+            character: String::from("a"),
+            size: Size::new(10, 20),
+            justification: 0
         });
 
         assert_eq!(word.characters.len(), 1);
-        assert_eq!(word.characters[0].character, 'a');
+        assert_eq!(word.characters[0].character, "a");
         assert_eq!(word.characters[0].size, Size::new(10, 20));
         assert_eq!(word.size, Size::new(10, 20));
     }
@@ -727,8 +989,9 @@ mod tests {
         assert!(word.is_empty());
 
         word.add_character(Character {
-            character: 'a',
-            size: Size::new(10, 20)
+            character: String::from("a"),
+            size: Size::new(10, 20),
+            justification: 0
         });
 
         assert!(!word.is_empty());
@@ -750,19 +1013,19 @@ mod tests {
         let word1 = &line_of_text.words[0];
         let word2 = &line_of_text.words[1];
 
-        assert_eq!(word1.characters[0].character, 'H');
-        assert_eq!(word1.characters[1].character, 'e');
-        assert_eq!(word1.characters[2].character, 'l');
-        assert_eq!(word1.characters[3].character, 'l');
-        assert_eq!(word1.characters[4].character, 'o');
-        assert_eq!(word1.characters[5].character, ',');
-        assert_eq!(word1.characters[6].character, ' ');
-        assert_eq!(word2.characters[0].character, 'w');
-        assert_eq!(word2.characters[1].character, 'o');
-        assert_eq!(word2.characters[2].character, 'r');
-        assert_eq!(word2.characters[3].character, 'l');
-        assert_eq!(word2.characters[4].character, 'd');
-        assert_eq!(word2.characters[5].character, '!');
+        assert_eq!(word1.characters[0].character, "H");
+        assert_eq!(word1.characters[1].character, "e");
+        assert_eq!(word1.characters[2].character, "l");
+        assert_eq!(word1.characters[3].character, "l");
+        assert_eq!(word1.characters[4].character, "o");
+        assert_eq!(word1.characters[5].character, ",");
+        assert_eq!(word1.characters[6].character, " ");
+        assert_eq!(word2.characters[0].character, "w");
+        assert_eq!(word2.characters[1].character, "o");
+        assert_eq!(word2.characters[2].character, "r");
+        assert_eq!(word2.characters[3].character, "l");
+        assert_eq!(word2.characters[4].character, "d");
+        assert_eq!(word2.characters[5].character, "!");
 
         assert_eq!(word1.size, Size::new(46, 16));
         assert_eq!(word2.size, Size::new(44, 16));
@@ -1613,4 +1876,67 @@ mod tests {
 
 
     }
+
+    #[test]
+    fn test_decoration_rectangles() {
+        let frame = Rectangle::new(10, 20, 8, 20);
+
+        assert!(Decoration::Underline.rectangles(LineStyle::None, &frame, 1.0).is_empty());
+
+        let single = Decoration::Underline.rectangles(LineStyle::Single, &frame, 1.0);
+        assert_eq!(single, vec![Rectangle::new(10, 37, 8, 1)]);
+
+        let thick = Decoration::Underline.rectangles(LineStyle::Thick, &frame, 2.0);
+        assert_eq!(thick, vec![Rectangle::new(10, 37, 8, 4)]);
+
+        let double = Decoration::Strikethrough.rectangles(LineStyle::Double, &frame, 1.0);
+        assert_eq!(double, vec![Rectangle::new(10, 31, 8, 1), Rectangle::new(10, 33, 8, 1)]);
+    }
+
+    #[test]
+    fn test_squiggle_rectangles() {
+        let frame = Rectangle::new(10, 0, 5, 20);
+        assert!(Decoration::Squiggle.rectangles(LineStyle::None, &frame, 1.0).is_empty());
+
+        let squiggle = Decoration::Squiggle.rectangles(LineStyle::Single, &frame, 1.0);
+        assert_eq!(squiggle, vec![
+            Rectangle::new(10, 17, 1, 1),
+            Rectangle::new(11, 18, 1, 1),
+            Rectangle::new(12, 19, 1, 1),
+            Rectangle::new(13, 18, 1, 1),
+            Rectangle::new(14, 17, 1, 1)
+        ]);
+
+        // The last dot is cut off at the end of the character.
+        let squiggle = Decoration::Squiggle.rectangles(LineStyle::Single, &Rectangle::new(0, 0, 5, 20), 2.0);
+        assert_eq!(squiggle.len(), 3);
+        assert_eq!(squiggle[2], Rectangle::new(4, 21, 1, 2));
+    }
+
+    #[test]
+    fn test_right_to_left_carat_positions() {
+        let attributed_string = AttributedString::new(String::from("אב"));
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let result = WholeText::from(&attributed_string, frame, 1.0).calculate_character_render_positions();
+
+        assert!(result.is_right_to_left_at_index(0));
+
+        let first = result.origin_for_character_at_index(0).unwrap().clone();
+        let second = result.origin_for_character_at_index(1).unwrap().clone();
+        let width = result.character_size_for_character_at_index(0).width as i32;
+        assert_eq!(second.x, 0);
+        assert!(first.x > second.x);
+
+        // Carats are placed on the right of right-to-left characters, and the
+        // end of the text is on the left.
+        assert_eq!(result.position_for_character_at_index(0).x, first.x + width);
+        assert_eq!(result.position_for_character_at_index(1).x, second.x + result.character_size_for_character_at_index(1).width as i32);
+        assert_eq!(result.position_for_character_at_index(2).x, second.x);
+
+        // Clicking the right half of the first letter places the carat
+        // before it, and the left half after it.
+        assert_eq!(result.character_at_position(Point::new(first.x + width - 1, 5)), 0);
+        assert_eq!(result.character_at_position(Point::new(first.x + 1, 5)), 1);
+        assert_eq!(result.character_at_position(Point::new(-10, 5)), 2);
+    }
 }
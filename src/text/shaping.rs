@@ -0,0 +1,199 @@
+use crate::graphics::{Font, Size};
+use crate::text::attributed_string::{AttributedSubstring, Key, Kerning};
+
+/// Sequences of characters replaced by a single ligature glyph when drawn,
+/// longest first so that "ffi" is preferred over "ff".
+const LIGATURES: &[(&str, char)] = &[
+    ("ffi", '\u{FB03}'),
+    ("ffl", '\u{FB04}'),
+    ("ff", '\u{FB00}'),
+    ("fi", '\u{FB01}'),
+    ("fl", '\u{FB02}')
+];
+
+/// Replaces sequences such as "fi" in `text` with their ligature glyphs,
+/// where `font` has a glyph for the ligature.
+pub fn apply_ligatures(text: &str, font: &Font) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut remaining = text;
+
+    'outer: while !remaining.is_empty() {
+        for (sequence, ligature) in LIGATURES {
+            if remaining.starts_with(sequence) && font.provides_glyphs_for(&ligature.to_string()) {
+                output.push(*ligature);
+                remaining = &remaining[sequence.len()..];
+                continue 'outer;
+            }
+        }
+
+        let character = remaining.chars().next().unwrap();
+        output.push(character);
+        remaining = &remaining[character.len_utf8()..];
+    }
+
+    output
+}
+
+/// The ligature starting at `graphemes[index]`, if any, along with the number
+/// of graphemes it replaces. Only graphemes made of a single character can be
+/// part of a ligature.
+fn ligature_at(graphemes: &[&str], index: usize) -> Option<(usize, char)> {
+    LIGATURES.iter().find_map(|(sequence, ligature)| {
+        let length = sequence.len();
+        if index + length > graphemes.len() {
+            return None;
+        }
+
+        let matches = graphemes[index..index + length]
+            .iter()
+            .zip(sequence.chars())
+            .all(|(grapheme, character)| grapheme.len() == 1 && grapheme.starts_with(character));
+
+        if matches {
+            Some((length, *ligature))
+        } else {
+            None
+        }
+    })
+}
+
+/// Measures each grapheme of `attributed_string` as it will be drawn, in the
+/// native resolution.
+///
+/// Characters are measured with their font, or a fallback font if it's
+/// missing glyphs. The widths are then adjusted for the `Kerning` attribute:
+/// kerning pairs and any extra spacing are added to the first character of
+/// each pair, and the widths of characters drawn as a ligature are adjusted
+/// so that together they match the ligature's width.
+///
+/// Kerning pairs and ligatures are only applied between characters with the
+/// same effective attributes, as only those are drawn together.
+pub fn character_sizes(attributed_string: &AttributedSubstring, render_scale: f32) -> Vec<Size<u32>> {
+    let graphemes: Vec<&str> = attributed_string.graphemes().collect();
+    let mut fonts: Vec<Font> = Vec::with_capacity(graphemes.len());
+    let mut kernings: Vec<Kerning> = Vec::with_capacity(graphemes.len());
+    let mut widths: Vec<i32> = Vec::with_capacity(graphemes.len());
+    let mut heights: Vec<u32> = Vec::with_capacity(graphemes.len());
+
+    for (index, grapheme) in graphemes.iter().enumerate() {
+        let font = attributed_string.get_attribute_for(index, Key::Font).font().fallback_for(grapheme);
+        let size = font.size_for(grapheme);
+
+        fonts.push(font);
+        kernings.push(attributed_string.get_attribute_for(index, Key::Kerning).kerning());
+        widths.push(size.width as i32);
+        heights.push(size.height);
+    }
+
+    // Whether the characters at `index` and the one after are drawn
+    // together.
+    let same_run = |index: usize| {
+        fonts[index] == fonts[index + 1] &&
+            attributed_string.effective_attributes_for(index) == attributed_string.effective_attributes_for(index + 1)
+    };
+
+    for index in 0..graphemes.len() {
+        if kernings[index] == Kerning::Disabled {
+            continue;
+        }
+
+        if index + 1 < graphemes.len() && same_run(index) {
+            widths[index] += fonts[index].kerning_between(graphemes[index], graphemes[index + 1]);
+        }
+
+        if let Kerning::Adjusted(extra) = kernings[index] {
+            widths[index] += extra;
+        }
+    }
+
+    let mut index = 0;
+    while index < graphemes.len() {
+        let ligature = match ligature_at(&graphemes, index) {
+            Some(ligature) if kernings[index] == Kerning::Default => ligature,
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+
+        let (length, character) = ligature;
+        let ligature = character.to_string();
+        let drawn_together = (index..index + length - 1).all(&same_run);
+
+        if drawn_together && fonts[index].provides_glyphs_for(&ligature) {
+            let ligature_width = fonts[index].size_for(&ligature).width as i32;
+            let current_width: i32 = widths[index..index + length].iter().sum();
+            widths[index] += ligature_width - current_width;
+            index += length;
+        } else {
+            index += 1;
+        }
+    }
+
+    widths
+        .iter()
+        .zip(heights.iter())
+        .map(|(width, height)| {
+            Size::new(
+                ((*width).max(0) as f32 * render_scale) as u32,
+                (*height as f32 * render_scale) as u32
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::attributed_string::{AttributedString, Attribute};
+
+    #[test]
+    fn test_ligature_at() {
+        let graphemes = vec!["o", "f", "f", "i", "c", "e"];
+        assert_eq!(ligature_at(&graphemes, 0), None);
+        assert_eq!(ligature_at(&graphemes, 1), Some((3, '\u{FB03}')));
+        assert_eq!(ligature_at(&graphemes, 2), Some((2, '\u{FB01}')));
+
+        // A combining mark prevents the ligature.
+        let graphemes = vec!["f", "i\u{301}"];
+        assert_eq!(ligature_at(&graphemes, 0), None);
+    }
+
+    #[test]
+    fn test_apply_ligatures() {
+        let font = Font::default();
+        let expected = if font.provides_glyphs_for("\u{FB03}") { "o\u{FB03}ce" } else { "office" };
+        assert_eq!(apply_ligatures("office", &font), expected);
+        assert_eq!(apply_ligatures("hello", &font), "hello");
+    }
+
+    #[test]
+    fn test_kerning_adjusted_and_disabled() {
+        let attributed_string = AttributedString::new(String::from("abab"));
+        let natural = character_sizes(&attributed_string.substring(0..4), 1.0);
+
+        attributed_string.set_attribute_for(0, Key::Kerning, Attribute::Kerning { kerning: Kerning::Disabled });
+        attributed_string.set_attribute_for(1, Key::Kerning, Attribute::Kerning { kerning: Kerning::Disabled });
+        attributed_string.set_attribute_for(2, Key::Kerning, Attribute::Kerning { kerning: Kerning::Adjusted(3) });
+        attributed_string.set_attribute_for(3, Key::Kerning, Attribute::Kerning { kerning: Kerning::Adjusted(3) });
+        let sizes = character_sizes(&attributed_string.substring(0..4), 2.0);
+
+        let font = Font::default();
+        assert_eq!(sizes[0].width, font.size_for("a").width * 2);
+        assert_eq!(sizes[1].width, font.size_for("b").width * 2);
+        assert_eq!(sizes[2].width as i32, (natural[2].width as i32 + 3) * 2);
+        assert_eq!(sizes[3].width, (natural[3].width + 3) * 2);
+        assert_eq!(sizes[3].height, natural[3].height * 2);
+    }
+
+    #[test]
+    fn test_ligature_widths_add_up() {
+        let attributed_string = AttributedString::new(String::from("fix"));
+        let sizes = character_sizes(&attributed_string.substring(0..3), 1.0);
+        let font = Font::default();
+
+        if font.provides_glyphs_for("\u{FB01}") {
+            assert_eq!(sizes[0].width + sizes[1].width, font.size_for("\u{FB01}").width);
+        }
+    }
+}
@@ -103,6 +103,21 @@ impl Text {
         &self.string
     }
 
+    /// Converts a byte offset into the underlying `String` to the index of
+    /// the grapheme containing it.
+    ///
+    /// An offset equal to the string's byte length returns `len()`.
+    pub fn grapheme_index_for_byte_offset(&self, byte_offset: usize) -> usize {
+        if byte_offset >= self.string.len() {
+            return self.len();
+        }
+
+        match self.grapheme_indices.binary_search(&byte_offset) {
+            Ok(index) => index,
+            Err(index) => index - 1
+        }
+    }
+
     /// Similar to `Index` but returns an `Option` instead of panicking when
     /// fetching out of bounds.
     pub fn nth(&self, index: usize) -> Option<&str> {
@@ -422,6 +437,17 @@ mod tests {
         assert_eq!(&text[0..1], "\n");
     }
 
+    #[test]
+    fn test_grapheme_index_for_byte_offset() {
+        let text = Text::from("a👨‍👨‍👧‍👧é");
+        assert_eq!(text.grapheme_index_for_byte_offset(0), 0);
+        assert_eq!(text.grapheme_index_for_byte_offset(1), 1);
+        assert_eq!(text.grapheme_index_for_byte_offset(5), 1);
+        assert_eq!(text.grapheme_index_for_byte_offset(26), 2);
+        assert_eq!(text.grapheme_index_for_byte_offset(28), 3);
+        assert_eq!(Text::from("").grapheme_index_for_byte_offset(0), 0);
+    }
+
     #[test]
     fn test_equal() {
         let a = Text::from("hello world");
@@ -1,63 +1,104 @@
 use crate::text::text::Text;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
 
-/// Finds a word boundary in a string.
-pub fn find_word_boundary(text: &Text, index: usize, rightwards: bool) -> usize {
-    let mut index = index;
-    let mut has_seen_at_least_one_non_boundary = false;
-    let mut only_whitespace_boundaries = true;
+/// The kind of a segment produced by `word_segments`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SegmentKind {
+    /// Contains at least one alphanumeric character, e.g. "hello",
+    /// "find_word_boundary" or "can't".
+    Word,
 
-    if rightwards {
-        while index < text.len() {
-            let character = text.nth(index).unwrap().chars().nth(0).unwrap();
-            if !(character.is_alphanumeric() || character == '_') {
-                let whitespace = character.is_whitespace();
+    /// Only whitespace, including newlines.
+    Whitespace,
 
-                if !whitespace { only_whitespace_boundaries = false; }
+    /// Anything else, e.g. punctuation, symbols and emoji.
+    Punctuation
+}
 
-                // if we've seen anything that isn't whitespace before, but now
-                // it's a whitespace, we end.
-                if !only_whitespace_boundaries && whitespace {
-                    break;
-                }
+/// Splits text at Unicode (UAX #29) word boundaries. Every grapheme belongs to
+/// exactly one segment, and the ranges are grapheme indices.
+///
+/// This is the canonical word segmentation; `find_word_boundary` and
+/// `AttributedString::words` are both built on it.
+pub fn word_segments(text: &Text) -> Vec<(Range<usize>, SegmentKind)> {
+    text.string()
+        .split_word_bound_indices()
+        .map(|(byte_offset, segment)| {
+            let start = text.grapheme_index_for_byte_offset(byte_offset);
+            let end = text.grapheme_index_for_byte_offset(byte_offset + segment.len());
+
+            let kind = if segment.chars().any(char::is_alphanumeric) {
+                SegmentKind::Word
+            } else if segment.chars().all(char::is_whitespace) {
+                SegmentKind::Whitespace
+            } else {
+                SegmentKind::Punctuation
+            };
 
-                // if we've seen a non-boundary character before and now we've
-                // seen a boundary, we end.
-                if has_seen_at_least_one_non_boundary {
-                    break;
-                }
+            (start..end, kind)
+        })
+        .collect()
+}
+
+/// Splits text at Unicode (UAX #29) sentence boundaries. Trailing whitespace
+/// is included in the preceding sentence. The ranges are grapheme indices.
+pub fn sentence_ranges(text: &Text) -> Vec<Range<usize>> {
+    text.string()
+        .split_sentence_bound_indices()
+        .map(|(byte_offset, sentence)| {
+            let start = text.grapheme_index_for_byte_offset(byte_offset);
+            let end = text.grapheme_index_for_byte_offset(byte_offset + sentence.len());
+            start..end
+        })
+        .collect()
+}
+
+/// Finds a word boundary in a string.
+///
+/// Moving from `index`, leading whitespace is skipped, and then either a
+/// single word, or a run of punctuation (and the word immediately following
+/// it) is passed over.
+pub fn find_word_boundary(text: &Text, index: usize, rightwards: bool) -> usize {
+    // Each segment, with its portion on the far side of `index` removed.
+    let mut segments: Vec<(Range<usize>, SegmentKind)> = word_segments(text)
+        .into_iter()
+        .filter_map(|(range, kind)| {
+            if rightwards && range.end > index {
+                Some((range.start.max(index)..range.end, kind))
+            } else if !rightwards && range.start < index {
+                Some((range.start..range.end.min(index), kind))
             } else {
-                has_seen_at_least_one_non_boundary = true;
-                only_whitespace_boundaries = false;
+                None
             }
+        })
+        .collect();
 
-            index += 1;
-        }
-    } else {
-        while index > 0 {
-            let character = text.nth(index - 1).unwrap().chars().nth(0).unwrap();
-            if !(character.is_alphanumeric() || character == '_') {
-                let whitespace = character.is_whitespace();
-
-                if !whitespace { only_whitespace_boundaries = false; }
+    if !rightwards {
+        segments.reverse();
+    }
 
-                // if we've seen anything that isn't whitespace before, but now
-                // it's a whitespace, we end.
-                if !only_whitespace_boundaries && whitespace {
-                    break;
-                }
+    let mut index = index;
+    let mut only_whitespace = true;
 
-                // if we've seen a non-boundary character before and now we've
-                // seen a boundary, we end.
-                if has_seen_at_least_one_non_boundary {
+    for (range, kind) in segments {
+        match kind {
+            SegmentKind::Whitespace => {
+                // Whitespace after anything else ends the movement.
+                if !only_whitespace {
                     break;
                 }
-            } else {
-                has_seen_at_least_one_non_boundary = true;
-                only_whitespace_boundaries = false;
             }
-
-            index -= 1;
+            SegmentKind::Punctuation => {
+                only_whitespace = false;
+            }
+            SegmentKind::Word => {
+                index = if rightwards { range.end } else { range.start };
+                break;
+            }
         }
+
+        index = if rightwards { range.end } else { range.start };
     }
 
     index
@@ -136,6 +177,32 @@ mod tests {
         assert_eq!(find_word_boundary(&Text::from("fn find_word_boundary(text: &str,"), 32, true), 33);
 
         assert_eq!(find_word_boundary(&Text::from("hello world hello world"), 12, true), 17);
+
+        // Words are grapheme based, and follow Unicode word boundaries.
+        assert_eq!(find_word_boundary(&Text::from("héllo 👨‍👩‍👧 wörld"), 0, true), 5);
+        assert_eq!(find_word_boundary(&Text::from("héllo 👨‍👩‍👧 wörld"), 5, true), 7);
+        assert_eq!(find_word_boundary(&Text::from("héllo 👨‍👩‍👧 wörld"), 7, true), 13);
+        assert_eq!(find_word_boundary(&Text::from("héllo 👨‍👩‍👧 wörld"), 13, false), 8);
+        assert_eq!(find_word_boundary(&Text::from("héllo 👨‍👩‍👧 wörld"), 8, false), 6);
+    }
+
+    #[test]
+    fn test_word_segments() {
+        let segments = word_segments(&Text::from("it's 2 👍!"));
+        assert_eq!(segments, vec![
+            (0..4, SegmentKind::Word),
+            (4..5, SegmentKind::Whitespace),
+            (5..6, SegmentKind::Word),
+            (6..7, SegmentKind::Whitespace),
+            (7..8, SegmentKind::Punctuation),
+            (8..9, SegmentKind::Punctuation)
+        ]);
+    }
+
+    #[test]
+    fn test_sentence_ranges() {
+        let text = Text::from("Hello there. How are you? Fine");
+        assert_eq!(sentence_ranges(&text), vec![0..13, 13..26, 26..30]);
     }
 
     #[test]
@@ -0,0 +1,406 @@
+//! What screen readers and other assistive technologies are told about a
+//! window's views: a tree of the views that mean something to the user,
+//! such as labels, buttons and text fields, with what each is, says and
+//! holds. See `Window::accessibility_tree`.
+//!
+//! Views describe themselves through their behavior, such as a `Label`
+//! reading out its text, and any view can be described differently with
+//! `View::set_accessibility_label` and the like.
+//!
+//! With the `accesskit` feature, a tree can be turned into an update for
+//! an AccessKit platform adapter with `AccessibilityNode::to_tree_update`.
+//! On Linux, each window is connected to screen readers this way, and what
+//! they ask of a view is performed with `View::perform_accessibility_action`.
+
+use crate::graphics::Rectangle;
+use crate::ui::View;
+
+/// What kind of control a view is, to assistive technologies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessibilityRole {
+    Window,
+
+    /// A view holding other views, such as a plain `View`. Groups are left
+    /// out of the tree unless they have a label.
+    Group,
+
+    /// Text that can't be edited, such as a `Label`.
+    StaticText,
+
+    Button,
+
+    /// A single line of editable text, such as a `TextField`.
+    TextField,
+
+    /// Lines of editable text, such as a `TextView`.
+    TextArea,
+
+    Image,
+    ScrollView
+}
+
+/// What an assistive technology can ask of a view, see
+/// `View::perform_accessibility_action`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessibilityAction {
+    /// Does what a tap on the view does, such as pressing a button.
+    Activate,
+
+    /// Makes the view the first responder.
+    Focus,
+
+    /// Replaces what the view holds, such as a text field's text.
+    SetValue(String)
+}
+
+/// A view as assistive technologies see it. See `Window::accessibility_tree`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityNode {
+    /// The view's `View::id`, which stays the same while the view exists.
+    pub id: usize,
+
+    pub role: AccessibilityRole,
+
+    /// What the view is read out as, such as a button's title.
+    pub label: Option<String>,
+
+    /// What the view holds, such as a text field's text.
+    pub value: Option<String>,
+
+    /// The view's identifier, for tests driving the application through
+    /// its accessibility tree.
+    pub identifier: Option<String>,
+
+    /// The view's frame, relative to the view the tree was built from.
+    pub frame: Rectangle<i32, u32>,
+
+    /// Whether the view has keyboard focus.
+    pub is_focused: bool,
+
+    pub children: Vec<AccessibilityNode>
+}
+
+impl AccessibilityNode {
+    /// The node for the view with `id` in this node's tree, if any.
+    pub fn node_with_id(&self, id: usize) -> Option<&AccessibilityNode> {
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            if node.id == id {
+                return Some(node);
+            }
+            stack.extend(node.children.iter());
+        }
+
+        None
+    }
+
+    /// The focused node in this node's tree, if any.
+    pub fn focused_node(&self) -> Option<&AccessibilityNode> {
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            if node.is_focused {
+                return Some(node);
+            }
+            stack.extend(node.children.iter());
+        }
+
+        None
+    }
+
+    /// The whole tree as an update for an AccessKit platform adapter, with
+    /// frames multiplied by `scale` to give pixels. Focus is on the focused
+    /// node, or on this one if there isn't one.
+    #[cfg(feature = "accesskit")]
+    pub fn to_tree_update(&self, scale: f64) -> accesskit::TreeUpdate {
+        let mut classes = accesskit::NodeClassSet::new();
+        let mut nodes = Vec::new();
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            let mut builder = accesskit::NodeBuilder::new(node.role.to_accesskit());
+            if let Some(label) = &node.label {
+                builder.set_name(label.as_str());
+            }
+            if let Some(value) = &node.value {
+                builder.set_value(value.as_str());
+            }
+
+            let frame = &node.frame;
+            builder.set_bounds(accesskit::Rect::new(
+                frame.left() as f64 * scale,
+                frame.top() as f64 * scale,
+                frame.right() as f64 * scale,
+                frame.bottom() as f64 * scale
+            ));
+            builder.set_children(node.children.iter().map(|child| accesskit::NodeId(child.id as u64)).collect::<Vec<_>>());
+            for action in node.role.accesskit_actions() {
+                builder.add_action(*action);
+            }
+
+            nodes.push((accesskit::NodeId(node.id as u64), builder.build(&mut classes)));
+            stack.extend(node.children.iter());
+        }
+
+        let root = accesskit::NodeId(self.id as u64);
+        let mut tree = accesskit::Tree::new(root);
+        tree.toolkit_name = Some(String::from("Pelican"));
+        tree.toolkit_version = Some(String::from(env!("CARGO_PKG_VERSION")));
+
+        accesskit::TreeUpdate {
+            nodes,
+            tree: Some(tree),
+            focus: self.focused_node().map_or(root, |node| accesskit::NodeId(node.id as u64))
+        }
+    }
+}
+
+#[cfg(feature = "accesskit")]
+impl AccessibilityRole {
+    fn to_accesskit(self) -> accesskit::Role {
+        match self {
+            AccessibilityRole::Window => accesskit::Role::Window,
+            AccessibilityRole::Group => accesskit::Role::Group,
+            AccessibilityRole::StaticText => accesskit::Role::StaticText,
+            AccessibilityRole::Button => accesskit::Role::Button,
+            AccessibilityRole::TextField => accesskit::Role::TextInput,
+            AccessibilityRole::TextArea => accesskit::Role::MultilineTextInput,
+            AccessibilityRole::Image => accesskit::Role::Image,
+            AccessibilityRole::ScrollView => accesskit::Role::ScrollView
+        }
+    }
+
+    /// The actions screen readers are offered on views with this role.
+    fn accesskit_actions(self) -> &'static [accesskit::Action] {
+        match self {
+            AccessibilityRole::Button => &[accesskit::Action::Default, accesskit::Action::Focus],
+            AccessibilityRole::TextField | AccessibilityRole::TextArea => &[accesskit::Action::Focus, accesskit::Action::SetValue],
+            _ => &[]
+        }
+    }
+}
+
+#[cfg(feature = "accesskit")]
+impl AccessibilityAction {
+    /// The action `request` asks for, and the id of the view it's for, or
+    /// `None` if it's an action views can't perform.
+    pub(crate) fn from_accesskit(request: accesskit::ActionRequest) -> Option<(usize, AccessibilityAction)> {
+        let action = match (request.action, request.data) {
+            (accesskit::Action::Default, _) => AccessibilityAction::Activate,
+            (accesskit::Action::Focus, _) => AccessibilityAction::Focus,
+            (accesskit::Action::SetValue, Some(accesskit::ActionData::Value(value))) => AccessibilityAction::SetValue(value.into()),
+            _ => return None
+        };
+
+        Some((request.target.0 as usize, action))
+    }
+}
+
+/// The accessibility tree of `root_view`'s hierarchy, leaving out hidden
+/// views, `excluded` views and their subviews.
+///
+/// Accessibility elements are leaves: their subviews, such as a button's
+/// title label, are read out as part of them. Other views only have nodes
+/// if they have a label; otherwise their subviews' nodes take their place.
+pub(crate) fn tree(root_view: &View, excluded: &[View], focused_view: Option<&View>) -> AccessibilityNode {
+    let mut root = node(root_view, root_view, focused_view);
+    root.children = children(root_view, root_view, excluded, focused_view);
+    root
+}
+
+fn node(view: &View, root_view: &View, focused_view: Option<&View>) -> AccessibilityNode {
+    let size = view.frame().size;
+    AccessibilityNode {
+        id: view.id(),
+        role: view.accessibility_role(),
+        label: view.accessibility_label(),
+        value: view.accessibility_value(),
+        identifier: view.identifier(),
+        frame: view.convert_rect_to(&Rectangle::new(0, 0, size.width, size.height), root_view),
+        is_focused: focused_view == Some(view),
+        children: Vec::new()
+    }
+}
+
+fn children(view: &View, root_view: &View, excluded: &[View], focused_view: Option<&View>) -> Vec<AccessibilityNode> {
+    let mut children = Vec::new();
+
+    for subview in view.subviews() {
+        if subview.is_hidden() || excluded.contains(&subview) {
+            continue;
+        }
+
+        if subview.is_accessibility_element() {
+            // An element is focused when a view inside it is, such as the
+            // text field inside a `TextView`.
+            let mut element = node(&subview, root_view, focused_view);
+            element.is_focused = focused_view.is_some_and(|focused_view| is_in_hierarchy(focused_view, &subview));
+            children.push(element);
+        } else if subview.accessibility_label().is_some() {
+            let mut group = node(&subview, root_view, focused_view);
+            group.children = self::children(&subview, root_view, excluded, focused_view);
+            children.push(group);
+        } else {
+            children.extend(self::children(&subview, root_view, excluded, focused_view));
+        }
+    }
+
+    children
+}
+
+/// Whether `view` is `ancestor` or one of its subviews, their subviews,
+/// and so on.
+fn is_in_hierarchy(view: &View, ancestor: &View) -> bool {
+    let mut current_view = Some(view.clone());
+    while let Some(view) = current_view {
+        if view == *ancestor {
+            return true;
+        }
+        current_view = view.superview().upgrade();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{Button, Label, TextField};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_tree() {
+        let root_view = View::new(Rectangle::new(0, 0, 400, 300));
+
+        let container = View::new(Rectangle::new(10, 10, 300, 200));
+        let title = Label::new(Rectangle::new(0, 0, 200, 20), String::from("Sign in"));
+        container.add_subview(title.view.clone());
+        let name = TextField::new(Rectangle::new(0, 30, 200, 30), String::from("Ada"));
+        name.view.set_identifier(Some("name"));
+        name.view.set_accessibility_label(Some("Name"));
+        container.add_subview(name.view.clone());
+        root_view.add_subview(container);
+
+        let buttons = View::new(Rectangle::new(10, 250, 300, 40));
+        buttons.set_accessibility_label(Some("Actions"));
+        let ok = Button::new(Rectangle::new(0, 0, 80, 40), "OK", || {});
+        buttons.add_subview(ok.view.clone());
+        let hidden = Button::new(Rectangle::new(100, 0, 80, 40), "Hidden", || {});
+        hidden.view.set_hidden(true);
+        buttons.add_subview(hidden.view);
+        root_view.add_subview(buttons.clone());
+
+        let excluded = Label::new(Rectangle::new(0, 0, 10, 10), String::from("Overlay"));
+        root_view.add_subview(excluded.view.clone());
+
+        let tree = tree(&root_view, &[excluded.view], Some(&name.view));
+        assert_eq!(tree.role, AccessibilityRole::Group);
+        assert_eq!(tree.children.len(), 3);
+
+        assert_eq!(tree.children[0], AccessibilityNode {
+            id: title.view.id(),
+            role: AccessibilityRole::StaticText,
+            label: Some(String::from("Sign in")),
+            value: None,
+            identifier: None,
+            frame: Rectangle::new(10, 10, 200, 20),
+            is_focused: false,
+            children: Vec::new()
+        });
+
+        let name_node = &tree.children[1];
+        assert_eq!(name_node.role, AccessibilityRole::TextField);
+        assert_eq!(name_node.label.as_deref(), Some("Name"));
+        assert_eq!(name_node.value.as_deref(), Some("Ada"));
+        assert_eq!(name_node.identifier.as_deref(), Some("name"));
+        assert!(name_node.children.is_empty());
+        assert_eq!(tree.focused_node(), Some(name_node));
+
+        let group = &tree.children[2];
+        assert_eq!(group.id, buttons.id());
+        assert_eq!(group.role, AccessibilityRole::Group);
+        assert_eq!(group.label.as_deref(), Some("Actions"));
+        assert_eq!(group.children.len(), 1);
+        assert_eq!(group.children[0].role, AccessibilityRole::Button);
+        assert_eq!(group.children[0].label.as_deref(), Some("OK"));
+        assert_eq!(group.children[0].frame, Rectangle::new(10, 250, 80, 40));
+        assert_eq!(tree.node_with_id(ok.view.id()), Some(&group.children[0]));
+    }
+
+    #[test]
+    fn test_overridden_properties() {
+        let view = View::new(Rectangle::new(0, 0, 100, 100));
+        assert_eq!(view.accessibility_role(), AccessibilityRole::Group);
+        assert!(!view.is_accessibility_element());
+
+        view.set_accessibility_role(Some(AccessibilityRole::Image));
+        view.set_accessibility_value(Some("3 of 5 stars"));
+        assert!(view.is_accessibility_element());
+        assert_eq!(view.accessibility_value().as_deref(), Some("3 of 5 stars"));
+
+        let label = Label::new(Rectangle::new(0, 0, 100, 20), String::from("Decoration"));
+        assert!(label.view.is_accessibility_element());
+        label.view.set_is_accessibility_element(Some(false));
+        assert!(!label.view.is_accessibility_element());
+        label.view.set_is_accessibility_element(None);
+        assert!(label.view.is_accessibility_element());
+    }
+
+    #[test]
+    fn test_perform_action() {
+        let root_view = View::new(Rectangle::new(0, 0, 400, 300));
+        let taps = Rc::new(Cell::new(0));
+        let taps_clone = taps.clone();
+        let ok = Button::new(Rectangle::new(0, 0, 80, 40), "OK", move || taps_clone.set(taps_clone.get() + 1));
+        root_view.add_subview(ok.view.clone());
+        let name = TextField::new(Rectangle::new(0, 50, 200, 30), String::from("Ada"));
+        root_view.add_subview(name.view.clone());
+
+        let button = root_view.view_with_id(ok.view.id()).unwrap();
+        assert!(button.perform_accessibility_action(&AccessibilityAction::Activate));
+        assert_eq!(taps.get(), 1);
+        assert!(!button.perform_accessibility_action(&AccessibilityAction::SetValue(String::from("Cancel"))));
+
+        let field = root_view.view_with_id(name.view.id()).unwrap();
+        assert!(field.perform_accessibility_action(&AccessibilityAction::SetValue(String::from("Grace"))));
+        assert_eq!(name.label().copy_text(), "Grace");
+        assert!(!field.perform_accessibility_action(&AccessibilityAction::Activate));
+
+        // Disabled buttons can't be pressed, and nothing can be focused
+        // outside a window.
+        ok.set_enabled(false);
+        assert!(!button.perform_accessibility_action(&AccessibilityAction::Activate));
+        assert_eq!(taps.get(), 1);
+        assert!(!field.perform_accessibility_action(&AccessibilityAction::Focus));
+    }
+
+    #[cfg(feature = "accesskit")]
+    #[test]
+    fn test_to_tree_update() {
+        let root_view = View::new(Rectangle::new(0, 0, 400, 300));
+        let ok = Button::new(Rectangle::new(10, 20, 80, 40), "OK", || {});
+        root_view.add_subview(ok.view.clone());
+
+        let update = tree(&root_view, &[], Some(&ok.view)).to_tree_update(2.0);
+        assert_eq!(update.nodes.len(), 2);
+        assert_eq!(update.tree.unwrap().root, accesskit::NodeId(root_view.id() as u64));
+        assert_eq!(update.focus, accesskit::NodeId(ok.view.id() as u64));
+
+        let (_, button) = update.nodes.iter().find(|(id, _)| id.0 == ok.view.id() as u64).unwrap();
+        assert_eq!(button.role(), accesskit::Role::Button);
+        assert_eq!(button.name(), Some("OK"));
+        assert_eq!(button.bounds(), Some(accesskit::Rect::new(20.0, 40.0, 180.0, 120.0)));
+        assert!(button.supports_action(accesskit::Action::Default));
+
+        let request = accesskit::ActionRequest {
+            action: accesskit::Action::SetValue,
+            target: accesskit::NodeId(7),
+            data: Some(accesskit::ActionData::Value("Ada".into()))
+        };
+        assert_eq!(AccessibilityAction::from_accesskit(request), Some((7, AccessibilityAction::SetValue(String::from("Ada")))));
+
+        let request = accesskit::ActionRequest { action: accesskit::Action::Blur, target: accesskit::NodeId(7), data: None };
+        assert_eq!(AccessibilityAction::from_accesskit(request), None);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::macros::*;
+use crate::ui::view::{AutoresizingMask, DefaultBehavior};
+use crate::ui::application::Application;
+use crate::ui::button::{Button, State};
+use crate::ui::key::KeyCode;
+use crate::ui::press::Press;
+use crate::ui::{Color, Label, SemanticColor, Shadow, View, WeakView};
+use crate::graphics::Rectangle;
+use crate::text::HorizontalAlignment;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The width of the box holding the alert's text and buttons.
+const ALERT_WIDTH: u32 = 280;
+
+/// The space around the alert's text.
+const PADDING: u32 = 16;
+
+/// The space between the title and the message.
+const TEXT_SPACING: u32 = 4;
+
+const BUTTON_HEIGHT: u32 = 44;
+
+type CompletionCallback = Rc<dyn Fn(usize, &AlertAction)>;
+
+/// Removes `view`, presented over a window's content such as an alert or a
+/// context menu. If `view` has keyboard focus, it's given back to
+/// `previous_first_responder`, the first responder from before `view` was
+/// presented.
+pub(crate) fn remove_giving_back_focus(view: &View, previous_first_responder: &WeakView) {
+    let window = view.window();
+    let is_first_responder = view.is_first_responder();
+    view.remove_from_superview();
+
+    if is_first_responder {
+        if let (Some(previous), Some(window)) = (previous_first_responder.upgrade(), window) {
+            window.replace_first_responder(previous);
+        }
+    }
+}
+
+/// How an alert's button is shown, and which keys choose it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertActionStyle {
+    /// Chosen with Return if it's the first default button.
+    Default,
+
+    /// Backs out of the alert. Chosen with Escape.
+    Cancel,
+
+    /// Does something that can't be undone, such as deleting. Shown in red.
+    Destructive
+}
+
+/// A button of an `Alert`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlertAction {
+    pub title: String,
+    pub style: AlertActionStyle
+}
+
+impl AlertAction {
+    pub fn new(title: &str, style: AlertActionStyle) -> AlertAction {
+        AlertAction {
+            title: title.to_string(),
+            style
+        }
+    }
+}
+
+// A modal message with buttons to choose from.
+//
+// An alert is presented over a window's content, dimming it and blocking
+// touches and key presses from reaching it until one of the alert's buttons
+// is chosen. The alert is then dismissed, and its completion callback is
+// called with the button chosen.
+//
+// Return chooses the first `Default` button, and Escape the `Cancel` button.
+custom_view!(
+    Alert subclasses DefaultBehavior
+
+    struct AlertBehavior {
+        title: String,
+        message: String,
+        actions: RefCell<Vec<AlertAction>>,
+        completion: RefCell<Option<CompletionCallback>>,
+
+        // The first responder before the alert was presented, given back
+        // when it's dismissed.
+        previous_first_responder: RefCell<WeakView>
+    }
+
+    impl Self {
+        pub fn new(title: &str, message: &str) -> Alert {
+            let alert = Self::new_all(
+                Rectangle::new(0, 0, 0, 0),
+                title.to_string(),
+                message.to_string(),
+                RefCell::new(Vec::new()),
+                RefCell::new(None),
+                RefCell::new(WeakView::none())
+            );
+
+            // The alert's own view dims the content behind it, and being in
+            // front of it takes the touches meant for it.
+            alert.view.set_background_color(Color::new(0, 0, 0, 100));
+            alert.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth, AutoresizingMask::FlexibleHeight]);
+            alert
+        }
+
+        pub fn title(&self) -> String {
+            self.behavior().title.clone()
+        }
+
+        pub fn message(&self) -> String {
+            self.behavior().message.clone()
+        }
+
+        /// Adds a button, after those already added. Buttons added after the
+        /// alert is presented aren't shown.
+        pub fn add_action(&self, action: AlertAction) {
+            self.behavior().actions.borrow_mut().push(action);
+        }
+
+        pub fn actions(&self) -> Vec<AlertAction> {
+            self.behavior().actions.borrow().clone()
+        }
+
+        /// Calls `action` with the index and action of the button chosen,
+        /// once the alert is dismissed.
+        pub fn on_complete(&self, action: impl Fn(usize, &AlertAction) + 'static) {
+            self.behavior().completion.replace(Some(Rc::new(action)));
+        }
+
+        /// Presents the alert over the key window's content. Returns `false`
+        /// if there's no key window.
+        pub fn present(&self) -> bool {
+            let window = Application::borrow().get_key_window().upgrade();
+            match window {
+                Some(window) => {
+                    self.present_in(&window);
+                    true
+                },
+                None => false
+            }
+        }
+
+        /// Presents the alert over the content of `view`, usually a window.
+        pub fn present_in(&self, view: &View) {
+            let size = view.bounds().size;
+            self.view.set_frame(Rectangle::new(0, 0, size.width, size.height));
+            self.layout_content();
+            view.add_subview(self.view.clone());
+
+            if let Some(window) = view.window() {
+                self.behavior().previous_first_responder.replace(window.first_responder().downgrade());
+            }
+            self.view.become_first_responder();
+        }
+
+        /// Whether the alert is presented.
+        pub fn is_presented(&self) -> bool {
+            self.view.superview().upgrade().is_some_and(|superview| superview.subviews().contains(&self.view))
+        }
+
+        /// Dismisses the alert as if the button at `index` was chosen,
+        /// calling the completion callback.
+        pub fn choose_action(&self, index: usize) {
+            let action = match self.behavior().actions.borrow().get(index) {
+                Some(action) => action.clone(),
+                None => return
+            };
+
+            if !self.is_presented() {
+                return;
+            }
+
+            self.dismiss();
+
+            let completion = self.behavior().completion.borrow().clone();
+            if let Some(completion) = completion {
+                completion(index, &action);
+            }
+        }
+
+        /// Removes the alert, giving keyboard focus back to where it was.
+        fn dismiss(&self) {
+            let previous_first_responder = self.behavior().previous_first_responder.replace(WeakView::none());
+            remove_giving_back_focus(&self.view, &previous_first_responder);
+        }
+
+        /// The index of the first action with `style`.
+        fn index_of_action(&self, style: AlertActionStyle) -> Option<usize> {
+            self.behavior().actions.borrow().iter().position(|action| action.style == style)
+        }
+
+        /// Builds the box holding the text and buttons, in the middle of the
+        /// alert.
+        fn layout_content(&self) {
+            for subview in self.view.subviews() {
+                subview.remove_from_superview();
+            }
+
+            let text_width = ALERT_WIDTH - PADDING * 2;
+            let mut y = PADDING;
+
+            let title = Label::new(Rectangle::new(PADDING as i32, y as i32, text_width, 0), self.title());
+            title.set_text_alignment(HorizontalAlignment::Center);
+            title.set_number_of_lines(0);
+            let height = title.size_that_fits(text_width).height;
+            title.view.set_frame(Rectangle::new(PADDING as i32, y as i32, text_width, height));
+            y += height + TEXT_SPACING;
+
+            let message = Label::new(Rectangle::new(PADDING as i32, y as i32, text_width, 0), self.message());
+            message.set_text_alignment(HorizontalAlignment::Center);
+            message.set_number_of_lines(0);
+            message.set_text_color(Color::semantic(SemanticColor::SecondaryLabel));
+            let height = message.size_that_fits(text_width).height;
+            message.view.set_frame(Rectangle::new(PADDING as i32, y as i32, text_width, height));
+            y += height + PADDING;
+
+            // Two buttons go side by side, any other number one above the
+            // other.
+            let actions = self.actions();
+            let is_side_by_side = actions.len() == 2;
+            let button_width = if is_side_by_side { ALERT_WIDTH / 2 } else { ALERT_WIDTH };
+
+            let mut buttons = Vec::new();
+            for (index, action) in actions.iter().enumerate() {
+                let frame = if is_side_by_side {
+                    Rectangle::new((index as u32 * button_width) as i32, y as i32, button_width, BUTTON_HEIGHT)
+                } else {
+                    Rectangle::new(0, (y + index as u32 * BUTTON_HEIGHT) as i32, button_width, BUTTON_HEIGHT)
+                };
+
+                let weak_alert = self.view.downgrade();
+                let button = Button::new(frame, &action.title, move || {
+                    if let Some(view) = weak_alert.upgrade() {
+                        Alert::from_view(view).choose_action(index);
+                    }
+                });
+                if action.style == AlertActionStyle::Destructive {
+                    button.set_title_color(Color::new(220, 40, 40, 255), State::Normal);
+                }
+                buttons.push(button);
+            }
+
+            let buttons_height = match actions.len() {
+                0 => 0,
+                _ if is_side_by_side => BUTTON_HEIGHT,
+                count => count as u32 * BUTTON_HEIGHT
+            };
+            let height = y + buttons_height;
+
+            let size = self.view.frame().size;
+            let content = View::new(Rectangle::new(
+                (size.width as i32 - ALERT_WIDTH as i32) / 2,
+                (size.height as i32 - height as i32) / 2,
+                ALERT_WIDTH,
+                height
+            ));
+            content.set_background_color(Color::semantic(SemanticColor::Background));
+            content.set_corner_radius(12.0);
+            content.set_shadow(Some(Shadow::default()));
+            content.set_autoresizing_mask(&[
+                AutoresizingMask::FlexibleLeftMargin,
+                AutoresizingMask::FlexibleRightMargin,
+                AutoresizingMask::FlexibleTopMargin,
+                AutoresizingMask::FlexibleBottomMargin
+            ]);
+
+            content.add_subview(title.view);
+            content.add_subview(message.view);
+            for button in buttons {
+                content.add_subview(button.view);
+            }
+
+            self.view.add_subview(content);
+        }
+    }
+
+    impl Behavior {
+        fn can_become_first_responder(&self) -> bool {
+            true
+        }
+
+        // Keys don't reach the content behind the alert.
+        fn press_began(&self, press: &Press) {
+            let alert = self.view_type();
+            let key_code = press.key().key_code();
+
+            let index = match key_code {
+                KeyCode::Return | KeyCode::KpEnter => alert.index_of_action(AlertActionStyle::Default),
+                KeyCode::Escape => alert.index_of_action(AlertActionStyle::Cancel),
+                _ => None
+            };
+
+            if let Some(index) = index {
+                alert.choose_action(index);
+            }
+        }
+
+        fn press_ended(&self, _press: &Press) {}
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::key::Key;
+    use crate::ui::Touch;
+    use crate::ui::event::TouchEvent;
+    use crate::graphics::Point;
+    use std::cell::Cell;
+
+    fn alert_in(content: &View) -> (Alert, Rc<Cell<Option<usize>>>) {
+        let alert = Alert::new("Delete file?", "This can't be undone.");
+        alert.add_action(AlertAction::new("Cancel", AlertActionStyle::Cancel));
+        alert.add_action(AlertAction::new("Delete", AlertActionStyle::Destructive));
+        alert.add_action(AlertAction::new("Keep", AlertActionStyle::Default));
+
+        let chosen = Rc::new(Cell::new(None));
+        let chosen_clone = chosen.clone();
+        alert.on_complete(move |index, _| chosen_clone.set(Some(index)));
+
+        alert.present_in(content);
+        (alert, chosen)
+    }
+
+    #[test]
+    fn test_present_and_choose() {
+        let content = View::new(Rectangle::new(0, 0, 400, 300));
+        let (alert, chosen) = alert_in(&content);
+
+        assert!(alert.is_presented());
+        assert_eq!(alert.view.frame(), Rectangle::new(0, 0, 400, 300));
+
+        // The alert covers the content, so it gets the touches.
+        let button_behind = View::new(Rectangle::new(0, 0, 100, 100));
+        content.insert_subview_at(button_behind.clone(), 0);
+        assert_eq!(content.hit_test(&Point::new(10, 10)), Some(alert.view.clone()));
+
+        // Tapping a button chooses it.
+        let box_view = alert.view.subviews()[0].clone();
+        let delete = box_view.subviews()[3].clone();
+        let origin = delete.convert_point_to(&Point::new(5, 5), &content);
+        let touches = vec![Touch::new(0, origin)];
+        let event = TouchEvent::new();
+        let press = delete.gesture_recognizers()[0].upgrade().unwrap();
+        press.touches_began(&touches, &event);
+        press.touches_ended(&touches, &event);
+
+        assert_eq!(chosen.get(), Some(1));
+        assert!(!alert.is_presented());
+        assert_eq!(content.hit_test(&Point::new(10, 10)), Some(button_behind));
+    }
+
+    #[test]
+    fn test_keys_choose_actions() {
+        let content = View::new(Rectangle::new(0, 0, 400, 300));
+        let (alert, chosen) = alert_in(&content);
+
+        // Other keys are swallowed.
+        alert.view.behavior().press_began(&Press::new(Key::new(KeyCode::A, vec![])));
+        assert_eq!(chosen.get(), None);
+
+        alert.view.behavior().press_began(&Press::new(Key::new(KeyCode::Return, vec![])));
+        assert_eq!(chosen.get(), Some(2));
+
+        let (alert, chosen) = alert_in(&content);
+        alert.view.behavior().press_began(&Press::new(Key::new(KeyCode::Escape, vec![])));
+        assert_eq!(chosen.get(), Some(0));
+
+        // Once dismissed, it can't be chosen again.
+        alert.choose_action(1);
+        assert_eq!(chosen.get(), Some(0));
+    }
+}
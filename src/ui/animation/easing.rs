@@ -0,0 +1,147 @@
+use crate::ui::animation::Spring;
+
+/// How an animation's progress changes over its duration.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+    /// Changes at a constant speed.
+    Linear,
+
+    /// Starts slowly and speeds up. The CSS `ease-in` curve.
+    EaseIn,
+
+    /// Starts quickly and slows down. The CSS `ease-out` curve.
+    EaseOut,
+
+    /// Starts slowly, speeds up, and slows down again at the end. The CSS
+    /// `ease-in-out` curve.
+    #[default]
+    EaseInOut,
+
+    /// A cubic Bézier curve from (0, 0) to (1, 1) with the control points
+    /// (x1, y1) and (x2, y2), as in CSS's `cubic-bezier()`. The x values are
+    /// clamped to between 0 and 1; the y values can overshoot.
+    CubicBezier(f32, f32, f32, f32),
+
+    /// Follows a spring from the old values to the new ones, stretched over
+    /// the animation's duration. Animate for `Spring::settling_duration` for
+    /// the spring to move at its natural speed.
+    Spring(Spring)
+}
+
+impl Easing {
+    /// The progress of the animated values when `time`, from 0 to 1, of the
+    /// duration has passed.
+    pub fn apply(&self, time: f32) -> f32 {
+        let time = time.clamp(0.0, 1.0);
+
+        match *self {
+            Easing::Linear => time,
+            Easing::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, time),
+            Easing::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, time),
+            Easing::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, time),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, time),
+            Easing::Spring(spring) => {
+                if time >= 1.0 {
+                    return 1.0;
+                }
+
+                let seconds = time * spring.settling_duration().as_secs_f32();
+                spring.value_at(seconds)
+            }
+        }
+    }
+}
+
+/// The y value of the curve at `x`, finding the curve's parameter for `x`
+/// with Newton's method and falling back to bisection.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let x1 = x1.clamp(0.0, 1.0);
+    let x2 = x2.clamp(0.0, 1.0);
+
+    // The polynomial for one coordinate of the curve, with the end points at
+    // 0 and 1.
+    let curve = |p1: f32, p2: f32, t: f32| {
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        let a = 1.0 - c - b;
+        ((a * t + b) * t + c) * t
+    };
+    let slope = |p1: f32, p2: f32, t: f32| {
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        let a = 1.0 - c - b;
+        (3.0 * a * t + 2.0 * b) * t + c
+    };
+
+    let mut t = x;
+    for _ in 0..8 {
+        let error = curve(x1, x2, t) - x;
+        if error.abs() < 1e-6 {
+            return curve(y1, y2, t);
+        }
+
+        let derivative = slope(x1, x2, t);
+        if derivative.abs() < 1e-6 {
+            break;
+        }
+        t -= error / derivative;
+    }
+
+    let mut low = 0.0;
+    let mut high = 1.0;
+    t = x;
+    while high - low > 1e-6 {
+        if curve(x1, x2, t) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.0;
+    }
+
+    curve(y1, y2, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 0.001, "{} isn't close to {}", value, expected);
+    }
+
+    #[test]
+    fn test_apply() {
+        let easings = [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::CubicBezier(0.25, 0.1, 0.25, 1.0),
+            Easing::Spring(Spring::default())
+        ];
+        for easing in easings.iter() {
+            assert_close(easing.apply(0.0), 0.0);
+            assert_close(easing.apply(1.0), 1.0);
+            assert_close(easing.apply(2.0), 1.0);
+        }
+
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert!(Easing::EaseIn.apply(0.25) < 0.25);
+        assert!(Easing::EaseOut.apply(0.25) > 0.25);
+        assert_close(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_cubic_bezier() {
+        // Control points on the diagonal make a straight line.
+        assert_close(Easing::CubicBezier(0.25, 0.25, 0.75, 0.75).apply(0.3), 0.3);
+
+        // Values from CSS's `ease` curve.
+        let ease = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+        assert_close(ease.apply(0.5), 0.8024);
+
+        // The y values can overshoot.
+        assert!(Easing::CubicBezier(0.3, 1.5, 0.7, 1.5).apply(0.5) > 1.0);
+    }
+}
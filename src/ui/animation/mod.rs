@@ -0,0 +1,17 @@
+//! Animating changes to views.
+//!
+//! `View::animate` makes changes to a view, such as moving it or fading it
+//! out, and then animates from the old values to the new ones over the
+//! following frames.
+
+mod property_animation;
+pub use property_animation::Animation;
+
+mod easing;
+pub use easing::Easing;
+
+mod spring;
+pub use spring::Spring;
+
+mod view_properties;
+pub(crate) use view_properties::ViewProperties;
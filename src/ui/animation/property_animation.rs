@@ -0,0 +1,260 @@
+use crate::ui::animation::{Easing, ViewProperties};
+use crate::ui::display_link::DisplayLink;
+use crate::ui::{View, WeakView};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+type CompletionCallback = Box<dyn FnOnce(bool)>;
+
+type ProgressCallback = Box<dyn Fn(&View, f32)>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Running,
+    Finished,
+    Cancelled
+}
+
+struct AnimationInner {
+    view: WeakView,
+
+    // Updates the view for how far through the animation it is, from 0 at
+    // the start to 1 at the end, after easing.
+    apply_progress: ProgressCallback,
+
+    duration: Duration,
+    easing: Easing,
+
+    // The timestamp of the first frame, when the animation starts.
+    started_at: Cell<Option<Instant>>,
+
+    state: Cell<State>,
+    completion: RefCell<Option<CompletionCallback>>,
+
+    // The display link stepping the animation. Taken and invalidated when
+    // the animation ends.
+    display_link: RefCell<Option<DisplayLink>>
+}
+
+/// An animation of a view's frame, background color and alpha, started with
+/// `View::animate`, or of something else about a view, such as a
+/// `ShapeView`'s path with `ShapeView::animate_path`.
+///
+/// The animation runs once per frame from the run loop, and stops on its own
+/// once its duration has passed. It's also stopped if the view is dropped.
+pub struct Animation {
+    inner: Rc<AnimationInner>
+}
+
+impl Animation {
+    /// Makes `changes` to `view`, then puts the view back and animates to
+    /// the changed values from the next frame.
+    pub(crate) fn new(view: &View, duration: Duration, easing: Easing, changes: impl FnOnce(&View)) -> Animation {
+        let animation = Animation::new_unscheduled(view, duration, easing, changes);
+        animation.schedule();
+        animation
+    }
+
+    /// Animates `view` from the next frame by calling `apply_progress` each
+    /// frame with the view and how far through the animation it is, from 0
+    /// to 1 after easing.
+    pub(crate) fn with_progress(
+        view: &View,
+        duration: Duration,
+        easing: Easing,
+        apply_progress: impl Fn(&View, f32) + 'static
+    ) -> Animation {
+        let animation = Animation::unscheduled_with_progress(view, duration, easing, apply_progress);
+        animation.schedule();
+        animation
+    }
+
+    /// Captures the changes to animate without stepping the animation.
+    fn new_unscheduled(view: &View, duration: Duration, easing: Easing, changes: impl FnOnce(&View)) -> Animation {
+        let from = ViewProperties::of(view);
+        changes(view);
+        let to = ViewProperties::of(view);
+        from.apply_to(view);
+
+        Animation::unscheduled_with_progress(view, duration, easing, move |view, progress| {
+            from.interpolate(&to, progress).apply_to(view);
+        })
+    }
+
+    fn unscheduled_with_progress(
+        view: &View,
+        duration: Duration,
+        easing: Easing,
+        apply_progress: impl Fn(&View, f32) + 'static
+    ) -> Animation {
+        Animation {
+            inner: Rc::new(AnimationInner {
+                view: view.downgrade(),
+                apply_progress: Box::new(apply_progress),
+                duration,
+                easing,
+                started_at: Cell::new(None),
+                state: Cell::new(State::Running),
+                completion: RefCell::new(None),
+                display_link: RefCell::new(None)
+            })
+        }
+    }
+
+    /// Steps the animation each frame from the run loop.
+    fn schedule(&self) {
+        let animation = self.clone();
+        let display_link = DisplayLink::new(move |frame| {
+            animation.step(frame.timestamp);
+        });
+        display_link.set_frame_interval(Duration::from_millis(0));
+        display_link.add_to_run_loop();
+        *self.inner.display_link.borrow_mut() = Some(display_link);
+    }
+
+    /// Sets a closure called once the animation ends, with `true` if it ran
+    /// to the end or `false` if it was cancelled. If the animation has
+    /// already ended, the closure is called right away.
+    pub fn on_complete(&self, completion: impl FnOnce(bool) + 'static) {
+        match self.inner.state.get() {
+            State::Running => *self.inner.completion.borrow_mut() = Some(Box::new(completion)),
+            State::Finished => completion(true),
+            State::Cancelled => completion(false)
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.inner.state.get() == State::Running
+    }
+
+    /// Whether the animation ran to the end, rather than being cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.inner.state.get() == State::Finished
+    }
+
+    /// Stops the animation, leaving the view as it is in the current frame.
+    pub fn cancel(&self) {
+        self.end(State::Cancelled);
+    }
+
+    /// Updates the view for the frame at `now`.
+    pub(crate) fn step(&self, now: Instant) {
+        if !self.is_running() {
+            return;
+        }
+
+        let view = match self.inner.view.upgrade() {
+            Some(view) => view,
+            None => {
+                self.cancel();
+                return;
+            }
+        };
+
+        let started_at = self.inner.started_at.get().unwrap_or(now);
+        self.inner.started_at.set(Some(started_at));
+        let elapsed = now.saturating_duration_since(started_at);
+        let time = if self.inner.duration.as_secs_f32() > 0.0 {
+            elapsed.as_secs_f32() / self.inner.duration.as_secs_f32()
+        } else {
+            1.0
+        };
+
+        let progress = self.inner.easing.apply(time);
+        (self.inner.apply_progress)(&view, progress);
+
+        if time >= 1.0 {
+            self.end(State::Finished);
+        }
+    }
+
+    fn end(&self, state: State) {
+        if !self.is_running() {
+            return;
+        }
+
+        self.inner.state.set(state);
+
+        if let Some(display_link) = self.inner.display_link.borrow_mut().take() {
+            display_link.invalidate();
+        }
+
+        let completion = self.inner.completion.borrow_mut().take();
+        if let Some(completion) = completion {
+            completion(state == State::Finished);
+        }
+    }
+}
+
+impl Clone for Animation {
+    fn clone(&self) -> Self {
+        Animation {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Rectangle;
+    use crate::ui::Color;
+
+    #[test]
+    fn test_step() {
+        let view = View::new(Rectangle::new(0, 0, 100, 100));
+        let animation = Animation::new_unscheduled(&view, Duration::from_millis(100), Easing::Linear, |view| {
+            view.set_frame(Rectangle::new(100, 0, 100, 100));
+            view.set_background_color(Color::black());
+            view.set_alpha(0.0);
+        });
+
+        // Nothing changes until the first frame.
+        assert_eq!(view.frame(), Rectangle::new(0, 0, 100, 100));
+        assert_eq!(view.background_color(), Color::white());
+
+        let completed = Rc::new(Cell::new(None));
+        let completed_clone = completed.clone();
+        animation.on_complete(move |finished| completed_clone.set(Some(finished)));
+
+        let start = Instant::now();
+        animation.step(start);
+        assert_eq!(view.frame(), Rectangle::new(0, 0, 100, 100));
+
+        animation.step(start + Duration::from_millis(25));
+        assert_eq!(view.frame(), Rectangle::new(25, 0, 100, 100));
+        assert_eq!(view.background_color(), Color::new(225, 225, 225, 255));
+        assert_eq!(view.alpha(), 0.75);
+        assert!(animation.is_running());
+
+        animation.step(start + Duration::from_millis(120));
+        assert_eq!(view.frame(), Rectangle::new(100, 0, 100, 100));
+        assert_eq!(view.background_color(), Color::black());
+        assert!(animation.is_finished());
+        assert_eq!(completed.get(), Some(true));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let view = View::new(Rectangle::new(0, 0, 100, 100));
+        let animation = Animation::new_unscheduled(&view, Duration::from_millis(100), Easing::Linear, |view| {
+            view.set_frame(Rectangle::new(0, 100, 100, 100));
+        });
+
+        let start = Instant::now();
+        animation.step(start);
+        animation.step(start + Duration::from_millis(50));
+        animation.cancel();
+        animation.step(start + Duration::from_millis(100));
+
+        assert_eq!(view.frame(), Rectangle::new(0, 50, 100, 100));
+        assert!(!animation.is_running());
+        assert!(!animation.is_finished());
+
+        let completed = Rc::new(Cell::new(None));
+        let completed_clone = completed.clone();
+        animation.on_complete(move |finished| completed_clone.set(Some(finished)));
+        assert_eq!(completed.get(), Some(false));
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+/// How close to the end a spring has to stay to be settled, as a fraction of
+/// the distance moved.
+const SETTLED_DISTANCE: f32 = 0.001;
+
+/// The longest a spring is simulated for.
+const MAX_SETTLING_SECONDS: f32 = 10.0;
+
+/// A damped spring, pulling an animation from its old values to its new
+/// ones.
+///
+/// Stiffer springs move faster, and less damped springs bounce past the end
+/// before settling. A spring is critically damped, settling as quickly as it
+/// can without bouncing, when `damping` is `2 * sqrt(stiffness * mass)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spring {
+    pub stiffness: f32,
+    pub damping: f32,
+    pub mass: f32,
+
+    /// The speed at the start, in the distance moved per second. Useful for
+    /// continuing the speed of a gesture.
+    pub initial_velocity: f32
+}
+
+impl Spring {
+    /// A spring with a mass of 1, starting at rest.
+    pub fn new(stiffness: f32, damping: f32) -> Spring {
+        Spring {
+            stiffness,
+            damping,
+            mass: 1.0,
+            initial_velocity: 0.0
+        }
+    }
+
+    /// The ratio of the damping to critical damping. Springs under 1 bounce.
+    pub fn damping_ratio(&self) -> f32 {
+        self.damping / (2.0 * (self.stiffness * self.mass).sqrt())
+    }
+
+    /// The progress from the old values, at 0, to the new ones, at 1, after
+    /// `seconds`.
+    pub fn value_at(&self, seconds: f32) -> f32 {
+        let t = seconds;
+        let v0 = self.initial_velocity;
+        let omega = (self.stiffness / self.mass).sqrt();
+        let zeta = self.damping_ratio();
+
+        // The remaining distance to the end starts at 1 and decays to 0.
+        let remaining = if zeta < 1.0 {
+            let damped_omega = omega * (1.0 - zeta * zeta).sqrt();
+            let envelope = (-zeta * omega * t).exp();
+            envelope * ((damped_omega * t).cos() + (zeta * omega - v0) / damped_omega * (damped_omega * t).sin())
+        } else if zeta == 1.0 {
+            (-omega * t).exp() * (1.0 + (omega - v0) * t)
+        } else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let slow = -omega * (zeta - root);
+            let fast = -omega * (zeta + root);
+            let fast_coefficient = (-v0 - slow) / (fast - slow);
+            (1.0 - fast_coefficient) * (slow * t).exp() + fast_coefficient * (fast * t).exp()
+        };
+
+        1.0 - remaining
+    }
+
+    /// How long until the spring comes to rest at the end, to within a
+    /// thousandth of the distance.
+    pub fn settling_duration(&self) -> Duration {
+        let step = 0.001;
+        let mut settled_at = 0.0;
+        let mut seconds = 0.0;
+
+        while seconds < MAX_SETTLING_SECONDS {
+            if (1.0 - self.value_at(seconds)).abs() >= SETTLED_DISTANCE {
+                settled_at = seconds + step;
+            }
+            seconds += step;
+        }
+
+        Duration::from_secs_f32(settled_at)
+    }
+}
+
+impl Default for Spring {
+    /// A responsive spring that bounces slightly.
+    fn default() -> Self {
+        Spring::new(300.0, 20.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_at() {
+        let springs = [
+            Spring::new(300.0, 10.0),
+            Spring::new(100.0, 20.0),
+            Spring::new(100.0, 60.0),
+            Spring { initial_velocity: 5.0, ..Spring::default() }
+        ];
+
+        for spring in springs.iter() {
+            assert!(spring.value_at(0.0).abs() < 0.0001);
+
+            let settling_duration = spring.settling_duration().as_secs_f32();
+            assert!(settling_duration > 0.0 && settling_duration < MAX_SETTLING_SECONDS);
+            assert!((1.0 - spring.value_at(settling_duration)).abs() < SETTLED_DISTANCE);
+        }
+
+        // Only the underdamped spring overshoots.
+        let overshoots = |spring: &Spring| (0..1000).any(|ms| spring.value_at(ms as f32 / 1000.0) > 1.0 + SETTLED_DISTANCE);
+        assert!(overshoots(&springs[0]));
+        assert!(!overshoots(&springs[1]));
+        assert!(!overshoots(&springs[2]));
+
+        // Stiffer springs settle sooner.
+        assert!(Spring::new(400.0, 40.0).settling_duration() < springs[1].settling_duration());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::graphics::{Rectangle, Transform};
+use crate::ui::{Color, View};
+
+/// The animatable properties of a view.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ViewProperties {
+    frame: Rectangle<i32, u32>,
+    background_color: Color,
+    alpha: f32,
+    transform: Transform
+}
+
+impl ViewProperties {
+    pub(crate) fn of(view: &View) -> ViewProperties {
+        ViewProperties {
+            frame: view.frame(),
+            background_color: view.background_color(),
+            alpha: view.alpha(),
+            transform: view.transform()
+        }
+    }
+
+    pub(crate) fn apply_to(&self, view: &View) {
+        view.set_frame(self.frame.clone());
+        view.set_background_color(self.background_color.clone());
+        view.set_alpha(self.alpha);
+        view.set_transform(self.transform);
+    }
+
+    /// The properties `progress` of the way from these to `to`. Progress
+    /// outside of 0 to 1 overshoots, as springs do.
+    pub(crate) fn interpolate(&self, to: &ViewProperties, progress: f32) -> ViewProperties {
+        ViewProperties {
+            frame: Rectangle::new(
+                lerp_i32(self.frame.origin.x, to.frame.origin.x, progress),
+                lerp_i32(self.frame.origin.y, to.frame.origin.y, progress),
+                lerp_u32(self.frame.size.width, to.frame.size.width, progress),
+                lerp_u32(self.frame.size.height, to.frame.size.height, progress)
+            ),
+            background_color: self.background_color.interpolate(&to.background_color, progress),
+            alpha: self.alpha + (to.alpha - self.alpha) * progress,
+            transform: interpolate_transform(&self.transform, &to.transform, progress)
+        }
+    }
+}
+
+/// Interpolates the scale, rotation and translation of the transforms
+/// separately, so a rotation turns rather than shrinking through the middle.
+fn interpolate_transform(from: &Transform, to: &Transform, progress: f32) -> Transform {
+    if from == to {
+        return *from;
+    }
+
+    let (from_sx, from_sy, from_angle) = from.decompose();
+    let (to_sx, to_sy, to_angle) = to.decompose();
+    let mix = |from: f32, to: f32| from + (to - from) * progress;
+
+    Transform::compose(
+        mix(from_sx, to_sx),
+        mix(from_sy, to_sy),
+        mix(from_angle, to_angle),
+        mix(from.tx, to.tx),
+        mix(from.ty, to.ty)
+    )
+}
+
+fn lerp(from: f32, to: f32, progress: f32) -> f32 {
+    (from + (to - from) * progress).round()
+}
+
+fn lerp_i32(from: i32, to: i32, progress: f32) -> i32 {
+    lerp(from as f32, to as f32, progress) as i32
+}
+
+fn lerp_u32(from: u32, to: u32, progress: f32) -> u32 {
+    lerp(from as f32, to as f32, progress).max(0.0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate() {
+        let from = ViewProperties {
+            frame: Rectangle::new(0, 10, 100, 50),
+            background_color: Color::new(0, 0, 0, 255),
+            alpha: 1.0,
+            transform: Transform::identity()
+        };
+        let to = ViewProperties {
+            frame: Rectangle::new(-100, 20, 200, 50),
+            background_color: Color::new(255, 100, 0, 0),
+            alpha: 0.0,
+            transform: Transform::identity()
+        };
+
+        assert_eq!(from.interpolate(&to, 0.0), from);
+        assert_eq!(from.interpolate(&to, 1.0), to);
+        assert_eq!(from.interpolate(&to, 0.5), ViewProperties {
+            frame: Rectangle::new(-50, 15, 150, 50),
+            // Fading into a clear color doesn't pick up its components.
+            background_color: Color::new(0, 0, 0, 128),
+            alpha: 0.5,
+            transform: Transform::identity()
+        });
+    }
+
+    #[test]
+    fn test_interpolate_transform() {
+        let from = Transform::identity();
+        let to = Transform::compose(3.0, 3.0, 2.0, 10.0, 0.0);
+
+        let halfway = interpolate_transform(&from, &to, 0.5);
+        let (sx, sy, angle) = halfway.decompose();
+        assert!((sx - 2.0).abs() < 0.0001);
+        assert!((sy - 2.0).abs() < 0.0001);
+        assert!((angle - 1.0).abs() < 0.0001);
+        assert!((halfway.tx - 5.0).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,187 @@
+//! Light and dark appearances, and the semantic colors they give views.
+//!
+//! Rather than a fixed color, a view can ask its appearance for the color
+//! of what it draws, such as `SemanticColor::Label` for text. The
+//! application has one active appearance, switched with `set_appearance`,
+//! and any view can override it for itself and its subviews with
+//! `View::set_appearance`.
+//!
+//! When the appearance changes, each view affected is told through
+//! `Behavior::appearance_did_change`, and redrawn. Views' background colors
+//! and labels' text colors that were never set follow the new appearance;
+//! a color the app set is left alone, even if it's the same as one of the
+//! appearance's. Dynamic colors, such as `Color::semantic`, need no
+//! restyling, as they're resolved each time the view is drawn.
+//!
+//! ```ignore
+//! label.set_text_color(Color::semantic(SemanticColor::SecondaryLabel));
+//! appearance::set_appearance(Appearance::dark());
+//! ```
+
+use crate::ui::Color;
+use crate::ui::application::Application;
+use crate::ui::notification::{self, Notification, NotificationCenter};
+
+/// A color named by what it's for, rather than what it looks like, given
+/// by the appearance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SemanticColor {
+    /// Primary text.
+    Label,
+
+    /// Less important text, such as a subtitle.
+    SecondaryLabel,
+
+    /// The background of windows and content.
+    Background,
+
+    /// Backgrounds grouped within the main background, such as bars.
+    SecondaryBackground,
+
+    /// Lines between content, such as between table view rows.
+    Separator,
+
+    /// The tint of controls, such as buttons.
+    Accent
+}
+
+impl SemanticColor {
+    const ALL: [SemanticColor; 6] = [
+        SemanticColor::Label,
+        SemanticColor::SecondaryLabel,
+        SemanticColor::Background,
+        SemanticColor::SecondaryBackground,
+        SemanticColor::Separator,
+        SemanticColor::Accent
+    ];
+}
+
+/// The colors views use for each `SemanticColor`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Appearance {
+    is_dark: bool,
+    label: Color,
+    secondary_label: Color,
+    background: Color,
+    secondary_background: Color,
+    separator: Color,
+    accent: Color
+}
+
+impl Appearance {
+    /// Dark text on light backgrounds, the default.
+    pub const fn light() -> Appearance {
+        Appearance {
+            is_dark: false,
+            label: Color::new(0, 0, 0, 255),
+            secondary_label: Color::new(80, 80, 80, 255),
+            background: Color::new(255, 255, 255, 255),
+            secondary_background: Color::new(242, 242, 247, 255),
+            separator: Color::new(200, 200, 204, 255),
+            accent: Color::new(2, 117, 227, 255)
+        }
+    }
+
+    /// Light text on dark backgrounds.
+    pub const fn dark() -> Appearance {
+        Appearance {
+            is_dark: true,
+            label: Color::new(255, 255, 255, 255),
+            secondary_label: Color::new(170, 170, 176, 255),
+            background: Color::new(28, 28, 30, 255),
+            secondary_background: Color::new(44, 44, 46, 255),
+            separator: Color::new(72, 72, 74, 255),
+            accent: Color::new(10, 132, 255, 255)
+        }
+    }
+
+    pub fn is_dark(&self) -> bool {
+        self.is_dark
+    }
+
+    pub fn color(&self, semantic_color: SemanticColor) -> Color {
+        match semantic_color {
+            SemanticColor::Label => self.label.clone(),
+            SemanticColor::SecondaryLabel => self.secondary_label.clone(),
+            SemanticColor::Background => self.background.clone(),
+            SemanticColor::SecondaryBackground => self.secondary_background.clone(),
+            SemanticColor::Separator => self.separator.clone(),
+            SemanticColor::Accent => self.accent.clone()
+        }
+    }
+
+    /// The appearance, with `color` for `semantic_color`, such as for an
+    /// application's own accent color.
+    pub fn with_color(mut self, semantic_color: SemanticColor, color: Color) -> Appearance {
+        let color = color.resolved(&self);
+        match semantic_color {
+            SemanticColor::Label => self.label = color,
+            SemanticColor::SecondaryLabel => self.secondary_label = color,
+            SemanticColor::Background => self.background = color,
+            SemanticColor::SecondaryBackground => self.secondary_background = color,
+            SemanticColor::Separator => self.separator = color,
+            SemanticColor::Accent => self.accent = color
+        }
+        self
+    }
+
+    /// The semantic color `color` is in this appearance, if any.
+    pub fn semantic_color(&self, color: &Color) -> Option<SemanticColor> {
+        SemanticColor::ALL.iter().copied().find(|semantic_color| self.color(*semantic_color) == *color)
+    }
+}
+
+impl Default for Appearance {
+    fn default() -> Appearance {
+        Appearance::light()
+    }
+}
+
+/// The application's active appearance, used by views without one of their
+/// own.
+pub fn appearance() -> Appearance {
+    Application::borrow().appearance()
+}
+
+/// Switches the application's appearance, such as to dark mode, restyling
+/// and redrawing every window. Views with an appearance of their own keep
+/// it. Posts `notification::APPEARANCE_DID_CHANGE`.
+pub fn set_appearance(appearance: Appearance) {
+    let (previous, windows) = {
+        let mut application = Application::borrow_mut();
+        let previous = application.appearance();
+        if previous == appearance {
+            return;
+        }
+
+        application.set_appearance(appearance);
+        (previous, application.windows().clone())
+    };
+
+    for window in windows.iter() {
+        window.view.appearance_did_change(&previous);
+    }
+
+    NotificationCenter::post(Notification::new(notification::APPEARANCE_DID_CHANGE));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semantic_colors() {
+        let light = Appearance::light();
+        let dark = Appearance::dark();
+        assert!(!light.is_dark());
+        assert!(dark.is_dark());
+        assert_eq!(light.color(SemanticColor::Background), Color::white());
+        assert_eq!(light.color(SemanticColor::Label), Color::black());
+        assert_ne!(light.color(SemanticColor::Background), dark.color(SemanticColor::Background));
+
+        let custom = Appearance::light().with_color(SemanticColor::Accent, Color::red());
+        assert_eq!(custom.color(SemanticColor::Accent), Color::red());
+        assert_eq!(custom.semantic_color(&Color::red()), Some(SemanticColor::Accent));
+    }
+
+}
@@ -3,17 +3,29 @@ use crate::macros::*;
 use crate::ui::touch::Touch;
 use crate::ui::run_loop::RunLoop;
 use crate::ui::gesture::recognizer::Recognizer;
-use std::rc::Weak;
+use crate::ui::key_repeat::KeyRepeat;
+use std::rc::{Rc, Weak};
+use crate::ui::ApplicationDelegate;
+use crate::ui::appearance::Appearance;
 
 singleton!(
     Application,
     key_window_index: None,
-    windows: Vec::new()
+    windows: Vec::new(),
+    key_repeat: Some(KeyRepeat::DEFAULT),
+    delegate: None,
+    appearance: Appearance::light()
 );
 
 pub struct Application {
     key_window_index: Option<usize>,
-    pub(crate) windows: Vec<Window>
+    pub(crate) windows: Vec<Window>,
+    key_repeat: Option<KeyRepeat>,
+
+    // The last responder, after the key window and its view controller.
+    delegate: Option<Rc<dyn ApplicationDelegate>>,
+
+    appearance: Appearance
 }
 
 impl<'a> Application {
@@ -48,6 +60,36 @@ impl<'a> Application {
         None
     }
 
+    /// How held keys repeat, or `None` if they don't.
+    pub fn key_repeat(&self) -> Option<KeyRepeat> {
+        self.key_repeat
+    }
+
+    /// Sets how held keys repeat. `None` turns off key repeat, so holding a
+    /// key sends a single press.
+    pub fn set_key_repeat(&mut self, key_repeat: Option<KeyRepeat>) {
+        self.key_repeat = key_repeat;
+    }
+
+    /// The delegate the application was launched with, if it has been.
+    pub fn delegate(&self) -> Option<Rc<dyn ApplicationDelegate>> {
+        self.delegate.clone()
+    }
+
+    pub(crate) fn set_delegate(&mut self, delegate: Option<Rc<dyn ApplicationDelegate>>) {
+        self.delegate = delegate;
+    }
+
+    /// The active appearance, used by views without one of their own. See
+    /// `appearance::set_appearance` to switch it.
+    pub fn appearance(&self) -> Appearance {
+        self.appearance.clone()
+    }
+
+    pub(crate) fn set_appearance(&mut self, appearance: Appearance) {
+        self.appearance = appearance;
+    }
+
     pub fn exit(&self) {
         let run_loop = RunLoop::borrow();
         run_loop.exit();
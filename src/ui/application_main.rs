@@ -2,16 +2,40 @@ use crate::ui::timer::Timer;
 use crate::ui::event_loop;
 use std::time::Duration;
 use crate::ui::run_loop::RunLoop;
+use crate::ui::application::Application;
+use crate::ui::press::Press;
+use crate::ui::command::Command;
+use crate::ui::notification::{self, Notification, NotificationCenter};
+use crate::ui::localization;
+use std::rc::Rc;
 
 pub trait ApplicationDelegate {
     fn application_will_finish_launching(&self) {}
     fn application_did_finish_launching(&self) {}
     fn application_did_become_active(&self) {}
     fn application_will_terminate(&self) {}
+
+    /// Called with key presses that nothing in the key window handled, at
+    /// the end of the responder chain. Return `true` if the press was
+    /// handled.
+    fn press_began(&self, _press: &Press) -> bool {
+        false
+    }
+
+    /// Performs a command that nothing in the key window handled, such as
+    /// quitting or opening a new window. Return `true` if it was handled.
+    fn perform_command(&self, _command: &Command) -> bool {
+        false
+    }
+
+    /// Whether `perform_command` would handle `command` now.
+    fn can_perform_command(&self, _command: &Command) -> bool {
+        false
+    }
 }
 
 pub struct ApplicationMain {
-    delegate: Box<dyn ApplicationDelegate>
+    delegate: Rc<dyn ApplicationDelegate>
 }
 
 #[cfg(target_os = "macos")]
@@ -23,7 +47,7 @@ extern {
 impl ApplicationMain {
     pub fn new<T>(delegate: T) -> ApplicationMain where T: ApplicationDelegate + 'static {
         ApplicationMain {
-            delegate: Box::new(delegate)
+            delegate: Rc::new(delegate)
         }
     }
 
@@ -34,8 +58,12 @@ impl ApplicationMain {
         #[cfg(target_os = "emscripten")]
         let _ = sdl2::hint::set("SDL_EMSCRIPTEN_ASYNCIFY","1");
 
+        Application::borrow_mut().set_delegate(Some(self.delegate.clone()));
+        localization::load_default_catalogs();
+
         self.delegate.application_will_finish_launching();
         self.delegate.application_did_finish_launching();
+        NotificationCenter::post(Notification::new(notification::APPLICATION_DID_FINISH_LAUNCHING));
 
         // Startup the RunLoop with the event loop as the only process to run.
         // Upon needing it, the UI code will add timers to deal with rendering
@@ -59,6 +87,7 @@ impl ApplicationMain {
         self.delegate.application_did_become_active();
         run_loop.run();
 
+        NotificationCenter::post(Notification::new(notification::APPLICATION_WILL_TERMINATE));
         self.delegate.application_will_terminate();
     }
 }
@@ -1,17 +1,37 @@
 use crate::macros::*;
 use crate::ui::view::DefaultBehavior;
-use crate::ui::{Touch, Label, Color};
-use crate::graphics::Rectangle;
+use crate::ui::accessibility::{AccessibilityAction, AccessibilityRole};
+use crate::ui::{Label, Color, SemanticColor};
+use crate::ui::gesture::{GestureState, LongPressRecognizer};
+use crate::ui::press::Press;
+use crate::ui::key::KeyCode;
+use crate::ui::view::{TrackingArea, TrackingOption};
+use crate::graphics::{Point, Rectangle};
 use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
 use crate::text::{HorizontalAlignment, VerticalAlignment};
+use crate::text::attributed_string::AttributedString;
 
-static DEFAULT_COLOR_NORMAL: Color = Color { red: 2, green: 117, blue: 227, alpha: 255 };
-static DEFAULT_COLOR_PRESSED: Color = Color { red: 64, green: 155, blue: 255, alpha: 255 };
+static DEFAULT_COLOR_PRESSED: Color = Color::new(64, 155, 255, 255);
+static DEFAULT_COLOR_HOVERED: Color = Color::new(30, 138, 245, 255);
+static DEFAULT_COLOR_DISABLED: Color = Color::new(160, 160, 160, 255);
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+type TapCallback = Rc<dyn Fn()>;
+
+/// The state of a button, which decides the color of its title.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum State {
     Normal,
-    Pressed
+
+    /// Being pressed, with the touch inside the button.
+    Highlighted,
+
+    /// Under the pointer, without being pressed.
+    Hovered,
+
+    /// Not responding to touches.
+    Disabled
 }
 
 custom_view!(
@@ -19,29 +39,44 @@ custom_view!(
 
     struct ButtonBehavior {
         state: Cell<State>,
-        pressed_text_color: RefCell<Color>,
-        last_normal_text_color: RefCell<Color>,
-        action: Box<dyn Fn() -> ()>
+        normal_title_color: RefCell<Color>,
+        highlighted_title_color: RefCell<Color>,
+        disabled_title_color: RefCell<Color>,
+        hovered_title_color: RefCell<Color>,
+        action: RefCell<TapCallback>,
+
+        // Whether the pointer is over the button.
+        is_hovered: Cell<bool>,
+
+        // The key pressing the button while it's focused, which fires it
+        // when released.
+        pressed_key: Cell<Option<KeyCode>>
     }
 
     impl Self {
-        pub fn new(frame: Rectangle<i32, u32>, text: &str, action: impl Fn() -> () + 'static) -> Button {
+        pub fn new(frame: Rectangle<i32, u32>, text: &str, action: impl Fn() + 'static) -> Button {
             let state = Cell::new(State::Normal);
             let button = Button::new_all(
                 frame.clone(),
                 state,
+                RefCell::new(Color::semantic(SemanticColor::Accent)),
                 RefCell::new(DEFAULT_COLOR_PRESSED.clone()),
-                RefCell::new(DEFAULT_COLOR_NORMAL.clone()),
-                Box::new(action)
+                RefCell::new(DEFAULT_COLOR_DISABLED.clone()),
+                RefCell::new(DEFAULT_COLOR_HOVERED.clone()),
+                RefCell::new(Rc::new(action)),
+                Cell::new(false),
+                Cell::new(None)
             );
             let label_rectangle = Rectangle::new(0, 0, frame.size.width, frame.size.height);
             let label = Label::new(label_rectangle, String::from(text));
-            label.set_text_color(DEFAULT_COLOR_NORMAL.clone());
+            label.set_text_color(Color::semantic(SemanticColor::Accent));
             label.view.set_user_interaction_enabled(false);
             label.set_text_alignment(HorizontalAlignment::Center);
             label.set_vertical_alignment(VerticalAlignment::Middle);
             button.view.add_subview(label.view);
             button.view.set_background_color(Color::clear());
+            button.view.add_tracking_area(TrackingArea::visible_rect(&[TrackingOption::MouseEnteredAndExited]));
+            button.view.add_gesture_recognizer(Box::new(press_recognizer()));
             button
         }
 
@@ -50,50 +85,166 @@ custom_view!(
             Label::from_view(view)
         }
 
+        /// The text of the button's title.
+        pub fn title(&self) -> String {
+            self.label().copy_text()
+        }
+
+        pub fn set_title(&self, title: &str) {
+            self.label().set_text(title.to_string());
+        }
+
+        /// Sets the title to the translation of `key`, translating it again
+        /// whenever the locale changes. See `Label::set_localized_text`.
+        pub fn set_localized_title(&self, key: &str) {
+            self.label().set_localized_text(key);
+        }
+
+        /// A copy of the button's title with its attributes.
+        pub fn attributed_title(&self) -> AttributedString {
+            self.label().attributed_text()
+        }
+
+        /// Sets the title with its attributes. The button still sets the
+        /// default text color for its state, which the title's own color
+        /// attributes take precedence over.
+        pub fn set_attributed_title(&self, title: AttributedString) {
+            let label = self.label();
+            label.set_attributed_text(title);
+            label.set_text_color(self.title_color(self.state()));
+        }
+
+        pub fn state(&self) -> State {
+            self.behavior().state.get()
+        }
+
+        /// The color of the title while the button is in `state`.
+        pub fn title_color(&self, state: State) -> Color {
+            let behavior = self.behavior();
+            let color = match state {
+                State::Normal => behavior.normal_title_color.borrow(),
+                State::Highlighted => behavior.highlighted_title_color.borrow(),
+                State::Disabled => behavior.disabled_title_color.borrow(),
+                State::Hovered => behavior.hovered_title_color.borrow()
+            };
+            color.clone()
+        }
+
+        /// Sets the color of the title while the button is in `state`.
+        pub fn set_title_color(&self, color: Color, state: State) {
+            {
+                let behavior = self.behavior();
+                let title_color = match state {
+                    State::Normal => &behavior.normal_title_color,
+                    State::Highlighted => &behavior.highlighted_title_color,
+                    State::Disabled => &behavior.disabled_title_color,
+                    State::Hovered => &behavior.hovered_title_color
+                };
+                title_color.replace(color.clone());
+            }
+
+            if self.state() == state {
+                self.label().set_text_color(color);
+            }
+        }
+
+        /// Sets the color of the title in the normal state.
         pub fn set_text_color(&self, color: Color) {
-            self.label().set_text_color(color);
+            self.set_title_color(color, State::Normal);
         }
 
+        /// Sets the color of the title while the button is pressed.
         pub fn set_pressed_text_color(&self, color: Color) {
+            self.set_title_color(color, State::Highlighted);
+        }
+
+        pub fn is_enabled(&self) -> bool {
+            self.state() != State::Disabled
+        }
+
+        /// Enables or disables the button. A disabled button ignores
+        /// touches and keys, can't be focused, and shows its title in the
+        /// disabled color.
+        pub fn set_enabled(&self, is_enabled: bool) {
             let behavior = self.behavior();
+            behavior.pressed_key.set(None);
+            behavior.set_state(if is_enabled { behavior.resting_state() } else { State::Disabled });
+        }
 
-            behavior.pressed_text_color.replace(color);
+        /// Sets what happens when the button is tapped, replacing the action
+        /// it was created with.
+        pub fn on_tap(&self, action: impl Fn() + 'static) {
+            self.behavior().action.replace(Rc::new(action));
         }
     }
 
     impl Behavior {
-        fn touches_began(&self, _touches: &Vec<Touch>) {
-            self.set_state(State::Pressed);
+        fn accessibility_role(&self) -> AccessibilityRole {
+            AccessibilityRole::Button
         }
 
-        fn touches_ended(&self, touches: &Vec<Touch>) {
-            if let Some(touch) = touches.first() {
+        fn accessibility_label(&self) -> Option<String> {
+            Some(self.view_type().title())
+        }
 
-                let view = self.view.upgrade().unwrap();
-                let window = touch.window().unwrap();
+        /// Screen readers press the button as a tap would, unless it's
+        /// disabled.
+        fn perform_accessibility_action(&self, action: &AccessibilityAction) -> bool {
+            if *action != AccessibilityAction::Activate || self.state.get() == State::Disabled {
+                return false;
+            }
 
-                let position = window.view.convert_point_to(&touch.position(), &view);
+            let action = self.action.borrow().clone();
+            action();
+            true
+        }
 
-                if view.bounds().contains(&position) {
-                    (self.action)();
-                }
+        /// Buttons join the Tab order when their window has full keyboard
+        /// access, unless they're disabled.
+        fn is_control(&self) -> bool {
+            self.state.get() != State::Disabled
+        }
+
+        /// Space or Return presses the focused button, which fires when the
+        /// key is released, as a tap does when the touch ends.
+        fn press_began(&self, press: &Press) {
+            let key = press.key();
+            let is_activation_key = matches!(key.key_code(), KeyCode::Space | KeyCode::Return | KeyCode::KpEnter);
+
+            if !is_activation_key || key.has_shortcut_modifier() || self.state.get() == State::Disabled {
+                self.super_behavior().unwrap().press_began(press);
+                return;
             }
 
-            self.set_state(State::Normal);
+            self.pressed_key.set(Some(key.key_code()));
+            self.set_state(State::Highlighted);
         }
 
-        fn touches_moved(&self, touches: &Vec<Touch>) {
-            if let Some(touch) = touches.first() {
-                let view = self.view.upgrade().unwrap();
-                let window = touch.window().unwrap();
+        fn press_ended(&self, press: &Press) {
+            let key_code = press.key().key_code();
+            if self.pressed_key.get() != Some(key_code) {
+                self.super_behavior().unwrap().press_ended(press);
+                return;
+            }
 
-                let position = window.view.convert_point_to(&touch.position(), &view);
+            self.pressed_key.set(None);
+            self.set_state(self.resting_state());
 
-                if view.bounds().contains(&position) {
-                    self.set_state(State::Pressed);
-                } else {
-                    self.set_state(State::Normal);
-                }
+            let action = self.action.borrow().clone();
+            action();
+        }
+
+        fn mouse_entered(&self, _position: &Point<i32>) {
+            self.is_hovered.set(true);
+            if self.state.get() == State::Normal {
+                self.set_state(State::Hovered);
+            }
+        }
+
+        fn mouse_exited(&self) {
+            self.is_hovered.set(false);
+            if self.state.get() == State::Hovered {
+                self.set_state(State::Normal);
             }
         }
     }
@@ -107,23 +258,85 @@ impl ButtonBehavior {
             return;
         }
 
+        self.state.set(state);
+        button.label().set_text_color(button.title_color(state));
+    }
+
+    /// The state of an enabled button that isn't being pressed.
+    fn resting_state(&self) -> State {
+        if self.is_hovered.get() {
+            State::Hovered
+        } else {
+            State::Normal
+        }
+    }
+
+    /// The touch pressing the button changed to `state`, at `location`.
+    /// It's highlighted while the touch is inside, and fires when the touch
+    /// is lifted inside.
+    fn press_did_change(&self, state: GestureState, location: &Point<i32>) {
+        if self.state.get() == State::Disabled {
+            return;
+        }
+
         match state {
-            State::Normal => {
-                button.label().set_text_color(self.last_normal_text_color.borrow().clone());
+            GestureState::Began | GestureState::Changed if self.contains_location(location) => {
+                self.set_state(State::Highlighted);
             },
-            State::Pressed => {
-                self.last_normal_text_color.replace(button.label().text_color());
-                button.label().set_text_color(self.pressed_text_color.borrow().clone());
-            }
+            GestureState::Began | GestureState::Changed | GestureState::Cancelled => {
+                self.set_state(self.resting_state());
+            },
+            GestureState::Ended => {
+                self.set_state(self.resting_state());
+                if self.contains_location(location) {
+                    let action = self.action.borrow().clone();
+                    action();
+                }
+            },
+            _ => ()
         }
+    }
 
-        self.state.set(state);
+    /// Whether `location`, in the button's window, or its root view's
+    /// without a window, is inside the button.
+    fn contains_location(&self, location: &Point<i32>) -> bool {
+        let view = self.view.upgrade().unwrap();
+        let position = view.root_view().convert_point_to(location, &view);
+        let size = view.frame().size;
+        Rectangle { origin: Point::new(0, 0), size }.contains_point(&position)
     }
 }
 
+/// Tracks the touch pressing a button from the moment it touches down,
+/// wherever it's dragged. It leaves the touch to the button's superviews'
+/// recognizers too, and is cancelled if one of them takes it, such as a
+/// scroll view's pan.
+fn press_recognizer() -> LongPressRecognizer {
+    let recognizer = LongPressRecognizer::new(|recognizer| {
+        if let Some(view) = recognizer.view().upgrade() {
+            let location = recognizer.location_in(&view);
+            Button::from_view(view).behavior().press_did_change(recognizer.state(), &location);
+        }
+    });
+    recognizer.set_minimum_duration(Duration::ZERO);
+    recognizer.set_allowable_movement(i32::MAX);
+    recognizer.set_cancels_touches_in_view(false);
+    recognizer.core().set_recognizes_simultaneously(true);
+    recognizer.set_should_begin(|recognizer| {
+        recognizer.view().upgrade().is_some_and(|view| Button::from_view(view).is_enabled())
+    });
+    recognizer
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ui::View;
+    use crate::ui::view::tracking_area::HoverTracker;
+    use crate::ui::key::Key;
+    use crate::ui::Touch;
+    use crate::ui::touch::TouchPhase;
+    use crate::ui::event::TouchEvent;
 
     #[test]
     fn test_button_new() {
@@ -136,4 +349,147 @@ mod tests {
         assert_eq!(button.view.frame(), Rectangle::new(0, 0, 100, 100));
         assert_eq!(button.label().copy_text(), String::from("Test"));
     }
+
+    #[test]
+    fn test_title() {
+        let button = Button::new(Rectangle::new(0, 0, 100, 40), "Test", || {});
+        button.set_title("Save");
+        assert_eq!(button.title(), "Save");
+
+        button.set_attributed_title(AttributedString::new("Save as".to_string()));
+        assert_eq!(button.title(), "Save as");
+        assert_eq!(button.label().text_color(), Color::semantic(SemanticColor::Accent));
+    }
+
+    /// Sends the button's press recognizer `phase` of a touch at
+    /// `position`, as the event loop does.
+    fn touch(button: &Button, phase: TouchPhase, position: Point<i32>) {
+        let recognizer = button.view.gesture_recognizers()[0].upgrade().unwrap();
+        let touches = vec![Touch::new(0, position)];
+        let event = TouchEvent::new();
+        match phase {
+            TouchPhase::Began => recognizer.touches_began(&touches, &event),
+            TouchPhase::Moved => recognizer.touches_moved(&touches, &event),
+            TouchPhase::Ended => recognizer.touches_ended(&touches, &event),
+            _ => recognizer.touches_cancelled(&touches, &event)
+        }
+    }
+
+    #[test]
+    fn test_touch_tracking() {
+        let superview = View::new(Rectangle::new(0, 0, 200, 200));
+        let taps = Rc::new(Cell::new(0));
+        let taps_clone = taps.clone();
+        let button = Button::new(Rectangle::new(50, 50, 100, 40), "Test", move || taps_clone.set(taps_clone.get() + 1));
+        superview.add_subview(button.view.clone());
+        button.set_title_color(Color::red(), State::Highlighted);
+
+        let inside = Point::new(60, 60);
+        let outside = Point::new(10, 10);
+
+        // Touching down highlights, and lifting inside fires.
+        touch(&button, TouchPhase::Began, inside.clone());
+        assert_eq!(button.state(), State::Highlighted);
+        assert_eq!(button.label().text_color(), Color::red());
+        touch(&button, TouchPhase::Ended, inside.clone());
+        assert_eq!(button.state(), State::Normal);
+        assert_eq!(taps.get(), 1);
+
+        // Dragging out un-highlights, and lifting outside doesn't fire.
+        touch(&button, TouchPhase::Began, inside.clone());
+        touch(&button, TouchPhase::Moved, outside.clone());
+        assert_eq!(button.state(), State::Normal);
+        touch(&button, TouchPhase::Moved, inside.clone());
+        assert_eq!(button.state(), State::Highlighted);
+        touch(&button, TouchPhase::Ended, outside.clone());
+        assert_eq!(taps.get(), 1);
+
+        // A cancelled touch doesn't fire either.
+        touch(&button, TouchPhase::Began, inside.clone());
+        touch(&button, TouchPhase::Cancelled, inside.clone());
+        assert_eq!(button.state(), State::Normal);
+        touch(&button, TouchPhase::Ended, inside.clone());
+        assert_eq!(taps.get(), 1);
+
+        // Disabled buttons ignore touches.
+        button.set_enabled(false);
+        assert_eq!(button.label().text_color(), DEFAULT_COLOR_DISABLED);
+        touch(&button, TouchPhase::Began, inside.clone());
+        touch(&button, TouchPhase::Ended, inside.clone());
+        assert_eq!(button.state(), State::Disabled);
+        assert_eq!(taps.get(), 1);
+
+        let tapped = Rc::new(Cell::new(false));
+        let tapped_clone = tapped.clone();
+        button.on_tap(move || tapped_clone.set(true));
+        button.set_enabled(true);
+        touch(&button, TouchPhase::Began, inside.clone());
+        touch(&button, TouchPhase::Ended, inside);
+        assert!(tapped.get());
+        assert_eq!(taps.get(), 1);
+    }
+
+    #[test]
+    fn test_key_activation() {
+        let superview = View::new(Rectangle::new(0, 0, 200, 200));
+        let taps = Rc::new(Cell::new(0));
+        let taps_clone = taps.clone();
+        let button = Button::new(Rectangle::new(50, 50, 100, 40), "Test", move || taps_clone.set(taps_clone.get() + 1));
+        superview.add_subview(button.view.clone());
+        assert!(button.view.is_control());
+
+        // Space highlights, and releasing it fires.
+        let space = Press::new(Key::new(KeyCode::Space, vec![]));
+        let behavior = button.view.behavior();
+        behavior.press_began(&space);
+        assert_eq!(button.state(), State::Highlighted);
+        assert_eq!(taps.get(), 0);
+        behavior.press_ended(&space);
+        assert_eq!(button.state(), State::Normal);
+        assert_eq!(taps.get(), 1);
+
+        // Other keys, and releasing a key that didn't press the button,
+        // don't fire.
+        let a = Press::new(Key::new(KeyCode::A, vec![]));
+        behavior.press_began(&a);
+        behavior.press_ended(&a);
+        behavior.press_ended(&space);
+        assert_eq!(button.state(), State::Normal);
+        assert_eq!(taps.get(), 1);
+        drop(behavior);
+
+        button.set_enabled(false);
+        assert!(!button.view.is_control());
+        let behavior = button.view.behavior();
+        let enter = Press::new(Key::new(KeyCode::Return, vec![]));
+        behavior.press_began(&enter);
+        behavior.press_ended(&enter);
+        assert_eq!(taps.get(), 1);
+    }
+
+    #[test]
+    fn test_hover() {
+        let superview = View::new(Rectangle::new(0, 0, 200, 200));
+        let button = Button::new(Rectangle::new(50, 50, 100, 40), "Test", || {});
+        superview.add_subview(button.view.clone());
+        button.set_title_color(Color::red(), State::Hovered);
+
+        let mut hover_tracker = HoverTracker::new();
+        hover_tracker.pointer_moved(&superview, &Point::new(60, 60));
+        assert_eq!(button.state(), State::Hovered);
+        assert_eq!(button.label().text_color(), Color::red());
+
+        // Pressing highlights, and releasing returns to hovered.
+        touch(&button, TouchPhase::Began, Point::new(60, 60));
+        assert_eq!(button.state(), State::Highlighted);
+        touch(&button, TouchPhase::Ended, Point::new(60, 60));
+        assert_eq!(button.state(), State::Hovered);
+
+        hover_tracker.pointer_moved(&superview, &Point::new(10, 10));
+        assert_eq!(button.state(), State::Normal);
+
+        hover_tracker.pointer_moved(&superview, &Point::new(60, 60));
+        hover_tracker.pointer_exited();
+        assert_eq!(button.state(), State::Normal);
+    }
 }
@@ -0,0 +1,89 @@
+use crate::ui::run_loop::RunLoop;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Tells the run loop what time it is, for firing timers, stepping display
+/// links and animations, and timestamping events.
+///
+/// The run loop uses a `SystemClock` unless told otherwise with
+/// `RunLoop::set_clock`. Tests can use a `ManualClock` instead, so timers
+/// fire when the test advances time rather than when enough real time has
+/// passed.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// The real time, as the platform tells it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock {}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that stands still until it's advanced. Clones share the same
+/// time, so a test can keep one to advance after giving another to the run
+/// loop.
+///
+/// ```ignore
+/// let clock = ManualClock::new();
+/// RunLoop::borrow().set_clock(clock.clone());
+///
+/// Timer::after(Duration::from_secs(1), || println!("fired"));
+/// clock.advance(Duration::from_secs(2));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Rc<Cell<Instant>>
+}
+
+impl ManualClock {
+    /// A clock stopped at the current time.
+    pub fn new() -> ManualClock {
+        ManualClock {
+            now: Rc::new(Cell::new(Instant::now()))
+        }
+    }
+
+    /// Moves the time forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> ManualClock {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+/// The current time by the run loop's clock. Use this rather than
+/// `Instant::now` for anything compared with when timers fire.
+pub fn now() -> Instant {
+    RunLoop::borrow().now()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+
+        let shared = clock.clone();
+        shared.advance(Duration::from_millis(500));
+        assert_eq!(clock.now(), start + Duration::from_millis(500));
+    }
+}
@@ -1,107 +1,260 @@
-use crate::graphics;
+use crate::graphics::{self, LinearColor};
+use crate::ui::View;
+use crate::ui::appearance::{self, Appearance, SemanticColor};
 
+/// A color, with components from 0 to 255.
+///
+/// A color can also be dynamic, changing with the appearance, such as
+/// `Color::dynamic` with a light and a dark variant, or `Color::semantic`.
+/// Its components are then those of the light appearance, and views resolve
+/// it against their effective appearance as they draw, so it follows the
+/// appearance as it changes without the view being told.
 pub struct Color {
     pub red: u8,
     pub green: u8,
     pub blue: u8,
-    pub alpha: u8
+    pub alpha: u8,
+    dynamic: Option<DynamicColor>
+}
+
+/// What a dynamic color resolves to, other than its components.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DynamicColor {
+    /// The components of the dark variant.
+    Dark([u8; 4]),
+
+    /// The appearance's color, with its alpha replaced if set.
+    Semantic(SemanticColor, Option<u8>)
 }
 
 impl Color {
-    pub fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Color {
-        Color { red, green, blue, alpha }
+    pub const fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Color {
+        Color { red, green, blue, alpha, dynamic: None }
     }
 
-    pub fn white() -> Color {
-        Color {
-            red: 255,
-            green: 255,
-            blue: 255,
-            alpha: 255
+    /// A color that's `light` in light appearances and `dark` in dark ones.
+    pub fn dynamic(light: Color, dark: Color) -> Color {
+        let dark = dark.resolved(&Appearance::dark());
+        let mut color = light.resolved(&Appearance::light());
+        color.dynamic = Some(DynamicColor::Dark([dark.red, dark.green, dark.blue, dark.alpha]));
+        color
+    }
+
+    /// The appearance's color for `semantic_color`, whichever appearance is
+    /// in effect.
+    pub fn semantic(semantic_color: SemanticColor) -> Color {
+        let mut color = Appearance::light().color(semantic_color);
+        color.dynamic = Some(DynamicColor::Semantic(semantic_color, None));
+        color
+    }
+
+    /// Whether the color changes with the appearance.
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic.is_some()
+    }
+
+    /// The color in `appearance`. Colors that aren't dynamic are the same in
+    /// every appearance.
+    pub fn resolved(&self, appearance: &Appearance) -> Color {
+        match self.dynamic {
+            None => self.clone(),
+            Some(DynamicColor::Dark([red, green, blue, alpha])) if appearance.is_dark() => Color::new(red, green, blue, alpha),
+            Some(DynamicColor::Dark(_)) => Color::new(self.red, self.green, self.blue, self.alpha),
+            Some(DynamicColor::Semantic(semantic_color, alpha)) => {
+                let color = appearance.color(semantic_color);
+                Color::new(color.red, color.green, color.blue, alpha.unwrap_or(color.alpha))
+            }
         }
     }
 
-    pub fn black() -> Color {
-        Color {
-            red: 0,
-            green: 0,
-            blue: 0,
-            alpha: 255
+    /// The color in `view`'s effective appearance.
+    pub fn resolved_in(&self, view: &View) -> Color {
+        if self.is_dynamic() {
+            self.resolved(&view.effective_appearance())
+        } else {
+            self.clone()
         }
     }
 
-    pub fn red() -> Color {
-        Color {
-            red: 255,
-            green: 0,
-            blue: 0,
-            alpha: 255
+    /// The color in the application's appearance.
+    fn current(&self) -> Color {
+        if self.is_dynamic() {
+            self.resolved(&appearance::appearance())
+        } else {
+            self.clone()
         }
     }
 
-    pub fn green() -> Color {
-        Color {
-            red: 0,
-            green: 255,
-            blue: 0,
-            alpha: 255
+    /// A color from its hue, in degrees around the color wheel, and its
+    /// saturation, brightness and alpha, from 0 to 1.
+    pub fn from_hsb(hue: f32, saturation: f32, brightness: f32, alpha: f32) -> Color {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = saturation.clamp(0.0, 1.0);
+        let brightness = brightness.clamp(0.0, 1.0);
+
+        let chroma = brightness * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (red, green, blue) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x)
+        };
+
+        let minimum = brightness - chroma;
+        Color::new(
+            component(red + minimum),
+            component(green + minimum),
+            component(blue + minimum),
+            component(alpha)
+        )
+    }
+
+    /// The hue in degrees, and the saturation and brightness from 0 to 1.
+    /// Dynamic colors are resolved in the application's appearance first.
+    pub fn hsb(&self) -> (f32, f32, f32) {
+        let color = self.current();
+        let red = color.red as f32 / 255.0;
+        let green = color.green as f32 / 255.0;
+        let blue = color.blue as f32 / 255.0;
+
+        let maximum = red.max(green).max(blue);
+        let chroma = maximum - red.min(green).min(blue);
+
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if maximum == red {
+            60.0 * ((green - blue) / chroma).rem_euclid(6.0)
+        } else if maximum == green {
+            60.0 * ((blue - red) / chroma + 2.0)
+        } else {
+            60.0 * ((red - green) / chroma + 4.0)
+        };
+        let saturation = if maximum == 0.0 { 0.0 } else { chroma / maximum };
+
+        (hue, saturation, maximum)
+    }
+
+    /// The alpha, from 0 to 1.
+    pub fn alpha_component(&self) -> f32 {
+        self.current().alpha as f32 / 255.0
+    }
+
+    /// The color with `alpha`, from 0 to 1. A dynamic color stays dynamic,
+    /// with the alpha in every appearance.
+    pub fn with_alpha_component(&self, alpha: f32) -> Color {
+        let alpha = component(alpha);
+        let mut color = self.clone();
+        color.alpha = alpha;
+        color.dynamic = match self.dynamic {
+            None => None,
+            Some(DynamicColor::Dark([red, green, blue, _])) => Some(DynamicColor::Dark([red, green, blue, alpha])),
+            Some(DynamicColor::Semantic(semantic_color, _)) => Some(DynamicColor::Semantic(semantic_color, Some(alpha)))
+        };
+        color
+    }
+
+    /// The color `progress` of the way from this color to `to`, such as for
+    /// animating between them, mixed in linear light as gradients are.
+    /// Progress outside of 0 to 1 overshoots, up to the limits of each
+    /// component. Dynamic colors are resolved in the application's
+    /// appearance first.
+    pub fn interpolate(&self, to: &Color, progress: f32) -> Color {
+        // Mixing premultiplied components loses a clear color's components,
+        // so the ends are returned as they are for animations to land on.
+        if progress == 0.0 {
+            return self.current();
+        } else if progress == 1.0 {
+            return to.current();
         }
+
+        Color::from_linear(&self.to_linear().interpolate(&to.to_linear(), progress))
+    }
+
+    /// This color drawn over `background`, blending by this color's alpha
+    /// in linear light. Dynamic colors are resolved in the application's
+    /// appearance first.
+    pub fn blended_over(&self, background: &Color) -> Color {
+        let source = self.to_linear();
+        let background = background.to_linear();
+        Color::from_linear(&source.over(&background))
+    }
+
+    /// The color in linear light, for blending. Dynamic colors are resolved
+    /// in the application's appearance first.
+    pub fn to_linear(&self) -> LinearColor {
+        LinearColor::from_srgb(&self.to_graphics_color())
+    }
+
+    pub fn from_linear(color: &LinearColor) -> Color {
+        Color::from_graphics_color(&color.to_srgb())
+    }
+
+    pub fn white() -> Color {
+        Color::new(255, 255, 255, 255)
+    }
+
+    pub fn black() -> Color {
+        Color::new(0, 0, 0, 255)
+    }
+
+    pub fn red() -> Color {
+        Color::new(255, 0, 0, 255)
+    }
+
+    pub fn green() -> Color {
+        Color::new(0, 255, 0, 255)
     }
 
     pub fn blue() -> Color {
-        Color {
-            red: 0,
-            green: 0,
-            blue: 255,
-            alpha: 255
-        }
+        Color::new(0, 0, 255, 255)
     }
 
     pub fn gray() -> Color {
-        Color {
-            red: 128,
-            green: 128,
-            blue: 128,
-            alpha: 255
-        }
+        Color::new(128, 128, 128, 255)
     }
 
     pub fn clear() -> Color {
-        Color {
-            red: 0,
-            green: 0,
-            blue: 0,
-            alpha: 0
-        }
+        Color::new(0, 0, 0, 0)
     }
 
+    /// The color for drawing, resolved in the application's appearance if
+    /// it's dynamic. Views use `resolved_in` instead, for their own
+    /// appearance.
     pub fn to_graphics_color(&self) -> graphics::Color {
+        let color = self.current();
         graphics::Color {
-            r: self.red,
-            g: self.green,
-            b: self.blue,
-            a: self.alpha
+            r: color.red,
+            g: color.green,
+            b: color.blue,
+            a: color.alpha
         }
     }
 
     pub fn from_graphics_color(color: &graphics::Color) -> Color {
-        Color {
-            red: color.r,
-            green: color.g,
-            blue: color.b,
-            alpha: color.a
-        }
+        Color::new(color.r, color.g, color.b, color.a)
     }
 }
 
+/// A component from 0 to 255, from one from 0 to 1.
+fn component(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
 impl std::fmt::Debug for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("Color")
-         .field(&self.red)
-         .field(&self.green)
-         .field(&self.blue)
-         .field(&self.alpha)
-         .finish()
+        let mut debug = f.debug_tuple("Color");
+        debug
+            .field(&self.red)
+            .field(&self.green)
+            .field(&self.blue)
+            .field(&self.alpha);
+        if let Some(dynamic) = &self.dynamic {
+            debug.field(dynamic);
+        }
+        debug.finish()
     }
 }
 
@@ -110,7 +263,8 @@ impl PartialEq for Color {
         self.red == rhs.red &&
             self.green == rhs.green &&
             self.blue == rhs.blue &&
-            self.alpha == rhs.alpha
+            self.alpha == rhs.alpha &&
+            self.dynamic == rhs.dynamic
     }
 }
 
@@ -120,7 +274,8 @@ impl Clone for Color {
             red: self.red,
             green: self.green,
             blue: self.blue,
-            alpha: self.alpha
+            alpha: self.alpha,
+            dynamic: self.dynamic
         }
     }
 }
@@ -213,4 +368,53 @@ mod tests {
         let color = Color::new(123, 45, 67, 89);
         assert_eq!(format!("{:?}", color), "Color(123, 45, 67, 89)");
     }
+
+    #[test]
+    fn test_dynamic() {
+        let color = Color::dynamic(Color::white(), Color::black());
+        assert!(color.is_dynamic());
+        assert_eq!(color.resolved(&Appearance::light()), Color::white());
+        assert_eq!(color.resolved(&Appearance::dark()), Color::black());
+        assert_ne!(color, Color::white());
+
+        let label = Color::semantic(SemanticColor::Label);
+        assert_eq!(label.resolved(&Appearance::dark()), Appearance::dark().color(SemanticColor::Label));
+
+        let faded = label.with_alpha_component(0.5);
+        assert!(faded.is_dynamic());
+        assert_eq!(faded.resolved(&Appearance::dark()).alpha, 128);
+        assert_eq!(color.with_alpha_component(0.0).resolved(&Appearance::dark()), Color::clear());
+    }
+
+    #[test]
+    fn test_hsb() {
+        assert_eq!(Color::from_hsb(0.0, 1.0, 1.0, 1.0), Color::red());
+        assert_eq!(Color::from_hsb(120.0, 1.0, 1.0, 1.0), Color::green());
+        assert_eq!(Color::from_hsb(600.0, 1.0, 1.0, 1.0), Color::blue());
+        assert_eq!(Color::from_hsb(0.0, 0.0, 1.0, 0.5), Color::new(255, 255, 255, 128));
+
+        let (hue, saturation, brightness) = Color::new(255, 128, 0, 255).hsb();
+        assert!((hue - 30.1).abs() < 0.1);
+        assert_eq!(saturation, 1.0);
+        assert_eq!(brightness, 1.0);
+        assert_eq!(Color::gray().hsb().1, 0.0);
+    }
+
+    #[test]
+    fn test_interpolate_and_blend() {
+        // Halfway in linear light looks halfway between them.
+        assert_eq!(Color::black().interpolate(&Color::white(), 0.5), Color::new(188, 188, 188, 255));
+        assert_eq!(Color::black().interpolate(&Color::white(), 2.0), Color::white());
+
+        // Fading out keeps the color, rather than darkening towards clear.
+        assert_eq!(Color::red().interpolate(&Color::clear(), 0.5), Color::new(255, 0, 0, 128));
+
+        let half_red = Color::red().with_alpha_component(0.5);
+        assert_eq!(half_red.alpha_component(), 128.0 / 255.0);
+
+        // Blending in linear light keeps half of each as bright as it looks.
+        assert_eq!(half_red.blended_over(&Color::blue()), Color::new(188, 0, 187, 255));
+        assert_eq!(Color::clear().blended_over(&Color::clear()), Color::clear());
+        assert_eq!(Color::white().blended_over(&Color::black()), Color::white());
+    }
 }
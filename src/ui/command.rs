@@ -0,0 +1,22 @@
+/// Something the user asked for without saying which view it's for, such as
+/// choosing Edit > Copy in the menu bar.
+///
+/// Commands are sent to the first responder, and passed up the responder
+/// chain until a view handles them. See `View::perform_command`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Undoes the last action of the nearest undo manager.
+    Undo,
+
+    /// Redoes the last undone action of the nearest undo manager.
+    Redo,
+
+    Cut,
+    Copy,
+    Paste,
+    SelectAll,
+
+    /// A command of the application's own, handled by views overriding
+    /// `Behavior::perform_command`.
+    Custom(String)
+}
@@ -0,0 +1,584 @@
+use crate::macros::*;
+use crate::ui::view::{AutoresizingMask, DefaultBehavior};
+use crate::ui::alert::remove_giving_back_focus;
+use crate::ui::popover::{clamp_to, MARGIN};
+use crate::ui::key::KeyCode;
+use crate::ui::menu::Menu;
+use crate::ui::press::Press;
+use crate::ui::gesture::{GestureState, LongPressRecognizer};
+use crate::ui::Window;
+use crate::ui::{Color, Label, SemanticColor, Shadow, Touch, View, WeakView};
+use crate::graphics::{Point, Rectangle, Size};
+use crate::text::VerticalAlignment;
+use std::cell::RefCell;
+
+const MENU_WIDTH: u32 = 220;
+
+const ROW_HEIGHT: u32 = 24;
+
+const SEPARATOR_HEIGHT: u32 = 9;
+
+/// The space above the first item of a menu and below the last.
+const PADDING: u32 = 4;
+
+/// The space between the edges of a row and its text.
+const TEXT_INSET: u32 = 12;
+
+static HIGHLIGHT_COLOR: Color = Color::new(2, 117, 227, 255);
+static DISABLED_TEXT_COLOR: Color = Color::new(160, 160, 160, 255);
+static SEPARATOR_COLOR: Color = Color::new(220, 220, 220, 255);
+
+/// The frame of each of `menu`'s items, relative to the menu's top left.
+fn item_frames(menu: &Menu) -> Vec<Rectangle<i32, u32>> {
+    let mut y = PADDING;
+    let mut frames = Vec::new();
+
+    for item in menu.items() {
+        let height = if item.is_separator() { SEPARATOR_HEIGHT } else { ROW_HEIGHT };
+        frames.push(Rectangle::new(0, y as i32, MENU_WIDTH, height));
+        y += height;
+    }
+
+    frames
+}
+
+fn menu_size(menu: &Menu) -> Size<u32> {
+    let bottom = item_frames(menu).last().map_or(PADDING, |frame| frame.bottom() as u32);
+    Size::new(MENU_WIDTH, bottom + PADDING)
+}
+
+/// Where a menu of `size` goes in a `container`, next to `anchor`: a point
+/// for a context menu, or the parent menu's row for a submenu.
+///
+/// The menu goes to the right of the anchor with its top level with the
+/// anchor's, flipping to the left when there's no room on the right, and
+/// above the anchor's bottom when there's no room below. It's then moved to
+/// stay inside the container, as a popover is.
+pub(crate) fn menu_frame(anchor: &Rectangle<i32, u32>, size: &Size<u32>, container: &Size<u32>) -> Rectangle<i32, u32> {
+    let (width, height) = (size.width as i32, size.height as i32);
+    let (container_width, container_height) = (container.width as i32, container.height as i32);
+
+    let mut x = anchor.right();
+    if x + width > container_width - MARGIN {
+        x = anchor.left() - width;
+    }
+
+    let mut y = anchor.top();
+    if y + height > container_height - MARGIN {
+        y = anchor.bottom() - height;
+    }
+
+    clamp_to(&Rectangle { origin: Point::new(x, y), size: size.clone() }, container)
+}
+
+/// The recognizer of each window's view, showing the context menu of the
+/// view a touch is held on once it's been held long enough. Touches held
+/// where there's no menu are left to the view and its other recognizers.
+pub(crate) fn long_press_recognizer() -> LongPressRecognizer {
+    let recognizer = LongPressRecognizer::new(|long_press_recognizer| {
+        let view = match long_press_recognizer.view().upgrade() {
+            Some(view) => view,
+            None => return
+        };
+
+        if long_press_recognizer.state() == GestureState::Began {
+            Window::from_view(view.clone()).show_context_menu(&long_press_recognizer.location_in(&view));
+        }
+    });
+
+    recognizer.set_should_begin(|long_press_recognizer| {
+        long_press_recognizer.view().upgrade().is_some_and(|view| {
+            view.context_menu_at(&long_press_recognizer.location_in(&view)).is_some()
+        })
+    });
+    recognizer
+}
+
+// A menu shown where the user right-clicked or long-pressed, with the
+// menu's items to choose from.
+//
+// The context menu covers the view it's presented in, so touches outside
+// the menu dismiss it instead of reaching the content. Choosing an item
+// with a submenu opens the submenu next to it; choosing any other enabled
+// item dismisses the menu and calls the item's action.
+//
+// While the menu is shown it has keyboard focus: the up and down arrows
+// move between items, right and Return open a submenu, left closes it,
+// Return chooses an item and Escape dismisses the menu.
+custom_view!(
+    ContextMenu subclasses DefaultBehavior
+
+    struct ContextMenuBehavior {
+        menu: Menu,
+
+        // The menus open, the first being the context menu itself and each
+        // other one a submenu of the one before.
+        menus: RefCell<Vec<Menu>>,
+
+        // The item highlighted in each open menu.
+        highlighted: RefCell<Vec<Option<usize>>>,
+
+        // The view showing each open menu.
+        panels: RefCell<Vec<View>>,
+
+        // The first responder before the menu was presented, given back
+        // when it's dismissed.
+        previous_first_responder: RefCell<WeakView>
+    }
+
+    impl Self {
+        pub fn new(menu: Menu) -> ContextMenu {
+            let context_menu = Self::new_all(
+                Rectangle::new(0, 0, 0, 0),
+                menu.clone(),
+                RefCell::new(vec![menu]),
+                RefCell::new(vec![None]),
+                RefCell::new(Vec::new()),
+                RefCell::new(WeakView::none())
+            );
+
+            context_menu.view.set_background_color(Color::clear());
+            context_menu.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth, AutoresizingMask::FlexibleHeight]);
+            context_menu
+        }
+
+        /// The menu shown, without any submenus opened from it.
+        pub fn menu(&self) -> Menu {
+            self.behavior().menu.clone()
+        }
+
+        /// Presents the menu over the content of `view`, usually a window,
+        /// with its top left corner at `point` in `view`'s coordinates.
+        ///
+        /// Command items are sent to the window's first responder, and are
+        /// disabled if it can't perform them.
+        pub fn present_at(&self, point: &Point<i32>, view: &View) {
+            let size = view.bounds().size;
+            self.view.set_frame(Rectangle::new(0, 0, size.width, size.height));
+
+            let target = view.window().map(|window| window.first_responder());
+            self.close_menus_after(0);
+            {
+                let behavior = self.behavior();
+                behavior.menus.borrow_mut()[0] = behavior.menu.validated(target.as_ref());
+                behavior.highlighted.borrow_mut()[0] = None;
+                for panel in behavior.panels.borrow_mut().drain(..) {
+                    panel.remove_from_superview();
+                }
+                behavior.previous_first_responder.replace(target.map_or(WeakView::none(), |target| target.downgrade()));
+            }
+
+            let anchor = Rectangle { origin: point.clone(), size: Size::new(0, 0) };
+            self.show_panel(0, &anchor);
+            view.add_subview(self.view.clone());
+            self.view.become_first_responder();
+        }
+
+        /// Whether the menu is presented.
+        pub fn is_presented(&self) -> bool {
+            self.view.superview().upgrade().is_some_and(|superview| superview.subviews().contains(&self.view))
+        }
+
+        /// Removes the menu without choosing an item, giving keyboard focus
+        /// back to where it was.
+        pub fn dismiss(&self) {
+            let previous_first_responder = self.behavior().previous_first_responder.borrow().clone();
+            remove_giving_back_focus(&self.view, &previous_first_responder);
+        }
+
+        /// The item highlighted in each open menu, starting with the
+        /// context menu itself and followed by any submenus opened from it.
+        pub fn highlighted_items(&self) -> Vec<Option<usize>> {
+            self.behavior().highlighted.borrow().clone()
+        }
+
+        /// Chooses the item at `index` of the open menu at `level`: opens
+        /// its submenu, or dismisses the menu and performs its action or
+        /// command.
+        /// Disabled items and separators are ignored.
+        fn choose_item(&self, level: usize, index: usize) {
+            let item = match self.behavior().menus.borrow().get(level).and_then(|menu| menu.items().get(index)) {
+                Some(item) if item.is_enabled() => item.clone(),
+                _ => return
+            };
+
+            if item.submenu_menu().is_some() {
+                self.open_submenu(level, index);
+            } else {
+                self.dismiss();
+                let target = self.behavior().previous_first_responder.borrow().upgrade();
+                item.perform(target.as_ref());
+            }
+        }
+
+        /// Opens the submenu of the item at `index` of the open menu at
+        /// `level`, closing any deeper menus.
+        fn open_submenu(&self, level: usize, index: usize) {
+            let submenu = match self.behavior().menus.borrow()[level].items()[index].submenu_menu() {
+                Some(submenu) => submenu.clone(),
+                None => return
+            };
+
+            self.close_menus_after(level);
+            self.set_highlighted(level, Some(index));
+
+            let anchor = {
+                let behavior = self.behavior();
+                let panel = behavior.panels.borrow()[level].frame();
+                let row = item_frames(&behavior.menus.borrow()[level])[index].clone();
+                Rectangle::new(panel.left(), panel.top() + row.top(), panel.size.width, row.size.height)
+            };
+
+            {
+                let behavior = self.behavior();
+                behavior.menus.borrow_mut().push(submenu);
+                behavior.highlighted.borrow_mut().push(None);
+            }
+            self.show_panel(level + 1, &anchor);
+        }
+
+        /// Closes the submenus deeper than `level`.
+        fn close_menus_after(&self, level: usize) {
+            let behavior = self.behavior();
+            behavior.menus.borrow_mut().truncate(level + 1);
+            behavior.highlighted.borrow_mut().truncate(level + 1);
+
+            let mut panels = behavior.panels.borrow_mut();
+            for panel in panels.iter().skip(level + 1) {
+                panel.remove_from_superview();
+            }
+            panels.truncate(level + 1);
+        }
+
+        /// Adds the view showing the open menu at `level`, next to `anchor`.
+        fn show_panel(&self, level: usize, anchor: &Rectangle<i32, u32>) {
+            let size = menu_size(&self.behavior().menus.borrow()[level]);
+            let frame = menu_frame(anchor, &size, &self.view.frame().size);
+
+            // The rows don't take touches, so they all come to the context
+            // menu's own view to be matched to an item.
+            let panel = View::new(frame);
+            panel.set_background_color(Color::semantic(SemanticColor::Background));
+            panel.set_corner_radius(6.0);
+            panel.set_shadow(Some(Shadow::default()));
+            panel.set_user_interaction_enabled(false);
+
+            self.view.add_subview(panel.clone());
+            self.behavior().panels.borrow_mut().push(panel);
+            self.layout_panel(level);
+        }
+
+        /// Rebuilds the rows of the view showing the open menu at `level`.
+        fn layout_panel(&self, level: usize) {
+            let behavior = self.behavior();
+            let panel = behavior.panels.borrow()[level].clone();
+            let menu = behavior.menus.borrow()[level].clone();
+            let highlighted = behavior.highlighted.borrow()[level];
+
+            for subview in panel.subviews() {
+                subview.remove_from_superview();
+            }
+
+            for (index, (item, frame)) in menu.items().iter().zip(item_frames(&menu)).enumerate() {
+                if item.is_separator() {
+                    let line = View::new(Rectangle::new(0, frame.top() + SEPARATOR_HEIGHT as i32 / 2, MENU_WIDTH, 1));
+                    line.set_background_color(SEPARATOR_COLOR.clone());
+                    panel.add_subview(line);
+                    continue;
+                }
+
+                let is_highlighted = highlighted == Some(index);
+                let row = View::new(frame.clone());
+                row.set_background_color(if is_highlighted { HIGHLIGHT_COLOR.clone() } else { Color::clear() });
+
+                let text_color = match (is_highlighted, item.is_enabled()) {
+                    (true, _) => Color::white(),
+                    (false, true) => Color::semantic(SemanticColor::Label),
+                    (false, false) => DISABLED_TEXT_COLOR.clone()
+                };
+
+                let text_width = MENU_WIDTH - TEXT_INSET * 2;
+                let title = Label::new(Rectangle::new(TEXT_INSET as i32, 0, text_width, ROW_HEIGHT), item.title().to_string());
+                title.set_text_color(text_color.clone());
+                title.set_vertical_alignment(VerticalAlignment::Middle);
+                row.add_subview(title.view);
+
+                if item.submenu_menu().is_some() {
+                    let arrow = Label::new(Rectangle::new((MENU_WIDTH - TEXT_INSET) as i32 - 8, 0, 8, ROW_HEIGHT), "›".to_string());
+                    arrow.set_text_color(text_color);
+                    arrow.set_vertical_alignment(VerticalAlignment::Middle);
+                    row.add_subview(arrow.view);
+                }
+
+                panel.add_subview(row);
+            }
+        }
+
+        fn set_highlighted(&self, level: usize, index: Option<usize>) {
+            let previous = self.behavior().highlighted.borrow_mut()[level];
+            if previous == index {
+                return;
+            }
+
+            self.behavior().highlighted.borrow_mut()[level] = index;
+            self.layout_panel(level);
+        }
+
+        /// Moves the highlight in the deepest open menu to the next item
+        /// that can be chosen, or the previous when `backwards`. With
+        /// nothing highlighted, the first or last item is.
+        fn move_highlight(&self, backwards: bool) {
+            let (level, next) = {
+                let behavior = self.behavior();
+                let menus = behavior.menus.borrow();
+                let level = menus.len() - 1;
+                let menu = &menus[level];
+                let current = behavior.highlighted.borrow()[level];
+
+                let count = menu.items().len();
+                let mut candidates: Box<dyn Iterator<Item = usize>> = match (current, backwards) {
+                    (None, false) => Box::new(0..count),
+                    (None, true) => Box::new((0..count).rev()),
+                    (Some(index), false) => Box::new(index + 1..count),
+                    (Some(index), true) => Box::new((0..index).rev())
+                };
+                (level, candidates.find(|index| menu.is_selectable(*index)))
+            };
+
+            if let Some(index) = next {
+                self.set_highlighted(level, Some(index));
+            }
+        }
+
+        /// The open menu at `point` and the item under it, if any. The
+        /// deepest menus are in front, so they're checked first.
+        fn item_at(&self, point: &Point<i32>) -> Option<(usize, Option<usize>)> {
+            let behavior = self.behavior();
+            let panels = behavior.panels.borrow();
+            let menus = behavior.menus.borrow();
+
+            for (level, panel) in panels.iter().enumerate().rev() {
+                let frame = panel.frame();
+                if !frame.contains_point(point) {
+                    continue;
+                }
+
+                let point = Point::new(point.x - frame.left(), point.y - frame.top());
+                let index = item_frames(&menus[level]).iter().position(|frame| frame.contains_point(&point));
+                return Some((level, index));
+            }
+
+            None
+        }
+
+        /// The position of `touch` in the context menu's coordinates.
+        fn touch_position(&self, touch: &Touch) -> Point<i32> {
+            let root = match touch.window() {
+                Some(window) => window.view,
+                None => self.view.root_view()
+            };
+
+            root.convert_point_to(&touch.position(), &self.view)
+        }
+    }
+
+    impl Behavior {
+        fn can_become_first_responder(&self) -> bool {
+            true
+        }
+
+        // Keys don't reach the content behind the menu.
+        fn press_began(&self, press: &Press) {
+            let context_menu = self.view_type();
+            let level = self.menus.borrow().len() - 1;
+            let highlighted = self.highlighted.borrow()[level];
+
+            match press.key().key_code() {
+                KeyCode::Up => context_menu.move_highlight(true),
+                KeyCode::Down => context_menu.move_highlight(false),
+                KeyCode::Left if level > 0 => context_menu.close_menus_after(level - 1),
+                KeyCode::Right | KeyCode::Return | KeyCode::KpEnter | KeyCode::Space => {
+                    let index = match highlighted {
+                        Some(index) => index,
+                        None => return
+                    };
+
+                    let is_submenu = self.menus.borrow()[level].items()[index].submenu_menu().is_some();
+                    if is_submenu {
+                        context_menu.open_submenu(level, index);
+                        context_menu.move_highlight(false);
+                    } else if press.key().key_code() != KeyCode::Right {
+                        context_menu.choose_item(level, index);
+                    }
+                },
+                KeyCode::Escape => context_menu.dismiss(),
+                _ => ()
+            }
+        }
+
+        fn press_ended(&self, _press: &Press) {}
+
+        fn touches_began(&self, touches: &Vec<Touch>) {
+            let context_menu = self.view_type();
+            if let Some(touch) = touches.first() {
+                if context_menu.item_at(&context_menu.touch_position(touch)).is_none() {
+                    context_menu.dismiss();
+                }
+            }
+        }
+
+        fn touches_moved(&self, touches: &Vec<Touch>) {
+            let context_menu = self.view_type();
+            if let Some(touch) = touches.first() {
+                if let Some((level, index)) = context_menu.item_at(&context_menu.touch_position(touch)) {
+                    let index = index.filter(|index| self.menus.borrow()[level].is_selectable(*index));
+                    context_menu.set_highlighted(level, index);
+                }
+            }
+        }
+
+        fn touches_ended(&self, touches: &Vec<Touch>) {
+            let context_menu = self.view_type();
+            if let Some(touch) = touches.first() {
+                if let Some((level, Some(index))) = context_menu.item_at(&context_menu.touch_position(touch)) {
+                    context_menu.choose_item(level, index);
+                }
+            }
+        }
+
+        // A touch taken over by a long press never ends, so there's nothing
+        // to do.
+        fn touches_cancelled(&self, _touches: &Vec<Touch>) {}
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::key::Key;
+    use crate::ui::menu::MenuItem;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn menu_in(content: &View) -> (ContextMenu, Rc<Cell<Option<&'static str>>>) {
+        let chosen = Rc::new(Cell::new(None));
+        let action = |title: &'static str| {
+            let chosen = chosen.clone();
+            move || chosen.set(Some(title))
+        };
+
+        let menu = Menu::new("", vec![
+            MenuItem::new("Cut", action("Cut")).enabled(false),
+            MenuItem::new("Copy", action("Copy")),
+            MenuItem::separator(),
+            MenuItem::submenu("Share", Menu::new("Share", vec![
+                MenuItem::new("Mail", action("Mail")),
+                MenuItem::new("Messages", action("Messages"))
+            ]))
+        ]);
+
+        let context_menu = ContextMenu::new(menu);
+        context_menu.present_at(&Point::new(50, 40), content);
+        (context_menu, chosen)
+    }
+
+    fn press(context_menu: &ContextMenu, key_code: KeyCode) {
+        context_menu.view.behavior().press_began(&Press::new(Key::new(key_code, vec![])));
+    }
+
+    #[test]
+    fn test_menu_frame() {
+        let size = Size::new(100, 50);
+        let container = Size::new(400, 300);
+        let at = |x, y| Rectangle::new(x, y, 0, 0);
+
+        assert_eq!(menu_frame(&at(50, 40), &size, &container), Rectangle::new(50, 40, 100, 50));
+
+        // Near the right and bottom edges it flips to the left and above.
+        assert_eq!(menu_frame(&at(350, 280), &size, &container), Rectangle::new(250, 230, 100, 50));
+
+        // A submenu goes beside its parent's row, or on the other side.
+        let row = Rectangle::new(100, 60, 100, 24);
+        assert_eq!(menu_frame(&row, &size, &container), Rectangle::new(200, 60, 100, 50));
+        let row = Rectangle::new(250, 60, 100, 24);
+        assert_eq!(menu_frame(&row, &size, &container), Rectangle::new(150, 60, 100, 50));
+
+        // Without room either way it's kept inside the container.
+        assert_eq!(menu_frame(&at(20, 10), &Size::new(500, 20), &container), Rectangle::new(0, 10, 500, 20));
+    }
+
+    #[test]
+    fn test_keyboard_navigation() {
+        let content = View::new(Rectangle::new(0, 0, 400, 300));
+        let (context_menu, chosen) = menu_in(&content);
+        assert!(context_menu.is_presented());
+
+        // The disabled item and the separator are skipped.
+        press(&context_menu, KeyCode::Down);
+        assert_eq!(context_menu.highlighted_items(), vec![Some(1)]);
+        press(&context_menu, KeyCode::Down);
+        assert_eq!(context_menu.highlighted_items(), vec![Some(3)]);
+        press(&context_menu, KeyCode::Down);
+        assert_eq!(context_menu.highlighted_items(), vec![Some(3)]);
+
+        // Right opens the submenu with its first item highlighted, and left
+        // closes it again.
+        press(&context_menu, KeyCode::Right);
+        assert_eq!(context_menu.highlighted_items(), vec![Some(3), Some(0)]);
+        assert_eq!(context_menu.view.subviews().len(), 2);
+        press(&context_menu, KeyCode::Left);
+        assert_eq!(context_menu.highlighted_items(), vec![Some(3)]);
+        assert_eq!(context_menu.view.subviews().len(), 1);
+
+        press(&context_menu, KeyCode::Return);
+        press(&context_menu, KeyCode::Down);
+        press(&context_menu, KeyCode::Return);
+        assert_eq!(chosen.get(), Some("Messages"));
+        assert!(!context_menu.is_presented());
+
+        let (context_menu, chosen) = menu_in(&content);
+        press(&context_menu, KeyCode::Up);
+        press(&context_menu, KeyCode::Escape);
+        assert_eq!(chosen.get(), None);
+        assert!(!context_menu.is_presented());
+    }
+
+    #[test]
+    fn test_touches_choose_items() {
+        let content = View::new(Rectangle::new(0, 0, 600, 300));
+        let (context_menu, chosen) = menu_in(&content);
+
+        // The menu takes the touches meant for the content.
+        assert_eq!(content.hit_test(&Point::new(300, 200)), Some(context_menu.view.clone()));
+
+        // The menu is at 50, 40, with the rows of its items at 44, 68 and
+        // 101, and the separator in between.
+        let touch = |x, y| vec![Touch::new(0, Point::new(x, y))];
+        let behavior = context_menu.view.behavior();
+
+        // The disabled item can't be chosen.
+        behavior.touches_began(&touch(60, 50));
+        behavior.touches_ended(&touch(60, 50));
+        assert!(context_menu.is_presented());
+
+        // The submenu item opens the submenu beside it.
+        behavior.touches_began(&touch(60, 110));
+        behavior.touches_ended(&touch(60, 110));
+        assert_eq!(context_menu.highlighted_items(), vec![Some(3), None]);
+        assert_eq!(context_menu.view.subviews()[1].frame().origin, Point::new(270, 101));
+
+        behavior.touches_began(&touch(280, 110));
+        behavior.touches_moved(&touch(280, 135));
+        assert_eq!(context_menu.highlighted_items(), vec![Some(3), Some(1)]);
+        behavior.touches_ended(&touch(280, 135));
+        assert_eq!(chosen.get(), Some("Messages"));
+        assert!(!context_menu.is_presented());
+        drop(behavior);
+
+        // Touching outside the menu dismisses it.
+        let (context_menu, chosen) = menu_in(&content);
+        context_menu.view.behavior().touches_began(&touch(10, 10));
+        assert!(!context_menu.is_presented());
+        assert_eq!(chosen.get(), None);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::ui::run_loop::RunLoop;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// The timing of a frame, passed to a `DisplayLink`'s callback.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameTime {
+    /// When the frame started, shortly before it's rendered.
+    pub timestamp: Instant,
+
+    /// The time since the display link's previous frame. Zero for its first
+    /// frame, and after being paused, so animations don't jump.
+    pub delta: Duration
+}
+
+struct DisplayLinkInner {
+    callback: Box<dyn Fn(&FrameTime)>,
+    frame_interval: Cell<Duration>,
+    is_paused: Cell<bool>,
+    is_valid: Cell<bool>,
+
+    // When the callback was last called. `None` before the first frame and
+    // while paused.
+    last_frame_at: Cell<Option<Instant>>
+}
+
+/// Calls a closure once per frame, for animations and other updates that
+/// change every frame.
+///
+/// Once added to the run loop, the callback is called on each pass of the run
+/// loop that's at least `frame_interval` after the previous frame, before
+/// windows are rendered, so views changed by the callback are drawn in the
+/// same frame.
+pub struct DisplayLink {
+    inner: Rc<DisplayLinkInner>
+}
+
+impl DisplayLink {
+    /// A display link calling `callback` at up to 60 frames a second. It
+    /// does nothing until it's added to the run loop with `add_to_run_loop`.
+    pub fn new(callback: impl Fn(&FrameTime) + 'static) -> DisplayLink {
+        DisplayLink {
+            inner: Rc::new(DisplayLinkInner {
+                callback: Box::new(callback),
+                frame_interval: Cell::new(Duration::from_micros(16_667)),
+                is_paused: Cell::new(false),
+                is_valid: Cell::new(true),
+                last_frame_at: Cell::new(None)
+            })
+        }
+    }
+
+    /// Starts calling the callback each frame.
+    pub fn add_to_run_loop(&self) {
+        RunLoop::borrow().add_display_link(self.clone());
+    }
+
+    /// The least time between frames.
+    pub fn frame_interval(&self) -> Duration {
+        self.inner.frame_interval.get()
+    }
+
+    /// Sets the least time between frames, such as a second divided by the
+    /// preferred frames per second.
+    pub fn set_frame_interval(&self, frame_interval: Duration) {
+        self.inner.frame_interval.set(frame_interval);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.is_paused.get()
+    }
+
+    /// Pauses or resumes calling the callback. The first frame after
+    /// resuming has a zero delta.
+    pub fn set_paused(&self, is_paused: bool) {
+        self.inner.is_paused.set(is_paused);
+        self.inner.last_frame_at.set(None);
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.inner.is_valid.get()
+    }
+
+    /// Stops the display link for good. The run loop removes it on its next
+    /// pass.
+    pub fn invalidate(&self) {
+        self.inner.is_valid.set(false);
+    }
+
+    /// Calls the callback if a frame is due at `now`. Returns whether it
+    /// was called.
+    pub(crate) fn tick(&self, now: Instant) -> bool {
+        if !self.is_valid() || self.is_paused() {
+            return false;
+        }
+
+        let delta = match self.inner.last_frame_at.get() {
+            Some(last_frame_at) => {
+                let delta = now.saturating_duration_since(last_frame_at);
+                if delta < self.frame_interval() {
+                    return false;
+                }
+                delta
+            },
+            None => Duration::from_secs(0)
+        };
+
+        self.inner.last_frame_at.set(Some(now));
+        (self.inner.callback)(&FrameTime { timestamp: now, delta });
+        true
+    }
+}
+
+impl Clone for DisplayLink {
+    fn clone(&self) -> Self {
+        DisplayLink {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_tick() {
+        let frames = Rc::new(RefCell::new(Vec::new()));
+        let frames_clone = frames.clone();
+        let display_link = DisplayLink::new(move |frame| frames_clone.borrow_mut().push(frame.delta));
+        display_link.set_frame_interval(Duration::from_millis(10));
+
+        let start = Instant::now();
+        assert!(display_link.tick(start));
+        assert!(!display_link.tick(start + Duration::from_millis(5)));
+        assert!(display_link.tick(start + Duration::from_millis(12)));
+
+        display_link.set_paused(true);
+        assert!(!display_link.tick(start + Duration::from_millis(30)));
+        display_link.set_paused(false);
+        assert!(display_link.tick(start + Duration::from_millis(40)));
+
+        display_link.invalidate();
+        assert!(!display_link.tick(start + Duration::from_millis(60)));
+
+        assert_eq!(*frames.borrow(), vec![
+            Duration::from_millis(0),
+            Duration::from_millis(12),
+            Duration::from_millis(0)
+        ]);
+    }
+}
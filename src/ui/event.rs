@@ -4,8 +4,17 @@ use std::rc::Rc;
 use std::cell::{Ref, RefCell};
 use crate::ui::touch::TouchPhase;
 use crate::graphics::Point;
-use crate::ui::key::Key;
-use crate::ui::press::Press;
+use crate::ui::key::{Key, KeyCode, ModifierFlag};
+use crate::ui::press::{Press, PressPhase};
+use crate::ui::key_repeat::KeyRepeat;
+use crate::ui::timer::Timer;
+use std::time::Duration;
+use crate::ui::run_loop::RunLoop;
+use crate::ui::clock;
+use std::time::Instant;
+use std::path::PathBuf;
+use crate::ui::view::tracking_area::HoverTracker;
+use crate::ui::Window;
 
 struct TouchEventInner {
     touches: Vec<Touch>
@@ -37,13 +46,28 @@ impl Clone for TouchEvent {
     }
 }
 
+/// Where a scroll is in the gesture that made it, such as fingers on a
+/// trackpad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollPhase {
+    /// Not part of a gesture, or the platform doesn't say.
+    None,
+    Began,
+    Changed,
+    Ended
+}
+
 struct ScrollEventInner {
     // While a scroll doesn't really have a "touch" per-se, we can use one to
     // keep track of the original cursor position of when the scroll started,
     // and most importantly which view is being scrolled (which would be
     // determined the same way a touch would, through `hit_test`).
     touch: Touch,
-    translation: Point<i32>
+    translation: Point<i32>,
+    delta: Point<f32>,
+    has_precise_deltas: bool,
+    phase: ScrollPhase,
+    momentum_phase: ScrollPhase
 }
 
 pub struct ScrollEvent {
@@ -57,7 +81,11 @@ impl ScrollEvent {
         ScrollEvent {
             inner: Rc::new(RefCell::new(ScrollEventInner {
                 touch,
-                translation
+                translation,
+                delta: Point::new(0.0, 0.0),
+                has_precise_deltas: false,
+                phase: ScrollPhase::None,
+                momentum_phase: ScrollPhase::None
             }))
         }
     }
@@ -66,9 +94,44 @@ impl ScrollEvent {
         self.inner.borrow().touch.clone()
     }
 
+    /// How far the wheel turned, as the platform reports it: in notches
+    /// for a mouse wheel, with up and right positive.
     pub fn translation(&self) -> Point<i32> {
         self.inner.borrow().translation.clone()
     }
+
+    /// How far to move the content offset of the view scrolled, in points.
+    /// Positive moves the content up and to the left, showing more of it
+    /// below and to the right.
+    pub fn delta(&self) -> Point<f32> {
+        self.inner.borrow().delta.clone()
+    }
+
+    /// Whether `delta` is exact, as from a trackpad, rather than a whole
+    /// number of lines, as from a mouse wheel. SDL only reports whole
+    /// notches, so this is `false` for platform events.
+    pub fn has_precise_deltas(&self) -> bool {
+        self.inner.borrow().has_precise_deltas
+    }
+
+    /// Where the scroll is in the gesture making it. Scrolls after a pause
+    /// begin a new gesture.
+    pub fn phase(&self) -> ScrollPhase {
+        self.inner.borrow().phase
+    }
+
+    /// Where the scroll is in the momentum after a gesture ends, for
+    /// platforms that report it; `ScrollPhase::None` otherwise, including
+    /// for SDL, which sends momentum as ordinary scrolls.
+    pub fn momentum_phase(&self) -> ScrollPhase {
+        self.inner.borrow().momentum_phase
+    }
+
+    pub(crate) fn set_delta(&self, delta: Point<f32>, has_precise_deltas: bool) {
+        let mut inner = self.inner.borrow_mut();
+        inner.delta = delta;
+        inner.has_precise_deltas = has_precise_deltas;
+    }
 }
 
 impl Clone for ScrollEvent {
@@ -111,14 +174,31 @@ impl Clone for PressEvent {
     }
 }
 
-singleton!(EventArena, touch_event: None, scroll_event: None, press_events: Vec::new());
+singleton!(EventArena, touch_event: None, scroll_event: None, last_scrolled_at: None, press_events: Vec::new(), key_repeat_timer: None, hover_tracker: HoverTracker::new(), dropped_files: Vec::new());
 
 pub(crate) struct EventArena {
     touch_event: Option<TouchEvent>,
     scroll_event: Option<ScrollEvent>,
-    press_events: Vec<PressEvent>
+
+    // When the last scroll happened, to tell when a new gesture begins.
+    last_scrolled_at: Option<Instant>,
+
+    press_events: Vec<PressEvent>,
+
+    // Repeats the most recently pressed key while it's held, along with
+    // that key.
+    key_repeat_timer: Option<(KeyCode, Timer)>,
+
+    // The tracking areas the pointer is in.
+    hover_tracker: HoverTracker,
+
+    // The files dropped so far, delivered together once the drop completes.
+    dropped_files: Vec<PathBuf>
 }
 
+/// How long after one scroll the next is part of the same gesture.
+const SCROLL_GESTURE_TIMEOUT: Duration = Duration::from_millis(100);
+
 impl EventArena {
     pub(crate) fn touch_event(&mut self) -> TouchEvent {
         if self.touch_event.is_none() {
@@ -142,8 +222,131 @@ impl EventArena {
         event
     }
 
-    pub(crate) fn press_ended(&mut self, key: Key) -> &PressEvent {
-        &self.press_events.iter().find(|event| event.press().key().key_code() == key.key_code()).unwrap()
+    /// Ends the press of `key`, removing its event. `None` if the key
+    /// wasn't pressed, such as when it was pressed before the window had
+    /// focus.
+    pub(crate) fn press_ended(&mut self, key: Key) -> Option<PressEvent> {
+        let index = self.press_events.iter().position(|event| event.press().key().key_code() == key.key_code())?;
+        let event = self.press_events.remove(index);
+        event.press().set_phase(PressPhase::Ended);
+
+        if matches!(&self.key_repeat_timer, Some((key_code, _)) if *key_code == key.key_code()) {
+            self.stop_key_repeat();
+        }
+
+        Some(event)
+    }
+
+    /// Updates the modifier flags of the keys being held, returning the
+    /// events of those that changed.
+    pub(crate) fn presses_changed(&mut self, modifier_flags: &[ModifierFlag]) -> Vec<PressEvent> {
+        let mut changed = Vec::new();
+
+        for event in self.press_events.iter() {
+            let press = event.press();
+            if press.key().modifier_flags().as_slice() == modifier_flags {
+                continue;
+            }
+
+            let key_code = press.key().key_code();
+            press.set_key(Key::new(key_code, modifier_flags.to_vec()));
+            press.set_phase(PressPhase::Changed);
+            changed.push(event.clone());
+        }
+
+        changed
+    }
+
+    /// Cancels every press, as when the window loses focus, returning their
+    /// events.
+    pub(crate) fn cancel_presses(&mut self) -> Vec<PressEvent> {
+        self.stop_key_repeat();
+
+        let events: Vec<PressEvent> = self.press_events.drain(..).collect();
+        for event in events.iter() {
+            event.press().set_phase(PressPhase::Cancelled);
+        }
+
+        events
+    }
+
+    /// Starts repeating `press` while it's held, replacing any key already
+    /// repeating. Each repeat is sent to the press's first responder.
+    pub(crate) fn start_key_repeat(&mut self, press: Press, key_repeat: KeyRepeat) {
+        self.stop_key_repeat();
+
+        let key_code = press.key().key_code();
+        let timer = Timer::new_repeating(key_repeat.interval(), move || {
+            if !press.phase().is_active() {
+                return;
+            }
+
+            let repeats = key_repeat.repeats_after(clock::now().saturating_duration_since(*press.timestamp()));
+            if repeats <= press.repeat_count() {
+                return;
+            }
+
+            // Repeats missed while the run loop was busy are dropped rather
+            // than sent all at once.
+            press.set_repeat_count(repeats);
+
+            let first_responder = press.first_responder().upgrade();
+            if let Some(first_responder) = first_responder {
+                first_responder.press_repeated(&press);
+            }
+        });
+
+        RunLoop::borrow().add_timer(timer.clone());
+        self.key_repeat_timer = Some((key_code, timer));
+    }
+
+    pub(crate) fn stop_key_repeat(&mut self) {
+        if let Some((_, timer)) = self.key_repeat_timer.take() {
+            timer.invalidate();
+        }
+    }
+
+    /// Tells the views whose tracking areas the pointer entered, left or
+    /// moved within, as it moved to `position` in `window`.
+    pub(crate) fn pointer_moved(&mut self, window: &Window, position: &Point<i32>) {
+        self.hover_tracker.pointer_moved(&window.view, position);
+    }
+
+    /// Tells the views whose tracking areas the pointer was in that it left
+    /// them, as it left the window.
+    pub(crate) fn pointer_exited(&mut self) {
+        self.hover_tracker.pointer_exited();
+    }
+
+    /// Where the pointer last moved to, in the window it's over.
+    pub(crate) fn pointer_position(&mut self) -> Point<i32> {
+        self.scroll_event().touch().position()
+    }
+
+    /// Starts collecting the files of a drop.
+    pub(crate) fn drop_began(&mut self) {
+        self.dropped_files.clear();
+    }
+
+    pub(crate) fn file_dropped(&mut self, path: PathBuf) {
+        self.dropped_files.push(path);
+    }
+
+    /// Ends the drop, returning the files dropped.
+    pub(crate) fn drop_completed(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.dropped_files)
+    }
+
+    /// Takes `touch` from its view once a gesture recognizer has recognized
+    /// it, while its recognizers carry on receiving it. Returns the event to
+    /// send the view `touches_cancelled` with, or `None` if it already was.
+    pub(crate) fn cancel_touch_in_view(&mut self, touch: &Touch) -> Option<TouchEvent> {
+        if touch.is_cancelled_in_view() {
+            return None;
+        }
+
+        touch.set_cancelled_in_view();
+        Some(self.touch_event())
     }
 
     pub(crate) fn touch_began(&mut self, touch: Touch) -> TouchEvent {
@@ -187,12 +390,23 @@ impl EventArena {
         panic!("Touch just ended but it doesn't exist");
     }
 
-    pub(crate) fn scroll_did_translate(&mut self, translation: Point<i32>) {
+    /// Updates the scroll event for the wheel turning by `translation`,
+    /// moving the content by `delta` points.
+    pub(crate) fn scroll_did_translate(&mut self, translation: Point<i32>, delta: Point<f32>) {
+        let now = clock::now();
+        let is_continued = self.last_scrolled_at
+            .is_some_and(|last_scrolled_at| now.saturating_duration_since(last_scrolled_at) < SCROLL_GESTURE_TIMEOUT);
+        self.last_scrolled_at = Some(now);
+
         let event = self.scroll_event();
-        event.inner.borrow_mut().translation = translation;
+        event.set_delta(delta, false);
+
+        let mut inner = event.inner.borrow_mut();
+        inner.translation = translation;
+        inner.phase = if is_continued { ScrollPhase::Changed } else { ScrollPhase::Began };
     }
 
-    /// Clears out any touches that have ended.
+    /// Clears out any touches that have ended or been cancelled.
     ///
     /// This is called by the start of the event loop.
     pub(crate) fn cleanup_ended_touches(&mut self) {
@@ -219,6 +433,19 @@ mod tests {
     use super::*;
     use crate::graphics::Point;
 
+    /// An arena with no events, as the singleton starts out.
+    fn empty_arena() -> EventArena {
+        EventArena {
+            touch_event: None,
+            scroll_event: None,
+            last_scrolled_at: None,
+            press_events: Vec::new(),
+            key_repeat_timer: None,
+            hover_tracker: HoverTracker::new(),
+            dropped_files: Vec::new()
+        }
+    }
+
     #[test]
     fn test_touch_event_clone() {
         let touch_event = TouchEvent::new();
@@ -236,14 +463,14 @@ mod tests {
 
     #[test]
     fn test_event_arena_touch_event() {
-        let mut arena = EventArena { touch_event: None, scroll_event: None, press_events: Vec::new() };
+        let mut arena = empty_arena();
         let touch_event = arena.touch_event();
         assert_eq!(touch_event.touches().len(), 0);
     }
 
     #[test]
     fn test_event_arena_touch_began() {
-        let mut arena = EventArena { touch_event: None, scroll_event: None, press_events: Vec::new() };
+        let mut arena = empty_arena();
         let touch = Touch::new(0, Point::new(0, 0));
         arena.touch_began(touch);
         let touch_event = arena.touch_event();
@@ -253,7 +480,7 @@ mod tests {
     #[test]
     #[should_panic]
     fn test_event_arena_touch_began_twice() {
-        let mut arena = EventArena { touch_event: None, scroll_event: None, press_events: Vec::new() };
+        let mut arena = empty_arena();
         let touch = Touch::new(0, Point::new(0, 0));
         arena.touch_began(touch);
         let touch = Touch::new(0, Point::new(0, 0));
@@ -262,7 +489,7 @@ mod tests {
 
     #[test]
     fn test_event_arena_touch_moved() {
-        let mut arena = EventArena { touch_event: None, scroll_event: None, press_events: Vec::new() };
+        let mut arena = empty_arena();
         let touch = Touch::new(0, Point::new(0, 0));
         arena.touch_began(touch);
         arena.touch_moved(0, Point::new(10, 50));
@@ -273,7 +500,7 @@ mod tests {
 
     #[test]
     fn test_event_arena_touch_ended() {
-        let mut arena = EventArena { touch_event: None, scroll_event: None, press_events: Vec::new() };
+        let mut arena = empty_arena();
         let touch = Touch::new(0, Point::new(0, 0));
         arena.touch_began(touch);
         arena.touch_ended(0, Point::new(10, 50));
@@ -286,13 +513,13 @@ mod tests {
     #[test]
     #[should_panic]
     fn test_event_arena_touch_ended_but_didnt_exist() {
-        let mut arena = EventArena { touch_event: None, scroll_event: None, press_events: Vec::new() };
+        let mut arena = empty_arena();
         arena.touch_ended(0, Point::new(10, 50));
     }
 
     #[test]
     fn test_event_arena_cleanup_ended_touches() {
-        let mut arena = EventArena { touch_event: None, scroll_event: None, press_events: Vec::new() };
+        let mut arena = empty_arena();
         let touch = Touch::new(0, Point::new(0, 0));
         arena.touch_began(touch);
         let touch = Touch::new(1, Point::new(0, 0));
@@ -307,7 +534,7 @@ mod tests {
 
     #[test]
     fn test_event_arena_began_to_stationary() {
-        let mut arena = EventArena { touch_event: None, scroll_event: None, press_events: Vec::new() };
+        let mut arena = empty_arena();
         let touch = Touch::new(0, Point::new(0, 0));
         arena.touch_began(touch);
         assert_eq!(arena.touch_event().touches().len(), 1);
@@ -316,4 +543,27 @@ mod tests {
         assert_eq!(arena.touch_event().touches().len(), 1);
         assert_eq!(arena.touch_event().touches()[0].phase(), TouchPhase::Stationary);
     }
+
+    #[test]
+    fn test_event_arena_presses() {
+        let mut arena = empty_arena();
+        let held = arena.press_began(Key::new(KeyCode::Left, vec![]));
+        arena.press_began(Key::new(KeyCode::LShift, vec![ModifierFlag::Shift]));
+
+        // Pressing Shift changes the held arrow key.
+        let changed = arena.presses_changed(&[ModifierFlag::Shift]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(*held.press().phase(), PressPhase::Changed);
+        assert_eq!(held.press().key().modifier_flags(), &vec![ModifierFlag::Shift]);
+
+        let ended = arena.press_ended(Key::new(KeyCode::LShift, vec![])).unwrap();
+        assert_eq!(*ended.press().phase(), PressPhase::Ended);
+        assert!(arena.press_ended(Key::new(KeyCode::LShift, vec![])).is_none());
+
+        let cancelled = arena.cancel_presses();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(*held.press().phase(), PressPhase::Cancelled);
+        assert!(!held.press().phase().is_active());
+        assert!(arena.cancel_presses().is_empty());
+    }
 }
@@ -1,187 +1,447 @@
 use crate::ui::application::Application;
 use crate::ui::touch::Touch;
+use crate::ui::Window;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
 use crate::graphics::Point;
-use crate::ui::event::EventArena;
+use crate::ui::event::{EventArena, PressEvent};
 use crate::ui::key::{Key, ModifierFlag};
+use crate::ui::gesture;
+use crate::platform::cursor;
+use std::path::PathBuf;
 
 pub(crate) fn update(sdl: &sdl2::Sdl) {
     let mut event_pump = sdl.event_pump().unwrap();
-    let mut event_arena = EventArena::borrow_mut();
-
-    event_arena.cleanup_ended_touches();
-    event_arena.update_began_to_stationary();
 
     #[allow(unused_mut)]
     let mut timeout = 0;
     #[cfg(target_os = "emscripten")]
     { timeout = 10; }
 
-    if let Some(sdl_event) = event_pump.wait_event_timeout(timeout) { //blocking wait for events
-        match sdl_event {
-            sdl2::event::Event::Quit { .. } => {
-                let application = Application::borrow();
-                application.exit();
-            },
-            sdl2::event::Event::MouseButtonDown { window_id, x, y, .. } => {
-                let touch = Touch::new(
-                    0,
-                    Point { x, y },
-                );
-
-                let application = Application::borrow();
-                application.assign_targets_to_touch(window_id, &touch);
-                let event = event_arena.touch_began(touch.clone());
-
-                for gesture_recognizer in touch.gesture_recognizers().iter() {
-                    if let Some(gesture_recognizer) = gesture_recognizer.upgrade() {
-                        gesture_recognizer.touches_began(&event.touches(), &event);
-                    }
-                }
+    match event_pump.wait_event_timeout(timeout) { //blocking wait for events
+        Some(sdl_event) => handle_event(sdl_event),
+        None => {
+            let mut event_arena = EventArena::borrow_mut();
+            event_arena.cleanup_ended_touches();
+            event_arena.update_began_to_stationary();
+        }
+    }
+}
+
+/// Sends `sdl_event` to the views of the window it happened in. Events
+/// synthesized by a `TestDriver` are handled here too, as if they came from
+/// the platform.
+pub(crate) fn handle_event(sdl_event: sdl2::event::Event) {
+    let mut event_arena = EventArena::borrow_mut();
+
+    event_arena.cleanup_ended_touches();
+    event_arena.update_began_to_stationary();
 
-                if let Some(view) = touch.view() {
-                    view.touches_began(&event.touches(), &event);
+    match sdl_event {
+        sdl2::event::Event::Quit { .. } => {
+            let application = Application::borrow();
+            application.exit();
+        },
+        // The right button shows a context menu rather than touching.
+        sdl2::event::Event::MouseButtonDown { mouse_btn: MouseButton::Right, window_id, x, y, .. } => {
+            let window = Application::borrow().get_window(window_id).cloned();
+            if let Some(window) = window {
+                window.show_context_menu(&Point { x, y });
+            }
+        },
+        sdl2::event::Event::MouseButtonUp { mouse_btn: MouseButton::Right, .. } => (),
+
+        // Touch screens also send the touches as a mouse, which are left
+        // for the finger events.
+        sdl2::event::Event::MouseButtonDown { which: TOUCH_MOUSE_ID, .. } |
+        sdl2::event::Event::MouseButtonUp { which: TOUCH_MOUSE_ID, .. } |
+        sdl2::event::Event::MouseMotion { which: TOUCH_MOUSE_ID, .. } => (),
+
+        sdl2::event::Event::MouseButtonDown { window_id, clicks, x, y, .. } => {
+            let touch = Touch::new(
+                0,
+                Point { x, y },
+            );
+            touch.set_tap_count(clicks as usize);
+
+            touch_began(&mut event_arena, window_id, touch);
+        },
+        sdl2::event::Event::MouseButtonUp { x, y, .. } => {
+            touch_ended(&mut event_arena, 0, Point { x, y });
+        },
+        sdl2::event::Event::MouseMotion { window_id, mousestate, x, y, .. } => {
+            let window = Application::borrow().get_window(window_id).cloned();
+            if let Some(window) = window {
+                event_arena.pointer_moved(&window, &Point { x, y });
+
+                // The cursor stays as it was while dragging, such as an
+                // I-beam while selecting text past the edge of a field.
+                if !mousestate.left() {
+                    cursor::set(window.view.cursor_at(&Point { x, y }));
+                }
+            }
+
+            touch_moved(&mut event_arena, 0, Point { x, y });
+        },
+
+        // Fingers are touches of their own, numbered after the mouse, in
+        // the window with focus. SDL reports where they are as a fraction
+        // of the window.
+        sdl2::event::Event::FingerDown { finger_id, x, y, .. } => {
+            if let Some((window_id, position)) = finger_position(x, y) {
+                touch_began(&mut event_arena, window_id, Touch::new(finger_id as usize + 1, position));
+            }
+        },
+        sdl2::event::Event::FingerMotion { finger_id, x, y, .. } => {
+            if let Some((_, position)) = finger_position(x, y) {
+                touch_moved(&mut event_arena, finger_id as usize + 1, position);
+            }
+        },
+        sdl2::event::Event::FingerUp { finger_id, x, y, .. } => {
+            if let Some((_, position)) = finger_position(x, y) {
+                touch_ended(&mut event_arena, finger_id as usize + 1, position);
+            }
+        },
+        // Pinches are recognized from the finger events SDL works these
+        // out from.
+        sdl2::event::Event::MultiGesture { .. } => (),
+
+        // https://stackoverflow.com/a/47597200/869367
+        sdl2::event::Event::MouseWheel { window_id, x, y, direction, .. } => {
+            let window = match Application::borrow().get_window(window_id).cloned() {
+                Some(window) => window,
+                None => return
+            };
+
+            // Natural scrolling reports the wheel turning the other way.
+            let (x, y) = match direction {
+                MouseWheelDirection::Flipped => (-x, -y),
+                _ => (x, y)
+            };
+
+            let render_scale = window.context().render_scale();
+            let delta = Point::new(x as f32 * render_scale, -y as f32 * render_scale);
+            event_arena.scroll_did_translate(Point::new(x, y), delta);
+
+            // The scroll happens where the pointer last moved to.
+            let event = event_arena.scroll_event();
+            let position = event.touch().position();
+            window.view.scroll_wheel_at(&position, &event);
+        },
+
+        // Several files dropped at once come between a begin and complete,
+        // and are sent to the view together.
+        sdl2::event::Event::DropBegin { .. } => event_arena.drop_began(),
+        sdl2::event::Event::DropFile { filename, .. } => event_arena.file_dropped(PathBuf::from(filename)),
+        sdl2::event::Event::DropComplete { window_id, .. } => {
+            let paths = event_arena.drop_completed();
+            let window = Application::borrow().get_window(window_id).cloned();
+            if let Some(window) = window.filter(|_| !paths.is_empty()) {
+                // SDL doesn't say where the files were dropped, so it's
+                // where the pointer last moved to.
+                let position = event_arena.pointer_position();
+                window.view.files_dropped_at(&position, &paths);
+            }
+        },
+
+        sdl2::event::Event::TextInput { window_id, text, .. } => {
+            let window = Application::borrow().get_window(window_id).cloned();
+            if let Some(window) = window {
+                let first_responder = window.first_responder();
+                first_responder.text_input_did_receive(&text);
+            }
+        },
+
+        // Held keys are repeated by the event arena rather than the
+        // platform, so the delay and interval can be set.
+        sdl2::event::Event::KeyDown { repeat: true, .. } => (),
+
+        sdl2::event::Event::KeyDown { window_id, keycode, keymod, .. } => {
+            // The application isn't kept borrowed while the press is sent,
+            // as responders and key commands may open windows.
+            let window = Application::borrow().get_window(window_id).cloned();
+
+            if let Some(keycode) = keycode {
+                let modifier_flags = modifier_flags(keymod);
+
+                // Keys already held change when a modifier is pressed.
+                for event in event_arena.presses_changed(&modifier_flags).iter() {
+                    send_presses_changed(event);
                 }
-            },
-            sdl2::event::Event::MouseButtonUp { x, y, .. } => {
-                event_arena.touch_ended(0, Point { x, y });
 
-                let event = event_arena.touch_event();
+                let key = Key::new(keycode, modifier_flags);
 
-                if let Some(existing_touch) = event_arena.touch_event().touches().first() {
-                    for gesture_recognizer in existing_touch.gesture_recognizers().iter() {
-                        if let Some(gesture_recognizer) = gesture_recognizer.upgrade() {
-                            gesture_recognizer.touches_ended(&event.touches(), &event);
-                        }
-                    }
+                // Keyboard shortcuts are matched before the press is sent to
+                // the first responder, which never sees a matched press.
+                let is_key_command = window.as_ref().is_some_and(|window| window.perform_key_command(&key));
 
-                    if let Some(view) = existing_touch.view() {
-                        view.touches_ended(&event.touches(), &event);
-                    }
+                let is_modifier = key.is_modifier();
+                let event = event_arena.press_began(key);
+                let press = event.press();
+                if let Some(window) = window.filter(|_| !is_key_command) {
+                    let first_responder = window.first_responder();
+                    press.set_first_responder(first_responder.downgrade());
+                    first_responder.presses_began(std::slice::from_ref(press), &event);
                 }
-            },
-            sdl2::event::Event::MouseMotion { x, y, .. } => {
-                event_arena.touch_moved(0, Point { x, y });
 
-                let event = event_arena.touch_event();
-
-                if let Some(existing_touch) = event_arena.touch_event().touches().first() {
-                    for gesture_recognizer in existing_touch.gesture_recognizers().iter() {
-                        if let Some(gesture_recognizer) = gesture_recognizer.upgrade() {
-                            gesture_recognizer.touches_moved(&event.touches(), &event);
-                        }
+                let key_repeat = Application::borrow().key_repeat();
+                if let Some(key_repeat) = key_repeat {
+                    if !is_modifier && !is_key_command {
+                        event_arena.start_key_repeat(press.clone(), key_repeat);
                     }
+                }
+            }
+        },
 
-                    if let Some(view) = existing_touch.view() {
-                        view.touches_moved(&event.touches(), &event);
+        sdl2::event::Event::KeyUp { keycode, keymod, .. } => {
+            if let Some(keycode) = keycode {
+                let key = Key::new(keycode, Vec::new());
+                if let Some(event) = event_arena.press_ended(key) {
+                    let press = event.press();
+                    let first_responder = press.first_responder().upgrade();
+                    if let Some(first_responder) = first_responder {
+                        first_responder.presses_ended(std::slice::from_ref(press), &event);
                     }
                 }
-            },
-            sdl2::event::Event::MultiGesture { .. } => {
-                println!("SDL_MultiGestureEvent")
-            },
 
-            // https://stackoverflow.com/a/47597200/869367
-            sdl2::event::Event::MouseWheel { window_id, x, y, .. } => {
-                let event = event_arena.scroll_event();
-                let touch = event.touch();
+                // Keys still held change when a modifier is released.
+                for event in event_arena.presses_changed(&modifier_flags(keymod)).iter() {
+                    send_presses_changed(event);
+                }
+            }
+        },
+
+        // Keys released while the window doesn't have focus would never
+        // end, so they're cancelled instead. Screen readers follow the
+        // window with focus.
+        sdl2::event::Event::Window { win_event: sdl2::event::WindowEvent::FocusLost, window_id, .. } => {
+            for event in event_arena.cancel_presses().iter() {
+                let press = event.press();
+                let first_responder = press.first_responder().upgrade();
+                if let Some(first_responder) = first_responder {
+                    first_responder.presses_cancelled(std::slice::from_ref(press), event);
+                }
+            }
+
+            let window = Application::borrow().get_window(window_id).cloned();
+            if let Some(window) = window {
+                window.did_change_focus(false);
+            }
+        },
+
+        sdl2::event::Event::Window { win_event: sdl2::event::WindowEvent::FocusGained, window_id, .. } => {
+            let window = Application::borrow().get_window(window_id).cloned();
+            if let Some(window) = window {
+                window.did_change_focus(true);
+            }
+        },
+
+        sdl2::event::Event::Window { win_event: sdl2::event::WindowEvent::Leave, .. } => {
+            event_arena.pointer_exited();
+        },
+
+        // The window's views are resized to fill it, rather than the old
+        // rendering being stretched.
+        sdl2::event::Event::Window { win_event: sdl2::event::WindowEvent::SizeChanged(..), window_id, .. } => {
+            let window = Application::borrow().get_window(window_id).cloned();
+            if let Some(window) = window {
+                window.did_resize();
+            }
+        },
+
+        // Moving to another screen can change the render scale.
+        sdl2::event::Event::Window { win_event: sdl2::event::WindowEvent::Moved(..), window_id, .. } => {
+            let window = Application::borrow().get_window(window_id).cloned();
+            if let Some(window) = window {
+                window.did_move();
+            }
+        },
+
+        _ => ()
+    }
+}
 
-                let application = Application::borrow();
-                application.assign_targets_to_touch(window_id, &touch);
+/// The `which` of mouse events SDL synthesizes from touches
+/// (`SDL_TOUCH_MOUSEID`).
+const TOUCH_MOUSE_ID: u32 = u32::MAX;
 
-                event_arena.scroll_did_translate(Point::new(x, y));
+/// Starts `touch` in the window with `window_id`, sending it to the
+/// recognizers and view under it.
+fn touch_began(event_arena: &mut EventArena, window_id: u32, touch: Touch) {
+    Application::borrow().assign_targets_to_touch(window_id, &touch);
+    let event = event_arena.touch_began(touch.clone());
 
-                for gesture_recognizer in touch.gesture_recognizers().iter() {
-                    if let Some(gesture_recognizer) = gesture_recognizer.upgrade() {
-                        gesture_recognizer.scroll_did_translate(&event.translation(), &event);
-                    }
-                }
-            },
+    for gesture_recognizer in touch.gesture_recognizers().iter() {
+        if let Some(gesture_recognizer) = gesture_recognizer.upgrade() {
+            gesture_recognizer.touches_began(&event.touches(), &event);
+        }
+    }
 
-            sdl2::event::Event::TextInput { window_id, text, .. } => {
-                let application = Application::borrow();
-                let window = application.get_window(window_id);
-                if let Some(window) = window {
-                    let first_responder = window.first_responder();
-                    first_responder.text_input_did_receive(&text);
-                }
-            },
+    resolve_gestures(event_arena, &touch);
 
-            sdl2::event::Event::KeyDown { window_id, keycode, keymod, .. } => {
-                let application = Application::borrow();
-                let window = application.get_window(window_id);
+    if let Some(view) = touch.view().filter(|_| !touch.is_cancelled_in_view()) {
+        view.touches_began(&event.touches(), &event);
+    }
+}
 
-                // let event = event_arena.key_down_event();
-                if let Some(keycode) = keycode {
-                    let mut modifier_flags = Vec::new();
+/// Moves the touch with `touch_id` to `position`, or the pointer if there's
+/// no such touch.
+fn touch_moved(event_arena: &mut EventArena, touch_id: usize, position: Point<i32>) {
+    event_arena.touch_moved(touch_id, position);
 
-                    if keymod.contains(sdl2::keyboard::Mod::LSHIFTMOD) {
-                        modifier_flags.push(ModifierFlag::Shift);
-                    }
+    let event = event_arena.touch_event();
+    let touch = match event.touches().iter().find(|touch| touch.id() == touch_id).cloned() {
+        Some(touch) => touch,
+        None => return
+    };
 
-                    if keymod.contains(sdl2::keyboard::Mod::RSHIFTMOD) {
-                        modifier_flags.push(ModifierFlag::Shift);
-                    }
+    for gesture_recognizer in touch.gesture_recognizers().iter() {
+        if let Some(gesture_recognizer) = gesture_recognizer.upgrade() {
+            gesture_recognizer.touches_moved(&event.touches(), &event);
+        }
+    }
 
-                    if keymod.contains(sdl2::keyboard::Mod::LCTRLMOD) {
-                        modifier_flags.push(ModifierFlag::Control);
-                    }
+    resolve_gestures(event_arena, &touch);
 
-                    if keymod.contains(sdl2::keyboard::Mod::RCTRLMOD) {
-                        modifier_flags.push(ModifierFlag::Control);
-                    }
+    if let Some(view) = touch.view().filter(|_| !touch.is_cancelled_in_view()) {
+        view.touches_moved(&event.touches(), &event);
+    }
+}
 
-                    if keymod.contains(sdl2::keyboard::Mod::LALTMOD) {
-                        modifier_flags.push(ModifierFlag::Alternate);
-                    }
+/// Ends the touch with `touch_id` at `position`. A touch that never began
+/// here, such as a click that focused the window, is ignored.
+fn touch_ended(event_arena: &mut EventArena, touch_id: usize, position: Point<i32>) {
+    let event = event_arena.touch_event();
+    let touch = match event.touches().iter().find(|touch| touch.id() == touch_id).cloned() {
+        Some(touch) => touch,
+        None => return
+    };
 
-                    if keymod.contains(sdl2::keyboard::Mod::RALTMOD) {
-                        modifier_flags.push(ModifierFlag::Alternate);
-                    }
+    event_arena.touch_ended(touch_id, position);
 
-                    if keymod.contains(sdl2::keyboard::Mod::LGUIMOD) {
-                        modifier_flags.push(ModifierFlag::Command);
-                    }
+    for gesture_recognizer in touch.gesture_recognizers().iter() {
+        if let Some(gesture_recognizer) = gesture_recognizer.upgrade() {
+            gesture_recognizer.touches_ended(&event.touches(), &event);
+        }
+    }
 
-                    if keymod.contains(sdl2::keyboard::Mod::RGUIMOD) {
-                        modifier_flags.push(ModifierFlag::Command);
-                    }
+    resolve_gestures(event_arena, &touch);
 
-                    if keymod.contains(sdl2::keyboard::Mod::NUMMOD) {
-                        modifier_flags.push(ModifierFlag::NumericPad);
-                    }
+    if let Some(view) = touch.view().filter(|_| !touch.is_cancelled_in_view()) {
+        view.touches_ended(&event.touches(), &event);
+    }
+}
 
-                    if keymod.contains(sdl2::keyboard::Mod::CAPSMOD) {
-                        modifier_flags.push(ModifierFlag::CapsLock);
-                    }
+/// The key window's id and where in it a finger at `x`, `y` is, as SDL's
+/// fractions of the window.
+fn finger_position(x: f32, y: f32) -> Option<(u32, Point<i32>)> {
+    let window = Application::borrow().get_key_window().upgrade().map(Window::from_view)?;
+    let frame = window.view.frame();
+    let position = Point::new(
+        (x * frame.size.width as f32) as i32,
+        (y * frame.size.height as f32) as i32
+    );
+    Some((window.context().id(), position))
+}
 
-                    if keymod.contains(sdl2::keyboard::Mod::MODEMOD) {
-                        modifier_flags.push(ModifierFlag::Alternate);
-                    }
+/// Fails the recognizers of `touch` that can't recognize their gesture
+/// alongside one that has, and cancels the touch in its view if that
+/// recognizer cancels touches in view.
+fn resolve_gestures(event_arena: &mut EventArena, touch: &Touch) {
+    let recognizers = touch.gesture_recognizers().clone();
+    if !gesture::resolve(&recognizers) {
+        return;
+    }
 
-                    let key = Key::new(keycode, modifier_flags);
+    if let Some(event) = event_arena.cancel_touch_in_view(touch) {
+        gesture::touch_cancelled_in_view(touch, &event);
+    }
+}
 
-                    let event = event_arena.press_began(key);
-                    let press = event.press();
-                    if let Some(window) = window {
-                        let first_responder = window.first_responder();
-                        press.set_first_responder(first_responder.downgrade());
-                        first_responder.press_began(press, &event);
-                    }
-                }
-            },
+fn send_presses_changed(event: &PressEvent) {
+    let press = event.press();
+    let first_responder = press.first_responder().upgrade();
+    if let Some(first_responder) = first_responder {
+        first_responder.presses_changed(std::slice::from_ref(press), event);
+    }
+}
 
-            sdl2::event::Event::KeyUp { keycode, .. } => {
-                if let Some(keycode) = keycode {
-                    let key = Key::new(keycode, Vec::new());
-                    let event = event_arena.press_ended(key);
-                    let press = event.press();
-                    if let Some(first_responder) = press.first_responder().upgrade() {
-                        first_responder.press_ended(press, &event);
-                    }
-                }
-            },
+fn modifier_flags(keymod: sdl2::keyboard::Mod) -> Vec<ModifierFlag> {
+    let mut modifier_flags = Vec::new();
 
-            _ => ()
-        }
+    if keymod.contains(sdl2::keyboard::Mod::LSHIFTMOD) {
+        modifier_flags.push(ModifierFlag::Shift);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::RSHIFTMOD) {
+        modifier_flags.push(ModifierFlag::Shift);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::LCTRLMOD) {
+        modifier_flags.push(ModifierFlag::Control);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::RCTRLMOD) {
+        modifier_flags.push(ModifierFlag::Control);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::LALTMOD) {
+        modifier_flags.push(ModifierFlag::Alternate);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::RALTMOD) {
+        modifier_flags.push(ModifierFlag::Alternate);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::LGUIMOD) {
+        modifier_flags.push(ModifierFlag::Command);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::RGUIMOD) {
+        modifier_flags.push(ModifierFlag::Command);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::NUMMOD) {
+        modifier_flags.push(ModifierFlag::NumericPad);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::CAPSMOD) {
+        modifier_flags.push(ModifierFlag::CapsLock);
+    }
+
+    if keymod.contains(sdl2::keyboard::Mod::MODEMOD) {
+        modifier_flags.push(ModifierFlag::Alternate);
+    }
+
+    modifier_flags
+}
+
+/// The SDL modifier state with `modifier_flags` held, for synthesized key
+/// events. The opposite of `modifier_flags`.
+pub(crate) fn keymod(modifier_flags: &[ModifierFlag]) -> sdl2::keyboard::Mod {
+    let mut keymod = sdl2::keyboard::Mod::NOMOD;
+
+    for modifier_flag in modifier_flags {
+        keymod |= match modifier_flag {
+            ModifierFlag::Shift => sdl2::keyboard::Mod::LSHIFTMOD,
+            ModifierFlag::Control => sdl2::keyboard::Mod::LCTRLMOD,
+            ModifierFlag::Alternate => sdl2::keyboard::Mod::LALTMOD,
+            ModifierFlag::Command => sdl2::keyboard::Mod::LGUIMOD,
+            ModifierFlag::CapsLock => sdl2::keyboard::Mod::CAPSMOD,
+            ModifierFlag::NumericPad => sdl2::keyboard::Mod::NUMMOD
+        };
+    }
+
+    keymod
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymod() {
+        let flags = vec![ModifierFlag::Shift, ModifierFlag::Command, ModifierFlag::NumericPad];
+        assert_eq!(modifier_flags(keymod(&flags)), flags);
+        assert!(modifier_flags(keymod(&[])).is_empty());
     }
 }
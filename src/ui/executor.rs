@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Wake, Waker};
+
+/// Marks its task to be polled on the run loop's next pass. Wakers can be
+/// sent to and woken from any thread.
+struct TaskWaker {
+    is_woken: AtomicBool
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.is_woken.store(true, Ordering::SeqCst);
+    }
+}
+
+struct LocalTask {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    waker: Arc<TaskWaker>
+}
+
+/// A single-threaded executor for futures spawned on the run loop.
+///
+/// Tasks are polled on the main thread, once when spawned and then on each
+/// pass of the run loop after their waker is woken. Since the run loop
+/// already wakes up every few milliseconds, waking a task from another
+/// thread only has to mark it; it's polled on the next pass.
+pub(crate) struct Executor {
+    tasks: RefCell<Vec<LocalTask>>
+}
+
+impl Executor {
+    pub(crate) const fn new() -> Executor {
+        Executor {
+            tasks: RefCell::new(Vec::new())
+        }
+    }
+
+    /// Adds `future` to be polled on the next call to `poll_woken_tasks`.
+    pub(crate) fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        self.tasks.borrow_mut().push(LocalTask {
+            future: Box::pin(future),
+            waker: Arc::new(TaskWaker {
+                is_woken: AtomicBool::new(true)
+            })
+        });
+    }
+
+    /// Polls each task woken since it was last polled, dropping those that
+    /// finish. Tasks spawned while polling are polled on the next call.
+    pub(crate) fn poll_woken_tasks(&self) {
+        // Take the tasks out while they're polled, so that they can spawn
+        // new tasks.
+        let tasks: Vec<LocalTask> = self.tasks.borrow_mut().drain(..).collect();
+        let mut pending = Vec::with_capacity(tasks.len());
+
+        for mut task in tasks {
+            if !task.waker.is_woken.swap(false, Ordering::SeqCst) {
+                pending.push(task);
+                continue;
+            }
+
+            let waker = Waker::from(task.waker.clone());
+            let mut context = Context::from_waker(&waker);
+            if task.future.as_mut().poll(&mut context).is_pending() {
+                pending.push(task);
+            }
+        }
+
+        let mut current = self.tasks.borrow_mut();
+        let spawned: Vec<LocalTask> = current.drain(..).collect();
+        current.extend(pending);
+        current.extend(spawned);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::Mutex;
+    use std::task::Poll;
+
+    /// A future that's ready once `is_ready` is set, after being woken.
+    struct Signal {
+        is_ready: Arc<AtomicBool>,
+        waker: Arc<Mutex<Option<Waker>>>
+    }
+
+    impl Future for Signal {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+            if self.is_ready.load(Ordering::SeqCst) {
+                Poll::Ready(())
+            } else {
+                *self.waker.lock().unwrap() = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_poll_woken_tasks() {
+        let executor = Executor::new();
+        let is_ready = Arc::new(AtomicBool::new(false));
+        let waker = Arc::new(Mutex::new(None));
+        let steps = Rc::new(Cell::new(0));
+
+        let signal = Signal { is_ready: is_ready.clone(), waker: waker.clone() };
+        let steps_clone = steps.clone();
+        executor.spawn(async move {
+            steps_clone.set(1);
+            signal.await;
+            steps_clone.set(2);
+        });
+        assert_eq!(steps.get(), 0);
+
+        executor.poll_woken_tasks();
+        assert_eq!(steps.get(), 1);
+        assert_eq!(executor.tasks.borrow().len(), 1);
+
+        // Without being woken, the task isn't polled again.
+        is_ready.store(true, Ordering::SeqCst);
+        executor.poll_woken_tasks();
+        assert_eq!(steps.get(), 1);
+
+        let waker = waker.lock().unwrap().take().unwrap();
+        std::thread::spawn(move || waker.wake()).join().unwrap();
+
+        executor.poll_woken_tasks();
+        assert_eq!(steps.get(), 2);
+        assert_eq!(executor.tasks.borrow().len(), 0);
+    }
+}
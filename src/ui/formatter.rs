@@ -0,0 +1,251 @@
+//! Formatters check what's typed into a `TextField` as it's typed, and tidy
+//! it up when editing ends, such as a number formatter only accepting
+//! digits and writing "1234.5" as "1,234.50". See `TextField::set_formatter`.
+
+use crate::ui::localization::{self, Date, Locale};
+use regex::Regex;
+
+/// Checks and tidies up the text of a `TextField`.
+pub trait Formatter {
+    /// Whether the text may be changed to `text` while it's edited. Text on
+    /// its way to being valid, such as "-" for a number, should be allowed.
+    fn is_partial_string_valid(&self, text: &str) -> bool;
+
+    /// The text to show for `text` once editing ends, or `None` if it isn't
+    /// valid, in which case it's left as typed.
+    fn display_string(&self, text: &str) -> Option<String>;
+
+    /// The text to edit for `text` as shown, when editing begins. The text
+    /// as shown, unless overridden.
+    fn editing_string(&self, text: &str) -> String {
+        text.to_string()
+    }
+}
+
+/// Accepts numbers written the way the locale writes them, and shows them
+/// with a set number of fraction digits and grouped digits.
+#[derive(Clone, Debug)]
+pub struct NumberFormatter {
+    pub locale: Locale,
+
+    /// How many digits may be typed after the decimal separator, and are
+    /// shown once editing ends.
+    pub fraction_digits: usize,
+
+    /// The smallest and largest numbers allowed. Numbers outside them are
+    /// clamped once editing ends, and negative numbers can't be typed at
+    /// all if the minimum isn't below zero.
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>
+}
+
+impl NumberFormatter {
+    /// A formatter for the application's locale, see `localization::locale`.
+    pub fn new(fraction_digits: usize) -> NumberFormatter {
+        NumberFormatter {
+            locale: localization::locale(),
+            fraction_digits,
+            minimum: None,
+            maximum: None
+        }
+    }
+
+    /// The number in `text`, if it's one.
+    pub fn number(&self, text: &str) -> Option<f64> {
+        self.locale.parse_number(text)
+    }
+
+    fn allows_negative_numbers(&self) -> bool {
+        self.minimum.is_none_or(|minimum| minimum < 0.0)
+    }
+}
+
+impl Formatter for NumberFormatter {
+    fn is_partial_string_valid(&self, text: &str) -> bool {
+        let decimal_separator = self.locale.decimal_separator();
+        let grouping_separator = self.locale.grouping_separator();
+
+        let text = match text.strip_prefix('-') {
+            Some(text) if self.allows_negative_numbers() => text,
+            Some(_) => return false,
+            None => text
+        };
+
+        let (whole, fraction) = match text.split_once(decimal_separator) {
+            Some(_) if self.fraction_digits == 0 => return false,
+            Some((whole, fraction)) => (whole, fraction),
+            None => (text, "")
+        };
+
+        // A space stands in for a no-break space, as in `Locale::parse_number`.
+        let is_grouping_separator = |character: char| {
+            character == grouping_separator || (grouping_separator == '\u{a0}' && character == ' ')
+        };
+
+        whole.chars().all(|character| character.is_ascii_digit() || is_grouping_separator(character))
+            && fraction.chars().all(|digit| digit.is_ascii_digit())
+            && fraction.len() <= self.fraction_digits
+    }
+
+    fn display_string(&self, text: &str) -> Option<String> {
+        if text.trim().is_empty() {
+            return Some(String::new());
+        }
+
+        let mut number = self.number(text)?;
+        if let Some(minimum) = self.minimum {
+            number = number.max(minimum);
+        }
+        if let Some(maximum) = self.maximum {
+            number = number.min(maximum);
+        }
+
+        Some(self.locale.format_number(number, self.fraction_digits))
+    }
+
+    fn editing_string(&self, text: &str) -> String {
+        let grouping_separator = self.locale.grouping_separator();
+        text.chars().filter(|character| *character != grouping_separator).collect()
+    }
+}
+
+/// Accepts dates written the way the locale writes them, such as
+/// "3/7/2024" in "en-US", and shows them as `Locale::format_date` does.
+#[derive(Clone, Debug)]
+pub struct DateFormatter {
+    pub locale: Locale
+}
+
+impl DateFormatter {
+    /// A formatter for the application's locale, see `localization::locale`.
+    pub fn new() -> DateFormatter {
+        DateFormatter { locale: localization::locale() }
+    }
+
+    /// The date in `text`, if it's one.
+    pub fn date(&self, text: &str) -> Option<Date> {
+        self.locale.parse_date(text)
+    }
+}
+
+impl Default for DateFormatter {
+    fn default() -> DateFormatter {
+        DateFormatter::new()
+    }
+}
+
+impl Formatter for DateFormatter {
+    fn is_partial_string_valid(&self, text: &str) -> bool {
+        self.locale.is_partial_date(text)
+    }
+
+    fn display_string(&self, text: &str) -> Option<String> {
+        if text.trim().is_empty() {
+            return Some(String::new());
+        }
+
+        self.date(text).map(|date| self.locale.format_date(&date))
+    }
+}
+
+/// Accepts text matching a regular expression. The whole text has to match
+/// as it's typed, so the expression has to match partial input too, such as
+/// `[0-9]{0,5}` for a ZIP code. A stricter pattern for the text once
+/// editing ends can be set with `with_complete_pattern`.
+#[derive(Clone, Debug)]
+pub struct RegexFormatter {
+    partial: Regex,
+    complete: Option<Regex>
+}
+
+impl RegexFormatter {
+    /// A formatter accepting text that `pattern` matches all of. Returns an
+    /// error if `pattern` isn't a valid regular expression.
+    pub fn new(pattern: &str) -> Result<RegexFormatter, String> {
+        Ok(RegexFormatter {
+            partial: whole_text_regex(pattern)?,
+            complete: None
+        })
+    }
+
+    /// Also requires the text to match all of `pattern` once editing ends,
+    /// such as `[0-9]{5}` for a whole ZIP code.
+    pub fn with_complete_pattern(self, pattern: &str) -> Result<RegexFormatter, String> {
+        Ok(RegexFormatter {
+            complete: Some(whole_text_regex(pattern)?),
+            ..self
+        })
+    }
+}
+
+impl Formatter for RegexFormatter {
+    fn is_partial_string_valid(&self, text: &str) -> bool {
+        self.partial.is_match(text)
+    }
+
+    fn display_string(&self, text: &str) -> Option<String> {
+        let regex = self.complete.as_ref().unwrap_or(&self.partial);
+        if regex.is_match(text) {
+            Some(text.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// `pattern` anchored to match the whole of a text.
+fn whole_text_regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(&format!("^(?:{})$", pattern)).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_formatter() {
+        let mut formatter = NumberFormatter { locale: Locale::new("de-DE"), fraction_digits: 2, minimum: None, maximum: None };
+
+        assert!(formatter.is_partial_string_valid(""));
+        assert!(formatter.is_partial_string_valid("-"));
+        assert!(formatter.is_partial_string_valid("1.234,5"));
+        assert!(formatter.is_partial_string_valid("12,"));
+        assert!(!formatter.is_partial_string_valid("12,345"));
+        assert!(!formatter.is_partial_string_valid("1a"));
+
+        assert_eq!(formatter.display_string("1234,5").as_deref(), Some("1.234,50"));
+        assert_eq!(formatter.display_string("").as_deref(), Some(""));
+        assert_eq!(formatter.display_string("-"), None);
+        assert_eq!(formatter.editing_string("1.234,50"), "1234,50");
+
+        formatter.minimum = Some(0.0);
+        formatter.maximum = Some(100.0);
+        assert!(!formatter.is_partial_string_valid("-1"));
+        assert_eq!(formatter.display_string("250").as_deref(), Some("100,00"));
+
+        formatter.fraction_digits = 0;
+        assert!(!formatter.is_partial_string_valid("1,"));
+    }
+
+    #[test]
+    fn test_date_formatter() {
+        let formatter = DateFormatter { locale: Locale::new("en-US") };
+        assert!(formatter.is_partial_string_valid("3/0"));
+        assert!(!formatter.is_partial_string_valid("3-7"));
+        assert_eq!(formatter.display_string("03/07/2024").as_deref(), Some("3/7/2024"));
+        assert_eq!(formatter.display_string("2/30/2024"), None);
+        assert_eq!(formatter.date("3/7/2024"), Date::new(2024, 3, 7));
+    }
+
+    #[test]
+    fn test_regex_formatter() {
+        let formatter = RegexFormatter::new("[0-9]{0,5}").unwrap().with_complete_pattern("[0-9]{5}").unwrap();
+        assert!(formatter.is_partial_string_valid("123"));
+        assert!(!formatter.is_partial_string_valid("123456"));
+        assert!(!formatter.is_partial_string_valid("12a"));
+        assert_eq!(formatter.display_string("123"), None);
+        assert_eq!(formatter.display_string("12345").as_deref(), Some("12345"));
+
+        assert!(RegexFormatter::new("(").is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use crate::ui::{View, WeakView};
+use crate::graphics::Point;
+use std::cell::RefCell;
+use crate::ui::gesture;
+use crate::ui::gesture::recognizer::{GestureState, Recognizer, RecognizerCore};
+use crate::ui::Touch;
+use crate::ui::timer::{Timer, TimerHandle};
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+use crate::ui::event::TouchEvent;
+
+/// Recognizes a touch held in place for a while.
+///
+/// The gesture is continuous: the action is called with `Began` once the
+/// touch has been held for the minimum duration, with `Changed` as it moves
+/// afterwards, and with `Ended` when it's lifted. Moving too far, or lifting
+/// the touch, before then makes the recognizer fail.
+///
+/// With a minimum duration of zero, and the movement allowed unlimited, it
+/// recognizes a touch pressing a control, from the moment it touches down.
+pub struct LongPressRecognizer {
+    inner: Rc<RefCell<LongPressRecognizerInner>>
+}
+
+struct LongPressRecognizerInner {
+    core: RecognizerCore,
+    action: Rc<dyn Fn(&LongPressRecognizer)>,
+    should_begin: Option<ShouldBeginCallback>,
+    minimum_duration: Duration,
+    allowable_movement: i32,
+    cancels_touches_in_view: bool,
+    initial_position: Point<i32>,
+    location: Point<i32>,
+
+    // The touch being held, and the timer recognizing it once it's been
+    // held long enough.
+    touch: Option<Touch>,
+    timer: Option<TimerHandle>
+}
+
+type ShouldBeginCallback = Rc<dyn Fn(&LongPressRecognizer) -> bool>;
+
+/// How far a touch can move before being recognized and still be a long
+/// press, unless set otherwise.
+const LONG_PRESS_DISTANCE: i32 = 10;
+
+impl LongPressRecognizer {
+    pub fn new(action: impl Fn(&LongPressRecognizer) + 'static) -> LongPressRecognizer {
+        let recognizer = LongPressRecognizer {
+            inner: Rc::new(RefCell::new(LongPressRecognizerInner {
+                core: RecognizerCore::new(),
+                action: Rc::new(action),
+                should_begin: None,
+                minimum_duration: Duration::from_millis(500),
+                allowable_movement: LONG_PRESS_DISTANCE,
+                cancels_touches_in_view: true,
+                initial_position: Point::new(0, 0),
+                location: Point::new(0, 0),
+                touch: None,
+                timer: None
+            }))
+        };
+
+        let weak_inner = Rc::downgrade(&recognizer.inner);
+        recognizer.core().set_action_sender(move || {
+            if let Some(inner) = weak_inner.upgrade() {
+                let action = inner.borrow().action.clone();
+                action(&LongPressRecognizer { inner });
+            }
+        });
+
+        recognizer
+    }
+
+    pub fn minimum_duration(&self) -> Duration {
+        self.inner.borrow().minimum_duration
+    }
+
+    /// Sets how long the touch has to be held. Default to half a second.
+    /// With zero, the gesture is recognized as soon as the touch begins.
+    pub fn set_minimum_duration(&self, minimum_duration: Duration) {
+        self.inner.borrow_mut().minimum_duration = minimum_duration;
+    }
+
+    pub fn allowable_movement(&self) -> i32 {
+        self.inner.borrow().allowable_movement
+    }
+
+    /// Sets how far, in points each way, the touch can move before it's
+    /// been held long enough. Default to 10.
+    pub fn set_allowable_movement(&self, allowable_movement: i32) {
+        self.inner.borrow_mut().allowable_movement = allowable_movement;
+    }
+
+    /// Sets whether recognizing the gesture takes the touch from the view,
+    /// see `Recognizer::cancels_touches_in_view`. Default to `true`.
+    pub fn set_cancels_touches_in_view(&self, cancels_touches_in_view: bool) {
+        self.inner.borrow_mut().cancels_touches_in_view = cancels_touches_in_view;
+    }
+
+    /// Sets what decides, once the touch has been held long enough, whether
+    /// it's the gesture after all, such as whether there's a context menu
+    /// where it's held. When it returns `false`, the recognizer fails
+    /// instead, leaving the touch to the others.
+    pub fn set_should_begin(&self, should_begin: impl Fn(&LongPressRecognizer) -> bool + 'static) {
+        self.inner.borrow_mut().should_begin = Some(Rc::new(should_begin));
+    }
+
+    /// Where the touch is, in the window.
+    pub fn location_in(&self, _view: &View) -> Point<i32> {
+        self.inner.borrow().location.clone()
+    }
+
+    pub fn state(&self) -> GestureState {
+        self.core().state()
+    }
+
+    pub fn view(&self) -> WeakView {
+        self.core().view()
+    }
+
+    /// The state machine of the recognizer. A long press is often let
+    /// recognize alongside a pan, with
+    /// `RecognizerCore::set_recognizes_simultaneously`, so a touch held down
+    /// can go on to drag what it picked up.
+    pub fn core(&self) -> RecognizerCore {
+        self.inner.borrow().core.clone()
+    }
+
+    /// The touch has been held long enough. The other recognizers of the
+    /// touch are resolved here, as no touch event follows to do it.
+    fn minimum_duration_did_pass(&self) {
+        let core = self.core();
+        if core.state() != GestureState::Possible {
+            return;
+        }
+
+        self.begin();
+
+        let touch = self.inner.borrow_mut().touch.take();
+        if let Some(touch) = touch.filter(|_| core.state().is_recognized()) {
+            gesture::resolve_touch(&touch);
+        }
+    }
+
+    /// Recognizes the gesture, unless it shouldn't begin.
+    fn begin(&self) {
+        let should_begin = self.inner.borrow().should_begin.clone();
+        if should_begin.is_some_and(|should_begin| !should_begin(self)) {
+            self.cancel_timer();
+            self.core().fail();
+        } else {
+            self.core().set_state(GestureState::Began);
+        }
+    }
+
+    fn cancel_timer(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.touch = None;
+        if let Some(timer) = inner.timer.take() {
+            timer.cancel();
+        }
+    }
+}
+
+impl Recognizer for LongPressRecognizer {
+    fn touches_began(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.cancel_timer();
+        self.core().reset();
+
+        let touch = touches.first().unwrap().clone();
+        let minimum_duration = {
+            let mut inner = self.inner.borrow_mut();
+            inner.initial_position = touch.position();
+            inner.location = inner.initial_position.clone();
+            inner.touch = Some(touch);
+            inner.minimum_duration
+        };
+
+        // Recognized straight away, the event loop resolves the other
+        // recognizers once they've been sent the touch too.
+        if minimum_duration.is_zero() {
+            self.inner.borrow_mut().touch = None;
+            self.begin();
+            return;
+        }
+
+        let weak_inner: Weak<RefCell<LongPressRecognizerInner>> = Rc::downgrade(&self.inner);
+        let timer = Timer::after(minimum_duration, move || {
+            if let Some(inner) = weak_inner.upgrade() {
+                LongPressRecognizer { inner }.minimum_duration_did_pass();
+            }
+        });
+        self.inner.borrow_mut().timer = Some(timer);
+    }
+
+    fn touches_ended(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.cancel_timer();
+        self.inner.borrow_mut().location = touches.first().unwrap().position();
+
+        let core = self.core();
+        match core.state() {
+            GestureState::Began | GestureState::Changed => core.set_state(GestureState::Ended),
+            GestureState::Possible => core.fail(),
+            _ => ()
+        }
+    }
+
+    fn touches_moved(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        let position = touches.first().unwrap().position();
+        let (initial_position, allowable_movement) = {
+            let mut inner = self.inner.borrow_mut();
+            inner.location = position.clone();
+            (inner.initial_position.clone(), inner.allowable_movement)
+        };
+
+        let core = self.core();
+        match core.state() {
+            GestureState::Possible if (position.x - initial_position.x).abs() > allowable_movement ||
+                (position.y - initial_position.y).abs() > allowable_movement => {
+                self.cancel_timer();
+                core.fail();
+            },
+            GestureState::Began | GestureState::Changed => core.set_state(GestureState::Changed),
+            _ => ()
+        }
+    }
+
+    fn touches_cancelled(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.cancel_timer();
+
+        let core = self.core();
+        match core.state() {
+            GestureState::Began | GestureState::Changed => core.set_state(GestureState::Cancelled),
+            _ => core.fail()
+        }
+    }
+
+    fn cancels_touches_in_view(&self) -> bool {
+        self.inner.borrow().cancels_touches_in_view
+    }
+
+    fn set_view(&self, view: WeakView) {
+        self.core().set_view(view);
+    }
+
+    fn state(&self) -> GestureState {
+        self.core().state()
+    }
+
+    fn fail(&self) {
+        if self.core().state() == GestureState::Possible {
+            self.cancel_timer();
+        }
+        self.core().fail();
+    }
+
+    fn recognizes_simultaneously_with(&self, _other: &dyn Recognizer) -> bool {
+        self.core().recognizes_simultaneously()
+    }
+}
+
+impl Clone for LongPressRecognizer {
+    fn clone(&self) -> LongPressRecognizer {
+        LongPressRecognizer {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_press() {
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let states_clone = states.clone();
+        let recognizer = LongPressRecognizer::new(move |long_press_recognizer| {
+            states_clone.borrow_mut().push(long_press_recognizer.state());
+        });
+
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        recognizer.minimum_duration_did_pass();
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(40, 10))], &event);
+        recognizer.touches_ended(&vec![Touch::new(0, Point::new(40, 10))], &event);
+        assert_eq!(*states.borrow(), vec![GestureState::Began, GestureState::Changed, GestureState::Ended]);
+    }
+
+    #[test]
+    fn test_released_early_fails() {
+        let recognizer = LongPressRecognizer::new(|_long_press_recognizer| panic!("not a long press"));
+
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        recognizer.touches_ended(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        assert_eq!(recognizer.state(), GestureState::Failed);
+
+        // The timer firing late doesn't recognize it after all.
+        recognizer.minimum_duration_did_pass();
+        assert_eq!(recognizer.state(), GestureState::Failed);
+    }
+
+    #[test]
+    fn test_should_begin() {
+        let recognizer = LongPressRecognizer::new(|_long_press_recognizer| panic!("shouldn't begin"));
+        recognizer.set_should_begin(|_long_press_recognizer| false);
+
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        recognizer.minimum_duration_did_pass();
+        assert_eq!(recognizer.state(), GestureState::Failed);
+    }
+
+    #[test]
+    fn test_pressed_without_delay() {
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let states_clone = states.clone();
+        let recognizer = LongPressRecognizer::new(move |long_press_recognizer| {
+            states_clone.borrow_mut().push(long_press_recognizer.state());
+        });
+        recognizer.set_minimum_duration(Duration::ZERO);
+        recognizer.set_allowable_movement(i32::MAX);
+
+        // Recognized as soon as it touches down, however far it moves.
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        assert_eq!(recognizer.state(), GestureState::Began);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(300, 10))], &event);
+        recognizer.touches_cancelled(&vec![Touch::new(0, Point::new(300, 10))], &event);
+        assert_eq!(*states.borrow(), vec![GestureState::Began, GestureState::Changed, GestureState::Cancelled]);
+    }
+}
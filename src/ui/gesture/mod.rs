@@ -1,2 +1,94 @@
+//! Gesture recognizers, turning touches into taps, long presses, pans,
+//! pinches and swipes.
+//!
+//! A recognizer is added to a view with `View::add_gesture_recognizer`. Each
+//! touch on the view, or on its subviews, is sent to the recognizers of the
+//! view touched and of its superviews, alongside the view itself. Once a
+//! recognizer recognizes its gesture, the others fail unless they recognize
+//! theirs simultaneously, and the view's touch is cancelled if the
+//! recognizer cancels touches in view. Recognizers that leave the touch to
+//! the view, such as a button's press, are cancelled along with it. A
+//! recognizer can also wait for another to fail first, see
+//! `RecognizerCore::require_to_fail`.
+
 pub mod recognizer;
 pub mod pan_recognizer;
+pub mod tap_recognizer;
+pub mod long_press_recognizer;
+pub mod pinch_recognizer;
+pub mod swipe_recognizer;
+
+pub use recognizer::{GestureState, Recognizer, RecognizerCore};
+pub use pan_recognizer::PanRecognizer;
+pub use tap_recognizer::TapRecognizer;
+pub use long_press_recognizer::LongPressRecognizer;
+pub use pinch_recognizer::PinchRecognizer;
+pub use swipe_recognizer::{SwipeDirection, SwipeRecognizer};
+
+use crate::ui::Touch;
+use crate::ui::event::{EventArena, TouchEvent};
+use std::rc::{Rc, Weak};
+
+/// Makes the recognizers of a touch fail when another has recognized its
+/// gesture, unless either recognizes simultaneously with the other. Called
+/// after the recognizers are sent each change of the touch.
+///
+/// Returns whether a recognizer that cancels touches in view has recognized
+/// its gesture, so the touch should be taken from the view.
+pub(crate) fn resolve(recognizers: &[Weak<Box<dyn Recognizer>>]) -> bool {
+    let recognizers: Vec<Rc<Box<dyn Recognizer>>> = recognizers.iter().filter_map(Weak::upgrade).collect();
+    let mut cancels_touches = false;
+
+    for recognizer in recognizers.iter() {
+        if !recognizer.state().is_recognized() {
+            continue;
+        }
+
+        cancels_touches |= recognizer.cancels_touches_in_view();
+
+        for other in recognizers.iter() {
+            if Rc::ptr_eq(recognizer, other) || other.state() != GestureState::Possible {
+                continue;
+            }
+
+            let is_simultaneous = recognizer.recognizes_simultaneously_with(other.as_ref().as_ref()) ||
+                other.recognizes_simultaneously_with(recognizer.as_ref().as_ref());
+            if !is_simultaneous {
+                other.fail();
+            }
+        }
+    }
+
+    cancels_touches
+}
+
+/// Like `resolve`, for a recognizer recognizing its gesture between touch
+/// events, such as a long press once it's been held long enough. The view's
+/// touch is cancelled here rather than by the event loop.
+pub(crate) fn resolve_touch(touch: &Touch) {
+    let recognizers = touch.gesture_recognizers().clone();
+    if !resolve(&recognizers) {
+        return;
+    }
+
+    let event = EventArena::borrow_mut().cancel_touch_in_view(touch);
+    if let Some(event) = event {
+        touch_cancelled_in_view(touch, &event);
+    }
+}
+
+/// Tells the view of `touch` it was taken away, with `event`. Recognizers
+/// that have recognized their gesture but leave the touch to the view, such
+/// as a button's press, are cancelled along with it.
+pub(crate) fn touch_cancelled_in_view(touch: &Touch, event: &TouchEvent) {
+    let recognizers = touch.gesture_recognizers().clone();
+    for recognizer in recognizers.iter().filter_map(Weak::upgrade) {
+        if recognizer.state().is_recognized() && !recognizer.cancels_touches_in_view() {
+            recognizer.touches_cancelled(&event.touches(), event);
+        }
+    }
+
+    if let Some(view) = touch.view() {
+        view.touches_cancelled(&event.touches(), event);
+    }
+}
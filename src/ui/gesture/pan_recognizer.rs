@@ -1,46 +1,52 @@
 use crate::ui::{View, WeakView};
 use crate::graphics::Point;
 use std::cell::RefCell;
-use crate::ui::gesture::recognizer::Recognizer;
+use crate::ui::gesture::recognizer::{GestureState, Recognizer, RecognizerCore};
 use crate::ui::Touch;
 use std::rc::Rc;
-use crate::ui::event::{TouchEvent, ScrollEvent};
-
-#[derive(PartialEq, Eq, Hash, Debug, Clone)]
-pub enum PanState {
-    Possible,
-    Began,
-    Changed,
-    Ended,
-    Cancelled,
-    Failed
-}
+use crate::ui::event::TouchEvent;
+
+/// The states of a `PanRecognizer`, which are those of any recognizer.
+pub type PanState = GestureState;
 
 pub struct PanRecognizer {
     inner: Rc<RefCell<PanRecognizerInner>>
 }
 
 struct PanRecognizerInner {
-    view: WeakView,
-    state: PanState,
+    core: RecognizerCore,
     action: Rc<Box<dyn Fn(&PanRecognizer) -> ()>>,
     translation: Point<i32>,
+    velocity: Point<f32>,
     initial_position: Point<i32>,
     last_position: Point<i32>
 }
 
+/// How far a touch moves before it's a pan.
+const PAN_DISTANCE: i32 = 10;
+
 impl PanRecognizer {
     pub fn new(action: impl Fn(&PanRecognizer) -> () + 'static) -> PanRecognizer {
-        PanRecognizer {
+        let recognizer = PanRecognizer {
             inner: Rc::new(RefCell::new(PanRecognizerInner {
-                view: WeakView::none(),
-                state: PanState::Possible,
+                core: RecognizerCore::new(),
                 action: Rc::new(Box::new(action)),
                 translation: Point::new(0, 0),
+                velocity: Point::new(0.0, 0.0),
                 initial_position: Point::new(0, 0),
                 last_position: Point::new(0, 0)
             }))
-        }
+        };
+
+        let weak_inner = Rc::downgrade(&recognizer.inner);
+        recognizer.core().set_action_sender(move || {
+            if let Some(inner) = weak_inner.upgrade() {
+                let action = inner.borrow().action.clone();
+                action(&PanRecognizer { inner });
+            }
+        });
+
+        recognizer
     }
 
     pub fn translation_in(&self, _view: &View) -> Point<i32> {
@@ -55,36 +61,57 @@ impl PanRecognizer {
         inner.initial_position = inner.last_position.clone();
     }
 
+    /// How fast the touch is panning, in points per second, such as to
+    /// keep a dragged view moving once it's let go.
+    pub fn velocity_in(&self, _view: &View) -> Point<f32> {
+        self.inner.borrow().velocity.clone()
+    }
+
     pub fn state(&self) -> PanState {
-        self.inner.borrow().state.clone()
+        self.core().state()
     }
 
     pub fn view(&self) -> WeakView {
-        self.inner.borrow().view.clone()
+        self.core().view()
+    }
+
+    /// The state machine of the recognizer. A pan can wait for a swipe of
+    /// the same touch to fail with `RecognizerCore::require_to_fail`, so a
+    /// quick flick isn't also a short drag.
+    pub fn core(&self) -> RecognizerCore {
+        self.inner.borrow().core.clone()
     }
 }
 
 impl Recognizer for PanRecognizer {
     fn touches_began(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
-        let mut inner = self.inner.borrow_mut();
-
-        inner.state = PanState::Possible;
+        self.core().reset();
 
+        let mut inner = self.inner.borrow_mut();
         inner.last_position = touches.first().unwrap().position().clone();
         inner.initial_position = inner.last_position.clone();
         inner.translation = Point::new(0, 0);
+        inner.velocity = Point::new(0.0, 0.0);
     }
 
     fn touches_ended(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {
-        let mut inner = self.inner.borrow_mut();
-        inner.state = PanState::Ended;
+        let core = self.core();
+
+        // Only a pan that began is told it ended, so a tap doesn't look
+        // like the end of a pan.
+        match core.state() {
+            PanState::Began | PanState::Changed => core.set_state(PanState::Ended),
+            PanState::Possible => core.fail(),
+            _ => ()
+        }
     }
 
     fn touches_moved(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
-        let action: Rc<Box<dyn Fn(&PanRecognizer) -> ()>>;
         {
-            let touch_position = touches.first().unwrap().position();
+            let touch = touches.first().unwrap();
+            let touch_position = touch.position();
             let mut inner = self.inner.borrow_mut();
+            inner.velocity = touch.velocity();
 
             inner.translation = Point::new(
                 touch_position.x - inner.initial_position.x,
@@ -92,58 +119,43 @@ impl Recognizer for PanRecognizer {
             );
 
             inner.last_position = touch_position;
+        }
 
-            match inner.state {
-                PanState::Possible => {
-                    if inner.translation.x.abs() > 10 || inner.translation.y.abs() > 10 {
-                        inner.state = PanState::Began;
-                    } else {
-                        return;
-                    }
-                },
-                PanState::Began => { inner.state = PanState::Changed },
-                PanState::Changed => {},
-                _ => {
-                    return;
+        let core = self.core();
+        match core.state() {
+            PanState::Possible => {
+                let translation = self.inner.borrow().translation.clone();
+                if translation.x.abs() > PAN_DISTANCE || translation.y.abs() > PAN_DISTANCE {
+                    core.set_state(PanState::Began);
                 }
-            }
-
-            action = inner.action.clone();
+            },
+            PanState::Began | PanState::Changed => core.set_state(PanState::Changed),
+            _ => ()
         }
-
-        action(self);
     }
 
-    fn scroll_did_translate(&self, translation: &Point<i32>, _: &ScrollEvent) {
-        let render_scale: f32;
-        {
-            if let Some(view) = self.view().upgrade() {
-                if let Some(layer) = view.layer() {
-                    render_scale = layer.context().render_scale();
-                } else {
-                    return;
-                }
-            } else {
-                return;
-            }
+    fn touches_cancelled(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {
+        let core = self.core();
+        match core.state() {
+            PanState::Began | PanState::Changed => core.set_state(PanState::Cancelled),
+            _ => core.fail()
         }
+    }
 
-        let action: Rc<Box<dyn Fn(&PanRecognizer) -> ()>>;
-        {
-            let mut inner = self.inner.borrow_mut();
-            inner.translation = Point::new(
-                -(translation.x as f32 * render_scale).round() as i32,
-                (translation.y as f32 * render_scale).round() as i32
-            );
+    fn set_view(&self, view: WeakView) {
+        self.core().set_view(view);
+    }
 
-            action = inner.action.clone();
-        }
-        action(self);
+    fn state(&self) -> GestureState {
+        self.core().state()
     }
 
-    fn set_view(&self, view: WeakView) {
-        let mut inner = self.inner.borrow_mut();
-        inner.view = view;
+    fn fail(&self) {
+        self.core().fail();
+    }
+
+    fn recognizes_simultaneously_with(&self, _other: &dyn Recognizer) -> bool {
+        self.core().recognizes_simultaneously()
     }
 }
 
@@ -225,6 +237,26 @@ mod tests {
         assert_eq!(recognizer.state(), PanState::Possible);
     }
 
+    #[test]
+    fn test_touches_ended_calls_action_once_panning() {
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let states_clone = states.clone();
+        let recognizer = PanRecognizer::new(move |pan_recognizer| {
+            states_clone.borrow_mut().push(pan_recognizer.state());
+        });
+
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        recognizer.touches_ended(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        assert!(states.borrow().is_empty());
+
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(30, 10))], &event);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(40, 10))], &event);
+        recognizer.touches_ended(&vec![Touch::new(0, Point::new(40, 10))], &event);
+        assert_eq!(*states.borrow(), vec![PanState::Began, PanState::Changed, PanState::Ended]);
+    }
+
     #[test]
     fn test_touches_began_when_previously_failed() {
         //TODO: assert_eq!(true, false)
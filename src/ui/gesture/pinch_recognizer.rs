@@ -0,0 +1,237 @@
+use crate::ui::{View, WeakView};
+use crate::graphics::Point;
+use std::cell::RefCell;
+use crate::ui::gesture::recognizer::{GestureState, Recognizer, RecognizerCore};
+use crate::ui::Touch;
+use std::rc::Rc;
+use crate::ui::event::TouchEvent;
+
+/// Recognizes two touches moving towards or away from each other.
+///
+/// The gesture is continuous: the action is called with `Began` once the
+/// distance between the touches has changed enough, with `Changed` as they
+/// keep moving, and with `Ended` when either is lifted. The scale is the
+/// distance between the touches relative to when the second touch began.
+///
+/// Pinching needs two touches, which a mouse doesn't give; only the first
+/// two touches of the event are looked at.
+pub struct PinchRecognizer {
+    inner: Rc<RefCell<PinchRecognizerInner>>
+}
+
+struct PinchRecognizerInner {
+    core: RecognizerCore,
+    action: Rc<dyn Fn(&PinchRecognizer)>,
+    initial_distance: Option<f32>,
+    scale: f32,
+    location: Point<i32>
+}
+
+/// How much the scale changes before it's a pinch.
+const PINCH_SCALE_THRESHOLD: f32 = 0.05;
+
+impl PinchRecognizer {
+    pub fn new(action: impl Fn(&PinchRecognizer) + 'static) -> PinchRecognizer {
+        let recognizer = PinchRecognizer {
+            inner: Rc::new(RefCell::new(PinchRecognizerInner {
+                core: RecognizerCore::new(),
+                action: Rc::new(action),
+                initial_distance: None,
+                scale: 1.0,
+                location: Point::new(0, 0)
+            }))
+        };
+
+        let weak_inner = Rc::downgrade(&recognizer.inner);
+        recognizer.core().set_action_sender(move || {
+            if let Some(inner) = weak_inner.upgrade() {
+                let action = inner.borrow().action.clone();
+                action(&PinchRecognizer { inner });
+            }
+        });
+
+        recognizer
+    }
+
+    /// The distance between the touches, relative to when the pinch
+    /// started. Less than 1 when pinching in, more than 1 when pinching out.
+    pub fn scale(&self) -> f32 {
+        self.inner.borrow().scale
+    }
+
+    /// Sets the scale, such as back to 1 after applying it, so the next
+    /// change is relative to the current distance between the touches.
+    pub fn set_scale(&self, scale: f32) {
+        let mut inner = self.inner.borrow_mut();
+        if let Some(initial_distance) = inner.initial_distance {
+            let distance = initial_distance * inner.scale;
+            inner.initial_distance = Some(distance / scale);
+        }
+        inner.scale = scale;
+    }
+
+    /// The point halfway between the touches, in the window.
+    pub fn location_in(&self, _view: &View) -> Point<i32> {
+        self.inner.borrow().location.clone()
+    }
+
+    pub fn state(&self) -> GestureState {
+        self.core().state()
+    }
+
+    pub fn view(&self) -> WeakView {
+        self.core().view()
+    }
+
+    /// The state machine of the recognizer. Pinching usually zooms while a
+    /// pan of the same touches moves the content, which needs one of them to
+    /// recognize simultaneously, see
+    /// `RecognizerCore::set_recognizes_simultaneously`.
+    pub fn core(&self) -> RecognizerCore {
+        self.inner.borrow().core.clone()
+    }
+
+    /// The distance between the first two touches, and the point halfway
+    /// between them.
+    fn measure(touches: &[Touch]) -> Option<(f32, Point<i32>)> {
+        if touches.len() < 2 {
+            return None;
+        }
+
+        let first = touches[0].position();
+        let second = touches[1].position();
+        let dx = (second.x - first.x) as f32;
+        let dy = (second.y - first.y) as f32;
+        let location = Point::new((first.x + second.x) / 2, (first.y + second.y) / 2);
+        Some(((dx * dx + dy * dy).sqrt(), location))
+    }
+}
+
+impl Recognizer for PinchRecognizer {
+    fn touches_began(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        let core = self.core();
+        let measurement = PinchRecognizer::measure(touches);
+
+        // The first touch starts over; the second sets the distance the
+        // scale is relative to.
+        if measurement.is_none() {
+            core.reset();
+        }
+
+        let mut inner = self.inner.borrow_mut();
+        inner.scale = 1.0;
+        inner.initial_distance = measurement.as_ref().map(|(distance, _)| *distance).filter(|distance| *distance > 0.0);
+        if let Some((_, location)) = measurement {
+            inner.location = location;
+        }
+    }
+
+    fn touches_ended(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.inner.borrow_mut().initial_distance = None;
+
+        let core = self.core();
+        match core.state() {
+            GestureState::Began | GestureState::Changed => core.set_state(GestureState::Ended),
+            GestureState::Possible => core.fail(),
+            _ => ()
+        }
+    }
+
+    fn touches_moved(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        let scale = {
+            let mut inner = self.inner.borrow_mut();
+            let (initial_distance, (distance, location)) = match (inner.initial_distance, PinchRecognizer::measure(touches)) {
+                (Some(initial_distance), Some(measurement)) => (initial_distance, measurement),
+                _ => return
+            };
+
+            inner.scale = distance / initial_distance;
+            inner.location = location;
+            inner.scale
+        };
+
+        let core = self.core();
+        match core.state() {
+            GestureState::Possible if (scale - 1.0).abs() > PINCH_SCALE_THRESHOLD => core.set_state(GestureState::Began),
+            GestureState::Began | GestureState::Changed => core.set_state(GestureState::Changed),
+            _ => ()
+        }
+    }
+
+    fn touches_cancelled(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.inner.borrow_mut().initial_distance = None;
+
+        let core = self.core();
+        match core.state() {
+            GestureState::Began | GestureState::Changed => core.set_state(GestureState::Cancelled),
+            _ => core.fail()
+        }
+    }
+
+    fn set_view(&self, view: WeakView) {
+        self.core().set_view(view);
+    }
+
+    fn state(&self) -> GestureState {
+        self.core().state()
+    }
+
+    fn fail(&self) {
+        self.core().fail();
+    }
+
+    fn recognizes_simultaneously_with(&self, _other: &dyn Recognizer) -> bool {
+        self.core().recognizes_simultaneously()
+    }
+}
+
+impl Clone for PinchRecognizer {
+    fn clone(&self) -> PinchRecognizer {
+        PinchRecognizer {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Rectangle;
+
+    #[test]
+    fn test_pinch_out() {
+        let scales = Rc::new(RefCell::new(Vec::new()));
+        let scales_clone = scales.clone();
+        let recognizer = PinchRecognizer::new(move |pinch_recognizer| {
+            scales_clone.borrow_mut().push((pinch_recognizer.state(), pinch_recognizer.scale()));
+        });
+
+        let event = TouchEvent::new();
+        let first = Touch::new(0, Point::new(0, 0));
+        let second = Touch::new(1, Point::new(100, 0));
+        recognizer.touches_began(&vec![first.clone()], &event);
+        recognizer.touches_began(&vec![first.clone(), second.clone()], &event);
+
+        second.set_position(Point::new(102, 0));
+        recognizer.touches_moved(&vec![first.clone(), second.clone()], &event);
+        assert_eq!(recognizer.state(), GestureState::Possible);
+
+        second.set_position(Point::new(200, 0));
+        recognizer.touches_moved(&vec![first.clone(), second.clone()], &event);
+        assert_eq!(recognizer.location_in(&View::new(Rectangle::new(0, 0, 100, 100))), Point::new(100, 0));
+        recognizer.touches_ended(&vec![first, second], &event);
+
+        assert_eq!(*scales.borrow(), vec![(GestureState::Began, 2.0), (GestureState::Ended, 2.0)]);
+    }
+
+    #[test]
+    fn test_single_touch_fails() {
+        let recognizer = PinchRecognizer::new(|_pinch_recognizer| panic!("not a pinch"));
+
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(80, 10))], &event);
+        recognizer.touches_ended(&vec![Touch::new(0, Point::new(80, 10))], &event);
+        assert_eq!(recognizer.state(), GestureState::Failed);
+    }
+}
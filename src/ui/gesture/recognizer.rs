@@ -1,14 +1,42 @@
 use crate::ui::Touch;
 use crate::ui::WeakView;
-use crate::ui::event::{TouchEvent, ScrollEvent};
-use crate::graphics::Point;
+use crate::ui::event::TouchEvent;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// Where a recognizer is in recognizing its gesture.
+///
+/// Every recognizer starts out `Possible`. A discrete gesture, such as a tap,
+/// goes straight to `Ended` once recognized. A continuous one, such as a pan,
+/// goes through `Began` and `Changed` as the touch moves, then `Ended`, or
+/// `Cancelled` if the touch is taken away. A recognizer that decides the
+/// touches aren't its gesture goes to `Failed`, and ignores them from then on.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum GestureState {
+    Possible,
+    Began,
+    Changed,
+    Ended,
+    Cancelled,
+    Failed
+}
+
+impl GestureState {
+    /// Whether the gesture has been recognized, and is under way or has just
+    /// ended.
+    pub fn is_recognized(&self) -> bool {
+        matches!(self, GestureState::Began | GestureState::Changed | GestureState::Ended)
+    }
+}
 
 pub trait Recognizer {
     fn touches_began(&self, touches: &Vec<Touch>, event: &TouchEvent);
     fn touches_ended(&self, touches: &Vec<Touch>, event: &TouchEvent);
     fn touches_moved(&self, touches: &Vec<Touch>, event: &TouchEvent);
 
-    fn scroll_did_translate(&self, _translation: &Point<i32>, _event: &ScrollEvent) {}
+    /// The touches were taken away, such as by a long press showing a
+    /// context menu. A gesture under way is cancelled.
+    fn touches_cancelled(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {}
 
     /// If `true`, the recognizer can cancel touches sent to the view if it
     /// recognizes the touch is its gesture.
@@ -25,4 +53,255 @@ pub trait Recognizer {
 
     /// This should only be called by `View.add_gesture_recognizer`.
     fn set_view(&self, view: WeakView);
+
+    fn state(&self) -> GestureState;
+
+    /// Makes the recognizer fail, if it hasn't recognized its gesture yet,
+    /// such as when another recognizer of the same touches recognized its
+    /// own first.
+    fn fail(&self);
+
+    /// Whether the recognizer can recognize its gesture at the same time as
+    /// `other` does. If neither can, whichever recognizes its gesture first
+    /// makes the other fail.
+    ///
+    /// Default to `false`.
+    fn recognizes_simultaneously_with(&self, _other: &dyn Recognizer) -> bool {
+        false
+    }
+}
+
+/// The state machine shared by the built-in recognizers, which custom
+/// recognizers can use too.
+///
+/// The recognizer moves the core between states with `set_state` as it
+/// watches the touches, and the core calls the action set with
+/// `set_action_sender` whenever the gesture is recognized, changes, ends,
+/// or is cancelled. When the recognizer requires others to fail first, see
+/// `require_to_fail`, recognizing the gesture is held back until they do.
+#[derive(Clone)]
+pub struct RecognizerCore {
+    inner: Rc<RefCell<RecognizerCoreInner>>
+}
+
+struct RecognizerCoreInner {
+    view: WeakView,
+    state: GestureState,
+
+    /// The state reached while waiting for `required_failures` to fail.
+    pending_state: Option<GestureState>,
+
+    /// Recognizers that must fail before this one can recognize its
+    /// gesture, and the ones waiting for this one to fail.
+    required_failures: Vec<Weak<RefCell<RecognizerCoreInner>>>,
+    dependents: Vec<Weak<RefCell<RecognizerCoreInner>>>,
+
+    recognizes_simultaneously: bool,
+    action_sender: Option<Rc<dyn Fn()>>
+}
+
+/// How the recognizers a core requires to fail are getting on.
+#[derive(PartialEq, Debug)]
+enum Requirement {
+    Waiting,
+    Failed,
+    Recognized
+}
+
+impl RecognizerCore {
+    pub fn new() -> RecognizerCore {
+        RecognizerCore {
+            inner: Rc::new(RefCell::new(RecognizerCoreInner {
+                view: WeakView::none(),
+                state: GestureState::Possible,
+                pending_state: None,
+                required_failures: Vec::new(),
+                dependents: Vec::new(),
+                recognizes_simultaneously: false,
+                action_sender: None
+            }))
+        }
+    }
+
+    pub fn state(&self) -> GestureState {
+        self.inner.borrow().state
+    }
+
+    pub fn view(&self) -> WeakView {
+        self.inner.borrow().view.clone()
+    }
+
+    pub fn set_view(&self, view: WeakView) {
+        self.inner.borrow_mut().view = view;
+    }
+
+    /// Sets what's called each time the state changes to tell about the
+    /// gesture. Usually calls the recognizer's action with the recognizer.
+    pub fn set_action_sender(&self, action_sender: impl Fn() + 'static) {
+        self.inner.borrow_mut().action_sender = Some(Rc::new(action_sender));
+    }
+
+    /// Whether the recognizer can recognize its gesture at the same time as
+    /// any other. See `Recognizer::recognizes_simultaneously_with`.
+    pub fn recognizes_simultaneously(&self) -> bool {
+        self.inner.borrow().recognizes_simultaneously
+    }
+
+    pub fn set_recognizes_simultaneously(&self, recognizes_simultaneously: bool) {
+        self.inner.borrow_mut().recognizes_simultaneously = recognizes_simultaneously;
+    }
+
+    /// Holds back recognizing this gesture until `other` fails. If `other`
+    /// recognizes its gesture instead, this one fails. Such as a single tap
+    /// waiting to be sure it isn't a double tap.
+    pub fn require_to_fail(&self, other: &RecognizerCore) {
+        self.inner.borrow_mut().required_failures.push(Rc::downgrade(&other.inner));
+        other.inner.borrow_mut().dependents.push(Rc::downgrade(&self.inner));
+    }
+
+    /// Starts over, ready for a new gesture.
+    pub fn reset(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.state = GestureState::Possible;
+        inner.pending_state = None;
+    }
+
+    /// Moves to `state`, telling the action about it. Recognizing the
+    /// gesture waits for the recognizers required to fail.
+    pub fn set_state(&self, state: GestureState) {
+        let is_waiting = self.inner.borrow().pending_state.is_some();
+        if state.is_recognized() && (is_waiting || !self.state().is_recognized()) {
+            match self.requirement() {
+                Requirement::Waiting => {
+                    self.inner.borrow_mut().pending_state = Some(state);
+                    return;
+                },
+                Requirement::Recognized => {
+                    self.finish(GestureState::Failed);
+                    return;
+                },
+                Requirement::Failed => ()
+            }
+        }
+
+        self.finish(state);
+    }
+
+    /// Fails, unless the gesture has already been recognized.
+    pub fn fail(&self) {
+        if self.state() == GestureState::Possible {
+            self.finish(GestureState::Failed);
+        }
+    }
+
+    fn requirement(&self) -> Requirement {
+        let required_failures: Vec<_> = self.inner.borrow().required_failures.iter().filter_map(Weak::upgrade).collect();
+        let mut requirement = Requirement::Failed;
+
+        for required in required_failures {
+            let state = required.borrow().state;
+            if state.is_recognized() {
+                return Requirement::Recognized;
+            } else if state == GestureState::Possible {
+                requirement = Requirement::Waiting;
+            }
+        }
+
+        requirement
+    }
+
+    fn finish(&self, state: GestureState) {
+        let (was_recognized, action_sender, dependents) = {
+            let mut inner = self.inner.borrow_mut();
+            let was_recognized = inner.state.is_recognized();
+            inner.state = state;
+            inner.pending_state = None;
+            (was_recognized, inner.action_sender.clone(), inner.dependents.clone())
+        };
+
+        let tells_action = state.is_recognized() || (state == GestureState::Cancelled && was_recognized);
+        if let Some(action_sender) = action_sender.filter(|_| tells_action) {
+            action_sender();
+        }
+
+        // Recognizers waiting on this one can go ahead, or fail, once it's
+        // decided.
+        if state == GestureState::Failed || (state.is_recognized() && !was_recognized) {
+            for dependent in dependents.iter().filter_map(Weak::upgrade) {
+                RecognizerCore { inner: dependent }.required_did_change();
+            }
+        }
+    }
+
+    fn required_did_change(&self) {
+        let pending_state = match self.inner.borrow().pending_state {
+            Some(pending_state) => pending_state,
+            None => return
+        };
+
+        match self.requirement() {
+            Requirement::Waiting => (),
+            Requirement::Failed => self.finish(pending_state),
+            Requirement::Recognized => self.finish(GestureState::Failed)
+        }
+    }
+}
+
+impl Default for RecognizerCore {
+    fn default() -> RecognizerCore {
+        RecognizerCore::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_require_to_fail() {
+        let single_tap = RecognizerCore::new();
+        let double_tap = RecognizerCore::new();
+        single_tap.require_to_fail(&double_tap);
+
+        let sent = Rc::new(Cell::new(0));
+        let sent_clone = sent.clone();
+        single_tap.set_action_sender(move || sent_clone.set(sent_clone.get() + 1));
+
+        // The single tap waits until the double tap fails.
+        single_tap.set_state(GestureState::Ended);
+        assert_eq!(single_tap.state(), GestureState::Possible);
+        assert_eq!(sent.get(), 0);
+
+        double_tap.fail();
+        assert_eq!(single_tap.state(), GestureState::Ended);
+        assert_eq!(sent.get(), 1);
+
+        // When the double tap is recognized, the single tap fails.
+        single_tap.reset();
+        double_tap.reset();
+        single_tap.set_state(GestureState::Ended);
+        double_tap.set_state(GestureState::Ended);
+        assert_eq!(single_tap.state(), GestureState::Failed);
+        assert_eq!(sent.get(), 1);
+    }
+
+    #[test]
+    fn test_action_sent_for_recognized_states() {
+        let core = RecognizerCore::new();
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let states_clone = states.clone();
+        let core_clone = core.clone();
+        core.set_action_sender(move || states_clone.borrow_mut().push(core_clone.state()));
+
+        core.set_state(GestureState::Began);
+        core.set_state(GestureState::Changed);
+        core.set_state(GestureState::Cancelled);
+
+        // Failing before being recognized isn't worth telling.
+        core.reset();
+        core.fail();
+        assert_eq!(core.state(), GestureState::Failed);
+        assert_eq!(*states.borrow(), vec![GestureState::Began, GestureState::Changed, GestureState::Cancelled]);
+    }
 }
@@ -0,0 +1,220 @@
+use crate::ui::WeakView;
+use crate::graphics::Point;
+use std::cell::RefCell;
+use crate::ui::gesture::recognizer::{GestureState, Recognizer, RecognizerCore};
+use crate::ui::Touch;
+use crate::ui::clock;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use crate::ui::event::TouchEvent;
+
+/// The direction of a swipe.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down
+}
+
+/// Recognizes a quick, mostly straight movement of a touch in one of the
+/// allowed directions.
+///
+/// The gesture is discrete: the action is called once, with `Ended`, as
+/// soon as the touch has moved far enough. Moving too slowly, mostly in
+/// another direction, or lifting the touch first makes the recognizer fail.
+pub struct SwipeRecognizer {
+    inner: Rc<RefCell<SwipeRecognizerInner>>
+}
+
+struct SwipeRecognizerInner {
+    core: RecognizerCore,
+    action: Rc<dyn Fn(&SwipeRecognizer)>,
+    directions: Vec<SwipeDirection>,
+    direction: Option<SwipeDirection>,
+    initial_position: Point<i32>,
+    began_at: Instant
+}
+
+/// How far a touch moves to be a swipe.
+const SWIPE_DISTANCE: i32 = 50;
+
+/// How long a swipe can take to move that far.
+const SWIPE_DURATION: Duration = Duration::from_millis(500);
+
+impl SwipeRecognizer {
+    pub fn new(action: impl Fn(&SwipeRecognizer) + 'static) -> SwipeRecognizer {
+        let recognizer = SwipeRecognizer {
+            inner: Rc::new(RefCell::new(SwipeRecognizerInner {
+                core: RecognizerCore::new(),
+                action: Rc::new(action),
+                directions: vec![SwipeDirection::Right],
+                direction: None,
+                initial_position: Point::new(0, 0),
+                began_at: clock::now()
+            }))
+        };
+
+        let weak_inner = Rc::downgrade(&recognizer.inner);
+        recognizer.core().set_action_sender(move || {
+            if let Some(inner) = weak_inner.upgrade() {
+                let action = inner.borrow().action.clone();
+                action(&SwipeRecognizer { inner });
+            }
+        });
+
+        recognizer
+    }
+
+    pub fn directions(&self) -> Vec<SwipeDirection> {
+        self.inner.borrow().directions.clone()
+    }
+
+    /// Sets the directions recognized as a swipe. Default to `Right`.
+    pub fn set_directions(&self, directions: Vec<SwipeDirection>) {
+        self.inner.borrow_mut().directions = directions;
+    }
+
+    /// The direction of the swipe recognized, if any.
+    pub fn direction(&self) -> Option<SwipeDirection> {
+        self.inner.borrow().direction
+    }
+
+    pub fn state(&self) -> GestureState {
+        self.core().state()
+    }
+
+    pub fn view(&self) -> WeakView {
+        self.core().view()
+    }
+
+    /// The state machine of the recognizer. A swipe is decided as soon as
+    /// the touch has moved far enough quickly enough, so recognizers that
+    /// would take the same touch, such as a pan, are usually made to wait
+    /// for it to fail.
+    pub fn core(&self) -> RecognizerCore {
+        self.inner.borrow().core.clone()
+    }
+
+    /// The direction of a movement by `translation`, if it's far and
+    /// straight enough to be a swipe. The movement along the direction has
+    /// to be more than twice that across it.
+    fn direction_of(translation: &Point<i32>) -> Option<SwipeDirection> {
+        let (dx, dy) = (translation.x.abs(), translation.y.abs());
+        if dx >= SWIPE_DISTANCE && dx > dy * 2 {
+            Some(if translation.x > 0 { SwipeDirection::Right } else { SwipeDirection::Left })
+        } else if dy >= SWIPE_DISTANCE && dy > dx * 2 {
+            Some(if translation.y > 0 { SwipeDirection::Down } else { SwipeDirection::Up })
+        } else {
+            None
+        }
+    }
+}
+
+impl Recognizer for SwipeRecognizer {
+    fn touches_began(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.core().reset();
+
+        let mut inner = self.inner.borrow_mut();
+        inner.initial_position = touches.first().unwrap().position();
+        inner.direction = None;
+        inner.began_at = clock::now();
+    }
+
+    fn touches_ended(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.core().fail();
+    }
+
+    fn touches_moved(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        let core = self.core();
+        if core.state() != GestureState::Possible {
+            return;
+        }
+
+        let (direction, is_allowed, is_too_slow) = {
+            let inner = self.inner.borrow();
+            let position = touches.first().unwrap().position();
+            let translation = Point::new(position.x - inner.initial_position.x, position.y - inner.initial_position.y);
+            let direction = SwipeRecognizer::direction_of(&translation);
+            let is_allowed = direction.is_some_and(|direction| inner.directions.contains(&direction));
+            (direction, is_allowed, clock::now().duration_since(inner.began_at) > SWIPE_DURATION)
+        };
+
+        if is_too_slow {
+            core.fail();
+        } else if is_allowed {
+            self.inner.borrow_mut().direction = direction;
+            core.set_state(GestureState::Ended);
+        } else if direction.is_some() {
+            core.fail();
+        }
+    }
+
+    fn touches_cancelled(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.core().fail();
+    }
+
+    fn set_view(&self, view: WeakView) {
+        self.core().set_view(view);
+    }
+
+    fn state(&self) -> GestureState {
+        self.core().state()
+    }
+
+    fn fail(&self) {
+        self.core().fail();
+    }
+
+    fn recognizes_simultaneously_with(&self, _other: &dyn Recognizer) -> bool {
+        self.core().recognizes_simultaneously()
+    }
+}
+
+impl Clone for SwipeRecognizer {
+    fn clone(&self) -> SwipeRecognizer {
+        SwipeRecognizer {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direction_of() {
+        assert_eq!(SwipeRecognizer::direction_of(&Point::new(60, 10)), Some(SwipeDirection::Right));
+        assert_eq!(SwipeRecognizer::direction_of(&Point::new(-60, 10)), Some(SwipeDirection::Left));
+        assert_eq!(SwipeRecognizer::direction_of(&Point::new(0, 60)), Some(SwipeDirection::Down));
+        assert_eq!(SwipeRecognizer::direction_of(&Point::new(0, -60)), Some(SwipeDirection::Up));
+
+        // Too short, and too diagonal.
+        assert_eq!(SwipeRecognizer::direction_of(&Point::new(30, 0)), None);
+        assert_eq!(SwipeRecognizer::direction_of(&Point::new(60, 40)), None);
+    }
+
+    #[test]
+    fn test_swipe() {
+        let directions = Rc::new(RefCell::new(Vec::new()));
+        let directions_clone = directions.clone();
+        let recognizer = SwipeRecognizer::new(move |swipe_recognizer| {
+            directions_clone.borrow_mut().push(swipe_recognizer.direction());
+        });
+        recognizer.set_directions(vec![SwipeDirection::Left, SwipeDirection::Right]);
+
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(100, 10))], &event);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(80, 10))], &event);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(30, 14))], &event);
+        assert_eq!(recognizer.state(), GestureState::Ended);
+
+        // A swipe up isn't allowed.
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 100))], &event);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(10, 20))], &event);
+        assert_eq!(recognizer.state(), GestureState::Failed);
+
+        assert_eq!(*directions.borrow(), vec![Some(SwipeDirection::Left)]);
+    }
+}
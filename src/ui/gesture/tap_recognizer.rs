@@ -0,0 +1,232 @@
+use crate::ui::{View, WeakView};
+use crate::graphics::Point;
+use std::cell::RefCell;
+use crate::ui::gesture::recognizer::{GestureState, Recognizer, RecognizerCore};
+use crate::ui::Touch;
+use crate::ui::timer::{Timer, TimerHandle};
+use std::rc::Rc;
+use std::time::Duration;
+use crate::ui::event::TouchEvent;
+
+/// Recognizes one or more taps in quick succession, such as a double tap.
+///
+/// The action is called once, with the state `Ended`, after the last tap
+/// required. A touch that moves too far, or waits too long before the next
+/// tap, makes the recognizer fail.
+pub struct TapRecognizer {
+    inner: Rc<RefCell<TapRecognizerInner>>
+}
+
+struct TapRecognizerInner {
+    core: RecognizerCore,
+    action: Rc<dyn Fn(&TapRecognizer)>,
+    number_of_taps_required: usize,
+    number_of_taps: usize,
+    initial_position: Point<i32>,
+    location: Point<i32>,
+
+    // Fails the recognizer when the next tap doesn't come in time.
+    interval_timer: Option<TimerHandle>
+}
+
+/// How far a touch can move and still be a tap.
+const TAP_DISTANCE: i32 = 10;
+
+/// How long to wait for the next tap, when more than one is required.
+const TAP_INTERVAL: Duration = Duration::from_millis(300);
+
+impl TapRecognizer {
+    pub fn new(action: impl Fn(&TapRecognizer) + 'static) -> TapRecognizer {
+        let recognizer = TapRecognizer {
+            inner: Rc::new(RefCell::new(TapRecognizerInner {
+                core: RecognizerCore::new(),
+                action: Rc::new(action),
+                number_of_taps_required: 1,
+                number_of_taps: 0,
+                initial_position: Point::new(0, 0),
+                location: Point::new(0, 0),
+                interval_timer: None
+            }))
+        };
+
+        let weak_inner = Rc::downgrade(&recognizer.inner);
+        recognizer.core().set_action_sender(move || {
+            if let Some(inner) = weak_inner.upgrade() {
+                let action = inner.borrow().action.clone();
+                action(&TapRecognizer { inner });
+            }
+        });
+
+        recognizer
+    }
+
+    pub fn number_of_taps_required(&self) -> usize {
+        self.inner.borrow().number_of_taps_required
+    }
+
+    /// Sets how many taps make the gesture, such as 2 for a double tap.
+    /// Default to 1.
+    pub fn set_number_of_taps_required(&self, number_of_taps_required: usize) {
+        self.inner.borrow_mut().number_of_taps_required = number_of_taps_required.max(1);
+    }
+
+    /// Where the last tap was, in the window.
+    pub fn location_in(&self, _view: &View) -> Point<i32> {
+        self.inner.borrow().location.clone()
+    }
+
+    pub fn state(&self) -> GestureState {
+        self.core().state()
+    }
+
+    pub fn view(&self) -> WeakView {
+        self.core().view()
+    }
+
+    /// The state machine of the recognizer, such as to require a double tap
+    /// to fail first.
+    pub fn core(&self) -> RecognizerCore {
+        self.inner.borrow().core.clone()
+    }
+
+    fn cancel_interval_timer(&self) {
+        if let Some(timer) = self.inner.borrow_mut().interval_timer.take() {
+            timer.cancel();
+        }
+    }
+}
+
+impl Recognizer for TapRecognizer {
+    fn touches_began(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        let core = self.core();
+        self.cancel_interval_timer();
+
+        {
+            let inner = self.inner.borrow();
+            if core.state() == GestureState::Possible && inner.number_of_taps >= inner.number_of_taps_required {
+                // Still waiting for a recognizer it requires to fail, the
+                // taps are done; this touch belongs to another gesture.
+                return;
+            }
+        }
+
+        if core.state() != GestureState::Possible {
+            core.reset();
+            self.inner.borrow_mut().number_of_taps = 0;
+        }
+
+        let mut inner = self.inner.borrow_mut();
+        inner.initial_position = touches.first().unwrap().position();
+        inner.location = inner.initial_position.clone();
+    }
+
+    fn touches_ended(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        let core = self.core();
+        if core.state() != GestureState::Possible {
+            return;
+        }
+
+        let is_done = {
+            let mut inner = self.inner.borrow_mut();
+            if inner.number_of_taps >= inner.number_of_taps_required {
+                return;
+            }
+
+            inner.number_of_taps += 1;
+            inner.location = touches.first().unwrap().position();
+            inner.number_of_taps >= inner.number_of_taps_required
+        };
+
+        if is_done {
+            core.set_state(GestureState::Ended);
+        } else {
+            let timer_core = core.clone();
+            let timer = Timer::after(TAP_INTERVAL, move || timer_core.fail());
+            self.inner.borrow_mut().interval_timer = Some(timer);
+        }
+    }
+
+    fn touches_moved(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        let core = self.core();
+        if core.state() != GestureState::Possible {
+            return;
+        }
+
+        let position = touches.first().unwrap().position();
+        let initial_position = self.inner.borrow().initial_position.clone();
+        if (position.x - initial_position.x).abs() > TAP_DISTANCE || (position.y - initial_position.y).abs() > TAP_DISTANCE {
+            core.fail();
+        }
+    }
+
+    fn touches_cancelled(&self, _touches: &Vec<Touch>, _event: &TouchEvent) {
+        self.cancel_interval_timer();
+        self.core().fail();
+    }
+
+    fn set_view(&self, view: WeakView) {
+        self.core().set_view(view);
+    }
+
+    fn state(&self) -> GestureState {
+        self.core().state()
+    }
+
+    fn fail(&self) {
+        self.cancel_interval_timer();
+        self.core().fail();
+    }
+
+    fn recognizes_simultaneously_with(&self, _other: &dyn Recognizer) -> bool {
+        self.core().recognizes_simultaneously()
+    }
+}
+
+impl Clone for TapRecognizer {
+    fn clone(&self) -> TapRecognizer {
+        TapRecognizer {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Rectangle;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_tap() {
+        let taps = Rc::new(Cell::new(0));
+        let taps_clone = taps.clone();
+        let recognizer = TapRecognizer::new(move |tap_recognizer| {
+            assert_eq!(tap_recognizer.state(), GestureState::Ended);
+            taps_clone.set(taps_clone.get() + 1);
+        });
+
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(14, 12))], &event);
+        recognizer.touches_ended(&vec![Touch::new(0, Point::new(14, 12))], &event);
+        assert_eq!(taps.get(), 1);
+        assert_eq!(recognizer.location_in(&View::new(Rectangle::new(0, 0, 100, 100))), Point::new(14, 12));
+
+        // The next touch starts over.
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        assert_eq!(recognizer.state(), GestureState::Possible);
+        recognizer.touches_ended(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        assert_eq!(taps.get(), 2);
+    }
+
+    #[test]
+    fn test_moving_too_far_fails() {
+        let recognizer = TapRecognizer::new(|_tap_recognizer| panic!("not a tap"));
+
+        let event = TouchEvent::new();
+        recognizer.touches_began(&vec![Touch::new(0, Point::new(10, 10))], &event);
+        recognizer.touches_moved(&vec![Touch::new(0, Point::new(40, 10))], &event);
+        recognizer.touches_ended(&vec![Touch::new(0, Point::new(40, 10))], &event);
+        assert_eq!(recognizer.state(), GestureState::Failed);
+    }
+}
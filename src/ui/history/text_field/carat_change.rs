@@ -0,0 +1,69 @@
+use crate::ui::view::WeakView;
+use crate::ui::view::TextField;
+use crate::platform::history::Action;
+use crate::ui::history::text_field::carat_snapshot::CaratSnapshot;
+
+/// A reversible action that changes a text field's carats without changing
+/// its text, such as adding a carat or selecting the next occurrence of the
+/// selected text.
+pub struct CaratChange {
+    view: WeakView,
+    cursors_before: Vec<CaratSnapshot>,
+    cursors_after: Vec<CaratSnapshot>
+}
+
+impl CaratChange {
+    pub fn new(view: WeakView, cursors_before: Vec<CaratSnapshot>, cursors_after: Vec<CaratSnapshot>) -> CaratChange {
+        CaratChange {
+            view,
+            cursors_before,
+            cursors_after
+        }
+    }
+
+    fn text_field(&self) -> TextField {
+        let view = self.view.upgrade().unwrap();
+        TextField::from_view(view)
+    }
+}
+
+impl Action for CaratChange {
+    fn name(&self) -> &str {
+        "CaratChange"
+    }
+
+    fn forward(&mut self) {
+        self.text_field().restore_carat_snapshots(&self.cursors_after);
+    }
+
+    fn backward(&mut self) {
+        self.text_field().restore_carat_snapshots(&self.cursors_before);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Rectangle;
+
+    #[test]
+    fn test_forward_and_backward() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "Hello".to_string());
+
+        let before = vec![CaratSnapshot::new(0, None)];
+        let after = vec![CaratSnapshot::new(0, None), CaratSnapshot::new(5, Some(1..5))];
+
+        let mut carat_change = CaratChange::new(text_field.view.downgrade(), before.clone(), after.clone());
+
+        carat_change.forward();
+        assert_eq!(text_field.carat_snapshots(), after);
+
+        carat_change.backward();
+        assert_eq!(text_field.carat_snapshots(), before);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod text_insertion;
 pub mod text_backspace;
+pub mod text_cut;
+pub mod text_replace;
+pub mod carat_change;
 pub mod carat_snapshot;
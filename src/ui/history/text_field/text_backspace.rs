@@ -61,6 +61,11 @@ impl Action for TextBackspace {
         text_field.restore_carat_snapshots(&self.cursors_before);
     }
 
+    /// Deleting a single character coalesces with the deletions before it.
+    fn can_coalesce(&self) -> bool {
+        self.count == 1 && self.cursor_movement == CursorMovement::Character
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -176,6 +181,22 @@ mod tests {
         assert_eq!(text_field.carat_indexes(), vec![7]);
     }
 
+    #[test]
+    fn test_single_cursor_grapheme_cluster() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "a👨‍👩‍👧".to_string());
+        let mut carats = Vec::new();
+        carats.push(CaratSnapshot::new(2, None));
+
+        let mut action = TextBackspace::new(text_field.view.downgrade(), 1, CursorMovement::Character, carats);
+        action.forward();
+        assert_eq!(text_field.label().text().string(), "a");
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+        action.backward();
+        assert_eq!(text_field.label().text().string(), "a👨‍👩‍👧");
+        assert_eq!(text_field.carat_indexes(), vec![2]);
+    }
+
     #[test]
     fn test_multiple_cursors_single_character() {
         let frame = Rectangle::new(0, 0, 100, 100);
@@ -0,0 +1,96 @@
+use crate::ui::view::WeakView;
+use crate::ui::view::TextField;
+use crate::platform::history::Action;
+use crate::ui::history::text_field::carat_snapshot::CaratSnapshot;
+use std::ops::Range;
+
+/// A reversible action that removes the selected text of each carat in a text
+/// field, as when cutting. Carats without a selection are left alone.
+pub struct TextCut {
+    view: WeakView,
+    cursors_before: Vec<CaratSnapshot>,
+    cursors_after: Vec<CaratSnapshot>,
+
+    // The edits putting the text cut back, made in reverse order.
+    undo_edits: Vec<(Range<usize>, String)>
+}
+
+impl TextCut {
+    pub fn new(view: WeakView, cursors_before: Vec<CaratSnapshot>) -> TextCut {
+        TextCut {
+            view,
+            cursors_before,
+            cursors_after: Vec::new(),
+            undo_edits: Vec::new()
+        }
+    }
+
+    fn text_field(&self) -> TextField {
+        let view = self.view.upgrade().unwrap();
+        TextField::from_view(view)
+    }
+}
+
+impl Action for TextCut {
+    fn name(&self) -> &str {
+        "TextCut"
+    }
+
+    fn forward(&mut self) {
+        let text_field = self.text_field();
+        text_field.restore_carat_snapshots(&self.cursors_before);
+
+        // Inserting nothing replaces each selection, and leaves carats
+        // without one unchanged.
+        self.undo_edits = text_field.insert_str("");
+        self.cursors_after = text_field.carat_snapshots();
+    }
+
+    fn backward(&mut self) {
+        if self.cursors_after.is_empty() {
+            return;
+        }
+
+        let text_field = self.text_field();
+        let label = text_field.label();
+
+        for (range, text) in self.undo_edits.iter().rev() {
+            label.replace_text_in_range(range.clone(), text);
+        }
+
+        text_field.restore_carat_snapshots(&self.cursors_before);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Rectangle;
+
+    #[test]
+    fn test_forward_and_backward() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "Hello big world".to_string());
+
+        let mut carats = Vec::new();
+        carats.push(CaratSnapshot::new(0, None));
+        carats.push(CaratSnapshot::new(9, Some(6..9)));
+        carats.push(CaratSnapshot::new(15, Some(10..15)));
+
+        let mut text_cut = TextCut::new(text_field.view.downgrade(), carats.clone());
+        text_cut.forward();
+
+        assert_eq!(text_field.label().text().string(), "Hello  ");
+        assert_eq!(text_cut.undo_edits, vec![(6..6, "big".to_string()), (7..7, "world".to_string())]);
+        assert_eq!(text_field.carat_indexes(), vec![0, 6, 7]);
+
+        text_cut.backward();
+
+        assert_eq!(text_field.label().text().string(), "Hello big world");
+        assert_eq!(text_field.carat_snapshots(), carats);
+    }
+}
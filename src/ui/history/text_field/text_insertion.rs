@@ -2,6 +2,8 @@ use crate::ui::view::WeakView;
 use crate::ui::view::TextField;
 use crate::platform::history::Action;
 use crate::ui::history::text_field::carat_snapshot::CaratSnapshot;
+use crate::text::Text;
+use std::ops::Range;
 
 /// A reversible action that inserts text into a text field.
 pub struct TextInsertion {
@@ -9,7 +11,10 @@ pub struct TextInsertion {
     text: String,
     cursors_before: Vec<CaratSnapshot>,
     cursors_after: Vec<CaratSnapshot>,
-    text_replaced: Vec<Option<String>>
+
+    // The edits undoing the insertion, made in reverse order: ranges of the
+    // text after it, and what was there before.
+    undo_edits: Vec<(Range<usize>, String)>
 }
 
 impl TextInsertion {
@@ -19,7 +24,7 @@ impl TextInsertion {
             text,
             cursors_before,
             cursors_after: Vec::new(),
-            text_replaced: Vec::new()
+            undo_edits: Vec::new()
         }
     }
 
@@ -37,7 +42,7 @@ impl Action for TextInsertion {
     fn forward(&mut self) {
         let text_field = self.text_field();
         text_field.restore_carat_snapshots(&self.cursors_before);
-        self.text_replaced = text_field.insert_str(&self.text);
+        self.undo_edits = text_field.insert_str(&self.text);
         self.cursors_after = text_field.carat_snapshots();
     }
 
@@ -47,17 +52,10 @@ impl Action for TextInsertion {
         }
 
         let text_field = self.text_field();
+        let label = text_field.label();
 
-        for (cursor_index, text_replaced) in self.text_replaced.iter().enumerate().rev() {
-            let cursor = &self.cursors_after[cursor_index];
-            let start = cursor.character_index() - self.text.len();
-            let end = cursor.character_index();
-
-            if let Some(text_replaced) = text_replaced {
-                text_field.label().replace_text_in_range(start..end, text_replaced);
-            } else {
-                text_field.label().replace_text_in_range(start..end, "");
-            }
+        for (range, text) in self.undo_edits.iter().rev() {
+            label.replace_text_in_range(range.clone(), text);
         }
 
         text_field.restore_carat_snapshots(&self.cursors_before);
@@ -77,36 +75,23 @@ impl Action for TextInsertion {
 
         let text = self.text.clone() + &other.text;
 
-        let mut combo_text_replaced: Vec<Option<String>> = Vec::new();
-
-        for (index, text_replaced) in self.text_replaced.iter().enumerate() {
-            if let Some(text_replaced) = text_replaced {
-                if let Some(other_text_replaced) = other.text_replaced.get(index) {
-                    if let Some(other_text_replaced) = other_text_replaced {
-                        combo_text_replaced.push(Some(text_replaced.clone() + other_text_replaced));
-                    } else {
-                        combo_text_replaced.push(Some(text_replaced.clone()));
-                    }
-                    combo_text_replaced.push(Some(text_replaced.clone()));
-                } else {
-                    combo_text_replaced.push(Some(text_replaced.clone()));
-                }
-            } else {
-                combo_text_replaced.push(None);
-            }
-        }
-
         let mut new = Self::new(
             self.view.clone(),
             text,
             self.cursors_before.clone(),
         );
 
+        // Undoing the merged insertion undoes the other one, then this one.
         new.cursors_after = other.cursors_after.clone();
-        new.text_replaced = combo_text_replaced;
+        new.undo_edits = self.undo_edits.iter().chain(other.undo_edits.iter()).cloned().collect();
         Some(Box::new(new))
     }
 
+    /// Typing a single character coalesces with the typing before it.
+    fn can_coalesce(&self) -> bool {
+        Text::from(&self.text).len() == 1
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -159,6 +144,30 @@ mod tests {
         assert_eq!(text_field.carat_indexes(), vec![5, 11]);
     }
 
+    #[test]
+    fn test_forward_and_backward_multi_cursor_grapheme_clusters() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+
+        let text_field = TextField::new(frame, "|".to_string());
+        let mut carats = Vec::new();
+        carats.push(CaratSnapshot::new(0, None));
+        carats.push(CaratSnapshot::new(1, None));
+
+        let mut text_insertion = TextInsertion::new(
+            text_field.view.downgrade(),
+            "é👨‍👩‍👧".to_string(),
+            carats
+        );
+
+        text_insertion.forward();
+        assert_eq!(text_field.label().text().string(), "é👨‍👩‍👧|é👨‍👩‍👧");
+        assert_eq!(text_field.carat_indexes(), vec![2, 5]);
+
+        text_insertion.backward();
+        assert_eq!(text_field.label().text().string(), "|");
+        assert_eq!(text_field.carat_indexes(), vec![0, 1]);
+    }
+
     #[test]
     fn test_backward() {
         let frame = Rectangle::new(0, 0, 100, 100);
@@ -0,0 +1,174 @@
+use crate::ui::view::WeakView;
+use crate::ui::view::TextField;
+use crate::platform::history::Action;
+use crate::ui::history::text_field::carat_snapshot::CaratSnapshot;
+use std::ops::Range;
+
+/// A reversible action that replaces ranges of a text field's text, such as
+/// pasting over the selection of each carat or replacing every match of a
+/// search. All of the ranges are replaced, and restored, in one step.
+///
+/// Afterwards there is a carat at the end of each replacement.
+pub struct TextReplace {
+    view: WeakView,
+
+    // The ranges to replace, in the text as it was before, and what to
+    // replace them with. Sorted by range, and not overlapping.
+    replacements: Vec<(Range<usize>, String)>,
+
+    cursors_before: Vec<CaratSnapshot>,
+
+    // The edits undoing the replacements, made in reverse order: ranges of
+    // the text after them, and what was there before.
+    undo_edits: Vec<(Range<usize>, String)>
+}
+
+impl TextReplace {
+    /// Replaces each of `replacements`' ranges with its string. Overlapping
+    /// ranges are dropped, keeping the earliest.
+    pub fn new(view: WeakView, replacements: Vec<(Range<usize>, String)>, cursors_before: Vec<CaratSnapshot>) -> TextReplace {
+        let mut replacements = replacements;
+        replacements.sort_by_key(|(range, _)| (range.start, range.end));
+
+        let mut end = 0;
+        replacements.retain(|(range, _)| {
+            if range.start < end {
+                return false;
+            }
+
+            end = range.end;
+            true
+        });
+
+        TextReplace {
+            view,
+            replacements,
+            cursors_before,
+            undo_edits: Vec::new()
+        }
+    }
+
+    /// The replacements for replacing the selection of each of `carats`, or
+    /// inserting at carats without a selection, with `texts[n]` for the nth
+    /// carat. If there are fewer texts than carats, the last text is used
+    /// for the rest.
+    pub fn replacements_for_carats(texts: &[String], carats: &[CaratSnapshot]) -> Vec<(Range<usize>, String)> {
+        carats
+            .iter()
+            .enumerate()
+            .map(|(index, carat)| {
+                let range = match carat.selection() {
+                    Some(selection) => selection.clone(),
+                    None => carat.character_index()..carat.character_index()
+                };
+                let text = texts.get(index).or_else(|| texts.last()).cloned().unwrap_or_default();
+                (range, text)
+            })
+            .collect()
+    }
+
+    fn text_field(&self) -> TextField {
+        let view = self.view.upgrade().unwrap();
+        TextField::from_view(view)
+    }
+}
+
+impl Action for TextReplace {
+    fn name(&self) -> &str {
+        "TextReplace"
+    }
+
+    fn forward(&mut self) {
+        let text_field = self.text_field();
+
+        // Each replacement moves the ranges after it by how much it changed
+        // the length of the text, which isn't always its own length, as it
+        // can join the graphemes beside it.
+        let mut undo_edits = Vec::new();
+        let mut cursors_after = Vec::new();
+        let mut shift: i32 = 0;
+        for (range, text) in self.replacements.iter() {
+            let start = (range.start as i32 + shift).max(0) as usize;
+            let end = (range.end as i32 + shift).max(start as i32) as usize;
+
+            let edit = text_field.replace_graphemes(start..end, text);
+            shift += edit.change;
+            undo_edits.push((edit.undo_range, edit.replaced_text));
+            cursors_after.push(CaratSnapshot::new(edit.end, None));
+        }
+        self.undo_edits = undo_edits;
+
+        text_field.restore_carat_snapshots(&cursors_after);
+    }
+
+    fn backward(&mut self) {
+        if self.undo_edits.is_empty() {
+            return;
+        }
+
+        let text_field = self.text_field();
+        let label = text_field.label();
+
+        for (range, text) in self.undo_edits.iter().rev() {
+            label.replace_text_in_range(range.clone(), text);
+        }
+
+        text_field.restore_carat_snapshots(&self.cursors_before);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Rectangle;
+
+    #[test]
+    fn test_replace_ranges() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "one two one".to_string());
+        let carats = text_field.carat_snapshots();
+
+        let mut text_replace = TextReplace::new(
+            text_field.view.downgrade(),
+            vec![(8..11, "three".to_string()), (0..3, "é".to_string()), (1..2, "x".to_string())],
+            carats.clone()
+        );
+
+        text_replace.forward();
+        assert_eq!(text_field.label().text().string(), "é two three");
+        assert_eq!(text_field.carat_indexes(), vec![1, 11]);
+
+        text_replace.backward();
+        assert_eq!(text_field.label().text().string(), "one two one");
+        assert_eq!(text_field.carat_snapshots(), carats);
+
+        text_replace.forward();
+        assert_eq!(text_field.label().text().string(), "é two three");
+    }
+
+    #[test]
+    fn test_replace_for_carats() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "cat dog".to_string());
+
+        let carats = vec![CaratSnapshot::new(3, Some(0..3)), CaratSnapshot::new(7, None)];
+        let texts = vec!["owl".to_string(), "!".to_string()];
+        let mut text_replace = TextReplace::new(
+            text_field.view.downgrade(),
+            TextReplace::replacements_for_carats(&texts, &carats),
+            carats.clone()
+        );
+
+        text_replace.forward();
+        assert_eq!(text_field.label().text().string(), "owl dog!");
+        assert_eq!(text_field.carat_indexes(), vec![3, 8]);
+
+        text_replace.backward();
+        assert_eq!(text_field.label().text().string(), "cat dog");
+        assert_eq!(text_field.carat_snapshots(), carats);
+    }
+}
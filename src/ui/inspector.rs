@@ -0,0 +1,161 @@
+//! Draws over a window the outline and name of each of its views, and their
+//! layout margins, to see how the window is laid out as it runs. See
+//! `Window::set_shows_inspector`.
+
+use crate::graphics::{EdgeInsets, FillRule, Path, Rectangle, StrokeStyle};
+use crate::ui::view::DefaultBehavior;
+use crate::ui::{Color, Label, View};
+use std::cell::RefCell;
+use crate::macros::*;
+
+const OUTLINE_COLOR: Color = Color::new(255, 0, 160, 200);
+const LAYOUT_MARGINS_COLOR: Color = Color::new(255, 190, 0, 64);
+const NAME_BACKGROUND_COLOR: Color = Color::new(255, 255, 255, 180);
+
+/// What the inspector shows of a view.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct InspectedView {
+    /// The view's frame, relative to the window.
+    frame: Rectangle<i32, u32>,
+
+    /// The view's identifier, or its behavior's name if it has none.
+    name: String,
+
+    layout_margins: EdgeInsets
+}
+
+/// The visible views in `window_view`'s hierarchy, back to front, leaving
+/// out `excluded` views and their subviews, such as the inspector itself.
+pub(crate) fn inspected_views(window_view: &View, excluded: &[View]) -> Vec<InspectedView> {
+    let mut inspected_views = Vec::new();
+    let mut stack: Vec<View> = window_view.subviews().into_iter().rev().collect();
+
+    while let Some(view) = stack.pop() {
+        if view.is_hidden() || excluded.contains(&view) {
+            continue;
+        }
+
+        let size = view.frame().size;
+        inspected_views.push(InspectedView {
+            frame: view.convert_rect_to(&Rectangle::new(0, 0, size.width, size.height), window_view),
+            name: view.identifier().unwrap_or_else(|| view.behavior_name()),
+            layout_margins: view.layout_margins()
+        });
+
+        stack.extend(view.subviews().into_iter().rev());
+    }
+
+    inspected_views
+}
+
+// Covers a window, in front of its views, drawing what the inspector shows
+// of them. It doesn't take touches, so the window can still be used.
+custom_view!(
+    InspectorOverlay subclasses DefaultBehavior
+
+    struct InspectorOverlayBehavior {
+        inspected_views: RefCell<Vec<InspectedView>>
+    }
+
+    impl Self {
+        pub(crate) fn new(frame: Rectangle<i32, u32>) -> InspectorOverlay {
+            let overlay = InspectorOverlay::new_all(frame, RefCell::new(Vec::new()));
+            overlay.view.set_background_color(Color::clear());
+            overlay.view.set_user_interaction_enabled(false);
+            overlay
+        }
+
+        /// Shows `inspected_views`. Nothing is drawn again unless they've
+        /// changed since they were last shown.
+        pub(crate) fn update(&self, inspected_views: Vec<InspectedView>) {
+            if *self.behavior().inspected_views.borrow() == inspected_views {
+                return;
+            }
+
+            for name_label in self.view.subviews() {
+                name_label.remove_from_superview();
+            }
+
+            for inspected_view in &inspected_views {
+                let frame = &inspected_view.frame;
+                let name_label = Label::new(Rectangle::new(frame.origin.x, frame.origin.y, frame.size.width, 0), inspected_view.name.clone());
+                name_label.set_text_color(OUTLINE_COLOR.clone());
+                name_label.set_number_of_lines(1);
+                name_label.view.set_background_color(NAME_BACKGROUND_COLOR.clone());
+                name_label.view.set_user_interaction_enabled(false);
+                name_label.fit_to_text();
+                self.view.add_subview(name_label.view);
+            }
+
+            self.behavior().inspected_views.replace(inspected_views);
+            self.view.set_needs_display();
+        }
+    }
+
+    impl Behavior {
+        fn draw(&self) {
+            self.super_behavior().unwrap().draw();
+
+            let view = self.view.upgrade().unwrap().clone();
+            let inner_self = view.inner_self.borrow();
+
+            if let Some(layer) = &inner_self.layer {
+                let outline_color = OUTLINE_COLOR.to_graphics_color();
+                let layout_margins_color = LAYOUT_MARGINS_COLOR.to_graphics_color();
+
+                for inspected_view in self.inspected_views.borrow().iter() {
+                    let frame = inspected_view.frame.to_f32();
+
+                    // The margins are filled between the frame and the
+                    // rectangle inside them.
+                    if inspected_view.layout_margins != EdgeInsets::zero() {
+                        let mut margins = Path::rectangle(&frame);
+                        margins.add_rectangle(&inspected_view.frame.inset_by_edges(&inspected_view.layout_margins).to_f32());
+                        layer.fill_path(&margins, layout_margins_color, FillRule::EvenOdd);
+                    }
+
+                    // Outlines are drawn just inside frames, so views at the
+                    // edge of the window are outlined too.
+                    let outline = Path::rectangle(&frame.inset_by(0.5, 0.5));
+                    layer.stroke_path(&outline, outline_color, &StrokeStyle::new(1.0));
+                }
+            }
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspected_views() {
+        let window_view = View::new(Rectangle::new(0, 0, 400, 300));
+        let view = View::new(Rectangle::new(10, 20, 200, 100));
+        view.set_identifier(Some("content"));
+        view.set_layout_margins(EdgeInsets::uniform(8));
+        let subview = View::new(Rectangle::new(5, 5, 50, 50));
+        view.add_subview(subview);
+        window_view.add_subview(view);
+
+        let hidden_view = View::new(Rectangle::new(0, 0, 10, 10));
+        hidden_view.set_hidden(true);
+        window_view.add_subview(hidden_view);
+
+        let excluded_view = View::new(Rectangle::new(0, 0, 400, 300));
+        window_view.add_subview(excluded_view.clone());
+
+        assert_eq!(inspected_views(&window_view, &[excluded_view]), vec![
+            InspectedView {
+                frame: Rectangle::new(10, 20, 200, 100),
+                name: String::from("content"),
+                layout_margins: EdgeInsets::uniform(8)
+            },
+            InspectedView {
+                frame: Rectangle::new(15, 25, 50, 50),
+                name: String::from("DefaultBehavior"),
+                layout_margins: EdgeInsets::zero()
+            }
+        ]);
+    }
+}
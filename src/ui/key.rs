@@ -30,6 +30,36 @@ impl Key {
     pub fn modifier_flags(&self) -> &Vec<ModifierFlag> {
         &self.modifier_flags
     }
+
+    /// Whether Command (or Control, on platforms without a Command key) is
+    /// held, the modifier used by most shortcuts.
+    pub fn has_shortcut_modifier(&self) -> bool {
+        self.modifier_flags.contains(&ModifierFlag::Command) || self.modifier_flags.contains(&ModifierFlag::Control)
+    }
+
+    /// Whether the key is a modifier, such as Shift or Command, rather than
+    /// a key that types or performs a command on its own.
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            self.key_code,
+            KeyCode::LShift | KeyCode::RShift |
+            KeyCode::LCtrl | KeyCode::RCtrl |
+            KeyCode::LAlt | KeyCode::RAlt |
+            KeyCode::LGui | KeyCode::RGui |
+            KeyCode::CapsLock | KeyCode::NumLockClear | KeyCode::Mode
+        )
+    }
+
+    /// Whether this is the undo shortcut, Command+Z or Control+Z.
+    pub fn is_undo(&self) -> bool {
+        self.key_code == KeyCode::Z && self.has_shortcut_modifier() && !self.modifier_flags.contains(&ModifierFlag::Shift)
+    }
+
+    /// Whether this is the redo shortcut, Shift+Command+Z or
+    /// Shift+Control+Z.
+    pub fn is_redo(&self) -> bool {
+        self.key_code == KeyCode::Z && self.has_shortcut_modifier() && self.modifier_flags.contains(&ModifierFlag::Shift)
+    }
 }
 
 impl PartialEq for Key {
@@ -58,6 +88,24 @@ mod tests {
         assert_eq!(key.modifier_flags(), &vec![ModifierFlag::Shift]);
     }
 
+    #[test]
+    fn test_undo_and_redo_keys() {
+        assert!(Key::new(KeyCode::Z, vec![ModifierFlag::Command]).is_undo());
+        assert!(Key::new(KeyCode::Z, vec![ModifierFlag::Control]).is_undo());
+        assert!(!Key::new(KeyCode::Z, vec![]).is_undo());
+        assert!(!Key::new(KeyCode::Z, vec![ModifierFlag::Command, ModifierFlag::Shift]).is_undo());
+        assert!(Key::new(KeyCode::Z, vec![ModifierFlag::Control, ModifierFlag::Shift]).is_redo());
+        assert!(!Key::new(KeyCode::Y, vec![ModifierFlag::Command, ModifierFlag::Shift]).is_redo());
+    }
+
+    #[test]
+    fn test_modifier_keys() {
+        assert!(Key::new(KeyCode::LShift, vec![]).is_modifier());
+        assert!(Key::new(KeyCode::RGui, vec![ModifierFlag::Command]).is_modifier());
+        assert!(!Key::new(KeyCode::Backspace, vec![]).is_modifier());
+        assert!(!Key::new(KeyCode::A, vec![ModifierFlag::Shift]).is_modifier());
+    }
+
     #[test]
     fn test_key_eq() {
         let key1 = Key::new(KeyCode::A, vec![ModifierFlag::Shift]);
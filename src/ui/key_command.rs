@@ -0,0 +1,161 @@
+use crate::ui::key::{Key, KeyCode, ModifierFlag};
+use crate::ui::menu::MenuItem;
+use std::rc::Rc;
+
+type KeyCommandAction = Rc<dyn Fn()>;
+
+/// A keyboard shortcut, such as Command+S to save, calling its action when
+/// pressed.
+///
+/// Key commands are added to views with `View::add_key_command`, or given by
+/// a view controller from `ViewControllerBehavior::key_commands`. When a key
+/// is pressed, the key window looks for a matching command on the first
+/// responder, then up its superviews, then on the window's view controller,
+/// before the press is sent to the first responder. A matched press isn't
+/// sent on, and doesn't repeat while held.
+///
+/// ```ignore
+/// let save = KeyCommand::new(KeyCode::S, &[ModifierFlag::Command], || document.save())
+///     .titled("Save");
+/// view.add_key_command(save);
+/// ```
+#[derive(Clone)]
+pub struct KeyCommand {
+    key_code: KeyCode,
+    modifier_flags: Vec<ModifierFlag>,
+    title: Option<String>,
+    action: KeyCommandAction
+}
+
+impl KeyCommand {
+    /// A command calling `action` when `key_code` is pressed with exactly
+    /// `modifier_flags` held.
+    ///
+    /// `ModifierFlag::Command` stands for the platform's shortcut modifier,
+    /// and matches Command or Control, like the undo shortcut does. Caps
+    /// lock and the numeric pad are ignored.
+    pub fn new(key_code: KeyCode, modifier_flags: &[ModifierFlag], action: impl Fn() + 'static) -> KeyCommand {
+        KeyCommand {
+            key_code,
+            modifier_flags: normalized(modifier_flags),
+            title: None,
+            action: Rc::new(action)
+        }
+    }
+
+    /// The command, with `title` to show it by in menus.
+    pub fn titled(mut self, title: &str) -> KeyCommand {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn key_code(&self) -> KeyCode {
+        self.key_code
+    }
+
+    pub fn modifier_flags(&self) -> &[ModifierFlag] {
+        &self.modifier_flags
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Whether pressing `key` triggers the command.
+    pub fn matches(&self, key: &Key) -> bool {
+        key.key_code() == self.key_code && normalized(key.modifier_flags()) == self.modifier_flags
+    }
+
+    /// Calls the command's action.
+    pub fn perform(&self) {
+        (self.action)();
+    }
+
+    /// An item calling the command's action, for adding to a menu, if the
+    /// command has a title.
+    pub fn menu_item(&self) -> Option<MenuItem> {
+        let action = self.action.clone();
+        self.title.as_ref().map(|title| MenuItem::new(title, move || action()))
+    }
+}
+
+impl std::fmt::Debug for KeyCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("KeyCommand")
+            .field("key_code", &self.key_code)
+            .field("modifier_flags", &self.modifier_flags)
+            .field("title", &self.title)
+            .finish()
+    }
+}
+
+/// The modifiers that matter for matching, in a fixed order, with Control
+/// standing in for Command.
+fn normalized(modifier_flags: &[ModifierFlag]) -> Vec<ModifierFlag> {
+    let has = |flag: ModifierFlag| modifier_flags.contains(&flag);
+
+    let mut normalized = Vec::new();
+    if has(ModifierFlag::Command) || has(ModifierFlag::Control) {
+        normalized.push(ModifierFlag::Command);
+    }
+    if has(ModifierFlag::Alternate) {
+        normalized.push(ModifierFlag::Alternate);
+    }
+    if has(ModifierFlag::Shift) {
+        normalized.push(ModifierFlag::Shift);
+    }
+    normalized
+}
+
+/// The first of `key_commands` triggered by pressing `key`, if any.
+pub(crate) fn matching(key_commands: &[KeyCommand], key: &Key) -> Option<KeyCommand> {
+    key_commands.iter().find(|key_command| key_command.matches(key)).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_matches() {
+        let save = KeyCommand::new(KeyCode::S, &[ModifierFlag::Command], || {});
+
+        assert!(save.matches(&Key::new(KeyCode::S, vec![ModifierFlag::Command])));
+        assert!(save.matches(&Key::new(KeyCode::S, vec![ModifierFlag::Control])));
+        assert!(save.matches(&Key::new(KeyCode::S, vec![ModifierFlag::Command, ModifierFlag::CapsLock])));
+        assert!(!save.matches(&Key::new(KeyCode::S, vec![])));
+        assert!(!save.matches(&Key::new(KeyCode::S, vec![ModifierFlag::Command, ModifierFlag::Shift])));
+        assert!(!save.matches(&Key::new(KeyCode::D, vec![ModifierFlag::Command])));
+
+        let save_as = KeyCommand::new(KeyCode::S, &[ModifierFlag::Shift, ModifierFlag::Command], || {});
+        assert!(save_as.matches(&Key::new(KeyCode::S, vec![ModifierFlag::Command, ModifierFlag::Shift])));
+    }
+
+    #[test]
+    fn test_matching_performs_first() {
+        let performed = Rc::new(Cell::new(0));
+        let performed_clone = performed.clone();
+        let key_commands = vec![
+            KeyCommand::new(KeyCode::F5, &[], move || performed_clone.set(1)),
+            KeyCommand::new(KeyCode::F5, &[], || panic!("only the first match is performed"))
+        ];
+
+        matching(&key_commands, &Key::new(KeyCode::F5, vec![])).unwrap().perform();
+        assert_eq!(performed.get(), 1);
+        assert!(matching(&key_commands, &Key::new(KeyCode::F6, vec![])).is_none());
+    }
+
+    #[test]
+    fn test_menu_item() {
+        let performed = Rc::new(Cell::new(false));
+        let performed_clone = performed.clone();
+        let untitled = KeyCommand::new(KeyCode::S, &[ModifierFlag::Command], move || performed_clone.set(true));
+        assert!(untitled.menu_item().is_none());
+
+        let item = untitled.titled("Save").menu_item().unwrap();
+        assert_eq!(item.title(), "Save");
+        assert!(item.perform(None));
+        assert!(performed.get());
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+/// How a held key repeats: after being held for `delay`, it repeats every
+/// `interval` until it's released.
+///
+/// Repeats are sent to the first responder as `Behavior::press_repeated`. The
+/// application's key repeat is set with `Application::set_key_repeat`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyRepeat {
+    delay: Duration,
+    interval: Duration
+}
+
+impl KeyRepeat {
+    /// Half a second before the first repeat, then 20 repeats a second. This
+    /// is what `KeyRepeat::default()` returns, as a constant so the
+    /// application can start out with it.
+    pub const DEFAULT: KeyRepeat = KeyRepeat::new(Duration::from_millis(500), Duration::from_millis(50));
+
+    pub const fn new(delay: Duration, interval: Duration) -> KeyRepeat {
+        KeyRepeat {
+            delay,
+            interval
+        }
+    }
+
+    /// How long a key is held before it first repeats.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// How long between repeats after the first.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// How many times a key held for `held_for` should have repeated.
+    pub fn repeats_after(&self, held_for: Duration) -> usize {
+        if held_for < self.delay {
+            return 0;
+        }
+
+        let repeating_for = (held_for - self.delay).as_nanos();
+        let interval = self.interval.as_nanos().max(1);
+        1 + (repeating_for / interval) as usize
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> KeyRepeat {
+        KeyRepeat::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeats_after() {
+        let key_repeat = KeyRepeat::new(Duration::from_millis(400), Duration::from_millis(100));
+
+        assert_eq!(key_repeat.repeats_after(Duration::from_millis(0)), 0);
+        assert_eq!(key_repeat.repeats_after(Duration::from_millis(399)), 0);
+        assert_eq!(key_repeat.repeats_after(Duration::from_millis(400)), 1);
+        assert_eq!(key_repeat.repeats_after(Duration::from_millis(499)), 1);
+        assert_eq!(key_repeat.repeats_after(Duration::from_millis(500)), 2);
+        assert_eq!(key_repeat.repeats_after(Duration::from_millis(1000)), 7);
+    }
+}
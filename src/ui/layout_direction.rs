@@ -0,0 +1,57 @@
+//! Which way an interface reads: left to right, as in English, or right to
+//! left, as in Arabic and Hebrew, where it's laid out as a mirror image.
+//!
+//! The application's direction follows its locale, and a view, or a whole
+//! window through its root view, can be given a direction of its own with
+//! `View::set_layout_direction`. The direction a view uses is its
+//! `View::effective_layout_direction`, which:
+//!
+//! - resolves `AutoresizingMask::FlexibleLeadingMargin` and
+//!   `FlexibleTrailingMargin` of its subviews to the left or right margin,
+//! - lines up the items of a `CollectionView` from the right,
+//! - and aligns the text of labels with the `Natural` alignment, the
+//!   default, on the right.
+//!
+//! This is about where views go, not the order of the characters in a line
+//! of text, which follows the text's own bidirectional rules either way.
+
+use crate::ui::localization::{self, Locale};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutDirection {
+    LeftToRight,
+    RightToLeft
+}
+
+impl LayoutDirection {
+    /// The direction interfaces read in `locale`'s language.
+    pub fn for_locale(locale: &Locale) -> LayoutDirection {
+        match locale.language() {
+            "ar" | "he" | "iw" | "fa" | "ur" | "ps" | "yi" | "dv" | "sd" | "ug" | "ckb" => LayoutDirection::RightToLeft,
+            _ => LayoutDirection::LeftToRight
+        }
+    }
+
+    pub fn is_right_to_left(self) -> bool {
+        self == LayoutDirection::RightToLeft
+    }
+}
+
+/// The application's layout direction, that of its locale. See
+/// `localization::set_locale`.
+pub fn layout_direction() -> LayoutDirection {
+    localization::layout_direction()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_locale() {
+        assert_eq!(LayoutDirection::for_locale(&Locale::new("en-US")), LayoutDirection::LeftToRight);
+        assert_eq!(LayoutDirection::for_locale(&Locale::new("ar-EG")), LayoutDirection::RightToLeft);
+        assert_eq!(LayoutDirection::for_locale(&Locale::new("he_IL.UTF-8")), LayoutDirection::RightToLeft);
+        assert!(!LayoutDirection::for_locale(&Locale::new("ja")).is_right_to_left());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::platform::bundle::Bundle;
+use crate::ui::run_loop::RunLoop;
+use crate::ui::timer::Timer;
+use crate::ui::view::scroll_view::ScrollViewBehavior;
+use crate::ui::view::text_field::TextFieldBehavior;
+use crate::ui::{ScrollView, TextField, View};
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::time::{Duration, SystemTime};
+
+/// How often the watched file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+type ReloadCallback = Rc<dyn Fn(&View)>;
+
+/// Watches a UI description file while the application runs, and rebuilds
+/// the view loaded from it each time it's saved, for trying out changes
+/// without restarting.
+///
+/// The new view takes the old one's place in its superview. Text typed
+/// into text fields and the offsets of scroll views are carried over to the
+/// views with the same identifiers in the new hierarchy, so only views with
+/// identifiers keep their state. A file that can't be loaded, such as one
+/// saved halfway through an edit, leaves the current view as it is.
+///
+/// ```ignore
+/// let view = loader::load_file("ui/settings.json")?;
+/// window.view.add_subview(view.clone());
+///
+/// let hot_reload = HotReload::watch("ui/settings.json", view);
+/// hot_reload.on_reload(|view| {
+///     Button::from_view(view.view_with_identifier("ok").unwrap()).on_tap(|| println!("OK"));
+/// });
+/// ```
+///
+/// Watching stops when the `HotReload` is dropped.
+pub struct HotReload {
+    inner: Rc<HotReloadInner>
+}
+
+struct HotReloadInner {
+    path: String,
+
+    /// The view most recently loaded from the file.
+    view: RefCell<View>,
+
+    /// When the file was last changed, as of the last check.
+    modified: Cell<Option<SystemTime>>,
+
+    on_reload: RefCell<Option<ReloadCallback>>,
+
+    timer: RefCell<Option<Timer>>
+}
+
+impl HotReload {
+    /// Starts watching the file `name`, which `view` was loaded from. A
+    /// relative path is relative to the application's `resource` directory.
+    pub fn watch(name: &str, view: View) -> HotReload {
+        let path = Bundle::path_for_resource(name);
+        let inner = Rc::new(HotReloadInner {
+            modified: Cell::new(modified_time(&path)),
+            path,
+            view: RefCell::new(view),
+            on_reload: RefCell::new(None),
+            timer: RefCell::new(None)
+        });
+
+        let weak_inner: Weak<HotReloadInner> = Rc::downgrade(&inner);
+        let timer = Timer::new_repeating(POLL_INTERVAL, move || {
+            if let Some(inner) = weak_inner.upgrade() {
+                HotReload { inner }.reload_if_modified();
+            }
+        });
+        RunLoop::borrow().add_timer(timer.clone());
+        inner.timer.replace(Some(timer));
+
+        HotReload { inner }
+    }
+
+    /// The view most recently loaded from the file.
+    pub fn view(&self) -> View {
+        self.inner.view.borrow().clone()
+    }
+
+    /// Sets a closure called with each newly loaded view, once it's in
+    /// place, such as to hook its actions up again.
+    pub fn on_reload(&self, action: impl Fn(&View) + 'static) {
+        self.inner.on_reload.replace(Some(Rc::new(action)));
+    }
+
+    /// Reloads the file straight away, whether or not it has changed.
+    pub fn reload(&self) -> Result<(), String> {
+        self.inner.modified.set(modified_time(&self.inner.path));
+
+        let new_view = super::load_file(&self.inner.path)?;
+        let old_view = self.inner.view.replace(new_view.clone());
+        replace_view(&old_view, &new_view);
+
+        let on_reload = self.inner.on_reload.borrow().clone();
+        if let Some(on_reload) = on_reload {
+            on_reload(&new_view);
+        }
+
+        Ok(())
+    }
+
+    /// Stops watching the file.
+    pub fn stop(&self) {
+        if let Some(timer) = self.inner.timer.replace(None) {
+            timer.invalidate();
+        }
+    }
+
+    fn reload_if_modified(&self) {
+        if modified_time(&self.inner.path) == self.inner.modified.get() {
+            return;
+        }
+
+        if let Err(error) = self.reload() {
+            println!("Warning: couldn't reload the view, keeping the current one. {}", error);
+        }
+    }
+}
+
+impl Drop for HotReloadInner {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.get_mut().take() {
+            timer.invalidate();
+        }
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Puts `new_view` in `old_view`'s place, carrying over the state of the
+/// views with identifiers that are in both. If `old_view` has been taken
+/// out of its superview, `new_view` isn't put anywhere.
+pub(crate) fn replace_view(old_view: &View, new_view: &View) {
+    carry_over_state(old_view, new_view);
+
+    // A removed view still points at its old superview, so it's looked for
+    // among the superview's subviews.
+    if let Some(superview) = old_view.superview().upgrade() {
+        if let Some(index) = superview.subviews().iter().position(|view| view == old_view) {
+            old_view.remove_from_superview();
+            superview.insert_subview_at(new_view.clone(), index);
+        }
+    }
+}
+
+/// Copies the text of text fields and the offsets of scroll views from the
+/// views in `old_view`'s hierarchy to the views with the same identifiers
+/// in `new_view`'s.
+fn carry_over_state(old_view: &View, new_view: &View) {
+    let mut stack = vec![new_view.clone()];
+
+    while let Some(view) = stack.pop() {
+        stack.extend(view.subviews());
+
+        let old = match view.identifier().and_then(|identifier| old_view.view_with_identifier(&identifier)) {
+            Some(old) => old,
+            None => continue
+        };
+
+        if is_text_field(&view) && is_text_field(&old) {
+            let text = TextField::from_view(old).label().copy_text();
+            TextField::from_view(view).label().set_text(text);
+        } else if is_scroll_view(&view) && is_scroll_view(&old) {
+            let offset = ScrollView::from_view(old).content_offset();
+            ScrollView::from_view(view).set_content_offset(offset);
+        }
+    }
+}
+
+fn is_text_field(view: &View) -> bool {
+    view.behavior().as_any().is::<TextFieldBehavior>()
+}
+
+fn is_scroll_view(view: &View) -> bool {
+    view.behavior().as_any().is::<ScrollViewBehavior>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::{Point, Rectangle};
+    use crate::ui::loader::load_str;
+
+    #[test]
+    fn test_replace_view_keeps_state() {
+        let description = r#"{
+            "subviews": [
+                { "type": "TextField", "identifier": "name", "frame": [0, 0, 100, 30] },
+                { "type": "TextField", "frame": [0, 40, 100, 30] },
+                {
+                    "type": "ScrollView",
+                    "identifier": "list",
+                    "frame": [0, 80, 100, 100],
+                    "content": { "frame": [0, 0, 100, 400] }
+                }
+            ]
+        }"#;
+
+        let superview = View::new(Rectangle::new(0, 0, 200, 200));
+        let old_view = load_str(description).unwrap();
+        superview.add_subview(View::new(Rectangle::new(0, 0, 10, 10)));
+        superview.add_subview(old_view.clone());
+        superview.add_subview(View::new(Rectangle::new(0, 0, 10, 10)));
+
+        let old_subviews = old_view.subviews();
+        TextField::from_view(old_subviews[0].clone()).label().set_text(String::from("Ada"));
+        TextField::from_view(old_subviews[1].clone()).label().set_text(String::from("Lost"));
+        ScrollView::from_view(old_subviews[2].clone()).set_content_offset(Point::new(0, 120));
+
+        let new_view = load_str(description).unwrap();
+        replace_view(&old_view, &new_view);
+
+        assert_eq!(superview.subviews().len(), 3);
+        assert!(superview.subviews()[1] == new_view);
+
+        let new_subviews = new_view.subviews();
+        assert_eq!(TextField::from_view(new_subviews[0].clone()).label().copy_text(), "Ada");
+        assert_eq!(TextField::from_view(new_subviews[1].clone()).label().copy_text(), "");
+        assert_eq!(ScrollView::from_view(new_subviews[2].clone()).content_offset(), Point::new(0, 120));
+
+        // A view taken out of its superview stays out once reloaded.
+        new_view.remove_from_superview();
+        let newer_view = load_str(description).unwrap();
+        replace_view(&new_view, &newer_view);
+        assert_eq!(superview.subviews().len(), 2);
+        assert!(!superview.subviews().contains(&newer_view));
+    }
+}
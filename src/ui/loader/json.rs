@@ -0,0 +1,295 @@
+//! A small JSON parser for view descriptions and string catalogs. Objects keep their keys in
+//! the order they were written, so views are built in that order too.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// How deep arrays and objects can be nested, so a deeply nested file is an
+/// error rather than running out of stack.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>)
+}
+
+impl Value {
+    /// A short description of the kind of value, for errors.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object"
+        }
+    }
+}
+
+/// Parses `source` as a single JSON value. Errors give the line and column,
+/// from 1, where parsing stopped.
+pub(crate) fn parse(source: &str) -> Result<Value, String> {
+    let mut parser = Parser { characters: source.chars().peekable(), line: 1, column: 1, depth: 0 };
+
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    match parser.characters.peek().copied() {
+        None => Ok(value),
+        Some(character) => Err(parser.error(&format!("unexpected {:?} after the end of the description", character)))
+    }
+}
+
+struct Parser<'a> {
+    characters: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+
+    // How many arrays and objects the parser is inside.
+    depth: usize
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("line {}, column {}: {}", self.line, self.column, message)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.characters.next()?;
+        if character == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(character)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.characters.peek().is_some_and(|character| character.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.characters.peek().copied() {
+            Some(character) if character == expected => {
+                self.next();
+                Ok(())
+            },
+            Some(character) => Err(self.error(&format!("expected {:?} but found {:?}", expected, character))),
+            None => Err(self.error(&format!("expected {:?} but the description ended", expected)))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.characters.peek().copied() {
+            Some('{') => self.parse_nested(Self::parse_object),
+            Some('[') => self.parse_nested(Self::parse_array),
+            Some('"') => Ok(Value::String(self.parse_string()?)),
+            Some('-') | Some('0'..='9') => self.parse_number(),
+            Some('t') => self.parse_keyword("true", Value::Bool(true)),
+            Some('f') => self.parse_keyword("false", Value::Bool(false)),
+            Some('n') => self.parse_keyword("null", Value::Null),
+            Some(character) => Err(self.error(&format!("unexpected {:?}", character))),
+            None => Err(self.error("expected a value but the description ended"))
+        }
+    }
+
+    /// Parses an array or object with `parse`, one level deeper.
+    fn parse_nested(&mut self, parse: fn(&mut Self) -> Result<Value, String>) -> Result<Value, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(&format!("arrays and objects are nested more than {} deep", MAX_DEPTH)));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_keyword(&mut self, keyword: &str, value: Value) -> Result<Value, String> {
+        for expected in keyword.chars() {
+            if self.characters.peek() != Some(&expected) {
+                return Err(self.error(&format!("expected {:?}", keyword)));
+            }
+            self.next();
+        }
+        Ok(value)
+    }
+
+    fn parse_number(&mut self) -> Result<Value, String> {
+        let mut number = String::new();
+        while let Some(&character) = self.characters.peek() {
+            if !(character.is_ascii_digit() || "+-.eE".contains(character)) {
+                break;
+            }
+            number.push(character);
+            self.next();
+        }
+
+        number.parse().map(Value::Number).map_err(|_| self.error(&format!("{:?} isn't a number", number)))
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.parse_escape()?),
+                Some(character) => string.push(character),
+                None => return Err(self.error("a string isn't closed"))
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, String> {
+        match self.next() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('/') => Ok('/'),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('u') => {
+                let code = self.parse_code_unit()?;
+
+                // Characters outside the Basic Multilingual Plane are written
+                // as a high surrogate followed by a low one.
+                let code = if (0xD800..0xDC00).contains(&code) {
+                    if self.next() != Some('\\') || self.next() != Some('u') {
+                        return Err(self.error(&format!("\\u{:04x} isn't followed by a low surrogate", code)));
+                    }
+                    let low = self.parse_code_unit()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error(&format!("\\u{:04x} isn't a low surrogate", low)));
+                    }
+                    0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    code
+                };
+
+                char::from_u32(code).ok_or_else(|| self.error(&format!("\\u{:04x} isn't a character", code)))
+            },
+            Some(character) => Err(self.error(&format!("\\{} isn't an escape", character))),
+            None => Err(self.error("a string isn't closed"))
+        }
+    }
+
+    /// The four hex digits of a `\u` escape.
+    fn parse_code_unit(&mut self) -> Result<u32, String> {
+        let digits: String = (0..4).filter_map(|_| self.next()).collect();
+        match u32::from_str_radix(&digits, 16) {
+            Ok(code) if digits.len() == 4 => Ok(code),
+            _ => Err(self.error(&format!("\\u{} isn't a character", digits)))
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.characters.peek() == Some(&']') {
+            self.next();
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.parse_value()?);
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return Err(self.error("expected ',' or ']' in an array"))
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+
+        let mut members: Vec<(String, Value)> = Vec::new();
+        self.skip_whitespace();
+        if self.characters.peek() == Some(&'}') {
+            self.next();
+            return Ok(Value::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            if members.iter().any(|(existing, _)| *existing == key) {
+                return Err(self.error(&format!("the key {:?} is given more than once", key)));
+            }
+
+            self.expect(':')?;
+            members.push((key, self.parse_value()?));
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Object(members)),
+                _ => return Err(self.error("expected ',' or '}' in an object"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values() {
+        let value = parse(r#"{"name": "ok", "size": [1, -2.5, 3e2], "enabled": true, "image": null, "text": "a\né\"b"}"#).unwrap();
+
+        assert_eq!(value, Value::Object(vec![
+            (String::from("name"), Value::String(String::from("ok"))),
+            (String::from("size"), Value::Array(vec![Value::Number(1.0), Value::Number(-2.5), Value::Number(300.0)])),
+            (String::from("enabled"), Value::Bool(true)),
+            (String::from("image"), Value::Null),
+            (String::from("text"), Value::String(String::from("a\n\u{e9}\"b")))
+        ]));
+        assert_eq!(parse(" [ ] ").unwrap(), Value::Array(Vec::new()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("{\n  \"a\": 1,\n  \"b\" 2\n}").unwrap_err(), "line 3, column 7: expected ':' but found '2'");
+        assert_eq!(parse("[1, 2").unwrap_err(), "line 1, column 6: expected ',' or ']' in an array");
+        assert_eq!(parse("{\"a\": 1, \"a\": 2}").unwrap_err(), "line 1, column 13: the key \"a\" is given more than once");
+        assert_eq!(parse("1 2").unwrap_err(), "line 1, column 3: unexpected '2' after the end of the description");
+        assert!(parse("\"open").is_err());
+        assert!(parse("tru").is_err());
+        assert!(parse(r#""\ud83d""#).is_err());
+        assert!(parse(r#""\ude00""#).is_err());
+    }
+
+    #[test]
+    fn test_parse_surrogate_pair() {
+        assert_eq!(parse(r#""\ud83d\ude00!""#).unwrap(), Value::String(String::from("\u{1f600}!")));
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse(&nested(100_000)).unwrap_err(),
+            format!("line 1, column {}: arrays and objects are nested more than {} deep", MAX_DEPTH + 1, MAX_DEPTH)
+        );
+    }
+}
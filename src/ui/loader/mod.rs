@@ -0,0 +1,621 @@
+//! Builds view hierarchies from descriptions written in JSON, so a UI can be
+//! changed without recompiling, and tests can load the same fixtures.
+//!
+//! A description is an object for the root view, with its subviews nested
+//! inside it:
+//!
+//! ```json
+//! {
+//!     "type": "View",
+//!     "frame": [0, 0, 320, 200],
+//!     "background_color": "background",
+//!     "subviews": [
+//!         {
+//!             "type": "Label",
+//!             "identifier": "title",
+//!             "frame": [16, 16, 288, 24],
+//!             "text": "Hello",
+//!             "font": "Helvetica Bold 17",
+//!             "text_color": "#333333",
+//!             "autoresizing": ["flexible_width"]
+//!         },
+//!         { "type": "Button", "identifier": "ok", "frame": [16, 150, 80, 32], "title": "OK" }
+//!     ]
+//! }
+//! ```
+//!
+//! Every view takes `type`, which defaults to "View", `identifier`, `frame`
+//! as `[x, y, width, height]`, `background_color`, `corner_radius`,
+//! `alpha`, `hidden`, `clips_to_bounds`, `autoresizing`, `layout_margins`,
+//! `layout_direction` and `subviews`. On top of those:
+//!
+//! - "Label" takes `text`, `font`, `text_color`, `alignment`,
+//!   `vertical_alignment`, `number_of_lines` and `content_insets`.
+//! - "TextField" takes `text`, `font`, `text_color` and `content_insets`.
+//! - "Button" takes `title` and `title_color`.
+//! - "ImageView" takes `image`, the name of an image as given to
+//!   `Image::new`, and `content_mode`.
+//! - "ScrollView" takes `content`, the description of its content view, and
+//!   `content_insets`.
+//!
+//! Colors are "#rrggbb" or "#rrggbbaa", `[red, green, blue]` or
+//! `[red, green, blue, alpha]` from 0 to 255, a plain color name such as
+//! "white" or "clear", or a semantic color name such as "label" or
+//! "accent", which follows the appearance. Fonts are descriptors such as
+//! "Helvetica Bold 17", see `Font::from_descriptor_string`. Insets are one
+//! number for every edge, or `[top, left, bottom, right]`. Names such as
+//! "flexible_width" or "aspect_fit" are written in snake case.
+//!
+//! Views are found in the loaded hierarchy by identifier, so no two views
+//! can be given the same one, and actions are hooked up from code:
+//!
+//! ```ignore
+//! let view = loader::load_file("ui/settings.json")?;
+//! let ok = Button::from_view(view.view_with_identifier("ok").unwrap());
+//! ok.on_tap(|| println!("OK"));
+//! ```
+//!
+//! While working on a UI, `HotReload` rebuilds the view each time its file
+//! is saved.
+
+pub(crate) mod json;
+use json::Value;
+
+mod hot_reload;
+pub use hot_reload::HotReload;
+
+use crate::graphics::{EdgeInsets, Font, Image, Rectangle};
+use crate::platform::bundle::Bundle;
+use crate::text::{HorizontalAlignment, VerticalAlignment};
+use crate::ui::view::AutoresizingMask;
+use crate::ui::layout_direction::LayoutDirection;
+use crate::ui::{Button, Color, ContentMode, ImageView, Label, ScrollView, SemanticColor, TextField, View};
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+/// The keys every view takes, whatever its type.
+const VIEW_KEYS: [&str; 12] = [
+    "type",
+    "identifier",
+    "frame",
+    "background_color",
+    "corner_radius",
+    "alpha",
+    "hidden",
+    "clips_to_bounds",
+    "autoresizing",
+    "layout_margins",
+    "layout_direction",
+    "subviews"
+];
+
+/// Builds the view hierarchy described by `description`.
+///
+/// Errors say where the description went wrong, such as
+/// "line 3, column 7: expected ':' but found '2'" for invalid JSON, or
+/// "subviews[1].frame: expected [x, y, width, height]" for a view that
+/// can't be built.
+pub fn load_str(description: &str) -> Result<View, String> {
+    let value = json::parse(description)?;
+    build_view(&value, "root", &RefCell::new(HashSet::new()))
+}
+
+/// Builds the view hierarchy described in the file `name`. A relative path
+/// is relative to the application's `resource` directory.
+pub fn load_file(name: &str) -> Result<View, String> {
+    let path = Bundle::path_for_resource(name);
+    let description = std::fs::read_to_string(&path).map_err(|error| format!("{}: {}", path, error))?;
+    load_str(&description).map_err(|error| format!("{}: {}", path, error))
+}
+
+/// The members of the object describing a view, looked up by key.
+struct Description<'a> {
+    members: &'a [(String, Value)],
+
+    /// Where the view is in the description, for errors.
+    path: &'a str,
+
+    /// The identifiers of the views built so far, as two views in the same
+    /// hierarchy can't share one.
+    identifiers: &'a RefCell<HashSet<String>>
+}
+
+impl<'a> Description<'a> {
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.members.iter().find(|(member_key, _)| member_key == key).map(|(_, value)| value)
+    }
+
+    fn error(&self, key: &str, message: &str) -> String {
+        if self.path == "root" {
+            format!("{}: {}", key, message)
+        } else {
+            format!("{}.{}: {}", self.path, key, message)
+        }
+    }
+
+    fn string(&self, key: &str) -> Result<Option<&'a str>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::String(string)) => Ok(Some(string)),
+            Some(value) => Err(self.error(key, &format!("expected a string but found {}", value.kind())))
+        }
+    }
+
+    fn number(&self, key: &str) -> Result<Option<f64>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::Number(number)) => Ok(Some(*number)),
+            Some(value) => Err(self.error(key, &format!("expected a number but found {}", value.kind())))
+        }
+    }
+
+    fn bool(&self, key: &str) -> Result<Option<bool>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::Bool(bool)) => Ok(Some(*bool)),
+            Some(value) => Err(self.error(key, &format!("expected a boolean but found {}", value.kind())))
+        }
+    }
+
+    /// The value at `key` converted by `parse`, which returns `None` for a
+    /// value it doesn't understand, described by `expected` in the error.
+    fn parsed<T>(&self, key: &str, expected: &str, parse: impl Fn(&Value) -> Option<T>) -> Result<Option<T>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(value) => parse(value).map(Some).ok_or_else(|| self.error(key, &format!("expected {}", expected)))
+        }
+    }
+
+    fn frame(&self) -> Result<Rectangle<i32, u32>, String> {
+        let frame = self.parsed("frame", "[x, y, width, height]", |value| {
+            match numbers(value)?.as_slice() {
+                [x, y, width, height] if *width >= 0.0 && *height >= 0.0 => {
+                    Some(Rectangle::new(*x as i32, *y as i32, *width as u32, *height as u32))
+                },
+                _ => None
+            }
+        })?;
+        Ok(frame.unwrap_or_else(|| Rectangle::new(0, 0, 0, 0)))
+    }
+
+    fn color(&self, key: &str) -> Result<Option<Color>, String> {
+        self.parsed(key, "a color such as \"#rrggbb\", [red, green, blue] or \"white\"", parse_color)
+    }
+
+    fn insets(&self, key: &str) -> Result<Option<EdgeInsets>, String> {
+        self.parsed(key, "a number or [top, left, bottom, right]", |value| {
+            match value {
+                Value::Number(inset) => Some(EdgeInsets::uniform(*inset as i32)),
+                _ => match numbers(value)?.as_slice() {
+                    [top, left, bottom, right] => Some(EdgeInsets::new(*top as i32, *left as i32, *bottom as i32, *right as i32)),
+                    _ => None
+                }
+            }
+        })
+    }
+
+    fn font(&self) -> Result<Option<Font>, String> {
+        match self.string("font")? {
+            None => Ok(None),
+            Some(descriptor) => Font::from_descriptor_string(descriptor)
+                .map(Some)
+                .ok_or_else(|| self.error("font", &format!("no installed font matches {:?}", descriptor)))
+        }
+    }
+
+    /// Errors on the first key that isn't in `VIEW_KEYS` or `type_keys`,
+    /// as it's most likely misspelled.
+    fn check_keys(&self, type_name: &str, type_keys: &[&str]) -> Result<(), String> {
+        match self.members.iter().find(|(key, _)| !VIEW_KEYS.contains(&key.as_str()) && !type_keys.contains(&key.as_str())) {
+            Some((key, _)) => Err(self.error(key, &format!("isn't a property of {}", type_name))),
+            None => Ok(())
+        }
+    }
+}
+
+/// The numbers in an array of numbers.
+fn numbers(value: &Value) -> Option<Vec<f64>> {
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                Value::Number(number) => Some(*number),
+                _ => None
+            })
+            .collect(),
+        _ => None
+    }
+}
+
+fn parse_color(value: &Value) -> Option<Color> {
+    match value {
+        Value::String(string) => match string.strip_prefix('#') {
+            Some(hex) => parse_hex_color(hex),
+            None => named_color(string)
+        },
+        _ => {
+            let component = |number: f64| (0.0..=255.0).contains(&number).then_some(number as u8);
+            match numbers(value)?.as_slice() {
+                [red, green, blue] => Some(Color::new(component(*red)?, component(*green)?, component(*blue)?, 255)),
+                [red, green, blue, alpha] => Some(Color::new(component(*red)?, component(*green)?, component(*blue)?, component(*alpha)?)),
+                _ => None
+            }
+        }
+    }
+}
+
+/// A color from "rrggbb" or "rrggbbaa".
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|character| character.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let component = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    let alpha = if hex.len() == 8 { component(6)? } else { 255 };
+    Some(Color::new(component(0)?, component(2)?, component(4)?, alpha))
+}
+
+fn named_color(name: &str) -> Option<Color> {
+    let color = match name {
+        "white" => Color::white(),
+        "black" => Color::black(),
+        "red" => Color::red(),
+        "green" => Color::green(),
+        "blue" => Color::blue(),
+        "gray" => Color::gray(),
+        "clear" => Color::clear(),
+        "label" => Color::semantic(SemanticColor::Label),
+        "secondary_label" => Color::semantic(SemanticColor::SecondaryLabel),
+        "background" => Color::semantic(SemanticColor::Background),
+        "secondary_background" => Color::semantic(SemanticColor::SecondaryBackground),
+        "separator" => Color::semantic(SemanticColor::Separator),
+        "accent" => Color::semantic(SemanticColor::Accent),
+        _ => return None
+    };
+    Some(color)
+}
+
+fn layout_direction(value: &Value) -> Option<LayoutDirection> {
+    match value {
+        Value::String(name) if name == "left_to_right" => Some(LayoutDirection::LeftToRight),
+        Value::String(name) if name == "right_to_left" => Some(LayoutDirection::RightToLeft),
+        _ => None
+    }
+}
+
+fn autoresizing_mask(name: &str) -> Option<AutoresizingMask> {
+    match name {
+        "flexible_left_margin" => Some(AutoresizingMask::FlexibleLeftMargin),
+        "flexible_width" => Some(AutoresizingMask::FlexibleWidth),
+        "flexible_right_margin" => Some(AutoresizingMask::FlexibleRightMargin),
+        "flexible_leading_margin" => Some(AutoresizingMask::FlexibleLeadingMargin),
+        "flexible_trailing_margin" => Some(AutoresizingMask::FlexibleTrailingMargin),
+        "flexible_top_margin" => Some(AutoresizingMask::FlexibleTopMargin),
+        "flexible_height" => Some(AutoresizingMask::FlexibleHeight),
+        "flexible_bottom_margin" => Some(AutoresizingMask::FlexibleBottomMargin),
+        _ => None
+    }
+}
+
+fn horizontal_alignment(value: &Value) -> Option<HorizontalAlignment> {
+    match value {
+        Value::String(name) if name == "natural" => Some(HorizontalAlignment::Natural),
+        Value::String(name) if name == "left" => Some(HorizontalAlignment::Left),
+        Value::String(name) if name == "center" => Some(HorizontalAlignment::Center),
+        Value::String(name) if name == "right" => Some(HorizontalAlignment::Right),
+        Value::String(name) if name == "justified" => Some(HorizontalAlignment::Justified),
+        _ => None
+    }
+}
+
+fn vertical_alignment(value: &Value) -> Option<VerticalAlignment> {
+    match value {
+        Value::String(name) if name == "top" => Some(VerticalAlignment::Top),
+        Value::String(name) if name == "middle" => Some(VerticalAlignment::Middle),
+        Value::String(name) if name == "bottom" => Some(VerticalAlignment::Bottom),
+        _ => None
+    }
+}
+
+fn content_mode(value: &Value) -> Option<ContentMode> {
+    match value {
+        Value::String(name) if name == "scale_to_fill" => Some(ContentMode::ScaleToFill),
+        Value::String(name) if name == "aspect_fit" => Some(ContentMode::AspectFit),
+        Value::String(name) if name == "aspect_fill" => Some(ContentMode::AspectFill),
+        Value::String(name) if name == "center" => Some(ContentMode::Center),
+        _ => None
+    }
+}
+
+/// Builds the view described by `value`, found at `path` in the
+/// description, and its subviews. `identifiers` are those already given to
+/// views in the hierarchy.
+fn build_view(value: &Value, path: &str, identifiers: &RefCell<HashSet<String>>) -> Result<View, String> {
+    let members = match value {
+        Value::Object(members) => members,
+        _ => return Err(format!("{}: expected an object describing a view but found {}", path, value.kind()))
+    };
+    let description = Description { members, path, identifiers };
+
+    let type_name = description.string("type")?.unwrap_or("View");
+    let frame = description.frame()?;
+
+    let view = match type_name {
+        "View" => {
+            description.check_keys(type_name, &[])?;
+            View::new(frame)
+        },
+        "Label" => {
+            description.check_keys(type_name, &["text", "font", "text_color", "alignment", "vertical_alignment", "number_of_lines", "content_insets"])?;
+            build_label(&description, frame)?
+        },
+        "TextField" => {
+            description.check_keys(type_name, &["text", "font", "text_color", "content_insets"])?;
+            build_text_field(&description, frame)?
+        },
+        "Button" => {
+            description.check_keys(type_name, &["title", "title_color"])?;
+            build_button(&description, frame)?
+        },
+        "ImageView" => {
+            description.check_keys(type_name, &["image", "content_mode"])?;
+            build_image_view(&description, frame)?
+        },
+        "ScrollView" => {
+            description.check_keys(type_name, &["content", "content_insets"])?;
+            build_scroll_view(&description, frame)?
+        },
+        _ => return Err(description.error("type", &format!("{:?} isn't a type of view that can be loaded", type_name)))
+    };
+
+    apply_view_properties(&description, &view)?;
+
+    if let Some(subviews) = description.get("subviews") {
+        let subviews = match subviews {
+            Value::Array(subviews) => subviews,
+            _ => return Err(description.error("subviews", &format!("expected an array but found {}", subviews.kind())))
+        };
+
+        for (index, subview) in subviews.iter().enumerate() {
+            let subview_path = if path == "root" {
+                format!("subviews[{}]", index)
+            } else {
+                format!("{}.subviews[{}]", path, index)
+            };
+            view.add_subview(build_view(subview, &subview_path, identifiers)?);
+        }
+    }
+
+    Ok(view)
+}
+
+/// Sets the properties every view has, after the view is built for its
+/// type.
+fn apply_view_properties(description: &Description, view: &View) -> Result<(), String> {
+    if let Some(identifier) = description.string("identifier")? {
+        if !description.identifiers.borrow_mut().insert(String::from(identifier)) {
+            return Err(description.error("identifier", &format!("{:?} is already the identifier of another view", identifier)));
+        }
+        view.set_identifier(Some(identifier));
+    }
+
+    if let Some(color) = description.color("background_color")? {
+        view.set_background_color(color);
+    }
+
+    if let Some(corner_radius) = description.number("corner_radius")? {
+        view.set_corner_radius(corner_radius as f32);
+    }
+
+    if let Some(alpha) = description.number("alpha")? {
+        view.set_alpha(alpha as f32);
+    }
+
+    if let Some(hidden) = description.bool("hidden")? {
+        view.set_hidden(hidden);
+    }
+
+    if let Some(clips_to_bounds) = description.bool("clips_to_bounds")? {
+        view.set_clips_to_bounds(clips_to_bounds);
+    }
+
+    let masks = description.parsed("autoresizing", "an array of names such as \"flexible_width\"", |value| {
+        match value {
+            Value::Array(values) => values
+                .iter()
+                .map(|value| match value {
+                    Value::String(name) => autoresizing_mask(name),
+                    _ => None
+                })
+                .collect::<Option<Vec<AutoresizingMask>>>(),
+            _ => None
+        }
+    })?;
+    if let Some(masks) = masks {
+        view.set_autoresizing_mask(&masks);
+    }
+
+    if let Some(layout_margins) = description.insets("layout_margins")? {
+        view.set_layout_margins(layout_margins);
+    }
+
+    if let Some(direction) = description.parsed("layout_direction", "\"left_to_right\" or \"right_to_left\"", layout_direction)? {
+        view.set_layout_direction(Some(direction));
+    }
+
+    Ok(())
+}
+
+fn build_label(description: &Description, frame: Rectangle<i32, u32>) -> Result<View, String> {
+    let label = Label::new(frame, String::from(description.string("text")?.unwrap_or("")));
+
+    if let Some(font) = description.font()? {
+        label.set_font(font);
+    }
+
+    if let Some(text_color) = description.color("text_color")? {
+        label.set_text_color(text_color);
+    }
+
+    if let Some(alignment) = description.parsed("alignment", "\"natural\", \"left\", \"center\", \"right\" or \"justified\"", horizontal_alignment)? {
+        label.set_text_alignment(alignment);
+    }
+
+    if let Some(alignment) = description.parsed("vertical_alignment", "\"top\", \"middle\" or \"bottom\"", vertical_alignment)? {
+        label.set_vertical_alignment(alignment);
+    }
+
+    if let Some(number_of_lines) = description.number("number_of_lines")? {
+        label.set_number_of_lines(number_of_lines.max(0.0) as usize);
+    }
+
+    if let Some(content_insets) = description.insets("content_insets")? {
+        label.set_content_insets(content_insets);
+    }
+
+    Ok(label.view)
+}
+
+fn build_text_field(description: &Description, frame: Rectangle<i32, u32>) -> Result<View, String> {
+    let text_field = TextField::new(frame, String::from(description.string("text")?.unwrap_or("")));
+
+    if let Some(font) = description.font()? {
+        text_field.label().set_font(font);
+    }
+
+    if let Some(text_color) = description.color("text_color")? {
+        text_field.label().set_text_color(text_color);
+    }
+
+    if let Some(content_insets) = description.insets("content_insets")? {
+        text_field.set_content_insets(content_insets);
+    }
+
+    Ok(text_field.view)
+}
+
+fn build_button(description: &Description, frame: Rectangle<i32, u32>) -> Result<View, String> {
+    // The action is hooked up from code, with `Button::on_tap`.
+    let button = Button::new(frame, description.string("title")?.unwrap_or(""), || {});
+
+    if let Some(title_color) = description.color("title_color")? {
+        button.set_text_color(title_color);
+    }
+
+    Ok(button.view)
+}
+
+fn build_image_view(description: &Description, frame: Rectangle<i32, u32>) -> Result<View, String> {
+    let name = description.string("image")?.ok_or_else(|| description.error("image", "an image view needs an image"))?;
+
+    // `Image::new` panics on a missing file, which a description shouldn't
+    // be able to cause.
+    let exists = |path: String| std::path::Path::new(&path).is_file();
+    if !exists(Bundle::path_for_resource(name)) && !exists(Image::scale_2x_name(name)) {
+        return Err(description.error("image", &format!("no image named {:?}", name)));
+    }
+
+    let image_view = ImageView::new_with_frame(Image::new(name), frame);
+
+    if let Some(content_mode) = description.parsed("content_mode", "\"scale_to_fill\", \"aspect_fit\", \"aspect_fill\" or \"center\"", content_mode)? {
+        image_view.set_content_mode(content_mode);
+    }
+
+    Ok(image_view.view)
+}
+
+fn build_scroll_view(description: &Description, frame: Rectangle<i32, u32>) -> Result<View, String> {
+    let scroll_view = ScrollView::new(frame);
+
+    // The insets go first, so the content view is placed inside them.
+    if let Some(content_insets) = description.insets("content_insets")? {
+        scroll_view.set_content_insets(content_insets);
+    }
+
+    if let Some(content) = description.get("content") {
+        let content_path = if description.path == "root" {
+            String::from("content")
+        } else {
+            format!("{}.content", description.path)
+        };
+        scroll_view.set_content_view(build_view(content, &content_path, description.identifiers)?);
+    }
+
+    Ok(scroll_view.view)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_hierarchy() {
+        let view = load_str(r##"{
+            "frame": [0, 0, 320, 200],
+            "identifier": "root",
+            "background_color": "#102030",
+            "layout_margins": 8,
+            "subviews": [
+                {
+                    "type": "Label",
+                    "identifier": "title",
+                    "frame": [16, 16, 288, 24],
+                    "text": "Hello",
+                    "alignment": "center",
+                    "autoresizing": ["flexible_width", "flexible_bottom_margin"]
+                },
+                { "identifier": "box", "frame": [16, 50, 40, 40], "background_color": [255, 0, 0, 128], "hidden": true }
+            ]
+        }"##).unwrap();
+
+        assert_eq!(view.frame(), Rectangle::new(0, 0, 320, 200));
+        assert_eq!(view.background_color(), Color::new(16, 32, 48, 255));
+        assert_eq!(view.layout_margins(), EdgeInsets::uniform(8));
+        assert_eq!(view.subviews().len(), 2);
+
+        let title = Label::from_view(view.view_with_identifier("title").unwrap());
+        assert_eq!(title.view.frame(), Rectangle::new(16, 16, 288, 24));
+        assert_eq!(title.copy_text(), "Hello");
+        assert_eq!(title.text_alignment(), HorizontalAlignment::Center);
+
+        let box_view = view.view_with_identifier("box").unwrap();
+        assert_eq!(box_view.background_color(), Color::new(255, 0, 0, 128));
+        assert!(box_view.is_hidden());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        assert_eq!(load_str("{\"frame\": [0, 0, 10]}").unwrap_err(), "frame: expected [x, y, width, height]");
+        assert_eq!(load_str("{\"type\": \"Slider\"}").unwrap_err(), "type: \"Slider\" isn't a type of view that can be loaded");
+        assert_eq!(
+            load_str("{\"subviews\": [{}, {\"type\": \"Label\", \"txt\": \"Hello\"}]}").unwrap_err(),
+            "subviews[1].txt: isn't a property of Label"
+        );
+        assert_eq!(
+            load_str("{\"subviews\": [{\"background_color\": \"#12345\"}]}").unwrap_err(),
+            "subviews[0].background_color: expected a color such as \"#rrggbb\", [red, green, blue] or \"white\""
+        );
+        assert!(load_str("[]").is_err());
+        assert_eq!(
+            load_str("{\"identifier\": \"title\", \"subviews\": [{}, {\"identifier\": \"title\"}]}").unwrap_err(),
+            "subviews[1].identifier: \"title\" is already the identifier of another view"
+        );
+        assert_eq!(
+            load_str("{\"type\": \"ScrollView\", \"content\": {\"identifier\": \"list\", \"subviews\": [{\"identifier\": \"list\"}]}}").unwrap_err(),
+            "content.subviews[0].identifier: \"list\" is already the identifier of another view"
+        );
+    }
+
+    #[test]
+    fn test_parse_colors() {
+        let color = |source: &str| parse_color(&json::parse(source).unwrap());
+
+        assert_eq!(color("\"#ff800040\""), Some(Color::new(255, 128, 0, 64)));
+        assert_eq!(color("[1, 2, 3]"), Some(Color::new(1, 2, 3, 255)));
+        assert_eq!(color("\"clear\""), Some(Color::clear()));
+        assert_eq!(color("\"accent\""), Some(Color::semantic(SemanticColor::Accent)));
+        assert_eq!(color("[1, 2, 300]"), None);
+        assert_eq!(color("\"#ggg000\""), None);
+        assert_eq!(color("\"mauve\""), None);
+    }
+}
@@ -0,0 +1,357 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The no-break space, which groups digits in locales such as French so
+/// numbers aren't split across lines.
+const NO_BREAK_SPACE: char = '\u{a0}';
+
+/// A language, and optionally the region it's used in, such as "en-US" or
+/// "fr". It picks the string catalog strings are looked up in, and how
+/// numbers and dates are written.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Locale {
+    identifier: String
+}
+
+/// The order the year, month and day of a date are written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DateOrder {
+    YearMonthDay,
+    DayMonthYear,
+    MonthDayYear
+}
+
+impl Locale {
+    /// The locale with `identifier`, such as "en-US". POSIX identifiers
+    /// such as "de_DE.UTF-8" are accepted too, and normalized to "de-DE".
+    pub fn new(identifier: &str) -> Locale {
+        let identifier = identifier.split(['.', '@']).next().unwrap_or("");
+        let mut parts = identifier.split(['-', '_']).filter(|part| !part.is_empty());
+
+        let mut normalized = parts.next().unwrap_or("en").to_lowercase();
+        if let Some(region) = parts.next() {
+            normalized.push('-');
+            normalized.push_str(&region.to_uppercase());
+        }
+
+        Locale { identifier: normalized }
+    }
+
+    /// The user's locale, from the `LC_ALL`, `LC_MESSAGES` or `LANG`
+    /// environment variables, or "en-US" if none of them are set, or
+    /// they're set to the "C" or "POSIX" locale.
+    pub fn from_environment() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|variable| std::env::var(variable).ok())
+            .find(|value| !value.is_empty())
+            .filter(|value| value != "C" && value != "POSIX" && !value.starts_with("C."))
+            .map_or_else(|| Locale::new("en-US"), |value| Locale::new(&value))
+    }
+
+    /// The normalized identifier, such as "en-US".
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The language code, such as "en".
+    pub fn language(&self) -> &str {
+        self.identifier.split('-').next().unwrap()
+    }
+
+    /// The region code, such as "US", if there is one.
+    pub fn region(&self) -> Option<&str> {
+        self.identifier.split('-').nth(1)
+    }
+
+    /// The character between the whole and fractional parts of a number.
+    pub fn decimal_separator(&self) -> char {
+        self.number_separators().0
+    }
+
+    /// The character between each group of three digits of large numbers.
+    pub fn grouping_separator(&self) -> char {
+        self.number_separators().1
+    }
+
+    fn number_separators(&self) -> (char, char) {
+        match (self.language(), self.region()) {
+            ("de", Some("CH")) | ("it", Some("CH")) => ('.', '\''),
+            ("de", _) | ("es", _) | ("it", _) | ("nl", _) | ("pt", _) | ("id", _) | ("tr", _) |
+            ("da", _) | ("el", _) | ("ro", _) | ("hr", _) | ("sl", _) | ("sr", _) | ("vi", _) => (',', '.'),
+            ("fr", _) | ("ru", _) | ("pl", _) | ("cs", _) | ("sk", _) | ("sv", _) | ("nb", _) |
+            ("no", _) | ("fi", _) | ("uk", _) | ("hu", _) | ("bg", _) | ("lt", _) | ("lv", _) | ("et", _) => (',', NO_BREAK_SPACE),
+            _ => ('.', ',')
+        }
+    }
+
+    /// `value` written with `fraction_digits` digits after the decimal
+    /// separator, rounded, and its whole part in groups of three digits,
+    /// such as "1,234.50" in "en-US" or "1.234,50" in "de-DE".
+    pub fn format_number(&self, value: f64, fraction_digits: usize) -> String {
+        if value.is_nan() {
+            return String::from("NaN");
+        } else if value.is_infinite() {
+            return String::from(if value < 0.0 { "-∞" } else { "∞" });
+        }
+
+        let (decimal_separator, grouping_separator) = self.number_separators();
+        let digits = format!("{:.*}", fraction_digits, value.abs());
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (digits.as_str(), None)
+        };
+
+        let mut formatted = String::new();
+        if value < 0.0 && digits.chars().any(|digit| digit != '0' && digit != '.') {
+            formatted.push('-');
+        }
+
+        for (index, digit) in whole.chars().enumerate() {
+            if index > 0 && (whole.len() - index) % 3 == 0 {
+                formatted.push(grouping_separator);
+            }
+            formatted.push(digit);
+        }
+
+        if let Some(fraction) = fraction {
+            formatted.push(decimal_separator);
+            formatted.push_str(fraction);
+        }
+
+        formatted
+    }
+
+    /// `value` written as a whole number, such as "1,234" in "en-US".
+    pub fn format_integer(&self, value: i64) -> String {
+        self.format_number(value as f64, 0)
+    }
+
+    /// The number in `text` written the way `format_number` writes it,
+    /// with or without grouping separators, such as "1.234,5" in "de-DE".
+    /// Returns `None` if it isn't a number.
+    pub fn parse_number(&self, text: &str) -> Option<f64> {
+        let (decimal_separator, grouping_separator) = self.number_separators();
+        let mut normalized = String::new();
+        for character in text.trim().chars() {
+            match character {
+                '0'..='9' | '-' => normalized.push(character),
+                character if character == decimal_separator => normalized.push('.'),
+                // A space stands in for a no-break space, which can't
+                // easily be typed.
+                character if character == grouping_separator || (grouping_separator == NO_BREAK_SPACE && character == ' ') => (),
+                _ => return None
+            }
+        }
+
+        if normalized.is_empty() || normalized.starts_with('.') || normalized.ends_with('.') {
+            return None;
+        }
+
+        normalized.parse().ok()
+    }
+
+    /// `date` written with numbers in the order, and with the separators,
+    /// used in the locale, such as "3/7/2024" in "en-US", "07.03.2024" in
+    /// "de-DE" or "2024/03/07" in "ja".
+    pub fn format_date(&self, date: &Date) -> String {
+        let (order, separator) = self.date_format();
+        match order {
+            DateOrder::MonthDayYear => format!("{}{sep}{}{sep}{}", date.month, date.day, date.year, sep = separator),
+            DateOrder::DayMonthYear => format!("{:02}{sep}{:02}{sep}{}", date.day, date.month, date.year, sep = separator),
+            DateOrder::YearMonthDay => format!("{}{sep}{:02}{sep}{:02}", date.year, date.month, date.day, sep = separator)
+        }
+    }
+
+    /// The date in `text` written in the order, and with the separators,
+    /// used in the locale, as `format_date` writes it. Returns `None` if it
+    /// isn't a date, or there's no such day.
+    pub fn parse_date(&self, text: &str) -> Option<Date> {
+        let (order, separator) = self.date_format();
+        let parts: Vec<&str> = text.trim().split(separator).collect();
+        if parts.len() != 3 || parts.iter().any(|part| part.is_empty() || !part.chars().all(|digit| digit.is_ascii_digit())) {
+            return None;
+        }
+
+        let (year, month, day) = match order {
+            DateOrder::MonthDayYear => (parts[2], parts[0], parts[1]),
+            DateOrder::DayMonthYear => (parts[2], parts[1], parts[0]),
+            DateOrder::YearMonthDay => (parts[0], parts[1], parts[2])
+        };
+
+        Date::new(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+    }
+
+    /// Whether `text` could be the start of a date written in the locale,
+    /// such as "12/" in "en-US", for checking dates as they're typed.
+    pub(crate) fn is_partial_date(&self, text: &str) -> bool {
+        let (order, separator) = self.date_format();
+        let maximum_lengths = match order {
+            DateOrder::MonthDayYear | DateOrder::DayMonthYear => [2, 2, 4],
+            DateOrder::YearMonthDay => [4, 2, 2]
+        };
+
+        let parts: Vec<&str> = text.split(separator).collect();
+        parts.len() <= 3 && parts
+            .iter()
+            .zip(maximum_lengths.iter())
+            .all(|(part, maximum_length)| part.len() <= *maximum_length && part.chars().all(|digit| digit.is_ascii_digit()))
+    }
+
+    fn date_format(&self) -> (DateOrder, char) {
+        match (self.language(), self.region()) {
+            ("en", None) | ("en", Some("US")) => (DateOrder::MonthDayYear, '/'),
+            ("en", _) | ("fr", _) | ("es", _) | ("it", _) | ("pt", _) | ("el", _) | ("vi", _) | ("id", _) => (DateOrder::DayMonthYear, '/'),
+            ("de", _) | ("ru", _) | ("pl", _) | ("cs", _) | ("sk", _) | ("fi", _) | ("nb", _) | ("no", _) |
+            ("da", _) | ("tr", _) | ("uk", _) | ("ro", _) | ("bg", _) | ("hr", _) | ("sl", _) | ("sr", _) => (DateOrder::DayMonthYear, '.'),
+            ("nl", _) => (DateOrder::DayMonthYear, '-'),
+            ("ja", _) | ("zh", _) => (DateOrder::YearMonthDay, '/'),
+            _ => (DateOrder::YearMonthDay, '-')
+        }
+    }
+}
+
+/// A day in the Gregorian calendar, such as to format with
+/// `Locale::format_date`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+
+    /// From 1 for January to 12.
+    pub month: u32,
+
+    /// From 1.
+    pub day: u32
+}
+
+impl Date {
+    /// The date, or `None` if there's no such day, such as February 30th.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Date { year, month, day })
+    }
+
+    /// The date of `time` in UTC.
+    pub fn from_system_time(time: SystemTime) -> Date {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs() as i64,
+            // Times before 1970 round down to the second before.
+            Err(error) => {
+                let duration = error.duration();
+                -(duration.as_secs() as i64) - if duration.subsec_nanos() > 0 { 1 } else { 0 }
+            }
+        };
+
+        Date::from_days_since_epoch(seconds.div_euclid(86_400))
+    }
+
+    /// Today's date in UTC.
+    pub fn today() -> Date {
+        Date::from_system_time(SystemTime::now())
+    }
+
+    /// The date `days` after January 1st 1970, counting back for negative
+    /// days. See http://howardhinnant.github.io/date_algorithms.html
+    fn from_days_since_epoch(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+        let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 } as u32;
+        let year = (year_of_era + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+
+        Date { year, month, day }
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_new() {
+        let locale = Locale::new("de_DE.UTF-8");
+        assert_eq!(locale.identifier(), "de-DE");
+        assert_eq!(locale.language(), "de");
+        assert_eq!(locale.region(), Some("DE"));
+
+        let locale = Locale::new("FR");
+        assert_eq!(locale.identifier(), "fr");
+        assert_eq!(locale.region(), None);
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(Locale::new("en-US").format_number(1234567.891, 2), "1,234,567.89");
+        assert_eq!(Locale::new("de-DE").format_number(1234.5, 2), "1.234,50");
+        assert_eq!(Locale::new("fr-FR").format_number(-1234.5, 1), "-1\u{a0}234,5");
+        assert_eq!(Locale::new("de-CH").format_integer(1_000_000), "1'000'000");
+        assert_eq!(Locale::new("en").format_number(-0.001, 2), "0.00");
+        assert_eq!(Locale::new("en").format_integer(999), "999");
+        assert_eq!(Locale::new("en").format_number(f64::NEG_INFINITY, 2), "-∞");
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = Date::new(2024, 3, 7).unwrap();
+        assert_eq!(Locale::new("en-US").format_date(&date), "3/7/2024");
+        assert_eq!(Locale::new("en-GB").format_date(&date), "07/03/2024");
+        assert_eq!(Locale::new("de-DE").format_date(&date), "07.03.2024");
+        assert_eq!(Locale::new("ja-JP").format_date(&date), "2024/03/07");
+        assert_eq!(Locale::new("sv-SE").format_date(&date), "2024-03-07");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Locale::new("en-US").parse_number("1,234.5"), Some(1234.5));
+        assert_eq!(Locale::new("de-DE").parse_number("-1.234,5"), Some(-1234.5));
+        assert_eq!(Locale::new("fr").parse_number("1 234,5"), Some(1234.5));
+        assert_eq!(Locale::new("en-US").parse_number("1,234,5.0.0"), None);
+        assert_eq!(Locale::new("en-US").parse_number("12a"), None);
+        assert_eq!(Locale::new("en-US").parse_number("-"), None);
+
+        let date = Date::new(2024, 3, 7);
+        assert_eq!(Locale::new("en-US").parse_date("3/7/2024"), date);
+        assert_eq!(Locale::new("de-DE").parse_date("07.03.2024"), date);
+        assert_eq!(Locale::new("ja").parse_date("2024/3/7"), date);
+        assert_eq!(Locale::new("en-US").parse_date("2/30/2024"), None);
+        assert_eq!(Locale::new("en-US").parse_date("3/7"), None);
+
+        assert!(Locale::new("en-US").is_partial_date("12/"));
+        assert!(Locale::new("en-US").is_partial_date(""));
+        assert!(!Locale::new("en-US").is_partial_date("123"));
+        assert!(!Locale::new("en-US").is_partial_date("1/2/3/4"));
+        assert!(!Locale::new("de-DE").is_partial_date("1/2"));
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(Date::new(2024, 2, 29), Some(Date { year: 2024, month: 2, day: 29 }));
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(Date::new(2023, 13, 1), None);
+
+        assert_eq!(Date::from_system_time(UNIX_EPOCH), Date::new(1970, 1, 1).unwrap());
+        assert_eq!(Date::from_system_time(UNIX_EPOCH + Duration::from_secs(1_709_807_400)), Date::new(2024, 3, 7).unwrap());
+        assert_eq!(Date::from_system_time(UNIX_EPOCH - Duration::from_secs(1)), Date::new(1969, 12, 31).unwrap());
+        assert_eq!(Date::from_system_time(UNIX_EPOCH - Duration::from_secs(86_400)), Date::new(1969, 12, 31).unwrap());
+    }
+}
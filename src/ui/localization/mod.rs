@@ -0,0 +1,282 @@
+//! Translations of the application's strings, and writing numbers and dates
+//! the way the user's locale does.
+//!
+//! Strings are kept in string catalogs, one per locale, loaded at launch
+//! from the JSON files in the `localization` directory of the application's
+//! resources, named for their locale:
+//!
+//! ```json
+//! {
+//!     "Save": "Sichern",
+//!     "Saved {0} files": "{0} Dateien gesichert"
+//! }
+//! ```
+//!
+//! A string is looked up in the catalog for the current locale, such as
+//! `de-DE.json`, then in the catalog for its language, `de.json`. Keys
+//! without a translation are used as they are, so keys are best written as
+//! the strings in the application's own language.
+//!
+//! ```ignore
+//! label.set_text(localized("Save"));
+//! label.set_text(localized_format("Saved {0} files", &[&localization::locale().format_integer(3)]));
+//!
+//! // Follows the locale as it changes.
+//! button.set_localized_title("Save");
+//! ```
+//!
+//! Switching the locale with `set_locale` updates the labels and buttons
+//! set with localized strings, and posts `notification::LOCALE_DID_CHANGE`
+//! for anything else to fetch its strings again.
+
+mod locale;
+pub use locale::{Date, Locale};
+
+use crate::macros::*;
+use crate::platform::bundle::Bundle;
+use crate::ui::application::Application;
+use crate::ui::layout_direction::LayoutDirection;
+use crate::ui::loader::json::{self, Value};
+use crate::ui::notification::{self, Notification, NotificationCenter};
+use std::collections::HashMap;
+
+/// The directory in the application's resources that string catalogs are
+/// loaded from at launch.
+const CATALOG_DIRECTORY: &str = "localization";
+
+/// The translations of the application's strings into one locale.
+#[derive(Clone, Debug)]
+pub struct StringCatalog {
+    locale: Locale,
+    strings: HashMap<String, String>
+}
+
+impl StringCatalog {
+    pub fn new(locale: Locale) -> StringCatalog {
+        StringCatalog { locale, strings: HashMap::new() }
+    }
+
+    /// The catalog for `locale` written in `source`, a JSON object of
+    /// strings keyed by the strings they translate.
+    pub fn parse(locale: Locale, source: &str) -> Result<StringCatalog, String> {
+        let members = match json::parse(source)? {
+            Value::Object(members) => members,
+            value => return Err(format!("expected an object of strings, but found {}", value.kind()))
+        };
+
+        let mut catalog = StringCatalog::new(locale);
+        for (key, value) in members {
+            match value {
+                Value::String(string) => catalog.insert(&key, &string),
+                value => return Err(format!("{}: expected a string, but found {}", key, value.kind()))
+            }
+        }
+
+        Ok(catalog)
+    }
+
+    /// Loads the catalog in the file `name`, for the locale it's named
+    /// after, such as "localization/fr-CA.json". A relative path is
+    /// relative to the application's `resource` directory.
+    pub fn load_file(name: &str) -> Result<StringCatalog, String> {
+        let path = Bundle::path_for_resource(name);
+        let locale = std::path::Path::new(&path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(Locale::new)
+            .ok_or_else(|| format!("{}: the file isn't named for a locale", path))?;
+
+        let source = std::fs::read_to_string(&path).map_err(|error| format!("{}: {}", path, error))?;
+        StringCatalog::parse(locale, &source).map_err(|error| format!("{}: {}", path, error))
+    }
+
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    /// Adds the translation of `key`, replacing any it had.
+    pub fn insert(&mut self, key: &str, string: &str) {
+        self.strings.insert(key.to_string(), string.to_string());
+    }
+
+    /// The translation of `key`, if the catalog has one.
+    pub fn string(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(String::as_str)
+    }
+}
+
+struct Localization {
+    /// The locale set with `set_locale`, or the user's once it's been read
+    /// from the environment, so it's only read once.
+    locale: Option<Locale>,
+    catalogs: Vec<StringCatalog>
+}
+
+singleton!(
+    Localization,
+    locale: None,
+    catalogs: Vec::new()
+);
+
+/// The locale strings are translated for. It's the user's locale, see
+/// `Locale::from_environment`, unless switched with `set_locale`.
+pub fn locale() -> Locale {
+    with_locale(Locale::clone)
+}
+
+/// The layout direction of the locale, see `LayoutDirection::for_locale`.
+/// Views ask for it as they're drawn, so it's worked out without copying
+/// the locale.
+pub(crate) fn layout_direction() -> LayoutDirection {
+    with_locale(LayoutDirection::for_locale)
+}
+
+/// Calls `f` with the locale, reading the user's from the environment the
+/// first time it's needed.
+fn with_locale<T>(f: impl FnOnce(&Locale) -> T) -> T {
+    if Localization::borrow().locale.is_none() {
+        let locale = Locale::from_environment();
+        Localization::borrow_mut().locale = Some(locale);
+    }
+
+    f(Localization::borrow().locale.as_ref().unwrap())
+}
+
+/// Switches the locale strings are translated for, updating the labels and
+/// buttons in every window that were given localized strings, such as with
+/// `Label::set_localized_text`. Windows are mirrored if the locale reads
+/// the other way, see `LayoutDirection`. Posts
+/// `notification::LOCALE_DID_CHANGE`, so other strings can be fetched
+/// again.
+pub fn set_locale(locale: Locale) {
+    let previous = self::locale();
+    if previous == locale {
+        return;
+    }
+
+    let direction_did_change = LayoutDirection::for_locale(&previous) != LayoutDirection::for_locale(&locale);
+    Localization::borrow_mut().locale = Some(locale);
+
+    let windows = Application::borrow().windows().clone();
+    for window in windows.iter() {
+        window.view.locale_did_change();
+
+        if direction_did_change && window.view.layout_direction().is_none() {
+            window.view.layout_direction_did_change();
+        }
+    }
+
+    NotificationCenter::post(Notification::new(notification::LOCALE_DID_CHANGE));
+}
+
+/// Adds `catalog`, with its strings taking the place of those of a catalog
+/// already added for the same locale.
+pub fn add_catalog(catalog: StringCatalog) {
+    let mut localization = Localization::borrow_mut();
+    match localization.catalogs.iter_mut().find(|existing| existing.locale == catalog.locale) {
+        Some(existing) => existing.strings.extend(catalog.strings),
+        None => localization.catalogs.push(catalog)
+    }
+}
+
+/// Loads and adds every catalog in `directory`, a JSON file per locale.
+/// A relative path is relative to the application's `resource`
+/// directory. Returns how many catalogs were loaded.
+pub fn load_catalogs(directory: &str) -> Result<usize, String> {
+    let path = Bundle::path_for_resource(directory);
+    let entries = std::fs::read_dir(&path).map_err(|error| format!("{}: {}", path, error))?;
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .collect();
+    paths.sort();
+
+    for path in paths.iter() {
+        add_catalog(StringCatalog::load_file(&path.to_string_lossy())?);
+    }
+
+    Ok(paths.len())
+}
+
+/// Loads the catalogs in the resources' `localization` directory, if there
+/// is one, as the application launches.
+pub(crate) fn load_default_catalogs() {
+    if !std::path::Path::new(&Bundle::path_for_resource(CATALOG_DIRECTORY)).is_dir() {
+        return;
+    }
+
+    if let Err(error) = load_catalogs(CATALOG_DIRECTORY) {
+        println!("Warning: couldn't load the string catalogs. {}", error);
+    }
+}
+
+/// The translation of `key` for the current locale, or `key` itself if
+/// there isn't one.
+pub fn localized(key: &str) -> String {
+    let locale = locale();
+    let localization = Localization::borrow();
+    lookup(&localization.catalogs, &locale, key).unwrap_or(key).to_string()
+}
+
+/// The translation of `key`, as `localized`, with `{0}`, `{1}` and so on
+/// replaced by the arguments at those indexes, so translations can put
+/// them in the order their language needs.
+pub fn localized_format(key: &str, arguments: &[&str]) -> String {
+    substitute(&localized(key), arguments)
+}
+
+/// The translation of `key` in the catalog for `locale`, or failing that,
+/// the catalog for its language.
+fn lookup<'a>(catalogs: &'a [StringCatalog], locale: &Locale, key: &str) -> Option<&'a str> {
+    let language = Locale::new(locale.language());
+    [locale, &language]
+        .iter()
+        .filter_map(|locale| catalogs.iter().find(|catalog| catalog.locale == **locale))
+        .find_map(|catalog| catalog.string(key))
+}
+
+fn substitute(format: &str, arguments: &[&str]) -> String {
+    let mut result = format.to_string();
+    for (index, argument) in arguments.iter().enumerate() {
+        result = result.replace(&format!("{{{}}}", index), argument);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let catalog = StringCatalog::parse(Locale::new("de"), r#"{ "Save": "Sichern", "Quit": "Beenden" }"#).unwrap();
+        assert_eq!(catalog.locale().identifier(), "de");
+        assert_eq!(catalog.string("Save"), Some("Sichern"));
+        assert_eq!(catalog.string("Open"), None);
+
+        assert_eq!(StringCatalog::parse(Locale::new("de"), "[]").unwrap_err(), "expected an object of strings, but found an array");
+        assert_eq!(StringCatalog::parse(Locale::new("de"), r#"{ "Save": 1 }"#).unwrap_err(), "Save: expected a string, but found a number");
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut german = StringCatalog::new(Locale::new("de"));
+        german.insert("Save", "Sichern");
+        german.insert("Color", "Farbe");
+        let mut swiss = StringCatalog::new(Locale::new("de-CH"));
+        swiss.insert("Save", "Speichern");
+        let catalogs = vec![german, swiss];
+
+        assert_eq!(lookup(&catalogs, &Locale::new("de-CH"), "Save"), Some("Speichern"));
+        assert_eq!(lookup(&catalogs, &Locale::new("de-CH"), "Color"), Some("Farbe"));
+        assert_eq!(lookup(&catalogs, &Locale::new("de-AT"), "Save"), Some("Sichern"));
+        assert_eq!(lookup(&catalogs, &Locale::new("fr"), "Save"), None);
+    }
+
+    #[test]
+    fn test_substitute() {
+        assert_eq!(substitute("{1} von {0}", &["3", "1"]), "1 von 3");
+        assert_eq!(substitute("{0} and {0} but not {2}", &["a", "b"]), "a and a but not {2}");
+    }
+}
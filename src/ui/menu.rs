@@ -0,0 +1,257 @@
+use crate::ui::View;
+use crate::ui::command::Command;
+use std::rc::Rc;
+
+type MenuAction = Rc<dyn Fn()>;
+
+/// What choosing a menu item does.
+#[derive(Clone)]
+pub enum MenuItemKind {
+    /// Calls the closure.
+    Action(MenuAction),
+
+    /// Sends the command up the responder chain, starting at the first
+    /// responder, such as Edit > Undo reaching the focused text field.
+    Command(Command),
+
+    /// Opens another menu.
+    Submenu(Menu),
+
+    /// A line between groups of items, which can't be chosen.
+    Separator
+}
+
+/// An item of a `Menu`.
+#[derive(Clone)]
+pub struct MenuItem {
+    title: String,
+    is_enabled: bool,
+    kind: MenuItemKind
+}
+
+impl MenuItem {
+    /// An item calling `action` when it's chosen.
+    pub fn new(title: &str, action: impl Fn() + 'static) -> MenuItem {
+        MenuItem {
+            title: title.to_string(),
+            is_enabled: true,
+            kind: MenuItemKind::Action(Rc::new(action))
+        }
+    }
+
+    /// An item sending `command` up the responder chain.
+    pub fn command(title: &str, command: Command) -> MenuItem {
+        MenuItem {
+            title: title.to_string(),
+            is_enabled: true,
+            kind: MenuItemKind::Command(command)
+        }
+    }
+
+    /// An item opening `menu`.
+    pub fn submenu(title: &str, menu: Menu) -> MenuItem {
+        MenuItem {
+            title: title.to_string(),
+            is_enabled: true,
+            kind: MenuItemKind::Submenu(menu)
+        }
+    }
+
+    pub fn separator() -> MenuItem {
+        MenuItem {
+            title: String::new(),
+            is_enabled: false,
+            kind: MenuItemKind::Separator
+        }
+    }
+
+    /// The item, enabled or disabled. Disabled items are shown grayed out,
+    /// and can't be chosen.
+    pub fn enabled(mut self, is_enabled: bool) -> MenuItem {
+        self.set_enabled(is_enabled);
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn kind(&self) -> &MenuItemKind {
+        &self.kind
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    /// Enables or disables the item. Separators are never enabled.
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled && !self.is_separator();
+    }
+
+    pub fn is_separator(&self) -> bool {
+        matches!(self.kind, MenuItemKind::Separator)
+    }
+
+    /// The menu the item opens, if it's a submenu item.
+    pub fn submenu_menu(&self) -> Option<&Menu> {
+        match &self.kind {
+            MenuItemKind::Submenu(menu) => Some(menu),
+            _ => None
+        }
+    }
+
+    /// Calls the item's action, or sends its command to `target`, usually
+    /// the first responder. Returns whether it was handled. Disabled items,
+    /// separators and submenu items do nothing.
+    pub fn perform(&self, target: Option<&View>) -> bool {
+        match &self.kind {
+            _ if !self.is_enabled => false,
+            MenuItemKind::Action(action) => {
+                action();
+                true
+            },
+            MenuItemKind::Command(command) => target.is_some_and(|target| target.perform_command(command)),
+            _ => false
+        }
+    }
+}
+
+impl std::fmt::Debug for MenuItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let kind = match &self.kind {
+            MenuItemKind::Action(_) => "Action",
+            MenuItemKind::Command(_) => "Command",
+            MenuItemKind::Submenu(_) => "Submenu",
+            MenuItemKind::Separator => "Separator"
+        };
+        f.debug_struct("MenuItem")
+            .field("title", &self.title)
+            .field("is_enabled", &self.is_enabled)
+            .field("kind", &kind)
+            .finish()
+    }
+}
+
+/// A list of items to choose from, shown as a context menu or in the menu
+/// bar. Items can open submenus of their own.
+#[derive(Clone, Debug)]
+pub struct Menu {
+    title: String,
+    items: Vec<MenuItem>
+}
+
+impl Menu {
+    pub fn new(title: &str, items: Vec<MenuItem>) -> Menu {
+        Menu {
+            title: title.to_string(),
+            items
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<MenuItem> {
+        &mut self.items
+    }
+
+    pub fn add_item(&mut self, item: MenuItem) {
+        self.items.push(item);
+    }
+
+    /// A copy of the menu, and its submenus, with command items disabled
+    /// if `target` can't perform them now. Without a target, every command
+    /// item is disabled.
+    pub fn validated(&self, target: Option<&View>) -> Menu {
+        let items = self.items.iter().map(|item| {
+            let mut item = item.clone();
+            match &item.kind {
+                MenuItemKind::Command(command) => {
+                    let can_perform = target.is_some_and(|target| target.can_perform_command(command));
+                    item.set_enabled(item.is_enabled && can_perform);
+                },
+                MenuItemKind::Submenu(menu) => item.kind = MenuItemKind::Submenu(menu.validated(target)),
+                _ => ()
+            }
+            item
+        }).collect();
+
+        Menu::new(&self.title, items)
+    }
+
+    /// Whether the item at `index` can be highlighted and chosen.
+    pub fn is_selectable(&self, index: usize) -> bool {
+        self.items.get(index).is_some_and(|item| item.is_enabled())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::TextField;
+    use crate::ui::view::Behavior;
+    use crate::graphics::Rectangle;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_menu_items() {
+        let count = Rc::new(Cell::new(0));
+        let count_clone = count.clone();
+
+        let menu = Menu::new("Edit", vec![
+            MenuItem::new("Copy", move || count_clone.set(count_clone.get() + 1)),
+            MenuItem::new("Paste", || {}).enabled(false),
+            MenuItem::separator().enabled(true),
+            MenuItem::submenu("Find", Menu::new("Find", vec![MenuItem::new("Find…", || {})]))
+        ]);
+
+        assert!(menu.items()[0].perform(None));
+        assert!(!menu.items()[1].perform(None));
+        assert!(!menu.items()[3].perform(None));
+        assert_eq!(count.get(), 1);
+
+        assert!(menu.is_selectable(0));
+        assert!(!menu.is_selectable(1));
+        assert!(!menu.is_selectable(2));
+        assert!(menu.is_selectable(3));
+        assert!(!menu.is_selectable(4));
+        assert_eq!(menu.items()[3].submenu_menu().unwrap().items()[0].title(), "Find…");
+    }
+
+    #[test]
+    fn test_commands() {
+        let menu = Menu::new("Edit", vec![
+            MenuItem::command("Undo", Command::Undo),
+            MenuItem::command("Select All", Command::SelectAll),
+            MenuItem::submenu("More", Menu::new("More", vec![MenuItem::command("Redo", Command::Redo)]))
+        ]);
+
+        // Commands sent to a view inside a text field reach the text field,
+        // up the responder chain.
+        let text_field = TextField::new(Rectangle::new(0, 0, 300, 100), "".to_string());
+        let target = text_field.label().view;
+
+        let validated = menu.validated(Some(&target));
+        assert!(!validated.items()[0].is_enabled());
+        assert!(!validated.items()[1].is_enabled());
+        assert!(!menu.items()[0].perform(Some(&target)));
+
+        text_field.behavior().text_input_did_receive("text");
+        let validated = menu.validated(Some(&target));
+        assert!(validated.items()[0].is_enabled());
+        assert!(validated.items()[1].is_enabled());
+        assert!(!validated.items()[2].submenu_menu().unwrap().items()[0].is_enabled());
+        assert!(!menu.validated(None).items()[0].is_enabled());
+
+        assert!(validated.items()[0].perform(Some(&target)));
+        assert_eq!(text_field.label().text().string(), "");
+        assert!(menu.validated(Some(&target)).items()[2].submenu_menu().unwrap().items()[0].is_enabled());
+        assert!(!menu.items()[0].perform(None));
+    }
+}
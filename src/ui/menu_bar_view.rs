@@ -0,0 +1,145 @@
+use crate::macros::*;
+use crate::ui::view::{AutoresizingMask, DefaultBehavior};
+use crate::ui::context_menu::ContextMenu;
+use crate::ui::{Color, Label, Touch};
+use crate::platform::menu_bar::MenuBar;
+use crate::graphics::{Point, Rectangle};
+use crate::text::{HorizontalAlignment, VerticalAlignment};
+use std::cell::RefCell;
+
+pub const MENU_BAR_HEIGHT: u32 = 24;
+
+/// The space on either side of each menu's title.
+const TITLE_PADDING: u32 = 10;
+
+static BAR_COLOR: Color = Color::new(240, 240, 240, 255);
+
+// A strip along the top of a window with the title of each menu of a
+// `MenuBar`. Touching a title opens its menu below it, as a `ContextMenu`
+// presented over the window.
+custom_view!(
+    MenuBarView subclasses DefaultBehavior
+
+    struct MenuBarViewBehavior {
+        menu_bar: RefCell<MenuBar>
+    }
+
+    impl Self {
+        /// A menu bar view `width` wide, for the top of a window.
+        pub fn new(width: u32, menu_bar: MenuBar) -> MenuBarView {
+            let menu_bar_view = Self::new_all(Rectangle::new(0, 0, width, MENU_BAR_HEIGHT), RefCell::new(menu_bar));
+            menu_bar_view.view.set_background_color(BAR_COLOR.clone());
+            menu_bar_view.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth]);
+            menu_bar_view.layout_titles();
+            menu_bar_view
+        }
+
+        pub fn menu_bar(&self) -> MenuBar {
+            self.behavior().menu_bar.borrow().clone()
+        }
+
+        pub fn set_menu_bar(&self, menu_bar: MenuBar) {
+            self.behavior().menu_bar.replace(menu_bar);
+            self.layout_titles();
+        }
+
+        /// Opens the menu at `index` below its title, over the content of
+        /// the root view, usually the window. Returns the menu presented.
+        pub fn open_menu(&self, index: usize) -> Option<ContextMenu> {
+            let menu = self.behavior().menu_bar.borrow().menus().get(index)?.clone();
+            let title = self.view.subviews().get(index)?.frame();
+
+            let root = self.view.root_view();
+            let point = self.view.convert_point_to(&Point::new(title.left(), MENU_BAR_HEIGHT as i32), &root);
+            let context_menu = ContextMenu::new(menu);
+            context_menu.present_at(&point, &root);
+            Some(context_menu)
+        }
+
+        /// The index of the menu whose title is at `point`, relative to the
+        /// top left of the view.
+        fn menu_at(&self, point: &Point<i32>) -> Option<usize> {
+            self.view.subviews().iter().position(|title| title.frame().contains_point(point))
+        }
+
+        /// Adds a label for the title of each menu, from left to right.
+        fn layout_titles(&self) {
+            for subview in self.view.subviews() {
+                subview.remove_from_superview();
+            }
+
+            let menu_bar = self.menu_bar();
+            let mut x = 0;
+            for menu in menu_bar.menus() {
+                let title = Label::new(Rectangle::new(x, 0, 0, MENU_BAR_HEIGHT), menu.title().to_string());
+                let width = title.size_that_fits(u32::MAX).width + TITLE_PADDING * 2;
+                title.view.set_frame(Rectangle::new(x, 0, width, MENU_BAR_HEIGHT));
+                title.set_text_alignment(HorizontalAlignment::Center);
+                title.set_vertical_alignment(VerticalAlignment::Middle);
+                title.view.set_background_color(Color::clear());
+                title.view.set_user_interaction_enabled(false);
+                self.view.add_subview(title.view);
+                x += width as i32;
+            }
+        }
+    }
+
+    impl Behavior {
+        fn touches_began(&self, touches: &Vec<Touch>) {
+            let menu_bar_view = self.view_type();
+            let touch = match touches.first() {
+                Some(touch) => touch,
+                None => return
+            };
+
+            let root = match touch.window() {
+                Some(window) => window.view,
+                None => menu_bar_view.view.root_view()
+            };
+            let point = root.convert_point_to(&touch.position(), &menu_bar_view.view);
+
+            if let Some(index) = menu_bar_view.menu_at(&point) {
+                menu_bar_view.open_menu(index);
+            }
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::menu::{Menu, MenuItem};
+    use crate::ui::View;
+
+    #[test]
+    fn test_open_menu() {
+        let root = View::new(Rectangle::new(0, 0, 600, 400));
+        let content = View::new(Rectangle::new(0, 20, 600, 380));
+        root.add_subview(content.clone());
+
+        let menu_bar = MenuBar::new(vec![
+            Menu::new("File", vec![MenuItem::new("Open", || {})]),
+            MenuBar::edit_menu()
+        ]);
+        let menu_bar_view = MenuBarView::new(600, menu_bar);
+        content.add_subview(menu_bar_view.view.clone());
+
+        let titles = menu_bar_view.view.subviews();
+        assert_eq!(titles.len(), 2);
+        assert_eq!(titles[0].frame().left(), 0);
+        assert_eq!(titles[1].frame().left(), titles[0].frame().right());
+
+        // Touching Edit opens it below its title, over the whole root view.
+        let edit = titles[1].frame();
+        let touch = Touch::new(0, Point::new(edit.left() + 5, 25));
+        menu_bar_view.view.behavior().touches_began(&vec![touch]);
+
+        let context_menu = ContextMenu::from_view(root.subviews().last().unwrap().clone());
+        assert_eq!(context_menu.menu().title(), "Edit");
+        assert_eq!(context_menu.view.subviews()[0].frame().origin, Point::new(edit.left(), 20 + MENU_BAR_HEIGHT as i32));
+
+        assert!(menu_bar_view.open_menu(2).is_none());
+        context_menu.dismiss();
+        assert_eq!(root.subviews().len(), 1);
+    }
+}
@@ -5,25 +5,44 @@ pub use application_main::ApplicationDelegate;
 mod color;
 pub use color::Color;
 
-mod event_loop;
+pub(crate) mod event_loop;
 
 pub mod run_loop;
 
 pub mod timer;
 
+pub mod clock;
+
+pub mod display_link;
+
+mod executor;
+
+pub mod animation;
+
+mod shadow;
+pub use shadow::Shadow;
+
 pub mod touch;
 pub use touch::Touch;
 
 pub mod view;
 pub use view::View;
 pub use view::WeakView;
-pub use view::ImageView;
+pub use view::{ContentMode, ImageView};
 pub use view::Label;
+pub use view::ShapeView;
 mod view_controller;
 pub use view_controller::ViewControllerBehavior;
 pub use view_controller::ViewController;
+pub use view_controller::{NavigationBar, NavigationController};
 pub use view::ScrollView;
 pub use view::TextField;
+pub use view::TextView;
+pub use view::FindBar;
+pub use view::{TableView, TableViewDataSource, TableViewCell};
+pub use view::{CollectionView, CollectionViewCell, CollectionViewDataSource, CollectionViewLayout, FlowLayout};
+pub use view::AutoresizingMask;
+pub use view::{TrackingArea, TrackingOption};
 
 pub mod gesture;
 
@@ -37,11 +56,61 @@ pub mod application;
 
 mod render;
 
+mod inspector;
+
+pub mod accessibility;
+pub use accessibility::{AccessibilityAction, AccessibilityNode, AccessibilityRole};
+
+mod performance_hud;
+pub use performance_hud::FrameStatistics;
+
 pub mod button;
 pub use button::Button;
 pub use button::ButtonBehavior;
 
+pub mod alert;
+pub use alert::{Alert, AlertAction, AlertActionStyle};
+
+pub mod appearance;
+pub use appearance::{Appearance, SemanticColor};
+
+pub mod localization;
+pub use localization::{Locale, StringCatalog};
+
+pub mod formatter;
+pub use formatter::{DateFormatter, Formatter, NumberFormatter, RegexFormatter};
+
+pub mod spell_checker;
+pub use spell_checker::{DictionarySpellChecker, SpellChecker};
+
+pub mod layout_direction;
+pub use layout_direction::LayoutDirection;
+
+pub mod notification;
+pub use notification::{Notification, NotificationCenter};
+
+pub mod command;
+pub use command::Command;
+
+pub mod menu;
+pub use menu::{Menu, MenuItem, MenuItemKind};
+
+pub mod context_menu;
+pub use context_menu::ContextMenu;
+
+pub mod menu_bar_view;
+pub use menu_bar_view::MenuBarView;
+
+pub mod popover;
+pub use popover::{ArrowDirection, Popover, Tooltip};
+
 pub mod press;
 pub mod key;
+pub mod key_repeat;
+
+pub mod loader;
+
+pub mod key_command;
+pub use key_command::KeyCommand;
 
 mod history;
@@ -0,0 +1,198 @@
+//! Named notifications, posted by one part of the application and observed
+//! by any other, without either knowing about the other.
+//!
+//! ```ignore
+//! let token = NotificationCenter::add_observer(notification::WINDOW_DID_BECOME_KEY, |notification| {
+//!     let window = notification.payload::<Window>().unwrap();
+//!     println!("{} became key", window.view.debug_name);
+//! });
+//!
+//! NotificationCenter::post(Notification::new("DocumentDidSave"));
+//! NotificationCenter::remove_observer(token);
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Posted when a window becomes the key window, receiving key presses. The
+/// payload is the `Window`.
+pub const WINDOW_DID_BECOME_KEY: &str = "WindowDidBecomeKey";
+
+/// Posted when a window stops being the key window, because another became
+/// key. The payload is the `Window`.
+pub const WINDOW_DID_RESIGN_KEY: &str = "WindowDidResignKey";
+
+/// Posted when the application's appearance changes, such as to dark mode,
+/// after every window has been restyled.
+pub const APPEARANCE_DID_CHANGE: &str = "AppearanceDidChange";
+
+/// Posted when the application's locale changes, after the localized
+/// strings of labels and buttons in every window have been fetched again.
+/// See `localization::set_locale`.
+pub const LOCALE_DID_CHANGE: &str = "LocaleDidChange";
+
+/// Posted once the application delegate has finished launching, before the
+/// run loop starts.
+pub const APPLICATION_DID_FINISH_LAUNCHING: &str = "ApplicationDidFinishLaunching";
+
+/// Posted once the run loop has exited, before the application terminates.
+pub const APPLICATION_WILL_TERMINATE: &str = "ApplicationWillTerminate";
+
+type NotificationObserver = Rc<dyn Fn(&Notification)>;
+
+/// Something that happened, told to the observers of its name. It can carry
+/// a payload of any type, such as the window that became key.
+#[derive(Clone)]
+pub struct Notification {
+    name: String,
+    payload: Option<Rc<dyn Any>>
+}
+
+impl Notification {
+    pub fn new(name: &str) -> Notification {
+        Notification {
+            name: name.to_string(),
+            payload: None
+        }
+    }
+
+    /// A notification carrying `payload`, which observers get back with
+    /// `payload`.
+    pub fn with_payload<T: Any>(name: &str, payload: T) -> Notification {
+        Notification {
+            name: name.to_string(),
+            payload: Some(Rc::new(payload))
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The payload, if there is one of type `T`.
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        self.payload.as_ref().and_then(|payload| payload.downcast_ref::<T>())
+    }
+}
+
+impl std::fmt::Debug for Notification {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Notification")
+            .field("name", &self.name)
+            .field("has_payload", &self.payload.is_some())
+            .finish()
+    }
+}
+
+/// Identifies an observer added with `NotificationCenter::add_observer`, to
+/// remove it later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ObserverToken(usize);
+
+thread_local! {
+    static NOTIFICATION_CENTER: RefCell<NotificationCenter> = const { RefCell::new(NotificationCenter {
+        observers: Vec::new(),
+        next_token: 0
+    }) };
+}
+
+/// Keeps the observers of notifications, and tells them when one is posted.
+///
+/// Observers are called in the order they were added, on the main thread,
+/// as soon as a notification is posted. They can post notifications, and
+/// add or remove observers, themselves. Each thread has its own center.
+pub struct NotificationCenter {
+    observers: Vec<(ObserverToken, String, NotificationObserver)>,
+    next_token: usize
+}
+
+impl NotificationCenter {
+    /// Calls `observer` with each notification named `name` posted from now
+    /// on, until it's removed with the token returned.
+    pub fn add_observer(name: &str, observer: impl Fn(&Notification) + 'static) -> ObserverToken {
+        NOTIFICATION_CENTER.with(|center| {
+            let mut center = center.borrow_mut();
+            let token = ObserverToken(center.next_token);
+            center.next_token += 1;
+            center.observers.push((token, name.to_string(), Rc::new(observer)));
+            token
+        })
+    }
+
+    /// Stops calling the observer added with `token`.
+    pub fn remove_observer(token: ObserverToken) {
+        NOTIFICATION_CENTER.with(|center| {
+            center.borrow_mut().observers.retain(|(observer_token, _, _)| *observer_token != token);
+        });
+    }
+
+    /// Tells the observers of the notification's name about it.
+    pub fn post(notification: Notification) {
+        let observers: Vec<(ObserverToken, NotificationObserver)> = NOTIFICATION_CENTER.with(|center| {
+            center.borrow().observers.iter()
+                .filter(|(_, name, _)| *name == notification.name)
+                .map(|(token, _, observer)| (*token, observer.clone()))
+                .collect()
+        });
+
+        for (token, observer) in observers {
+            // An earlier observer may have removed this one.
+            if NOTIFICATION_CENTER.with(|center| center.borrow().is_observing(token)) {
+                observer(&notification);
+            }
+        }
+    }
+
+    fn is_observing(&self, token: ObserverToken) -> bool {
+        self.observers.iter().any(|(observer_token, _, _)| *observer_token == token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_post_to_observers_of_name() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received_clone = received.clone();
+        let token = NotificationCenter::add_observer("TestPostDidHappen", move |notification| {
+            received_clone.borrow_mut().push(*notification.payload::<i32>().unwrap());
+        });
+
+        NotificationCenter::post(Notification::with_payload("TestPostDidHappen", 1));
+        NotificationCenter::post(Notification::with_payload("TestPostOtherName", 2));
+        assert_eq!(*received.borrow(), vec![1]);
+
+        NotificationCenter::remove_observer(token);
+        NotificationCenter::post(Notification::with_payload("TestPostDidHappen", 3));
+        assert_eq!(*received.borrow(), vec![1]);
+    }
+
+    #[test]
+    fn test_observer_removing_another() {
+        let later_token = Rc::new(RefCell::new(None));
+        let later_token_clone = later_token.clone();
+        let first = NotificationCenter::add_observer("TestObserverRemoving", move |_| {
+            if let Some(token) = later_token_clone.borrow_mut().take() {
+                NotificationCenter::remove_observer(token);
+            }
+        });
+        let later = NotificationCenter::add_observer("TestObserverRemoving", |_| {
+            panic!("removed observers aren't called");
+        });
+        *later_token.borrow_mut() = Some(later);
+
+        NotificationCenter::post(Notification::new("TestObserverRemoving"));
+        NotificationCenter::remove_observer(first);
+    }
+
+    #[test]
+    fn test_payload_of_other_type() {
+        let notification = Notification::with_payload("TestPayload", String::from("saved"));
+        assert_eq!(notification.payload::<String>(), Some(&String::from("saved")));
+        assert!(notification.payload::<i32>().is_none());
+        assert!(Notification::new("TestPayload").payload::<String>().is_none());
+    }
+}
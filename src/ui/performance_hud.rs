@@ -0,0 +1,131 @@
+//! Measures how each frame of a window is drawn, and shows the measurements
+//! in a corner of the window. See `Window::set_shows_performance_hud`.
+
+use crate::graphics::{EdgeInsets, Rectangle, Size};
+use crate::ui::{Color, Label};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How often the performance HUD shows new measurements.
+pub(crate) const UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How far the performance HUD is from the window's top left corner, and
+/// how wide it is.
+const HUD_MARGIN: i32 = 8;
+const HUD_WIDTH: u32 = 160;
+
+/// Measurements of drawing a frame of a window. See
+/// `Window::frame_statistics`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameStatistics {
+    /// How long drawing the views into the window took, on the clock on the
+    /// wall.
+    pub draw_time: Duration,
+
+    /// How many views' layers were drawn again, rather than reused.
+    pub layers_drawn: usize,
+
+    /// The area of the window drawn again, in square points.
+    pub dirty_area: u64
+}
+
+/// The frames a window drew in the last second, to count frames per
+/// second.
+#[derive(Default)]
+pub(crate) struct FrameHistory {
+    frames: VecDeque<(Instant, FrameStatistics)>
+}
+
+impl FrameHistory {
+    /// Records a frame drawn at `drawn_at`, forgetting frames drawn over a
+    /// second before it.
+    pub(crate) fn record(&mut self, drawn_at: Instant, statistics: FrameStatistics) {
+        self.frames.push_back((drawn_at, statistics));
+
+        while self.frames.front().is_some_and(|(frame_drawn_at, _)| drawn_at.saturating_duration_since(*frame_drawn_at) >= Duration::from_secs(1)) {
+            self.frames.pop_front();
+        }
+    }
+
+    /// How many frames were drawn in the second before `now`.
+    pub(crate) fn frames_per_second(&self, now: Instant) -> usize {
+        self.frames
+            .iter()
+            .filter(|(drawn_at, _)| is_within_second(*drawn_at, now))
+            .count()
+    }
+
+    /// The measurements of the last frame drawn, if it was drawn in the
+    /// second before `now`.
+    pub(crate) fn last(&self, now: Instant) -> Option<&FrameStatistics> {
+        self.frames
+            .back()
+            .filter(|(drawn_at, _)| is_within_second(*drawn_at, now))
+            .map(|(_, statistics)| statistics)
+    }
+}
+
+fn is_within_second(drawn_at: Instant, now: Instant) -> bool {
+    now.saturating_duration_since(drawn_at) < Duration::from_secs(1)
+}
+
+/// The lines shown by the performance HUD, for a window of `window_size`.
+pub(crate) fn summary(frames_per_second: usize, statistics: &FrameStatistics, window_size: &Size<u32>) -> String {
+    let window_area = window_size.width as u64 * window_size.height as u64;
+    let dirty_percent = (statistics.dirty_area * 100).checked_div(window_area).unwrap_or(0);
+
+    format!(
+        "{} fps\n{:.1} ms draw\n{} layers drawn\n{}% dirty",
+        frames_per_second,
+        statistics.draw_time.as_secs_f64() * 1000.0,
+        statistics.layers_drawn,
+        dirty_percent
+    )
+}
+
+/// The label the performance HUD is shown in.
+pub(crate) fn new_hud() -> Label {
+    let hud = Label::new(Rectangle::new(HUD_MARGIN, HUD_MARGIN, HUD_WIDTH, 0), String::new());
+    hud.set_text_color(Color::white());
+    hud.set_content_insets(EdgeInsets::symmetric(8, 6));
+    hud.view.set_background_color(Color::new(0, 0, 0, 180));
+    hud.view.set_corner_radius(6.0);
+    hud.view.set_user_interaction_enabled(false);
+    hud
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statistics(layers_drawn: usize) -> FrameStatistics {
+        FrameStatistics { draw_time: Duration::from_micros(2500), layers_drawn, dirty_area: 1000 }
+    }
+
+    #[test]
+    fn test_frames_per_second() {
+        let start = Instant::now();
+        let mut history = FrameHistory::default();
+        assert_eq!(history.frames_per_second(start), 0);
+        assert_eq!(history.last(start), None);
+
+        for frame in 0..90 {
+            history.record(start + Duration::from_millis(frame * 1000 / 60), statistics(frame as usize));
+        }
+
+        let now = start + Duration::from_millis(1490);
+        assert_eq!(history.frames_per_second(now), 60);
+        assert_eq!(history.last(now), Some(&statistics(89)));
+        assert_eq!(history.frames_per_second(now + Duration::from_secs(2)), 0);
+        assert_eq!(history.last(now + Duration::from_secs(2)), None);
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(
+            summary(60, &statistics(3), &Size::new(100, 100)),
+            "60 fps\n2.5 ms draw\n3 layers drawn\n10% dirty"
+        );
+        assert_eq!(summary(0, &FrameStatistics::default(), &Size::new(0, 0)), "0 fps\n0.0 ms draw\n0 layers drawn\n0% dirty");
+    }
+}
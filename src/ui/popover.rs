@@ -0,0 +1,386 @@
+use crate::macros::*;
+use crate::ui::view::DefaultBehavior;
+use crate::ui::{Color, Label, SemanticColor, Shadow, Touch, View};
+use crate::graphics::{Point, Rectangle, Size, Transform};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// How far the arrow sticks out of a popover towards its source view.
+const ARROW_SIZE: u32 = 10;
+
+/// The least space kept between a popover or a context menu and the edges
+/// of the view it's presented in.
+pub(crate) const MARGIN: i32 = 8;
+
+const CORNER_RADIUS: f32 = 8.0;
+
+/// The widest a tooltip gets before its text wraps.
+const TOOLTIP_MAX_WIDTH: u32 = 240;
+
+/// The space between a tooltip's edges and its text.
+const TOOLTIP_PADDING: u32 = 6;
+
+type DismissCallback = Rc<dyn Fn()>;
+
+/// Which way a popover's arrow points, at its source view. A popover with
+/// its arrow pointing up is below its source view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrowDirection {
+    Up,
+    Down,
+    Left,
+    Right
+}
+
+/// Where a popover of `size` goes to point at `source` from `direction`,
+/// before it's moved to stay inside `container`.
+fn frame_pointing(direction: ArrowDirection, source: &Rectangle<i32, u32>, size: &Size<u32>, gap: u32) -> Rectangle<i32, u32> {
+    let center = source.center();
+    let (width, height) = (size.width as i32, size.height as i32);
+    let gap = gap as i32;
+
+    let origin = match direction {
+        ArrowDirection::Up => Point::new(center.x - width / 2, source.bottom() + gap),
+        ArrowDirection::Down => Point::new(center.x - width / 2, source.top() - gap - height),
+        ArrowDirection::Left => Point::new(source.right() + gap, center.y - height / 2),
+        ArrowDirection::Right => Point::new(source.left() - gap - width, center.y - height / 2)
+    };
+
+    Rectangle { origin, size: size.clone() }
+}
+
+/// Whether `frame` is inside `container`, keeping the margin.
+fn fits(frame: &Rectangle<i32, u32>, container: &Size<u32>) -> bool {
+    frame.left() >= MARGIN && frame.top() >= MARGIN &&
+        frame.right() <= container.width as i32 - MARGIN &&
+        frame.bottom() <= container.height as i32 - MARGIN
+}
+
+/// Moves `frame` as little as needed to be inside `container`, keeping the
+/// margin where there's room.
+pub(crate) fn clamp_to(frame: &Rectangle<i32, u32>, container: &Size<u32>) -> Rectangle<i32, u32> {
+    let clamp = |origin: i32, length: u32, container_length: u32| {
+        let max = container_length as i32 - MARGIN - length as i32;
+        origin.min(max).max(MARGIN.min(max.max(0)))
+    };
+
+    Rectangle {
+        origin: Point::new(
+            clamp(frame.origin.x, frame.size.width, container.width),
+            clamp(frame.origin.y, frame.size.height, container.height)
+        ),
+        size: frame.size.clone()
+    }
+}
+
+/// Where a popover of `size` goes in a `container` to point at `source`,
+/// trying each of `directions` in turn until one fits. If none of them fit
+/// the first is used. The popover slides along the side of the source view
+/// to stay inside the container.
+pub(crate) fn popover_placement(
+    source: &Rectangle<i32, u32>,
+    size: &Size<u32>,
+    container: &Size<u32>,
+    directions: &[ArrowDirection],
+    gap: u32
+) -> (Rectangle<i32, u32>, ArrowDirection) {
+    let direction = directions
+        .iter()
+        .cloned()
+        .find(|direction| {
+            let frame = frame_pointing(*direction, source, size, gap);
+
+            // Only the distance from the source view matters, as the
+            // popover can slide along it.
+            let frame = match direction {
+                ArrowDirection::Up | ArrowDirection::Down => Rectangle::new(MARGIN, frame.origin.y, frame.size.width, frame.size.height),
+                ArrowDirection::Left | ArrowDirection::Right => Rectangle::new(frame.origin.x, MARGIN, frame.size.width, frame.size.height)
+            };
+            fits(&frame, container)
+        })
+        .or_else(|| directions.first().cloned())
+        .unwrap_or(ArrowDirection::Up);
+
+    let frame = frame_pointing(direction, source, size, gap);
+    (clamp_to(&frame, container), direction)
+}
+
+// A view presented next to another view, pointing at it with an arrow, such
+// as for a picker or extra options for a button.
+//
+// A popover is presented over the rest of the window, in the direction from
+// its source view that has room for it. Tapping outside of it dismisses it.
+custom_view!(
+    Popover subclasses DefaultBehavior
+
+    struct PopoverBehavior {
+        content_view: View,
+        permitted_arrow_directions: RefCell<Vec<ArrowDirection>>,
+        arrow_direction: RefCell<ArrowDirection>,
+        dismiss_action: RefCell<Option<DismissCallback>>
+    }
+
+    impl Self {
+        /// A popover showing `content_view` at its size.
+        pub fn new(content_view: View) -> Popover {
+            let popover = Self::new_all(
+                Rectangle::new(0, 0, 0, 0),
+                content_view,
+                RefCell::new(vec![ArrowDirection::Up, ArrowDirection::Down, ArrowDirection::Left, ArrowDirection::Right]),
+                RefCell::new(ArrowDirection::Up),
+                RefCell::new(None)
+            );
+
+            // The popover's own view covers the view it's presented in, to
+            // catch taps outside of it.
+            popover.view.set_background_color(Color::clear());
+            popover
+        }
+
+        pub fn content_view(&self) -> View {
+            self.behavior().content_view.clone()
+        }
+
+        /// The directions the arrow may point in, in order of preference.
+        pub fn set_permitted_arrow_directions(&self, directions: &[ArrowDirection]) {
+            self.behavior().permitted_arrow_directions.replace(directions.to_vec());
+        }
+
+        /// Which way the arrow points, as of when the popover was last
+        /// presented.
+        pub fn arrow_direction(&self) -> ArrowDirection {
+            *self.behavior().arrow_direction.borrow()
+        }
+
+        /// Calls `action` when the popover is dismissed, whether by tapping
+        /// outside of it or with `dismiss`.
+        pub fn on_dismiss(&self, action: impl Fn() + 'static) {
+            self.behavior().dismiss_action.replace(Some(Rc::new(action)));
+        }
+
+        /// Presents the popover pointing at `source`, over the rest of the
+        /// source's window, or its root view outside of a window.
+        pub fn present_from(&self, source: &View) {
+            let container = source.root_view();
+            let container_size = container.bounds().size;
+            let source_frame = source.convert_rect_to(&source.bounds(), &container);
+
+            let content_view = self.content_view();
+            let content_size = content_view.frame().size;
+            let directions = self.behavior().permitted_arrow_directions.borrow().clone();
+            let (frame, direction) = popover_placement(&source_frame, &content_size, &container_size, &directions, ARROW_SIZE);
+            self.behavior().arrow_direction.replace(direction);
+
+            for subview in self.view.subviews() {
+                subview.remove_from_superview();
+            }
+
+            self.view.set_frame(Rectangle::new(0, 0, container_size.width, container_size.height));
+            self.view.add_subview(Self::new_arrow(direction, &source_frame, &frame));
+
+            let bubble = View::new(frame.clone());
+            bubble.set_background_color(Color::semantic(SemanticColor::Background));
+            bubble.set_corner_radius(CORNER_RADIUS);
+            bubble.set_clips_to_bounds(true);
+            bubble.set_shadow(Some(Shadow::default()));
+            content_view.set_frame(Rectangle::new(0, 0, frame.size.width, frame.size.height));
+            bubble.add_subview(content_view);
+            self.view.add_subview(bubble);
+
+            container.add_subview(self.view.clone());
+        }
+
+        /// The arrow, a square turned on its corner tucked behind the edge
+        /// of the popover facing its source view, with its tip pointing at
+        /// the source's center as far as the rounded corners allow.
+        fn new_arrow(direction: ArrowDirection, source: &Rectangle<i32, u32>, frame: &Rectangle<i32, u32>) -> View {
+            let inset = ARROW_SIZE as i32 + CORNER_RADIUS as i32;
+            let center = source.center();
+            let along_x = center.x.max(frame.left() + inset).min(frame.right() - inset);
+            let along_y = center.y.max(frame.top() + inset).min(frame.bottom() - inset);
+
+            let arrow_center = match direction {
+                ArrowDirection::Up => Point::new(along_x, frame.top()),
+                ArrowDirection::Down => Point::new(along_x, frame.bottom()),
+                ArrowDirection::Left => Point::new(frame.left(), along_y),
+                ArrowDirection::Right => Point::new(frame.right(), along_y)
+            };
+
+            // A square's half diagonal is the distance its corner sticks out.
+            let side = (ARROW_SIZE as f32 * std::f32::consts::SQRT_2).round() as u32;
+            let arrow = View::new(Rectangle::new_from_center(arrow_center, Size::new(side, side)));
+            arrow.set_background_color(Color::semantic(SemanticColor::Background));
+            arrow.set_transform(Transform::rotation(std::f32::consts::FRAC_PI_4));
+            arrow.set_user_interaction_enabled(false);
+            arrow
+        }
+
+        /// Whether the popover is presented.
+        pub fn is_presented(&self) -> bool {
+            self.view.superview().upgrade().is_some_and(|superview| superview.subviews().contains(&self.view))
+        }
+
+        /// Removes the popover, calling the `on_dismiss` callback.
+        pub fn dismiss(&self) {
+            if !self.is_presented() {
+                return;
+            }
+
+            self.view.remove_from_superview();
+
+            let dismiss_action = self.behavior().dismiss_action.borrow().clone();
+            if let Some(dismiss_action) = dismiss_action {
+                dismiss_action();
+            }
+        }
+
+        /// The rounded box holding the content view.
+        fn bubble(&self) -> Option<View> {
+            self.view.subviews().get(1).cloned()
+        }
+    }
+
+    impl Behavior {
+        // Touches inside the popover go to its content, so any reaching the
+        // popover's own view are outside of it.
+        fn touches_began(&self, _touches: &Vec<Touch>) {
+            self.view_type().dismiss();
+        }
+    }
+);
+
+// A short description shown next to a control, such as what a button does.
+//
+// Unlike a popover, a tooltip has no arrow and doesn't take touches, so
+// taps go to the views underneath it. It's shown below its source view where
+// there's room, and above otherwise. A view given one with
+// `View::set_tooltip` shows it when the pointer rests on the view.
+custom_view!(
+    Tooltip subclasses DefaultBehavior
+
+    struct TooltipBehavior {
+
+    }
+
+    impl Self {
+        pub fn new(text: &str) -> Tooltip {
+            let tooltip = Self::new_all(Rectangle::new(0, 0, 0, 0));
+            tooltip.view.set_background_color(Color::new(40, 40, 40, 230));
+            tooltip.view.set_corner_radius(4.0);
+            tooltip.view.set_user_interaction_enabled(false);
+
+            let label = Label::new(Rectangle::new(0, 0, 0, 0), text.to_string());
+            label.set_text_color(Color::white());
+            label.set_number_of_lines(0);
+            tooltip.view.add_subview(label.view);
+            tooltip
+        }
+
+        fn label(&self) -> Label {
+            let view = self.view.subviews().first().unwrap().clone();
+            Label::from_view(view)
+        }
+
+        pub fn text(&self) -> String {
+            self.label().copy_text()
+        }
+
+        /// Shows the tooltip next to `source`, over the rest of its window.
+        pub fn present_from(&self, source: &View) {
+            let container = source.root_view();
+            let source_frame = source.convert_rect_to(&source.bounds(), &container);
+
+            let label = self.label();
+            let text_size = label.size_that_fits(TOOLTIP_MAX_WIDTH - TOOLTIP_PADDING * 2);
+            label.view.set_frame(Rectangle::new(
+                TOOLTIP_PADDING as i32,
+                TOOLTIP_PADDING as i32,
+                text_size.width,
+                text_size.height
+            ));
+
+            let size = Size::new(text_size.width + TOOLTIP_PADDING * 2, text_size.height + TOOLTIP_PADDING * 2);
+            let directions = [ArrowDirection::Up, ArrowDirection::Down];
+            let (frame, _) = popover_placement(&source_frame, &size, &container.bounds().size, &directions, 4);
+            self.view.set_frame(frame);
+
+            container.add_subview(self.view.clone());
+        }
+
+        pub fn dismiss(&self) {
+            self.view.remove_from_superview();
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popover_placement() {
+        let container = Size::new(400, 300);
+        let size = Size::new(100, 50);
+        let all = [ArrowDirection::Up, ArrowDirection::Down, ArrowDirection::Left, ArrowDirection::Right];
+
+        // Below the source, centered on it.
+        let source = Rectangle::new(150, 20, 100, 30);
+        assert_eq!(popover_placement(&source, &size, &container, &all, 10), (Rectangle::new(150, 60, 100, 50), ArrowDirection::Up));
+
+        // No room below, so above.
+        let source = Rectangle::new(150, 240, 100, 30);
+        assert_eq!(popover_placement(&source, &size, &container, &all, 10), (Rectangle::new(150, 180, 100, 50), ArrowDirection::Down));
+
+        // Slides along the source to stay inside the container.
+        let source = Rectangle::new(0, 20, 40, 30);
+        assert_eq!(popover_placement(&source, &size, &container, &all, 10), (Rectangle::new(8, 60, 100, 50), ArrowDirection::Up));
+
+        // Only to the sides.
+        let source = Rectangle::new(150, 100, 40, 100);
+        let sides = [ArrowDirection::Right, ArrowDirection::Left];
+        assert_eq!(popover_placement(&source, &size, &container, &sides, 10), (Rectangle::new(40, 125, 100, 50), ArrowDirection::Right));
+    }
+
+    #[test]
+    fn test_popover_dismisses_on_outside_tap() {
+        let window = View::new(Rectangle::new(0, 0, 400, 300));
+        let source = View::new(Rectangle::new(150, 20, 100, 30));
+        window.add_subview(source.clone());
+
+        let content = View::new(Rectangle::new(0, 0, 100, 50));
+        let popover = Popover::new(content.clone());
+        let dismissed = Rc::new(std::cell::Cell::new(false));
+        let dismissed_clone = dismissed.clone();
+        popover.on_dismiss(move || dismissed_clone.set(true));
+
+        popover.present_from(&source);
+        assert!(popover.is_presented());
+        assert_eq!(popover.arrow_direction(), ArrowDirection::Up);
+        assert_eq!(popover.bubble().unwrap().frame(), Rectangle::new(150, 60, 100, 50));
+
+        // Inside the popover the content gets the touches, and outside the
+        // popover does.
+        assert_eq!(window.hit_test(&Point::new(160, 70)), Some(content));
+        let outside = window.hit_test(&Point::new(10, 200)).unwrap();
+        assert_eq!(outside, popover.view);
+
+        outside.behavior().touches_began(&vec![Touch::new(0, Point::new(10, 200))]);
+        assert!(!popover.is_presented());
+        assert!(dismissed.get());
+    }
+
+    #[test]
+    fn test_tooltip_doesnt_take_touches() {
+        let window = View::new(Rectangle::new(0, 0, 400, 300));
+        let source = View::new(Rectangle::new(150, 20, 100, 30));
+        window.add_subview(source.clone());
+
+        let tooltip = Tooltip::new("Saves the file");
+        tooltip.present_from(&source);
+        assert_eq!(tooltip.view.frame().origin.y, 54);
+        assert_eq!(window.hit_test(&tooltip.view.frame().center()), Some(window.clone()));
+
+        tooltip.dismiss();
+        assert!(!window.subviews().contains(&tooltip.view));
+    }
+}
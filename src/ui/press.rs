@@ -1,5 +1,6 @@
 use crate::ui::key::Key;
 use std::time::Instant;
+use crate::ui::clock;
 use std::rc::Rc;
 use std::cell::{Ref, RefCell};
 use crate::ui::view::WeakView;
@@ -8,7 +9,8 @@ struct PressInner {
     key: Key,
     phase: PressPhase,
     timestamp: Instant,
-    first_responder: WeakView
+    first_responder: WeakView,
+    repeat_count: usize
 }
 
 pub struct Press {
@@ -21,8 +23,9 @@ impl Press {
             inner: Rc::new(RefCell::new(PressInner {
                 key,
                 phase: PressPhase::Began,
-                timestamp: Instant::now(),
-                first_responder: WeakView::none()
+                timestamp: clock::now(),
+                first_responder: WeakView::none(),
+                repeat_count: 0
             }))
         }
     }
@@ -31,6 +34,10 @@ impl Press {
         Ref::map(self.inner.borrow(), |inner| &inner.key)
     }
 
+    pub(crate) fn set_key(&self, key: Key) {
+        self.inner.borrow_mut().key = key;
+    }
+
     pub fn phase(&self) -> Ref<'_, PressPhase> {
         Ref::map(self.inner.borrow(), |inner| &inner.phase)
     }
@@ -46,12 +53,42 @@ impl Press {
     pub(crate) fn set_first_responder(&self, first_responder: WeakView) {
         self.inner.borrow_mut().first_responder = first_responder;
     }
+
+    pub(crate) fn set_phase(&self, phase: PressPhase) {
+        self.inner.borrow_mut().phase = phase;
+    }
+
+    /// How many times the key has repeated while held. 0 until the first
+    /// repeat.
+    pub fn repeat_count(&self) -> usize {
+        self.inner.borrow().repeat_count
+    }
+
+    pub(crate) fn set_repeat_count(&self, repeat_count: usize) {
+        self.inner.borrow_mut().repeat_count = repeat_count;
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PressPhase {
     Began,
-    Ended
+
+    /// The modifier keys held with the key changed, such as Shift being
+    /// pressed while an arrow key is held.
+    Changed,
+
+    Ended,
+
+    /// The key stopped being tracked before it was released, for example
+    /// because the window lost focus.
+    Cancelled
+}
+
+impl PressPhase {
+    /// Whether the key is still held.
+    pub fn is_active(&self) -> bool {
+        matches!(self, PressPhase::Began | PressPhase::Changed)
+    }
 }
 
 impl PartialEq for Press {
@@ -2,15 +2,29 @@ use crate::ui::view::View;
 use crate::ui::window::WindowBehavior;
 use crate::graphics::Layer;
 use crate::graphics::Rectangle;
+use crate::graphics::{Color, Image, Size, Transform};
 use crate::ui::Window;
 use crate::graphics::Context;
+use crate::ui::shadow::ShadowCache;
+use crate::macros::trace_span;
+use crate::ui::FrameStatistics;
+use std::time::Instant;
+
+/// Drawn over the part of the window drawn again each frame, when the window
+/// shows dirty rectangles.
+const DIRTY_RECTANGLE_COLOR: sdl2::pixels::Color = sdl2::pixels::Color { r: 255, g: 0, b: 0, a: 64 };
 
 pub(crate) fn window_display(window_view: View) {
     if window_view.is_hidden() {
         return;
     }
 
+    trace_span!("draw_window");
+
     let window = Window::from_view(window_view.clone());
+    window.update_inspector();
+    window.update_accessibility_tree();
+    window.audit_keyboard_access();
 
     // Additional reference for view controller notification.
     let window1 = window_view.clone();
@@ -18,8 +32,14 @@ pub(crate) fn window_display(window_view: View) {
     let behavior = window_view.behavior.borrow();
     let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
 
+    // The part of the window drawn again, or all of it the first time.
+    let dirty_rectangle = window_view.inner_self.borrow().layer.as_ref()
+        .map_or(Some(window_view.bounds()), |layer| layer.dirty_rectangle());
+
+    let started_at = Instant::now();
+
     // Recursively draw the texture for each layer that needs redisplay.
-    draw_view(&window_view, behavior, &window.context());
+    let layers_drawn = draw_view(&window_view, &window.context());
 
     let inner_view = window_view.inner_self.borrow();
 
@@ -31,57 +51,105 @@ pub(crate) fn window_display(window_view: View) {
     // Draw window texture to renderer
     layer.draw_into_context();
 
+    window.record_frame(FrameStatistics {
+        draw_time: started_at.elapsed(),
+        layers_drawn,
+        dirty_area: dirty_rectangle.as_ref().map_or(0, |rectangle| rectangle.width() as u64 * rectangle.height() as u64)
+    });
+
+    if let Some(dirty_rectangle) = dirty_rectangle.filter(|_| window.shows_dirty_rectangles()) {
+        let context = layer.context();
+        context.fill_rectangle_in_context(DIRTY_RECTANGLE_COLOR, &(&dirty_rectangle * context.render_scale()));
+    }
+
     // Actually draw the window to the screen.
     layer.context().draw();
 
     behavior.view_controller.window_displayed(window1);
 }
 
-fn draw_view(view: &View, behavior: &WindowBehavior, context: &Context) {
+/// Draws the layers of `view` and its subviews that need it, returning how
+/// many were drawn again.
+fn draw_view(view: &View, context: &Context) -> usize {
+    trace_span!("draw_view", view = %view.behavior_name());
+
     let hidden = view.is_hidden();
 
-    {
-        {
-            let mut inner_view = view.inner_self.borrow_mut();
+    let dirty_rectangle = {
+        let mut inner_view = view.inner_self.borrow_mut();
 
-            // TODO: lazily recreate layer if mismatch contexts
-            if inner_view.layer.as_ref().is_none() {
-                let size = inner_view.frame.size.clone();
-                let layer = Layer::new(context.clone(), size, Box::new(view.clone()));
-                inner_view.layer = Some(layer);
-            }
+        // Textures can only be drawn in the context that made them, so the
+        // layer is recreated when the view is drawn in another, such as
+        // after a snapshot, or when moved to another window.
+        //
+        // The layer is also recreated when the view has been resized, such
+        // as the window's root view after the window was, rather than
+        // stretching the old texture, and when the window has moved to a
+        // screen with a different scale, so it isn't blurry.
+        let is_stale = inner_view.layer.as_ref().is_some_and(|layer| {
+            layer.context().id() != context.id() ||
+                *layer.size() != inner_view.frame.size ||
+                layer.render_scale() != context.render_scale()
+        });
 
-            let layer = inner_view.layer.as_mut().unwrap();
+        if inner_view.layer.as_ref().is_none() || is_stale {
+            let size = inner_view.frame.size.clone();
+            let layer = Layer::new(context.clone(), size, Box::new(view.clone()));
+            inner_view.layer = Some(layer);
+        }
 
-            if hidden {
-                layer.skip_draw();
-                return;
-            }
+        let layer = inner_view.layer.as_mut().unwrap();
 
-            if !layer.get_needs_display() {
-                return;
-            }
+        if hidden {
+            layer.skip_draw();
+            return 0;
         }
 
-        let inner_view = view.inner_self.borrow();
-        let layer = inner_view.layer.as_ref().unwrap();
+        match layer.dirty_rectangle() {
+            Some(dirty_rectangle) => dirty_rectangle,
+            None => return 0
+        }
+    };
 
-        layer.draw();
+    // Subviews are drawn into their own layers first, as drawing this view
+    // is limited to the part of its layer that changed.
+    let mut layers_drawn = 0;
+    for subview in view.subviews().iter() {
+        // redraw the subview (if it needs it!)
+        layers_drawn += draw_view(subview, context);
     }
 
     let inner_view = view.inner_self.borrow();
     let layer = inner_view.layer.as_ref().unwrap();
+    let has_subviews = !inner_view.subviews.is_empty();
 
-    for subview in view.subviews().iter() {
-        // redraw the subview (if it needs it!)
-        draw_view(subview, behavior, context);
+    context.set_clip_rectangle(Some(&(&dirty_rectangle * context.render_scale())));
 
-        if subview.is_hidden() {
-            continue;
+    // The view's own drawing is kept apart from its subviews drawn over it,
+    // so when only a subview changed it's copied back rather than drawn
+    // again. Views without subviews are always drawn in full.
+    let needs_display = layer.get_needs_display();
+    if needs_display || !has_subviews || !layer.restore_content() {
+        layer.draw();
+        layers_drawn += 1;
+
+        if !inner_view.clips_to_bounds {
+            layer.clip_to_corner_radius(inner_view.corner_radius);
         }
 
-        let sub_inner_view = subview.inner_self.borrow();
-        let subview_layer = sub_inner_view.layer.as_ref().unwrap();
+        // Only a layer drawn in full can be copied back in full.
+        if has_subviews && needs_display {
+            layer.cache_content();
+        } else {
+            layer.clear_content_cache();
+        }
+    }
+
+    for subview in view.subviews().iter() {
+        let alpha = subview.alpha();
+        if subview.is_hidden() || alpha == 0.0 {
+            continue;
+        }
 
         let frame = subview.frame();
 
@@ -92,6 +160,177 @@ fn draw_view(view: &View, behavior: &WindowBehavior, context: &Context) {
             frame.size.height,
         );
 
-        layer.draw_child_layer(subview_layer, &frame_relative_to_superview_bounds);
+        // Subviews outside the dirty rectangle would be clipped anyway.
+        // Shadows and transforms can be drawn outside the frame, but then
+        // the whole layer is dirty.
+        let is_shadowed_or_transformed = subview.shadow().is_some() || !subview.transform().is_identity();
+        if !is_shadowed_or_transformed && frame_relative_to_superview_bounds.intersection(&dirty_rectangle).is_none() {
+            continue;
+        }
+
+        draw_shadow(layer, subview, &frame_relative_to_superview_bounds, alpha, context);
+
+        let sub_inner_view = subview.inner_self.borrow();
+        let subview_layer = sub_inner_view.layer.as_ref().unwrap();
+
+        // The subview's layer already has its own subviews drawn into it, so
+        // its opacity applies to them as a group.
+        subview_layer.set_opacity(alpha);
+
+        let transform = subview.transform();
+        if transform.is_identity() {
+            layer.draw_child_layer(subview_layer, &frame_relative_to_superview_bounds);
+        } else {
+            layer.draw_child_layer_transformed(subview_layer, &frame_relative_to_superview_bounds, &transform);
+        }
+    }
+
+    if inner_view.clips_to_bounds {
+        layer.clip_to_corner_radius(inner_view.corner_radius);
+    }
+
+    if inner_view.border_width > 0.0 {
+        layer.draw_border(inner_view.border_width, inner_view.border_color.resolved_in(view).to_graphics_color(), inner_view.corner_radius);
+    }
+
+    context.set_clip_rectangle(None);
+
+    layers_drawn
+}
+
+/// Draws `view` and its subviews in `context`, and returns an image of what
+/// was drawn. The view's own alpha, shadow and transform are left out, as
+/// they're applied when drawing it into its superview.
+pub(crate) fn snapshot(view: &View, context: &Context) -> Image<'static> {
+    let size = view.frame().size;
+    let pixel_size = Size {
+        width: (size.width as f32 * context.render_scale()).round() as u32,
+        height: (size.height as f32 * context.render_scale()).round() as u32
+    };
+
+    // Hidden views aren't drawn, so there's nothing to show.
+    if view.is_hidden() {
+        let pixels = vec![0; pixel_size.width as usize * pixel_size.height as usize * 4];
+        return Image::from_pixels(&pixels, &pixel_size, context.render_scale());
+    }
+
+    draw_view(view, context);
+
+    let inner_view = view.inner_self.borrow();
+    let layer = inner_view.layer.as_ref().unwrap();
+    let pixels = layer.read_pixels();
+    Image::from_pixels(&pixels, &layer.pixel_size(), context.render_scale())
+}
+
+/// Draws `view` and its subviews in `context`, then composites them the way
+/// they're drawn into a superview, with the view's own alpha, shadow and
+/// transform, and returns an image of that. The image covers the view's
+/// frame, grown to fit its shadow and transform.
+pub(crate) fn snapshot_subtree(view: &View, context: &Context) -> Image<'static> {
+    let size = view.frame().size;
+    let frame = Rectangle::new(0, 0, size.width, size.height);
+    let transform = view.transform();
+
+    let mut bounds = transformed_bounds(&frame, &transform);
+    if let Some(shadow) = view.shadow() {
+        let blur_radius = shadow.blur_radius as i32;
+        let shadow_frame = Rectangle::new(
+            shadow.offset.x - blur_radius,
+            shadow.offset.y - blur_radius,
+            size.width + shadow.blur_radius * 2,
+            size.height + shadow.blur_radius * 2
+        );
+        bounds = bounds.union(&transformed_bounds(&shadow_frame, &transform));
+    }
+
+    let canvas = Layer::new_no_render(context.clone(), bounds.size.clone());
+    canvas.clear_with_color(Color::RGBA(0, 0, 0, 0));
+
+    let alpha = view.alpha();
+    if !view.is_hidden() && alpha > 0.0 {
+        draw_view(view, context);
+
+        let destination = Rectangle::new(-bounds.origin.x, -bounds.origin.y, size.width, size.height);
+        draw_shadow(&canvas, view, &destination, alpha, context);
+
+        let inner_view = view.inner_self.borrow();
+        let layer = inner_view.layer.as_ref().unwrap();
+        layer.set_opacity(alpha);
+
+        if transform.is_identity() {
+            canvas.draw_child_layer(layer, &destination);
+        } else {
+            canvas.draw_child_layer_transformed(layer, &destination, &transform);
+        }
+    }
+
+    let pixels = canvas.read_pixels();
+    Image::from_pixels(&pixels, &canvas.pixel_size(), context.render_scale())
+}
+
+/// The smallest rectangle holding `rectangle` once `transform` is applied
+/// about its center, the way views are drawn transformed.
+fn transformed_bounds(rectangle: &Rectangle<i32, u32>, transform: &Transform) -> Rectangle<i32, u32> {
+    if transform.is_identity() {
+        return rectangle.clone();
+    }
+
+    let left = rectangle.origin.x as f32;
+    let top = rectangle.origin.y as f32;
+    let right = left + rectangle.size.width as f32;
+    let bottom = top + rectangle.size.height as f32;
+    let center_x = (left + right) / 2.0;
+    let center_y = (top + bottom) / 2.0;
+
+    let about_center = Transform::translation(-center_x, -center_y).concat(transform).translated(center_x, center_y);
+    let corners = [(left, top), (right, top), (right, bottom), (left, bottom)]
+        .map(|(x, y)| about_center.apply(x, y));
+
+    let min_x = corners.iter().map(|corner| corner.0).fold(f32::INFINITY, f32::min).floor();
+    let min_y = corners.iter().map(|corner| corner.1).fold(f32::INFINITY, f32::min).floor();
+    let max_x = corners.iter().map(|corner| corner.0).fold(f32::NEG_INFINITY, f32::max).ceil();
+    let max_y = corners.iter().map(|corner| corner.1).fold(f32::NEG_INFINITY, f32::max).ceil();
+
+    Rectangle::new(min_x as i32, min_y as i32, (max_x - min_x) as u32, (max_y - min_y) as u32)
+}
+
+/// Draws the shadow of `subview` into `layer`, beneath where the subview is
+/// drawn at `destination`.
+fn draw_shadow(layer: &Layer, subview: &View, destination: &Rectangle<i32, u32>, alpha: f32, context: &Context) {
+    let mut inner_view = subview.inner_self.borrow_mut();
+
+    let shadow = match inner_view.shadow.clone() {
+        Some(shadow) => shadow,
+        None => {
+            inner_view.shadow_cache = None;
+            return;
+        }
+    };
+
+    let size = inner_view.frame.size.clone();
+    let corner_radius = inner_view.corner_radius;
+    let is_cached = inner_view.shadow_cache
+        .as_ref()
+        .is_some_and(|cache| cache.matches(context, &shadow, &size, corner_radius));
+
+    if !is_cached {
+        inner_view.shadow_cache = Some(ShadowCache::new(context, &shadow, &size, corner_radius));
+    }
+
+    let shadow_layer = inner_view.shadow_cache.as_ref().unwrap().layer();
+    shadow_layer.set_opacity(shadow.opacity * alpha);
+
+    let blur_radius = shadow.blur_radius as i32;
+    let shadow_destination = Rectangle::new(
+        destination.origin.x + shadow.offset.x - blur_radius,
+        destination.origin.y + shadow.offset.y - blur_radius,
+        shadow_layer.size().width,
+        shadow_layer.size().height
+    );
+
+    if inner_view.transform.is_identity() {
+        layer.draw_child_layer(shadow_layer, &shadow_destination);
+    } else {
+        layer.draw_child_layer_transformed(shadow_layer, &shadow_destination, &inner_view.transform);
     }
 }
@@ -1,23 +1,171 @@
 use crate::ui::timer::Timer;
+use crate::ui::clock::Clock;
+use crate::ui::display_link::DisplayLink;
+use crate::ui::executor::Executor;
+use std::future::Future;
 use std::time::Instant;
 use std::thread::sleep;
 use std::cell::{Cell, RefCell};
 use std::time::Duration;
 use crate::macros::*;
 use crate::platform::thread;
+use std::sync::Mutex;
 
 singleton!(
     RunLoop,
     timers: RefCell::new(Vec::new()),
+    display_links: RefCell::new(Vec::new()),
+    posted_tasks: RefCell::new(Vec::new()),
+    executor: Executor::new(),
+    clock: RefCell::new(None),
     state: Cell::new(State::Running)
 );
 
 pub struct RunLoop {
     timers: RefCell<Vec<Timer>>,
+    display_links: RefCell<Vec<DisplayLink>>,
+
+    // Closures posted from the main thread, to run on the next pass.
+    posted_tasks: RefCell<Vec<Box<dyn FnOnce()>>>,
+
+    // Futures spawned with `spawn_local`.
+    executor: Executor,
+
+    // What time timers and display links see. `None` for the system clock.
+    clock: RefCell<Option<Box<dyn Clock>>>,
+
     state: Cell<State>
 }
 
+type MainThreadTask = Box<dyn FnOnce() + Send>;
+
+/// Closures posted from other threads, waiting for the main thread to take
+/// them.
+pub(crate) struct MainThreadQueue {
+    tasks: Mutex<Vec<MainThreadTask>>
+}
+
+impl MainThreadQueue {
+    pub(crate) const fn new() -> MainThreadQueue {
+        MainThreadQueue {
+            tasks: Mutex::new(Vec::new())
+        }
+    }
+
+    fn push(&self, task: MainThreadTask) {
+        self.tasks.lock().unwrap().push(task);
+    }
+
+    /// Takes the closures posted so far.
+    fn take(&self) -> Vec<MainThreadTask> {
+        std::mem::take(&mut *self.tasks.lock().unwrap())
+    }
+}
+
+/// Closures posted from other threads through a `MainThreadProxy`, to run on
+/// the main thread's next pass of the run loop.
+static MAIN_THREAD_TASKS: MainThreadQueue = MainThreadQueue::new();
+
+/// Schedules closures onto the run loop from any thread.
+///
+/// Views can only be used from the main thread. Work done on a background
+/// thread, such as loading a file, can send its result back with a proxy and
+/// update views from the posted closure:
+///
+/// ```ignore
+/// let proxy = RunLoop::main_thread_proxy();
+/// std::thread::spawn(move || {
+///     let text = std::fs::read_to_string("notes.txt").unwrap_or_default();
+///     proxy.post(move || println!("Loaded {} bytes", text.len()));
+/// });
+/// ```
+#[derive(Clone, Copy)]
+pub struct MainThreadProxy {
+    queue: &'static MainThreadQueue
+}
+
+impl MainThreadProxy {
+    /// Runs `task` on the main thread, on the run loop's next pass.
+    pub fn post(&self, task: impl FnOnce() + Send + 'static) {
+        self.queue.push(Box::new(task));
+    }
+}
+
+impl Default for MainThreadProxy {
+    fn default() -> MainThreadProxy {
+        MainThreadProxy {
+            queue: &MAIN_THREAD_TASKS
+        }
+    }
+}
+
+impl std::fmt::Debug for MainThreadProxy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MainThreadProxy").finish_non_exhaustive()
+    }
+}
+
 impl RunLoop {
+    /// A proxy for posting closures to the run loop from other threads.
+    pub fn main_thread_proxy() -> MainThreadProxy {
+        MainThreadProxy::default()
+    }
+
+    /// Runs `task` on the run loop's next pass, after the current event or
+    /// timer has been handled. Must be called on the main thread; use
+    /// `main_thread_proxy` from other threads.
+    pub fn post(&self, task: impl FnOnce() + 'static) {
+        if !thread::is_main() {
+            println!("Warning: attempted to post a task from non-main thread. Use `RunLoop::main_thread_proxy` instead.");
+            return;
+        }
+
+        self.posted_tasks.borrow_mut().push(Box::new(task));
+    }
+
+    /// Runs `future` on the main thread, polling it on the run loop's next
+    /// pass and then whenever its waker is woken. Must be called on the main
+    /// thread.
+    ///
+    /// Futures can await anything that wakes its waker when ready, including
+    /// channels and I/O completed on other threads:
+    ///
+    /// ```ignore
+    /// RunLoop::borrow().spawn_local(async move {
+    ///     let text = load_notes().await;
+    ///     label.set_text(text);
+    /// });
+    /// ```
+    pub fn spawn_local(&self, future: impl Future<Output = ()> + 'static) {
+        if !thread::is_main() {
+            println!("Warning: attempted to spawn a task from non-main thread. The task has not been spawned.");
+            return;
+        }
+
+        self.executor.spawn(future);
+    }
+
+    /// The current time by the run loop's clock. See `clock::now`.
+    pub fn now(&self) -> Instant {
+        match self.clock.borrow().as_ref() {
+            Some(clock) => clock.now(),
+            None => Instant::now()
+        }
+    }
+
+    /// Replaces the clock timers and display links go by, such as with a
+    /// `ManualClock` in tests. Timers already added keep the time they fire
+    /// at, so the clock should be set before they're added.
+    pub fn set_clock(&self, clock: impl Clock + 'static) {
+        self.replace_clock(Some(Box::new(clock)));
+    }
+
+    /// Replaces the clock, `None` going back to the system clock, and
+    /// returns the clock it replaced so it can be put back.
+    pub(crate) fn replace_clock(&self, clock: Option<Box<dyn Clock>>) -> Option<Box<dyn Clock>> {
+        self.clock.replace(clock)
+    }
+
     pub fn add_timer(&self, timer: Timer) {
         if !thread::is_main() {
             println!("Warning: attempted to add timer from non-main thread. The timer has not been added.");
@@ -27,6 +175,17 @@ impl RunLoop {
         timers.push(timer)
     }
 
+    /// Starts calling `display_link`'s callback each frame. Prefer
+    /// `DisplayLink::add_to_run_loop`.
+    pub fn add_display_link(&self, display_link: DisplayLink) {
+        if !thread::is_main() {
+            println!("Warning: attempted to add display link from non-main thread. The display link has not been added.");
+            return;
+        }
+
+        self.display_links.borrow_mut().push(display_link);
+    }
+
     /// Run the run loop until the application exits.
     ///
     /// This isn't intended to be called in your app.
@@ -42,7 +201,7 @@ impl RunLoop {
             let delta = now.duration_since(last_loop_instant);
             last_loop_instant = now;
 
-            self.run_timers();
+            self.run_once();
 
             let delta_milliseconds = delta.as_millis();
 
@@ -52,38 +211,84 @@ impl RunLoop {
         }
     }
 
+    /// Makes a single pass of the run loop, without waiting: runs posted
+    /// tasks and woken futures, then display links, then the timers that are
+    /// due. Used by `TestDriver` to run the loop a step at a time.
+    pub(crate) fn run_once(&self) {
+        trace_span!("run_loop_pass");
+
+        self.run_posted_tasks();
+        self.executor.poll_woken_tasks();
+
+        // Display links run before the timers that render windows, so
+        // what they change is drawn in the same frame.
+        self.run_display_links();
+        self.run_timers();
+    }
+
     /// Notify the run loop to break the loop and end.
     pub fn exit(&self) {
         self.state.set(State::Exit);
     }
 
-    fn run_timers(&self) {
-        let mut local_timers: Vec<Timer> = Vec::new();
+    /// Runs the closures posted since the last pass. Closures they post run
+    /// on the following pass.
+    fn run_posted_tasks(&self) {
+        trace_span!("posted_tasks");
+        let posted_tasks = std::mem::take(&mut *self.posted_tasks.borrow_mut());
+        for task in posted_tasks {
+            task();
+        }
 
-        {
-            let mut timers = self.timers.borrow_mut();
+        for task in MAIN_THREAD_TASKS.take() {
+            task();
+        }
+    }
 
-            for timer in timers.drain(..) {
-                if timer.is_valid() {
-                    local_timers.push(timer);
-                }
-            }
+    fn run_display_links(&self) {
+        trace_span!("display_links");
+        let display_links: Vec<DisplayLink> = self.display_links.borrow_mut().drain(..).collect();
+        let now = self.now();
 
-            timers.clear();
+        for display_link in display_links.iter() {
+            display_link.tick(now);
         }
 
-        for timer in local_timers.iter() {
-            if timer.fire_at() < Instant::now() {
-                timer.fire();
-            }
-        }
+        // Keep the display links still valid, after any added by callbacks.
+        let mut current = self.display_links.borrow_mut();
+        let added: Vec<DisplayLink> = current.drain(..).collect();
+        current.extend(display_links.into_iter().filter(|display_link| display_link.is_valid()));
+        current.extend(added);
+    }
 
-        for timer in local_timers.drain(..) {
+    fn run_timers(&self) {
+        trace_span!("timers");
+        // Take the timers out while they fire, so that their actions can add
+        // new timers.
+        let local_timers: Vec<Timer> = self.timers.borrow_mut().drain(..).collect();
+
+        for timer in fire_due_timers(local_timers, self.now()) {
             self.add_timer(timer);
         }
     }
 }
 
+/// Fires each valid timer due by `now`, returning the timers still valid
+/// afterwards.
+///
+/// A timer invalidated by an earlier timer's action doesn't fire, and
+/// invalidated timers, including once-off timers that have fired, are
+/// dropped.
+fn fire_due_timers(timers: Vec<Timer>, now: Instant) -> Vec<Timer> {
+    for timer in timers.iter() {
+        if timer.is_valid() && timer.fire_at() < now {
+            timer.fire();
+        }
+    }
+
+    timers.into_iter().filter(|timer| timer.is_valid()).collect()
+}
+
 #[derive(Copy, Clone)]
 enum State {
     Running,
@@ -98,3 +303,53 @@ impl State {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_main_thread_proxy() {
+        // A queue of the test's own, as other tests may post to the run
+        // loop's at the same time.
+        static QUEUE: MainThreadQueue = MainThreadQueue::new();
+        let proxy = MainThreadProxy { queue: &QUEUE };
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            proxy.post(move || sender.send("loaded").unwrap());
+        }).join().unwrap();
+
+        // Nothing runs until the run loop takes the tasks.
+        assert!(receiver.try_recv().is_err());
+
+        for task in QUEUE.take() {
+            task();
+        }
+        assert_eq!(receiver.try_recv(), Ok("loaded"));
+        assert!(QUEUE.take().is_empty());
+    }
+
+    #[test]
+    fn test_fire_due_timers() {
+        let fired = Rc::new(Cell::new(0));
+
+        let fired_clone = fired.clone();
+        let repeating = Timer::new_repeating(Duration::from_millis(0), move || fired_clone.set(fired_clone.get() + 1));
+
+        // The first timer invalidates the second before it's due to fire.
+        let invalidated = Timer::new_repeating(Duration::from_millis(0), || panic!("an invalidated timer fired"));
+        let invalidated_clone = invalidated.clone();
+        let once = Timer::new_once(move || invalidated_clone.invalidate());
+
+        let later = Timer::new_once_delayed(Duration::from_secs(3600), || panic!("a timer fired early"));
+
+        let now = Instant::now() + Duration::from_millis(1);
+        let timers = fire_due_timers(vec![once, invalidated.clone(), repeating.clone(), later], now);
+
+        assert_eq!(fired.get(), 1);
+        assert!(!invalidated.is_valid());
+        assert_eq!(timers.len(), 2);
+    }
+}
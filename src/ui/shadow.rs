@@ -0,0 +1,83 @@
+use crate::graphics::{Context, Layer, Point, Size};
+use crate::ui::Color;
+
+/// A shadow drawn beneath a view, in the shape of its rounded rectangle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shadow {
+    pub color: Color,
+
+    /// How far the shadow is moved from the view.
+    pub offset: Point<i32>,
+
+    /// How far the edges of the shadow are blurred.
+    pub blur_radius: u32,
+
+    /// The opacity of the shadow, from 0 to 1, applied on top of the
+    /// color's alpha.
+    pub opacity: f32
+}
+
+impl Shadow {
+    pub fn new(color: Color, offset: Point<i32>, blur_radius: u32, opacity: f32) -> Shadow {
+        Shadow {
+            color,
+            offset,
+            blur_radius,
+            opacity
+        }
+    }
+}
+
+impl Default for Shadow {
+    /// A soft black shadow just below the view.
+    fn default() -> Self {
+        Shadow::new(Color::black(), Point::new(0, 3), 6, 0.3)
+    }
+}
+
+/// The blurred shadow of a view, kept between frames so the blur is only
+/// computed again when the shadow's shape changes.
+pub(crate) struct ShadowCache {
+    color: Color,
+    blur_radius: u32,
+    size: Size<u32>,
+    corner_radius: f32,
+    context_id: u32,
+    layer: Layer
+}
+
+impl ShadowCache {
+    pub(crate) fn new(context: &Context, shadow: &Shadow, size: &Size<u32>, corner_radius: f32) -> ShadowCache {
+        let layer = Layer::new_shadow(
+            context.clone(),
+            size.clone(),
+            corner_radius,
+            shadow.blur_radius,
+            shadow.color.to_graphics_color()
+        );
+
+        ShadowCache {
+            color: shadow.color.clone(),
+            blur_radius: shadow.blur_radius,
+            size: size.clone(),
+            corner_radius,
+            context_id: context.id(),
+            layer
+        }
+    }
+
+    /// Whether the cached shadow can be drawn for these values. The offset
+    /// and opacity are applied when drawing, so they can change freely.
+    pub(crate) fn matches(&self, context: &Context, shadow: &Shadow, size: &Size<u32>, corner_radius: f32) -> bool {
+        self.color == shadow.color &&
+            self.blur_radius == shadow.blur_radius &&
+            self.size == *size &&
+            self.corner_radius == corner_radius &&
+            self.context_id == context.id() &&
+            self.layer.render_scale() == context.render_scale()
+    }
+
+    pub(crate) fn layer(&self) -> &Layer {
+        &self.layer
+    }
+}
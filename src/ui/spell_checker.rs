@@ -0,0 +1,276 @@
+//! Spell checking for text fields and text views.
+//!
+//! Spell checking is off until the application sets a spell checker, such
+//! as one loaded from a word list at launch:
+//!
+//! ```ignore
+//! let checker = DictionarySpellChecker::load_file("words.txt")?;
+//! spell_checker::set_spell_checker(Some(Arc::new(checker)));
+//! ```
+//!
+//! Text fields then check their text on a background thread once typing
+//! pauses, underline misspelled words with a red squiggle, see
+//! `Key::Misspelled`, and offer suggestions in the context menu of a
+//! misspelled word. Implement `SpellChecker` to plug in another spelling
+//! library, such as Hunspell.
+
+use crate::macros::*;
+use crate::platform::bundle::Bundle;
+use crate::text::text::Text;
+use crate::text::word_boundary::{self, SegmentKind};
+use crate::ui::run_loop::RunLoop;
+use std::collections::HashSet;
+use std::future::Future;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The most suggestions offered for a misspelled word.
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// How long typing has to pause before a text field checks its spelling, so
+/// it isn't checked again on every keystroke.
+pub(crate) const CHECK_DELAY: Duration = Duration::from_millis(300);
+
+/// Checks the spelling of words. Words are checked on a background thread,
+/// so spell checkers have to be safe to share between threads.
+pub trait SpellChecker: Send + Sync {
+    /// Whether `word` is spelled correctly.
+    fn is_correct(&self, word: &str) -> bool;
+
+    /// Words `word` may have been meant to be, best first.
+    fn suggestions(&self, word: &str) -> Vec<String>;
+}
+
+/// Checks words against a list of correctly spelled words. Words are
+/// matched regardless of case, unless they're in the list capitalized, such
+/// as names. Suggestions are the words that are the fewest typos away.
+#[derive(Clone, Debug, Default)]
+pub struct DictionarySpellChecker {
+    words: HashSet<String>
+}
+
+impl DictionarySpellChecker {
+    pub fn new<'a>(words: impl IntoIterator<Item = &'a str>) -> DictionarySpellChecker {
+        let mut checker = DictionarySpellChecker::default();
+        for word in words {
+            checker.add_word(word);
+        }
+        checker
+    }
+
+    /// The checker for the word list in `source`, one word per line. Blank
+    /// lines, and lines starting with `#`, are left out.
+    pub fn parse(source: &str) -> DictionarySpellChecker {
+        DictionarySpellChecker::new(
+            source
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        )
+    }
+
+    /// Loads the word list in the file `name`. A relative path is relative
+    /// to the application's `resource` directory.
+    pub fn load_file(name: &str) -> Result<DictionarySpellChecker, String> {
+        let path = Bundle::path_for_resource(name);
+        let source = std::fs::read_to_string(&path).map_err(|error| format!("{}: {}", path, error))?;
+        Ok(DictionarySpellChecker::parse(&source))
+    }
+
+    pub fn add_word(&mut self, word: &str) {
+        self.words.insert(word.to_string());
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word) || self.words.contains(&word.to_lowercase())
+    }
+}
+
+impl SpellChecker for DictionarySpellChecker {
+    fn is_correct(&self, word: &str) -> bool {
+        self.contains(word)
+    }
+
+    fn suggestions(&self, word: &str) -> Vec<String> {
+        let lowercase_word = word.to_lowercase();
+        let length = lowercase_word.chars().count();
+
+        let mut suggestions: Vec<(usize, &String)> = self.words
+            .iter()
+            .filter(|candidate| candidate.chars().count().abs_diff(length) <= 2)
+            .map(|candidate| (edit_distance(&lowercase_word, &candidate.to_lowercase()), candidate))
+            .filter(|(distance, _)| *distance <= 2)
+            .collect();
+        suggestions.sort();
+
+        let is_capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        suggestions
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, suggestion)| if is_capitalized { capitalized(suggestion) } else { suggestion.clone() })
+            .collect()
+    }
+}
+
+/// How many characters have to be inserted, deleted, replaced or swapped
+/// with their neighbour to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // Distances from the prefixes of `a` to those of `b`, two rows back.
+    let mut before_previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+
+    previous[b.len()]
+}
+
+fn capitalized(word: &str) -> String {
+    let mut characters = word.chars();
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => String::new()
+    }
+}
+
+struct SpellChecking {
+    checker: Option<Arc<dyn SpellChecker>>
+}
+
+singleton!(
+    SpellChecking,
+    checker: None
+);
+
+/// The spell checker text fields use, if spell checking is on.
+pub fn spell_checker() -> Option<Arc<dyn SpellChecker>> {
+    SpellChecking::borrow().checker.clone()
+}
+
+/// Turns spell checking on with `checker`, or off with `None`. Text fields
+/// check their text the next time it's edited, so this is best set before
+/// any windows open.
+pub fn set_spell_checker(checker: Option<Arc<dyn SpellChecker>>) {
+    SpellChecking::borrow_mut().checker = checker;
+}
+
+/// The grapheme ranges of the words in `text` that `checker` says are
+/// misspelled. Words with digits in them, such as "2nd", are left alone.
+pub(crate) fn misspelled_ranges(checker: &dyn SpellChecker, text: &str) -> Vec<Range<usize>> {
+    let text = Text::new(text.to_string());
+    let graphemes: Vec<&str> = text.string().graphemes(true).collect();
+
+    word_boundary::word_segments(&text)
+        .into_iter()
+        .filter(|(_, kind)| *kind == SegmentKind::Word)
+        .filter(|(range, _)| {
+            let word = graphemes[range.clone()].concat();
+            !word.chars().any(|character| character.is_numeric()) && !checker.is_correct(&word)
+        })
+        .map(|(range, _)| range)
+        .collect()
+}
+
+/// What a background check has found, and the task waiting for it.
+#[derive(Default)]
+struct BackgroundCheck {
+    ranges: Option<Vec<Range<usize>>>,
+    waker: Option<Waker>
+}
+
+/// Waits for a background check to finish.
+struct BackgroundCheckFuture {
+    check: Arc<Mutex<BackgroundCheck>>
+}
+
+impl Future for BackgroundCheckFuture {
+    type Output = Vec<Range<usize>>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Vec<Range<usize>>> {
+        let mut check = self.check.lock().unwrap();
+        match check.ranges.take() {
+            Some(ranges) => Poll::Ready(ranges),
+            None => {
+                check.waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Finds the misspelled words of `text` on a background thread, and calls
+/// `completion` with their ranges back on the main thread.
+pub(crate) fn check_in_background(checker: Arc<dyn SpellChecker>, text: String, completion: impl FnOnce(Vec<Range<usize>>) + 'static) {
+    let check = Arc::new(Mutex::new(BackgroundCheck::default()));
+
+    let future = BackgroundCheckFuture { check: check.clone() };
+    RunLoop::borrow().spawn_local(async move {
+        completion(future.await);
+    });
+
+    std::thread::spawn(move || {
+        let ranges = misspelled_ranges(checker.as_ref(), &text);
+
+        let mut check = check.lock().unwrap();
+        check.ranges = Some(ranges);
+        if let Some(waker) = check.waker.take() {
+            waker.wake();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker() -> DictionarySpellChecker {
+        DictionarySpellChecker::parse("# Words\nthe\ncat\nsat\non\nmat\nhat\nParis\n")
+    }
+
+    #[test]
+    fn test_is_correct() {
+        let checker = checker();
+        assert!(checker.is_correct("cat"));
+        assert!(checker.is_correct("The"));
+        assert!(checker.is_correct("Paris"));
+        assert!(!checker.is_correct("paris"));
+        assert!(!checker.is_correct("teh"));
+        assert!(!checker.is_correct("# Words"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let checker = checker();
+        assert_eq!(checker.suggestions("teh"), vec!["the"]);
+        assert_eq!(checker.suggestions("Cst"), vec!["Cat", "Hat", "Mat", "Sat"]);
+        assert!(checker.suggestions("xylophone").is_empty());
+
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("ab", "ba"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_misspelled_ranges() {
+        let checker = checker();
+        assert_eq!(misspelled_ranges(&checker, "Teh cat sat on the 2nd mtt."), vec![0..3, 23..26]);
+        assert_eq!(misspelled_ranges(&checker, "👨‍👩‍👧 teh"), vec![2..5]);
+        assert!(misspelled_ranges(&checker, "").is_empty());
+    }
+}
@@ -2,6 +2,8 @@ use std::time::Duration;
 use std::time::Instant;
 use std::cell::Cell;
 use std::rc::Rc;
+use crate::ui::run_loop::RunLoop;
+use crate::ui::clock;
 
 // A repeating or once-off Timer object, to be run by the main loop.
 pub struct Timer {
@@ -31,7 +33,7 @@ struct TimerInner {
 
 impl Timer {
     pub fn new(interval: Duration, repeats: bool, action: impl Fn() -> () + 'static) -> Self {
-        let now = Instant::now();
+        let now = clock::now();
         Self {
             inner: Rc::new(TimerInner {
                 interval,
@@ -56,9 +58,27 @@ impl Timer {
         Timer::new(interval, true, action)
     }
 
+    /// Runs `action` once, after `delay`, on the run loop. The returned
+    /// handle can cancel or reschedule it; dropping the handle doesn't.
+    pub fn after(delay: Duration, action: impl Fn() + 'static) -> TimerHandle {
+        let timer = Timer::new(delay, false, action);
+        RunLoop::borrow().add_timer(timer.clone());
+        TimerHandle { timer }
+    }
+
+    /// Runs `action` once, at `date`, on the run loop. A date in the past
+    /// runs it on the next pass of the run loop. The returned handle can
+    /// cancel or reschedule it; dropping the handle doesn't.
+    pub fn scheduled(date: Instant, action: impl Fn() + 'static) -> TimerHandle {
+        let timer = Timer::new(date.saturating_duration_since(clock::now()), false, action);
+        timer.inner.fire_at.set(date);
+        RunLoop::borrow().add_timer(timer.clone());
+        TimerHandle { timer }
+    }
+
     // Run the action
     pub(crate) fn fire(&self) {
-        let current_fire_at = Instant::now();
+        let current_fire_at = clock::now();
         (self.inner.action)();
 
         self.inner.fire_at.set(current_fire_at + self.inner.interval);
@@ -86,6 +106,51 @@ impl Timer {
     }
 }
 
+/// A handle to a timer added to the run loop by `Timer::after` or
+/// `Timer::scheduled`, for cancelling or rescheduling it before it fires.
+pub struct TimerHandle {
+    timer: Timer
+}
+
+impl TimerHandle {
+    /// Whether the timer has yet to fire and hasn't been cancelled.
+    pub fn is_pending(&self) -> bool {
+        self.timer.is_valid()
+    }
+
+    /// When the timer will fire, or `None` if it isn't pending.
+    pub fn fire_at(&self) -> Option<Instant> {
+        if self.is_pending() {
+            Some(self.timer.fire_at())
+        } else {
+            None
+        }
+    }
+
+    /// Stops the timer from firing. The run loop drops it on its next pass,
+    /// even if it's cancelled by another timer firing in the same pass.
+    pub fn cancel(&self) {
+        self.timer.invalidate();
+    }
+
+    /// Moves the timer to fire at `date` instead. Returns `false`, doing
+    /// nothing, if the timer has already fired or was cancelled.
+    pub fn reschedule(&self, date: Instant) -> bool {
+        if !self.is_pending() {
+            return false;
+        }
+
+        self.timer.inner.fire_at.set(date);
+        true
+    }
+
+    /// Moves the timer to fire `delay` from now instead. Returns `false`,
+    /// doing nothing, if the timer has already fired or was cancelled.
+    pub fn reschedule_after(&self, delay: Duration) -> bool {
+        self.reschedule(clock::now() + delay)
+    }
+}
+
 impl Clone for Timer {
     fn clone(&self) -> Self {
         Self {
@@ -162,4 +227,28 @@ mod tests {
         timer.fire();
         unsafe { assert!(FIRED); }
     }
+
+    #[test]
+    fn test_timer_handle() {
+        let handle = Timer::after(Duration::from_secs(60), || {});
+        assert!(handle.is_pending());
+        assert!(handle.fire_at().unwrap() > Instant::now() + Duration::from_secs(59));
+
+        let date = Instant::now() + Duration::from_secs(5);
+        assert!(handle.reschedule(date));
+        assert_eq!(handle.fire_at(), Some(date));
+
+        handle.cancel();
+        assert!(!handle.is_pending());
+        assert_eq!(handle.fire_at(), None);
+        assert!(!handle.reschedule_after(Duration::from_secs(1)));
+
+        let date = Instant::now() + Duration::from_secs(30);
+        let handle = Timer::scheduled(date, || {});
+        assert_eq!(handle.fire_at(), Some(date));
+
+        // Once a once-off timer fires, it's no longer pending.
+        handle.timer.fire();
+        assert!(!handle.is_pending());
+    }
 }
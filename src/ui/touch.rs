@@ -1,6 +1,7 @@
 use crate::graphics::Point;
 use crate::ui::{View, Window};
 use std::time::Instant;
+use crate::ui::clock;
 use crate::ui::gesture::recognizer::Recognizer;
 use std::rc::{Rc, Weak};
 use std::cell::{Ref, RefCell};
@@ -9,10 +10,21 @@ struct TouchInner {
     id: usize,
     timestamp: Instant,
     position: Point<i32>,
+    previous_timestamp: Instant,
+    previous_position: Point<i32>,
+
+    // In points per second, smoothed over the moves so far.
+    velocity: Point<f32>,
+
+    tap_count: usize,
     phase: TouchPhase,
     view: Option<View>,
     window: Option<Window>,
-    gesture_recognizers: Vec<Weak<Box<dyn Recognizer>>>
+    gesture_recognizers: Vec<Weak<Box<dyn Recognizer>>>,
+
+    // Set once a gesture recognizer has recognized the touch as its gesture
+    // and cancelled it in the view. Its recognizers still receive it.
+    is_cancelled_in_view: bool
 }
 
 pub struct Touch {
@@ -31,15 +43,21 @@ pub enum TouchPhase {
 
 impl Touch {
     pub fn new(id: usize, position: Point<i32>) -> Touch {
+        let now = clock::now();
         Touch {
             inner: Rc::new(RefCell::new(TouchInner {
                 id,
-                timestamp: Instant::now(),
-                position,
+                timestamp: now,
+                position: position.clone(),
+                previous_timestamp: now,
+                previous_position: position,
+                velocity: Point::new(0.0, 0.0),
+                tap_count: 1,
                 phase: TouchPhase::Began,
                 view: None,
                 window: None,
-                gesture_recognizers: Vec::new()
+                gesture_recognizers: Vec::new(),
+                is_cancelled_in_view: false
             }))
         }
     }
@@ -48,8 +66,52 @@ impl Touch {
         self.inner.borrow().position.clone()
     }
 
+    /// Where the touch was before it last moved, or where it began if it
+    /// hasn't moved.
+    pub fn previous_position(&self) -> Point<i32> {
+        self.inner.borrow().previous_position.clone()
+    }
+
+    /// Moves the touch to `position`, keeping where it was, and updating
+    /// its velocity. Staying put isn't a move.
     pub(crate) fn set_position(&self, position: Point<i32>) {
-        self.inner.borrow_mut().position = position;
+        self.move_to(position, clock::now());
+    }
+
+    fn move_to(&self, position: Point<i32>, now: Instant) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.position == position {
+            return;
+        }
+
+        let seconds = now.saturating_duration_since(inner.timestamp).as_secs_f32();
+        if seconds > 0.0 {
+            // Smooth out the velocity, as touch events don't arrive evenly.
+            let velocity = inner.velocity.clone();
+            inner.velocity = Point::new(
+                velocity.x * 0.2 + (position.x - inner.position.x) as f32 / seconds * 0.8,
+                velocity.y * 0.2 + (position.y - inner.position.y) as f32 / seconds * 0.8
+            );
+        }
+
+        inner.previous_position = std::mem::replace(&mut inner.position, position);
+        inner.previous_timestamp = std::mem::replace(&mut inner.timestamp, now);
+    }
+
+    /// How fast the touch is moving, in points per second, with right and
+    /// down positive. Zero until it has moved over some time.
+    pub fn velocity(&self) -> Point<f32> {
+        self.inner.borrow().velocity.clone()
+    }
+
+    /// How many times in quick succession the touch's place was touched,
+    /// as the platform counts them: 2 for the second touch of a double tap.
+    pub fn tap_count(&self) -> usize {
+        self.inner.borrow().tap_count
+    }
+
+    pub(crate) fn set_tap_count(&self, tap_count: usize) {
+        self.inner.borrow_mut().tap_count = tap_count.max(1);
     }
 
     pub(crate) fn set_view(&self, view: View) {
@@ -82,6 +144,15 @@ impl Touch {
         self.inner.borrow().window.clone()
     }
 
+    /// Whether a gesture recognizer took the touch away from its view.
+    pub(crate) fn is_cancelled_in_view(&self) -> bool {
+        self.inner.borrow().is_cancelled_in_view
+    }
+
+    pub(crate) fn set_cancelled_in_view(&self) {
+        self.inner.borrow_mut().is_cancelled_in_view = true;
+    }
+
     pub fn phase(&self) -> TouchPhase {
         self.inner.borrow().phase
     }
@@ -90,10 +161,16 @@ impl Touch {
         self.inner.borrow_mut().phase = phase;
     }
 
+    /// When the touch began, or last moved.
     pub fn timestamp(&self) -> Instant {
         self.inner.borrow().timestamp
     }
 
+    /// When the touch began, or moved before its last move.
+    pub fn previous_timestamp(&self) -> Instant {
+        self.inner.borrow().previous_timestamp
+    }
+
     pub fn id(&self) -> usize {
         self.inner.borrow().id
     }
@@ -127,6 +204,7 @@ mod tests {
     use crate::graphics::Rectangle;
     use crate::ui::gesture::pan_recognizer::PanRecognizer;
     use std::rc::Rc;
+    use std::time::Duration;
 
     #[test]
     fn test_get_position() {
@@ -185,6 +263,26 @@ mod tests {
         assert_eq!(touch.gesture_recognizers().len(), 1);
     }
 
+    #[test]
+    fn test_previous_position_and_velocity() {
+        let touch = Touch::new(0, Point::new(10, 10));
+        assert_eq!(touch.previous_position(), Point::new(10, 10));
+        assert_eq!(touch.velocity(), Point::new(0.0, 0.0));
+        assert_eq!(touch.tap_count(), 1);
+
+        let began_at = touch.timestamp();
+        touch.move_to(Point::new(30, 0), began_at + Duration::from_millis(100));
+        assert_eq!(touch.previous_position(), Point::new(10, 10));
+        assert_eq!(touch.previous_timestamp(), began_at);
+        assert_eq!(touch.timestamp(), began_at + Duration::from_millis(100));
+        assert_eq!(touch.velocity(), Point::new(160.0, -80.0));
+
+        // Staying put doesn't count as moving.
+        touch.move_to(Point::new(30, 0), began_at + Duration::from_millis(200));
+        assert_eq!(touch.previous_position(), Point::new(10, 10));
+        assert_eq!(touch.timestamp(), began_at + Duration::from_millis(100));
+    }
+
     #[test]
     fn test_clone() {
         let touch = Touch::new(0, Point { x: 5, y: 5 });
@@ -0,0 +1,159 @@
+use crate::graphics::{Rectangle, Size};
+use crate::ui::layout_direction::LayoutDirection;
+
+/// The parts of a view's frame that change when its superview is resized.
+///
+/// A view without any keeps its size and distance from the top left of its
+/// superview. Adding flexible parts spreads the change in the superview's
+/// size between them, in proportion to their current sizes: a view with a
+/// `FlexibleWidth` stretches with the window, and one with a
+/// `FlexibleLeftMargin` stays the same distance from the right edge.
+///
+/// The leading and trailing margins are the left and right margins in a
+/// superview laid out left to right, and the other way around right to
+/// left, see `View::effective_layout_direction`. A view keeping to its
+/// superview's trailing edge is mirrored when the direction flips.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutoresizingMask {
+    FlexibleLeftMargin,
+    FlexibleWidth,
+    FlexibleRightMargin,
+    FlexibleLeadingMargin,
+    FlexibleTrailingMargin,
+    FlexibleTopMargin,
+    FlexibleHeight,
+    FlexibleBottomMargin
+}
+
+/// The frame of a view with `mask` after its superview, laid out in
+/// `layout_direction`, is resized from `old_size` to `new_size`.
+pub(crate) fn autoresized_frame(
+    frame: &Rectangle<i32, u32>,
+    mask: &[AutoresizingMask],
+    layout_direction: LayoutDirection,
+    old_size: &Size<u32>,
+    new_size: &Size<u32>
+) -> Rectangle<i32, u32> {
+    let (left_margin, right_margin) = match layout_direction {
+        LayoutDirection::LeftToRight => (AutoresizingMask::FlexibleLeadingMargin, AutoresizingMask::FlexibleTrailingMargin),
+        LayoutDirection::RightToLeft => (AutoresizingMask::FlexibleTrailingMargin, AutoresizingMask::FlexibleLeadingMargin)
+    };
+
+    let (x, width) = resize_axis(
+        frame.origin.x,
+        frame.size.width,
+        old_size.width,
+        new_size.width,
+        [
+            mask.contains(&AutoresizingMask::FlexibleLeftMargin) || mask.contains(&left_margin),
+            mask.contains(&AutoresizingMask::FlexibleWidth),
+            mask.contains(&AutoresizingMask::FlexibleRightMargin) || mask.contains(&right_margin)
+        ]
+    );
+
+    let (y, height) = resize_axis(
+        frame.origin.y,
+        frame.size.height,
+        old_size.height,
+        new_size.height,
+        [
+            mask.contains(&AutoresizingMask::FlexibleTopMargin),
+            mask.contains(&AutoresizingMask::FlexibleHeight),
+            mask.contains(&AutoresizingMask::FlexibleBottomMargin)
+        ]
+    );
+
+    Rectangle::new(x, y, width, height)
+}
+
+/// Spreads the change in the superview's size along one axis between the
+/// flexible parts: the margin before the view, its size, and the margin
+/// after.
+fn resize_axis(origin: i32, size: u32, old_length: u32, new_length: u32, flexible: [bool; 3]) -> (i32, u32) {
+    let delta = new_length as f32 - old_length as f32;
+    let flexible_count = flexible.iter().filter(|is_flexible| **is_flexible).count();
+    if delta == 0.0 || flexible_count == 0 {
+        return (origin, size);
+    }
+
+    let parts = [
+        origin as f32,
+        size as f32,
+        old_length as f32 - origin as f32 - size as f32
+    ];
+
+    let total: f32 = parts
+        .iter()
+        .zip(flexible.iter())
+        .filter(|(_, is_flexible)| **is_flexible)
+        .map(|(part, _)| part.max(0.0))
+        .sum();
+
+    // Shares in proportion to the parts' sizes, or evenly if they're all
+    // empty.
+    let share = |index: usize| {
+        if !flexible[index] {
+            0.0
+        } else if total > 0.0 {
+            delta * parts[index].max(0.0) / total
+        } else {
+            delta / flexible_count as f32
+        }
+    };
+
+    let origin = (origin as f32 + share(0)).round() as i32;
+    let size = (size as f32 + share(1)).round().max(0.0) as u32;
+    (origin, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AutoresizingMask::*;
+    use LayoutDirection::*;
+
+    #[test]
+    fn test_autoresized_frame() {
+        let frame = Rectangle::new(10, 20, 100, 50);
+        let old_size = Size::new(200, 100);
+        let new_size = Size::new(300, 150);
+
+        // Fixed to the top left.
+        assert_eq!(autoresized_frame(&frame, &[], LeftToRight, &old_size, &new_size), frame);
+
+        // Stretching with the superview.
+        assert_eq!(
+            autoresized_frame(&frame, &[FlexibleWidth, FlexibleHeight], LeftToRight, &old_size, &new_size),
+            Rectangle::new(10, 20, 200, 100)
+        );
+
+        // Fixed to the bottom right.
+        assert_eq!(
+            autoresized_frame(&frame, &[FlexibleLeftMargin, FlexibleTopMargin], LeftToRight, &old_size, &new_size),
+            Rectangle::new(110, 70, 100, 50)
+        );
+
+        // Centered, with the margins growing in proportion.
+        let centered = Rectangle::new(50, 25, 100, 50);
+        assert_eq!(
+            autoresized_frame(&centered, &[FlexibleLeftMargin, FlexibleRightMargin, FlexibleTopMargin, FlexibleBottomMargin], LeftToRight, &old_size, &new_size),
+            Rectangle::new(100, 50, 100, 50)
+        );
+
+        // Shrinking never makes the size negative.
+        let tiny = Size::new(10, 10);
+        assert_eq!(autoresized_frame(&frame, &[FlexibleWidth], LeftToRight, &old_size, &tiny).size.width, 0);
+
+        // Fixed to the trailing edge, which is on the right left to right
+        // and on the left right to left.
+        assert_eq!(
+            autoresized_frame(&frame, &[FlexibleLeadingMargin], LeftToRight, &old_size, &new_size),
+            Rectangle::new(110, 20, 100, 50)
+        );
+        assert_eq!(autoresized_frame(&frame, &[FlexibleLeadingMargin], RightToLeft, &old_size, &new_size), frame);
+        assert_eq!(
+            autoresized_frame(&frame, &[FlexibleTrailingMargin], RightToLeft, &old_size, &new_size),
+            Rectangle::new(110, 20, 100, 50)
+        );
+    }
+}
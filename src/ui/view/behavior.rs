@@ -1,5 +1,13 @@
 use crate::ui::{View, WeakView, Touch};
 use crate::ui::press::Press;
+use crate::ui::event::ScrollEvent;
+use crate::ui::command::Command;
+use crate::graphics::{Point, Rectangle};
+use crate::platform::history::UndoManager;
+use crate::platform::cursor::Cursor;
+use crate::ui::appearance::Appearance;
+use crate::ui::accessibility::{AccessibilityAction, AccessibilityRole};
+use crate::ui::menu::Menu;
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -37,6 +45,16 @@ pub trait Behavior {
         }
     }
 
+    /// Like `set_needs_display`, but only for `rectangle` of the view,
+    /// relative to its top left. See `View::set_needs_display_in_rect`.
+    fn set_needs_display_in_rect(&self, rectangle: &Rectangle<i32, u32>) {
+        if let Some(super_behavior) = self.super_behavior() {
+            super_behavior.set_needs_display_in_rect(rectangle);
+        } else {
+            panic!("set_needs_display_in_rect behavior not implemented. Have you implemented `super_behavior()`?")
+        }
+    }
+
     fn draw(&self) {
         if let Some(super_behavior) = self.super_behavior() {
             super_behavior.draw()
@@ -45,10 +63,151 @@ pub trait Behavior {
         }
     }
 
+    /// Called after the view is resized, such as when the window is, once
+    /// the subviews' autoresizing masks have been applied.
+    ///
+    /// Override this to position subviews that autoresizing masks can't,
+    /// such as to keep them evenly spaced.
+    fn layout_subviews(&self) {}
+
+    /// Called when the view's window moves to a screen with a different
+    /// render scale, such as from a 1x monitor to a Retina one. The view is
+    /// redrawn at the new scale afterwards.
+    ///
+    /// Override this to update anything sized in pixels rather than points,
+    /// such as text layout.
+    fn render_scale_did_change(&self, _render_scale: f32) {}
+
+    /// Whether `point`, relative to the top left of the view, is within the
+    /// view. By default, any point in the view's frame is.
+    ///
+    /// Override this to give a small view a larger area to touch, or to
+    /// ignore touches on transparent parts of the view.
+    fn point_inside(&self, point: &Point<i32>) -> bool {
+        if let Some(super_behavior) = self.super_behavior() {
+            super_behavior.point_inside(point)
+        } else {
+            panic!("point_inside behavior not implemented. Have you implemented `super_behavior()`?")
+        }
+    }
+
+    /// The deepest view at `point`, relative to the top left of the view, to
+    /// send touches and other pointer events to. See `View::hit_test`.
+    ///
+    /// Override this to change which view gets events, such as to let
+    /// touches through to the views behind this one.
+    fn hit_test(&self, point: &Point<i32>) -> Option<View> {
+        if let Some(super_behavior) = self.super_behavior() {
+            super_behavior.hit_test(point)
+        } else {
+            panic!("hit_test behavior not implemented. Have you implemented `super_behavior()`?")
+        }
+    }
+
     fn touches_began(&self, _touches: &Vec<Touch>) {}
     fn touches_ended(&self, _touches: &Vec<Touch>) {}
     fn touches_moved(&self, _touches: &Vec<Touch>) {}
 
+    /// Called when touches stop being tracked without ending, such as when
+    /// a long press shows a context menu. Views tracking a touch, such as a
+    /// field selecting text, should reset without acting on it.
+    fn touches_cancelled(&self, _touches: &Vec<Touch>) {}
+
+    /// Called when the pointer moves into one of the view's tracking areas
+    /// with `TrackingOption::MouseEnteredAndExited`, at `position` relative
+    /// to the top left of the view. See `View::add_tracking_area`.
+    ///
+    /// Override this to show the view is under the pointer, such as by
+    /// highlighting it.
+    fn mouse_entered(&self, _position: &Point<i32>) {}
+
+    /// Called when the pointer moves out of one of the view's tracking
+    /// areas with `TrackingOption::MouseEnteredAndExited`, or out of the
+    /// window.
+    fn mouse_exited(&self) {}
+
+    /// Called as the pointer moves within one of the view's tracking areas
+    /// with `TrackingOption::MouseMoved`, whether or not a button is held.
+    fn mouse_moved(&self, _position: &Point<i32>) {}
+
+    /// The shape the pointer prefers at `position`, relative to the top left
+    /// of the view, or `None` to leave it to the superview. A cursor set
+    /// with `View::set_cursor` takes precedence.
+    ///
+    /// Override this for views with their own pointer, such as text fields
+    /// showing an I-beam.
+    fn cursor(&self, _position: &Point<i32>) -> Option<Cursor> {
+        None
+    }
+
+    /// Called when the view's effective appearance changes from `previous`,
+    /// such as switching to dark mode. The view's background color has
+    /// already been restyled if it was never set, and it will be redrawn.
+    ///
+    /// Override this to restyle colors of the view's own that aren't
+    /// dynamic, such as with `Appearance::color`.
+    fn appearance_did_change(&self, _previous: &Appearance) {}
+
+    /// Called when the application's locale changes, see
+    /// `localization::set_locale`. Override this to fetch the view's own
+    /// localized strings again.
+    fn locale_did_change(&self) {}
+
+    /// Called when the view's effective layout direction flips, see
+    /// `View::set_layout_direction`, before its subviews are laid out and
+    /// it's drawn again. Override this to mirror what the view arranges
+    /// itself.
+    fn layout_direction_did_change(&self) {}
+
+    /// Called when the scroll wheel turns, or the trackpad is swiped, over
+    /// the view. Return `true` if the view scrolled; otherwise the event is
+    /// sent on to its superview, so the deepest view that can scroll in
+    /// that direction does.
+    ///
+    /// Returns `false` by default. `ScrollView` overrides this.
+    fn scroll_wheel(&self, _event: &ScrollEvent) -> bool {
+        false
+    }
+
+    /// A menu for a right-click or long press at `point`, relative to the
+    /// view's top left, shown instead of the view's `View::context_menu`.
+    /// Override this to offer items for what's under the pointer, such as
+    /// suggestions for a misspelled word.
+    ///
+    /// Returns `None` by default.
+    fn context_menu_at(&self, _point: &Point<i32>) -> Option<Menu> {
+        None
+    }
+
+    /// What kind of control the view is to screen readers, unless set with
+    /// `View::set_accessibility_role`.
+    ///
+    /// Returns `AccessibilityRole::Group` by default.
+    fn accessibility_role(&self) -> AccessibilityRole {
+        AccessibilityRole::Group
+    }
+
+    /// What screen readers read the view out as, unless set with
+    /// `View::set_accessibility_label`, such as a label's text.
+    fn accessibility_label(&self) -> Option<String> {
+        None
+    }
+
+    /// What the view holds, unless set with
+    /// `View::set_accessibility_value`, such as a text field's text.
+    fn accessibility_value(&self) -> Option<String> {
+        None
+    }
+
+    /// Performs `action` for a screen reader, such as a button firing on
+    /// `AccessibilityAction::Activate`. Returns whether the view could. See
+    /// `View::perform_accessibility_action`.
+    ///
+    /// Returns `false` by default.
+    fn perform_accessibility_action(&self, _action: &AccessibilityAction) -> bool {
+        false
+    }
+
     /// Return `true` if the view can resign the first responder.
     ///
     /// Returns `true` by default.
@@ -56,6 +215,60 @@ pub trait Behavior {
         true
     }
 
+    /// Whether Tab can move keyboard focus to the view. Views that handle
+    /// typing, such as `TextField`, return `true`.
+    fn can_become_first_responder(&self) -> bool {
+        false
+    }
+
+    /// Whether the view is a control, such as a `Button`, that Tab moves
+    /// keyboard focus to when its window has full keyboard access. See
+    /// `Window::set_full_keyboard_access`.
+    ///
+    /// Returns `false` by default.
+    fn is_control(&self) -> bool {
+        false
+    }
+
+    /// Called after the view becomes its window's first responder.
+    fn did_become_first_responder(&self) {}
+
+    /// Called after the view stops being its window's first responder.
+    fn did_resign_first_responder(&self) {}
+
+    /// Called when keys are pressed, with a `Press` for each, as
+    /// `touches_began` is for touches. By default each press is passed to
+    /// `press_began`, so views can handle one press at a time.
+    fn presses_began(&self, presses: &[Press]) {
+        for press in presses.iter() {
+            self.press_began(press);
+        }
+    }
+
+    /// Called when the modifier keys held with pressed keys change.
+    fn presses_changed(&self, presses: &[Press]) {
+        if let Some(next) = self.next_responder() {
+            next.borrow().presses_changed(presses);
+        }
+    }
+
+    /// Called when pressed keys are released. By default each press is
+    /// passed to `press_ended`.
+    fn presses_ended(&self, presses: &[Press]) {
+        for press in presses.iter() {
+            self.press_ended(press);
+        }
+    }
+
+    /// Called when pressed keys stop being tracked without being released,
+    /// such as when the window loses focus. By default each press is passed
+    /// to `press_ended`, so views keeping track of held keys stay correct.
+    fn presses_cancelled(&self, presses: &[Press]) {
+        for press in presses.iter() {
+            self.press_ended(press);
+        }
+    }
+
     fn press_began(&self, press: &Press) {
         if let Some(next) = self.next_responder() {
             next.borrow().press_began(press);
@@ -68,6 +281,17 @@ pub trait Behavior {
         }
     }
 
+    /// Called repeatedly while a key is held, after the key repeat delay.
+    /// `press.repeat_count()` is how many times it has repeated. See
+    /// `Application::set_key_repeat`.
+    ///
+    /// Modifier keys don't repeat.
+    fn press_repeated(&self, press: &Press) {
+        if let Some(next) = self.next_responder() {
+            next.borrow().press_repeated(press);
+        }
+    }
+
     /// Override this behavior if the view should accept text typing input. E.g.
     /// if the view is a text field. `TextField` utilizes this function.
     ///
@@ -88,6 +312,73 @@ pub trait Behavior {
         }
     }
 
+    /// The undo manager for actions performed in this view, used by the
+    /// undo and redo shortcuts.
+    ///
+    /// By default this is the next responder's, so views share their
+    /// window's undo manager. Override this for a view to keep its own
+    /// history, as `TextField` does.
+    fn undo_manager(&self) -> Option<Rc<RefCell<UndoManager>>> {
+        if let Some(next) = self.next_responder() {
+            next.borrow().undo_manager()
+        } else {
+            None
+        }
+    }
+
+    /// Performs `command`, returning whether it was handled. By default
+    /// undo and redo use `undo_manager`, and other commands are passed to
+    /// the next responder.
+    ///
+    /// Override this to handle commands such as `Command::Copy`, along with
+    /// `can_perform_command`.
+    fn perform_command(&self, command: &Command) -> bool {
+        match command {
+            Command::Undo | Command::Redo => {
+                let undo_manager = match self.undo_manager() {
+                    Some(undo_manager) => undo_manager,
+                    None => return false
+                };
+
+                let mut undo_manager = undo_manager.borrow_mut();
+                if *command == Command::Undo && undo_manager.can_undo() {
+                    undo_manager.undo();
+                    true
+                } else if *command == Command::Redo && undo_manager.can_redo() {
+                    undo_manager.redo();
+                    true
+                } else {
+                    false
+                }
+            },
+            _ => match self.next_responder() {
+                Some(next) => next.borrow().perform_command(command),
+                None => false
+            }
+        }
+    }
+
+    /// Whether `perform_command` would handle `command` now, used to enable
+    /// and disable menu items.
+    fn can_perform_command(&self, command: &Command) -> bool {
+        match command {
+            Command::Undo => self.undo_manager().is_some_and(|undo_manager| undo_manager.borrow().can_undo()),
+            Command::Redo => self.undo_manager().is_some_and(|undo_manager| undo_manager.borrow().can_redo()),
+            _ => match self.next_responder() {
+                Some(next) => next.borrow().can_perform_command(command),
+                None => false
+            }
+        }
+    }
+
+    /// Where presses, typed text and commands this view doesn't handle go
+    /// next, forming the responder chain: by default each view's superview,
+    /// up to the window's root view. The window then offers them to its view
+    /// controller, handles what it can itself, such as Tab and undo, and
+    /// passes the rest to the application delegate.
+    ///
+    /// Override this to route unhandled events elsewhere, such as a popover
+    /// sending them to the view it was shown from.
     fn next_responder(&self) -> Option<Rc<RefCell<Box<dyn Behavior>>>> {
         if let Some(super_behavior) = self.super_behavior() {
             super_behavior.next_responder()
@@ -0,0 +1,361 @@
+use crate::graphics::{Rectangle, Size};
+use crate::ui::view::{DefaultBehavior, ScrollView, View};
+use crate::ui::view::collection_view_layout::{CollectionViewLayout, FlowLayout};
+use crate::ui::view::reuse_pool::{ReusableCell, ReusePool};
+use crate::ui::Color;
+use crate::macros::*;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+type PrepareForReuseCallback = Rc<dyn Fn(&CollectionViewCell)>;
+
+/// Provides the items of a `CollectionView`.
+pub trait CollectionViewDataSource {
+    /// How many items the collection view has.
+    fn number_of_items(&self, collection_view: &CollectionView) -> usize;
+
+    /// The cell showing `item`. This is only asked for items about to
+    /// scroll into view, and should use
+    /// `CollectionView::dequeue_reusable_cell` to reuse a cell that's
+    /// scrolled out of view where it can.
+    fn cell_for_item(&self, collection_view: &CollectionView, item: usize) -> CollectionViewCell;
+}
+
+// An item of a `CollectionView`.
+//
+// A cell is an empty view to add the item's views to. Cells with the same
+// reuse identifier are interchangeable, and are reused for other items once
+// they scroll out of view.
+custom_view!(
+    CollectionViewCell subclasses DefaultBehavior
+
+    struct CollectionViewCellBehavior {
+        reuse_identifier: String,
+        prepare_for_reuse_action: RefCell<Option<PrepareForReuseCallback>>
+    }
+
+    impl Self {
+        pub fn new(reuse_identifier: &str) -> CollectionViewCell {
+            let cell = Self::new_all(Rectangle::new(0, 0, 50, 50), reuse_identifier.to_string(), RefCell::new(None));
+            cell.view.set_background_color(Color::clear());
+            cell
+        }
+
+        /// Identifies which cells can be reused for each other.
+        pub fn reuse_identifier(&self) -> String {
+            self.behavior().reuse_identifier.clone()
+        }
+
+        /// Sets the callback called when the cell scrolls out of view, before
+        /// it's reused for another item, to clear whatever the cell's views
+        /// were showing for the last one.
+        pub fn on_prepare_for_reuse(&self, action: impl Fn(&CollectionViewCell) + 'static) {
+            self.behavior().prepare_for_reuse_action.replace(Some(Rc::new(action)));
+        }
+    }
+);
+
+impl ReusableCell for CollectionViewCell {
+    fn view(&self) -> &View {
+        &self.view
+    }
+
+    fn reuse_identifier(&self) -> String {
+        CollectionViewCell::reuse_identifier(self)
+    }
+
+    fn prepare_for_reuse(&self) {
+        let action = self.behavior().prepare_for_reuse_action.borrow().clone();
+        if let Some(action) = action {
+            action(self);
+        }
+    }
+}
+
+// A scrolling arrangement of items, such as a grid of photos.
+//
+// Where each item goes is up to the collection view's layout, a `FlowLayout`
+// unless it's changed, and the items come from a `CollectionViewDataSource`.
+// Only the items in view have cells, which are reused as items scroll in and
+// out of view.
+custom_view!(
+    CollectionView subclasses DefaultBehavior
+
+    struct CollectionViewBehavior {
+        data_source: RefCell<Option<Rc<dyn CollectionViewDataSource>>>,
+        layout: RefCell<Rc<dyn CollectionViewLayout>>,
+        number_of_items: Cell<usize>,
+
+        // The cells in view, by item.
+        visible_cells: RefCell<BTreeMap<usize, CollectionViewCell>>,
+
+        // Cells scrolled out of view, by reuse identifier.
+        reusable_cells: ReusePool<CollectionViewCell>
+    }
+
+    impl Self {
+        pub fn new(frame: Rectangle<i32, u32>) -> CollectionView {
+            let layout: Rc<dyn CollectionViewLayout> = Rc::new(FlowLayout::new(Size::new(100, 100), 8));
+            let collection_view = Self::new_all(
+                frame.clone(),
+                RefCell::new(None),
+                RefCell::new(layout),
+                Cell::new(0),
+                RefCell::new(BTreeMap::new()),
+                ReusePool::new()
+            );
+            collection_view.view.set_background_color(Color::clear());
+
+            let size = frame.size;
+            let scroll_view = ScrollView::new(Rectangle::new(0, 0, size.width, size.height));
+            let cells_view = View::new(Rectangle::new(0, 0, size.width, 0));
+            cells_view.set_background_color(Color::clear());
+            scroll_view.set_content_view(cells_view);
+            collection_view.view.add_subview(scroll_view.view.clone());
+
+            let weak_collection_view = collection_view.view.downgrade();
+            scroll_view.on_scroll(move |_| {
+                if let Some(view) = weak_collection_view.upgrade() {
+                    CollectionView::from_view(view).layout_visible_items();
+                }
+            });
+
+            collection_view
+        }
+
+        /// The scroll view the items scroll in.
+        pub fn scroll_view(&self) -> ScrollView {
+            let view = self.view.subviews().first().unwrap().clone();
+            ScrollView::from_view(view)
+        }
+
+        /// The view the cells are added to.
+        fn cells_view(&self) -> View {
+            self.scroll_view().content_view().unwrap()
+        }
+
+        /// Sets where the items come from, and loads them.
+        pub fn set_data_source(&self, data_source: Rc<dyn CollectionViewDataSource>) {
+            self.behavior().data_source.replace(Some(data_source));
+            self.reload_data();
+        }
+
+        pub fn layout(&self) -> Rc<dyn CollectionViewLayout> {
+            self.behavior().layout.borrow().clone()
+        }
+
+        /// Sets how the items are arranged, reloading them.
+        pub fn set_layout(&self, layout: Rc<dyn CollectionViewLayout>) {
+            self.behavior().layout.replace(layout);
+            self.reload_data();
+        }
+
+        /// How many items there were when the data was last loaded.
+        pub fn number_of_items(&self) -> usize {
+            self.behavior().number_of_items.get()
+        }
+
+        /// Asks the data source for the items again, such as after they've
+        /// changed. Every cell in view is made reusable and asked for
+        /// again.
+        pub fn reload_data(&self) {
+            let cells: Vec<CollectionViewCell> = self.behavior().visible_cells.replace(BTreeMap::new()).into_values().collect();
+            for cell in cells {
+                self.enqueue_reusable_cell(cell);
+            }
+
+            let data_source = self.behavior().data_source.borrow().clone();
+            let number_of_items = data_source.map_or(0, |data_source| data_source.number_of_items(self));
+            self.behavior().number_of_items.set(number_of_items);
+
+            let size = self.view.frame().size;
+            let content_size = self.layout().content_size(&size, number_of_items);
+            self.scroll_view().set_content_size(content_size);
+            self.layout_visible_items();
+        }
+
+        /// A cell with `reuse_identifier` that's scrolled out of view and
+        /// can be used for another item, if there is one.
+        pub fn dequeue_reusable_cell(&self, reuse_identifier: &str) -> Option<CollectionViewCell> {
+            self.behavior().reusable_cells.dequeue(reuse_identifier)
+        }
+
+        fn enqueue_reusable_cell(&self, cell: CollectionViewCell) {
+            self.behavior().reusable_cells.enqueue(cell);
+        }
+
+        /// The part of the content in view.
+        fn visible_rectangle(&self) -> Rectangle<i32, u32> {
+            Rectangle {
+                origin: self.scroll_view().content_offset(),
+                size: self.view.frame().size
+            }
+        }
+
+        /// Gives each item in view a cell, reusing the cells of items that
+        /// have scrolled out of view.
+        fn layout_visible_items(&self) {
+            let size = self.view.frame().size;
+            let layout = self.layout();
+            let visible_rectangle = self.mirrored_if_right_to_left(self.visible_rectangle());
+            let items = layout.items_in_rectangle(&size, self.number_of_items(), &visible_rectangle);
+
+            let hidden_items: Vec<usize> = self.behavior().visible_cells.borrow()
+                .keys()
+                .filter(|item| !items.contains(item))
+                .cloned()
+                .collect();
+            for item in hidden_items {
+                let cell = self.behavior().visible_cells.borrow_mut().remove(&item).unwrap();
+                self.enqueue_reusable_cell(cell);
+            }
+
+            let data_source = match self.behavior().data_source.borrow().clone() {
+                Some(data_source) => data_source,
+                None => return
+            };
+
+            let cells_view = self.cells_view();
+            for item in items {
+                if self.behavior().visible_cells.borrow().contains_key(&item) {
+                    continue;
+                }
+
+                let cell = data_source.cell_for_item(self, item);
+                cell.view.set_frame(self.frame_for_item(item));
+                cells_view.add_subview(cell.view.clone());
+
+                self.behavior().visible_cells.borrow_mut().insert(item, cell);
+            }
+        }
+
+        /// The cell showing `item`, if it's in view.
+        pub fn cell_for_item(&self, item: usize) -> Option<CollectionViewCell> {
+            self.behavior().visible_cells.borrow().get(&item).cloned()
+        }
+
+        /// The items in view, in order.
+        pub fn visible_items(&self) -> Vec<usize> {
+            self.behavior().visible_cells.borrow().keys().cloned().collect()
+        }
+
+        /// Scrolls as little as needed for all of `item` to be in view.
+        pub fn scroll_to_item(&self, item: usize) {
+            self.scroll_view().scroll_rectangle_to_visible(self.frame_for_item(item));
+        }
+
+        /// Where `item` goes in the content. In a collection view laid out
+        /// right to left, the layout's frames are mirrored, so that rows
+        /// start on the right.
+        fn frame_for_item(&self, item: usize) -> Rectangle<i32, u32> {
+            let size = self.view.frame().size;
+            self.mirrored_if_right_to_left(self.layout().frame_for_item(&size, item))
+        }
+
+        fn mirrored_if_right_to_left(&self, mut rectangle: Rectangle<i32, u32>) -> Rectangle<i32, u32> {
+            if self.view.effective_layout_direction().is_right_to_left() {
+                let size = self.view.frame().size;
+                let content_width = self.layout().content_size(&size, self.number_of_items()).width as i32;
+                rectangle.origin.x = content_width - rectangle.origin.x - rectangle.size.width as i32;
+            }
+            rectangle
+        }
+    }
+
+    impl Behavior {
+        fn layout_direction_did_change(&self) {
+            self.view_type().reload_data();
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Point;
+    use crate::ui::LayoutDirection;
+
+    struct PhotoDataSource {
+        number_of_items: usize,
+        cells_created: Cell<usize>
+    }
+
+    impl CollectionViewDataSource for PhotoDataSource {
+        fn number_of_items(&self, _collection_view: &CollectionView) -> usize {
+            self.number_of_items
+        }
+
+        fn cell_for_item(&self, collection_view: &CollectionView, _item: usize) -> CollectionViewCell {
+            collection_view.dequeue_reusable_cell("photo").unwrap_or_else(|| {
+                self.cells_created.set(self.cells_created.get() + 1);
+                CollectionViewCell::new("photo")
+            })
+        }
+    }
+
+    #[test]
+    fn test_only_visible_items_have_cells() {
+        let collection_view = CollectionView::new(Rectangle::new(0, 0, 100, 100));
+        collection_view.set_layout(Rc::new(FlowLayout::new(Size::new(30, 30), 5)));
+
+        let data_source = Rc::new(PhotoDataSource { number_of_items: 3_000, cells_created: Cell::new(0) });
+        collection_view.set_data_source(data_source.clone());
+
+        // Three items to a row, 1000 rows.
+        assert_eq!(collection_view.scroll_view().content_size(), Size::new(100, 34_995));
+        assert_eq!(collection_view.visible_items(), (0..9).collect::<Vec<usize>>());
+        assert_eq!(collection_view.cell_for_item(4).unwrap().view.frame(), Rectangle::new(35, 35, 30, 30));
+
+        collection_view.scroll_view().set_content_offset(Point::new(0, 360));
+        assert_eq!(collection_view.visible_items(), (30..42).collect::<Vec<usize>>());
+
+        for y in 0..100 {
+            collection_view.scroll_view().set_content_offset(Point::new(0, y * 11));
+        }
+        assert_eq!(data_source.cells_created.get(), 12);
+        assert_eq!(collection_view.cells_view().subviews().len(), collection_view.visible_items().len());
+
+        collection_view.scroll_to_item(0);
+        assert_eq!(collection_view.scroll_view().content_offset(), Point::new(0, 0));
+    }
+
+    #[test]
+    fn test_cells_are_prepared_for_reuse() {
+        let collection_view = CollectionView::new(Rectangle::new(0, 0, 100, 100));
+        collection_view.set_layout(Rc::new(FlowLayout::new(Size::new(30, 30), 5)));
+        collection_view.set_data_source(Rc::new(PhotoDataSource { number_of_items: 100, cells_created: Cell::new(0) }));
+
+        let prepared = Rc::new(Cell::new(0));
+        for item in collection_view.visible_items() {
+            let prepared = prepared.clone();
+            let cell = collection_view.cell_for_item(item).unwrap();
+            cell.on_prepare_for_reuse(move |_| prepared.set(prepared.get() + 1));
+        }
+
+        // The first row scrolls out of view.
+        collection_view.scroll_view().set_content_offset(Point::new(0, 40));
+        assert_eq!(prepared.get(), 3);
+
+        // Reloading makes every cell in view reusable, the first row's
+        // cells included now that they show the fourth row.
+        collection_view.reload_data();
+        assert_eq!(prepared.get(), 12);
+    }
+
+    #[test]
+    fn test_right_to_left() {
+        let collection_view = CollectionView::new(Rectangle::new(0, 0, 100, 100));
+        collection_view.view.set_layout_direction(Some(LayoutDirection::RightToLeft));
+        collection_view.set_layout(Rc::new(FlowLayout::new(Size::new(30, 30), 5)));
+        collection_view.set_data_source(Rc::new(PhotoDataSource { number_of_items: 5, cells_created: Cell::new(0) }));
+
+        // Rows start on the right.
+        assert_eq!(collection_view.cell_for_item(0).unwrap().view.frame(), Rectangle::new(70, 0, 30, 30));
+        assert_eq!(collection_view.cell_for_item(4).unwrap().view.frame(), Rectangle::new(35, 35, 30, 30));
+
+        collection_view.view.set_layout_direction(Some(LayoutDirection::LeftToRight));
+        assert_eq!(collection_view.cell_for_item(0).unwrap().view.frame(), Rectangle::new(0, 0, 30, 30));
+        assert_eq!(collection_view.visible_items(), (0..5).collect::<Vec<usize>>());
+    }
+}
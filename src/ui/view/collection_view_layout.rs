@@ -0,0 +1,123 @@
+use crate::graphics::{Rectangle, Size};
+
+/// Arranges the items of a `CollectionView`.
+///
+/// A layout works out where each item goes from the number of items and the
+/// size of the collection view, and which items are in a rectangle of the
+/// content, so the collection view only creates cells for the items in view.
+pub trait CollectionViewLayout {
+    /// The size of all of the items together, for `number_of_items` items
+    /// in a collection view of `size`.
+    fn content_size(&self, size: &Size<u32>, number_of_items: usize) -> Size<u32>;
+
+    /// Where `item` goes, in the content's coordinate space.
+    fn frame_for_item(&self, size: &Size<u32>, item: usize) -> Rectangle<i32, u32>;
+
+    /// The items at least partly in `rectangle` of the content, in order.
+    fn items_in_rectangle(&self, size: &Size<u32>, number_of_items: usize, rectangle: &Rectangle<i32, u32>) -> Vec<usize>;
+}
+
+/// Lays items of the same size out in rows, left to right and then top to
+/// bottom, fitting as many items in each row as the width allows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowLayout {
+    pub item_size: Size<u32>,
+
+    /// The space between items in a row, and between rows.
+    pub spacing: u32
+}
+
+impl FlowLayout {
+    pub fn new(item_size: Size<u32>, spacing: u32) -> FlowLayout {
+        FlowLayout {
+            item_size,
+            spacing
+        }
+    }
+
+    /// How many items fit in a row of a collection view of `size`. Always
+    /// at least one, even if the items are wider than the view.
+    pub fn items_per_row(&self, size: &Size<u32>) -> usize {
+        let item_width = self.item_size.width + self.spacing;
+        if item_width == 0 {
+            return 1;
+        }
+
+        (((size.width + self.spacing) / item_width) as usize).max(1)
+    }
+
+    fn row_height(&self) -> u32 {
+        self.item_size.height + self.spacing
+    }
+}
+
+impl CollectionViewLayout for FlowLayout {
+    fn content_size(&self, size: &Size<u32>, number_of_items: usize) -> Size<u32> {
+        let items_per_row = self.items_per_row(size);
+        let rows = number_of_items.div_ceil(items_per_row);
+        let height = (rows as u32 * self.row_height()).saturating_sub(self.spacing);
+        Size::new(size.width, height)
+    }
+
+    fn frame_for_item(&self, size: &Size<u32>, item: usize) -> Rectangle<i32, u32> {
+        let items_per_row = self.items_per_row(size);
+        let column = (item % items_per_row) as u32;
+        let row = (item / items_per_row) as u32;
+
+        Rectangle::new(
+            (column * (self.item_size.width + self.spacing)) as i32,
+            (row * self.row_height()) as i32,
+            self.item_size.width,
+            self.item_size.height
+        )
+    }
+
+    fn items_in_rectangle(&self, size: &Size<u32>, number_of_items: usize, rectangle: &Rectangle<i32, u32>) -> Vec<usize> {
+        let row_height = self.row_height() as i32;
+        if row_height == 0 || rectangle.size.height == 0 {
+            return Vec::new();
+        }
+
+        let items_per_row = self.items_per_row(size);
+        let first_row = (rectangle.top().max(0) / row_height) as usize;
+        let last_row = ((rectangle.bottom().max(0) + row_height - 1) / row_height) as usize;
+
+        let first = (first_row * items_per_row).min(number_of_items);
+        let last = (last_row * items_per_row).min(number_of_items);
+        (first..last)
+            .filter(|item| {
+                // Leave out items only in the spacing below the rectangle's
+                // last row.
+                self.frame_for_item(size, *item).top() < rectangle.bottom()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flow_layout() {
+        let layout = FlowLayout::new(Size::new(30, 20), 5);
+        let size = Size::new(100, 50);
+
+        // Three 30 point items and two 5 point gaps fit in 100 points.
+        assert_eq!(layout.items_per_row(&size), 3);
+        assert_eq!(layout.frame_for_item(&size, 0), Rectangle::new(0, 0, 30, 20));
+        assert_eq!(layout.frame_for_item(&size, 2), Rectangle::new(70, 0, 30, 20));
+        assert_eq!(layout.frame_for_item(&size, 4), Rectangle::new(35, 25, 30, 20));
+
+        assert_eq!(layout.content_size(&size, 0), Size::new(100, 0));
+        assert_eq!(layout.content_size(&size, 3), Size::new(100, 20));
+        assert_eq!(layout.content_size(&size, 7), Size::new(100, 70));
+
+        assert_eq!(layout.items_in_rectangle(&size, 100, &Rectangle::new(0, 0, 100, 50)), (0..6).collect::<Vec<usize>>());
+        assert_eq!(layout.items_in_rectangle(&size, 100, &Rectangle::new(0, 30, 100, 50)), (3..12).collect::<Vec<usize>>());
+        assert_eq!(layout.items_in_rectangle(&size, 4, &Rectangle::new(0, 30, 100, 50)), vec![3]);
+
+        // Items wider than the view get a row each.
+        assert_eq!(FlowLayout::new(Size::new(200, 20), 5).items_per_row(&size), 1);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::ui::view::{WeakView, Behavior};
+use crate::ui::view::{View, WeakView, Behavior};
+use crate::graphics::{Point, Rectangle};
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -37,6 +38,17 @@ impl Behavior for DefaultBehavior {
         None
     }
 
+    fn point_inside(&self, point: &Point<i32>) -> bool {
+        let view = self.view.upgrade().unwrap();
+        let size = view.frame().size;
+        Rectangle::new(0, 0, size.width, size.height).contains_point(point)
+    }
+
+    fn hit_test(&self, point: &Point<i32>) -> Option<View> {
+        let view = self.view.upgrade().unwrap();
+        view.default_hit_test(point)
+    }
+
     /// Request for this view to be redrawn soon.
     ///
     /// See `#draw`, which includes the instructions on what would actually be
@@ -56,26 +68,59 @@ impl Behavior for DefaultBehavior {
             }
 
             layer.set_needs_display();
+        }
+
+        // Only the part of the superview under this view has to be drawn
+        // again. Without a layer, this view hasn't been drawn, and its
+        // superview has to draw it for the first time.
+        drop(inner_self);
+        view.set_needs_display_in_superview();
+    }
+
+    fn set_needs_display_in_rect(&self, rectangle: &Rectangle<i32, u32>) {
+        let view = self.view.upgrade().unwrap().clone();
 
-            if let Some(superview) = &inner_self.superview.upgrade() {
-                superview.set_needs_display();
+        let has_layer = {
+            let inner_self = view.inner_self.borrow();
+
+            match &inner_self.layer {
+                // Already being drawn in full.
+                Some(layer) if layer.get_needs_display() => return,
+                Some(layer) => {
+                    layer.set_needs_display_in_rect(rectangle);
+                    true
+                },
+                None => false
             }
+        };
+
+        if has_layer {
+            view.set_needs_display_in_superview_rect(rectangle);
+        } else {
+            view.set_needs_display_in_superview();
         }
     }
 
     /// Defines what actually gets drawn to screen to represent this view.
     ///
     /// For example, the default `View` implementation simply draws the
-    /// background color as a box of the size of the frame.
+    /// background color as a box of the size of the frame, and the
+    /// background gradient over it, if there is one.
     fn draw(&self) {
         let view = self.view.upgrade().unwrap().clone();
 
         let inner_self = view.inner_self.borrow();
 
-        let color = inner_self.background_color.to_graphics_color();
+        let color = inner_self.background_color.resolved_in(&view).to_graphics_color();
 
         if let Some(layer) = &inner_self.layer {
             layer.clear_with_color(color);
+
+            if let Some(gradient) = &inner_self.background_gradient {
+                let size = layer.size();
+                let rectangle = Rectangle::new(0, 0, size.width, size.height);
+                layer.fill_gradient(&rectangle, gradient.stops(), gradient.direction());
+            }
         }
     }
 }
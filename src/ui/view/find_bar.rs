@@ -0,0 +1,189 @@
+use crate::graphics::{EdgeInsets, Rectangle};
+use crate::macros::*;
+use crate::text::{HorizontalAlignment, VerticalAlignment};
+use crate::ui::button::Button;
+use crate::ui::key::KeyCode;
+use crate::ui::key_command::KeyCommand;
+use crate::ui::view::text_view;
+use crate::ui::view::{AutoresizingMask, DefaultBehavior, Label, TextField, TextView, WeakView};
+use crate::ui::{Color, SemanticColor};
+
+/// The height of the find bar, which has a row for finding and a row for
+/// replacing.
+pub(crate) const FIND_BAR_HEIGHT: u32 = 72;
+
+/// The space around and between the bar's fields and buttons.
+const PADDING: u32 = 8;
+
+const ROW_HEIGHT: u32 = 24;
+
+const STATUS_WIDTH: u32 = 72;
+const PREVIOUS_BUTTON_WIDTH: u32 = 72;
+const NEXT_BUTTON_WIDTH: u32 = 56;
+const DONE_BUTTON_WIDTH: u32 = 56;
+const REPLACE_BUTTON_WIDTH: u32 = 72;
+const REPLACE_ALL_BUTTON_WIDTH: u32 = 96;
+
+// The bar a `TextView` shows above its text for finding and replacing,
+// with Command+F (or Control+F).
+//
+// The top row has the search field, how many matches there are, buttons for
+// the previous and next match, and a button to close the bar. The bottom row
+// has the replacement field, and buttons to replace the selected match or
+// all of them.
+//
+// Typing in the search field searches as you type. Return in the search
+// field finds the next match, Return in the replacement field replaces the
+// selected match, and Escape closes the bar.
+custom_view!(
+    FindBar subclasses DefaultBehavior
+
+    struct FindBarBehavior {
+
+    }
+
+    impl Self {
+        /// A find bar searching the text of the text view `text_view`.
+        pub(crate) fn new(frame: Rectangle<i32, u32>, text_view: WeakView) -> FindBar {
+            let find_bar = Self::new_all(frame.clone());
+            find_bar.view.set_background_color(Color::semantic(SemanticColor::SecondaryBackground));
+
+            let width = frame.size.width;
+            let (find_frame, find_row) = row_frames(
+                width,
+                PADDING as i32,
+                &[STATUS_WIDTH, PREVIOUS_BUTTON_WIDTH, NEXT_BUTTON_WIDTH, DONE_BUTTON_WIDTH]
+            );
+            let (replace_frame, replace_row) = row_frames(
+                width,
+                (PADDING * 2 + ROW_HEIGHT) as i32,
+                &[REPLACE_BUTTON_WIDTH, REPLACE_ALL_BUTTON_WIDTH]
+            );
+
+            let find_field = search_field(find_frame);
+            let find = text_view::action_with_text_view(&text_view, |text_view| {
+                text_view.find(&text_view.find_bar().query());
+            });
+            find_field.on_text_change(move |_| find());
+            let find_next = text_view::action_with_text_view(&text_view, TextView::find_next);
+            find_field.on_return(move |_| find_next());
+            find_bar.view.add_subview(find_field.view.clone());
+
+            let replace_field = search_field(replace_frame);
+            let replace = text_view::action_with_text_view(&text_view, |text_view| {
+                text_view.replace(&text_view.find_bar().replacement());
+            });
+            replace_field.on_return(move |_| replace());
+            find_bar.view.add_subview(replace_field.view.clone());
+
+            let status_label = Label::new(find_row[0].clone(), String::new());
+            status_label.set_text_alignment(HorizontalAlignment::Center);
+            status_label.set_vertical_alignment(VerticalAlignment::Middle);
+            status_label.set_text_color(Color::semantic(SemanticColor::SecondaryLabel));
+            status_label.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleLeftMargin]);
+            find_bar.view.add_subview(status_label.view.clone());
+
+            let buttons = [
+                (find_row[1].clone(), "Previous", text_view::action_with_text_view(&text_view, TextView::find_previous)),
+                (find_row[2].clone(), "Next", text_view::action_with_text_view(&text_view, TextView::find_next)),
+                (find_row[3].clone(), "Done", text_view::action_with_text_view(&text_view, TextView::hide_find_bar)),
+                (replace_row[0].clone(), "Replace", text_view::action_with_text_view(&text_view, |text_view| {
+                    text_view.replace(&text_view.find_bar().replacement());
+                })),
+                (replace_row[1].clone(), "Replace All", text_view::action_with_text_view(&text_view, |text_view| {
+                    text_view.replace_all(&text_view.find_bar().replacement());
+                }))
+            ];
+            for (frame, title, action) in buttons {
+                let button = Button::new(frame, title, action);
+                button.set_localized_title(title);
+                button.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleLeftMargin]);
+                find_bar.view.add_subview(button.view.clone());
+            }
+
+            find_bar.view.add_key_command(KeyCommand::new(
+                KeyCode::Escape,
+                &[],
+                text_view::action_with_text_view(&text_view, TextView::hide_find_bar)
+            ));
+
+            find_bar
+        }
+
+        /// The field the search is typed in.
+        pub fn find_field(&self) -> TextField {
+            TextField::from_view(self.view.subviews()[0].clone())
+        }
+
+        /// The field the replacement is typed in.
+        pub fn replace_field(&self) -> TextField {
+            TextField::from_view(self.view.subviews()[1].clone())
+        }
+
+        /// The label showing how many matches were found.
+        pub fn status_label(&self) -> Label {
+            Label::from_view(self.view.subviews()[2].clone())
+        }
+
+        /// What's typed in the search field.
+        pub fn query(&self) -> String {
+            self.find_field().label().copy_text()
+        }
+
+        /// What's typed in the replacement field.
+        pub fn replacement(&self) -> String {
+            self.replace_field().label().copy_text()
+        }
+
+        pub(crate) fn set_status(&self, status: &str) {
+            let status_label = self.status_label();
+            if status_label.text().string() != status {
+                status_label.set_text(status.to_string());
+            }
+        }
+    }
+);
+
+/// A single line text field for the search or the replacement.
+fn search_field(frame: Rectangle<i32, u32>) -> TextField {
+    let text_field = TextField::new(frame, String::new());
+    text_field.set_content_insets(EdgeInsets::new(4, 6, 4, 6));
+    text_field.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth]);
+    text_field
+}
+
+/// The frames of a row of the bar at `y`, for a bar `width` wide: a text
+/// field taking up the space that's left, followed by views `widths` wide.
+fn row_frames(width: u32, y: i32, widths: &[u32]) -> (Rectangle<i32, u32>, Vec<Rectangle<i32, u32>>) {
+    let trailing_width: u32 = widths.iter().map(|width| width + PADDING).sum();
+    let field_width = width.saturating_sub(PADDING * 2 + trailing_width);
+    let field_frame = Rectangle::new(PADDING as i32, y, field_width, ROW_HEIGHT);
+
+    let mut x = (PADDING * 2 + field_width) as i32;
+    let frames = widths
+        .iter()
+        .map(|width| {
+            let frame = Rectangle::new(x, y, *width, ROW_HEIGHT);
+            x += (width + PADDING) as i32;
+            frame
+        })
+        .collect();
+
+    (field_frame, frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_frames() {
+        let (field, frames) = row_frames(400, 8, &[72, 56]);
+        assert_eq!(field, Rectangle::new(8, 8, 240, ROW_HEIGHT));
+        assert_eq!(frames, vec![Rectangle::new(256, 8, 72, ROW_HEIGHT), Rectangle::new(336, 8, 56, ROW_HEIGHT)]);
+
+        // The field shrinks away before the buttons do.
+        let (field, _) = row_frames(100, 8, &[72, 56]);
+        assert_eq!(field.size.width, 0);
+    }
+}
@@ -1,24 +1,95 @@
-use crate::graphics::{Image, Rectangle, Point};
+use crate::graphics::{Image, Rectangle, Point, Size};
 use crate::ui::view::DefaultBehavior;
-use std::cell::RefCell;
+use crate::ui::accessibility::AccessibilityRole;
+use std::cell::{Cell, RefCell};
 use crate::macros::*;
 
+/// How an `ImageView` fits its image to its size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ContentMode {
+    /// Stretches the image to fill the view, changing its aspect ratio if
+    /// need be.
+    #[default]
+    ScaleToFill,
+
+    /// Scales the image to fit inside the view, keeping its aspect ratio,
+    /// and centers it. This can leave empty space on two sides.
+    AspectFit,
+
+    /// Scales the image to cover the view, keeping its aspect ratio, and
+    /// centers it. This can clip two sides of the image.
+    AspectFill,
+
+    /// Centers the image at its own size.
+    Center
+}
+
+/// Where an image of `image_size` is drawn in a view of `size` for
+/// `content_mode`.
+pub(crate) fn image_rectangle(content_mode: ContentMode, image_size: &Size<u32>, size: &Size<u32>) -> Rectangle<i32, u32> {
+    let scale_x = size.width as f32 / image_size.width.max(1) as f32;
+    let scale_y = size.height as f32 / image_size.height.max(1) as f32;
+
+    let scale = match content_mode {
+        ContentMode::ScaleToFill => return Rectangle::new(0, 0, size.width, size.height),
+        ContentMode::AspectFit => scale_x.min(scale_y),
+        ContentMode::AspectFill => scale_x.max(scale_y),
+        ContentMode::Center => 1.0
+    };
+
+    let width = (image_size.width as f32 * scale).round() as i32;
+    let height = (image_size.height as f32 * scale).round() as i32;
+    Rectangle::new(
+        (size.width as i32 - width) / 2,
+        (size.height as i32 - height) / 2,
+        width as u32,
+        height as u32
+    )
+}
+
 custom_view!(
     ImageView subclasses DefaultBehavior
 
     struct ImageViewBehavior {
-        image: RefCell<Image<'static>>
+        image: RefCell<Image<'static>>,
+        content_mode: Cell<ContentMode>
     }
 
     impl Self {
+        /// An image view the size of `image`, with its top left corner at
+        /// `origin`.
         pub fn new(image: Image<'static>, origin: Point<i32>) -> ImageView {
             let size = image.size().clone();
             let frame = Rectangle { origin, size };
-            Self::new_all(frame, RefCell::new(image))
+            Self::new_with_frame(image, frame)
+        }
+
+        /// An image view of any size, fitting `image` to it according to its
+        /// content mode.
+        pub fn new_with_frame(image: Image<'static>, frame: Rectangle<i32, u32>) -> ImageView {
+            Self::new_all(frame, RefCell::new(image), Cell::new(ContentMode::default()))
+        }
+
+        pub fn set_image(&self, image: Image<'static>) {
+            self.behavior().image.replace(image);
+            self.view.set_needs_display();
+        }
+
+        pub fn content_mode(&self) -> ContentMode {
+            self.behavior().content_mode.get()
+        }
+
+        pub fn set_content_mode(&self, content_mode: ContentMode) {
+            self.behavior().content_mode.set(content_mode);
+            self.view.set_needs_display();
         }
     }
 
     impl Behavior {
+        fn accessibility_role(&self) -> AccessibilityRole {
+            AccessibilityRole::Image
+        }
+
         fn draw(&self) {
             let view = self.view.upgrade().unwrap().clone();
             let inner_self = view.inner_self.borrow();
@@ -28,12 +99,29 @@ custom_view!(
             if let Some(layer) = &inner_self.layer {
                 let mut image = behavior.image.borrow_mut();
                 let child_layer = image.layer_for(layer.context());
-                let rectangle = Rectangle {
-                    origin: Point::new(0, 0),
-                    size: view.frame().size
-                };
+
+                // Parts of the image outside the view are clipped by its
+                // layer.
+                let rectangle = image_rectangle(behavior.content_mode.get(), image.size(), &view.frame().size);
                 layer.draw_child_layer(&child_layer, &rectangle);
             }
         }
     }
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_rectangle() {
+        let image_size = Size::new(200, 100);
+        let size = Size::new(100, 100);
+
+        assert_eq!(image_rectangle(ContentMode::ScaleToFill, &image_size, &size), Rectangle::new(0, 0, 100, 100));
+        assert_eq!(image_rectangle(ContentMode::AspectFit, &image_size, &size), Rectangle::new(0, 25, 100, 50));
+        assert_eq!(image_rectangle(ContentMode::AspectFill, &image_size, &size), Rectangle::new(-50, 0, 200, 100));
+        assert_eq!(image_rectangle(ContentMode::Center, &image_size, &size), Rectangle::new(-50, 0, 200, 100));
+        assert_eq!(image_rectangle(ContentMode::Center, &Size::new(20, 10), &size), Rectangle::new(40, 45, 20, 10));
+    }
+}
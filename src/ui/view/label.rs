@@ -1,14 +1,19 @@
-use crate::graphics::{Rectangle, Font, Size};
+use crate::graphics::{EdgeInsets, Rectangle, Font, Size, Point};
 use crate::ui::Color;
+use crate::ui::appearance::{Appearance, SemanticColor};
+use crate::ui::accessibility::AccessibilityRole;
+use crate::ui::localization;
 use crate::ui::view::{Behavior, DefaultBehavior};
 use std::cell::{Cell, RefCell, Ref};
-use crate::text::attributed_string::{AttributedString, Key, Attribute};
-use crate::text::rendering;
+use crate::text::attributed_string::{AttributedString, Key, Attribute, LineStyle};
+use crate::text::rendering::{self, Decoration};
+use crate::text::layout::Layout;
 use crate::macros::*;
 use crate::text::{VerticalAlignment, HorizontalAlignment};
 use std::ops::Range;
 use std::rc::Rc;
 use crate::text::Text;
+use crate::ui::Touch;
 
 custom_view!(
     Label subclasses DefaultBehavior
@@ -17,13 +22,31 @@ custom_view!(
         attributed_text: Rc<RefCell<AttributedString>>,
         text_alignment: Cell<HorizontalAlignment>,
         text_vertical_alignment: Cell<VerticalAlignment>,
-        rendering_result: RefCell<Option<rendering::Result>>
+        number_of_lines: Cell<usize>,
+        layout: RefCell<Option<Rc<Layout>>>,
+        link_action: RefCell<Option<Box<dyn Fn(&str)>>>,
+        touched_link: RefCell<Option<String>>,
+
+        // The text color, if it's dynamic, resolved each time it's drawn.
+        dynamic_text_color: RefCell<Option<Color>>,
+
+        // The semantic color the text color was left as, so it follows the
+        // appearance as it changes. `None` once the label is given a text
+        // color of its own.
+        text_color_role: Cell<Option<SemanticColor>>,
+
+        // The space between the label's edges and its text.
+        content_insets: Cell<EdgeInsets>,
+
+        // The key the text was localized from, to fetch it again when the
+        // locale changes.
+        localized_key: RefCell<Option<String>>
     }
 
     impl Self {
         pub fn new(frame: Rectangle<i32, u32>, text: String) -> Label {
             let text = Rc::new(RefCell::new(AttributedString::new(text)));
-            let text_alignment = Cell::new(HorizontalAlignment::Left);
+            let text_alignment = Cell::new(HorizontalAlignment::Natural);
             let text_vertical_alignment = Cell::new(VerticalAlignment::Top);
 
             let label = Self::new_all(
@@ -31,6 +54,13 @@ custom_view!(
                 text,
                 text_alignment,
                 text_vertical_alignment,
+                Cell::new(0),
+                RefCell::new(None),
+                RefCell::new(None),
+                RefCell::new(None),
+                RefCell::new(None),
+                Cell::new(Some(SemanticColor::Label)),
+                Cell::new(EdgeInsets::zero()),
                 RefCell::new(None)
             );
             label.view.set_background_color(Color::clear());
@@ -62,6 +92,7 @@ custom_view!(
         /// `AttributedString`.
         pub fn set_text(&self, text: String) {
             let behavior = self.behavior();
+            behavior.localized_key.replace(None);
             let attributed_text: AttributedString;
             {
                 let existing = behavior.attributed_text.borrow();
@@ -74,10 +105,95 @@ custom_view!(
 
         pub fn set_attributed_text(&self, attributed_text: AttributedString) {
             let behavior = self.behavior();
+            behavior.localized_key.replace(None);
             (*behavior.attributed_text).replace(attributed_text);
             behavior.set_needs_display();
         }
 
+        /// Sets the text to the translation of `key` for the current
+        /// locale, see `localization::localized`, and translates it again
+        /// whenever the locale changes, until the text is set otherwise.
+        pub fn set_localized_text(&self, key: &str) {
+            self.set_text(localization::localized(key));
+            self.behavior().localized_key.replace(Some(key.to_string()));
+        }
+
+        /// The key the text was translated from with `set_localized_text`,
+        /// if it was.
+        pub fn localized_key(&self) -> Option<String> {
+            self.behavior().localized_key.borrow().clone()
+        }
+
+        /// Returns an independent _copy_ of the label's attributed text,
+        /// including its default attributes. Changes to the copy don't
+        /// affect the label; use `set_attributed_text` to apply them.
+        pub fn attributed_text(&self) -> AttributedString {
+            let behavior = self.behavior();
+            let attributed_text = behavior.attributed_text.borrow();
+            attributed_text.substring(0..attributed_text.len()).to_owned()
+        }
+
+        /// Marks the characters in each of `ranges` as misspelled, and
+        /// every other character as spelled correctly. Clearing the marks
+        /// when none are set leaves the text untouched, so its layout is
+        /// still cached.
+        pub(crate) fn set_misspelled_ranges(&self, ranges: &[Range<usize>]) {
+            let behavior = self.behavior();
+            if ranges.is_empty() {
+                let attributed_text = behavior.attributed_text.borrow();
+                let is_misspelled = |index: usize| attributed_text.get_attribute_for(index, Key::Misspelled).is_misspelled();
+                if !(0..attributed_text.len()).any(is_misspelled) {
+                    return;
+                }
+            }
+
+            {
+                let attributed_text = behavior.attributed_text.borrow();
+                attributed_text.remove_attribute_in_range(0..attributed_text.len(), Key::Misspelled);
+                for range in ranges.iter().filter(|range| range.end <= attributed_text.len()) {
+                    attributed_text.set_attribute_in_range(range.clone(), Key::Misspelled, Attribute::Misspelled { is_misspelled: true });
+                }
+            }
+            behavior.set_needs_display();
+        }
+
+        /// Highlights each range with its color, such as the matches of a
+        /// search, clearing any other highlights. Highlights are drawn over
+        /// the text's background colors, which are left as they are.
+        pub(crate) fn set_highlighted_ranges(&self, ranges: &[(Range<usize>, Color)]) {
+            let behavior = self.behavior();
+            {
+                let attributed_text = behavior.attributed_text.borrow();
+                attributed_text.remove_attribute_in_range(0..attributed_text.len(), Key::Highlight);
+                for (range, color) in ranges.iter().filter(|(range, _)| range.end <= attributed_text.len()) {
+                    let color = color.to_graphics_color();
+                    attributed_text.set_attribute_in_range(range.clone(), Key::Highlight, Attribute::Highlight { color });
+                }
+            }
+            behavior.set_needs_display();
+        }
+
+        /// The range of the misspelled word with the character at `index`,
+        /// if it's in one.
+        pub(crate) fn misspelled_range_at_index(&self, index: usize) -> Option<Range<usize>> {
+            let behavior = self.behavior();
+            let attributed_text = behavior.attributed_text.borrow();
+            let is_misspelled = |index: usize| attributed_text.get_attribute_for(index, Key::Misspelled).is_misspelled();
+            if index >= attributed_text.len() || !is_misspelled(index) {
+                return None;
+            }
+
+            let mut start = index;
+            while start > 0 && is_misspelled(start - 1) {
+                start -= 1;
+            }
+            let mut end = index + 1;
+            while end < attributed_text.len() && is_misspelled(end) {
+                end += 1;
+            }
+            Some(start..end)
+        }
+
         pub fn insert_text_at_index(&self, index: usize, text_to_insert: &str) {
             let behavior = self.behavior();
 
@@ -100,22 +216,31 @@ custom_view!(
             behavior.set_needs_display();
         }
 
+        /// Sets the color of text without a color of its own. A dynamic
+        /// color, such as `Color::semantic(SemanticColor::Label)`, follows
+        /// the label's appearance. Until it's set, the text is the
+        /// appearance's `SemanticColor::Label`, and follows it too.
         pub fn set_text_color(&self, text_color: Color) {
             let behavior = self.behavior();
+            behavior.text_color_role.set(None);
 
             {
                 let attributed_text = behavior.attributed_text.borrow();
                 attributed_text.set_default_attribute(
                     Key::Color,
-                    Attribute::Color { color: text_color.to_graphics_color() }
+                    Attribute::Color { color: text_color.resolved_in(&self.view).to_graphics_color() }
                 );
             }
 
+            behavior.dynamic_text_color.replace(Some(text_color).filter(Color::is_dynamic));
             behavior.set_needs_display();
         }
 
         pub fn text_color(&self) -> Color {
             let behavior = self.behavior();
+            if let Some(text_color) = behavior.dynamic_text_color.borrow().as_ref() {
+                return text_color.clone();
+            }
 
             let attributed_text = behavior.attributed_text.borrow();
             let attribute = attributed_text.default_attribute(Key::Color);
@@ -146,6 +271,9 @@ custom_view!(
             attribute.font().clone()
         }
 
+        /// Sets how the lines of text are aligned. The default, `Natural`,
+        /// aligns them to the leading edge, which is the right edge in a
+        /// view laid out right to left.
         pub fn set_text_alignment(&self, text_alignment: HorizontalAlignment) {
             let behavior = self.behavior();
             behavior.text_alignment.set(text_alignment);
@@ -157,29 +285,158 @@ custom_view!(
             behavior.text_alignment.get()
         }
 
+        /// The text alignment with `Natural` resolved to left or right for
+        /// the label's effective layout direction.
+        fn resolved_text_alignment(&self) -> HorizontalAlignment {
+            match self.text_alignment() {
+                HorizontalAlignment::Natural if self.view.effective_layout_direction().is_right_to_left() => HorizontalAlignment::Right,
+                HorizontalAlignment::Natural => HorizontalAlignment::Left,
+                alignment => alignment
+            }
+        }
+
         pub fn set_vertical_alignment(&self, text_vertical_alignment: VerticalAlignment) {
             let behavior = self.behavior();
             behavior.text_vertical_alignment.set(text_vertical_alignment);
             behavior.set_needs_display();
         }
 
-        /// Resizes the view's frame to fit the size of the text.
-        pub fn fit_to_text(&self) {
-            // TODO: add size to rendering_result
+        /// Sets the maximum number of lines to draw, including lines created
+        /// by wrapping. Text beyond the last line isn't drawn.
+        ///
+        /// 0 means no limit (the default).
+        pub fn set_number_of_lines(&self, number_of_lines: usize) {
+            let behavior = self.behavior();
+            behavior.number_of_lines.set(number_of_lines);
+            behavior.set_needs_display();
+        }
+
+        pub fn number_of_lines(&self) -> usize {
+            self.behavior().number_of_lines.get()
+        }
+
+        /// The space between the label's edges and its text, so the text
+        /// isn't flush against a background or border. Defaults to none.
+        pub fn content_insets(&self) -> EdgeInsets {
+            self.behavior().content_insets.get()
+        }
+
+        /// Sets the space between the label's edges and its text. The text
+        /// is wrapped and aligned within the insets.
+        pub fn set_content_insets(&self, content_insets: EdgeInsets) {
+            let behavior = self.behavior();
+            behavior.content_insets.set(content_insets);
+            behavior.set_needs_display();
+        }
+
+        /// Sets the callback invoked with the URL of a `Key::Link` attribute
+        /// when a touch begins and ends over the linked text.
+        pub fn set_link_action(&self, action: impl Fn(&str) + 'static) {
+            self.behavior().link_action.replace(Some(Box::new(action)));
+        }
+
+        /// Returns the position of the carat before the character at
+        /// `index`, in the label's coordinate space.
+        pub fn position_for_character_at_index(&self, index: usize) -> Point<i32> {
+            let behavior = self.behavior();
+            let layout = behavior.layout();
+            let render_scale = layout.render_scale();
+            let position = layout.rendering().position_for_character_at_index(index);
+
+            let insets = behavior.content_insets.get();
+
+            Point {
+                x: (position.x as f32 / render_scale).round() as i32 + insets.left,
+                y: (position.y as f32 / render_scale).round() as i32 + insets.top
+            }
+        }
+
+        /// Returns the index of the character drawn at `point`, or `None` if
+        /// there isn't one. The point is in the label's coordinate space.
+        pub fn character_index_at_point(&self, point: Point<i32>) -> Option<usize> {
+            let behavior = self.behavior();
+            let layout = behavior.layout();
+            layout.character_index_at_point(&self.text_point(&point, layout.render_scale()))
+        }
+
+        /// Returns the index where a carat would be placed for `point`, the
+        /// closest boundary between characters. The point is in the label's
+        /// coordinate space.
+        pub fn carat_index_at_point(&self, point: Point<i32>) -> usize {
+            let behavior = self.behavior();
+            let layout = behavior.layout();
+            layout.carat_index_at_point(&self.text_point(&point, layout.render_scale()))
+        }
+
+        /// Returns the range of characters on the line drawn at `point`, or
+        /// `None` if the point is above or below all lines. The point is in
+        /// the label's coordinate space.
+        pub fn character_range_at_point(&self, point: Point<i32>) -> Option<Range<usize>> {
+            let behavior = self.behavior();
+            let layout = behavior.layout();
+            layout.character_range_at_point(&self.text_point(&point, layout.render_scale()))
+        }
 
-            // let behavior = self.behavior();
-            // let text = behavior.text.borrow().clone();
-            // let font = behavior.font.borrow();
+        /// Returns the URL of the link drawn at `position`, if any. The
+        /// position is in the label's coordinate space.
+        pub fn link_at_position(&self, position: &Point<i32>) -> Option<String> {
+            let index = self.character_index_at_point(position.clone())?;
+            let behavior = self.behavior();
+            let attributed_text = behavior.attributed_text.borrow();
+            let link = attributed_text.get_attribute_if_set_for(index, Key::Link)?;
 
-            // let size = font.size_for(&text);
+            Some(link.link().to_string())
+        }
 
-            // let origin = self.view.frame().origin;
-            // let frame = Rectangle { origin, size };
+        /// `point`, in the label's coordinate space, relative to the top
+        /// left of the text's laid out pixels.
+        fn text_point(&self, point: &Point<i32>, render_scale: f32) -> Point<i32> {
+            let insets = self.behavior().content_insets.get();
+            scaled_point(&point.offset_by(-insets.left, -insets.top), render_scale)
+        }
 
-            // self.view.set_frame(frame);
+        fn link_for_touch(&self, touch: &Touch) -> Option<String> {
+            let window = touch.window()?;
+            let position = window.view.convert_point_to(&touch.position(), &self.view);
+            self.link_at_position(&position)
         }
 
-        fn generate_rendering_result(&self) {
+        /// The size needed to draw the label's text when constrained to
+        /// `width`, respecting `number_of_lines`, including the content
+        /// insets.
+        pub fn size_that_fits(&self, width: u32) -> Size<u32> {
+            let behavior = self.behavior();
+            let attributed_string = behavior.attributed_text.borrow();
+            let render_scale = behavior.layout().render_scale();
+            let insets = behavior.content_insets.get();
+            let text_width = insets.inset_size(&Size::new(width, 0)).width;
+
+            let layout = Layout::cached_in_frame(
+                &attributed_string,
+                Rectangle::new(0, 0, text_width, 0),
+                HorizontalAlignment::Left,
+                VerticalAlignment::Top,
+                behavior.number_of_lines.get(),
+                render_scale
+            );
+
+            let size = layout.size();
+            insets.outset_size(&Size {
+                width: (size.width as f32 / render_scale).ceil() as u32,
+                height: (size.height as f32 / render_scale).ceil() as u32
+            })
+        }
+
+        /// Resizes the view's frame to fit the size of the text, wrapping at
+        /// the current width.
+        pub fn fit_to_text(&self) {
+            let frame = self.view.frame();
+            let size = self.size_that_fits(frame.size.width);
+
+            self.view.set_frame(Rectangle { origin: frame.origin, size });
+        }
+
+        fn generate_layout(&self) {
             let inner_self = self.view.inner_self.borrow();
             let behavior = self.behavior();
             let attributed_string = behavior.attributed_text.borrow();
@@ -192,20 +449,69 @@ custom_view!(
                 render_scale = 1.0;
             }
 
-            let mut whole_text = rendering::WholeText::from(&attributed_string, self.view.frame(), render_scale);
-            whole_text.align_horizontally(behavior.text_alignment.get());
-            whole_text.align_vertically(behavior.text_vertical_alignment.get());
-
-            let rendering_result = whole_text.calculate_character_render_positions();
-            behavior.rendering_result.replace(Some(rendering_result));
+            let layout = Layout::cached_in_frame(
+                &attributed_string,
+                self.view.frame().inset_by_edges(&behavior.content_insets.get()),
+                self.resolved_text_alignment(),
+                behavior.text_vertical_alignment.get(),
+                behavior.number_of_lines.get(),
+                render_scale
+            );
+            behavior.layout.replace(Some(layout));
         }
     }
 
     impl Behavior {
+        fn accessibility_role(&self) -> AccessibilityRole {
+            AccessibilityRole::StaticText
+        }
+
+        fn accessibility_label(&self) -> Option<String> {
+            Some(String::from(self.attributed_text.borrow().text().string()))
+        }
+
+        fn locale_did_change(&self) {
+            let key = self.localized_key.borrow().clone();
+            if let Some(key) = key {
+                self.view_type().set_localized_text(&key);
+            }
+        }
+
+        /// Text left in the appearance's label color follows the new
+        /// appearance. Dynamic colors follow it as the label is drawn.
+        fn appearance_did_change(&self, _previous: &Appearance) {
+            if let Some(text_color_role) = self.text_color_role.get() {
+                let label = self.view_type();
+                label.set_text_color(label.view.effective_appearance().color(text_color_role));
+                self.text_color_role.set(Some(text_color_role));
+            }
+        }
+
+        fn touches_began(&self, touches: &Vec<Touch>) {
+            let label = self.view_type();
+            let link = touches.first().and_then(|touch| label.link_for_touch(touch));
+            self.touched_link.replace(link);
+        }
+
+        fn touches_ended(&self, touches: &Vec<Touch>) {
+            let touched_link = self.touched_link.replace(None);
+            let label = self.view_type();
+
+            if let (Some(touched_link), Some(touch)) = (touched_link, touches.first()) {
+                // Only follow the link if the touch is still over the same
+                // link it began on.
+                if label.link_for_touch(touch).as_ref() == Some(&touched_link) {
+                    if let Some(link_action) = self.link_action.borrow().as_ref() {
+                        link_action(&touched_link);
+                    }
+                }
+            }
+        }
+
         fn set_needs_display(&self) {
             self.super_behavior().unwrap().set_needs_display();
             let label = Label::from_view(self.view.upgrade().unwrap());
-            label.generate_rendering_result();
+            label.generate_layout();
         }
 
         fn draw(&self) {
@@ -219,53 +525,122 @@ custom_view!(
 
             let mut needs_generation = false;
 
-            if let Some(rendering_result) = self.rendering_result.borrow().as_ref() {
+            // A dynamic text color is resolved for the appearance the label
+            // is drawn in, which changes the color of the laid out runs.
+            let dynamic_text_color = self.dynamic_text_color.borrow().as_ref().map(|color| color.resolved_in(&view).to_graphics_color());
+            if let Some(color) = dynamic_text_color {
+                let attributed_text = self.attributed_text.borrow();
+                if *attributed_text.default_attribute(Key::Color).color() != color {
+                    attributed_text.set_default_attribute(Key::Color, Attribute::Color { color });
+                    needs_generation = true;
+                }
+            }
+
+            if let Some(layout) = self.layout.borrow().as_ref() {
                 if let Some(parent_layer) = &inner_self.layer {
                     let context = parent_layer.context();
 
-                    if context.render_scale() != rendering_result.render_scale() {
+                    if context.render_scale() != layout.render_scale() {
                         needs_generation = true;
                     }
                 }
+
+                // The label may have moved into a superview laid out in the
+                // other direction since its text was laid out.
+                if layout.horizontal_alignment() != label.resolved_text_alignment() {
+                    needs_generation = true;
+                }
             } else {
                 needs_generation = true;
             };
 
             if needs_generation {
-                label.generate_rendering_result();
+                label.generate_layout();
             }
 
-            let attributed_string = self.attributed_text.borrow();
-
             if let Some(parent_layer) = &inner_self.layer {
-                let rendering_result = self.rendering_result.borrow();
-                let rendering_result = rendering_result.as_ref().unwrap();
-
-                for (index, character) in attributed_string.chars().enumerate() {
-                    let font_attribute = &attributed_string.get_attribute_for(index, Key::Font);
-                    let color_attribute = &attributed_string.get_attribute_for(index, Key::Color);
-                    let font = font_attribute.font();
-                    let color = color_attribute.color();
-                    let position = rendering_result.position_for_character_at_index(index);
-
-                    let child_layer = font.layer_for(
-                        parent_layer.context(),
-                        &character.to_string(),
-                        color.clone()
-                    );
-
-                    let size = child_layer.size();
-                    let size = Size {
-                        width: size.width,
-                        height: size.height
-                    };
-
-                    let character_frame = Rectangle {
-                        origin: position.clone(),
-                        size: size
-                    };
-
-                    parent_layer.draw_child_layer_without_scaling(&child_layer, &character_frame);
+                let layout = self.layout.borrow();
+                let layout = layout.as_ref().unwrap();
+                let render_scale = layout.render_scale();
+
+                // The layout is in pixels from the top left of the text,
+                // inside the content insets.
+                let insets = self.content_insets.get();
+                let inset_x = (insets.left as f32 * render_scale).round() as i32;
+                let inset_y = (insets.top as f32 * render_scale).round() as i32;
+
+                // Backgrounds are drawn first so that neighbouring runs that
+                // overhang their frame are not covered up.
+                for fragment in layout.line_fragments() {
+                    for run in fragment.glyph_runs() {
+                        let background_color = run.attribute(Key::BackgroundColor).unwrap().background_color();
+                        let highlight_color = run.attribute(Key::Highlight).unwrap().highlight_color();
+                        for color in [background_color, highlight_color] {
+                            if color.a != 0 {
+                                parent_layer.fill_rectangle_without_scaling(&run.frame().offset_by(inset_x, inset_y), *color);
+                            }
+                        }
+                    }
+                }
+
+                for fragment in layout.line_fragments() {
+                    for run in fragment.glyph_runs() {
+                        let color = run.color();
+
+                        // Positive offsets raise the text.
+                        let baseline_offset = run.attribute(Key::BaselineOffset).unwrap().baseline_offset();
+                        let baseline_offset = (baseline_offset as f32 * render_scale).round() as i32;
+
+                        for (text, x_offset) in run.segments() {
+                            let child_layer = run.font().layer_for(
+                                parent_layer.context(),
+                                text,
+                                *color
+                            );
+
+                            let size = child_layer.size();
+                            let size = Size {
+                                width: size.width,
+                                height: size.height
+                            };
+
+                            let segment_frame = Rectangle {
+                                origin: Point {
+                                    x: run.frame().origin.x + x_offset + inset_x,
+                                    y: run.frame().origin.y - baseline_offset + inset_y
+                                },
+                                size: size
+                            };
+
+                            parent_layer.draw_child_layer_without_scaling(&child_layer, &segment_frame);
+                        }
+
+                        let run_frame = Rectangle {
+                            origin: Point {
+                                x: run.frame().origin.x + inset_x,
+                                y: run.frame().origin.y - baseline_offset + inset_y
+                            },
+                            size: run.frame().size.clone()
+                        };
+
+                        let underline_style = run.attribute(Key::Underline).unwrap().underline_style();
+                        let strikethrough_style = run.attribute(Key::Strikethrough).unwrap().strikethrough_style();
+                        let decorations = Decoration::Underline
+                            .rectangles(underline_style, &run_frame, render_scale)
+                            .into_iter()
+                            .chain(Decoration::Strikethrough.rectangles(strikethrough_style, &run_frame, render_scale));
+
+                        for decoration in decorations {
+                            parent_layer.fill_rectangle_without_scaling(&decoration, *color);
+                        }
+
+                        if run.attribute(Key::Misspelled).unwrap().is_misspelled() {
+                            let misspelling_color = Color::red().to_graphics_color();
+                            for dot in Decoration::Squiggle.rectangles(LineStyle::Single, &run_frame, render_scale) {
+                                parent_layer.fill_rectangle_without_scaling(&dot, misspelling_color);
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -273,20 +648,34 @@ custom_view!(
 );
 
 impl LabelBehavior {
+    /// The layout of the label's text, generating it if needed.
+    pub fn layout(&self) -> Ref<'_, Layout> {
+        if self.layout.borrow().is_none() {
+            let label = Label::from_view(self.view.upgrade().unwrap());
+            label.generate_layout();
+        }
+
+        Ref::map(self.layout.borrow(), |layout| &**layout.as_ref().unwrap())
+    }
+
     pub fn rendering(&self) -> Ref<'_, rendering::Result> {
-        let label = Label::from_view(self.view.upgrade().unwrap());
-        let rendering_result = self.rendering_result.borrow();
-        if rendering_result.is_none() {
-            label.generate_rendering_result();
-        };
+        Ref::map(self.layout(), |layout| layout.rendering())
+    }
+}
 
-        Ref::map(self.rendering_result.borrow(), |rendering_result| rendering_result.as_ref().unwrap())
+/// Converts a point in the label's coordinate space to the layout's native
+/// resolution.
+fn scaled_point(point: &Point<i32>, render_scale: f32) -> Point<i32> {
+    Point {
+        x: (point.x as f32 * render_scale).round() as i32,
+        y: (point.y as f32 * render_scale).round() as i32
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ui::{LayoutDirection, View};
 
     #[test]
     fn test_label_text() {
@@ -300,6 +689,24 @@ mod tests {
         assert_eq!(label.copy_text(), String::from("Hello World 1"));
     }
 
+    #[test]
+    fn test_localized_text() {
+        let label = Label::new(Rectangle::new(0, 0, 100, 100), String::new());
+
+        // Without a catalog with a translation, the key is the text.
+        label.set_localized_text("A key nothing translates");
+        assert_eq!(label.copy_text(), "A key nothing translates");
+        assert_eq!(label.localized_key().as_deref(), Some("A key nothing translates"));
+
+        label.view.locale_did_change();
+        assert_eq!(label.localized_key().as_deref(), Some("A key nothing translates"));
+
+        label.set_text(String::from("Plain"));
+        assert_eq!(label.localized_key(), None);
+        label.view.locale_did_change();
+        assert_eq!(label.copy_text(), "Plain");
+    }
+
     #[test]
     fn test_label_attributed_text() {
         let frame = Rectangle::new(0, 0, 100, 100);
@@ -380,6 +787,27 @@ mod tests {
         assert_eq!(label.text_color(), Color::black());
     }
 
+    #[test]
+    fn test_label_text_color_follows_appearance() {
+        let view = View::new(Rectangle::new(0, 0, 200, 100));
+        let label = Label::new(Rectangle::new(0, 0, 100, 20), String::from("A"));
+        let white_label = Label::new(Rectangle::new(0, 20, 100, 20), String::from("B"));
+        white_label.set_text_color(Color::white());
+        view.add_subview(label.view.clone());
+        view.add_subview(white_label.view.clone());
+
+        let dark = Appearance::dark();
+        view.set_appearance(Some(dark.clone()));
+        assert_eq!(label.text_color(), dark.color(SemanticColor::Label));
+        assert_eq!(white_label.text_color(), Color::white());
+
+        // White is the dark appearance's label color, but was set by the
+        // app, so it stays white in the light appearance too.
+        view.set_appearance(Some(Appearance::light()));
+        assert_eq!(label.text_color(), Color::black());
+        assert_eq!(white_label.text_color(), Color::white());
+    }
+
     #[test]
     fn test_label_font() {
         let frame = Rectangle::new(0, 0, 100, 100);
@@ -393,7 +821,168 @@ mod tests {
     fn test_label_text_alignment() {
         let frame = Rectangle::new(0, 0, 100, 100);
         let label = Label::new(frame, String::from("A"));
+        assert_eq!(label.text_alignment(), HorizontalAlignment::Natural);
         label.set_text_alignment(HorizontalAlignment::Right);
         assert_eq!(label.text_alignment(), HorizontalAlignment::Right);
     }
+
+    #[test]
+    fn test_misspelled_ranges() {
+        let label = Label::new(Rectangle::new(0, 0, 100, 20), "teh cat".to_string());
+        let revision = || label.behavior().attributed_text.borrow().revision();
+
+        // Clearing marks that aren't there leaves the text alone.
+        let unmarked = revision();
+        label.set_misspelled_ranges(&[]);
+        assert_eq!(revision(), unmarked);
+
+        label.set_misspelled_ranges(&[0..3]);
+        assert_eq!(label.misspelled_range_at_index(2), Some(0..3));
+        label.set_misspelled_ranges(&[]);
+        assert_eq!(label.misspelled_range_at_index(2), None);
+        assert!(revision() > unmarked);
+    }
+
+    #[test]
+    fn test_natural_text_alignment() {
+        let superview = View::new(Rectangle::new(0, 0, 200, 100));
+        superview.set_layout_direction(Some(LayoutDirection::LeftToRight));
+        let label = Label::new(Rectangle::new(0, 0, 100, 20), String::from("A"));
+        superview.add_subview(label.view.clone());
+        assert_eq!(label.resolved_text_alignment(), HorizontalAlignment::Left);
+
+        superview.set_layout_direction(Some(LayoutDirection::RightToLeft));
+        assert_eq!(label.resolved_text_alignment(), HorizontalAlignment::Right);
+        assert_eq!(label.behavior().layout().horizontal_alignment(), HorizontalAlignment::Right);
+
+        label.set_text_alignment(HorizontalAlignment::Center);
+        assert_eq!(label.resolved_text_alignment(), HorizontalAlignment::Center);
+    }
+
+    #[test]
+    fn test_link_at_position() {
+        let frame = Rectangle::new(0, 0, 200, 100);
+        let label = Label::new(frame, String::from("see docs"));
+
+        let attributed_text = AttributedString::new(String::from("see docs"));
+        for i in 4..8 {
+            attributed_text.set_attribute_for(i, Key::Link, Attribute::Link { url: String::from("https://example.com") });
+        }
+        label.set_attributed_text(attributed_text);
+
+        let linked_position;
+        let unlinked_position;
+        {
+            let behavior = label.behavior();
+            let rendering = behavior.rendering();
+            let position = rendering.position_for_character_at_index(5);
+            linked_position = Point { x: position.x + 1, y: position.y + 1 };
+            let position = rendering.position_for_character_at_index(1);
+            unlinked_position = Point { x: position.x + 1, y: position.y + 1 };
+        }
+
+        assert_eq!(label.link_at_position(&linked_position), Some(String::from("https://example.com")));
+        assert_eq!(label.link_at_position(&unlinked_position), None);
+        assert_eq!(label.link_at_position(&Point { x: 199, y: 99 }), None);
+    }
+
+    #[test]
+    fn test_character_index_at_point() {
+        let frame = Rectangle::new(0, 0, 1000, 1000);
+        let label = Label::new(frame, String::from("Hello\nWorld"));
+
+        let position = label.position_for_character_at_index(7);
+        let inside = Point { x: position.x + 1, y: position.y + 1 };
+        assert_eq!(label.character_index_at_point(inside.clone()), Some(7));
+        assert_eq!(label.carat_index_at_point(inside.clone()), 7);
+        assert_eq!(label.character_range_at_point(inside), Some(6..11));
+
+        let beyond = Point { x: 900, y: position.y + 1 };
+        assert_eq!(label.character_index_at_point(beyond.clone()), None);
+        assert_eq!(label.carat_index_at_point(beyond), 11);
+        assert_eq!(label.character_range_at_point(Point { x: 0, y: 900 }), None);
+    }
+
+    #[test]
+    fn test_content_insets() {
+        let frame = Rectangle::new(0, 0, 1000, 1000);
+        let label = Label::new(frame, String::from("Hello\nWorld"));
+        let size = label.size_that_fits(1000);
+        let position = label.position_for_character_at_index(7);
+
+        label.set_content_insets(EdgeInsets::new(10, 20, 30, 40));
+        assert_eq!(label.content_insets(), EdgeInsets::new(10, 20, 30, 40));
+        assert_eq!(label.size_that_fits(1000), Size::new(size.width + 60, size.height + 40));
+
+        let inset_position = label.position_for_character_at_index(7);
+        assert_eq!(inset_position, Point { x: position.x + 20, y: position.y + 10 });
+
+        let inside = Point { x: inset_position.x + 1, y: inset_position.y + 1 };
+        assert_eq!(label.character_index_at_point(inside), Some(7));
+        assert_eq!(label.character_index_at_point(Point { x: 5, y: 5 }), None);
+    }
+
+    #[test]
+    fn test_number_of_lines() {
+        let frame = Rectangle::new(0, 0, 1000, 1000);
+        let label = Label::new(frame, String::from("one\ntwo\nthree"));
+        assert_eq!(label.number_of_lines(), 0);
+
+        let unlimited_size = label.size_that_fits(1000);
+        assert_eq!(label.behavior().layout().line_fragments().len(), 3);
+
+        label.set_number_of_lines(1);
+        assert_eq!(label.behavior().layout().line_fragments().len(), 1);
+
+        let limited_size = label.size_that_fits(1000);
+        assert!(limited_size.height < unlimited_size.height);
+        assert!(limited_size.height > 0);
+    }
+
+    #[test]
+    fn test_fit_to_text() {
+        let frame = Rectangle::new(5, 10, 1000, 1000);
+        let label = Label::new(frame, String::from("one two"));
+        let expected = label.size_that_fits(1000);
+
+        label.fit_to_text();
+
+        assert_eq!(label.view.frame(), Rectangle { origin: Point { x: 5, y: 10 }, size: expected });
+    }
+
+    #[test]
+    fn test_attributed_text_copy() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let label = Label::new(frame, String::from("A"));
+
+        let attributed_text = AttributedString::new(String::from("Hi"));
+        attributed_text.set_attribute_for(1, Key::Color, Attribute::Color { color: sdl2::pixels::Color::RED });
+        label.set_attributed_text(attributed_text);
+
+        let copy = label.attributed_text();
+        assert_eq!(copy.get_attribute_for(1, Key::Color).color(), &sdl2::pixels::Color::RED);
+
+        copy.set_attribute_for(0, Key::Color, Attribute::Color { color: sdl2::pixels::Color::BLUE });
+        assert_eq!(label.attributed_text().get_attribute_for(0, Key::Color).color(), &sdl2::pixels::Color::BLACK);
+    }
+
+    #[test]
+    fn test_setters_invalidate_layout() {
+        let frame = Rectangle::new(0, 0, 1000, 100);
+        let label = Label::new(frame, String::from("Hello"));
+        assert_eq!(label.behavior().layout().line_fragments()[0].glyph_runs()[0].text(), "Hello");
+
+        label.set_text(String::from("Goodbye"));
+        assert_eq!(label.behavior().layout().line_fragments()[0].glyph_runs()[0].text(), "Goodbye");
+
+        label.set_text_color(Color::new(255, 0, 0, 255));
+        assert_eq!(label.behavior().layout().line_fragments()[0].glyph_runs()[0].color(), &sdl2::pixels::Color::RED);
+
+        let font = Font::new("Arial", 30);
+        label.set_font(font.clone());
+        assert_eq!(label.behavior().layout().line_fragments()[0].glyph_runs()[0].font(), &font);
+
+        label.set_attributed_text(AttributedString::new(String::from("Attributed")));
+        assert_eq!(label.behavior().layout().line_fragments()[0].glyph_runs()[0].text(), "Attributed");
+    }
 }
@@ -6,18 +6,37 @@ pub mod weak_view;
 pub mod delegate;
 pub mod image_view;
 pub mod label;
+pub mod shape_view;
 pub mod scroll_view;
 pub mod text_field;
+pub mod text_view;
+pub mod find_bar;
+pub mod table_view;
+pub mod table_view_cell;
+pub mod collection_view;
+pub mod collection_view_layout;
+mod reuse_pool;
+pub mod autoresizing;
+pub mod tracking_area;
 
 pub use view::View;
 pub use weak_view::WeakView;
 pub use behavior::Behavior;
 pub use default_behavior::DefaultBehavior;
 pub(crate) use view_inner::ViewInner;
-pub use image_view::ImageView;
+pub use image_view::{ContentMode, ImageView};
 pub use label::Label;
+pub use shape_view::ShapeView;
 pub use scroll_view::ScrollView;
 pub use text_field::TextField;
+pub use text_view::TextView;
+pub use find_bar::FindBar;
+pub use table_view::{TableView, TableViewDataSource};
+pub use table_view_cell::TableViewCell;
+pub use collection_view::{CollectionView, CollectionViewCell, CollectionViewDataSource};
+pub use collection_view_layout::{CollectionViewLayout, FlowLayout};
+pub use autoresizing::AutoresizingMask;
+pub use tracking_area::{TrackingArea, TrackingOption};
 
 #[cfg(test)]
 mod tests {
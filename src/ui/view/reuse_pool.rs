@@ -0,0 +1,96 @@
+use crate::ui::view::View;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A cell of a `TableView` or `CollectionView`, which is kept in a
+/// `ReusePool` while it's scrolled out of view.
+pub(crate) trait ReusableCell {
+    fn view(&self) -> &View;
+
+    /// Identifies which cells can be reused for each other.
+    fn reuse_identifier(&self) -> String;
+
+    /// Readies the cell to be shown for another row or item, clearing
+    /// whatever it was left showing.
+    fn prepare_for_reuse(&self);
+}
+
+/// The cells scrolled out of view, by reuse identifier, waiting to be
+/// dequeued for the rows or items scrolling into view.
+pub(crate) struct ReusePool<T> {
+    cells: RefCell<HashMap<String, Vec<T>>>
+}
+
+impl<T: ReusableCell> ReusePool<T> {
+    pub fn new() -> ReusePool<T> {
+        ReusePool {
+            cells: RefCell::new(HashMap::new())
+        }
+    }
+
+    /// A cell with `reuse_identifier`, if there is one in the pool.
+    pub fn dequeue(&self, reuse_identifier: &str) -> Option<T> {
+        self.cells.borrow_mut().get_mut(reuse_identifier).and_then(|cells| cells.pop())
+    }
+
+    /// Takes `cell` out of view and readies it for reuse, keeping it
+    /// until it's dequeued.
+    pub fn enqueue(&self, cell: T) {
+        cell.view().remove_from_superview();
+        cell.prepare_for_reuse();
+        self.cells.borrow_mut().entry(cell.reuse_identifier()).or_default().push(cell);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Rectangle;
+    use std::cell::Cell;
+
+    struct TestCell {
+        view: View,
+        reuse_identifier: &'static str,
+        times_prepared: Cell<usize>
+    }
+
+    impl ReusableCell for TestCell {
+        fn view(&self) -> &View {
+            &self.view
+        }
+
+        fn reuse_identifier(&self) -> String {
+            self.reuse_identifier.to_string()
+        }
+
+        fn prepare_for_reuse(&self) {
+            self.times_prepared.set(self.times_prepared.get() + 1);
+        }
+    }
+
+    fn cell(reuse_identifier: &'static str) -> TestCell {
+        TestCell {
+            view: View::new(Rectangle::new(0, 0, 10, 10)),
+            reuse_identifier,
+            times_prepared: Cell::new(0)
+        }
+    }
+
+    #[test]
+    fn test_enqueue_and_dequeue() {
+        let pool = ReusePool::new();
+        let superview = View::new(Rectangle::new(0, 0, 100, 100));
+        let photo = cell("photo");
+        superview.add_subview(photo.view.clone());
+
+        pool.enqueue(photo);
+        pool.enqueue(cell("caption"));
+        assert!(superview.subviews().is_empty());
+
+        let photo = pool.dequeue("photo").unwrap();
+        assert_eq!(photo.times_prepared.get(), 1);
+        assert!(pool.dequeue("photo").is_none());
+        assert!(pool.dequeue("caption").is_some());
+        assert!(pool.dequeue("missing").is_none());
+    }
+}
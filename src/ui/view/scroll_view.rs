@@ -1,18 +1,68 @@
-use crate::graphics::Rectangle;
+use crate::graphics::{EdgeInsets, Rectangle};
 use crate::graphics::Size;
 use crate::ui::view::View;
 use crate::ui::Color;
 use crate::ui::view::DefaultBehavior;
+use crate::ui::accessibility::AccessibilityRole;
 use crate::macros::*;
-use crate::ui::gesture::pan_recognizer::PanRecognizer;
+use crate::ui::gesture::pan_recognizer::{PanRecognizer, PanState};
+use crate::ui::display_link::DisplayLink;
 use crate::graphics::Point;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use crate::ui::clock;
+use crate::ui::event::ScrollEvent;
+
+type ScrollCallback = Rc<dyn Fn(&ScrollView)>;
+
+/// How much of the scrolling velocity is kept after each millisecond of
+/// momentum scrolling.
+const DECELERATION_RATE: f32 = 0.998;
+
+/// The speed, in points per second, below which momentum scrolling stops.
+const MINIMUM_VELOCITY: f32 = 10.0;
+
+/// How long a pan has to be held still before it's let go for it to end
+/// without momentum.
+const MOMENTUM_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The distance travelled, and the velocity after, when scrolling at
+/// `velocity` points per second decelerates for `seconds`.
+fn decelerate(velocity: f32, seconds: f32) -> (f32, f32) {
+    let decay = DECELERATION_RATE.powf(seconds * 1000.0);
+    let distance = velocity * (decay - 1.0) / (DECELERATION_RATE.ln() * 1000.0);
+    (distance, velocity * decay)
+}
 
+// A view that scrolls its content view.
+//
+// The content view is moved by changing the scroll view's content offset,
+// either directly, by dragging, or with the scroll wheel or trackpad. Letting
+// go of a drag while it's moving carries on scrolling with momentum, slowing
+// down each frame until it stops or reaches the edge of the content. Content
+// outside the scroll view is clipped, as with any other view's subviews.
 custom_view!(
     ScrollView subclasses DefaultBehavior
 
     struct ScrollViewBehavior {
+        shows_scroll_indicators: Cell<bool>,
+
+        // The velocity of the content offset while decelerating, in points
+        // per second, and when a drag last moved it, to tell whether the
+        // drag was held still before being let go.
+        velocity: Cell<(f32, f32)>,
+        last_dragged_at: Cell<Option<Instant>>,
+
+        // While decelerating, the display link moving the content, and the
+        // content offset including the fractions of a point it's moved.
+        deceleration: RefCell<Option<DisplayLink>>,
+        decelerating_offset: Cell<(f32, f32)>,
+
+        scroll_action: RefCell<Option<ScrollCallback>>,
 
+        // Extra space around the content that can be scrolled to.
+        content_insets: Cell<EdgeInsets>
     }
 
     impl Self {
@@ -23,7 +73,16 @@ custom_view!(
             let content_view = View::new(Rectangle::new(0, 0, 0, 0));
             content_view.set_background_color(Color::clear());
 
-            let scroll_view = Self::new_all(frame);
+            let scroll_view = Self::new_all(
+                frame,
+                Cell::new(true),
+                Cell::new((0.0, 0.0)),
+                Cell::new(None),
+                RefCell::new(None),
+                Cell::new((0.0, 0.0)),
+                RefCell::new(None),
+                Cell::new(EdgeInsets::zero())
+            );
             scroll_view.view.set_background_color(Color::clear());
             scroll_view.view.add_subview(content_view);
             scroll_view.view.add_subview(vertical_scroll_bar.view.clone());
@@ -47,11 +106,25 @@ custom_view!(
                     -translation.y
                 );
 
-                scroll_view.set_content_offset(
-                    scroll_view.content_offset() + translation
-                );
+                let state = gesture_recognizer.state();
+                if state == PanState::Began {
+                    scroll_view.stop_deceleration();
+                }
 
+                scroll_view.scroll_by(translation);
                 gesture_recognizer.set_translation(Point::new(0, 0), &view);
+
+                match state {
+                    PanState::Began | PanState::Changed => {
+                        scroll_view.behavior().last_dragged_at.set(Some(clock::now()));
+                    },
+                    PanState::Ended => {
+                        // The content moves the opposite way to the touch.
+                        let velocity = gesture_recognizer.velocity_in(&view);
+                        scroll_view.begin_deceleration((-velocity.x, -velocity.y), clock::now());
+                    },
+                    _ => scroll_view.stop_deceleration()
+                }
             });
 
             scroll_view.view.add_gesture_recognizer(Box::new(pan_gesture));
@@ -63,7 +136,8 @@ custom_view!(
             self.inner_content_view().bounds().origin
         }
 
-        fn content_size(&self) -> Size<u32> {
+        /// The size of the content view, or zero without one.
+        pub fn content_size(&self) -> Size<u32> {
             if let Some(content_view) = self.content_view() {
                 content_view.frame().size
             } else {
@@ -71,11 +145,41 @@ custom_view!(
             }
         }
 
-        fn set_content_offset(&self, offset: Point<i32>) {
-            let mut content_width = self.content_size().width;
+        /// The space around the content that can be scrolled to, such as so
+        /// the content isn't flush against the scroll view's edges, or can
+        /// be scrolled out from under a bar over the top. Defaults to none.
+        pub fn content_insets(&self) -> EdgeInsets {
+            self.behavior().content_insets.get()
+        }
+
+        /// Sets the space around the content, moving the content view inside
+        /// it and keeping the content offset within the new size.
+        pub fn set_content_insets(&self, content_insets: EdgeInsets) {
+            self.behavior().content_insets.set(content_insets);
+
+            if let Some(content_view) = self.content_view() {
+                let size = content_view.frame().size;
+                content_view.set_frame(Rectangle::new(content_insets.left, content_insets.top, size.width, size.height));
+            }
+
+            self.update_content_size(self.content_size());
+            self.set_content_offset(self.content_offset());
+        }
+
+        /// The size of the content with the content insets around it, how
+        /// far there is to scroll.
+        fn scrollable_size(&self) -> Size<u32> {
+            self.content_insets().outset_size(&self.content_size())
+        }
+
+        /// Scrolls so that `offset` of the content, including the content
+        /// insets, is at the top left of the scroll view, as far as the
+        /// content allows.
+        pub fn set_content_offset(&self, offset: Point<i32>) {
+            let mut content_width = self.scrollable_size().width;
             let scrollview_width = self.view.frame().size.width;
 
-            let mut content_height = self.content_size().height;
+            let mut content_height = self.scrollable_size().height;
             let scrollview_height = self.view.frame().size.height;
 
             if content_width < scrollview_width {
@@ -106,6 +210,147 @@ custom_view!(
 
             self.vertical_scroll_bar().set_percent(vertical_percent as u8);
             self.horizontal_scroll_bar().set_percent(horizontal_percent as u8);
+
+            let scroll_action = self.behavior().scroll_action.borrow().clone();
+            if let Some(scroll_action) = scroll_action {
+                scroll_action(self);
+            }
+        }
+
+        /// Calls `action` whenever the content offset is set, whether or not
+        /// it changed, such as to lay out content as it scrolls into view.
+        pub fn on_scroll(&self, action: impl Fn(&ScrollView) + 'static) {
+            self.behavior().scroll_action.replace(Some(Rc::new(action)));
+        }
+
+        /// Moves the content offset by `delta`, as far as the content
+        /// allows.
+        pub fn scroll_by(&self, delta: Point<i32>) {
+            self.set_content_offset(self.content_offset() + delta);
+        }
+
+        /// Whether the content is scrolling with momentum after a drag.
+        pub fn is_decelerating(&self) -> bool {
+            self.behavior().deceleration.borrow().is_some()
+        }
+
+        /// Stops scrolling with momentum, leaving the content where it is.
+        pub fn stop_deceleration(&self) {
+            if let Some(display_link) = self.behavior().deceleration.replace(None) {
+                display_link.invalidate();
+            }
+        }
+
+        /// Carries on scrolling at `velocity`, that of the drag's touch
+        /// when it ended at `now`, unless it was held still before being
+        /// let go.
+        fn begin_deceleration(&self, velocity: (f32, f32), now: Instant) {
+            self.stop_deceleration();
+
+            let behavior = self.behavior();
+            behavior.velocity.set((0.0, 0.0));
+            let is_held = match behavior.last_dragged_at.replace(None) {
+                Some(last_dragged_at) => now.saturating_duration_since(last_dragged_at) > MOMENTUM_TIMEOUT,
+                None => true
+            };
+
+            if is_held || (velocity.0.abs() < MINIMUM_VELOCITY && velocity.1.abs() < MINIMUM_VELOCITY) {
+                return;
+            }
+
+            let offset = self.content_offset();
+            behavior.velocity.set(velocity);
+            behavior.decelerating_offset.set((offset.x as f32, offset.y as f32));
+
+            let weak_view = self.view.downgrade();
+            let display_link = DisplayLink::new(move |frame| {
+                if let Some(view) = weak_view.upgrade() {
+                    let scroll_view = ScrollView::from_view(view);
+                    if !scroll_view.decelerate(frame.delta.as_secs_f32()) {
+                        scroll_view.stop_deceleration();
+                    }
+                }
+            });
+            display_link.set_frame_interval(Duration::from_secs(0));
+            display_link.add_to_run_loop();
+            behavior.deceleration.replace(Some(display_link));
+        }
+
+        /// Moves the content for `seconds` of momentum scrolling. Returns
+        /// whether it's still moving.
+        fn decelerate(&self, seconds: f32) -> bool {
+            let (velocity_x, velocity_y) = self.behavior().velocity.get();
+            let (distance_x, velocity_x) = decelerate(velocity_x, seconds);
+            let (distance_y, velocity_y) = decelerate(velocity_y, seconds);
+
+            let (x, y) = self.behavior().decelerating_offset.get();
+            let (x, y) = (x + distance_x, y + distance_y);
+            let target = Point::new(x.round() as i32, y.round() as i32);
+            self.set_content_offset(target.clone());
+
+            // Stop moving along an axis once the edge of the content is
+            // reached.
+            let offset = self.content_offset();
+            let velocity_x = if offset.x == target.x { velocity_x } else { 0.0 };
+            let velocity_y = if offset.y == target.y { velocity_y } else { 0.0 };
+
+            let behavior = self.behavior();
+            behavior.velocity.set((velocity_x, velocity_y));
+            behavior.decelerating_offset.set((x, y));
+
+            velocity_x.abs() >= MINIMUM_VELOCITY || velocity_y.abs() >= MINIMUM_VELOCITY
+        }
+
+        /// Whether the scroll bars are shown when the content is bigger than
+        /// the scroll view. Defaults to `true`.
+        pub fn shows_scroll_indicators(&self) -> bool {
+            self.behavior().shows_scroll_indicators.get()
+        }
+
+        pub fn set_shows_scroll_indicators(&self, shows_scroll_indicators: bool) {
+            self.behavior().shows_scroll_indicators.set(shows_scroll_indicators);
+            self.vertical_scroll_bar().view.set_hidden(!shows_scroll_indicators);
+            self.horizontal_scroll_bar().view.set_hidden(!shows_scroll_indicators);
+        }
+
+        /// Scrolls as little as needed so that `rectangle`, in the content
+        /// view's coordinate space, is visible. If it's bigger than the
+        /// scroll view, its top left corner is made visible.
+        pub fn scroll_rectangle_to_visible(&self, rectangle: Rectangle<i32, u32>) {
+            let insets = self.content_insets();
+            let rectangle = rectangle.offset_by(insets.left, insets.top);
+            let size = self.view.frame().size;
+            let mut offset = self.content_offset();
+
+            let right = rectangle.origin.x + rectangle.size.width as i32;
+            if right > offset.x + size.width as i32 {
+                offset.x = right - size.width as i32;
+            }
+            if rectangle.origin.x < offset.x {
+                offset.x = rectangle.origin.x;
+            }
+
+            let bottom = rectangle.origin.y + rectangle.size.height as i32;
+            if bottom > offset.y + size.height as i32 {
+                offset.y = bottom - size.height as i32;
+            }
+            if rectangle.origin.y < offset.y {
+                offset.y = rectangle.origin.y;
+            }
+
+            self.set_content_offset(offset);
+        }
+
+        /// Resizes the content view, for example after its content grows,
+        /// keeping the content offset within the new size.
+        pub fn set_content_size(&self, size: Size<u32>) {
+            if let Some(content_view) = self.content_view() {
+                let frame = content_view.frame();
+                content_view.set_frame(Rectangle { origin: frame.origin, size: size.clone() });
+            }
+
+            self.update_content_size(size);
+            self.set_content_offset(self.content_offset());
         }
 
         fn inner_content_view(&self) -> View {
@@ -119,7 +364,12 @@ custom_view!(
                 existing_subview.remove_from_superview();
             }
 
-            self.update_content_size(view.frame().size);
+            // The content view is placed inside the content insets.
+            let insets = self.content_insets();
+            let size = view.frame().size;
+            view.set_frame(Rectangle::new(insets.left, insets.top, size.width, size.height));
+
+            self.update_content_size(size);
 
             self.inner_content_view().add_subview(view);
         }
@@ -144,12 +394,42 @@ custom_view!(
         }
 
         fn update_content_size(&self, size: Size<u32>) {
+            let size = self.content_insets().outset_size(&size);
             let inner_content_view = self.inner_content_view();
             inner_content_view.set_frame(Rectangle::new(0, 0, size.width, size.height));
 
             self.vertical_scroll_bar().update_scroll_handle();
             self.horizontal_scroll_bar().update_scroll_handle();
         }
+
+        /// Whether the content is bigger than the scroll view along the
+        /// axes `delta` moves along, so it can scroll that way.
+        fn can_scroll_by(&self, delta: &Point<f32>) -> bool {
+            let content_size = self.scrollable_size();
+            let size = self.view.frame().size;
+            (delta.x != 0.0 && content_size.width > size.width) ||
+                (delta.y != 0.0 && content_size.height > size.height)
+        }
+    }
+
+    impl Behavior {
+        fn accessibility_role(&self) -> AccessibilityRole {
+            AccessibilityRole::ScrollView
+        }
+
+        fn scroll_wheel(&self, event: &ScrollEvent) -> bool {
+            let scroll_view = self.view_type();
+            let delta = event.delta();
+            if !scroll_view.can_scroll_by(&delta) {
+                return false;
+            }
+
+            // Scrolling with the scroll wheel or trackpad stops any
+            // momentum from a previous drag.
+            scroll_view.stop_deceleration();
+            scroll_view.scroll_by(Point::new(delta.x.round() as i32, delta.y.round() as i32));
+            true
+        }
     }
 );
 
@@ -288,16 +568,20 @@ custom_view!(
                         height: 10
                     };
 
-                    let percent_of_scrollview = self.percent() as f32 / 100.0;
-                    let origin_x = (percent_of_scrollview * (scrollview_size.width - handle_size.width) as f32) as i32;
-                    let origin_y = handle.frame().origin.y;
+                    if scrollview_size.width < handle_size.width {
+                        handle.set_hidden(true);
+                    } else {
+                        let percent_of_scrollview = self.percent() as f32 / 100.0;
+                        let origin_x = (percent_of_scrollview * (scrollview_size.width - handle_size.width) as f32) as i32;
+                        let origin_y = handle.frame().origin.y;
 
-                    handle.set_frame(Rectangle {
-                        origin: Point::new(origin_x, origin_y),
-                        size: handle_size
-                    });
+                        handle.set_frame(Rectangle {
+                            origin: Point::new(origin_x, origin_y),
+                            size: handle_size
+                        });
 
-                    handle.set_hidden(scrollview_size.width == width_of_horizontal_handle);
+                        handle.set_hidden(scrollview_size.width == width_of_horizontal_handle);
+                    }
                 }
             }
         }
@@ -372,4 +656,135 @@ mod tests {
             assert_eq!(scroll_view.content_offset(), Point::new(10, 10));
         }
     }
+
+    #[test]
+    fn test_content_insets() {
+        let scroll_view = ScrollView::new(Rectangle::new(0, 0, 100, 100));
+        scroll_view.set_content_view(View::new(Rectangle::new(0, 0, 100, 200)));
+        scroll_view.set_content_insets(EdgeInsets::new(10, 20, 30, 0));
+
+        assert_eq!(scroll_view.content_view().unwrap().frame(), Rectangle::new(20, 10, 100, 200));
+        assert_eq!(scroll_view.content_size(), Size::new(100, 200));
+
+        // The insets can be scrolled to, past the content.
+        scroll_view.set_content_offset(Point::new(100, 1000));
+        assert_eq!(scroll_view.content_offset(), Point::new(20, 140));
+
+        // Rectangles are in the content view's coordinate space.
+        scroll_view.set_content_offset(Point::new(0, 0));
+        scroll_view.scroll_rectangle_to_visible(Rectangle::new(0, 150, 10, 10));
+        assert_eq!(scroll_view.content_offset(), Point::new(0, 70));
+    }
+
+    #[test]
+    fn test_scroll_wheel_goes_to_scroll_view_that_can_scroll() {
+        let root = View::new(Rectangle::new(0, 0, 400, 400));
+        let outer = ScrollView::new(Rectangle::new(0, 0, 200, 200));
+        outer.set_content_view(View::new(Rectangle::new(0, 0, 200, 1000)));
+        root.add_subview(outer.view.clone());
+
+        // The inner scroll view only scrolls sideways.
+        let inner = ScrollView::new(Rectangle::new(0, 0, 100, 100));
+        inner.set_content_view(View::new(Rectangle::new(0, 0, 500, 100)));
+        outer.content_view().unwrap().add_subview(inner.view.clone());
+
+        let event = ScrollEvent::new();
+        event.set_delta(Point::new(0.0, 30.0), false);
+        assert_eq!(root.scroll_wheel_at(&Point::new(50, 50), &event), Some(outer.view.clone()));
+        assert_eq!(outer.content_offset(), Point::new(0, 30));
+        assert_eq!(inner.content_offset(), Point::new(0, 0));
+
+        event.set_delta(Point::new(40.0, 0.0), false);
+        assert_eq!(root.scroll_wheel_at(&Point::new(50, 50), &event), Some(inner.view.clone()));
+        assert_eq!(inner.content_offset(), Point::new(40, 0));
+
+        // Nothing scrolls outside the scroll views.
+        assert!(root.scroll_wheel_at(&Point::new(300, 300), &event).is_none());
+    }
+
+    #[test]
+    fn test_scroll_rectangle_to_visible() {
+        let scroll_view = ScrollView::new(Rectangle::new(0, 0, 100, 100));
+        scroll_view.set_content_view(View::new(Rectangle::new(0, 0, 100, 100)));
+        scroll_view.set_content_size(Size::new(100, 300));
+
+        scroll_view.scroll_rectangle_to_visible(Rectangle::new(0, 150, 10, 20));
+        assert_eq!(scroll_view.content_offset(), Point::new(0, 70));
+
+        // Already visible, so nothing moves.
+        scroll_view.scroll_rectangle_to_visible(Rectangle::new(0, 80, 10, 20));
+        assert_eq!(scroll_view.content_offset(), Point::new(0, 70));
+
+        scroll_view.scroll_rectangle_to_visible(Rectangle::new(0, 20, 10, 20));
+        assert_eq!(scroll_view.content_offset(), Point::new(0, 20));
+
+        // Shrinking the content keeps the offset within it.
+        scroll_view.set_content_size(Size::new(100, 110));
+        assert_eq!(scroll_view.content_offset(), Point::new(0, 10));
+    }
+
+    #[test]
+    fn test_decelerate() {
+        let (distance, velocity) = decelerate(1000.0, 0.0);
+        assert_eq!(distance, 0.0);
+        assert_eq!(velocity, 1000.0);
+
+        let (distance, velocity) = decelerate(1000.0, 0.1);
+        assert!((velocity - 1000.0 * 0.998f32.powf(100.0)).abs() < 0.01);
+        assert!(distance > 0.0 && distance < 100.0);
+
+        // The total distance approaches the velocity over the rate of decay.
+        let (distance, velocity) = decelerate(-1000.0, 10.0);
+        assert!(velocity.abs() < MINIMUM_VELOCITY);
+        assert!((distance + 1000.0 / (-0.998f32.ln() * 1000.0)).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_momentum_scrolling() {
+        let scroll_view = ScrollView::new(Rectangle::new(0, 0, 100, 100));
+        scroll_view.set_content_view(View::new(Rectangle::new(0, 0, 100, 1000)));
+
+        let start = Instant::now();
+        scroll_view.scroll_by(Point::new(0, 40));
+        scroll_view.behavior().last_dragged_at.set(Some(start));
+
+        // Held still before letting go, so there's no momentum.
+        scroll_view.begin_deceleration((0.0, 800.0), start + Duration::from_millis(200));
+        assert!(!scroll_view.is_decelerating());
+        assert_eq!(scroll_view.behavior().velocity.get(), (0.0, 0.0));
+
+        scroll_view.behavior().last_dragged_at.set(Some(start));
+        scroll_view.begin_deceleration((0.0, 800.0), start + Duration::from_millis(20));
+        assert!(scroll_view.is_decelerating());
+        assert_eq!(scroll_view.behavior().velocity.get(), (0.0, 800.0));
+        scroll_view.stop_deceleration();
+
+        // Carries on moving until it slows to a stop.
+        scroll_view.behavior().velocity.set((0.0, 800.0));
+        scroll_view.behavior().decelerating_offset.set((0.0, 40.0));
+        let mut frames = 0;
+        while scroll_view.decelerate(0.016) {
+            frames += 1;
+        }
+        assert!(frames > 10);
+        let offset = scroll_view.content_offset();
+        assert!(offset.y > 300 && offset.y < 450);
+
+        // Reaching the end of the content stops it.
+        scroll_view.behavior().velocity.set((0.0, 10000.0));
+        scroll_view.behavior().decelerating_offset.set((0.0, 850.0));
+        assert!(!scroll_view.decelerate(0.1));
+        assert_eq!(scroll_view.content_offset(), Point::new(0, 900));
+    }
+
+    #[test]
+    fn test_shows_scroll_indicators() {
+        let scroll_view = ScrollView::new(Rectangle::new(0, 0, 100, 100));
+        assert!(scroll_view.shows_scroll_indicators());
+
+        scroll_view.set_shows_scroll_indicators(false);
+        assert!(!scroll_view.shows_scroll_indicators());
+        assert!(scroll_view.vertical_scroll_bar().view.is_hidden());
+        assert!(scroll_view.horizontal_scroll_bar().view.is_hidden());
+    }
 }
@@ -0,0 +1,242 @@
+use crate::graphics::{FillRule, Path, Rectangle, StrokeStyle};
+use crate::ui::Color;
+use crate::ui::animation::{Animation, Easing};
+use crate::ui::view::DefaultBehavior;
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use crate::macros::*;
+
+// A view drawing a path, filled and outlined, such as for charts,
+// icons, or progress rings.
+//
+// The path is in the view's own coordinates, in points, and drawn into
+// its layer when the view is displayed. Changing the path, or animating
+// it to another with the same elements, redraws the view.
+//
+// ```ignore
+// let mut path = Path::new();
+// path.arc(Point::new(20.0, 20.0), 16.0, 0.0, progress * 2.0 * PI, true);
+// let ring = ShapeView::new(Rectangle::new(0, 0, 40, 40), path);
+// ring.set_fill_color(None);
+// ring.set_stroke_color(Some(Color::semantic(SemanticColor::Accent)));
+// ring.set_line_width(4.0);
+// ```
+custom_view!(
+    ShapeView subclasses DefaultBehavior
+
+    struct ShapeViewBehavior {
+        path: RefCell<Path>,
+        fill_color: RefCell<Option<Color>>,
+        fill_rule: Cell<FillRule>,
+        stroke_color: RefCell<Option<Color>>,
+        stroke_style: RefCell<StrokeStyle>,
+        path_animation: RefCell<Option<Animation>>
+    }
+
+    impl Self {
+        /// A shape view drawing `path`, filled in black, on a clear
+        /// background.
+        pub fn new(frame: Rectangle<i32, u32>, path: Path) -> ShapeView {
+            let shape_view = Self::new_all(
+                frame,
+                RefCell::new(path),
+                RefCell::new(Some(Color::black())),
+                Cell::new(FillRule::NonZero),
+                RefCell::new(None),
+                RefCell::new(StrokeStyle::new(1.0)),
+                RefCell::new(None)
+            );
+            shape_view.view.set_background_color(Color::clear());
+            shape_view
+        }
+
+        pub fn path(&self) -> Path {
+            self.behavior().path.borrow().clone()
+        }
+
+        /// Changes the path drawn, stopping any path animation.
+        pub fn set_path(&self, path: Path) {
+            self.stop_path_animation();
+            self.update_path(path);
+        }
+
+        /// The color the inside of the path is filled with, if it's
+        /// filled. Defaults to black.
+        pub fn fill_color(&self) -> Option<Color> {
+            self.behavior().fill_color.borrow().clone()
+        }
+
+        pub fn set_fill_color(&self, fill_color: Option<Color>) {
+            self.behavior().fill_color.replace(fill_color);
+            self.view.set_needs_display();
+        }
+
+        /// Which parts of the path count as inside it. Defaults to
+        /// `FillRule::NonZero`.
+        pub fn fill_rule(&self) -> FillRule {
+            self.behavior().fill_rule.get()
+        }
+
+        pub fn set_fill_rule(&self, fill_rule: FillRule) {
+            self.behavior().fill_rule.set(fill_rule);
+            self.view.set_needs_display();
+        }
+
+        /// The color the path is outlined with, if it's outlined. Defaults
+        /// to `None`.
+        pub fn stroke_color(&self) -> Option<Color> {
+            self.behavior().stroke_color.borrow().clone()
+        }
+
+        pub fn set_stroke_color(&self, stroke_color: Option<Color>) {
+            self.behavior().stroke_color.replace(stroke_color);
+            self.view.set_needs_display();
+        }
+
+        /// How wide the outline is, in points. Defaults to 1.
+        pub fn line_width(&self) -> f32 {
+            self.behavior().stroke_style.borrow().line_width
+        }
+
+        pub fn set_line_width(&self, line_width: f32) {
+            self.behavior().stroke_style.borrow_mut().line_width = line_width;
+            self.view.set_needs_display();
+        }
+
+        /// The width, caps, joins and dashes of the outline.
+        pub fn stroke_style(&self) -> StrokeStyle {
+            self.behavior().stroke_style.borrow().clone()
+        }
+
+        pub fn set_stroke_style(&self, stroke_style: StrokeStyle) {
+            self.behavior().stroke_style.replace(stroke_style);
+            self.view.set_needs_display();
+        }
+
+        /// Animates from the current path to `path` over `duration`, moving
+        /// each point along a straight line. The paths must be compatible,
+        /// see `Path::is_compatible`; if they aren't, the path is changed
+        /// straight away and there's no animation.
+        ///
+        /// Any path animation already running is stopped where it is, and
+        /// animated on from there.
+        pub fn animate_path(&self, path: Path, duration: Duration, easing: Easing) -> Option<Animation> {
+            self.stop_path_animation();
+
+            let current = self.path();
+            if !current.is_compatible(&path) {
+                self.update_path(path);
+                return None;
+            }
+
+            let animation = Animation::with_progress(&self.view, duration, easing, move |view, progress| {
+                let shape_view = ShapeView::from_view(view.clone());
+                if progress == 1.0 {
+                    shape_view.update_path(path.clone());
+                } else if let Some(frame_path) = current.interpolate(&path, progress) {
+                    shape_view.update_path(frame_path);
+                }
+            });
+
+            self.behavior().path_animation.replace(Some(animation.clone()));
+            Some(animation)
+        }
+
+        pub fn is_animating_path(&self) -> bool {
+            self.behavior().path_animation.borrow().as_ref().is_some_and(Animation::is_running)
+        }
+
+        /// Stops any path animation, leaving the path as it is in the
+        /// current frame.
+        pub fn stop_path_animation(&self) {
+            let animation = self.behavior().path_animation.replace(None);
+            if let Some(animation) = animation {
+                animation.cancel();
+            }
+        }
+
+        fn update_path(&self, path: Path) {
+            self.behavior().path.replace(path);
+            self.view.set_needs_display();
+        }
+    }
+
+    impl Behavior {
+        fn draw(&self) {
+            self.super_behavior().unwrap().draw();
+
+            let view = self.view.upgrade().unwrap().clone();
+            let inner_self = view.inner_self.borrow();
+
+            if let Some(layer) = &inner_self.layer {
+                let path = self.path.borrow();
+
+                if let Some(fill_color) = self.fill_color.borrow().as_ref() {
+                    layer.fill_path(&path, fill_color.resolved_in(&view).to_graphics_color(), self.fill_rule.get());
+                }
+
+                if let Some(stroke_color) = self.stroke_color.borrow().as_ref() {
+                    layer.stroke_path(&path, stroke_color.resolved_in(&view).to_graphics_color(), &self.stroke_style.borrow());
+                }
+            }
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Point;
+    use std::rc::Rc;
+    use std::time::Instant;
+
+    #[test]
+    fn test_path_animation_steps() {
+        let from = Path::rectangle(&Rectangle::new(0.0, 0.0, 10.0, 10.0));
+        let to = Path::rectangle(&Rectangle::new(10.0, 0.0, 10.0, 10.0));
+        let shape_view = ShapeView::new(Rectangle::new(0, 0, 20, 20), from.clone());
+        let animation = shape_view.animate_path(to.clone(), Duration::from_millis(100), Easing::Linear).unwrap();
+
+        let completed = Rc::new(Cell::new(None));
+        let completed_clone = completed.clone();
+        animation.on_complete(move |finished| completed_clone.set(Some(finished)));
+
+        let start = Instant::now();
+        animation.step(start);
+        assert_eq!(shape_view.path(), from);
+
+        animation.step(start + Duration::from_millis(50));
+        assert!(shape_view.is_animating_path());
+        assert_eq!(shape_view.path().current_point(), Some(Point::new(5.0, 0.0)));
+
+        animation.step(start + Duration::from_millis(150));
+        assert_eq!(shape_view.path(), to);
+        assert!(!shape_view.is_animating_path());
+        assert_eq!(completed.get(), Some(true));
+    }
+
+    #[test]
+    fn test_setting_the_path_cancels_the_animation() {
+        let from = Path::rectangle(&Rectangle::new(0.0, 0.0, 10.0, 10.0));
+        let to = Path::rectangle(&Rectangle::new(10.0, 0.0, 10.0, 10.0));
+        let shape_view = ShapeView::new(Rectangle::new(0, 0, 20, 20), from.clone());
+        let animation = shape_view.animate_path(to, Duration::from_millis(100), Easing::Linear).unwrap();
+
+        shape_view.set_path(from.clone());
+        assert!(!animation.is_running());
+        assert!(!animation.is_finished());
+
+        animation.step(Instant::now());
+        assert_eq!(shape_view.path(), from);
+    }
+
+    #[test]
+    fn test_incompatible_path_is_set_straight_away() {
+        let shape_view = ShapeView::new(Rectangle::new(0, 0, 20, 20), Path::rectangle(&Rectangle::new(0.0, 0.0, 10.0, 10.0)));
+        let ellipse = Path::ellipse(&Rectangle::new(0.0, 0.0, 20.0, 20.0));
+
+        assert!(shape_view.animate_path(ellipse.clone(), Duration::from_millis(100), Easing::Linear).is_none());
+        assert!(!shape_view.is_animating_path());
+        assert_eq!(shape_view.path(), ellipse);
+    }
+}
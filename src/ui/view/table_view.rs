@@ -0,0 +1,460 @@
+use crate::graphics::{Point, Rectangle, Size};
+use crate::ui::view::{DefaultBehavior, ScrollView, TableViewCell, View};
+use crate::ui::view::reuse_pool::ReusePool;
+use crate::ui::{Color, SemanticColor};
+use crate::ui::press::Press;
+use crate::ui::key::KeyCode;
+use crate::macros::*;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// The height of each row unless it's changed with `set_row_height`.
+const DEFAULT_ROW_HEIGHT: u32 = 44;
+
+type SelectRowCallback = Rc<dyn Fn(&TableView, usize)>;
+
+/// Provides the rows of a `TableView`.
+pub trait TableViewDataSource {
+    /// How many rows the table view has.
+    fn number_of_rows(&self, table_view: &TableView) -> usize;
+
+    /// The cell showing `row`. This is only asked for rows about to scroll
+    /// into view, and should use `TableView::dequeue_reusable_cell` to reuse
+    /// a cell that's scrolled out of view where it can, rather than
+    /// creating a new one.
+    fn cell_for_row(&self, table_view: &TableView, row: usize) -> TableViewCell;
+}
+
+// A scrolling list of rows.
+//
+// The rows come from a `TableViewDataSource`, and only the rows in view have
+// cells, so a table view with thousands of rows only has as many cells as
+// fit on screen. Cells that scroll out of view are kept to be reused for the
+// rows scrolling into view.
+//
+// Tapping a row selects it, highlighting its cell and calling the
+// `on_select_row` callback. While the table view has keyboard focus, the
+// arrow keys, Home and End move the selection.
+custom_view!(
+    TableView subclasses DefaultBehavior
+
+    struct TableViewBehavior {
+        data_source: RefCell<Option<Rc<dyn TableViewDataSource>>>,
+        row_height: Cell<u32>,
+        number_of_rows: Cell<usize>,
+        selected_row: Cell<Option<usize>>,
+        select_row_action: RefCell<Option<SelectRowCallback>>,
+
+        // The cells in view, by row.
+        visible_cells: RefCell<BTreeMap<usize, TableViewCell>>,
+
+        // Cells scrolled out of view, by reuse identifier.
+        reusable_cells: ReusePool<TableViewCell>
+    }
+
+    impl Self {
+        pub fn new(frame: Rectangle<i32, u32>) -> TableView {
+            let table_view = Self::new_all(
+                frame.clone(),
+                RefCell::new(None),
+                Cell::new(DEFAULT_ROW_HEIGHT),
+                Cell::new(0),
+                Cell::new(None),
+                RefCell::new(None),
+                RefCell::new(BTreeMap::new()),
+                ReusePool::new()
+            );
+            table_view.view.set_background_color(Color::semantic(SemanticColor::Background));
+
+            let size = frame.size;
+            let scroll_view = ScrollView::new(Rectangle::new(0, 0, size.width, size.height));
+            let cells_view = View::new(Rectangle::new(0, 0, size.width, 0));
+            cells_view.set_background_color(Color::clear());
+            scroll_view.set_content_view(cells_view);
+            table_view.view.add_subview(scroll_view.view.clone());
+
+            let weak_table_view = table_view.view.downgrade();
+            scroll_view.on_scroll(move |_| {
+                if let Some(view) = weak_table_view.upgrade() {
+                    TableView::from_view(view).layout_visible_rows();
+                }
+            });
+
+            table_view
+        }
+
+        /// The scroll view the rows scroll in.
+        pub fn scroll_view(&self) -> ScrollView {
+            let view = self.view.subviews().first().unwrap().clone();
+            ScrollView::from_view(view)
+        }
+
+        /// The view the cells are added to.
+        fn cells_view(&self) -> View {
+            self.scroll_view().content_view().unwrap()
+        }
+
+        /// Sets where the rows come from, and loads them.
+        pub fn set_data_source(&self, data_source: Rc<dyn TableViewDataSource>) {
+            self.behavior().data_source.replace(Some(data_source));
+            self.reload_data();
+        }
+
+        pub fn row_height(&self) -> u32 {
+            self.behavior().row_height.get()
+        }
+
+        /// Sets the height of every row, reloading the rows.
+        pub fn set_row_height(&self, row_height: u32) {
+            self.behavior().row_height.set(row_height);
+            self.reload_data();
+        }
+
+        /// How many rows there were when the data was last loaded.
+        pub fn number_of_rows(&self) -> usize {
+            self.behavior().number_of_rows.get()
+        }
+
+        /// Asks the data source for the rows again, such as after they've
+        /// changed. Every cell in view is made reusable and asked for
+        /// again.
+        pub fn reload_data(&self) {
+            let cells: Vec<TableViewCell> = self.behavior().visible_cells.replace(BTreeMap::new()).into_values().collect();
+            for cell in cells {
+                self.enqueue_reusable_cell(cell);
+            }
+
+            let data_source = self.behavior().data_source.borrow().clone();
+            let number_of_rows = data_source.map_or(0, |data_source| data_source.number_of_rows(self));
+
+            {
+                let behavior = self.behavior();
+                behavior.number_of_rows.set(number_of_rows);
+                if behavior.selected_row.get().is_some_and(|row| row >= number_of_rows) {
+                    behavior.selected_row.set(None);
+                }
+            }
+
+            let width = self.view.frame().size.width;
+            let height = number_of_rows as u32 * self.row_height();
+            self.scroll_view().set_content_size(Size::new(width, height));
+            self.layout_visible_rows();
+        }
+
+        /// A cell with `reuse_identifier` that's scrolled out of view and
+        /// can be used for another row, if there is one.
+        pub fn dequeue_reusable_cell(&self, reuse_identifier: &str) -> Option<TableViewCell> {
+            self.behavior().reusable_cells.dequeue(reuse_identifier)
+        }
+
+        fn enqueue_reusable_cell(&self, cell: TableViewCell) {
+            self.behavior().reusable_cells.enqueue(cell);
+        }
+
+        /// The rows at least partly in view.
+        fn visible_row_range(&self) -> Range<usize> {
+            let row_height = self.row_height() as i32;
+            if row_height == 0 {
+                return 0..0;
+            }
+
+            let top = self.scroll_view().content_offset().y.max(0);
+            let bottom = top + self.view.frame().size.height as i32;
+
+            let first = (top / row_height) as usize;
+            let last = ((bottom + row_height - 1) / row_height) as usize;
+            first.min(self.number_of_rows())..last.min(self.number_of_rows())
+        }
+
+        /// Gives each row in view a cell, reusing the cells of rows that
+        /// have scrolled out of view.
+        fn layout_visible_rows(&self) {
+            let range = self.visible_row_range();
+
+            let hidden_rows: Vec<usize> = self.behavior().visible_cells.borrow()
+                .keys()
+                .filter(|row| !range.contains(row))
+                .cloned()
+                .collect();
+            for row in hidden_rows {
+                let cell = self.behavior().visible_cells.borrow_mut().remove(&row).unwrap();
+                self.enqueue_reusable_cell(cell);
+            }
+
+            let data_source = match self.behavior().data_source.borrow().clone() {
+                Some(data_source) => data_source,
+                None => return
+            };
+
+            let width = self.view.frame().size.width;
+            let row_height = self.row_height();
+            let selected_row = self.selected_row();
+            let cells_view = self.cells_view();
+
+            for row in range {
+                if self.behavior().visible_cells.borrow().contains_key(&row) {
+                    continue;
+                }
+
+                let cell = data_source.cell_for_row(self, row);
+                cell.view.set_frame(Rectangle::new(0, (row as u32 * row_height) as i32, width, row_height));
+                cell.set_selected(selected_row == Some(row));
+                cell.set_table_view(self.view.downgrade());
+                cells_view.add_subview(cell.view.clone());
+
+                self.behavior().visible_cells.borrow_mut().insert(row, cell);
+            }
+        }
+
+        /// The cell showing `row`, if it's in view.
+        pub fn cell_for_row(&self, row: usize) -> Option<TableViewCell> {
+            self.behavior().visible_cells.borrow().get(&row).cloned()
+        }
+
+        /// The rows in view, in order.
+        pub fn visible_rows(&self) -> Vec<usize> {
+            self.behavior().visible_cells.borrow().keys().cloned().collect()
+        }
+
+        /// The row at `point`, in the table view's coordinate space.
+        pub fn row_at_point(&self, point: Point<i32>) -> Option<usize> {
+            let row_height = self.row_height() as i32;
+            let y = point.y + self.scroll_view().content_offset().y;
+            if row_height == 0 || y < 0 {
+                return None;
+            }
+
+            let row = (y / row_height) as usize;
+            if row < self.number_of_rows() {
+                Some(row)
+            } else {
+                None
+            }
+        }
+
+        /// Scrolls as little as needed for all of `row` to be in view.
+        pub fn scroll_to_row(&self, row: usize) {
+            let row_height = self.row_height();
+            let width = self.view.frame().size.width;
+            self.scroll_view().scroll_rectangle_to_visible(
+                Rectangle::new(0, (row as u32 * row_height) as i32, width, row_height)
+            );
+        }
+
+        pub fn selected_row(&self) -> Option<usize> {
+            self.behavior().selected_row.get()
+        }
+
+        /// Selects `row`, highlighting its cell, or clears the selection.
+        /// This doesn't call the `on_select_row` callback, which is only
+        /// called when the user chooses a row.
+        pub fn select_row(&self, row: Option<usize>) {
+            let row = row.filter(|row| *row < self.number_of_rows());
+            self.behavior().selected_row.set(row);
+
+            for (visible_row, cell) in self.behavior().visible_cells.borrow().iter() {
+                cell.set_selected(row == Some(*visible_row));
+            }
+        }
+
+        /// Calls `action` with the row when a row is tapped, or chosen with
+        /// the arrow keys, after it's selected.
+        pub fn on_select_row(&self, action: impl Fn(&TableView, usize) + 'static) {
+            self.behavior().select_row_action.replace(Some(Rc::new(action)));
+        }
+
+        /// Selects the row of the tapped cell.
+        pub(crate) fn did_tap_cell(&self, cell_view: &View) {
+            let row = self.behavior().visible_cells.borrow()
+                .iter()
+                .find(|(_, cell)| cell.view == *cell_view)
+                .map(|(row, _)| *row);
+
+            if let Some(row) = row {
+                self.select_row(Some(row));
+                self.did_choose_row(row);
+            }
+        }
+
+        /// Moves the selection for the arrow key, Home or End key
+        /// `key_code`, scrolling the newly selected row into view. Returns
+        /// `false` for other keys.
+        pub(crate) fn move_selection(&self, key_code: KeyCode) -> bool {
+            let number_of_rows = self.number_of_rows();
+            if number_of_rows == 0 {
+                return matches!(key_code, KeyCode::Up | KeyCode::Down | KeyCode::Home | KeyCode::End);
+            }
+
+            let last_row = number_of_rows - 1;
+            let row = match (key_code, self.selected_row()) {
+                (KeyCode::Down, Some(row)) => (row + 1).min(last_row),
+                (KeyCode::Down, None) | (KeyCode::Home, _) => 0,
+                (KeyCode::Up, Some(row)) => row.saturating_sub(1),
+                (KeyCode::Up, None) | (KeyCode::End, _) => last_row,
+                _ => return false
+            };
+
+            if self.selected_row() != Some(row) {
+                self.select_row(Some(row));
+                self.scroll_to_row(row);
+                self.did_choose_row(row);
+            }
+            true
+        }
+
+        fn did_choose_row(&self, row: usize) {
+            let action = self.behavior().select_row_action.borrow().clone();
+            if let Some(action) = action {
+                action(self, row);
+            }
+        }
+    }
+
+    impl Behavior {
+        /// Table views join the Tab order when their window has full
+        /// keyboard access.
+        fn is_control(&self) -> bool {
+            true
+        }
+
+        fn press_began(&self, press: &Press) {
+            let key = press.key();
+            if key.has_shortcut_modifier() || !self.view_type().move_selection(key.key_code()) {
+                self.super_behavior().unwrap().press_began(press);
+            }
+        }
+
+        fn press_repeated(&self, press: &Press) {
+            let key_code = press.key().key_code();
+            if matches!(key_code, KeyCode::Up | KeyCode::Down) {
+                self.view_type().move_selection(key_code);
+            }
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NumberDataSource {
+        number_of_rows: usize,
+        cells_created: Cell<usize>
+    }
+
+    impl TableViewDataSource for NumberDataSource {
+        fn number_of_rows(&self, _table_view: &TableView) -> usize {
+            self.number_of_rows
+        }
+
+        fn cell_for_row(&self, table_view: &TableView, row: usize) -> TableViewCell {
+            let cell = table_view.dequeue_reusable_cell("number").unwrap_or_else(|| {
+                self.cells_created.set(self.cells_created.get() + 1);
+                TableViewCell::new("number")
+            });
+            cell.text_label().set_text(row.to_string());
+            cell
+        }
+    }
+
+    fn table_view_with_rows(number_of_rows: usize) -> (TableView, Rc<NumberDataSource>) {
+        let table_view = TableView::new(Rectangle::new(0, 0, 200, 100));
+        table_view.set_row_height(20);
+
+        let data_source = Rc::new(NumberDataSource { number_of_rows, cells_created: Cell::new(0) });
+        table_view.set_data_source(data_source.clone());
+        (table_view, data_source)
+    }
+
+    #[test]
+    fn test_only_visible_rows_have_cells() {
+        let (table_view, data_source) = table_view_with_rows(10_000);
+
+        assert_eq!(table_view.number_of_rows(), 10_000);
+        assert_eq!(table_view.scroll_view().content_size(), Size::new(200, 200_000));
+        assert_eq!(table_view.visible_rows(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(table_view.cell_for_row(3).unwrap().text_label().copy_text(), "3");
+        assert_eq!(table_view.cell_for_row(3).unwrap().view.frame(), Rectangle::new(0, 60, 200, 20));
+
+        // Scrolling reuses the cells scrolled out of view.
+        table_view.scroll_view().set_content_offset(Point::new(0, 5_010));
+        assert_eq!(table_view.visible_rows(), vec![250, 251, 252, 253, 254, 255]);
+        assert_eq!(table_view.cell_for_row(250).unwrap().text_label().copy_text(), "250");
+
+        for y in 0..100 {
+            table_view.scroll_view().set_content_offset(Point::new(0, y * 7));
+        }
+        assert_eq!(data_source.cells_created.get(), 6);
+        assert_eq!(table_view.cells_view().subviews().len(), table_view.visible_rows().len());
+    }
+
+    #[test]
+    fn test_row_at_point() {
+        let (table_view, _) = table_view_with_rows(20);
+        assert_eq!(table_view.row_at_point(Point::new(10, 45)), Some(2));
+
+        table_view.scroll_view().set_content_offset(Point::new(0, 100));
+        assert_eq!(table_view.row_at_point(Point::new(10, 45)), Some(7));
+        assert_eq!(table_view.row_at_point(Point::new(10, 1000)), None);
+
+        table_view.scroll_to_row(1);
+        assert_eq!(table_view.scroll_view().content_offset(), Point::new(0, 20));
+    }
+
+    #[test]
+    fn test_selection() {
+        let (table_view, _) = table_view_with_rows(20);
+
+        let tapped = Rc::new(Cell::new(None));
+        let tapped_clone = tapped.clone();
+        table_view.on_select_row(move |_, row| tapped_clone.set(Some(row)));
+
+        let cell = table_view.cell_for_row(2).unwrap();
+        table_view.did_tap_cell(&cell.view);
+        assert_eq!(tapped.get(), Some(2));
+        assert_eq!(table_view.selected_row(), Some(2));
+        assert!(cell.is_selected());
+
+        // The highlight goes with the row rather than the cell.
+        table_view.scroll_view().set_content_offset(Point::new(0, 200));
+        assert!(table_view.visible_rows().iter().all(|row| !table_view.cell_for_row(*row).unwrap().is_selected()));
+        table_view.scroll_view().set_content_offset(Point::new(0, 0));
+        assert!(table_view.cell_for_row(2).unwrap().is_selected());
+
+        table_view.select_row(Some(3));
+        assert!(!table_view.cell_for_row(2).unwrap().is_selected());
+        assert!(table_view.cell_for_row(3).unwrap().is_selected());
+        assert_eq!(tapped.get(), Some(2));
+
+        table_view.select_row(Some(50));
+        assert_eq!(table_view.selected_row(), None);
+    }
+
+    #[test]
+    fn test_move_selection() {
+        let (table_view, _) = table_view_with_rows(20);
+
+        let chosen = Rc::new(RefCell::new(Vec::new()));
+        let chosen_clone = chosen.clone();
+        table_view.on_select_row(move |_, row| chosen_clone.borrow_mut().push(row));
+
+        assert!(table_view.move_selection(KeyCode::Down));
+        assert_eq!(table_view.selected_row(), Some(0));
+        assert!(table_view.move_selection(KeyCode::Up));
+        assert_eq!(table_view.selected_row(), Some(0));
+
+        // Moving past the rows in view scrolls to the selected row.
+        assert!(table_view.move_selection(KeyCode::End));
+        assert_eq!(table_view.selected_row(), Some(19));
+        assert_eq!(table_view.scroll_view().content_offset(), Point::new(0, 300));
+        assert!(table_view.cell_for_row(19).unwrap().is_selected());
+        assert!(table_view.move_selection(KeyCode::Up));
+        assert!(table_view.move_selection(KeyCode::Home));
+        assert_eq!(table_view.scroll_view().content_offset(), Point::new(0, 0));
+
+        assert!(!table_view.move_selection(KeyCode::Left));
+        assert_eq!(*chosen.borrow(), vec![0, 19, 18, 0]);
+    }
+}
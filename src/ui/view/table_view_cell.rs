@@ -0,0 +1,143 @@
+use crate::graphics::Rectangle;
+use crate::ui::view::{AutoresizingMask, DefaultBehavior, Label, TableView, View, WeakView};
+use crate::ui::view::reuse_pool::ReusableCell;
+use crate::ui::{Color, SemanticColor};
+use crate::ui::gesture::TapRecognizer;
+use crate::text::VerticalAlignment;
+use crate::macros::*;
+use std::cell::{Cell, RefCell};
+
+/// The space between the edges of a cell and its text label.
+const LABEL_PADDING: u32 = 12;
+
+// A row of a `TableView`.
+//
+// A cell has a text label filling it, and is highlighted while its row is
+// selected. Cells with the same reuse identifier are interchangeable: once
+// a cell scrolls out of view it's kept by the table view, and handed out
+// again by `TableView::dequeue_reusable_cell` for another row.
+custom_view!(
+    TableViewCell subclasses DefaultBehavior
+
+    struct TableViewCellBehavior {
+        reuse_identifier: String,
+        is_selected: Cell<bool>,
+        background_color: RefCell<Color>,
+        selected_background_color: RefCell<Color>,
+
+        // The table view showing the cell, told when the cell is tapped.
+        table_view: RefCell<WeakView>
+    }
+
+    impl Self {
+        pub fn new(reuse_identifier: &str) -> TableViewCell {
+            let frame = Rectangle::new(0, 0, 320, 44);
+            let cell = Self::new_all(
+                frame.clone(),
+                reuse_identifier.to_string(),
+                Cell::new(false),
+                RefCell::new(Color::semantic(SemanticColor::Background)),
+                RefCell::new(Color::new(200, 222, 250, 255)),
+                RefCell::new(WeakView::none())
+            );
+            cell.view.set_background_color(Color::semantic(SemanticColor::Background));
+
+            let label = Label::new(
+                Rectangle::new(
+                    LABEL_PADDING as i32,
+                    0,
+                    frame.size.width - LABEL_PADDING * 2,
+                    frame.size.height
+                ),
+                String::new()
+            );
+            label.set_vertical_alignment(VerticalAlignment::Middle);
+            label.view.set_user_interaction_enabled(false);
+            label.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth, AutoresizingMask::FlexibleHeight]);
+            cell.view.add_subview(label.view);
+
+            // A touch that moves is scrolling the table view, whose pan
+            // makes the tap fail.
+            let weak_view = cell.view.downgrade();
+            cell.view.add_gesture_recognizer(Box::new(TapRecognizer::new(move |_tap_recognizer| {
+                if let Some(view) = weak_view.upgrade() {
+                    TableViewCell::from_view(view).did_tap();
+                }
+            })));
+
+            cell
+        }
+
+        /// The label showing the cell's text.
+        pub fn text_label(&self) -> Label {
+            let view = self.view.subviews().first().unwrap().clone();
+            Label::from_view(view)
+        }
+
+        /// Identifies which cells can be reused for each other.
+        pub fn reuse_identifier(&self) -> String {
+            self.behavior().reuse_identifier.clone()
+        }
+
+        pub fn is_selected(&self) -> bool {
+            self.behavior().is_selected.get()
+        }
+
+        /// Highlights the cell with its selected background color, or
+        /// returns it to its background color. This is done by the table
+        /// view as its selected row changes.
+        pub fn set_selected(&self, is_selected: bool) {
+            let behavior = self.behavior();
+            behavior.is_selected.set(is_selected);
+
+            let color = if is_selected {
+                behavior.selected_background_color.borrow().clone()
+            } else {
+                behavior.background_color.borrow().clone()
+            };
+            self.view.set_background_color(color);
+        }
+
+        /// Sets the background color used while the cell isn't selected.
+        pub fn set_background_color(&self, color: Color) {
+            self.behavior().background_color.replace(color);
+            self.set_selected(self.is_selected());
+        }
+
+        /// Sets the background color used while the cell is selected.
+        pub fn set_selected_background_color(&self, color: Color) {
+            self.behavior().selected_background_color.replace(color);
+            self.set_selected(self.is_selected());
+        }
+
+        pub(crate) fn set_table_view(&self, table_view: WeakView) {
+            self.behavior().table_view.replace(table_view);
+        }
+
+        /// Readies the cell to be shown for another row.
+        pub(crate) fn prepare_for_reuse(&self) {
+            self.set_selected(false);
+        }
+
+        fn did_tap(&self) {
+            let table_view = self.behavior().table_view.borrow().upgrade();
+            if let Some(table_view) = table_view {
+                TableView::from_view(table_view).did_tap_cell(&self.view);
+            }
+        }
+    }
+);
+
+impl ReusableCell for TableViewCell {
+    fn view(&self) -> &View {
+        &self.view
+    }
+
+    fn reuse_identifier(&self) -> String {
+        TableViewCell::reuse_identifier(self)
+    }
+
+    fn prepare_for_reuse(&self) {
+        TableViewCell::prepare_for_reuse(self);
+    }
+}
@@ -1,28 +1,45 @@
-use crate::graphics::{Rectangle, Size, Point};
+use crate::graphics::{EdgeInsets, Rectangle, Size, Point};
 use crate::ui::view::{View, WeakView};
 use crate::ui::view::DefaultBehavior;
+use crate::ui::accessibility::{AccessibilityAction, AccessibilityRole};
+use crate::ui::formatter::Formatter;
+use crate::ui::spell_checker;
+use crate::ui::localization;
+use crate::ui::menu::{Menu, MenuItem};
 use crate::ui::Color;
 use crate::macros::*;
 use crate::ui::view::Label;
 use crate::ui::run_loop::RunLoop;
-use crate::ui::timer::Timer;
+use crate::ui::timer::{Timer, TimerHandle};
+use crate::ui::clock;
 use crate::ui::touch::Touch;
 use crate::ui::press::Press;
-use crate::ui::key::{KeyCode, ModifierFlag};
+use crate::ui::command::Command;
+use crate::ui::key::{Key, KeyCode, ModifierFlag};
 use std::cell::RefCell;
+use std::rc::Rc;
 use std::time::Duration;
 use std::cell::Cell;
 use std::ops::Range;
 use crate::text::word_boundary;
 use std::time::Instant;
 use crate::text::text::Text;
-use crate::platform::clipboard;
+use crate::platform::pasteboard::Pasteboard;
+use crate::platform::cursor::Cursor;
 use crate::ui::history::text_field::text_insertion::TextInsertion;
 use crate::ui::history::text_field::text_backspace::TextBackspace;
+use crate::ui::history::text_field::text_cut::TextCut;
+use crate::ui::history::text_field::text_replace::TextReplace;
+use crate::ui::history::text_field::carat_change::CaratChange;
 use crate::platform::history::Action;
-use crate::platform::history::History;
+use crate::platform::history::UndoManager;
 use crate::ui::history::text_field::carat_snapshot::CaratSnapshot;
 use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The space, in points, between each edge of a text field and its text,
+/// unless set otherwise.
+const DEFAULT_CONTENT_INSET: i32 = 8;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CursorMovement {
@@ -34,7 +51,12 @@ pub enum CursorMovement {
 pub(crate) struct Carat {
     view: WeakView,
     character_index: Cell<usize>,
-    selection: Option<Selection>
+    selection: Option<Selection>,
+
+    // The horizontal position (in the label's native resolution) the carat
+    // moves towards when moving up and down, so that moving through a short
+    // line doesn't lose the column. Cleared by any other movement or edit.
+    preferred_x: Cell<Option<i32>>
 }
 
 impl Carat {
@@ -56,6 +78,21 @@ impl Drop for Carat {
     }
 }
 
+/// What replacing part of a text field's text did, see
+/// `TextField::replace_graphemes`.
+pub(crate) struct GraphemeEdit {
+    /// The index just after the replacement, where a carat typing it goes.
+    pub end: usize,
+
+    /// How many graphemes longer the text got, or shorter if negative.
+    pub change: i32,
+
+    /// The range of the text to replace with `replaced_text` to undo the
+    /// edit.
+    pub undo_range: Range<usize>,
+    pub replaced_text: String
+}
+
 pub(crate) struct Selection {
     start: usize,
     end: usize,
@@ -72,6 +109,26 @@ impl Drop for Selection {
     }
 }
 
+/// The closures a `TextField` calls as it's edited. See `TextField::on_change`
+/// and friends.
+#[derive(Default)]
+pub(crate) struct EditingCallbacks {
+    change: Option<EditingCallback>,
+    should_change_text_in_range: Option<ShouldChangeCallback>,
+    begin_editing: Option<EditingCallback>,
+    end_editing: Option<EditingCallback>,
+    return_pressed: Option<EditingCallback>,
+    input_rejected: Option<InputRejectedCallback>,
+
+    // Used by a view containing the text field, such as a `TextView`, to
+    // resize and scroll after edits and carat movements.
+    layout: Option<EditingCallback>
+}
+
+type EditingCallback = Box<dyn Fn(&TextField)>;
+type ShouldChangeCallback = Box<dyn Fn(&TextField, Range<usize>, &str) -> bool>;
+type InputRejectedCallback = Box<dyn Fn(&TextField, &str)>;
+
 impl Drop for TextFieldBehavior {
     fn drop(&mut self) {
         let carat_animation_timer = self.carat_animation_timer.borrow();
@@ -102,6 +159,11 @@ custom_view!(
         // is made from where the touch started to where the finger is now.
         touch_began_at_index: Cell<usize>,
 
+        // Whether a touch that began in the text field is being dragged to
+        // extend a selection. Double and triple clicks, which select words
+        // and lines, don't track the drag.
+        tracking_touch: Cell<bool>,
+
         // A timer responsible for animating the carats.
         carat_animation_timer: RefCell<Option<Timer>>,
 
@@ -110,22 +172,54 @@ custom_view!(
         last_click: Cell<Instant>,
         click_count: Cell<u8>,
 
-        history: RefCell<History>,
+        // Each text field has its own undo manager, so that undoing in one
+        // doesn't affect another.
+        history: Rc<RefCell<UndoManager>>,
+
+        // Where text is copied to and pasted from; the general pasteboard
+        // unless set otherwise.
+        pasteboard: RefCell<Pasteboard>,
+
+        // The most graphemes typing and pasting may make the text, if
+        // limited.
+        max_length: Cell<Option<usize>>,
+
+        // Which characters may be typed or pasted, if limited.
+        allowed_characters: Cell<Option<fn(char) -> bool>>,
+
+        // Checks edits as they're made, and tidies up the text when editing
+        // ends, if set.
+        formatter: RefCell<Option<Rc<dyn Formatter>>>,
+
+        // Whether misspelled words are underlined, when the application has
+        // a spell checker.
+        checks_spelling: Cell<bool>,
+
+        // The spell check waiting for typing to pause, if there is one.
+        spell_check_timer: RefCell<Option<TimerHandle>>,
 
-        text_change: RefCell<Option<Box<dyn Fn(&TextField) -> ()>>>
+        // Counts edits, so a spell check finishing after the text was
+        // edited again knows its result is stale.
+        spell_check_generation: Cell<u64>,
+
+        // Whether a spell check is running in the background. Only one runs
+        // at a time; when it finishes, it starts the next if one is due.
+        is_checking_spelling: Cell<bool>,
+
+        callbacks: RefCell<EditingCallbacks>,
+
+        // The label showing the text, a subview of the text field.
+        label: Label,
+
+        // The space between the text field's edges and its text.
+        content_insets: Cell<EdgeInsets>
     }
 
     impl Self {
         pub fn new(frame: Rectangle<i32, u32>, text: String) -> TextField {
-            let label_padding = 8;
-            let label_frame = Rectangle::new(
-                label_padding as i32,
-                label_padding as i32,
-                frame.width() - (label_padding * 2),
-                frame.height() - (label_padding * 2)
-            );
+            let content_insets = EdgeInsets::uniform(DEFAULT_CONTENT_INSET);
+            let label_frame = Rectangle::new(0, 0, frame.width(), frame.height()).inset_by_edges(&content_insets);
             let label = Label::new(label_frame, text);
-            label.view.set_tag(1);
             label.view.set_user_interaction_enabled(false);
 
             let carats = RefCell::new(Vec::new());
@@ -135,12 +229,23 @@ custom_view!(
                 Cell::new(0),
                 Cell::new(0),
                 Cell::new(0),
+                Cell::new(false),
                 RefCell::new(None),
                 Cell::new(false),
-                Cell::new(Instant::now()),
+                Cell::new(clock::now()),
+                Cell::new(0),
+                Rc::new(RefCell::new(UndoManager::new())),
+                RefCell::new(Pasteboard::general()),
+                Cell::new(None),
+                Cell::new(None),
+                RefCell::new(None),
+                Cell::new(true),
+                RefCell::new(None),
                 Cell::new(0),
-                RefCell::new(History::new()),
-                RefCell::new(None)
+                Cell::new(false),
+                RefCell::new(EditingCallbacks::default()),
+                label.clone(),
+                Cell::new(content_insets)
             );
 
             text_field.view.add_subview(label.view);
@@ -162,30 +267,94 @@ custom_view!(
             text_field.clone()
         }
 
-        pub fn on_text_change(&self, action: impl Fn(&TextField) -> () + 'static) {
-            let behavior = self.behavior();
-            behavior.text_change.replace(Some(Box::new(action)));
+        /// Sets a closure called after each edit of the text, including
+        /// undoing and redoing.
+        pub fn on_change(&self, action: impl Fn(&TextField) + 'static) {
+            self.behavior().callbacks.borrow_mut().change = Some(Box::new(action));
+        }
+
+        /// The same as `on_change`.
+        pub fn on_text_change(&self, action: impl Fn(&TextField) + 'static) {
+            self.on_change(action);
+        }
+
+        /// Sets a closure asked before each edit whether the grapheme `range`
+        /// of the text should be replaced with the replacement string. An
+        /// insertion has an empty range, and a deletion an empty
+        /// replacement. When editing with multiple carats it's asked once
+        /// per carat, and if it returns `false` for any of them the edit
+        /// isn't made.
+        pub fn should_change_text_in_range(&self, action: impl Fn(&TextField, Range<usize>, &str) -> bool + 'static) {
+            self.behavior().callbacks.borrow_mut().should_change_text_in_range = Some(Box::new(action));
+        }
+
+        /// Sets a closure called when the text field becomes the first
+        /// responder.
+        pub fn on_begin_editing(&self, action: impl Fn(&TextField) + 'static) {
+            self.behavior().callbacks.borrow_mut().begin_editing = Some(Box::new(action));
+        }
+
+        /// Sets a closure called when the text field stops being the first
+        /// responder.
+        pub fn on_end_editing(&self, action: impl Fn(&TextField) + 'static) {
+            self.behavior().callbacks.borrow_mut().end_editing = Some(Box::new(action));
+        }
+
+        /// Sets a closure called with the input that was typed or pasted but
+        /// left out, because it was longer than the maximum length or had
+        /// characters that aren't allowed.
+        pub fn on_input_rejected(&self, action: impl Fn(&TextField, &str) + 'static) {
+            self.behavior().callbacks.borrow_mut().input_rejected = Some(Box::new(action));
+        }
+
+        /// Sets a closure called when Return is pressed. While set, Return
+        /// calls it instead of inserting a newline.
+        pub fn on_return(&self, action: impl Fn(&TextField) + 'static) {
+            self.behavior().callbacks.borrow_mut().return_pressed = Some(Box::new(action));
+        }
+
+        /// The space between the text field's edges and its text. Defaults
+        /// to 8 points on each side.
+        pub fn content_insets(&self) -> EdgeInsets {
+            self.behavior().content_insets.get()
+        }
+
+        pub fn set_content_insets(&self, content_insets: EdgeInsets) {
+            self.behavior().content_insets.set(content_insets);
+            self.layout_label();
+        }
+
+        /// Fits the label inside the content insets.
+        fn layout_label(&self) {
+            let size = self.view.frame().size;
+            let label_frame = Rectangle::new(0, 0, size.width, size.height).inset_by_edges(&self.content_insets());
+            self.label().view.set_frame(label_frame);
+            self.view.set_needs_display();
         }
 
         pub fn label(&self) -> Label {
-            let view = self.view.view_with_tag(1).unwrap();
-            Label::from_view(view)
+            self.behavior().label.clone()
         }
 
+        /// Hit tests `touch` against the label, returning the index where a
+        /// carat should be placed for it. Touches that haven't been
+        /// delivered through a window are treated as being in window
+        /// coordinates.
         fn touch_to_index(&self, touch: &Touch) -> usize {
-            let window = touch.window().unwrap();
             let label = self.label();
-            let position = window.view.convert_point_to(&touch.position(), &label.view);
-            let label_behavior = label.behavior();
-            let rendering = label_behavior.rendering();
-            let render_scale = rendering.render_scale();
-
-            let position = Point {
-                x: (position.x as f32 * render_scale).round() as i32,
-                y: (position.y as f32 * render_scale).round() as i32
+            let position = match touch.window() {
+                Some(window) => window.view.convert_point_to(&touch.position(), &label.view),
+                None => {
+                    let position = touch.position();
+                    let label_location = label.view.get_location_in_window();
+                    Point {
+                        x: position.x - label_location.x,
+                        y: position.y - label_location.y
+                    }
+                }
             };
 
-            rendering.character_at_position(position)
+            label.carat_index_at_point(position)
         }
 
         fn select(&self, carat: &mut Carat, index_one: usize, index_two: usize) {
@@ -241,7 +410,8 @@ custom_view!(
                 let carat = Carat {
                     view: carat_view.downgrade(),
                     character_index: Cell::new(character_index),
-                    selection: None
+                    selection: None,
+                    preferred_x: Cell::new(None)
                 };
 
                 carats.push(carat);
@@ -272,6 +442,22 @@ custom_view!(
             }
         }
 
+        /// Whether the text field itself handles `command` now. Undo and
+        /// redo use the text field's own history, so they never reach
+        /// another view.
+        fn can_perform(&self, command: &Command) -> bool {
+            let history = self.behavior().history.clone();
+            let history = history.borrow();
+            match command {
+                Command::Undo => history.can_undo(),
+                Command::Redo => history.can_redo(),
+                Command::Cut | Command::Copy => !self.selected_text().is_empty(),
+                Command::Paste => true,
+                Command::SelectAll => self.label().text_len() > 0,
+                Command::Custom(_) => false
+            }
+        }
+
         fn select_all(&self) {
             self.remove_carats();
             let behavior = self.behavior();
@@ -378,6 +564,8 @@ custom_view!(
                     extra_movement_for_following_carat += distance;
                 }
 
+                carat.preferred_x.set(None);
+
                 if let Some(carat_view) = carat.view.upgrade() {
                     carat_view.set_hidden(false);
                     carat_view.set_needs_display();
@@ -388,6 +576,42 @@ custom_view!(
             deleted_text
         }
 
+        /// Replaces the grapheme `range` of the text with `replacement`.
+        ///
+        /// A replacement can join the graphemes beside it into one, such as
+        /// a combining accent typed after a letter, so the text doesn't
+        /// always grow by the replacement's own number of graphemes. The
+        /// change is measured instead, and undoing a replacement that joined
+        /// graphemes restores the graphemes beside it too.
+        pub(crate) fn replace_graphemes(&self, range: Range<usize>, replacement: &str) -> GraphemeEdit {
+            let label = self.label();
+            let length_before = label.text_len();
+            let range = range.start.min(length_before)..range.end.clamp(range.start.min(length_before), length_before);
+
+            // The graphemes either side, in case the replacement joins them.
+            let surrounding = range.start.saturating_sub(1)..(range.end + 1).min(length_before);
+            let surrounding_text = label.text()[surrounding.clone()].to_string();
+            let replaced_text = label.text()[range.clone()].to_string();
+
+            label.replace_text_in_range(range.clone(), replacement);
+
+            let length_after = label.text_len();
+            let change = length_after as i32 - length_before as i32;
+            let end = (range.end as i32 + change).clamp(range.start as i32, length_after as i32) as usize;
+
+            if change == Text::from(replacement).len() as i32 - range.len() as i32 {
+                GraphemeEdit { end, change, undo_range: range.start..end, replaced_text }
+            } else {
+                let surrounding_end = (surrounding.end as i32 + change).max(surrounding.start as i32) as usize;
+                GraphemeEdit {
+                    end,
+                    change,
+                    undo_range: surrounding.start..surrounding_end.min(length_after),
+                    replaced_text: surrounding_text
+                }
+            }
+        }
+
         /// Multi-carat operation.
         ///
         /// Inserts a str at each of the current carat positions. This will move
@@ -396,10 +620,10 @@ custom_view!(
         /// If any of the carats are currently selected, the inserted text will
         /// replace the selected text.
         ///
-        /// Returns the contents of any text that was replaced (one element
-        /// per carat).
-        pub(crate) fn insert_str(&self, text: &str) -> Vec<Option<String>> {
-            let mut result = Vec::new();
+        /// Returns the edits that undo it, one for each carat that changed
+        /// the text, to be made in reverse order.
+        pub(crate) fn insert_str(&self, text: &str) -> Vec<(Range<usize>, String)> {
+            let mut undo_edits = Vec::new();
 
             let view = &self.view;
             let text_field = TextField::from_view(view.clone());
@@ -428,25 +652,27 @@ custom_view!(
                         let selection_start = carat.selection.as_ref().unwrap().start as i32 + extra_movement_for_following_carat;
                         let selection_end = carat.selection.as_ref().unwrap().end as i32 + extra_movement_for_following_carat;
 
-                        text_field.select(carat, selection_start as usize, selection_end as usize);
+                        text_field.select(carat, selection_start.max(0) as usize, selection_end.max(0) as usize);
                     }
                 }
 
-                if let Some(selection) = &carat.selection {
-                    result.push(Some(label.text()[selection.start..selection.end].to_string()));
-                    label.replace_text_in_range(selection.start..selection.end, &text);
-                    extra_movement_for_following_carat -= (selection.end - selection.start) as i32;
-                    carat.character_index.set(selection.start + text.len());
-                } else {
-                    result.push(None);
-                    let index = carat.character_index.get();
-                    label.insert_text_at_index(index, text);
-                    carat.character_index.set(index + Text::from(text).len());
+                // Carat indices are grapheme indices, and the text may not
+                // grow by the inserted text's own graphemes, so the carat
+                // moves by how much the text actually changed.
+                let range = match &carat.selection {
+                    Some(selection) => selection.start..selection.end,
+                    None => carat.character_index.get()..carat.character_index.get()
+                };
+                let edit = self.replace_graphemes(range.clone(), text);
+                carat.character_index.set(edit.end);
+                if !(range.is_empty() && text.is_empty()) {
+                    undo_edits.push((edit.undo_range, edit.replaced_text));
                 }
 
                 carat.selection = None;
+                carat.preferred_x.set(None);
 
-                extra_movement_for_following_carat += text.len() as i32;
+                extra_movement_for_following_carat += edit.change;
 
                 if let Some(carat_view) = carat.view.upgrade() {
                     carat_view.set_hidden(false);
@@ -456,7 +682,7 @@ custom_view!(
             let behavior = self.behavior();
             behavior.delay_animation.set(true);
 
-            result
+            undo_edits
         }
 
         // TODO: is this still correct?
@@ -466,33 +692,35 @@ custom_view!(
         /// window. Or the line of text that the cursor is sized on have have
         /// changed size.
         fn position_cursors(&self) {
-            let label = self.label();
-            let label_behavior = label.behavior();
-
             let behavior = self.behavior();
             let carats = behavior.carats.borrow();
-            let rendering = label_behavior.rendering();
-
-            let render_scale = rendering.render_scale();
 
             for carat in carats.iter() {
-                let character_index = carat.character_index.get();
-                let label_origin = &self.label().view.frame().origin;
                 let carat_view = carat.view.upgrade().unwrap();
-                let cursor_rectangle = rendering.cursor_rectangle_for_character_at_index(character_index);
+                carat_view.set_frame(self.carat_rectangle(carat.character_index.get()));
+            }
+        }
 
-                let cursor_rectangle = Rectangle {
-                    origin: Point {
-                        x: (cursor_rectangle.origin.x as f32 / render_scale).round() as i32 + label_origin.x - 1,
-                        y: (cursor_rectangle.origin.y as f32 / render_scale).round() as i32 + label_origin.y
-                    },
-                    size: Size {
-                        width: 2,
-                        height: (cursor_rectangle.size.height as f32 / render_scale).round() as u32
-                    }
-                };
+        /// The frame of a carat before the character at `character_index`,
+        /// in the text field's coordinate space.
+        pub(crate) fn carat_rectangle(&self, character_index: usize) -> Rectangle<i32, u32> {
+            let label = self.label();
+            let label_behavior = label.behavior();
+            let rendering = label_behavior.rendering();
+            let render_scale = rendering.render_scale();
 
-                carat_view.set_frame(cursor_rectangle);
+            let label_origin = label.view.frame().origin;
+            let cursor_rectangle = rendering.cursor_rectangle_for_character_at_index(character_index);
+
+            Rectangle {
+                origin: Point {
+                    x: (cursor_rectangle.origin.x as f32 / render_scale).round() as i32 + label_origin.x - 1,
+                    y: (cursor_rectangle.origin.y as f32 / render_scale).round() as i32 + label_origin.y
+                },
+                size: Size {
+                    width: 2,
+                    height: (cursor_rectangle.size.height as f32 / render_scale).round() as u32
+                }
             }
         }
 
@@ -521,567 +749,1363 @@ custom_view!(
             result
         }
 
-        /// Called internally by `press_began` (when holding shift and pressing
-        /// a directional key) and `touches_moved`.
-        fn move_carat_selecting(&self, carat: &mut Carat, previous_character_index: usize, target_character_index: usize) {
-            let text_field = self;
+        /// The pasteboard used to copy, cut and paste text.
+        pub fn pasteboard(&self) -> Pasteboard {
+            self.behavior().pasteboard.borrow().clone()
+        }
 
-            if let Some(selection) = carat.selection.as_mut() {
-                let current_cursor = carat.character_index.get();
-                carat.character_index.set(target_character_index);
+        /// Sets the pasteboard used to copy, cut and paste text, such as a
+        /// named pasteboard to keep text private to the application.
+        pub fn set_pasteboard(&self, pasteboard: Pasteboard) {
+            self.behavior().pasteboard.replace(pasteboard);
+        }
 
-                let start_distance = (current_cursor as i32  - selection.start as i32).abs();
-                let end_distance = (current_cursor as i32 - selection.end as i32).abs();
-                if start_distance < end_distance {
-                    selection.start = target_character_index;
-                    text_field.position_selection(&carat.selection.as_ref().unwrap());
-                } else if end_distance < start_distance {
-                    selection.end = target_character_index;
-                    text_field.position_selection(&carat.selection.as_ref().unwrap());
-                } else {
-                    let start = previous_character_index;
+        /// The most graphemes the text can have, or `None` if it isn't
+        /// limited.
+        pub fn max_length(&self) -> Option<usize> {
+            self.behavior().max_length.get()
+        }
 
-                    text_field.select(carat, start, target_character_index);
-                    carat.character_index.set(target_character_index);
-                }
+        /// Limits typing and pasting to leave at most `max_length`
+        /// graphemes in the text. Input past the limit is left out. Text
+        /// that is already longer isn't shortened.
+        pub fn set_max_length(&self, max_length: usize) {
+            self.behavior().max_length.set(Some(max_length));
+        }
 
-            } else {
-                let start = previous_character_index;
+        /// Removes the limit on the length of the text.
+        pub fn clear_max_length(&self) {
+            self.behavior().max_length.set(None);
+        }
 
-                text_field.select(carat, start, target_character_index);
-                carat.character_index.set(target_character_index);
-            }
+        /// Limits typing and pasting to characters for which
+        /// `allowed_characters` returns `true`, such as
+        /// `|c| c.is_ascii_digit()` for a numeric field. Other characters
+        /// are left out of the input.
+        pub fn set_allowed_characters(&self, allowed_characters: fn(char) -> bool) {
+            self.behavior().allowed_characters.set(Some(allowed_characters));
         }
 
-        fn position_selection(&self, selection: &Selection) {
+        /// Allows any character to be typed or pasted.
+        pub fn clear_allowed_characters(&self) {
+            self.behavior().allowed_characters.set(None);
+        }
+
+        /// The formatter checking and tidying up the text, if any.
+        pub fn formatter(&self) -> Option<Rc<dyn Formatter>> {
+            self.behavior().formatter.borrow().clone()
+        }
+
+        /// Sets a formatter, such as a `NumberFormatter`, that edits have to
+        /// leave valid text for, and that tidies up the text when editing
+        /// ends. The text is tidied up straight away, if it's valid. Edits
+        /// the formatter rejects are reported to the `on_input_rejected`
+        /// closure.
+        pub fn set_formatter(&self, formatter: Option<Rc<dyn Formatter>>) {
+            self.behavior().formatter.replace(formatter);
+            self.apply_display_string();
+        }
+
+        /// Replaces the text with the formatter's display string for it, if
+        /// it has one, such as when editing ends.
+        fn apply_display_string(&self) {
             let label = self.label();
-            let label_behavior = label.behavior();
-            let rendering = label_behavior.rendering();
+            let display_string = self.formatter().and_then(|formatter| formatter.display_string(label.text().string()));
+            if let Some(display_string) = display_string {
+                self.replace_text_for_formatting(display_string);
+            }
+        }
 
-            let render_scale = rendering.render_scale();
+        /// Replaces the text with the formatter's editing string for it, as
+        /// editing begins.
+        fn apply_editing_string(&self) {
+            if let Some(formatter) = self.formatter() {
+                let label = self.label();
+                let editing_string = formatter.editing_string(label.text().string());
+                self.replace_text_for_formatting(editing_string);
+            }
+        }
 
-            for view in selection.views.borrow().iter() {
-                if let Some(view) = view.upgrade() {
-                    view.remove_from_superview();
-                }
+        /// Sets the text tidied up by the formatter. The edits before can't
+        /// be undone, as they were made to text that's no longer there.
+        fn replace_text_for_formatting(&self, text: String) {
+            let label = self.label();
+            if label.text().string() == text {
+                return;
             }
 
-            selection.views.borrow_mut().clear();
+            label.set_text(text);
+            self.restore_carat_snapshots(&vec![CaratSnapshot::new(label.text_len(), None)]);
+            self.behavior().history.borrow_mut().clear();
+            self.did_change_text();
+        }
 
-            let label_origin = &self.label().view.frame().origin;
+        /// Whether misspelled words are underlined and offered corrections
+        /// for in the context menu. Only if the application has a spell
+        /// checker, see `spell_checker::set_spell_checker`. Defaults to
+        /// `true`.
+        pub fn checks_spelling(&self) -> bool {
+            self.behavior().checks_spelling.get()
+        }
 
-            let mut character_index = selection.start;
-            let mut last_y = -9999999;
-            let mut current_view: View = View::new(Rectangle::new(0, 0, 1, 1));
-            loop {
-                if character_index >= selection.end {
-                    break;
-                }
+        pub fn set_checks_spelling(&self, checks_spelling: bool) {
+            self.behavior().checks_spelling.set(checks_spelling);
+            self.check_spelling();
+        }
 
-                let cursor_rectangle = rendering.cursor_rectangle_for_character_at_index(character_index);
-                let character_size = rendering.character_size_for_character_at_index(character_index);
+        /// Checks the text's spelling on a background thread once typing
+        /// pauses for `spell_checker::CHECK_DELAY`, underlining the
+        /// misspelled words once it's done, unless the text has changed by
+        /// then. Each call replaces the check that's waiting, if there is
+        /// one.
+        pub(crate) fn check_spelling(&self) {
+            let behavior = self.behavior();
+            behavior.spell_check_generation.set(behavior.spell_check_generation.get() + 1);
+            if let Some(timer) = behavior.spell_check_timer.take() {
+                timer.cancel();
+            }
 
-                if cursor_rectangle.origin.y != last_y {
-                    last_y = cursor_rectangle.origin.y;
-                    current_view = View::new(Rectangle::new(0, 0, 1, 1));
-                    current_view.set_user_interaction_enabled(false);
-                    self.view.add_subview(current_view.clone());
-                    current_view.set_frame(Rectangle {
-                        origin: Point {
-                            x: (cursor_rectangle.origin.x as f32 / render_scale).round() as i32 + label_origin.x,
-                            y: (cursor_rectangle.origin.y as f32 / render_scale).round() as i32 + label_origin.y
-                        },
-                        size: Size {
-                            width: 0,
-                            height: (cursor_rectangle.size.height as f32 / render_scale).round() as u32
-                        }
-                    });
-                    current_view.set_background_color(Color::new(226, 175, 10, 64));
+            if spell_checker::spell_checker().is_none() || !self.checks_spelling() {
+                self.label().set_misspelled_ranges(&[]);
+                return;
+            }
+
+            let weak_view = self.view.downgrade();
+            let timer = Timer::after(spell_checker::CHECK_DELAY, move || {
+                if let Some(view) = weak_view.upgrade() {
+                    TextField::from_view(view).start_spell_check();
                 }
+            });
+            behavior.spell_check_timer.replace(Some(timer));
+        }
 
-                let current_frame = current_view.frame();
+        /// Starts checking the text as it is now in the background, unless
+        /// a check is already running, which starts this one when it's done.
+        fn start_spell_check(&self) {
+            let behavior = self.behavior();
+            let checker = match spell_checker::spell_checker() {
+                Some(checker) if self.checks_spelling() => checker,
+                _ => return
+            };
+            if behavior.is_checking_spelling.get() {
+                return;
+            }
+            behavior.is_checking_spelling.set(true);
+
+            let generation = behavior.spell_check_generation.get();
+            let text = self.label().text().string().to_string();
+            let weak_view = self.view.downgrade();
+            spell_checker::check_in_background(checker, text, move |ranges| {
+                if let Some(view) = weak_view.upgrade() {
+                    TextField::from_view(view).spell_check_did_finish(generation, &ranges);
+                }
+            });
+        }
 
-                current_view.set_frame(Rectangle::new(
-                    current_frame.origin.x,
-                    current_frame.origin.y,
-                    current_frame.size.width + (character_size.width as f32 / render_scale).round() as u32,
-                    current_frame.size.height
-                ));
+        /// Underlines the misspelled words found by the check of the text
+        /// at `generation`, if it hasn't been edited since. Otherwise starts
+        /// checking the text as edited, if no check is waiting to.
+        fn spell_check_did_finish(&self, generation: u64, ranges: &[Range<usize>]) {
+            let behavior = self.behavior();
+            behavior.is_checking_spelling.set(false);
 
-                selection.views.borrow_mut().push(current_view.downgrade());
+            if behavior.spell_check_generation.get() == generation {
+                self.label().set_misspelled_ranges(ranges);
+                return;
+            }
 
-                character_index += 1;
+            let is_check_waiting = behavior.spell_check_timer.borrow().as_ref().is_some_and(TimerHandle::is_pending);
+            if !is_check_waiting {
+                self.start_spell_check();
             }
         }
 
-        /// If more than one cursor is in the same spot, only one should
-        /// survive.
-        fn consume_and_sort_cursors(&self) {
-            let behavior = self.behavior();
-            let mut carats = behavior.carats.borrow_mut();
+        /// The context menu for the misspelled word drawn at `point`, if
+        /// there is one: the spell checker's suggestions, which replace the
+        /// word when chosen, followed by the text field's own context menu.
+        fn spelling_menu_at(&self, point: &Point<i32>) -> Option<Menu> {
+            let checker = spell_checker::spell_checker()?;
+            let label = self.label();
+            let index = label.character_index_at_point(self.view.convert_point_to(point, &label.view))?;
+            let range = label.misspelled_range_at_index(index)?;
+
+            let word: String = label.text().string().graphemes(true).skip(range.start).take(range.len()).collect();
+            let mut items: Vec<MenuItem> = checker
+                .suggestions(&word)
+                .into_iter()
+                .take(spell_checker::MAX_SUGGESTIONS)
+                .map(|suggestion| {
+                    let weak_view = self.view.downgrade();
+                    let range = range.clone();
+                    let word = word.clone();
+                    MenuItem::new(&suggestion.clone(), move || {
+                        if let Some(view) = weak_view.upgrade() {
+                            TextField::from_view(view).replace_word(range.clone(), &word, &suggestion);
+                        }
+                    })
+                })
+                .collect();
 
-            // if we sort upfront, then any overlapping cursors / selections
-            // should be next to each other.
-            carats.sort_by(|a, b| a.character_index.get().cmp(&b.character_index.get()));
+            if items.is_empty() {
+                items.push(MenuItem::new(&localization::localized("No Guesses Found"), || {}).enabled(false));
+            }
 
-            // 1. search for intersecting, set the character index to be the same
-            for index in 0..(carats.len()) {
-                if index == 0 {
-                    continue;
-                }
+            if let Some(menu) = self.view.context_menu() {
+                items.push(MenuItem::separator());
+                items.extend(menu.items().iter().cloned());
+            }
 
-                let mut last_snapshot = carats[index - 1].snapshot();
-                let current_snapshot = carats[index].snapshot();
+            Some(Menu::new(&localization::localized("Spelling"), items))
+        }
 
-                if current_snapshot.selection_intersects(&last_snapshot) {
-                    // Replace the previous carat's selection with one that
-                    // encompasses both.
-                    {
-                        let last_carat = carats.get_mut(index - 1).unwrap();
-                        let merged_end = current_snapshot.selection().as_ref().unwrap().end;
-                        last_carat.selection = Some(Selection {
-                            start: last_snapshot.selection().as_ref().unwrap().start,
-                            end: merged_end,
-                            views: RefCell::new(Vec::new())
-                        });
+        /// Replaces `word` in `range` with `replacement`, if it's still
+        /// there. This can be undone.
+        fn replace_word(&self, range: Range<usize>, word: &str, replacement: &str) {
+            let label = self.label();
+            let current: String = label.text().string().graphemes(true).skip(range.start).take(range.len()).collect();
+            if current == word {
+                self.replace_text_in_ranges(vec![(range, replacement.to_string())]);
+            }
+        }
 
-                        // If the cursor wasn't on the left hand side of the
-                        // selection, then we need to move it to the new right.
-                        if last_snapshot.character_index() != last_snapshot.selection().as_ref().unwrap().start {
-                            last_carat.character_index.replace(merged_end);
-                            last_snapshot = last_carat.snapshot();
-                        }
+        /// Leaves out the parts of each replacement that aren't allowed or
+        /// would make the text longer than the maximum length, reporting
+        /// them to the `on_input_rejected` closure. The ranges must not
+        /// overlap.
+        fn constrain_input(&self, changes: &mut [(Range<usize>, String)]) {
+            let behavior = self.behavior();
+            let mut rejected = String::new();
+
+            if let Some(allowed_characters) = behavior.allowed_characters.get() {
+                for (_, replacement) in changes.iter_mut() {
+                    let (allowed, disallowed): (String, String) = replacement
+                        .chars()
+                        .partition(|character| allowed_characters(*character));
+                    rejected.push_str(&disallowed);
+                    *replacement = allowed;
+                }
+            }
 
-                        self.position_selection(&last_carat.selection.as_ref().unwrap());
-                    }
+            if let Some(max_length) = behavior.max_length.get() {
+                let replaced: usize = changes.iter().map(|(range, _)| range.len()).sum();
+                let mut remaining = max_length.saturating_sub(self.label().text_len().saturating_sub(replaced));
 
-                    // Set the current carat to matching character index, so it
-                    // will be removed.
-                    {
-                        let current_carat = carats.get_mut(index).unwrap();
-                        current_carat.character_index.set(last_snapshot.character_index());
+                for (_, replacement) in changes.iter_mut() {
+                    let length = Text::from(replacement).len();
+                    if length > remaining {
+                        let kept: String = replacement.graphemes(true).take(remaining).collect();
+                        rejected.push_str(&replacement[kept.len()..]);
+                        *replacement = kept;
                     }
+                    remaining -= Text::from(replacement).len();
                 }
             }
 
-            // 2. drain ones that are the same
-            let mut indexes = HashMap::new();
-            carats.retain(|carat| {
-                let character_index = carat.character_index.get();
-                if indexes.contains_key(&character_index) {
-                    return false;
-                } else {
-                    indexes.insert(character_index, true);
-                    return true;
+            if !rejected.is_empty() {
+                let callbacks = behavior.callbacks.borrow();
+                if let Some(input_rejected) = callbacks.input_rejected.as_ref() {
+                    input_rejected(self, &rejected);
                 }
-            });
+            }
         }
-    }
 
-    impl Behavior {
-        fn draw(&self) {
-            self.super_behavior().unwrap().draw();
-            let text_field = TextField::from_view(self.get_view().upgrade().unwrap());
-            text_field.position_cursors();
+        /// Copies the selected text to the pasteboard, one item per carat
+        /// with a selection. Does nothing if no text is selected.
+        pub fn copy(&self) {
+            let selected_text = self.selected_text();
+            if !selected_text.is_empty() {
+                self.pasteboard().set_strings(selected_text);
+            }
         }
 
-        fn touches_moved(&self, touches: &Vec<Touch>) {
-            let view = self.view.upgrade().unwrap();
-            let text_field = TextField::from_view(view.clone());
-
-            if let Some(last_cursor) = self.carats.borrow_mut().last_mut() {
-                let touched_character_index = text_field.touch_to_index(touches.first().unwrap());
+        /// Copies the selected text to the pasteboard and then removes it.
+        /// This can be undone. Does nothing if no text is selected.
+        pub fn cut(&self) {
+            let selected_text = self.selected_text();
+            if selected_text.is_empty() {
+                return;
+            }
 
-                let previous_character_index = self.touch_began_at_index.get();
-                text_field.move_carat_selecting(last_cursor, previous_character_index, touched_character_index);
+            if !self.should_change_text(&self.changes_at_carats("")) {
+                return;
             }
-        }
 
-        fn touches_began(&self, touches: &Vec<Touch>) {
-            let view = self.view.upgrade().unwrap();
-            let text_field = TextField::from_view(view.clone());
+            self.pasteboard().set_strings(selected_text);
 
-            let touched_character_index = text_field.touch_to_index(touches.first().unwrap());
+            let behavior = self.behavior();
+            let mut text_cut = TextCut::new(self.view.downgrade(), self.carat_snapshots());
+            text_cut.forward();
+            behavior.history.borrow_mut().push(Box::new(text_cut));
 
-            self.touch_began_at_index.set(touched_character_index);
+            self.did_change_text();
+        }
 
-            if self.holding_shift.get() > 0 {
-                let behavior = text_field.behavior();
-                for cursor in behavior.carats.borrow_mut().iter_mut() {
-                    let cursor_index = cursor.character_index.get();
+        /// Inserts the pasteboard's text at each carat, replacing any
+        /// selected text. If the pasteboard has one item for each carat,
+        /// such as after copying from the same carats, each carat gets its
+        /// own item. This can be undone.
+        pub fn paste(&self) {
+            let mut items = self.pasteboard().strings();
+            if items.is_empty() {
+                return;
+            }
 
-                    text_field.select(cursor, cursor_index, touched_character_index);
+            self.consume_and_sort_cursors();
+            let carat_count = self.behavior().carats.borrow().len();
+            if items.len() != carat_count {
+                items = vec![items.join("\n")];
+            }
 
-                    cursor.character_index.set(touched_character_index);
+            let carats = self.carat_snapshots();
+            let mut changes = TextReplace::replacements_for_carats(&items, &carats);
 
-                    // We only actually care to do this for one cursor; it's an
-                    // edge case that we don't want to handle if there's more
-                    // than one.
-                    return;
-                }
-            } else if self.holding_alternative.get() > 0 {
-                text_field.spawn_carat(touched_character_index);
+            // Pasting nothing but rejected input doesn't remove the
+            // selections.
+            self.constrain_input(&mut changes);
+            if changes.iter().all(|(_, replacement)| replacement.is_empty()) {
                 return;
             }
 
-            text_field.remove_carats();
-            text_field.spawn_carat(touched_character_index);
+            if !self.should_change_text(&changes) {
+                return;
+            }
 
+            let behavior = self.behavior();
+            let mut text_replace = TextReplace::new(self.view.downgrade(), changes, carats);
+            text_replace.forward();
+            behavior.history.borrow_mut().push(Box::new(text_replace));
 
-            if self.last_click.get().elapsed().as_millis() < 500 {
-                self.click_count.set(self.click_count.get() + 1);
+            self.did_change_text();
+        }
 
-                let label = text_field.label();
-                let text = label.text();
+        /// Replaces each range of the text with its string, for example to
+        /// replace every match of a search. Leaves a carat at the end of
+        /// each replacement, and can be undone in one step.
+        pub fn replace_text_in_ranges(&self, replacements: Vec<(Range<usize>, String)>) {
+            let mut replacements = replacements;
+            self.constrain_input(&mut replacements);
 
-                if self.click_count.get() == 2 {
-                    // go forward, back, and then forward again incase it
-                    // started on whitespace (otherwise multiple words would
-                    // be selected).
-                    let rhs = word_boundary::find_word_boundary(text, touched_character_index, true);
-                    let lhs = word_boundary::find_word_boundary(text, rhs, false);
-                    let rhs = word_boundary::find_word_boundary(text, touched_character_index, true);
+            if replacements.is_empty() || !self.should_change_text(&replacements) {
+                return;
+            }
 
-                    let mut carats = self.carats.borrow_mut();
-                    let carat = carats.first_mut().unwrap();
-                    text_field.select(carat, lhs, rhs);
-                    carat.character_index.set(rhs);
-                } else if self.click_count.get() == 3 {
-                    // go forward, back, and then forward again incase it
-                    // started on whitespace (otherwise multiple words would
-                    // be selected).
-                    let rhs = word_boundary::find_line_boundary(text, touched_character_index, true);
-                    let lhs = word_boundary::find_line_boundary(text, rhs, false);
-                    let rhs = word_boundary::find_line_boundary(text, touched_character_index, true);
+            let behavior = self.behavior();
+            let mut text_replace = TextReplace::new(self.view.downgrade(), replacements, self.carat_snapshots());
+            text_replace.forward();
+            behavior.history.borrow_mut().push(Box::new(text_replace));
 
-                    let mut carats = self.carats.borrow_mut();
-                    let carat = carats.first_mut().unwrap();
-                    text_field.select(carat, lhs, rhs);
-                    carat.character_index.set(rhs);
-                }
+            self.did_change_text();
+        }
 
-            } else {
-                self.click_count.set(1);
-            }
+        /// Adds a carat at `character_index`, keeping the existing carats.
+        /// This can be undone.
+        pub fn add_carat(&self, character_index: usize) {
+            let carats_before = self.carat_snapshots();
+            self.spawn_carat(character_index.min(self.label().text_len()));
+            self.add_carat_change(carats_before);
+        }
 
-            self.last_click.set(Instant::now());
+        /// Adds a carat on the line above the first carat, in the same
+        /// column. This can be undone.
+        pub fn add_carat_above(&self) {
+            self.add_carat_on_adjacent_line(false);
         }
 
-        fn text_input_did_receive(&self, text: &str) {
-            let view = self.view.upgrade().unwrap();
-            let text_field = TextField::from_view(view.clone());
+        /// Adds a carat on the line below the last carat, in the same column.
+        /// This can be undone.
+        pub fn add_carat_below(&self) {
+            self.add_carat_on_adjacent_line(true);
+        }
 
-            let mut text_insertion = TextInsertion::new(
-                self.view.clone(),
-                text.to_string(),
-                text_field.carat_snapshots()
-            );
+        fn add_carat_on_adjacent_line(&self, downwards: bool) {
+            self.consume_and_sort_cursors();
+            let carat_indexes = self.carat_indexes();
+            let index = if downwards { carat_indexes.last() } else { carat_indexes.first() };
+
+            if let Some(&index) = index {
+                let x = {
+                    let label = self.label();
+                    let label_behavior = label.behavior();
+                    let rendering = label_behavior.rendering();
+                    rendering.position_for_character_at_index(index).x
+                };
+
+                if let Some(new_index) = self.index_on_adjacent_line(index, downwards, x) {
+                    self.add_carat(new_index);
+                }
+            }
+        }
 
-            text_insertion.forward();
+        /// Selects the next occurrence of the selected text with a new
+        /// carat, keeping the existing carats. The text after the last
+        /// selection is searched first, wrapping around to the start.
+        ///
+        /// If nothing is selected, the word at the last carat is selected
+        /// instead. This can be undone.
+        pub fn select_next_occurrence(&self) {
+            self.consume_and_sort_cursors();
+            let carats_before = self.carat_snapshots();
 
-            let mut history = self.history.borrow_mut();
-            history.add(Box::new(text_insertion));
+            let label = self.label();
+            let text = label.text();
+            let last_selection = carats_before.iter().rev().find_map(|carat| carat.selection().clone());
 
-            if let Some(text_change) = self.text_change.borrow().as_ref() {
-                text_change(&text_field);
+            let behavior = self.behavior();
+            match last_selection {
+                Some(selection) => {
+                    let found = self.find_next_occurrence(&selection, &carats_before);
+                    if let Some(range) = found {
+                        self.spawn_carat(range.end);
+                        let mut carats = behavior.carats.borrow_mut();
+                        if let Some(carat) = carats.iter_mut().find(|carat| carat.character_index.get() == range.end) {
+                            self.select_range(carat, &range);
+                        }
+                    }
+                },
+                None => {
+                    let mut carats = behavior.carats.borrow_mut();
+                    if let Some(carat) = carats.last_mut() {
+                        let index = carat.character_index.get();
+                        let rhs = word_boundary::find_word_boundary(text, index, true);
+                        let lhs = word_boundary::find_word_boundary(text, rhs, false);
+                        self.select(carat, lhs, rhs);
+                        carat.character_index.set(rhs);
+                    }
+                }
             }
-        }
 
-        fn press_ended(&self, press: &Press) {
-            let view = self.view.upgrade().unwrap();
-            let text_field = TextField::from_view(view.clone());
-            let text_field_behavior = text_field.behavior();
-            let key = press.key();
+            self.consume_and_sort_cursors();
+            self.add_carat_change(carats_before);
+        }
 
-            if key.key_code() == KeyCode::LAlt || key.key_code() == KeyCode::RAlt {
-                let count = text_field_behavior.holding_alternative.get();
-                text_field_behavior.holding_alternative.set(count - 1);
+        /// Finds the next occurrence of the text in `selection` that isn't
+        /// already selected by one of `carats`, wrapping around to the start
+        /// of the text.
+        fn find_next_occurrence(&self, selection: &Range<usize>, carats: &[CaratSnapshot]) -> Option<Range<usize>> {
+            let label = self.label();
+            let text = label.text();
+            let string = text.string();
+            let needle = &text[selection.clone()];
+            if needle.is_empty() {
+                return None;
             }
 
-            if key.key_code() == KeyCode::LShift || key.key_code() == KeyCode::RShift {
-                let count = text_field_behavior.holding_shift.get();
-                text_field_behavior.holding_shift.set(count - 1);
+            let after = text[0..selection.end].len();
+            let occurrences = string[after..]
+                .match_indices(needle)
+                .map(|(offset, _)| offset + after)
+                .chain(string[..after].match_indices(needle).map(|(offset, _)| offset));
+
+            for offset in occurrences {
+                let start = text.grapheme_index_for_byte_offset(offset);
+                let end = text.grapheme_index_for_byte_offset(offset + needle.len());
+
+                // Skip matches that only cover part of a grapheme, or overlap
+                // a selection.
+                let candidate = CaratSnapshot::new(end, Some(start..end));
+                let already_selected = carats.iter().any(|carat| carat.selection_intersects(&candidate));
+                if &text[start..end] == needle && !already_selected {
+                    return Some(start..end);
+                }
             }
+
+            None
         }
 
-        fn press_began(&self, press: &Press) {
-            let view = self.view.upgrade().unwrap();
-            let text_field = TextField::from_view(view.clone());
-            text_field.consume_and_sort_cursors();
-            let label = text_field.label();
-            let text_field_behavior = text_field.behavior();
+        /// Adds a history action for changing the carats from
+        /// `carats_before` to the current carats, if they changed.
+        fn add_carat_change(&self, carats_before: Vec<CaratSnapshot>) {
+            let carats_after = self.carat_snapshots();
+            if carats_after == carats_before {
+                return;
+            }
 
-            let key = press.key();
+            let behavior = self.behavior();
+            let carat_change = CaratChange::new(self.view.downgrade(), carats_before, carats_after);
+            behavior.history.borrow_mut().push(Box::new(carat_change));
+        }
 
-            if key.key_code() == KeyCode::LAlt || key.key_code() == KeyCode::RAlt {
-                let count = text_field_behavior.holding_alternative.get();
-                text_field_behavior.holding_alternative.set(count + 1);
-            }
+        fn did_change_text(&self) {
+            self.check_spelling();
 
-            if key.key_code() == KeyCode::LShift || key.key_code() == KeyCode::RShift {
-                let count = text_field_behavior.holding_shift.get();
-                text_field_behavior.holding_shift.set(count + 1);
+            let behavior = self.behavior();
+            let callbacks = behavior.callbacks.borrow();
+            if let Some(change) = callbacks.change.as_ref() {
+                change(self);
             }
+            if let Some(layout) = callbacks.layout.as_ref() {
+                layout(self);
+            }
+        }
+
+        /// Performs the command for a key press: typing shortcuts, moving
+        /// the carats, deleting and new lines.
+        fn handle_key(&self, key: &Key) {
+            let text_field_behavior = self.behavior();
 
             match key.key_code() {
                 KeyCode::C => {
                     if key.modifier_flags().contains(&ModifierFlag::Control) || key.modifier_flags().contains(&ModifierFlag::Command) {
-                        let text_to_copy = text_field.selected_text().join("\n");
-                        clipboard::set_string(&text_to_copy);
+                        self.copy();
                     }
                 },
                 KeyCode::V => {
                     if key.modifier_flags().contains(&ModifierFlag::Control) || key.modifier_flags().contains(&ModifierFlag::Command) {
-                        if let Some(text_to_paste) = clipboard::get_string() {
-                            self.text_input_did_receive(&text_to_paste);
-                        }
+                        self.paste();
                     }
                 },
                 KeyCode::X => {
                     if key.modifier_flags().contains(&ModifierFlag::Control) || key.modifier_flags().contains(&ModifierFlag::Command) {
-                        let text_to_copy = text_field.selected_text().join("\n");
-                        clipboard::set_string(&text_to_copy);
-
-                        let mut text_backspace = TextBackspace::new(
-                            self.view.clone(),
-                            1,
-                            CursorMovement::Character,
-                            text_field.carat_snapshots()
-                        );
-
-                        text_backspace.forward();
-
-                        let mut history = self.history.borrow_mut();
-                        history.add(Box::new(text_backspace));
+                        self.cut();
                     }
                 },
-                KeyCode::Z => {
-                    if key.modifier_flags().contains(&ModifierFlag::Control) || key.modifier_flags().contains(&ModifierFlag::Command) {
-                        let mut history = self.history.borrow_mut();
-                        if key.modifier_flags().contains(&ModifierFlag::Shift) {
-                            history.redo();
-                        } else {
-                            history.undo();
-                        }
+                KeyCode::Z if key.is_undo() || key.is_redo() => {
+                    // The text field has its own undo manager, so undoing
+                    // here never affects another view.
+                    let done = if key.is_undo() { self.view.undo() } else { self.view.redo() };
 
-                        if let Some(text_change) = self.text_change.borrow().as_ref() {
-                            text_change(&text_field);
-                        }
+                    if done {
+                        self.did_change_text();
+                    }
+                },
+                KeyCode::Up | KeyCode::Down if key.modifier_flags().contains(&ModifierFlag::Alternate) &&
+                    (key.modifier_flags().contains(&ModifierFlag::Command) || key.modifier_flags().contains(&ModifierFlag::Control)) => {
+                    if key.key_code() == KeyCode::Up {
+                        self.add_carat_above();
+                    } else {
+                        self.add_carat_below();
                     }
                 },
-                KeyCode::Left => {
+                KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down | KeyCode::Home | KeyCode::End => {
                     let highlight = key.modifier_flags().contains(&ModifierFlag::Shift);
                     let mut carats = text_field_behavior.carats.borrow_mut();
                     for carat in carats.iter_mut() {
                         let index = carat.character_index.get();
-
-                        let mut distance = 1;
-                        if key.modifier_flags().contains(&ModifierFlag::Alternate) || key.modifier_flags().contains(&ModifierFlag::Command) {
-                            let text_field = TextField::from_view(self.view.upgrade().unwrap());
-                            let label = text_field.label();
-                            let text = label.text();
-                            let boundary: usize;
-                            if key.modifier_flags().contains(&ModifierFlag::Alternate) {
-                                boundary = word_boundary::find_word_boundary(text, index, false);
-                            } else {
-                                boundary = word_boundary::find_line_boundary(text, index, false);
-                            }
-
-                            distance = index as i32 - boundary as i32;
-                        }
-
-                        let mut new_index = index as i32 - distance;
-                        if new_index < 0 {
-                            new_index = 0;
-                        }
-                        let new_index = new_index as usize;
+                        let new_index = self.index_for_key_movement(carat, key.key_code(), key.modifier_flags());
 
                         if highlight {
-                            let previous_character_index = carat.character_index.get();
-                            text_field.move_carat_selecting(carat, previous_character_index, new_index as usize);
+                            self.move_carat_selecting(carat, index, new_index);
                         } else {
-                            carat.character_index.set(new_index as usize);
-                            text_field.select_range(carat, &(0..0));
+                            carat.character_index.set(new_index);
+                            self.select_range(carat, &(0..0));
                         }
 
                         if let Some(carat_view) = carat.view.upgrade() {
                             carat_view.set_hidden(false);
                             carat_view.set_needs_display();
                         }
-                        self.delay_animation.set(true);
+                        text_field_behavior.delay_animation.set(true);
                     }
                 },
-                KeyCode::Right => {
-                    let highlight = key.modifier_flags().contains(&ModifierFlag::Shift);
-                    let mut carats = text_field_behavior.carats.borrow_mut();
-                    for carat in carats.iter_mut() {
-                        let index = carat.character_index.get();
+                KeyCode::Backspace => {
+                    let mut movement_type = CursorMovement::Character;
 
-                        let mut distance = 1;
-                        if key.modifier_flags().contains(&ModifierFlag::Alternate) || key.modifier_flags().contains(&ModifierFlag::Command) {
-                            let text_field = TextField::from_view(self.view.upgrade().unwrap());
-                            let label = text_field.label();
-                            let text = label.text();
-                            let boundary: usize;
-                            if key.modifier_flags().contains(&ModifierFlag::Alternate) {
-                                boundary = word_boundary::find_word_boundary(text, index, true);
-                            } else {
-                                boundary = word_boundary::find_line_boundary(text, index, true);
-                            }
-
-                            distance = boundary as i32 - index as i32;
-                        }
+                    if key.modifier_flags().contains(&ModifierFlag::Alternate) {
+                        movement_type = CursorMovement::Word;
+                    } else if key.modifier_flags().contains(&ModifierFlag::Command) {
+                        movement_type = CursorMovement::Line;
+                    }
 
-                        let mut new_index = index as i32 + distance;
-                        if new_index > label.text_len() as i32 {
-                            new_index = label.text_len() as i32;
-                        }
+                    self.delete_backward(movement_type);
+                },
+                KeyCode::A => {
+                    if key.modifier_flags().contains(&ModifierFlag::Command) {
+                        self.select_all();
+                    }
+                },
+                KeyCode::D if key.modifier_flags().contains(&ModifierFlag::Control) || key.modifier_flags().contains(&ModifierFlag::Command) => {
+                    self.select_next_occurrence();
+                },
+                KeyCode::Return => {
+                    let callbacks = text_field_behavior.callbacks.borrow();
+                    if let Some(return_pressed) = callbacks.return_pressed.as_ref() {
+                        return_pressed(self);
+                    } else {
+                        drop(callbacks);
+                        self.type_text("\n");
+                    }
+                }
+                _ => ()
+            }
 
-                        if highlight {
-                            let previous_character_index = carat.character_index.get();
-                            text_field.move_carat_selecting(carat, previous_character_index, new_index as usize);
-                        } else {
-                            carat.character_index.set(new_index as usize);
-                            text_field.select_range(carat, &(0..0));
-                        }
+            self.consume_and_sort_cursors();
+            self.did_move_carats();
+        }
 
-                        if let Some(carat_view) = carat.view.upgrade() {
-                            carat_view.set_hidden(false);
-                            carat_view.set_needs_display();
-                        }
-                        self.delay_animation.set(true);
+        /// Sets a closure called after each edit and carat movement, for the
+        /// view containing the text field to lay it out.
+        pub(crate) fn on_layout(&self, action: impl Fn(&TextField) + 'static) {
+            self.behavior().callbacks.borrow_mut().layout = Some(Box::new(action));
+        }
+
+        fn did_move_carats(&self) {
+            let behavior = self.behavior();
+            let callbacks = behavior.callbacks.borrow();
+            if let Some(layout) = callbacks.layout.as_ref() {
+                layout(self);
+            }
+        }
+
+        /// Asks the `should_change_text_in_range` closure whether each of
+        /// `changes` may be made, and the formatter, if there is one,
+        /// whether the text they'd make is valid.
+        fn should_change_text(&self, changes: &[(Range<usize>, String)]) -> bool {
+            let behavior = self.behavior();
+            let callbacks = behavior.callbacks.borrow();
+
+            let should_change = match callbacks.should_change_text_in_range.as_ref() {
+                Some(should_change) => changes
+                    .iter()
+                    .all(|(range, replacement)| should_change(self, range.clone(), replacement)),
+                None => true
+            };
+            if !should_change {
+                return false;
+            }
+
+            let formatter = behavior.formatter.borrow().clone();
+            match formatter {
+                Some(formatter) if !formatter.is_partial_string_valid(&self.text_after_changes(changes)) => {
+                    let rejected: String = changes.iter().map(|(_, replacement)| replacement.as_str()).collect();
+                    if let Some(input_rejected) = callbacks.input_rejected.as_ref().filter(|_| !rejected.is_empty()) {
+                        input_rejected(self, &rejected);
                     }
-                },
-                KeyCode::Up => {
-                    let highlight = key.modifier_flags().contains(&ModifierFlag::Shift);
-                    let mut carats = text_field_behavior.carats.borrow_mut();
-                    for carat in carats.iter_mut() {
-                        let new_index;
-                        let label_behavior = label.behavior();
-                        let rendering = label_behavior.rendering();
-                        let position = rendering.position_for_character_at_index(carat.character_index.get());
+                    false
+                }
+                _ => true
+            }
+        }
 
-                        if position.y < rendering.line_height_for_character_at_index(0) as i32 {
-                            new_index = 0;
-                        } else {
-                            let line_height = rendering.line_height_for_character_at_index(carat.character_index.get());
-                            let new_position = Point {
-                                x: position.x,
-                                y: position.y - (line_height / 2) as i32,
-                            };
+        /// The text as it would be after replacing each grapheme range of
+        /// `changes` with its string. The ranges must not overlap.
+        fn text_after_changes(&self, changes: &[(Range<usize>, String)]) -> String {
+            let label = self.label();
+            let text = label.text().string();
+            let graphemes: Vec<&str> = text.graphemes(true).collect();
+
+            let mut sorted_changes: Vec<&(Range<usize>, String)> = changes.iter().collect();
+            sorted_changes.sort_by_key(|(range, _)| range.start);
+
+            let mut result = String::new();
+            let mut index = 0;
+            for (range, replacement) in sorted_changes {
+                let start = range.start.clamp(index, graphemes.len());
+                result.push_str(&graphemes[index..start].concat());
+                result.push_str(replacement);
+                index = range.end.clamp(start, graphemes.len());
+            }
+            result.push_str(&graphemes[index..].concat());
+            result
+        }
 
-                            new_index = rendering.character_at_position(new_position);
-                        }
+        /// The range each carat's edit would replace with `replacement`: its
+        /// selection, or an empty range at the carat.
+        fn changes_at_carats(&self, replacement: &str) -> Vec<(Range<usize>, String)> {
+            self.carat_snapshots()
+                .iter()
+                .map(|carat| {
+                    let range = match carat.selection() {
+                        Some(selection) => selection.clone(),
+                        None => carat.character_index()..carat.character_index()
+                    };
+                    (range, replacement.to_string())
+                })
+                .collect()
+        }
 
-                        if highlight {
-                            let previous_character_index = carat.character_index.get();
-                            text_field.move_carat_selecting(carat, previous_character_index, new_index as usize);
-                        } else {
-                            carat.character_index.set(new_index as usize);
-                            text_field.select_range(carat, &(0..0));
-                        }
+        /// Inserts `text` at each carat as typing, replacing any selections.
+        /// This can be undone.
+        pub(crate) fn type_text(&self, text: &str) {
+            let mut changes = self.changes_at_carats(text);
+            self.constrain_input(&mut changes);
 
-                        if let Some(carat_view) = carat.view.upgrade() {
-                            carat_view.set_needs_display();
+            // Typing nothing but rejected input doesn't remove the
+            // selections.
+            if !text.is_empty() && changes.iter().all(|(_, replacement)| replacement.is_empty()) {
+                return;
+            }
+
+            if !self.should_change_text(&changes) {
+                return;
+            }
+
+            let behavior = self.behavior();
+            if changes.iter().all(|(_, replacement)| replacement == text) {
+                let mut text_insertion = TextInsertion::new(self.view.downgrade(), text.to_string(), self.carat_snapshots());
+                text_insertion.forward();
+                behavior.history.borrow_mut().push(Box::new(text_insertion));
+            } else {
+                // Only part of the text fits at some carats.
+                let mut text_replace = TextReplace::new(self.view.downgrade(), changes, self.carat_snapshots());
+                text_replace.forward();
+                behavior.history.borrow_mut().push(Box::new(text_replace));
+            }
+
+            self.did_change_text();
+        }
+
+        /// Deletes backwards from each carat by `movement`, or deletes the
+        /// selections. This can be undone.
+        pub(crate) fn delete_backward(&self, movement: CursorMovement) {
+            let label = self.label();
+            let text = label.text();
+
+            let changes: Vec<(Range<usize>, String)> = self.carat_snapshots()
+                .iter()
+                .filter_map(|carat| {
+                    let index = carat.character_index();
+                    let range = match carat.selection() {
+                        Some(selection) => selection.clone(),
+                        None => {
+                            let start = match movement {
+                                CursorMovement::Character => index.saturating_sub(1),
+                                CursorMovement::Word => word_boundary::find_word_boundary(text, index, false),
+                                CursorMovement::Line => word_boundary::find_line_boundary(text, index, false)
+                            };
+                            start..index
                         }
-                        self.delay_animation.set(true);
+                    };
+
+                    if range.is_empty() {
+                        None
+                    } else {
+                        Some((range, String::new()))
                     }
-                },
-                KeyCode::Down => {
-                    let highlight = key.modifier_flags().contains(&ModifierFlag::Shift);
-                    let mut carats = text_field_behavior.carats.borrow_mut();
-                    for carat in carats.iter_mut() {
-                        let label_behavior = label.behavior();
-                        let rendering = label_behavior.rendering();
-                        let position = rendering.position_for_character_at_index(carat.character_index.get());
+                })
+                .collect();
 
-                        let last_index = label.text().len();
-                        let last_line_height = rendering.line_height_for_character_at_index(last_index as usize);
-                        let last_character_position = rendering.position_for_character_at_index(last_index as usize);
-                        let bottom = last_character_position.y + last_line_height as i32;
+            if !self.should_change_text(&changes) {
+                return;
+            }
 
-                        let line_height = rendering.line_height_for_character_at_index(carat.character_index.get());
+            let behavior = self.behavior();
+            let mut text_backspace = TextBackspace::new(self.view.downgrade(), 1, movement, self.carat_snapshots());
+            text_backspace.forward();
+            behavior.history.borrow_mut().push(Box::new(text_backspace));
 
-                        let new_position = Point {
-                            x: position.x,
-                            y: position.y + line_height as i32 + (line_height / 2) as i32,
-                        };
+            self.did_change_text();
+        }
 
-                        let new_index;
-                        if new_position.y >= bottom {
-                            new_index = last_index;
-                        } else {
-                            new_index = rendering.character_at_position(new_position);
-                        }
+        /// The index `carat` moves to when `key_code`, an arrow, Home or End
+        /// key, is pressed while holding `modifier_flags`.
+        ///
+        /// - Left and Right move by a character, or by a word when holding
+        ///   Alternate or Control, or to the start or end of the paragraph
+        ///   (the text between newlines) when holding Command.
+        /// - Up and Down move to the previous or next line, or to the start
+        ///   or end of the text when holding Command.
+        /// - Home and End move to the start or end of the line, or of the text
+        ///   when holding Control or Command.
+        ///
+        /// Otherwise lines are the lines as laid out, so a paragraph that
+        /// wraps is moved through one visual line at a time. Moving up and
+        /// down keeps to the column the carat started in, even through lines
+        /// too short to reach it.
+        fn index_for_key_movement(&self, carat: &Carat, key_code: KeyCode, modifier_flags: &[ModifierFlag]) -> usize {
+            let text_len = self.label().text_len();
+            let command = modifier_flags.contains(&ModifierFlag::Command);
+            let word = modifier_flags.contains(&ModifierFlag::Alternate) || modifier_flags.contains(&ModifierFlag::Control);
+            let index = carat.character_index.get().min(text_len);
 
-                        if highlight {
-                            text_field.move_carat_selecting(carat, carat.character_index.get(), new_index);
-                        } else {
-                            carat.character_index.set(new_index);
-                            text_field.select_range(carat, &(0..0));
+            let vertical = (key_code == KeyCode::Up || key_code == KeyCode::Down) && !command;
+            if !vertical {
+                carat.preferred_x.set(None);
+            }
+
+            match key_code {
+                KeyCode::Left | KeyCode::Right => {
+                    let rightwards = key_code == KeyCode::Right;
+                    if command {
+                        word_boundary::find_line_boundary(self.label().text(), index, rightwards)
+                    } else if word {
+                        word_boundary::find_word_boundary(self.label().text(), index, rightwards)
+                    } else if rightwards {
+                        (index + 1).min(text_len)
+                    } else {
+                        index.saturating_sub(1)
+                    }
+                },
+                KeyCode::Up if command => 0,
+                KeyCode::Down if command => text_len,
+                KeyCode::Up | KeyCode::Down => {
+                    let preferred_x = match carat.preferred_x.get() {
+                        Some(preferred_x) => preferred_x,
+                        None => {
+                            let label = self.label();
+                            let label_behavior = label.behavior();
+                            let rendering = label_behavior.rendering();
+                            rendering.position_for_character_at_index(index).x
                         }
-                        if let Some(carat_view) = carat.view.upgrade() {
-                            carat_view.set_needs_display();
+                    };
+
+                    let downwards = key_code == KeyCode::Down;
+                    match self.index_on_adjacent_line(index, downwards, preferred_x) {
+                        Some(new_index) => {
+                            carat.preferred_x.set(Some(preferred_x));
+                            new_index
+                        },
+                        None => {
+                            // Moving past the first or last line jumps to the
+                            // start or end of the text, which becomes the
+                            // new column.
+                            carat.preferred_x.set(None);
+                            if downwards { text_len } else { 0 }
                         }
-                        self.delay_animation.set(true);
                     }
                 },
-                KeyCode::Backspace => {
-                    let view = self.view.upgrade().unwrap();
-                    let text_field = TextField::from_view(view);
+                KeyCode::Home if command || word => 0,
+                KeyCode::End if command || word => text_len,
+                KeyCode::Home => self.line_boundary(index, false),
+                KeyCode::End => self.line_boundary(index, true),
+                _ => index
+            }
+        }
 
-                    let mut movement_type = CursorMovement::Character;
+        /// The index of the laid out line the carat at `index` is on. A carat
+        /// after a trailing newline is on an empty line after the last line
+        /// fragment, whose index is the number of line fragments.
+        fn line_index_for_carat(&self, index: usize) -> usize {
+            let label = self.label();
+            let label_behavior = label.behavior();
+            let layout = label_behavior.layout();
+            let line_fragments = layout.line_fragments();
+
+            line_fragments
+                .iter()
+                .position(|line_fragment| line_fragment.range().contains(&index))
+                .or_else(|| {
+                    let last = line_fragments.last()?;
+                    if last.range().end == index && !last.ends_with_newline() {
+                        Some(line_fragments.len() - 1)
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(line_fragments.len())
+        }
+
+        /// The start or end of the laid out line that the carat at `index` is
+        /// on. The end is before any newline, and before the whitespace a
+        /// wrapped line was broken at, so that the carat stays on the line.
+        fn line_boundary(&self, index: usize, rightwards: bool) -> usize {
+            let label = self.label();
+            let text = label.text();
+            let label_behavior = label.behavior();
+            let layout = label_behavior.layout();
+            let line_fragments = layout.line_fragments();
+            let line_index = self.line_index_for_carat(index);
+
+            let line_fragment = match line_fragments.get(line_index) {
+                Some(line_fragment) => line_fragment,
+                None => return index
+            };
+
+            let range = line_fragment.range();
+            if !rightwards {
+                return range.start;
+            }
+
+            let is_last_line = line_index + 1 == line_fragments.len();
+            let ends_with_whitespace = range.end > range.start && text
+                .nth(range.end - 1)
+                .is_some_and(|character| character.chars().all(char::is_whitespace));
+
+            if line_fragment.ends_with_newline() || (!is_last_line && ends_with_whitespace) {
+                range.end - 1
+            } else {
+                range.end
+            }
+        }
+
+        /// The index on the line above or below the carat at `index` closest
+        /// to `x`, or `None` if there is no line above or below.
+        fn index_on_adjacent_line(&self, index: usize, downwards: bool, x: i32) -> Option<usize> {
+            let label = self.label();
+            let label_behavior = label.behavior();
+            let layout = label_behavior.layout();
+            let line_fragments = layout.line_fragments();
+            let line_index = self.line_index_for_carat(index);
+
+            let target_line_index = if downwards {
+                line_index + 1
+            } else {
+                line_index.checked_sub(1)?
+            };
+
+            let frame = line_fragments.get(target_line_index)?.frame();
+            Some(layout.carat_index_at_point(&Point {
+                x,
+                y: frame.origin.y + (frame.size.height / 2) as i32
+            }))
+        }
+
+        /// Called internally by `press_began` when holding shift and pressing
+        /// a directional key.
+        fn move_carat_selecting(&self, carat: &mut Carat, previous_character_index: usize, target_character_index: usize) {
+            let text_field = self;
+
+            if let Some(selection) = carat.selection.as_mut() {
+                let current_cursor = carat.character_index.get();
+                carat.character_index.set(target_character_index);
+
+                let start_distance = (current_cursor as i32  - selection.start as i32).abs();
+                let end_distance = (current_cursor as i32 - selection.end as i32).abs();
+                if start_distance < end_distance {
+                    selection.start = target_character_index;
+                    text_field.position_selection(&carat.selection.as_ref().unwrap());
+                } else if end_distance < start_distance {
+                    selection.end = target_character_index;
+                    text_field.position_selection(&carat.selection.as_ref().unwrap());
+                } else {
+                    let start = previous_character_index;
+
+                    text_field.select(carat, start, target_character_index);
+                    carat.character_index.set(target_character_index);
+                }
+
+            } else {
+                let start = previous_character_index;
+
+                text_field.select(carat, start, target_character_index);
+                carat.character_index.set(target_character_index);
+            }
+        }
+
+        /// Called by `touches_moved` and `touches_ended` while dragging.
+        /// Selects from where the touch began to `target_character_index`,
+        /// moving the carat to the end being dragged.
+        fn drag_selection_to(&self, target_character_index: usize) {
+            let behavior = self.behavior();
+            let anchor = behavior.touch_began_at_index.get();
+
+            {
+                let mut carats = behavior.carats.borrow_mut();
+                if let Some(carat) = carats.first_mut() {
+                    self.select(carat, anchor, target_character_index);
+                    carat.character_index.set(target_character_index);
+                }
+            }
+
+            self.did_move_carats();
+        }
+
+        fn position_selection(&self, selection: &Selection) {
+            let label = self.label();
+            let label_behavior = label.behavior();
+            let rendering = label_behavior.rendering();
+
+            let render_scale = rendering.render_scale();
+
+            for view in selection.views.borrow().iter() {
+                if let Some(view) = view.upgrade() {
+                    view.remove_from_superview();
+                }
+            }
+
+            selection.views.borrow_mut().clear();
+
+            let label_origin = &self.label().view.frame().origin;
+
+            let mut character_index = selection.start;
+            let mut last_y = -9999999;
+            let mut current_view: View = View::new(Rectangle::new(0, 0, 1, 1));
+            loop {
+                if character_index >= selection.end {
+                    break;
+                }
+
+                let cursor_rectangle = rendering.cursor_rectangle_for_character_at_index(character_index);
+                let character_size = rendering.character_size_for_character_at_index(character_index);
+
+                if cursor_rectangle.origin.y != last_y {
+                    last_y = cursor_rectangle.origin.y;
+                    current_view = View::new(Rectangle::new(0, 0, 1, 1));
+                    current_view.set_user_interaction_enabled(false);
+                    self.view.add_subview(current_view.clone());
+                    current_view.set_frame(Rectangle {
+                        origin: Point {
+                            x: (cursor_rectangle.origin.x as f32 / render_scale).round() as i32 + label_origin.x,
+                            y: (cursor_rectangle.origin.y as f32 / render_scale).round() as i32 + label_origin.y
+                        },
+                        size: Size {
+                            width: 0,
+                            height: (cursor_rectangle.size.height as f32 / render_scale).round() as u32
+                        }
+                    });
+                    current_view.set_background_color(Color::new(226, 175, 10, 64));
+                }
+
+                let current_frame = current_view.frame();
+
+                current_view.set_frame(Rectangle::new(
+                    current_frame.origin.x,
+                    current_frame.origin.y,
+                    current_frame.size.width + (character_size.width as f32 / render_scale).round() as u32,
+                    current_frame.size.height
+                ));
+
+                selection.views.borrow_mut().push(current_view.downgrade());
+
+                character_index += 1;
+            }
+        }
+
+        /// If more than one cursor is in the same spot, only one should
+        /// survive.
+        fn consume_and_sort_cursors(&self) {
+            let behavior = self.behavior();
+            let mut carats = behavior.carats.borrow_mut();
+
+            // if we sort upfront, then any overlapping cursors / selections
+            // should be next to each other.
+            carats.sort_by(|a, b| a.character_index.get().cmp(&b.character_index.get()));
+
+            // 1. search for intersecting, set the character index to be the same
+            for index in 0..(carats.len()) {
+                if index == 0 {
+                    continue;
+                }
+
+                let mut last_snapshot = carats[index - 1].snapshot();
+                let current_snapshot = carats[index].snapshot();
+
+                if current_snapshot.selection_intersects(&last_snapshot) {
+                    // Replace the previous carat's selection with one that
+                    // encompasses both.
+                    {
+                        let last_carat = carats.get_mut(index - 1).unwrap();
+                        let merged_end = current_snapshot.selection().as_ref().unwrap().end;
+                        last_carat.selection = Some(Selection {
+                            start: last_snapshot.selection().as_ref().unwrap().start,
+                            end: merged_end,
+                            views: RefCell::new(Vec::new())
+                        });
+
+                        // If the cursor wasn't on the left hand side of the
+                        // selection, then we need to move it to the new right.
+                        if last_snapshot.character_index() != last_snapshot.selection().as_ref().unwrap().start {
+                            last_carat.character_index.replace(merged_end);
+                            last_snapshot = last_carat.snapshot();
+                        }
+
+                        self.position_selection(&last_carat.selection.as_ref().unwrap());
+                    }
+
+                    // Set the current carat to matching character index, so it
+                    // will be removed.
+                    {
+                        let current_carat = carats.get_mut(index).unwrap();
+                        current_carat.character_index.set(last_snapshot.character_index());
+                    }
+                }
+            }
+
+            // 2. drain ones that are the same
+            let mut indexes = HashMap::new();
+            carats.retain(|carat| {
+                let character_index = carat.character_index.get();
+                if indexes.contains_key(&character_index) {
+                    return false;
+                } else {
+                    indexes.insert(character_index, true);
+                    return true;
+                }
+            });
+        }
+    }
+
+    impl Behavior {
+        fn accessibility_role(&self) -> AccessibilityRole {
+            AccessibilityRole::TextField
+        }
+
+        fn accessibility_value(&self) -> Option<String> {
+            Some(self.view_type().label().copy_text())
+        }
+
+        /// Screen readers replace the whole text, which can be undone.
+        fn perform_accessibility_action(&self, action: &AccessibilityAction) -> bool {
+            let value = match action {
+                AccessibilityAction::SetValue(value) => value,
+                _ => return false
+            };
+
+            let text_field = self.view_type();
+            text_field.replace_text_in_ranges(vec![(0..text_field.label().text_len(), value.clone())]);
+            true
+        }
+
+        fn undo_manager(&self) -> Option<Rc<RefCell<UndoManager>>> {
+            Some(self.history.clone())
+        }
+
+        fn cursor(&self, _position: &Point<i32>) -> Option<Cursor> {
+            Some(Cursor::IBeam)
+        }
+
+        fn perform_command(&self, command: &Command) -> bool {
+            let text_field = TextField::from_view(self.get_view().upgrade().unwrap());
+            if !text_field.can_perform(command) {
+                return self.super_behavior().unwrap().perform_command(command);
+            }
+
+            match command {
+                Command::Undo => {
+                    text_field.view.undo();
+                    text_field.did_change_text();
+                },
+                Command::Redo => {
+                    text_field.view.redo();
+                    text_field.did_change_text();
+                },
+                Command::Cut => text_field.cut(),
+                Command::Copy => text_field.copy(),
+                Command::Paste => text_field.paste(),
+                Command::SelectAll => text_field.select_all(),
+                Command::Custom(_) => ()
+            }
+            true
+        }
+
+        fn can_perform_command(&self, command: &Command) -> bool {
+            let text_field = TextField::from_view(self.get_view().upgrade().unwrap());
+            text_field.can_perform(command) || self.super_behavior().unwrap().can_perform_command(command)
+        }
+
+        fn draw(&self) {
+            self.super_behavior().unwrap().draw();
+            let text_field = TextField::from_view(self.get_view().upgrade().unwrap());
+            text_field.position_cursors();
+        }
+
+        fn layout_subviews(&self) {
+            let text_field = TextField::from_view(self.get_view().upgrade().unwrap());
+            text_field.layout_label();
+        }
+
+        /// The carats are sized from the text's layout, which is made again
+        /// at the new scale first.
+        fn render_scale_did_change(&self, _render_scale: f32) {
+            let text_field = TextField::from_view(self.get_view().upgrade().unwrap());
+            text_field.label().view.set_needs_display();
+            text_field.position_cursors();
+        }
+
+        fn touches_moved(&self, touches: &Vec<Touch>) {
+            if !self.tracking_touch.get() {
+                return;
+            }
+
+            let view = self.view.upgrade().unwrap();
+            let text_field = TextField::from_view(view.clone());
+
+            if let Some(touch) = touches.first() {
+                text_field.drag_selection_to(text_field.touch_to_index(touch));
+            }
+        }
+
+        fn touches_ended(&self, touches: &Vec<Touch>) {
+            if !self.tracking_touch.replace(false) {
+                return;
+            }
+
+            let view = self.view.upgrade().unwrap();
+            let text_field = TextField::from_view(view.clone());
+
+            if let Some(touch) = touches.first() {
+                text_field.drag_selection_to(text_field.touch_to_index(touch));
+            }
+        }
+
+        fn touches_began(&self, touches: &Vec<Touch>) {
+            let view = self.view.upgrade().unwrap();
+            let text_field = TextField::from_view(view.clone());
+
+            let touch = match touches.first() {
+                Some(touch) => touch,
+                None => return
+            };
+
+            // Clicking a text field gives it keyboard focus.
+            view.become_first_responder();
+
+            let touched_character_index = text_field.touch_to_index(touch);
+
+            self.touch_began_at_index.set(touched_character_index);
+            self.tracking_touch.set(false);
+            self.delay_animation.set(true);
+
+            if self.holding_shift.get() > 0 || self.holding_alternative.get() > 0 {
+                // A modifier click shouldn't count towards a double click.
+                self.click_count.set(0);
+            }
+
+            if self.holding_shift.get() > 0 {
+                let mut carats = self.carats.borrow_mut();
+
+                // We only actually care to do this for one carat; it's an
+                // edge case that we don't want to handle if there's more
+                // than one.
+                if let Some(carat) = carats.first_mut() {
+                    // Extend from the end of the selection the carat isn't
+                    // at, so that shift-clicking either side of a selection
+                    // grows it from its anchor.
+                    let carat_index = carat.character_index.get();
+                    let anchor = match carat.selection.as_ref() {
+                        Some(selection) if selection.start == carat_index => selection.end,
+                        Some(selection) => selection.start,
+                        None => carat_index
+                    };
+
+                    text_field.select(carat, anchor, touched_character_index);
+                    carat.character_index.set(touched_character_index);
+
+                    self.touch_began_at_index.set(anchor);
+                    self.tracking_touch.set(true);
+                    return;
+                }
+            } else if self.holding_alternative.get() > 0 {
+                text_field.add_carat(touched_character_index);
+                return;
+            }
+
+            text_field.remove_carats();
+            text_field.spawn_carat(touched_character_index);
+
+            if clock::now().saturating_duration_since(self.last_click.get()).as_millis() < 500 {
+                self.click_count.set(self.click_count.get() + 1);
+
+                let label = text_field.label();
+                let text = label.text();
+
+                if self.click_count.get() == 2 {
+                    // go forward, back, and then forward again incase it
+                    // started on whitespace (otherwise multiple words would
+                    // be selected).
+                    let rhs = word_boundary::find_word_boundary(text, touched_character_index, true);
+                    let lhs = word_boundary::find_word_boundary(text, rhs, false);
+                    let rhs = word_boundary::find_word_boundary(text, touched_character_index, true);
+
+                    let mut carats = self.carats.borrow_mut();
+                    let carat = carats.first_mut().unwrap();
+                    text_field.select(carat, lhs, rhs);
+                    carat.character_index.set(rhs);
+                } else if self.click_count.get() == 3 {
+                    // go forward, back, and then forward again incase it
+                    // started on whitespace (otherwise multiple words would
+                    // be selected).
+                    let rhs = word_boundary::find_line_boundary(text, touched_character_index, true);
+                    let lhs = word_boundary::find_line_boundary(text, rhs, false);
+                    let rhs = word_boundary::find_line_boundary(text, touched_character_index, true);
+
+                    let mut carats = self.carats.borrow_mut();
+                    let carat = carats.first_mut().unwrap();
+                    text_field.select(carat, lhs, rhs);
+                    carat.character_index.set(rhs);
+                }
+
+            } else {
+                self.click_count.set(1);
+            }
+
+            if self.click_count.get() == 1 {
+                self.tracking_touch.set(true);
+            }
+
+            self.last_click.set(clock::now());
+        }
+
+        fn text_input_did_receive(&self, text: &str) {
+            let text_field = self.view_type();
+            text_field.type_text(text);
+        }
+
+        fn can_become_first_responder(&self) -> bool {
+            true
+        }
+
+        fn context_menu_at(&self, point: &Point<i32>) -> Option<Menu> {
+            self.view_type().spelling_menu_at(point)
+        }
+
+        fn did_become_first_responder(&self) {
+            let text_field = self.view_type();
+            text_field.apply_editing_string();
+
+            let callbacks = self.callbacks.borrow();
+            if let Some(begin_editing) = callbacks.begin_editing.as_ref() {
+                begin_editing(&text_field);
+            }
+        }
+
+        fn did_resign_first_responder(&self) {
+            let text_field = self.view_type();
+            text_field.apply_display_string();
+
+            let callbacks = self.callbacks.borrow();
+            if let Some(end_editing) = callbacks.end_editing.as_ref() {
+                end_editing(&text_field);
+            }
+        }
+
+        fn press_ended(&self, press: &Press) {
+            let view = self.view.upgrade().unwrap();
+            let text_field = TextField::from_view(view.clone());
+            let text_field_behavior = text_field.behavior();
+            let key = press.key();
+
+            if key.key_code() == KeyCode::LAlt || key.key_code() == KeyCode::RAlt {
+                let count = text_field_behavior.holding_alternative.get();
+                text_field_behavior.holding_alternative.set(count - 1);
+            }
 
-                    if key.modifier_flags().contains(&ModifierFlag::Alternate) {
-                        movement_type = CursorMovement::Word;
-                    } else if key.modifier_flags().contains(&ModifierFlag::Command) {
-                        movement_type = CursorMovement::Line;
-                    }
+            if key.key_code() == KeyCode::LShift || key.key_code() == KeyCode::RShift {
+                let count = text_field_behavior.holding_shift.get();
+                text_field_behavior.holding_shift.set(count - 1);
+            }
+        }
 
-                    let mut text_backspace = TextBackspace::new(
-                        self.view.clone(),
-                        1,
-                        movement_type,
-                        text_field.carat_snapshots()
-                    );
+        fn press_began(&self, press: &Press) {
+            let view = self.view.upgrade().unwrap();
+            let text_field = TextField::from_view(view.clone());
+            text_field.consume_and_sort_cursors();
+            let text_field_behavior = text_field.behavior();
 
-                    text_backspace.forward();
+            let key = press.key();
 
-                    let mut history = self.history.borrow_mut();
-                    history.add(Box::new(text_backspace));
+            if key.key_code() == KeyCode::LAlt || key.key_code() == KeyCode::RAlt {
+                let count = text_field_behavior.holding_alternative.get();
+                text_field_behavior.holding_alternative.set(count + 1);
+            }
 
-                    if let Some(text_change) = self.text_change.borrow().as_ref() {
-                        text_change(&text_field);
-                    }
-                },
-                KeyCode::A => {
-                    if key.modifier_flags().contains(&ModifierFlag::Command) {
-                        text_field.select_all();
-                    }
-                },
-                KeyCode::Return => {
-                    let view = self.view.upgrade().unwrap();
-                    let text_field = TextField::from_view(view.clone());
+            if key.key_code() == KeyCode::LShift || key.key_code() == KeyCode::RShift {
+                let count = text_field_behavior.holding_shift.get();
+                text_field_behavior.holding_shift.set(count + 1);
+            }
 
-                    let mut text_insertion = TextInsertion::new(
-                        self.view.clone(),
-                        '\n'.to_string(),
-                        text_field.carat_snapshots()
-                    );
+            // Tab moves keyboard focus, which the window handles.
+            if key.key_code() == KeyCode::Tab {
+                self.super_behavior().unwrap().press_began(press);
+                return;
+            }
 
-                    text_insertion.forward();
+            text_field.handle_key(&key);
+        }
 
-                    let mut history = self.history.borrow_mut();
-                    history.add(Box::new(text_insertion));
+        fn press_repeated(&self, press: &Press) {
+            let text_field = self.view_type();
+            let key = press.key();
 
-                    if let Some(text_change) = self.text_change.borrow().as_ref() {
-                        text_change(&text_field);
-                    }
-                }
+            // Only deleting, moving and new lines repeat while the key is
+            // held, not shortcuts.
+            match key.key_code() {
+                KeyCode::Backspace | KeyCode::Return |
+                KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down => {
+                    text_field.consume_and_sort_cursors();
+                    text_field.handle_key(&key);
+                },
                 _ => ()
             }
-
-            text_field.consume_and_sort_cursors();
         }
     }
 );
@@ -1093,6 +2117,18 @@ mod tests {
     use crate::ui::key::{Key, ModifierFlag};
     use crate::ui::press::Press;
 
+    /// A touch at the carat position before `index`, in window coordinates.
+    fn touch_at_index(text_field: &TextField, index: usize) -> Vec<Touch> {
+        let label = text_field.label();
+        let position = label.position_for_character_at_index(index);
+        let label_location = label.view.get_location_in_window();
+
+        vec![Touch::new(0, Point {
+            x: position.x + label_location.x,
+            y: position.y + label_location.y + 2
+        })]
+    }
+
     #[test]
     fn test_text_field_integration() {
         let frame = Rectangle::new(0, 0, 100, 100);
@@ -1403,9 +2439,11 @@ mod tests {
         behavior.press_began(&press);
         behavior.press_ended(&press);
 
+        // The carat keeps to the column it started in (after the "b")
+        // rather than the empty line's.
         let cursors = text_field.carat_snapshots();
         assert_eq!(cursors.len(), 1);
-        assert_eq!(cursors[0].character_index(), 0);
+        assert_eq!(cursors[0].character_index(), 1);
         assert_eq!(cursors[0].selection(), &None);
 
         let key = Key::new(KeyCode::Down, vec![]);
@@ -1425,7 +2463,7 @@ mod tests {
 
         let cursors = text_field.carat_snapshots();
         assert_eq!(cursors.len(), 1);
-        assert_eq!(cursors[0].character_index(), 3);
+        assert_eq!(cursors[0].character_index(), 4);
         assert_eq!(cursors[0].selection(), &None);
     }
 
@@ -1474,4 +2512,606 @@ mod tests {
 
         assert_eq!(*test.borrow(), "hell\n");
     }
+
+    #[test]
+    fn test_click_and_drag_selection() {
+        let frame = Rectangle::new(0, 0, 300, 100);
+        let text_field = TextField::new(frame, "hello world".to_string());
+        let behavior = text_field.behavior();
+
+        behavior.touches_began(&touch_at_index(&text_field, 2));
+
+        let cursors = text_field.carat_snapshots();
+        assert_eq!(cursors.len(), 1);
+        assert_eq!(cursors[0].character_index(), 2);
+        assert_eq!(cursors[0].selection(), &None);
+
+        behavior.touches_moved(&touch_at_index(&text_field, 9));
+        behavior.touches_moved(&touch_at_index(&text_field, 7));
+        behavior.touches_ended(&touch_at_index(&text_field, 7));
+
+        let cursors = text_field.carat_snapshots();
+        assert_eq!(cursors.len(), 1);
+        assert_eq!(cursors[0].character_index(), 7);
+        assert_eq!(cursors[0].selection(), &Some(2..7));
+        assert_eq!(text_field.selected_text(), vec!["llo w".to_string()]);
+
+        // Moving after the touch ended doesn't change the selection.
+        behavior.touches_moved(&touch_at_index(&text_field, 11));
+        assert_eq!(text_field.carat_snapshots()[0].selection(), &Some(2..7));
+
+        let shift = Press::new(Key::new(KeyCode::LShift, vec![]));
+        behavior.press_began(&shift);
+
+        // Shift-clicking extends from the selection's anchor.
+        behavior.touches_began(&touch_at_index(&text_field, 9));
+        behavior.touches_ended(&touch_at_index(&text_field, 9));
+
+        let cursors = text_field.carat_snapshots();
+        assert_eq!(cursors[0].character_index(), 9);
+        assert_eq!(cursors[0].selection(), &Some(2..9));
+
+        behavior.touches_began(&touch_at_index(&text_field, 0));
+        behavior.touches_ended(&touch_at_index(&text_field, 0));
+
+        let cursors = text_field.carat_snapshots();
+        assert_eq!(cursors[0].character_index(), 0);
+        assert_eq!(cursors[0].selection(), &Some(0..2));
+
+        behavior.press_ended(&shift);
+
+        // A double click selects the word.
+        behavior.touches_began(&touch_at_index(&text_field, 1));
+        behavior.touches_ended(&touch_at_index(&text_field, 1));
+        behavior.touches_began(&touch_at_index(&text_field, 1));
+        behavior.touches_moved(&touch_at_index(&text_field, 8));
+        behavior.touches_ended(&touch_at_index(&text_field, 8));
+
+        let cursors = text_field.carat_snapshots();
+        assert_eq!(cursors.len(), 1);
+        assert_eq!(cursors[0].selection(), &Some(0..5));
+    }
+
+    #[test]
+    fn test_copy_cut_and_paste() {
+        let frame = Rectangle::new(0, 0, 300, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        text_field.set_pasteboard(Pasteboard::named("test_copy_cut_and_paste"));
+        let behavior = text_field.behavior();
+
+        let press = |key_code: KeyCode| {
+            let press = Press::new(Key::new(key_code, vec![ModifierFlag::Command]));
+            behavior.press_began(&press);
+            behavior.press_ended(&press);
+        };
+
+        behavior.text_input_did_receive("one two");
+
+        // Nothing is selected, so nothing is copied or cut.
+        press(KeyCode::C);
+        press(KeyCode::X);
+        assert!(!text_field.pasteboard().has_strings());
+        assert_eq!(text_field.label().text().string(), "one two");
+
+        text_field.remove_carats();
+        text_field.spawn_carat(3);
+        text_field.spawn_carat(7);
+        {
+            let mut carats = behavior.carats.borrow_mut();
+            text_field.select(&mut carats[0], 0, 3);
+            text_field.select(&mut carats[1], 4, 7);
+        }
+
+        press(KeyCode::C);
+        assert_eq!(text_field.pasteboard().strings(), vec!["one".to_string(), "two".to_string()]);
+
+        press(KeyCode::X);
+        assert_eq!(text_field.label().text().string(), " ");
+        assert_eq!(text_field.carat_indexes(), vec![0, 1]);
+
+        // With as many items as carats, each carat gets its own item.
+        press(KeyCode::V);
+        assert_eq!(text_field.label().text().string(), "one two");
+        assert_eq!(text_field.carat_indexes(), vec![3, 7]);
+
+        press(KeyCode::Z);
+        assert_eq!(text_field.label().text().string(), " ");
+        press(KeyCode::Z);
+        assert_eq!(text_field.label().text().string(), "one two");
+        assert_eq!(text_field.selected_text(), vec!["one".to_string(), "two".to_string()]);
+
+        // Otherwise the items are pasted together at every carat.
+        text_field.remove_carats();
+        text_field.spawn_carat(7);
+        press(KeyCode::V);
+        assert_eq!(text_field.label().text().string(), "one twoone\ntwo");
+    }
+
+    #[test]
+    fn test_home_end_and_word_movement() {
+        let frame = Rectangle::new(0, 0, 300, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        let behavior = text_field.behavior();
+
+        let press = |key_code: KeyCode, modifier_flags: Vec<ModifierFlag>| {
+            let press = Press::new(Key::new(key_code, modifier_flags));
+            behavior.press_began(&press);
+            behavior.press_ended(&press);
+        };
+
+        behavior.text_input_did_receive("one two\nthree four");
+
+        press(KeyCode::Home, vec![]);
+        assert_eq!(text_field.carat_indexes(), vec![8]);
+
+        press(KeyCode::Right, vec![ModifierFlag::Control]);
+        assert_eq!(text_field.carat_indexes(), vec![13]);
+
+        press(KeyCode::End, vec![ModifierFlag::Shift]);
+        let cursors = text_field.carat_snapshots();
+        assert_eq!(cursors[0].character_index(), 18);
+        assert_eq!(cursors[0].selection(), &Some(13..18));
+
+        press(KeyCode::Up, vec![]);
+        press(KeyCode::End, vec![]);
+        assert_eq!(text_field.carat_indexes(), vec![7]);
+
+        press(KeyCode::Left, vec![ModifierFlag::Control]);
+        assert_eq!(text_field.carat_indexes(), vec![4]);
+
+        press(KeyCode::End, vec![ModifierFlag::Control]);
+        assert_eq!(text_field.carat_indexes(), vec![18]);
+
+        press(KeyCode::Home, vec![ModifierFlag::Control, ModifierFlag::Shift]);
+        let cursors = text_field.carat_snapshots();
+        assert_eq!(cursors[0].character_index(), 0);
+        assert_eq!(cursors[0].selection(), &Some(0..18));
+    }
+
+    #[test]
+    fn test_movement_through_wrapped_lines() {
+        // Wide enough for six characters, so "aaaa bbbb" wraps after the
+        // space.
+        let character_width = crate::graphics::Font::default().size_for("a").width;
+        let frame = Rectangle::new(0, 0, 16 + character_width * 6, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        let behavior = text_field.behavior();
+
+        let press = |key_code: KeyCode| {
+            let press = Press::new(Key::new(key_code, vec![]));
+            behavior.press_began(&press);
+            behavior.press_ended(&press);
+        };
+
+        behavior.text_input_did_receive("aaaa bbbb");
+        assert_eq!(text_field.label().behavior().layout().line_fragments().len(), 2);
+
+        press(KeyCode::Home);
+        assert_eq!(text_field.carat_indexes(), vec![5]);
+
+        press(KeyCode::Right);
+        press(KeyCode::Right);
+        press(KeyCode::Up);
+        assert_eq!(text_field.carat_indexes(), vec![2]);
+
+        press(KeyCode::End);
+        assert_eq!(text_field.carat_indexes(), vec![4]);
+
+        press(KeyCode::Down);
+        assert_eq!(text_field.carat_indexes(), vec![9]);
+    }
+
+    #[test]
+    fn test_vertical_movement_keeps_column() {
+        let frame = Rectangle::new(0, 0, 300, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        let behavior = text_field.behavior();
+
+        let press = |key_code: KeyCode| {
+            let press = Press::new(Key::new(key_code, vec![]));
+            behavior.press_began(&press);
+            behavior.press_ended(&press);
+        };
+
+        behavior.text_input_did_receive("abcdef\nab\nabcdef");
+        text_field.remove_carats();
+        text_field.spawn_carat(5);
+
+        // Passing through the short line returns to the original column.
+        press(KeyCode::Down);
+        assert_eq!(text_field.carat_indexes(), vec![9]);
+        press(KeyCode::Down);
+        assert_eq!(text_field.carat_indexes(), vec![15]);
+        press(KeyCode::Up);
+        press(KeyCode::Up);
+        assert_eq!(text_field.carat_indexes(), vec![5]);
+
+        // Moving horizontally picks a new column.
+        press(KeyCode::Down);
+        press(KeyCode::Left);
+        press(KeyCode::Down);
+        assert_eq!(text_field.carat_indexes(), vec![11]);
+
+        // As does typing.
+        press(KeyCode::Up);
+        press(KeyCode::Up);
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+        behavior.text_input_did_receive("x");
+        press(KeyCode::Down);
+        press(KeyCode::Down);
+        assert_eq!(text_field.carat_indexes(), vec![13]);
+    }
+
+    #[test]
+    fn test_multi_cursor_commands() {
+        let frame = Rectangle::new(0, 0, 300, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        let behavior = text_field.behavior();
+
+        let press = |key_code: KeyCode, modifier_flags: Vec<ModifierFlag>| {
+            let press = Press::new(Key::new(key_code, modifier_flags));
+            behavior.press_began(&press);
+            behavior.press_ended(&press);
+        };
+
+        behavior.text_input_did_receive("cat dog\ncat\ncatalog");
+        text_field.remove_carats();
+        text_field.spawn_carat(1);
+
+        // The first press selects the word at the carat, and later presses
+        // select further occurrences, wrapping around.
+        press(KeyCode::D, vec![ModifierFlag::Command]);
+        assert_eq!(text_field.selected_text(), vec!["cat".to_string()]);
+
+        press(KeyCode::D, vec![ModifierFlag::Command]);
+        press(KeyCode::D, vec![ModifierFlag::Control]);
+        let cursors = text_field.carat_snapshots();
+        assert_eq!(cursors.len(), 3);
+        assert_eq!(cursors[1].selection(), &Some(8..11));
+        assert_eq!(cursors[2].selection(), &Some(12..15));
+
+        press(KeyCode::D, vec![ModifierFlag::Command]);
+        assert_eq!(text_field.carat_snapshots().len(), 3);
+
+        behavior.text_input_did_receive("owl");
+        assert_eq!(text_field.label().text().string(), "owl dog\nowl\nowlalog");
+
+        // Carat changes are undone separately from the typing.
+        press(KeyCode::Z, vec![ModifierFlag::Command]);
+        press(KeyCode::Z, vec![ModifierFlag::Command]);
+        assert_eq!(text_field.label().text().string(), "cat dog\ncat\ncatalog");
+        assert_eq!(text_field.carat_snapshots().len(), 2);
+
+        text_field.remove_carats();
+        text_field.spawn_carat(10);
+
+        press(KeyCode::Up, vec![ModifierFlag::Command, ModifierFlag::Alternate]);
+        assert_eq!(text_field.carat_indexes(), vec![2, 10]);
+        press(KeyCode::Down, vec![ModifierFlag::Control, ModifierFlag::Alternate]);
+        assert_eq!(text_field.carat_indexes(), vec![2, 10, 14]);
+
+        press(KeyCode::Z, vec![ModifierFlag::Command]);
+        assert_eq!(text_field.carat_indexes(), vec![2, 10]);
+
+        behavior.text_input_did_receive("!");
+        assert_eq!(text_field.label().text().string(), "ca!t dog\nca!t\ncatalog");
+    }
+
+    #[test]
+    fn test_undo_is_per_text_field() {
+        let container = View::new(Rectangle::new(0, 0, 300, 300));
+        let first = TextField::new(Rectangle::new(0, 0, 300, 100), "".to_string());
+        let second = TextField::new(Rectangle::new(0, 100, 300, 100), "".to_string());
+        container.add_subview(first.view.clone());
+        container.add_subview(second.view.clone());
+
+        // Views inside a text field use its undo manager, and views outside
+        // a window have none.
+        assert!(container.undo_manager().is_none());
+        let label_undo_manager = first.label().view.undo_manager().unwrap();
+        assert!(Rc::ptr_eq(&label_undo_manager, &first.view.undo_manager().unwrap()));
+        assert!(!Rc::ptr_eq(&label_undo_manager, &second.view.undo_manager().unwrap()));
+
+        first.behavior().text_input_did_receive("first");
+        second.behavior().text_input_did_receive("second");
+
+        let press = Press::new(Key::new(KeyCode::Z, vec![ModifierFlag::Control]));
+        first.behavior().press_began(&press);
+        first.behavior().press_ended(&press);
+
+        assert_eq!(first.label().text().string(), "");
+        assert_eq!(second.label().text().string(), "second");
+
+        // Nothing is left to undo in the first text field.
+        assert!(!first.view.undo());
+        assert!(first.view.redo());
+        assert_eq!(first.label().text().string(), "first");
+    }
+
+    #[test]
+    fn test_editing_callbacks() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        let behavior = text_field.behavior();
+
+        let asked = Rc::new(RefCell::new(Vec::new()));
+        let asked_clone = asked.clone();
+        text_field.should_change_text_in_range(move |_, range, replacement| {
+            asked_clone.borrow_mut().push((range, replacement.to_string()));
+            !replacement.contains(char::is_numeric)
+        });
+
+        behavior.text_input_did_receive("ab");
+        behavior.text_input_did_receive("1");
+        assert_eq!(text_field.label().text().string(), "ab");
+
+        let press = Press::new(Key::new(KeyCode::Backspace, vec![]));
+        behavior.press_began(&press);
+        behavior.press_ended(&press);
+        assert_eq!(text_field.label().text().string(), "a");
+
+        assert_eq!(*asked.borrow(), vec![
+            (0..0, "ab".to_string()),
+            (2..2, "1".to_string()),
+            (1..2, "".to_string())
+        ]);
+
+        let returned = Rc::new(Cell::new(0));
+        let returned_clone = returned.clone();
+        text_field.on_return(move |_| returned_clone.set(returned_clone.get() + 1));
+
+        let press = Press::new(Key::new(KeyCode::Return, vec![]));
+        behavior.press_began(&press);
+        behavior.press_ended(&press);
+        assert_eq!(returned.get(), 1);
+        assert_eq!(text_field.label().text().string(), "a");
+
+        let editing = Rc::new(RefCell::new(Vec::new()));
+        let editing_clone = editing.clone();
+        text_field.on_begin_editing(move |_| editing_clone.borrow_mut().push("begin"));
+        let editing_clone = editing.clone();
+        text_field.on_end_editing(move |_| editing_clone.borrow_mut().push("end"));
+
+        behavior.did_become_first_responder();
+        behavior.did_resign_first_responder();
+        assert_eq!(*editing.borrow(), vec!["begin", "end"]);
+    }
+
+    #[test]
+    fn test_max_length_and_allowed_characters() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        text_field.set_pasteboard(Pasteboard::named("test_max_length_and_allowed_characters"));
+        let behavior = text_field.behavior();
+
+        let rejected = Rc::new(RefCell::new(Vec::new()));
+        let rejected_clone = rejected.clone();
+        text_field.on_input_rejected(move |_, input| rejected_clone.borrow_mut().push(input.to_string()));
+
+        text_field.set_allowed_characters(|c| c.is_ascii_digit());
+        text_field.set_max_length(4);
+
+        behavior.text_input_did_receive("1a2");
+        behavior.text_input_did_receive("b");
+        assert_eq!(text_field.label().text().string(), "12");
+
+        text_field.pasteboard().set_string("3456");
+        text_field.paste();
+        assert_eq!(text_field.label().text().string(), "1234");
+        assert_eq!(*rejected.borrow(), vec!["a", "b", "56"]);
+
+        // Replacing a selection makes room for what replaces it.
+        text_field.restore_carat_snapshots(&vec![CaratSnapshot::new(2, Some(0..2))]);
+        behavior.text_input_did_receive("99");
+        assert_eq!(text_field.label().text().string(), "9934");
+
+        text_field.clear_max_length();
+        text_field.clear_allowed_characters();
+        behavior.text_input_did_receive("x");
+        assert_eq!(text_field.label().text().string(), "99x34");
+    }
+
+    #[test]
+    fn test_combining_mark() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        let behavior = text_field.behavior();
+        let press = |key_code: KeyCode| {
+            let press = Press::new(Key::new(key_code, vec![]));
+            behavior.press_began(&press);
+            behavior.press_ended(&press);
+        };
+
+        // A combining accent joins the letter before it, so the carat stays
+        // after the one grapheme.
+        behavior.text_input_did_receive("e");
+        behavior.text_input_did_receive("\u{301}");
+        assert_eq!(text_field.label().text().string(), "e\u{301}");
+        assert_eq!(text_field.label().text_len(), 1);
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+
+        press(KeyCode::Left);
+        assert_eq!(text_field.carat_indexes(), vec![0]);
+        press(KeyCode::Right);
+        press(KeyCode::Right);
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+
+        press(KeyCode::Backspace);
+        assert_eq!(text_field.label().text().string(), "");
+        assert_eq!(text_field.carat_indexes(), vec![0]);
+        assert!(text_field.view.undo());
+        assert_eq!(text_field.label().text().string(), "e\u{301}");
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+
+        // Undoing an accent typed in the middle of the text takes it off the
+        // letter again.
+        text_field.label().set_text("ex".to_string());
+        text_field.restore_carat_snapshots(&vec![CaratSnapshot::new(1, None)]);
+        behavior.text_input_did_receive("\u{301}");
+        assert_eq!(text_field.label().text().string(), "e\u{301}x");
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+        press(KeyCode::Right);
+        assert_eq!(text_field.carat_indexes(), vec![2]);
+
+        assert!(text_field.view.undo());
+        assert_eq!(text_field.label().text().string(), "ex");
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+        assert!(text_field.view.redo());
+        assert_eq!(text_field.label().text().string(), "e\u{301}x");
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+
+        // So does undoing one replacing a selection.
+        text_field.replace_text_in_ranges(vec![(1..2, "\u{301}".to_string())]);
+        assert_eq!(text_field.label().text().string(), "e\u{301}\u{301}");
+        assert_eq!(text_field.carat_indexes(), vec![1]);
+        assert!(text_field.view.undo());
+        assert_eq!(text_field.label().text().string(), "e\u{301}x");
+    }
+
+    #[test]
+    fn test_formatter() {
+        use crate::ui::formatter::NumberFormatter;
+        use crate::ui::localization::Locale;
+
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "1234.5".to_string());
+        let behavior = text_field.behavior();
+
+        let rejected = Rc::new(RefCell::new(Vec::new()));
+        let rejected_clone = rejected.clone();
+        text_field.on_input_rejected(move |_, input| rejected_clone.borrow_mut().push(input.to_string()));
+
+        let formatter = NumberFormatter { locale: Locale::new("en-US"), fraction_digits: 2, minimum: Some(0.0), maximum: None };
+        text_field.set_formatter(Some(Rc::new(formatter)));
+        assert_eq!(text_field.label().text().string(), "1,234.50");
+
+        // Editing starts from the number without grouping separators, and
+        // edits that wouldn't leave a number are rejected.
+        behavior.did_become_first_responder();
+        assert_eq!(text_field.label().text().string(), "1234.50");
+        behavior.text_input_did_receive("1");
+        behavior.text_input_did_receive("-");
+        assert_eq!(text_field.label().text().string(), "1234.50");
+        assert_eq!(*rejected.borrow(), vec!["1", "-"]);
+
+        let press = Press::new(Key::new(KeyCode::Backspace, vec![]));
+        behavior.press_began(&press);
+        behavior.press_ended(&press);
+        behavior.text_input_did_receive("7");
+        assert_eq!(text_field.label().text().string(), "1234.57");
+
+        behavior.did_resign_first_responder();
+        assert_eq!(text_field.label().text().string(), "1,234.57");
+
+        assert_eq!(text_field.text_after_changes(&[(0..1, "9".to_string()), (8..8, "!".to_string())]), "9,234.57!");
+    }
+
+    #[test]
+    fn test_misspelled_words() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "teh cat".to_string());
+        let label = text_field.label();
+
+        label.set_misspelled_ranges(&[0..3]);
+        assert_eq!(label.misspelled_range_at_index(1), Some(0..3));
+        assert_eq!(label.misspelled_range_at_index(4), None);
+
+        // A correction only replaces the word if it's still there.
+        text_field.replace_word(0..3, "tha", "the");
+        assert_eq!(label.text().string(), "teh cat");
+        text_field.replace_word(0..3, "teh", "the");
+        assert_eq!(label.text().string(), "the cat");
+
+        // A check finishing after the text was edited again is dropped.
+        let generation = text_field.behavior().spell_check_generation.get();
+        text_field.check_spelling();
+        text_field.spell_check_did_finish(generation, &[0..3]);
+        assert_eq!(label.misspelled_range_at_index(0), None);
+        text_field.spell_check_did_finish(generation + 1, &[0..3]);
+        assert_eq!(label.misspelled_range_at_index(0), Some(0..3));
+
+        // Without a spell checker, nothing is underlined.
+        label.set_misspelled_ranges(&[4..7]);
+        text_field.set_checks_spelling(false);
+        assert!(!text_field.checks_spelling());
+        assert_eq!(label.misspelled_range_at_index(4), None);
+    }
+
+    #[test]
+    fn test_key_repeat() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        text_field.set_pasteboard(Pasteboard::named("test_key_repeat"));
+        let behavior = text_field.behavior();
+
+        behavior.text_input_did_receive("hello");
+
+        let press = Press::new(Key::new(KeyCode::Backspace, vec![]));
+        behavior.press_began(&press);
+        press.set_repeat_count(1);
+        behavior.press_repeated(&press);
+        press.set_repeat_count(2);
+        behavior.press_repeated(&press);
+        behavior.press_ended(&press);
+        assert_eq!(text_field.label().text().string(), "he");
+
+        let press = Press::new(Key::new(KeyCode::Left, vec![]));
+        behavior.press_began(&press);
+        press.set_repeat_count(1);
+        behavior.press_repeated(&press);
+        behavior.press_ended(&press);
+        assert_eq!(text_field.carat_indexes(), vec![0]);
+
+        // Shortcuts don't repeat.
+        text_field.pasteboard().set_string("!");
+        let press = Press::new(Key::new(KeyCode::V, vec![ModifierFlag::Control]));
+        behavior.press_began(&press);
+        press.set_repeat_count(1);
+        behavior.press_repeated(&press);
+        behavior.press_ended(&press);
+        assert_eq!(text_field.label().text().string(), "!he");
+    }
+
+    #[test]
+    fn test_presses_reach_press_methods() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "ab".to_string());
+        let behavior = text_field.behavior();
+
+        let shift = Press::new(Key::new(KeyCode::LShift, vec![ModifierFlag::Shift]));
+        let right = Press::new(Key::new(KeyCode::Right, vec![ModifierFlag::Shift]));
+        behavior.presses_began(&[shift.clone(), right.clone()]);
+        assert_eq!(behavior.holding_shift.get(), 1);
+        assert_eq!(text_field.carat_snapshots(), vec![CaratSnapshot::new(1, Some(0..1))]);
+
+        // Cancelled presses end, so the text field doesn't think Shift is
+        // still held.
+        behavior.presses_cancelled(&[shift, right]);
+        assert_eq!(behavior.holding_shift.get(), 0);
+    }
+
+    #[test]
+    fn test_carat_timer_ends_with_text_field() {
+        let frame = Rectangle::new(0, 0, 100, 100);
+        let text_field = TextField::new(frame, "".to_string());
+        let timer = text_field.behavior().carat_animation_timer.borrow().clone().unwrap();
+
+        assert!(timer.is_valid());
+        drop(text_field);
+        assert!(!timer.is_valid());
+    }
+
+    #[test]
+    fn test_content_insets() {
+        let text_field = TextField::new(Rectangle::new(0, 0, 200, 40), "".to_string());
+        assert_eq!(text_field.content_insets(), EdgeInsets::uniform(8));
+        assert_eq!(text_field.label().view.frame(), Rectangle::new(8, 8, 184, 24));
+
+        text_field.set_content_insets(EdgeInsets::symmetric(12, 4));
+        assert_eq!(text_field.label().view.frame(), Rectangle::new(12, 4, 176, 32));
+
+        // The label keeps inside the insets as the text field is resized.
+        text_field.view.set_frame(Rectangle::new(0, 0, 300, 40));
+        assert_eq!(text_field.label().view.frame(), Rectangle::new(12, 4, 276, 32));
+    }
 }
@@ -0,0 +1,663 @@
+use crate::graphics::{Rectangle, Size};
+use crate::ui::view::{AutoresizingMask, DefaultBehavior, ScrollView, TextField, WeakView};
+use crate::ui::view::find_bar::{FindBar, FIND_BAR_HEIGHT};
+use crate::ui::history::text_field::carat_snapshot::CaratSnapshot;
+use crate::ui::Color;
+use crate::ui::accessibility::{AccessibilityAction, AccessibilityRole};
+use crate::ui::command::Command;
+use crate::ui::key::{KeyCode, ModifierFlag};
+use crate::ui::key_command::KeyCommand;
+use crate::ui::localization;
+use crate::macros::*;
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The space between the edges of the text field and its label, matching
+/// `TextField`.
+const LABEL_PADDING: u32 = 8;
+
+/// The width kept clear on the right for the vertical scroll bar.
+const SCROLL_BAR_WIDTH: u32 = 10;
+
+/// The background of the matches of a search.
+const MATCH_COLOR: Color = Color::new(255, 214, 10, 96);
+
+/// The background of the selected match of a search.
+const CURRENT_MATCH_COLOR: Color = Color::new(255, 149, 0, 192);
+
+// A multi-line text editor.
+//
+// A `TextView` is a `TextField` in a `ScrollView`. The text wraps at the width
+// of the view, and the text field grows as lines are added so the text can be
+// scrolled vertically. Editing scrolls to keep the carat visible.
+//
+// Carats, selections, the pasteboard, undo and the editing callbacks all work
+// as they do in a `TextField`, and are used through `text_field()`.
+//
+// Command+F (or Control+F) shows a find bar above the text, see `FindBar`.
+// Every match of the search is highlighted as it's typed, Command+G and
+// Command+Shift+G select the next and previous match, and matches can be
+// replaced one at a time or all at once, each in a step that can be undone.
+custom_view!(
+    TextView subclasses DefaultBehavior
+
+    struct TextViewBehavior {
+        // What's being searched for, empty if nothing is.
+        find_query: RefCell<String>,
+
+        // The ranges of the search's matches, in the text they were found
+        // in. The text is searched again when it's edited.
+        find_matches: RefCell<Vec<Range<usize>>>,
+        searched_text: RefCell<String>,
+
+        // The index in `find_matches` of the match that's selected.
+        current_match: Cell<Option<usize>>
+    }
+
+    impl Self {
+        pub fn new(frame: Rectangle<i32, u32>, text: String) -> TextView {
+            let text_view = Self::new_all(
+                frame.clone(),
+                RefCell::new(String::new()),
+                RefCell::new(Vec::new()),
+                RefCell::new(String::new()),
+                Cell::new(None)
+            );
+            text_view.view.set_background_color(Color::clear());
+
+            let size = frame.size;
+            let scroll_view = ScrollView::new(Rectangle::new(0, 0, size.width, size.height));
+            let text_field = TextField::new(
+                Rectangle::new(0, 0, size.width.saturating_sub(SCROLL_BAR_WIDTH), size.height),
+                text
+            );
+            text_field.label().set_number_of_lines(0);
+
+            scroll_view.set_content_view(text_field.view.clone());
+            text_view.view.add_subview(scroll_view.view.clone());
+
+            let weak_text_view = text_view.view.downgrade();
+            text_field.on_layout(move |_| {
+                if let Some(view) = weak_text_view.upgrade() {
+                    let text_view = TextView::from_view(view);
+                    text_view.refresh_find_matches();
+                    text_view.fit_text_field();
+                    text_view.scroll_to_carat();
+                }
+            });
+
+            let find_bar = FindBar::new(Rectangle::new(0, 0, size.width, FIND_BAR_HEIGHT), text_view.view.downgrade());
+            find_bar.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth]);
+            find_bar.view.set_hidden(true);
+            text_view.view.add_subview(find_bar.view.clone());
+
+            let weak_text_view = text_view.view.downgrade();
+            let key_commands = [
+                (KeyCode::F, vec![ModifierFlag::Command], "Find", action_with_text_view(&weak_text_view, TextView::show_find_bar)),
+                (KeyCode::G, vec![ModifierFlag::Command], "Find Next", action_with_text_view(&weak_text_view, TextView::find_next)),
+                (KeyCode::G, vec![ModifierFlag::Command, ModifierFlag::Shift], "Find Previous", action_with_text_view(&weak_text_view, TextView::find_previous))
+            ];
+            for (key_code, modifier_flags, title, action) in key_commands {
+                text_view.view.add_key_command(KeyCommand::new(key_code, &modifier_flags, action).titled(&localization::localized(title)));
+            }
+
+            text_view.fit_text_field();
+            text_view
+        }
+
+        /// The scroll view containing the text field.
+        pub fn scroll_view(&self) -> ScrollView {
+            let view = self.view.subviews().first().unwrap().clone();
+            ScrollView::from_view(view)
+        }
+
+        /// The text field being edited.
+        pub fn text_field(&self) -> TextField {
+            let view = self.scroll_view().content_view().unwrap();
+            TextField::from_view(view)
+        }
+
+        /// The text being edited.
+        pub fn text(&self) -> String {
+            self.text_field().label().copy_text()
+        }
+
+        /// Replaces the text, putting a single carat at the end. Replacing
+        /// the text can't be undone, and clears the undo history.
+        pub fn set_text(&self, text: String) {
+            let text_field = self.text_field();
+            let label = text_field.label();
+            label.set_text(text);
+            text_field.restore_carat_snapshots(&vec![CaratSnapshot::new(label.text_len(), None)]);
+            text_field.check_spelling();
+
+            if let Some(undo_manager) = text_field.view.undo_manager() {
+                undo_manager.borrow_mut().clear();
+            }
+
+            self.refresh_find_matches();
+            self.fit_text_field();
+            self.scroll_to_carat();
+        }
+
+        /// The bar for finding and replacing text, shown above the text with
+        /// `show_find_bar`.
+        pub fn find_bar(&self) -> FindBar {
+            FindBar::from_view(self.view.subviews()[1].clone())
+        }
+
+        pub fn is_find_bar_visible(&self) -> bool {
+            !self.find_bar().view.is_hidden()
+        }
+
+        /// Shows the find bar and moves the focus to its search field, with
+        /// the search selected so it can be typed over. The search is
+        /// highlighted again if there is one. Pressing Command+F (or
+        /// Control+F) in the text view calls this.
+        pub fn show_find_bar(&self) {
+            let find_bar = self.find_bar();
+            if !self.is_find_bar_visible() {
+                find_bar.view.set_hidden(false);
+                self.layout_find_bar();
+            }
+
+            let find_field = find_bar.find_field();
+            find_field.view.become_first_responder();
+            find_field.view.perform_command(&Command::SelectAll);
+            self.find(&find_bar.query());
+        }
+
+        /// Hides the find bar, clears the highlighted matches and moves the
+        /// focus back to the text. What's typed in the bar is kept for the
+        /// next time it's shown.
+        pub fn hide_find_bar(&self) {
+            if !self.is_find_bar_visible() {
+                return;
+            }
+
+            self.find_bar().view.set_hidden(true);
+            self.layout_find_bar();
+            self.find("");
+            self.text_field().view.become_first_responder();
+        }
+
+        /// Searches the text for `query`, ignoring case, highlighting every
+        /// match and selecting the first one from the selection or carat
+        /// on, wrapping around to the start. An empty query ends the search.
+        ///
+        /// Typing in the find bar's search field calls this as you type, so
+        /// the selection stays on the same match while it still matches.
+        pub fn find(&self, query: &str) {
+            self.behavior().find_query.replace(query.to_string());
+            self.update_find_matches();
+
+            if self.behavior().current_match.get().is_none() {
+                let start = self.selection_range().start;
+                if let Some(index) = self.match_index_after(start) {
+                    self.select_match(index);
+                }
+            }
+        }
+
+        /// Selects the match after the selected one, or after the carat,
+        /// wrapping around to the first match. Return in the find bar's
+        /// search field, and Command+G (or Control+G), call this.
+        pub fn find_next(&self) {
+            self.refresh_find_matches();
+
+            let behavior = self.behavior();
+            let count = behavior.find_matches.borrow().len();
+            let index = match behavior.current_match.get() {
+                Some(current) => Some((current + 1) % count),
+                None => self.match_index_after(self.selection_range().end)
+            };
+
+            if let Some(index) = index {
+                self.select_match(index);
+            }
+        }
+
+        /// Selects the match before the selected one, or before the carat,
+        /// wrapping around to the last match. Command+Shift+G (or
+        /// Control+Shift+G) calls this.
+        pub fn find_previous(&self) {
+            self.refresh_find_matches();
+
+            let behavior = self.behavior();
+            let count = behavior.find_matches.borrow().len();
+            let index = match behavior.current_match.get() {
+                Some(current) => Some((current + count - 1) % count),
+                None => self.match_index_before(self.selection_range().start)
+            };
+
+            if let Some(index) = index {
+                self.select_match(index);
+            }
+        }
+
+        /// Replaces the selected match with `replacement`, and selects the
+        /// next match. If no match is selected, the next one is selected
+        /// instead, to be replaced by calling this again. The replacement
+        /// can be undone.
+        pub fn replace(&self, replacement: &str) {
+            self.refresh_find_matches();
+
+            let behavior = self.behavior();
+            let current_match = behavior.current_match.get().map(|index| behavior.find_matches.borrow()[index].clone());
+            let range = match current_match {
+                Some(range) => range,
+                None => return self.find_next()
+            };
+
+            self.text_field().replace_text_in_ranges(vec![(range, replacement.to_string())]);
+            self.refresh_find_matches();
+            if let Some(index) = self.match_index_after(self.selection_range().end) {
+                self.select_match(index);
+            }
+        }
+
+        /// Replaces every match with `replacement`, leaving a carat at the
+        /// end of each replacement. The replacements are undone in one step.
+        pub fn replace_all(&self, replacement: &str) {
+            self.refresh_find_matches();
+
+            let replacements: Vec<(Range<usize>, String)> = self
+                .behavior()
+                .find_matches
+                .borrow()
+                .iter()
+                .map(|range| (range.clone(), replacement.to_string()))
+                .collect();
+
+            if !replacements.is_empty() {
+                self.text_field().replace_text_in_ranges(replacements);
+                self.refresh_find_matches();
+            }
+        }
+
+        /// The grapheme ranges of the matches of the search.
+        pub fn find_matches(&self) -> Vec<Range<usize>> {
+            self.refresh_find_matches();
+            self.behavior().find_matches.borrow().clone()
+        }
+
+        /// The range of the selected match of the search, if one is.
+        pub fn current_match(&self) -> Option<Range<usize>> {
+            self.refresh_find_matches();
+            let behavior = self.behavior();
+            behavior.current_match.get().map(|index| behavior.find_matches.borrow()[index].clone())
+        }
+
+        /// Searches the text again if it's changed since it was searched,
+        /// and forgets the selected match if it isn't selected anymore.
+        fn refresh_find_matches(&self) {
+            let behavior = self.behavior();
+            if behavior.find_query.borrow().is_empty() {
+                return;
+            }
+
+            if *behavior.searched_text.borrow() != self.text() {
+                self.update_find_matches();
+                return;
+            }
+
+            let current_match = behavior.current_match.get().map(|index| behavior.find_matches.borrow()[index].clone());
+            if current_match.is_some_and(|range| range != self.selection_range()) {
+                behavior.current_match.set(None);
+                self.update_find_highlights();
+            }
+        }
+
+        /// Searches the text for the query, keeping the match that's
+        /// selected, if there is one.
+        fn update_find_matches(&self) {
+            let behavior = self.behavior();
+            let text = self.text();
+            let matches = ranges_matching(&text, &behavior.find_query.borrow());
+
+            let selection = self.selection_range();
+            behavior.current_match.set(matches.iter().position(|range| *range == selection));
+            behavior.find_matches.replace(matches);
+            behavior.searched_text.replace(text);
+
+            self.update_find_highlights();
+        }
+
+        /// Highlights the matches, and shows how many there are in the find
+        /// bar.
+        fn update_find_highlights(&self) {
+            let behavior = self.behavior();
+            let matches = behavior.find_matches.borrow();
+            let current_match = behavior.current_match.get();
+
+            let highlights: Vec<(Range<usize>, Color)> = matches
+                .iter()
+                .enumerate()
+                .map(|(index, range)| {
+                    let color = if current_match == Some(index) { CURRENT_MATCH_COLOR } else { MATCH_COLOR };
+                    (range.clone(), color)
+                })
+                .collect();
+            self.text_field().label().set_highlighted_ranges(&highlights);
+
+            let count = matches.len().to_string();
+            let status = match current_match {
+                _ if behavior.find_query.borrow().is_empty() => String::new(),
+                _ if matches.is_empty() => localization::localized("Not Found"),
+                Some(index) => localization::localized_format("{0} of {1}", &[&(index + 1).to_string(), &count]),
+                None => localization::localized_format("{0} Found", &[&count])
+            };
+            self.find_bar().set_status(&status);
+        }
+
+        /// Selects the match at `index` in the matches, and scrolls to it.
+        fn select_match(&self, index: usize) {
+            let behavior = self.behavior();
+            let range = behavior.find_matches.borrow()[index].clone();
+            behavior.current_match.set(Some(index));
+
+            self.text_field().restore_carat_snapshots(&vec![CaratSnapshot::new(range.end, Some(range))]);
+            self.update_find_highlights();
+            self.scroll_to_carat();
+        }
+
+        /// The index of the first match starting at or after the grapheme
+        /// `index`, wrapping around to the first match.
+        fn match_index_after(&self, index: usize) -> Option<usize> {
+            let behavior = self.behavior();
+            let matches = behavior.find_matches.borrow();
+            if matches.is_empty() {
+                return None;
+            }
+
+            Some(matches.iter().position(|range| range.start >= index).unwrap_or(0))
+        }
+
+        /// The index of the last match ending at or before the grapheme
+        /// `index`, wrapping around to the last match.
+        fn match_index_before(&self, index: usize) -> Option<usize> {
+            let behavior = self.behavior();
+            let matches = behavior.find_matches.borrow();
+            if matches.is_empty() {
+                return None;
+            }
+
+            Some(matches.iter().rposition(|range| range.end <= index).unwrap_or(matches.len() - 1))
+        }
+
+        /// The selection of the first carat, or an empty range at the carat
+        /// if nothing is selected.
+        fn selection_range(&self) -> Range<usize> {
+            match self.text_field().carat_snapshots().first() {
+                Some(carat) => match carat.selection() {
+                    Some(selection) => selection.clone(),
+                    None => carat.character_index()..carat.character_index()
+                },
+                None => 0..0
+            }
+        }
+
+        /// Makes room for the find bar above the text while it's visible.
+        fn layout_find_bar(&self) {
+            let size = self.view.frame().size;
+            let top = if self.is_find_bar_visible() { FIND_BAR_HEIGHT } else { 0 };
+            self.scroll_view().view.set_frame(Rectangle::new(0, top as i32, size.width, size.height.saturating_sub(top)));
+
+            self.fit_text_field();
+            self.scroll_to_carat();
+        }
+
+        /// Resizes the text field to fit its text, wrapping at the width of
+        /// the scroll view, and at least as tall as the scroll view.
+        fn fit_text_field(&self) {
+            let size = self.scroll_view().view.frame().size;
+            let width = size.width.saturating_sub(SCROLL_BAR_WIDTH);
+            let label_width = width.saturating_sub(LABEL_PADDING * 2);
+
+            let text_field = self.text_field();
+            let label = text_field.label();
+            let text_height = label.size_that_fits(label_width).height;
+            let height = size.height.max(text_height + LABEL_PADDING * 2);
+
+            label.view.set_frame(Rectangle::new(
+                LABEL_PADDING as i32,
+                LABEL_PADDING as i32,
+                label_width,
+                height - LABEL_PADDING * 2
+            ));
+
+            self.scroll_view().set_content_size(Size::new(width, height));
+        }
+
+        /// Scrolls so the last carat, and the padding around it, is visible.
+        fn scroll_to_carat(&self) {
+            let text_field = self.text_field();
+            let index = match text_field.carat_indexes().last() {
+                Some(index) => *index,
+                None => return
+            };
+
+            let carat = text_field.carat_rectangle(index);
+            self.scroll_view().scroll_rectangle_to_visible(Rectangle::new(
+                carat.origin.x,
+                carat.origin.y - LABEL_PADDING as i32,
+                carat.size.width,
+                carat.size.height + LABEL_PADDING * 2
+            ));
+        }
+    }
+
+    impl Behavior {
+        fn accessibility_role(&self) -> AccessibilityRole {
+            AccessibilityRole::TextArea
+        }
+
+        fn accessibility_value(&self) -> Option<String> {
+            Some(self.view_type().text())
+        }
+
+        /// Screen readers focus and set the text of the text field, so
+        /// typing goes to it and setting the text can be undone.
+        fn perform_accessibility_action(&self, action: &AccessibilityAction) -> bool {
+            self.view_type().text_field().view.perform_accessibility_action(action)
+        }
+    }
+);
+
+/// A closure calling `action` with the text view `text_view`, while it's
+/// still around.
+pub(crate) fn action_with_text_view(text_view: &WeakView, action: fn(&TextView)) -> impl Fn() + 'static {
+    let text_view = text_view.clone();
+    move || {
+        if let Some(view) = text_view.upgrade() {
+            action(&TextView::from_view(view));
+        }
+    }
+}
+
+/// The grapheme ranges of the matches of `query` in `text`, ignoring case.
+/// Matches don't overlap, the earlier of two overlapping matches winning.
+fn ranges_matching(text: &str, query: &str) -> Vec<Range<usize>> {
+    let folded = |string: &str| -> Vec<String> {
+        string.graphemes(true).map(str::to_lowercase).collect()
+    };
+    let text = folded(text);
+    let query = folded(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start + query.len() <= text.len() {
+        if text[start..start + query.len()] == query[..] {
+            ranges.push(start..start + query.len());
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::view::behavior::Behavior;
+    use crate::ui::key::{Key, KeyCode, ModifierFlag};
+    use crate::ui::press::Press;
+    use crate::graphics::Point;
+    use crate::text::attributed_string::{Attribute, Key as AttributeKey};
+    use crate::graphics::Color as GraphicsColor;
+
+    #[test]
+    fn test_text_view_grows_and_scrolls() {
+        let text_view = TextView::new(Rectangle::new(0, 0, 200, 100), "".to_string());
+        let text_field = text_view.text_field();
+        let behavior = text_field.behavior();
+
+        assert_eq!(text_field.view.frame().size, Size::new(190, 100));
+        assert_eq!(text_view.scroll_view().content_offset(), Point::new(0, 0));
+
+        behavior.text_input_did_receive("first line");
+        let press = Press::new(Key::new(KeyCode::Return, vec![]));
+        for _ in 0..20 {
+            behavior.press_began(&press);
+            behavior.press_ended(&press);
+        }
+        behavior.text_input_did_receive("last line");
+
+        assert_eq!(text_view.text(), format!("first line{}last line", "\n".repeat(20)));
+        assert!(text_field.view.frame().size.height > 100);
+
+        // The end of the text is scrolled into view.
+        let offset = text_view.scroll_view().content_offset();
+        let carat = text_field.carat_rectangle(text_field.label().text_len());
+        assert!(offset.y > 0);
+        assert!(carat.origin.y >= offset.y);
+        assert!(carat.origin.y + carat.size.height as i32 <= offset.y + 100);
+
+        // Moving to the start of the text scrolls back up.
+        let press = Press::new(Key::new(KeyCode::Home, vec![ModifierFlag::Control]));
+        behavior.press_began(&press);
+        behavior.press_ended(&press);
+        assert_eq!(text_view.scroll_view().content_offset(), Point::new(0, 0));
+
+        text_view.set_text("short".to_string());
+        assert_eq!(text_field.view.frame().size, Size::new(190, 100));
+        assert_eq!(text_field.carat_indexes(), vec![5]);
+        assert!(!text_field.view.undo());
+    }
+
+    #[test]
+    fn test_ranges_matching() {
+        assert_eq!(ranges_matching("One two one", "one"), vec![0..3, 8..11]);
+        assert_eq!(ranges_matching("aaaaa", "aa"), vec![0..2, 2..4]);
+        assert_eq!(ranges_matching("é👨‍👩‍👧É", "é"), vec![0..1, 2..3]);
+        assert!(ranges_matching("one", "").is_empty());
+        assert!(ranges_matching("on", "one").is_empty());
+    }
+
+    #[test]
+    fn test_find_and_replace() {
+        let text_view = TextView::new(Rectangle::new(0, 0, 400, 200), "one two One two one".to_string());
+        let text_field = text_view.text_field();
+        let label = text_field.label();
+        let highlight_color = |index: usize| *label.attributed_text().get_attribute_for(index, AttributeKey::Highlight).highlight_color();
+        let background_color = |index: usize| *label.attributed_text().get_attribute_for(index, AttributeKey::BackgroundColor).background_color();
+        text_field.restore_carat_snapshots(&vec![CaratSnapshot::new(4, None)]);
+
+        // The text's own background colors are kept through searches.
+        let background = GraphicsColor::RGBA(0, 128, 255, 255);
+        let attributed_text = label.attributed_text();
+        attributed_text.set_attribute_in_range(4..7, AttributeKey::BackgroundColor, Attribute::BackgroundColor { color: background });
+        label.set_attributed_text(attributed_text);
+
+        // The first match from the carat on is selected, and every match is
+        // highlighted.
+        text_view.find("one");
+        assert_eq!(text_view.find_matches(), vec![0..3, 8..11, 16..19]);
+        assert_eq!(text_view.current_match(), Some(8..11));
+        assert_eq!(text_field.carat_snapshots(), vec![CaratSnapshot::new(11, Some(8..11))]);
+        assert_eq!(text_view.find_bar().status_label().text().string(), "2 of 3");
+        assert_eq!(highlight_color(8), CURRENT_MATCH_COLOR.to_graphics_color());
+        assert_eq!(highlight_color(0), MATCH_COLOR.to_graphics_color());
+        assert_eq!(highlight_color(4).a, 0);
+        assert_eq!(background_color(4), background);
+
+        text_view.find_next();
+        assert_eq!(text_view.current_match(), Some(16..19));
+        text_view.find_next();
+        assert_eq!(text_view.current_match(), Some(0..3));
+        text_view.find_previous();
+        assert_eq!(text_view.current_match(), Some(16..19));
+
+        // Replacing selects the next match, and can be undone.
+        text_view.replace("1");
+        assert_eq!(text_view.text(), "one two One two 1");
+        assert_eq!(text_view.current_match(), Some(0..3));
+        assert!(text_field.view.undo());
+        assert_eq!(text_view.text(), "one two One two one");
+        assert_eq!(text_view.find_matches().len(), 3);
+
+        // Replacing all of them is undone in one step.
+        text_view.replace_all("1");
+        assert_eq!(text_view.text(), "1 two 1 two 1");
+        assert!(text_view.find_matches().is_empty());
+        assert_eq!(text_view.find_bar().status_label().text().string(), "Not Found");
+        assert!(text_field.view.undo());
+        assert_eq!(text_view.text(), "one two One two one");
+
+        // Moving the carat away deselects the match.
+        text_view.find_next();
+        assert!(text_view.current_match().is_some());
+        text_field.restore_carat_snapshots(&vec![CaratSnapshot::new(4, None)]);
+        assert_eq!(text_view.current_match(), None);
+        assert_eq!(text_view.find_bar().status_label().text().string(), "3 Found");
+
+        text_view.find("");
+        assert!(text_view.find_matches().is_empty());
+        assert_eq!(highlight_color(0).a, 0);
+        assert_eq!(background_color(4), background);
+    }
+
+    #[test]
+    fn test_find_bar() {
+        let text_view = TextView::new(Rectangle::new(0, 0, 400, 200), "cat hat".to_string());
+        let text_field = text_view.text_field();
+        let find_bar = text_view.find_bar();
+        assert!(!text_view.is_find_bar_visible());
+
+        let find = Key::new(KeyCode::F, vec![ModifierFlag::Control]);
+        text_field.view.key_command_matching(&find).unwrap().perform();
+        assert!(text_view.is_find_bar_visible());
+        assert_eq!(text_view.scroll_view().view.frame(), Rectangle::new(0, FIND_BAR_HEIGHT as i32, 400, 200 - FIND_BAR_HEIGHT));
+
+        // Typing in the search field searches as you type.
+        find_bar.find_field().behavior().text_input_did_receive("at");
+        assert_eq!(text_view.find_matches(), vec![1..3, 5..7]);
+        assert_eq!(text_view.current_match(), Some(1..3));
+
+        // Return in the replacement field replaces the selected match.
+        let replace_field = find_bar.replace_field();
+        replace_field.behavior().text_input_did_receive("og");
+        let press = Press::new(Key::new(KeyCode::Return, vec![]));
+        replace_field.behavior().press_began(&press);
+        replace_field.behavior().press_ended(&press);
+        assert_eq!(text_view.text(), "cog hat");
+        assert_eq!(text_view.current_match(), Some(5..7));
+
+        let find_previous = Key::new(KeyCode::G, vec![ModifierFlag::Command, ModifierFlag::Shift]);
+        find_bar.find_field().view.key_command_matching(&find_previous).unwrap().perform();
+        assert_eq!(text_view.current_match(), Some(5..7));
+
+        // Escape closes the bar, and the matches are no longer highlighted.
+        let escape = Key::new(KeyCode::Escape, vec![]);
+        find_bar.find_field().view.key_command_matching(&escape).unwrap().perform();
+        assert!(!text_view.is_find_bar_visible());
+        assert_eq!(text_view.scroll_view().view.frame(), Rectangle::new(0, 0, 400, 200));
+        assert!(text_view.find_matches().is_empty());
+        assert_eq!(find_bar.query(), "at");
+    }
+}
@@ -0,0 +1,355 @@
+use crate::graphics::{Point, Rectangle};
+use crate::ui::timer::{Timer, TimerHandle};
+use crate::ui::view::{View, WeakView};
+use crate::ui::Tooltip;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// How long the pointer rests on a view before its tooltip is shown.
+const TOOLTIP_DELAY: Duration = Duration::from_millis(800);
+
+/// What a `TrackingArea` tells its view about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrackingOption {
+    /// Calls `Behavior::mouse_entered` and `Behavior::mouse_exited` as the
+    /// pointer moves in and out of the area.
+    MouseEnteredAndExited,
+
+    /// Calls `Behavior::mouse_moved` as the pointer moves within the area.
+    MouseMoved,
+
+    /// The area is the whole view, whatever its size, rather than the
+    /// rectangle it was made with.
+    InVisibleRect
+}
+
+/// A part of a view that tells the view as the pointer moves over it, such
+/// as for a button to show it's under the pointer. Added to a view with
+/// `View::add_tracking_area`.
+///
+/// Only views under the pointer are told; an area covered by another view,
+/// other than the view's own subviews, isn't entered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackingArea {
+    rectangle: Rectangle<i32, u32>,
+    options: Vec<TrackingOption>
+}
+
+impl TrackingArea {
+    /// An area covering `rectangle`, relative to the top left of the view.
+    pub fn new(rectangle: Rectangle<i32, u32>, options: &[TrackingOption]) -> TrackingArea {
+        TrackingArea {
+            rectangle,
+            options: options.to_vec()
+        }
+    }
+
+    /// An area covering the whole view, following it as it's resized.
+    pub fn visible_rect(options: &[TrackingOption]) -> TrackingArea {
+        let mut options = options.to_vec();
+        options.push(TrackingOption::InVisibleRect);
+        TrackingArea::new(Rectangle::new(0, 0, 0, 0), &options)
+    }
+
+    pub fn rectangle(&self) -> Rectangle<i32, u32> {
+        self.rectangle.clone()
+    }
+
+    pub fn options(&self) -> &[TrackingOption] {
+        &self.options
+    }
+
+    pub fn has_option(&self, option: TrackingOption) -> bool {
+        self.options.contains(&option)
+    }
+
+    /// Whether `point`, relative to the top left of `view`, is in the area.
+    fn contains(&self, point: &Point<i32>, view: &View) -> bool {
+        if self.has_option(TrackingOption::InVisibleRect) {
+            let size = view.frame().size;
+            Rectangle { origin: Point::new(0, 0), size }.contains_point(point)
+        } else {
+            self.rectangle.contains_point(point)
+        }
+    }
+}
+
+/// The tooltip of the view the pointer is over, waiting for the pointer to
+/// rest there long enough to be shown.
+struct HoveredTooltip {
+    source: WeakView,
+    timer: RefCell<Option<TimerHandle>>,
+    tooltip: RefCell<Option<Tooltip>>
+}
+
+impl HoveredTooltip {
+    /// Starts waiting to show the tooltip of `source`.
+    fn new(source: &View) -> Rc<HoveredTooltip> {
+        let hovered = Rc::new(HoveredTooltip {
+            source: source.downgrade(),
+            timer: RefCell::new(None),
+            tooltip: RefCell::new(None)
+        });
+
+        let weak_hovered = Rc::downgrade(&hovered);
+        let timer = Timer::after(TOOLTIP_DELAY, move || {
+            if let Some(hovered) = weak_hovered.upgrade() {
+                hovered.show();
+            }
+        });
+        hovered.timer.replace(Some(timer));
+        hovered
+    }
+
+    fn show(&self) {
+        if self.tooltip.borrow().is_some() {
+            return;
+        }
+
+        let source = match self.source.upgrade() {
+            Some(source) => source,
+            None => return
+        };
+
+        if let Some(text) = source.tooltip() {
+            let tooltip = Tooltip::new(&text);
+            tooltip.present_from(&source);
+            self.tooltip.replace(Some(tooltip));
+        }
+    }
+
+    /// Stops waiting, and hides the tooltip if it was shown.
+    fn hide(&self) {
+        if let Some(timer) = self.timer.take() {
+            timer.cancel();
+        }
+
+        if let Some(tooltip) = self.tooltip.take() {
+            tooltip.dismiss();
+        }
+    }
+}
+
+/// Keeps track of the tracking areas the pointer is in, to tell their views
+/// as it enters and leaves them, and shows the tooltip of the view it rests
+/// on.
+pub(crate) struct HoverTracker {
+    // The view and index of each tracking area the pointer is in.
+    areas: Vec<(WeakView, usize)>,
+
+    // The tooltip of the view the pointer is over, shown or waiting to be.
+    tooltip: Option<Rc<HoveredTooltip>>
+}
+
+impl HoverTracker {
+    pub(crate) const fn new() -> HoverTracker {
+        HoverTracker {
+            areas: Vec::new(),
+            tooltip: None
+        }
+    }
+
+    /// The pointer moved to `position`, relative to the top left of `root`,
+    /// usually a window. Views whose areas the pointer left are told first,
+    /// then those it entered, then those it moved within.
+    pub(crate) fn pointer_moved(&mut self, root: &View, position: &Point<i32>) {
+        let mut areas: Vec<(View, usize, Point<i32>)> = Vec::new();
+        let mut tooltip_source: Option<View> = None;
+
+        // The view under the pointer and its superviews; views covered by
+        // others aren't hovered. Every view along the way is looked at, so
+        // nothing is returned to stop at one.
+        root.first_in_responder_path(position, |view, point| {
+            for (index, area) in view.tracking_areas().iter().enumerate() {
+                if area.contains(point, view) {
+                    areas.push((view.clone(), index, point.clone()));
+                }
+            }
+            if tooltip_source.is_none() && view.tooltip().is_some() {
+                tooltip_source = Some(view.clone());
+            }
+            None::<()>
+        });
+
+        self.hover_tooltip(tooltip_source.as_ref());
+
+        let previous_areas: Vec<(View, usize)> = std::mem::take(&mut self.areas).into_iter()
+            .filter_map(|(view, index)| view.upgrade().map(|view| (view, index)))
+            .collect();
+
+        for (view, index) in previous_areas.iter() {
+            let is_still_in = areas.iter().any(|(area_view, area_index, _)| area_view == view && area_index == index);
+            if !is_still_in && view.tracking_area_has_option(*index, TrackingOption::MouseEnteredAndExited) {
+                view.mouse_exited();
+            }
+        }
+
+        for (view, index, point) in areas.iter() {
+            let was_in = previous_areas.iter().any(|(area_view, area_index)| area_view == view && area_index == index);
+            if !was_in && view.tracking_area_has_option(*index, TrackingOption::MouseEnteredAndExited) {
+                view.mouse_entered(point);
+            }
+        }
+
+        for (view, index, point) in areas.iter() {
+            if view.tracking_area_has_option(*index, TrackingOption::MouseMoved) {
+                view.mouse_moved(point);
+            }
+        }
+
+        self.areas = areas.into_iter().map(|(view, index, _)| (view.downgrade(), index)).collect();
+    }
+
+    /// The pointer left the window, or the views were taken away. Every
+    /// area it was in is exited, and the tooltip shown is hidden.
+    pub(crate) fn pointer_exited(&mut self) {
+        self.hover_tooltip(None);
+
+        for (view, index) in std::mem::take(&mut self.areas) {
+            if let Some(view) = view.upgrade() {
+                if view.tracking_area_has_option(index, TrackingOption::MouseEnteredAndExited) {
+                    view.mouse_exited();
+                }
+            }
+        }
+    }
+
+    /// The pointer is over `source`, the nearest view under it with a
+    /// tooltip. Moving onto another view hides the tooltip shown, and starts
+    /// waiting to show the new view's.
+    fn hover_tooltip(&mut self, source: Option<&View>) {
+        let is_same_source = match (&self.tooltip, source) {
+            (Some(hovered), Some(source)) => hovered.source.upgrade().as_ref() == Some(source),
+            (None, None) => true,
+            _ => false
+        };
+        if is_same_source {
+            return;
+        }
+
+        if let Some(hovered) = self.tooltip.take() {
+            hovered.hide();
+        }
+        self.tooltip = source.map(HoveredTooltip::new);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::view::DefaultBehavior;
+    use crate::macros::*;
+    use std::cell::RefCell;
+
+    custom_view!(
+        HoverView subclasses DefaultBehavior
+
+        struct HoverViewBehavior {
+            events: RefCell<Vec<String>>
+        }
+
+        impl Behavior {
+            fn mouse_entered(&self, position: &Point<i32>) {
+                self.events.borrow_mut().push(format!("entered {},{}", position.x, position.y));
+            }
+
+            fn mouse_exited(&self) {
+                self.events.borrow_mut().push(String::from("exited"));
+            }
+
+            fn mouse_moved(&self, position: &Point<i32>) {
+                self.events.borrow_mut().push(format!("moved {},{}", position.x, position.y));
+            }
+        }
+    );
+
+    fn take_events(view: &HoverView) -> Vec<String> {
+        view.behavior().events.borrow_mut().drain(..).collect()
+    }
+
+    #[test]
+    fn test_pointer_moved() {
+        let root = View::new(Rectangle::new(0, 0, 200, 200));
+        let view = HoverView::new_all(Rectangle::new(50, 50, 100, 100), RefCell::new(Vec::new()));
+        view.view.add_tracking_area(TrackingArea::new(
+            Rectangle::new(0, 0, 20, 20),
+            &[TrackingOption::MouseEnteredAndExited, TrackingOption::MouseMoved]
+        ));
+        root.add_subview(view.view.clone());
+
+        let mut hover_tracker = HoverTracker::new();
+        hover_tracker.pointer_moved(&root, &Point::new(10, 10));
+        assert!(take_events(&view).is_empty());
+
+        hover_tracker.pointer_moved(&root, &Point::new(55, 60));
+        assert_eq!(take_events(&view), vec!["entered 5,10", "moved 5,10"]);
+
+        hover_tracker.pointer_moved(&root, &Point::new(60, 60));
+        assert_eq!(take_events(&view), vec!["moved 10,10"]);
+
+        // Still in the view, but out of its area.
+        hover_tracker.pointer_moved(&root, &Point::new(100, 100));
+        assert_eq!(take_events(&view), vec!["exited"]);
+
+        hover_tracker.pointer_moved(&root, &Point::new(55, 55));
+        hover_tracker.pointer_exited();
+        assert_eq!(take_events(&view), vec!["entered 5,5", "moved 5,5", "exited"]);
+    }
+
+    #[test]
+    fn test_covered_area_isnt_entered() {
+        let root = View::new(Rectangle::new(0, 0, 200, 200));
+        let view = HoverView::new_all(Rectangle::new(0, 0, 100, 100), RefCell::new(Vec::new()));
+        view.view.add_tracking_area(TrackingArea::visible_rect(&[TrackingOption::MouseEnteredAndExited]));
+        root.add_subview(view.view.clone());
+        root.add_subview(View::new(Rectangle::new(0, 0, 50, 50)));
+
+        let mut hover_tracker = HoverTracker::new();
+        hover_tracker.pointer_moved(&root, &Point::new(10, 10));
+        assert!(take_events(&view).is_empty());
+
+        hover_tracker.pointer_moved(&root, &Point::new(70, 70));
+        assert_eq!(take_events(&view), vec!["entered 70,70"]);
+    }
+
+    #[test]
+    fn test_tooltip_shown_on_hover() {
+        let root = View::new(Rectangle::new(0, 0, 200, 200));
+        let button = View::new(Rectangle::new(50, 50, 100, 30));
+        button.set_tooltip(Some("Saves the file"));
+        let icon = View::new(Rectangle::new(0, 0, 20, 20));
+        button.add_subview(icon);
+        root.add_subview(button.clone());
+
+        let mut hover_tracker = HoverTracker::new();
+        hover_tracker.pointer_moved(&root, &Point::new(10, 10));
+        assert!(hover_tracker.tooltip.is_none());
+
+        // Over a subview without a tooltip of its own, the tooltip isn't
+        // shown until the delay is up.
+        hover_tracker.pointer_moved(&root, &Point::new(55, 55));
+        let hovered = hover_tracker.tooltip.clone().unwrap();
+        assert_eq!(hovered.source.upgrade(), Some(button.clone()));
+        assert!(hovered.tooltip.borrow().is_none());
+        assert_eq!(root.subviews().len(), 1);
+
+        // Timers don't fire off the main thread, so it's shown as the
+        // timer would.
+        hovered.show();
+        let tooltip = hovered.tooltip.borrow().clone().unwrap();
+        assert_eq!(tooltip.text(), "Saves the file");
+        assert!(root.subviews().contains(&tooltip.view));
+
+        // Moving within the view keeps it shown, and leaving hides it.
+        hover_tracker.pointer_moved(&root, &Point::new(100, 60));
+        assert!(root.subviews().contains(&tooltip.view));
+        hover_tracker.pointer_moved(&root, &Point::new(10, 10));
+        assert!(!root.subviews().contains(&tooltip.view));
+        assert!(hover_tracker.tooltip.is_none());
+
+        hover_tracker.pointer_moved(&root, &Point::new(55, 55));
+        hover_tracker.pointer_exited();
+        assert!(hover_tracker.tooltip.is_none());
+    }
+}
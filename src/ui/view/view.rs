@@ -1,16 +1,37 @@
 use crate::ui::Color;
+use crate::ui::Shadow;
+use crate::ui::view::AutoresizingMask;
+use crate::ui::view::{TrackingArea, TrackingOption};
+use crate::ui::view::autoresizing::autoresized_frame;
 use crate::ui::Touch;
 use crate::ui::view::{WeakView, Behavior, DefaultBehavior, ViewInner};
-use crate::graphics::{Layer, Rectangle, Point, LayerDelegate};
+use crate::graphics::{Context, EdgeInsets, Gradient, Image, Layer, Rectangle, Point, LayerDelegate, Transform};
+use crate::ui::render;
+use crate::macros::trace_span;
 use std::rc::{Rc, Weak};
 use std::cell::RefCell;
 use std::cell::Ref;
 use crate::ui::gesture::recognizer::Recognizer;
-use crate::ui::event::{TouchEvent, PressEvent};
+use crate::ui::event::{TouchEvent, PressEvent, ScrollEvent};
 use crate::ui::window::Window;
 use crate::ui::press::Press;
+use crate::ui::menu::Menu;
+use crate::platform::cursor::Cursor;
+use crate::ui::command::Command;
+use crate::ui::key_command::{self, KeyCommand};
+use crate::ui::key::Key;
+use crate::ui::appearance::{self, Appearance, SemanticColor};
+use crate::ui::layout_direction::{self, LayoutDirection};
+use crate::ui::accessibility::{AccessibilityAction, AccessibilityRole};
+use crate::platform::history::UndoManager;
+use crate::ui::animation::{Animation, Easing};
+use std::time::Duration;
+use std::path::PathBuf;
 
 use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub(crate) type FilesDroppedCallback = Rc<dyn Fn(&View, &[PathBuf])>;
+
 fn next_id() -> usize {
     static COUNTER:AtomicUsize = AtomicUsize::new(1);
     COUNTER.fetch_add(1, Ordering::Relaxed)
@@ -53,15 +74,40 @@ impl View {
         let inner_self = ViewInner {
             id: next_id(),
             tag: 0,
+            identifier: None,
             frame: frame,
             bounds: bounds,
             background_color: white,
+            background_color_role: Some(SemanticColor::Background),
+            background_gradient: None,
             layer: None,
             superview: WeakView::none(),
             subviews: Vec::new(),
             gesture_recognizers: Vec::new(),
+            tracking_areas: Vec::new(),
             hidden: false,
-            user_interaction_enabled: true
+            alpha: 1.0,
+            transform: Transform::identity(),
+            corner_radius: 0.0,
+            border_width: 0.0,
+            border_color: Color::black(),
+            clips_to_bounds: false,
+            autoresizing_mask: Vec::new(),
+            layout_margins: EdgeInsets::zero(),
+            shadow: None,
+            shadow_cache: None,
+            user_interaction_enabled: true,
+            context_menu: None,
+            cursor: None,
+            tooltip: None,
+            files_dropped_action: None,
+            key_commands: Vec::new(),
+            appearance: None,
+            layout_direction: None,
+            accessibility_label: None,
+            accessibility_role: None,
+            accessibility_value: None,
+            is_accessibility_element: None
         };
 
         let view = View {
@@ -122,26 +168,265 @@ impl View {
         found
     }
 
+    /// The view's identifier, if it has one. See `View::set_identifier`.
+    pub fn identifier(&self) -> Option<String> {
+        self.inner_self.borrow().identifier.clone()
+    }
+
+    /// Names the view, to find it later with `view_with_identifier`, such
+    /// as from a test or a controller. `None` removes the identifier.
+    ///
+    /// Identifiers should be unique within a view hierarchy. In debug
+    /// builds, giving two views in the same hierarchy the same identifier,
+    /// here or by adding one to the other's hierarchy, panics.
+    pub fn set_identifier(&self, identifier: Option<&str>) {
+        self.inner_self.borrow_mut().identifier = identifier.map(String::from);
+
+        if identifier.is_some() {
+            self.root_view().debug_assert_unique_identifiers();
+        }
+    }
+
+    /// Finds the view whose `id` is `id` in this view's hierarchy.
+    pub fn view_with_id(&self, id: usize) -> Option<View> {
+        let mut stack = vec![self.clone()];
+
+        while let Some(view) = stack.pop() {
+            if view.id() == id {
+                return Some(view);
+            }
+            stack.extend(view.inner_self.borrow().subviews.iter().cloned());
+        }
+
+        None
+    }
+
+    /// Finds the view with `identifier` in this view's hierarchy: this view,
+    /// its subviews, their subviews, and so on.
+    pub fn view_with_identifier(&self, identifier: &str) -> Option<View> {
+        let mut stack = vec![self.clone()];
+
+        while let Some(view) = stack.pop() {
+            if view.inner_self.borrow().identifier.as_deref() == Some(identifier) {
+                return Some(view);
+            }
+
+            // Subviews are searched front to back, in the order they were
+            // added.
+            stack.extend(view.inner_self.borrow().subviews.iter().rev().cloned());
+        }
+
+        None
+    }
+
+    /// The identifiers of this view and every view below it.
+    fn identifiers_in_hierarchy(&self) -> Vec<String> {
+        let mut identifiers = Vec::new();
+        let mut stack = vec![self.clone()];
+
+        while let Some(view) = stack.pop() {
+            let inner_self = view.inner_self.borrow();
+            identifiers.extend(inner_self.identifier.clone());
+            stack.extend(inner_self.subviews.iter().cloned());
+        }
+
+        identifiers
+    }
+
+    /// Panics, in debug builds, if two views in this view's hierarchy have
+    /// the same identifier, as only one of them could be found.
+    fn debug_assert_unique_identifiers(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        let mut identifiers = self.identifiers_in_hierarchy();
+        identifiers.sort();
+        if let Some(duplicate) = identifiers.windows(2).find(|pair| pair[0] == pair[1]) {
+            panic!("More than one view in the same hierarchy has the identifier {:?}", duplicate[0]);
+        }
+    }
+
+    /// The name of the view's behavior without its module, such as
+    /// "LabelBehavior".
+    pub(crate) fn behavior_name(&self) -> String {
+        let name = self.behavior.borrow().name();
+        String::from(name.rsplit("::").next().unwrap_or(&name))
+    }
+
+    /// An outline of this view and every view below it, for debugging. Each
+    /// view is on its own line, indented below its superview, with its
+    /// behavior, frame, and identifier and tag if it has them:
+    ///
+    /// ```text
+    /// DefaultBehavior (0, 0, 320, 200)
+    ///     LabelBehavior (16, 16, 288, 24) "title"
+    ///     ButtonBehavior (16, 150, 80, 32) "ok" hidden
+    /// ```
+    pub fn recursive_description(&self) -> String {
+        let mut description = String::new();
+        let mut stack = vec![(self.clone(), 0)];
+
+        while let Some((view, depth)) = stack.pop() {
+            let frame = view.frame();
+            description.push_str(&"    ".repeat(depth));
+            description.push_str(&format!(
+                "{} ({}, {}, {}, {})",
+                view.behavior_name(),
+                frame.origin.x,
+                frame.origin.y,
+                frame.size.width,
+                frame.size.height
+            ));
+
+            if let Some(identifier) = view.identifier() {
+                description.push_str(&format!(" {:?}", identifier));
+            }
+            if view.tag() != 0 {
+                description.push_str(&format!(" tag {}", view.tag()));
+            }
+            if view.is_hidden() {
+                description.push_str(" hidden");
+            }
+            if view.alpha() != 1.0 {
+                description.push_str(&format!(" alpha {}", view.alpha()));
+            }
+            description.push('\n');
+
+            stack.extend(view.subviews().into_iter().rev().map(|subview| (subview, depth + 1)));
+        }
+
+        description
+    }
+
+    /// Checks identifiers are still unique in the hierarchy `child` was just
+    /// added to. Only hierarchies gaining identifiers need checking.
+    fn debug_assert_unique_identifiers_adding(&self, child: &View) {
+        if cfg!(debug_assertions) && !child.identifiers_in_hierarchy().is_empty() {
+            self.root_view().debug_assert_unique_identifiers();
+        }
+    }
 
     /// Adds a child `View` to this `View`.
     ///
     /// Also sets the parent (`superview`) of the child view to this `View`.
     pub fn add_subview(&self, child: View) {
         let weak_self = self.downgrade();
-        let mut inner_self = self.inner_self.borrow_mut();
+        let directions = child.layout_directions_before_move();
 
         {
+            let mut inner_self = self.inner_self.borrow_mut();
             let mut child_inner = child.inner_self.borrow_mut();
 
             // Set the child superview
             child_inner.superview = weak_self;
+            inner_self.subviews.push(child.clone());
         }
 
-        inner_self.subviews.push(child.clone());
+        self.debug_assert_unique_identifiers_adding(&child);
+        self.mirror_added_subview(&child, directions);
 
         child.set_needs_display();
     }
 
+    /// Inserts `child` into this view's subviews at `index`, drawing it
+    /// above the subviews before it and below those after. An index past the
+    /// end adds it in front of all of them.
+    ///
+    /// If `child` is already in a superview, including this one, it's moved.
+    pub fn insert_subview_at(&self, child: View, index: usize) {
+        let directions = child.layout_directions_before_move();
+        if child.superview().upgrade().is_some() {
+            child.remove_from_superview();
+        }
+
+        {
+            let mut inner_self = self.inner_self.borrow_mut();
+            child.inner_self.borrow_mut().superview = self.downgrade();
+
+            let index = index.min(inner_self.subviews.len());
+            inner_self.subviews.insert(index, child.clone());
+        }
+
+        self.debug_assert_unique_identifiers_adding(&child);
+        self.mirror_added_subview(&child, directions);
+
+        child.set_needs_display();
+        self.set_needs_display();
+    }
+
+    /// Before the view is added to a superview, the direction its frame is
+    /// laid out for, that of its last superview or left to right if it
+    /// hasn't had one, and the direction its own subviews are laid out in.
+    fn layout_directions_before_move(&self) -> (LayoutDirection, LayoutDirection) {
+        let frame_direction = self.superview().upgrade()
+            .map_or(LayoutDirection::LeftToRight, |superview| superview.effective_layout_direction());
+        (frame_direction, self.effective_layout_direction())
+    }
+
+    /// Lays `child`, just added, out for the view's direction, as
+    /// `layout_direction_did_change` would have had it been added before
+    /// the direction flipped: frames are given left to right, so `child`
+    /// is mirrored if it keeps to a leading or trailing edge of a view laid
+    /// out right to left, and its subviews are mirrored too if the move
+    /// flipped its direction. `directions` are those from
+    /// `layout_directions_before_move`.
+    fn mirror_added_subview(&self, child: &View, directions: (LayoutDirection, LayoutDirection)) {
+        let (frame_direction, previous_direction) = directions;
+
+        let mask = child.autoresizing_mask();
+        let keeps_to_edge = mask.contains(&AutoresizingMask::FlexibleLeadingMargin) ||
+            mask.contains(&AutoresizingMask::FlexibleTrailingMargin);
+        if keeps_to_edge && self.effective_layout_direction() != frame_direction {
+            let mut frame = child.frame();
+            frame.origin.x = self.bounds().size.width as i32 - frame.origin.x - frame.size.width as i32;
+            child.set_frame(frame);
+        }
+
+        if child.effective_layout_direction() != previous_direction {
+            child.layout_direction_did_change();
+        }
+    }
+
+    /// Moves `subview` in front of the view's other subviews, so it's drawn
+    /// over them and receives touches first. Does nothing if it isn't a
+    /// subview of this view.
+    pub fn bring_subview_to_front(&self, subview: &View) {
+        let mut subviews = self.subviews();
+        if let Some(index) = subviews.iter().position(|view| view == subview) {
+            let subview = subviews.remove(index);
+            subviews.push(subview);
+            self.set_subview_order(subviews);
+        }
+    }
+
+    /// Moves `subview` behind the view's other subviews. Does nothing if it
+    /// isn't a subview of this view.
+    pub fn send_subview_to_back(&self, subview: &View) {
+        let mut subviews = self.subviews();
+        if let Some(index) = subviews.iter().position(|view| view == subview) {
+            let subview = subviews.remove(index);
+            subviews.insert(0, subview);
+            self.set_subview_order(subviews);
+        }
+    }
+
+    /// Swaps the subviews at `index1` and `index2`. Does nothing if either
+    /// index is out of range.
+    pub fn exchange_subviews(&self, index1: usize, index2: usize) {
+        let mut subviews = self.subviews();
+        if index1 < subviews.len() && index2 < subviews.len() && index1 != index2 {
+            subviews.swap(index1, index2);
+            self.set_subview_order(subviews);
+        }
+    }
+
+    /// Replaces the subviews with the same views in a new order.
+    fn set_subview_order(&self, subviews: Vec<View>) {
+        self.inner_self.borrow_mut().subviews = subviews;
+        self.set_needs_display();
+    }
+
     /// Remove the view from its superview.
     pub fn remove_from_superview(&self) {
         let inner_self = self.inner_self.borrow();
@@ -171,15 +456,44 @@ impl View {
             .map(|recognizer| Rc::downgrade(recognizer)).collect()
     }
 
+    /// Adds an area of the view that calls `Behavior::mouse_entered`,
+    /// `mouse_exited` and `mouse_moved` as the pointer moves over it.
+    pub fn add_tracking_area(&self, tracking_area: TrackingArea) {
+        self.inner_self.borrow_mut().tracking_areas.push(tracking_area);
+    }
+
+    /// Removes the first tracking area equal to `tracking_area`, if any.
+    pub fn remove_tracking_area(&self, tracking_area: &TrackingArea) {
+        let mut inner_self = self.inner_self.borrow_mut();
+        if let Some(index) = inner_self.tracking_areas.iter().position(|area| area == tracking_area) {
+            inner_self.tracking_areas.remove(index);
+        }
+    }
+
+    pub fn tracking_areas(&self) -> Vec<TrackingArea> {
+        self.inner_self.borrow().tracking_areas.clone()
+    }
+
+    pub(crate) fn tracking_area_has_option(&self, index: usize, option: TrackingOption) -> bool {
+        self.inner_self.borrow().tracking_areas.get(index).is_some_and(|area| area.has_option(option))
+    }
+
     fn draw(&self) {
         let behavior = self.behavior.borrow();
         behavior.draw();
     }
 
-    /// Change the background color for this view.
+    pub fn background_color(&self) -> Color {
+        self.inner_self.borrow().background_color.clone()
+    }
+
+    /// Change the background color for this view. Until it's changed, the
+    /// background is the appearance's `SemanticColor::Background`, and
+    /// follows the appearance as it changes.
     pub fn set_background_color(&self, color: Color) {
         {
             let mut inner_self = self.inner_self.borrow_mut();
+            inner_self.background_color_role = None;
 
             if inner_self.background_color == color {
                 return;
@@ -191,6 +505,356 @@ impl View {
         self.set_needs_display();
     }
 
+    pub fn background_gradient(&self) -> Option<Gradient> {
+        self.inner_self.borrow().background_gradient.clone()
+    }
+
+    /// Sets a gradient to fill the view's background with, drawn over the
+    /// background color, which shows through where the gradient is
+    /// transparent. `None` leaves just the background color.
+    ///
+    /// ```ignore
+    /// header.set_background_gradient(Some(Gradient::between(
+    ///     Color::RGBA(90, 160, 250, 255),
+    ///     Color::RGBA(20, 90, 200, 255),
+    ///     GradientDirection::vertical()
+    /// )));
+    /// ```
+    pub fn set_background_gradient(&self, gradient: Option<Gradient>) {
+        {
+            let mut inner_self = self.inner_self.borrow_mut();
+
+            if inner_self.background_gradient == gradient {
+                return;
+            }
+
+            inner_self.background_gradient = gradient;
+        }
+
+        self.set_needs_display();
+    }
+
+    /// The appearance set for the view and its subviews, if any. See
+    /// `effective_appearance` for the one they use.
+    pub fn appearance(&self) -> Option<Appearance> {
+        self.inner_self.borrow().appearance.clone()
+    }
+
+    /// Sets an appearance for the view and its subviews, overriding the
+    /// application's, such as a dark sidebar in a light window. `None`
+    /// goes back to the superview's.
+    pub fn set_appearance(&self, appearance: Option<Appearance>) {
+        let previous = self.effective_appearance();
+        self.inner_self.borrow_mut().appearance = appearance;
+
+        if self.effective_appearance() != previous {
+            self.appearance_did_change(&previous);
+        }
+    }
+
+    /// The appearance the view uses: its own, or its nearest superview's,
+    /// or the application's.
+    pub fn effective_appearance(&self) -> Appearance {
+        let mut current_view = Some(self.clone());
+        while let Some(view) = current_view {
+            if let Some(appearance) = view.appearance() {
+                return appearance;
+            }
+            current_view = view.superview().upgrade();
+        }
+
+        appearance::appearance()
+    }
+
+    /// Restyles the view, and the subviews without an appearance of their
+    /// own, after the effective appearance changed from `previous`.
+    pub(crate) fn appearance_did_change(&self, previous: &Appearance) {
+        let background_color_role = self.inner_self.borrow().background_color_role;
+        if let Some(background_color_role) = background_color_role {
+            self.set_background_color(self.effective_appearance().color(background_color_role));
+            self.inner_self.borrow_mut().background_color_role = Some(background_color_role);
+        }
+
+        self.behavior().appearance_did_change(previous);
+        self.set_needs_display();
+
+        for subview in self.subviews().iter().filter(|subview| subview.appearance().is_none()) {
+            subview.appearance_did_change(previous);
+        }
+    }
+
+    /// The layout direction set for the view and its subviews, if any. See
+    /// `effective_layout_direction` for the one they use.
+    pub fn layout_direction(&self) -> Option<LayoutDirection> {
+        self.inner_self.borrow().layout_direction
+    }
+
+    /// Sets a layout direction for the view and its subviews, overriding
+    /// the application's, such as to lay out a whole window right to left
+    /// through its root view. `None` goes back to the superview's.
+    pub fn set_layout_direction(&self, layout_direction: Option<LayoutDirection>) {
+        let previous = self.effective_layout_direction();
+        self.inner_self.borrow_mut().layout_direction = layout_direction;
+
+        if self.effective_layout_direction() != previous {
+            self.layout_direction_did_change();
+        }
+    }
+
+    /// The layout direction the view lays its subviews out in: its own, or
+    /// its nearest superview's, or the application's. See
+    /// `layout_direction`.
+    pub fn effective_layout_direction(&self) -> LayoutDirection {
+        let mut current_view = Some(self.clone());
+        while let Some(view) = current_view {
+            if let Some(layout_direction) = view.layout_direction() {
+                return layout_direction;
+            }
+            current_view = view.superview().upgrade();
+        }
+
+        layout_direction::layout_direction()
+    }
+
+    /// Mirrors the view's subviews that keep to a leading or trailing edge,
+    /// and does the same for the subviews without a layout direction of
+    /// their own, after the effective layout direction flipped. Everything
+    /// is laid out and drawn again for the new direction.
+    pub(crate) fn layout_direction_did_change(&self) {
+        let width = self.bounds().size.width as i32;
+        for subview in self.subviews().iter() {
+            let mask = subview.autoresizing_mask();
+            if mask.contains(&AutoresizingMask::FlexibleLeadingMargin) || mask.contains(&AutoresizingMask::FlexibleTrailingMargin) {
+                let mut frame = subview.frame();
+                frame.origin.x = width - frame.origin.x - frame.size.width as i32;
+                subview.set_frame(frame);
+            }
+
+            if subview.layout_direction().is_none() {
+                subview.layout_direction_did_change();
+            }
+        }
+
+        self.behavior().layout_direction_did_change();
+        self.layout_subviews();
+        self.set_needs_display();
+    }
+
+    /// Tells the view, and its subviews, that the application's locale
+    /// changed.
+    pub(crate) fn locale_did_change(&self) {
+        self.behavior().locale_did_change();
+
+        for subview in self.subviews().iter() {
+            subview.locale_did_change();
+        }
+    }
+
+    pub fn corner_radius(&self) -> f32 {
+        self.inner_self.borrow().corner_radius
+    }
+
+    /// Rounds the corners of the view's background, and its border. Set
+    /// `clips_to_bounds` to also cut subviews off at the corners.
+    pub fn set_corner_radius(&self, corner_radius: f32) {
+        let corner_radius = corner_radius.max(0.0);
+
+        {
+            let mut inner_self = self.inner_self.borrow_mut();
+
+            if inner_self.corner_radius == corner_radius {
+                return;
+            }
+
+            inner_self.corner_radius = corner_radius;
+        }
+
+        self.set_needs_display();
+    }
+
+    pub fn border_width(&self) -> f32 {
+        self.inner_self.borrow().border_width
+    }
+
+    pub fn border_color(&self) -> Color {
+        self.inner_self.borrow().border_color.clone()
+    }
+
+    /// Draws a border of `width` in `color` along the inside edge of the
+    /// view, over its subviews and following its rounded corners. A width
+    /// of 0 removes the border.
+    pub fn set_border(&self, width: f32, color: Color) {
+        let width = width.max(0.0);
+
+        {
+            let mut inner_self = self.inner_self.borrow_mut();
+
+            if inner_self.border_width == width && inner_self.border_color == color {
+                return;
+            }
+
+            inner_self.border_width = width;
+            inner_self.border_color = color;
+        }
+
+        self.set_needs_display();
+    }
+
+    pub fn clips_to_bounds(&self) -> bool {
+        self.inner_self.borrow().clips_to_bounds
+    }
+
+    /// Sets whether subviews are cut off at the view's rounded corners.
+    ///
+    /// Each view is drawn into its own layer the size of the view, so
+    /// subviews never draw outside of the view's rectangle either way.
+    pub fn set_clips_to_bounds(&self, clips_to_bounds: bool) {
+        {
+            let mut inner_self = self.inner_self.borrow_mut();
+
+            if inner_self.clips_to_bounds == clips_to_bounds {
+                return;
+            }
+
+            inner_self.clips_to_bounds = clips_to_bounds;
+        }
+
+        self.set_needs_display();
+    }
+
+    pub fn shadow(&self) -> Option<Shadow> {
+        self.inner_self.borrow().shadow.clone()
+    }
+
+    /// Sets the shadow drawn beneath the view, in the shape of the view's
+    /// rounded rectangle, or removes it with `None`.
+    ///
+    /// The blurred shadow is kept between frames, and only blurred again
+    /// when its color, blur radius, or the view's size or corner radius
+    /// change.
+    pub fn set_shadow(&self, shadow: Option<Shadow>) {
+        {
+            let mut inner_self = self.inner_self.borrow_mut();
+
+            if inner_self.shadow == shadow {
+                return;
+            }
+
+            inner_self.shadow = shadow;
+        }
+
+        if let Some(superview) = self.superview().upgrade() {
+            superview.set_needs_display();
+        }
+    }
+
+    /// The transform applied about the center of the view when it's drawn.
+    pub fn transform(&self) -> Transform {
+        self.inner_self.borrow().transform
+    }
+
+    /// Rotates, scales or moves the view as it's drawn into its superview,
+    /// about the center of the view, without changing its frame. Touches are
+    /// mapped back through the transform when hit testing.
+    ///
+    /// The window's own transform is ignored.
+    pub fn set_transform(&self, transform: Transform) {
+        {
+            let mut inner_self = self.inner_self.borrow_mut();
+
+            if inner_self.transform == transform {
+                return;
+            }
+
+            inner_self.transform = transform;
+        }
+
+        if let Some(superview) = self.superview().upgrade() {
+            superview.set_needs_display();
+        }
+    }
+
+    /// Maps points relative to the top left of this view to points relative
+    /// to the top left of its superview, including this view's transform.
+    ///
+    /// The view at the top of the hierarchy, usually the window, is only
+    /// moved by its frame's origin, as its transform isn't drawn.
+    fn transform_to_superview(&self) -> Transform {
+        let (frame, transform, superview) = {
+            let inner_self = self.inner_self.borrow();
+            (inner_self.frame.clone(), inner_self.transform, inner_self.superview.upgrade())
+        };
+
+        let superview = match superview {
+            Some(superview) => superview,
+            None => return Transform::translation(frame.origin.x as f32, frame.origin.y as f32)
+        };
+
+        let bounds_origin = superview.bounds().origin;
+        let about_center = if transform.is_identity() {
+            transform
+        } else {
+            let center_x = frame.size.width as f32 / 2.0;
+            let center_y = frame.size.height as f32 / 2.0;
+            Transform::translation(-center_x, -center_y).concat(&transform).translated(center_x, center_y)
+        };
+
+        about_center.translated(
+            (frame.origin.x - bounds_origin.x) as f32,
+            (frame.origin.y - bounds_origin.y) as f32
+        )
+    }
+
+    /// Maps points relative to the top left of this view to the coordinates
+    /// of the view at the top of its hierarchy.
+    fn transform_to_root(&self) -> Transform {
+        let mut transform = self.transform_to_superview();
+        let mut current = self.superview().upgrade();
+
+        while let Some(view) = current {
+            transform = transform.concat(&view.transform_to_superview());
+            current = view.superview().upgrade();
+        }
+
+        transform
+    }
+
+    /// Maps a point relative to the top left of this view's superview to one
+    /// relative to the top left of this view, undoing its transform. `None`
+    /// if the transform can't be undone, such as a scale of 0.
+    pub(crate) fn convert_point_from_superview(&self, point: &Point<i32>) -> Option<Point<i32>> {
+        Some(self.transform_to_superview().inverted()?.apply_to_point(point))
+    }
+
+    /// The transform mapping points relative to the top left of this view to
+    /// ones relative to the top left of `to_view`. If `to_view` is scaled to
+    /// nothing, points can't be mapped into it, and are left in the
+    /// coordinates of the top of the hierarchy.
+    fn transform_to(&self, to_view: &View) -> Transform {
+        let to_root = self.transform_to_root();
+        match to_view.transform_to_root().inverted() {
+            Some(from_root) => to_root.concat(&from_root),
+            None => to_root
+        }
+    }
+
+    /// Animates changes to the view's frame, background color, alpha and
+    /// transform.
+    ///
+    /// `changes` is called right away to make the changes, and the view then
+    /// moves from its old values to the new ones over `duration`, starting
+    /// from the next frame:
+    ///
+    /// ```ignore
+    /// let animation = panel.animate(Duration::from_millis(250), Easing::EaseOut, |panel| {
+    ///     panel.set_frame(Rectangle::new(0, 0, 200, 400));
+    /// });
+    /// animation.on_complete(|finished| println!("Slid in: {}", finished));
+    /// ```
+    pub fn animate(&self, duration: Duration, easing: Easing, changes: impl FnOnce(&View)) -> Animation {
+        Animation::new(self, duration, easing, changes)
+    }
+
     /// Request for this view to be redrawn soon.
     ///
     /// See `#draw`, which includes the instructions on what would actually be
@@ -200,8 +864,176 @@ impl View {
         behavior.set_needs_display();
     }
 
+    /// Request for `rectangle` of this view, relative to its top left, to be
+    /// redrawn soon. Only that part of the view, and of the views it's drawn
+    /// into, is drawn again, rather than all of them.
+    pub fn set_needs_display_in_rect(&self, rectangle: &Rectangle<i32, u32>) {
+        let behavior = self.behavior.borrow();
+        behavior.set_needs_display_in_rect(rectangle);
+    }
+
+    /// Draws the view and its subviews now, and returns an image of them,
+    /// such as to compare with a known good image in tests. The view's own
+    /// alpha, shadow and transform are left out.
+    ///
+    /// Views in a window are drawn at the window's scale. Other views are
+    /// drawn offscreen at a scale of 1, without needing a display.
+    pub fn snapshot(&self) -> Image<'static> {
+        let context = match self.window() {
+            Some(window) => window.context(),
+            None => Context::new_headless(self.frame().size, 1.0)
+        };
+
+        self.snapshot_in_context(&context)
+    }
+
+    /// Like `snapshot`, drawing the view in `context`, such as a headless
+    /// context at a given scale. See `Context::new_headless`.
+    pub fn snapshot_in_context(&self, context: &Context) -> Image<'static> {
+        render::snapshot(self, context)
+    }
+
+    /// Draws the view and its subviews now, and returns an image of them as
+    /// they're drawn into the superview: unlike `snapshot`, with the view's
+    /// own alpha, shadow and transform. The image is grown past the frame to
+    /// fit the shadow and transform. Save it with `Image::save_png`, such as
+    /// for a bug report or documentation.
+    ///
+    /// Like `snapshot`, views in a window are drawn at the window's scale,
+    /// and other views offscreen at a scale of 1.
+    pub fn snapshot_subtree(&self) -> Image<'static> {
+        let context = match self.window() {
+            Some(window) => window.context(),
+            None => Context::new_headless(self.frame().size, 1.0)
+        };
+
+        render::snapshot_subtree(self, &context)
+    }
+
+    /// Marks the part of the superview this view is drawn over to be drawn
+    /// again, such as after the view changed or moved.
+    pub(crate) fn set_needs_display_in_superview(&self) {
+        let size = self.frame().size;
+        self.set_needs_display_in_superview_rect(&Rectangle::new(0, 0, size.width, size.height));
+    }
+
+    /// Marks the part of the superview under `rectangle` of this view,
+    /// relative to its top left, to be drawn again.
+    pub(crate) fn set_needs_display_in_superview_rect(&self, rectangle: &Rectangle<i32, u32>) {
+        let (frame, superview, is_drawn_outside_frame) = {
+            let inner_self = self.inner_self.borrow();
+            let is_drawn_outside_frame = !inner_self.transform.is_identity() || inner_self.shadow.is_some();
+            (inner_self.frame.clone(), inner_self.superview.upgrade(), is_drawn_outside_frame)
+        };
+
+        let superview = match superview {
+            Some(superview) => superview,
+            None => return
+        };
+
+        // Transforms and shadows can draw the view anywhere in the superview.
+        if is_drawn_outside_frame {
+            superview.set_needs_display();
+            return;
+        }
+
+        let bounds = superview.bounds();
+        superview.set_needs_display_in_rect(&Rectangle {
+            origin: Point {
+                x: rectangle.origin.x + frame.origin.x - bounds.origin.x,
+                y: rectangle.origin.y + frame.origin.y - bounds.origin.y
+            },
+            size: rectangle.size.clone()
+        });
+    }
+
+    /// The shape of the pointer over the view, if set with `set_cursor`.
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.inner_self.borrow().cursor
+    }
+
+    /// Sets the shape of the pointer over the view and its subviews, unless
+    /// they prefer another. With `None`, the default, the view's behavior
+    /// decides, see `Behavior::cursor`.
+    pub fn set_cursor(&self, cursor: Option<Cursor>) {
+        self.inner_self.borrow_mut().cursor = cursor;
+    }
+
+    /// The text shown next to the view once the pointer rests on it, if set
+    /// with `set_tooltip`.
+    pub fn tooltip(&self) -> Option<String> {
+        self.inner_self.borrow().tooltip.clone()
+    }
+
+    /// Sets the text shown in a `Tooltip` next to the view once the pointer
+    /// rests on it, or on a subview without a tooltip of its own, for a
+    /// moment. It's hidden when the pointer leaves.
+    pub fn set_tooltip(&self, tooltip: Option<&str>) {
+        self.inner_self.borrow_mut().tooltip = tooltip.map(String::from);
+    }
+
+    /// The shape of the pointer at `point`, relative to the top left of this
+    /// view: the cursor of the view there, or of the nearest superview that
+    /// has one, or the arrow.
+    pub(crate) fn cursor_at(&self, point: &Point<i32>) -> Cursor {
+        self.first_in_responder_path(point, |view, position| {
+            view.cursor().or_else(|| view.behavior().cursor(position))
+        })
+        .unwrap_or(Cursor::Arrow)
+    }
+
+    /// Calls `action` with the paths of files dropped on the view, or on a
+    /// subview without an action of its own, such as files dragged from the
+    /// platform's file manager onto an image viewer.
+    pub fn on_files_dropped(&self, action: impl Fn(&View, &[PathBuf]) + 'static) {
+        self.inner_self.borrow_mut().files_dropped_action = Some(Rc::new(action));
+    }
+
+    /// Sends `paths` to the action of the view at `point`, relative to the
+    /// top left of this view, or of its nearest superview with one. Returns
+    /// the view whose action was called, if any.
+    pub(crate) fn files_dropped_at(&self, point: &Point<i32>, paths: &[PathBuf]) -> Option<View> {
+        self.first_in_responder_path(point, |view, _| {
+            let action = view.inner_self.borrow().files_dropped_action.clone()?;
+            action(view, paths);
+            Some(view.clone())
+        })
+    }
+
+    /// Adds a keyboard shortcut, performed when it's pressed while the view,
+    /// or one of its subviews, is the first responder. Shortcuts of views
+    /// nearer the first responder win.
+    pub fn add_key_command(&self, key_command: KeyCommand) {
+        self.inner_self.borrow_mut().key_commands.push(key_command);
+    }
+
+    /// Removes the view's shortcuts triggered by pressing `key`.
+    pub fn remove_key_command(&self, key: &Key) {
+        self.inner_self.borrow_mut().key_commands.retain(|key_command| !key_command.matches(key));
+    }
+
+    pub fn key_commands(&self) -> Vec<KeyCommand> {
+        self.inner_self.borrow().key_commands.clone()
+    }
+
+    /// The shortcut triggered by pressing `key`, from this view or its
+    /// nearest superview with a matching one.
+    pub(crate) fn key_command_matching(&self, key: &Key) -> Option<KeyCommand> {
+        let mut current_view = Some(self.clone());
+        while let Some(view) = current_view {
+            let key_command = key_command::matching(&view.inner_self.borrow().key_commands, key);
+            if key_command.is_some() {
+                return key_command;
+            }
+            current_view = view.superview().upgrade();
+        }
+
+        None
+    }
+
     /// Sets whether this view can be interacted with by the user. If `false`,
-    /// then this view will not receive any touch events.
+    /// then neither this view nor its subviews will receive touch events or
+    /// keyboard focus; touches on them go to the view behind instead.
     pub fn set_user_interaction_enabled(&self, enabled: bool) {
         {
             let mut inner_self = self.inner_self.borrow_mut();
@@ -209,6 +1041,131 @@ impl View {
         }
     }
 
+    pub fn is_user_interaction_enabled(&self) -> bool {
+        self.inner_self.borrow().user_interaction_enabled
+    }
+
+    /// The menu shown when the view is right-clicked or long-pressed.
+    pub fn context_menu(&self) -> Option<Menu> {
+        self.inner_self.borrow().context_menu.clone()
+    }
+
+    /// Sets the menu shown when the view, or a subview without a menu of
+    /// its own, is right-clicked or long-pressed.
+    pub fn set_context_menu(&self, menu: Option<Menu>) {
+        self.inner_self.borrow_mut().context_menu = menu;
+    }
+
+    /// The context menu for `point`, relative to the top left of the view:
+    /// that of the view hit at the point, or of its nearest superview with
+    /// one. A behavior's menu for the point, see `Behavior::context_menu_at`,
+    /// comes before the view's own.
+    pub(crate) fn context_menu_at(&self, point: &Point<i32>) -> Option<Menu> {
+        self.first_in_responder_path(point, |view, position| {
+            view.behavior().context_menu_at(position).or_else(|| view.context_menu())
+        })
+    }
+
+    /// Calls `f` with the view hit at `point`, relative to the top left of
+    /// this view, then with each of its superviews in turn, until `f`
+    /// returns something, and returns that. `f` is also given `point`
+    /// relative to the view it's called with.
+    ///
+    /// Events that aren't sent to the first responder, such as the scroll
+    /// wheel or dropped files, go along this path to the first view that
+    /// takes them.
+    pub(crate) fn first_in_responder_path<T>(&self, point: &Point<i32>, mut f: impl FnMut(&View, &Point<i32>) -> Option<T>) -> Option<T> {
+        let mut current_view = self.hit_test(point);
+        while let Some(view) = current_view {
+            let position = self.convert_point_to(point, &view);
+            if let Some(found) = f(&view, &position) {
+                return Some(found);
+            }
+            current_view = view.superview().upgrade();
+        }
+
+        None
+    }
+
+    /// What screen readers read the view out as, such as a button's title.
+    /// See `set_accessibility_label`.
+    pub fn accessibility_label(&self) -> Option<String> {
+        let label = self.inner_self.borrow().accessibility_label.clone();
+        label.or_else(|| self.behavior.borrow().accessibility_label())
+    }
+
+    /// Sets what screen readers read the view out as, such as for a button
+    /// showing only an image. With `None`, the default, the view's behavior
+    /// decides: a `Label` is read out as its text, for example.
+    ///
+    /// A view that isn't an accessibility element, such as a plain `View`,
+    /// is read out as a group of its subviews once it has a label.
+    pub fn set_accessibility_label(&self, label: Option<&str>) {
+        self.inner_self.borrow_mut().accessibility_label = label.map(String::from);
+
+        // Windows send their accessibility tree on again as they're drawn.
+        self.set_needs_display();
+    }
+
+    /// What kind of control the view is to screen readers. See
+    /// `set_accessibility_role`.
+    pub fn accessibility_role(&self) -> AccessibilityRole {
+        let role = self.inner_self.borrow().accessibility_role;
+        role.unwrap_or_else(|| self.behavior.borrow().accessibility_role())
+    }
+
+    /// Sets what kind of control the view is to screen readers, such as
+    /// `AccessibilityRole::Button` for a view that handles taps itself.
+    /// With `None`, the default, the view's behavior decides.
+    pub fn set_accessibility_role(&self, role: Option<AccessibilityRole>) {
+        self.inner_self.borrow_mut().accessibility_role = role;
+        self.set_needs_display();
+    }
+
+    /// What the view holds, such as a text field's text. See
+    /// `set_accessibility_value`.
+    pub fn accessibility_value(&self) -> Option<String> {
+        let value = self.inner_self.borrow().accessibility_value.clone();
+        value.or_else(|| self.behavior.borrow().accessibility_value())
+    }
+
+    /// Sets what screen readers read out as the view's value, such as "3 of
+    /// 5 stars". With `None`, the default, the view's behavior decides.
+    pub fn set_accessibility_value(&self, value: Option<&str>) {
+        self.inner_self.borrow_mut().accessibility_value = value.map(String::from);
+        self.set_needs_display();
+    }
+
+    /// Whether screen readers read the view out on its own, with its
+    /// subviews as part of it, rather than reading out its subviews. See
+    /// `set_is_accessibility_element`.
+    pub fn is_accessibility_element(&self) -> bool {
+        let is_accessibility_element = self.inner_self.borrow().is_accessibility_element;
+        is_accessibility_element.unwrap_or_else(|| self.accessibility_role() != AccessibilityRole::Group)
+    }
+
+    /// Sets whether screen readers read the view out on its own, such as
+    /// `Some(false)` for a label that only decorates. With `None`, the
+    /// default, every view but a group is.
+    pub fn set_is_accessibility_element(&self, is_accessibility_element: Option<bool>) {
+        self.inner_self.borrow_mut().is_accessibility_element = is_accessibility_element;
+        self.set_needs_display();
+    }
+
+    /// Performs `action` for a screen reader, such as pressing a button
+    /// or typing into a text field. Returns whether the view could.
+    ///
+    /// The view's behavior performs it, see
+    /// `Behavior::perform_accessibility_action`. If it doesn't, any view can
+    /// still be focused, see `become_first_responder`.
+    pub fn perform_accessibility_action(&self, action: &AccessibilityAction) -> bool {
+        if self.behavior.borrow().perform_accessibility_action(action) {
+            return true;
+        }
+
+        *action == AccessibilityAction::Focus && self.become_first_responder()
+    }
+
     pub fn set_hidden(&self, value: bool) {
         {
             let mut inner_self = self.inner_self.borrow_mut();
@@ -227,6 +1184,35 @@ impl View {
         self.inner_self.borrow().hidden
     }
 
+    /// The opacity of the view and its subviews, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        self.inner_self.borrow().alpha
+    }
+
+    /// Sets the opacity of the view and its subviews, clamped to between 0
+    /// (transparent) and 1 (opaque).
+    ///
+    /// The view and its subviews are drawn as usual, and the result is
+    /// blended into the superview, so overlapping subviews don't show
+    /// through each other. The alpha of the window itself is ignored.
+    pub fn set_alpha(&self, alpha: f32) {
+        let alpha = alpha.clamp(0.0, 1.0);
+
+        {
+            let mut inner_self = self.inner_self.borrow_mut();
+
+            if inner_self.alpha == alpha {
+                return;
+            }
+
+            inner_self.alpha = alpha;
+        }
+
+        // The view's own layer is unchanged; only the superview has to
+        // blend it in again.
+        self.set_needs_display_in_superview();
+    }
+
     pub fn touches_began(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
         let behavior = self.behavior.borrow();
         behavior.touches_began(touches);
@@ -242,6 +1228,63 @@ impl View {
         behavior.touches_moved(touches);
     }
 
+    pub fn touches_cancelled(&self, touches: &Vec<Touch>, _event: &TouchEvent) {
+        let behavior = self.behavior.borrow();
+        behavior.touches_cancelled(touches);
+    }
+
+    /// The pointer entered one of the view's tracking areas, at `position`
+    /// relative to the top left of the view.
+    pub fn mouse_entered(&self, position: &Point<i32>) {
+        let behavior = self.behavior.borrow();
+        behavior.mouse_entered(position);
+    }
+
+    /// The pointer left one of the view's tracking areas.
+    pub fn mouse_exited(&self) {
+        let behavior = self.behavior.borrow();
+        behavior.mouse_exited();
+    }
+
+    /// The pointer moved to `position`, relative to the top left of the
+    /// view, within one of the view's tracking areas.
+    pub fn mouse_moved(&self, position: &Point<i32>) {
+        let behavior = self.behavior.borrow();
+        behavior.mouse_moved(position);
+    }
+
+    pub fn scroll_wheel(&self, event: &ScrollEvent) -> bool {
+        let behavior = self.behavior.borrow();
+        behavior.scroll_wheel(event)
+    }
+
+    /// Sends `event` to the view at `point`, relative to the top left of
+    /// this view, and on to its superviews until one scrolls. Returns the
+    /// view that scrolled, if any.
+    pub(crate) fn scroll_wheel_at(&self, point: &Point<i32>, event: &ScrollEvent) -> Option<View> {
+        self.first_in_responder_path(point, |view, _| view.scroll_wheel(event).then(|| view.clone()))
+    }
+
+    pub fn presses_began(&self, presses: &[Press], _event: &PressEvent) {
+        let behavior = self.behavior.borrow();
+        behavior.presses_began(presses);
+    }
+
+    pub fn presses_changed(&self, presses: &[Press], _event: &PressEvent) {
+        let behavior = self.behavior.borrow();
+        behavior.presses_changed(presses);
+    }
+
+    pub fn presses_ended(&self, presses: &[Press], _event: &PressEvent) {
+        let behavior = self.behavior.borrow();
+        behavior.presses_ended(presses);
+    }
+
+    pub fn presses_cancelled(&self, presses: &[Press], _event: &PressEvent) {
+        let behavior = self.behavior.borrow();
+        behavior.presses_cancelled(presses);
+    }
+
     pub fn press_began(&self, press: &Press, _event: &PressEvent) {
         let behavior = self.behavior.borrow();
         behavior.press_began(press);
@@ -252,6 +1295,11 @@ impl View {
         behavior.press_ended(press);
     }
 
+    pub fn press_repeated(&self, press: &Press) {
+        let behavior = self.behavior.borrow();
+        behavior.press_repeated(press);
+    }
+
     /// Returns the location of this view in the highest superview coordinate
     /// space (usually the window).
     pub fn get_location_in_window(&self) -> Point<i32> {
@@ -277,43 +1325,94 @@ impl View {
 
     /// Convert the given point from the coordinate system of this view to the
     /// coordinate system of the given view.
+    ///
+    /// Points are relative to the top left of each view. The views' frames,
+    /// the bounds of the views between them, and any transforms are all
+    /// accounted for, so the point is at the same place on screen. Both views
+    /// should be in the same window.
     pub fn convert_point_to(&self, point: &Point<i32>, to_view: &View) -> Point<i32> {
-        let from = self.get_location_in_window();
-        let to = to_view.get_location_in_window();
+        self.transform_to(to_view).apply_to_point(point)
+    }
 
-        let x_shift = from.x - to.x;
-        let y_shift = from.y - to.y;
+    /// Convert the given point from the coordinate system of the given view
+    /// to the coordinate system of this view. See `convert_point_to`.
+    pub fn convert_point_from(&self, point: &Point<i32>, from_view: &View) -> Point<i32> {
+        from_view.convert_point_to(point, self)
+    }
 
-        let x = point.x + x_shift;
-        let y = point.y + y_shift;
+    /// Convert the given rectangle from the coordinate system of this view to
+    /// the coordinate system of the given view. If the rectangle is rotated
+    /// on the way, this is the smallest rectangle containing it.
+    pub fn convert_rect_to(&self, rectangle: &Rectangle<i32, u32>, to_view: &View) -> Rectangle<i32, u32> {
+        let transform = self.transform_to(to_view);
+        let left = rectangle.left() as f32;
+        let top = rectangle.top() as f32;
+        let right = rectangle.right() as f32;
+        let bottom = rectangle.bottom() as f32;
+
+        let corners = [
+            transform.apply(left, top),
+            transform.apply(right, top),
+            transform.apply(left, bottom),
+            transform.apply(right, bottom)
+        ];
+
+        let min_x = corners.iter().map(|corner| corner.0).fold(f32::INFINITY, f32::min).round();
+        let min_y = corners.iter().map(|corner| corner.1).fold(f32::INFINITY, f32::min).round();
+        let max_x = corners.iter().map(|corner| corner.0).fold(f32::NEG_INFINITY, f32::max).round();
+        let max_y = corners.iter().map(|corner| corner.1).fold(f32::NEG_INFINITY, f32::max).round();
+
+        Rectangle::new(min_x as i32, min_y as i32, (max_x - min_x) as u32, (max_y - min_y) as u32)
+    }
 
-        Point { x, y }
+    /// Convert the given rectangle from the coordinate system of the given
+    /// view to the coordinate system of this view. See `convert_rect_to`.
+    pub fn convert_rect_from(&self, rectangle: &Rectangle<i32, u32>, from_view: &View) -> Rectangle<i32, u32> {
+        from_view.convert_rect_to(rectangle, self)
     }
 
-    /// Returns the deepest subview that contains the given point.
+    /// Returns the deepest subview that contains the given point, relative to
+    /// the top left of this view.
     ///
-    /// Used for click/touch handling in regards to determining which view it
-    /// should fire an event to.
+    /// This is how touches are routed to views: the window hit tests each
+    /// touch, walking its subviews from front to back. Other pointer events
+    /// should be routed the same way.
     ///
-    /// Will not return views that have `user_interaction_enabled` set to
-    /// `false`.
+    /// Views that are hidden, fully transparent, or have
+    /// `user_interaction_enabled` set to `false` are skipped along with their
+    /// subviews, so touches on them go to the view behind.
+    ///
+    /// Behaviors can change the result by overriding `Behavior::hit_test` or
+    /// `Behavior::point_inside`.
     pub fn hit_test(&self, point: &Point<i32>) -> Option<View> {
-        let inner_self = self.inner_self.borrow();
+        let behavior = self.behavior.borrow();
+        behavior.hit_test(point)
+    }
 
-        if inner_self.hidden {
-            return None;
-        }
+    /// Whether `point`, relative to the top left of this view, is within the
+    /// view. See `Behavior::point_inside`.
+    pub fn point_inside(&self, point: &Point<i32>) -> bool {
+        let behavior = self.behavior.borrow();
+        behavior.point_inside(point)
+    }
 
-        let user_interaction_enabled = inner_self.user_interaction_enabled;
+    /// The hit testing behind `DefaultBehavior::hit_test`: this view, or the
+    /// frontmost of its subviews containing `point`.
+    pub(crate) fn default_hit_test(&self, point: &Point<i32>) -> Option<View> {
+        {
+            let inner_self = self.inner_self.borrow();
 
-        let relative_frame = Rectangle {
-            origin: Point::new(0, 0),
-            size: inner_self.frame.size.clone(),
-        };
+            if inner_self.hidden || inner_self.alpha == 0.0 || !inner_self.user_interaction_enabled {
+                return None;
+            }
+        }
 
-        if relative_frame.contains(point) && user_interaction_enabled {
+        if self.point_inside(point) {
             for subview in self.subviews().iter().rev() {
-                let subview_point = self.convert_point_to(point, subview);
+                let subview_point = match subview.convert_point_from_superview(point) {
+                    Some(subview_point) => subview_point,
+                    None => continue
+                };
 
                 let hit_test_result = subview.hit_test(&subview_point);
 
@@ -328,13 +1427,26 @@ impl View {
         None
     }
 
+    /// Moves and resizes the view within its superview.
+    ///
+    /// If the size changes, subviews with an autoresizing mask are moved and
+    /// resized to match.
     pub fn set_frame(&self, frame: Rectangle<i32, u32>) {
+        let old_size = self.frame().size;
+        let new_size = frame.size.clone();
+
+        if self.frame() != frame {
+            // Where the view was has to be drawn again, as well as where it
+            // is now.
+            self.set_needs_display_in_superview();
+        }
+
         {
             let mut inner_self = self.inner_self.borrow_mut();
 
             let bounds = Rectangle {
                 origin: inner_self.bounds.origin.clone(),
-                size: inner_self.frame.size.clone(),
+                size: frame.size.clone(),
             };
 
             if inner_self.frame == frame && inner_self.bounds == bounds {
@@ -345,9 +1457,116 @@ impl View {
             inner_self.bounds = bounds;
         }
 
+        if old_size != new_size {
+            let layout_margins = self.layout_margins();
+            self.resize_subviews(
+                &Rectangle { origin: Point::new(0, 0), size: old_size }.inset_by_edges(&layout_margins),
+                &Rectangle { origin: Point::new(0, 0), size: new_size }.inset_by_edges(&layout_margins)
+            );
+            self.layout_subviews();
+        }
+
+        self.set_needs_display_in_superview();
+        self.set_needs_display();
+    }
+
+    /// Tells this view and its subviews that the window is now drawn at
+    /// `render_scale`, and redraws them. See
+    /// `Behavior::render_scale_did_change`.
+    pub fn render_scale_did_change(&self, render_scale: f32) {
+        {
+            let behavior = self.behavior.borrow();
+            behavior.render_scale_did_change(render_scale);
+        }
+
+        for subview in self.subviews() {
+            subview.render_scale_did_change(render_scale);
+        }
+
+        self.set_needs_display();
+    }
+
+    /// The space to leave between the view's edges and the subviews laid
+    /// out in it. Defaults to none.
+    pub fn layout_margins(&self) -> EdgeInsets {
+        self.inner_self.borrow().layout_margins
+    }
+
+    /// Sets the space to leave between the view's edges and its subviews,
+    /// and lays them out again.
+    ///
+    /// Subviews with an autoresizing mask keep to the margins: their
+    /// flexible parts stretch or shrink as the margins change, and the rest
+    /// stay the same distance from the margins. Views laying out their own
+    /// subviews, in `Behavior::layout_subviews`, place them within
+    /// `layout_margins_rectangle` rather than the whole bounds.
+    pub fn set_layout_margins(&self, layout_margins: EdgeInsets) {
+        let (old_layout_margins, size) = {
+            let mut inner_self = self.inner_self.borrow_mut();
+
+            if inner_self.layout_margins == layout_margins {
+                return;
+            }
+
+            let old_layout_margins = std::mem::replace(&mut inner_self.layout_margins, layout_margins);
+            (old_layout_margins, inner_self.bounds.size.clone())
+        };
+
+        let bounds = Rectangle { origin: Point::new(0, 0), size };
+        self.resize_subviews(&bounds.inset_by_edges(&old_layout_margins), &bounds.inset_by_edges(&layout_margins));
+        self.layout_subviews();
         self.set_needs_display();
     }
 
+    /// The part of the view's bounds inside its layout margins, in its own
+    /// coordinate space.
+    pub fn layout_margins_rectangle(&self) -> Rectangle<i32, u32> {
+        let inner_self = self.inner_self.borrow();
+        inner_self.bounds.inset_by_edges(&inner_self.layout_margins)
+    }
+
+    /// Lays out the subviews for the view's current bounds. Called after the
+    /// view is resized, once the subviews' autoresizing masks have been
+    /// applied. See `Behavior::layout_subviews`.
+    pub fn layout_subviews(&self) {
+        trace_span!("layout_subviews", view = %self.behavior_name());
+
+        let behavior = self.behavior.borrow();
+        behavior.layout_subviews();
+    }
+
+    /// The parts of the view's frame that change when its superview is
+    /// resized.
+    pub fn autoresizing_mask(&self) -> Vec<AutoresizingMask> {
+        self.inner_self.borrow().autoresizing_mask.clone()
+    }
+
+    /// Sets which parts of the view's frame change when its superview is
+    /// resized, such as `FlexibleWidth` to stretch with it. With none, the
+    /// default, the view keeps its frame. See `AutoresizingMask`.
+    pub fn set_autoresizing_mask(&self, mask: &[AutoresizingMask]) {
+        self.inner_self.borrow_mut().autoresizing_mask = mask.to_vec();
+    }
+
+    /// Applies the subviews' autoresizing masks after the part of this view
+    /// inside its layout margins changed from `old_rectangle` to
+    /// `new_rectangle`, whether by resizing the view or changing the
+    /// margins.
+    fn resize_subviews(&self, old_rectangle: &Rectangle<i32, u32>, new_rectangle: &Rectangle<i32, u32>) {
+        let layout_direction = self.effective_layout_direction();
+        for subview in self.subviews() {
+            let mask = subview.autoresizing_mask();
+            if !mask.is_empty() {
+                let mut frame = subview.frame();
+                frame.origin = frame.origin - old_rectangle.origin.clone();
+
+                let mut frame = autoresized_frame(&frame, &mask, layout_direction, &old_rectangle.size, &new_rectangle.size);
+                frame.origin = frame.origin + new_rectangle.origin.clone();
+                subview.set_frame(frame);
+            }
+        }
+    }
+
     pub fn bounds(&self) -> Rectangle<i32, u32> {
         self.inner_self.borrow().bounds.clone()
     }
@@ -366,7 +1585,7 @@ impl View {
     pub fn is_point_inside(&self, point: &Point<i32>) -> bool {
         let inner_self = self.inner_self.borrow();
         let bounds = &inner_self.bounds;
-        bounds.contains(point)
+        bounds.contains_point(point)
     }
 
     pub fn is_window(&self) -> bool {
@@ -425,25 +1644,156 @@ impl View {
     ///
     /// If this returns `false`, the existing first responder has denied the
     /// request to switch. That is, the already existing first responder
-    /// returned `false` when `resign_first_responder` was called.
+    /// returned `false` from `can_resign_first_responder`.
     ///
     /// Note: This requires the view to be within the view hierarchy belonging
     /// to a `Window`. If the view is not in a window, this will return `false`.
     pub fn become_first_responder(&self) -> bool {
+        match self.window() {
+            Some(window) => window.replace_first_responder(self.clone()),
+            None => false
+        }
+    }
+
+    /// Stops this view being the first responder, leaving the window as the
+    /// first responder.
+    ///
+    /// Returns `false` if the view isn't the first responder, or it refused
+    /// to resign.
+    pub fn resign_first_responder(&self) -> bool {
+        match self.window() {
+            Some(window) if window.first_responder() == *self => {
+                window.replace_first_responder(window.view.clone())
+            },
+            _ => false
+        }
+    }
+
+    /// Whether this view is its window's first responder.
+    pub fn is_first_responder(&self) -> bool {
+        match self.window() {
+            Some(window) => window.first_responder() == *self,
+            None => false
+        }
+    }
+
+    /// Whether Tab can move keyboard focus to this view. Any view can still
+    /// be made the first responder with `become_first_responder`.
+    pub fn can_become_first_responder(&self) -> bool {
+        let behavior = self.behavior.borrow();
+        behavior.can_become_first_responder()
+    }
+
+    /// Whether this view is a control, such as a `Button`, that Tab moves
+    /// keyboard focus to when its window has full keyboard access. See
+    /// `Window::set_full_keyboard_access`.
+    pub fn is_control(&self) -> bool {
+        let behavior = self.behavior.borrow();
+        behavior.is_control()
+    }
+
+    /// The view at the top of this view's hierarchy, such as its window.
+    /// A view without a superview is its own root.
+    pub(crate) fn root_view(&self) -> View {
+        let mut current_view = self.clone();
+        while let Some(superview) = current_view.superview().upgrade() {
+            current_view = superview;
+        }
+        current_view
+    }
+
+    /// The window this view is in, if any.
+    pub fn window(&self) -> Option<Window> {
         let mut current_view = self.clone();
         loop {
             if current_view.is_window() {
-                let window = Window::from_view(current_view.clone());
-                window.replace_first_responder(self.clone());
-                return true;
+                return Some(Window::from_view(current_view));
+            }
+
+            current_view = current_view.superview().upgrade()?;
+        }
+    }
+
+    /// The views in this view's hierarchy, including itself, that Tab moves
+    /// keyboard focus through, in order. Views are ordered depth first, with
+    /// each view before its subviews and subviews in the order they were
+    /// added. Hidden views and their subviews are skipped, as are views
+    /// with user interaction disabled and their subviews.
+    ///
+    /// Controls such as buttons are only included when the view's window
+    /// has full keyboard access.
+    pub fn focusable_views(&self) -> Vec<View> {
+        let full_keyboard_access = self.window().is_some_and(|window| window.full_keyboard_access());
+        self.tab_order(full_keyboard_access)
+    }
+
+    /// The views Tab moves keyboard focus through, as `focusable_views`,
+    /// with or without full keyboard access.
+    pub(crate) fn tab_order(&self, full_keyboard_access: bool) -> Vec<View> {
+        let mut views = Vec::new();
+        self.collect_focusable_views(full_keyboard_access, &mut views);
+        views
+    }
+
+    /// The visible views in this view's hierarchy that could take keyboard
+    /// focus, being controls or able to become the first responder, but
+    /// that Tab doesn't reach, leaving out `excluded` views and their
+    /// subviews. Views with user interaction disabled on them or a
+    /// superview can't be reached, nor can controls without full keyboard
+    /// access.
+    pub(crate) fn unreachable_focusable_views(&self, full_keyboard_access: bool, excluded: &[View]) -> Vec<View> {
+        let tab_order = self.tab_order(full_keyboard_access);
+        let mut unreachable_views = Vec::new();
+        let mut stack = vec![self.clone()];
+
+        while let Some(view) = stack.pop() {
+            if view.is_hidden() || excluded.contains(&view) {
+                continue;
             }
 
-            if let Some(superview) = current_view.superview().upgrade() {
-                current_view = superview;
-            } else {
-                return false;
+            if (view.can_become_first_responder() || view.is_control()) && !tab_order.contains(&view) {
+                unreachable_views.push(view.clone());
             }
+
+            stack.extend(view.subviews().into_iter().rev());
+        }
+
+        unreachable_views
+    }
+
+    fn collect_focusable_views(&self, full_keyboard_access: bool, views: &mut Vec<View>) {
+        if self.is_hidden() || !self.is_user_interaction_enabled() {
+            return;
+        }
+
+        if self.can_become_first_responder() || (full_keyboard_access && self.is_control()) {
+            views.push(self.clone());
+        }
+
+        for subview in self.subviews().iter() {
+            subview.collect_focusable_views(full_keyboard_access, views);
+        }
+    }
+
+    /// The view Tab (or Shift+Tab when `backwards`) moves keyboard focus to
+    /// from `current`, wrapping around at the ends. With no current view,
+    /// or one that isn't focusable, focus moves to the first (or last)
+    /// view.
+    pub fn focusable_view_after(&self, current: Option<&View>, backwards: bool) -> Option<View> {
+        let views = self.focusable_views();
+        if views.is_empty() {
+            return None;
         }
+
+        let position = current.and_then(|current| views.iter().position(|view| view == current));
+        let index = match (position, backwards) {
+            (Some(position), false) => (position + 1) % views.len(),
+            (Some(position), true) => (position + views.len() - 1) % views.len(),
+            (None, false) => 0,
+            (None, true) => views.len() - 1
+        };
+
+        Some(views[index].clone())
     }
 
     pub fn can_resign_first_responder(&self) -> bool {
@@ -455,6 +1805,50 @@ impl View {
         let behavior = self.behavior.borrow();
         behavior.text_input_did_receive(text);
     }
+
+    /// The undo manager for this view, found by walking up the responder
+    /// chain. See `Behavior::undo_manager`.
+    pub fn undo_manager(&self) -> Option<Rc<RefCell<UndoManager>>> {
+        let behavior = self.behavior.borrow();
+        behavior.undo_manager()
+    }
+
+    /// Performs `command` in this view, or the first view up the responder
+    /// chain that handles it. Returns `false` if none of them do.
+    pub fn perform_command(&self, command: &Command) -> bool {
+        let behavior = self.behavior.borrow();
+        behavior.perform_command(command)
+    }
+
+    /// Whether `perform_command` would handle `command` now.
+    pub fn can_perform_command(&self, command: &Command) -> bool {
+        let behavior = self.behavior.borrow();
+        behavior.can_perform_command(command)
+    }
+
+    /// Undoes the last action of this view's undo manager. Returns `false`
+    /// if there's no undo manager or nothing to undo.
+    pub fn undo(&self) -> bool {
+        match self.undo_manager() {
+            Some(undo_manager) if undo_manager.borrow().can_undo() => {
+                undo_manager.borrow_mut().undo();
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Redoes the last undone action of this view's undo manager. Returns
+    /// `false` if there's no undo manager or nothing to redo.
+    pub fn redo(&self) -> bool {
+        match self.undo_manager() {
+            Some(undo_manager) if undo_manager.borrow().can_redo() => {
+                undo_manager.borrow_mut().redo();
+                true
+            },
+            _ => false
+        }
+    }
 }
 
 impl LayerDelegate for View {
@@ -493,7 +1887,59 @@ impl std::fmt::Debug for View {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::macros::*;
     use crate::graphics::Size;
+    use crate::ui::view::TextField;
+    use crate::ui::SemanticColor;
+    use crate::ui::Button;
+
+    #[test]
+    fn test_focusable_views() {
+        let root = View::new(Rectangle::new(0, 0, 300, 300));
+        let group = View::new(Rectangle::new(0, 0, 300, 100));
+        let first = TextField::new(Rectangle::new(0, 0, 100, 100), "".to_string());
+        let second = TextField::new(Rectangle::new(100, 0, 100, 100), "".to_string());
+        let hidden = TextField::new(Rectangle::new(200, 0, 100, 100), "".to_string());
+        let third = TextField::new(Rectangle::new(0, 100, 100, 100), "".to_string());
+
+        group.add_subview(first.view.clone());
+        group.add_subview(second.view.clone());
+        group.add_subview(hidden.view.clone());
+        hidden.view.set_hidden(true);
+        root.add_subview(group.clone());
+        root.add_subview(third.view.clone());
+
+        assert!(!group.can_become_first_responder());
+        assert_eq!(root.focusable_views(), vec![first.view.clone(), second.view.clone(), third.view.clone()]);
+
+        assert_eq!(root.focusable_view_after(None, false), Some(first.view.clone()));
+        assert_eq!(root.focusable_view_after(None, true), Some(third.view.clone()));
+        assert_eq!(root.focusable_view_after(Some(&first.view), false), Some(second.view.clone()));
+        assert_eq!(root.focusable_view_after(Some(&third.view), false), Some(first.view.clone()));
+        assert_eq!(root.focusable_view_after(Some(&first.view), true), Some(third.view.clone()));
+        assert_eq!(root.focusable_view_after(Some(&group), false), Some(first.view.clone()));
+
+        // Controls join in with full keyboard access, but views with user
+        // interaction disabled on a superview still can't be reached.
+        let button = Button::new(Rectangle::new(0, 200, 100, 40), "OK", || {});
+        root.add_subview(button.view.clone());
+        assert!(button.view.is_control());
+        assert_eq!(root.tab_order(true), vec![first.view.clone(), second.view.clone(), third.view.clone(), button.view.clone()]);
+        assert_eq!(root.unreachable_focusable_views(false, &[]), vec![button.view.clone()]);
+        assert!(root.unreachable_focusable_views(true, &[]).is_empty());
+
+        group.set_user_interaction_enabled(false);
+        assert_eq!(root.tab_order(true), vec![third.view.clone(), button.view.clone()]);
+        assert_eq!(root.unreachable_focusable_views(true, &[]), vec![first.view.clone(), second.view.clone()]);
+        assert!(root.unreachable_focusable_views(true, &[group.clone()]).is_empty());
+        group.set_user_interaction_enabled(true);
+
+        // Outside a window nothing can be the first responder.
+        assert!(!first.view.become_first_responder());
+        assert!(!first.view.is_first_responder());
+        assert!(!first.view.resign_first_responder());
+        assert!(first.view.window().is_none());
+    }
 
     #[test]
     fn test_get_location_in_window() {
@@ -597,6 +2043,30 @@ mod tests {
         assert_eq!(main.convert_point_to(&Point { x: 2, y: 2 }, &c), Point { x: 0, y: 1 });
     }
 
+    #[test]
+    fn test_convert_through_transforms_and_bounds() {
+        let main = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let scrolled = View::new(Rectangle::new(100, 100, 200, 200));
+        scrolled.set_bounds(Rectangle::new(0, 50, 200, 200));
+        let rotated = View::new(Rectangle::new(0, 100, 100, 20));
+        rotated.set_transform(Transform::rotation(std::f32::consts::FRAC_PI_2));
+        main.add_subview(scrolled.clone());
+        scrolled.add_subview(rotated.clone());
+
+        // Scrolled down by 50, the view's center is at 50, 60 from the top
+        // left of `scrolled`. Rotated a quarter turn about it, the view's top
+        // left corner ends up at the top right, 10 right of the center and
+        // 50 above it.
+        assert_eq!(rotated.convert_point_to(&Point::new(0, 0), &scrolled), Point::new(60, 10));
+        assert_eq!(rotated.convert_point_to(&Point::new(0, 0), &main), Point::new(160, 110));
+        assert_eq!(main.convert_point_to(&Point::new(160, 110), &rotated), Point::new(0, 0));
+        assert_eq!(rotated.convert_point_from(&Point::new(160, 110), &main), Point::new(0, 0));
+
+        let rectangle = rotated.convert_rect_to(&Rectangle::new(0, 0, 100, 20), &main);
+        assert_eq!(rectangle, Rectangle::new(140, 110, 20, 100));
+        assert_eq!(main.convert_rect_from(&Rectangle::new(0, 0, 100, 20), &rotated), rectangle);
+    }
+
     #[test]
     fn test_point_inside() {
         let frame = Rectangle::new(0, 0, 1000, 1000);
@@ -637,6 +2107,283 @@ mod tests {
         assert_eq!(result, red);
     }
 
+    #[test]
+    fn test_cursor_at() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let text_field = TextField::new(Rectangle::new(10, 10, 100, 30), "".to_string());
+        let child = View::new(Rectangle::new(200, 200, 100, 100));
+        let grandchild = View::new(Rectangle::new(10, 10, 50, 50));
+        child.add_subview(grandchild.clone());
+        parent_view.add_subview(text_field.view.clone());
+        parent_view.add_subview(child.clone());
+
+        assert_eq!(parent_view.cursor_at(&Point::new(500, 500)), Cursor::Arrow);
+        assert_eq!(parent_view.cursor_at(&Point::new(20, 20)), Cursor::IBeam);
+
+        // Subviews without a cursor use their superview's, and a cursor set
+        // on the view takes precedence over its behavior's.
+        child.set_cursor(Some(Cursor::PointingHand));
+        assert_eq!(parent_view.cursor_at(&Point::new(220, 220)), Cursor::PointingHand);
+        text_field.view.set_cursor(Some(Cursor::NotAllowed));
+        assert_eq!(parent_view.cursor_at(&Point::new(20, 20)), Cursor::NotAllowed);
+    }
+
+    #[test]
+    fn test_files_dropped_at() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let child = View::new(Rectangle::new(10, 10, 100, 100));
+        let grandchild = View::new(Rectangle::new(10, 10, 50, 50));
+        child.add_subview(grandchild.clone());
+        parent_view.add_subview(child.clone());
+
+        let paths = vec![PathBuf::from("/tmp/a.png"), PathBuf::from("/tmp/b.png")];
+        assert!(parent_view.files_dropped_at(&Point::new(30, 30), &paths).is_none());
+
+        // Subviews without an action send the files to their superview's.
+        let dropped = Rc::new(RefCell::new(Vec::new()));
+        let dropped_clone = dropped.clone();
+        child.on_files_dropped(move |_view, paths| dropped_clone.borrow_mut().extend_from_slice(paths));
+        assert_eq!(parent_view.files_dropped_at(&Point::new(30, 30), &paths), Some(child.clone()));
+        assert_eq!(*dropped.borrow(), paths);
+        assert!(parent_view.files_dropped_at(&Point::new(500, 500), &paths).is_none());
+    }
+
+    #[test]
+    fn test_set_appearance_restyles_subviews() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let child = View::new(Rectangle::new(10, 10, 100, 100));
+        let light_child = View::new(Rectangle::new(10, 10, 100, 100));
+        let red_child = View::new(Rectangle::new(10, 10, 100, 100));
+        red_child.set_background_color(Color::red());
+        let white_child = View::new(Rectangle::new(10, 10, 100, 100));
+        white_child.set_background_color(Color::white());
+        light_child.set_appearance(Some(Appearance::light()));
+        parent_view.add_subview(child.clone());
+        parent_view.add_subview(light_child.clone());
+        parent_view.add_subview(red_child.clone());
+        parent_view.add_subview(white_child.clone());
+
+        let dark = Appearance::dark();
+        parent_view.set_appearance(Some(dark.clone()));
+        assert_eq!(child.effective_appearance(), dark);
+        assert_eq!(child.background_color(), dark.color(SemanticColor::Background));
+
+        // Subviews with an appearance of their own, and colors that aren't
+        // semantic, are left alone.
+        assert_eq!(light_child.background_color(), Color::white());
+        assert_eq!(red_child.background_color(), Color::red());
+
+        // Nor are colors set by the app that happen to be the appearance's.
+        assert_eq!(white_child.background_color(), Color::white());
+
+        parent_view.set_appearance(Some(Appearance::light()));
+        assert_eq!(child.background_color(), Color::white());
+    }
+
+    #[test]
+    fn test_set_layout_direction_mirrors_subviews() {
+        let parent_view = View::new(Rectangle::new(0, 0, 300, 100));
+        parent_view.set_layout_direction(Some(LayoutDirection::LeftToRight));
+        let back_button = View::new(Rectangle::new(10, 10, 50, 30));
+        back_button.set_autoresizing_mask(&[AutoresizingMask::FlexibleTrailingMargin]);
+        let done_button = View::new(Rectangle::new(240, 10, 50, 30));
+        done_button.set_autoresizing_mask(&[AutoresizingMask::FlexibleLeadingMargin]);
+        let fixed = View::new(Rectangle::new(100, 10, 50, 30));
+        parent_view.add_subview(back_button.clone());
+        parent_view.add_subview(done_button.clone());
+        parent_view.add_subview(fixed.clone());
+
+        let rtl_child = View::new(Rectangle::new(0, 50, 100, 50));
+        rtl_child.set_layout_direction(Some(LayoutDirection::RightToLeft));
+        parent_view.add_subview(rtl_child.clone());
+
+        parent_view.set_layout_direction(Some(LayoutDirection::RightToLeft));
+        assert_eq!(back_button.effective_layout_direction(), LayoutDirection::RightToLeft);
+        assert_eq!(back_button.frame(), Rectangle::new(240, 10, 50, 30));
+        assert_eq!(done_button.frame(), Rectangle::new(10, 10, 50, 30));
+        assert_eq!(fixed.frame(), Rectangle::new(100, 10, 50, 30));
+
+        // Leading and trailing margins follow the direction as the view is
+        // resized.
+        parent_view.set_frame(Rectangle::new(0, 0, 400, 100));
+        assert_eq!(back_button.frame(), Rectangle::new(340, 10, 50, 30));
+        assert_eq!(done_button.frame(), Rectangle::new(10, 10, 50, 30));
+
+        parent_view.set_layout_direction(None);
+        assert_eq!(rtl_child.effective_layout_direction(), LayoutDirection::RightToLeft);
+    }
+
+    #[test]
+    fn test_adding_to_right_to_left_superview_mirrors_subview() {
+        let parent_view = View::new(Rectangle::new(0, 0, 300, 100));
+        parent_view.set_layout_direction(Some(LayoutDirection::RightToLeft));
+
+        // A toolbar laid out left to right before it's added.
+        let toolbar = View::new(Rectangle::new(0, 0, 300, 40));
+        toolbar.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth]);
+        let back_button = View::new(Rectangle::new(10, 5, 50, 30));
+        back_button.set_autoresizing_mask(&[AutoresizingMask::FlexibleTrailingMargin]);
+        toolbar.add_subview(back_button.clone());
+
+        let done_button = View::new(Rectangle::new(240, 50, 50, 30));
+        done_button.set_autoresizing_mask(&[AutoresizingMask::FlexibleLeadingMargin]);
+        let fixed = View::new(Rectangle::new(100, 50, 50, 30));
+
+        parent_view.add_subview(toolbar.clone());
+        parent_view.insert_subview_at(done_button.clone(), 0);
+        parent_view.add_subview(fixed.clone());
+
+        // The same as if they'd been added before the direction flipped.
+        assert_eq!(back_button.frame(), Rectangle::new(240, 5, 50, 30));
+        assert_eq!(done_button.frame(), Rectangle::new(10, 50, 50, 30));
+        assert_eq!(fixed.frame(), Rectangle::new(100, 50, 50, 30));
+
+        // Moving within the superview doesn't mirror it again.
+        parent_view.insert_subview_at(done_button.clone(), 2);
+        assert_eq!(done_button.frame(), Rectangle::new(10, 50, 50, 30));
+    }
+
+    #[test]
+    fn test_key_command_matching() {
+        use crate::ui::key::{KeyCode, ModifierFlag};
+
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let child = View::new(Rectangle::new(10, 10, 100, 100));
+        parent_view.add_subview(child.clone());
+
+        let save = Key::new(KeyCode::S, vec![ModifierFlag::Command]);
+        assert!(child.key_command_matching(&save).is_none());
+
+        // Shortcuts nearer the first responder win over their superview's.
+        parent_view.add_key_command(KeyCommand::new(KeyCode::S, &[ModifierFlag::Command], || {}).titled("Parent"));
+        assert_eq!(child.key_command_matching(&save).unwrap().title(), Some("Parent"));
+        child.add_key_command(KeyCommand::new(KeyCode::S, &[ModifierFlag::Command], || {}).titled("Child"));
+        assert_eq!(child.key_command_matching(&save).unwrap().title(), Some("Child"));
+
+        child.remove_key_command(&save);
+        assert!(child.key_commands().is_empty());
+        assert_eq!(child.key_command_matching(&save).unwrap().title(), Some("Parent"));
+    }
+
+    #[test]
+    fn test_context_menu_at() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let child = View::new(Rectangle::new(10, 10, 100, 100));
+        let grandchild = View::new(Rectangle::new(10, 10, 50, 50));
+        child.add_subview(grandchild.clone());
+        parent_view.add_subview(child.clone());
+
+        assert!(parent_view.context_menu_at(&Point::new(30, 30)).is_none());
+
+        // Subviews without a menu use their superview's.
+        child.set_context_menu(Some(Menu::new("Child", Vec::new())));
+        assert_eq!(parent_view.context_menu_at(&Point::new(30, 30)).unwrap().title(), "Child");
+        grandchild.set_context_menu(Some(Menu::new("Grandchild", Vec::new())));
+        assert_eq!(parent_view.context_menu_at(&Point::new(30, 30)).unwrap().title(), "Grandchild");
+        assert_eq!(parent_view.context_menu_at(&Point::new(100, 100)).unwrap().title(), "Child");
+        assert!(parent_view.context_menu_at(&Point::new(500, 500)).is_none());
+    }
+
+    #[test]
+    fn test_hit_test_skips_hidden_and_disabled_views() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let back = View::new(Rectangle::new(0, 0, 100, 100));
+        let front = View::new(Rectangle::new(0, 0, 100, 100));
+        let child = View::new(Rectangle::new(0, 0, 50, 50));
+        front.add_subview(child.clone());
+        parent_view.add_subview(back.clone());
+        parent_view.add_subview(front.clone());
+
+        let point = Point::new(25, 25);
+        assert_eq!(parent_view.hit_test(&point).unwrap(), child);
+
+        // The whole subtree is skipped, and the touch goes to the view behind.
+        front.set_hidden(true);
+        assert_eq!(parent_view.hit_test(&point).unwrap(), back);
+        front.set_hidden(false);
+
+        front.set_user_interaction_enabled(false);
+        assert!(!front.is_user_interaction_enabled());
+        assert_eq!(parent_view.hit_test(&point).unwrap(), back);
+        front.set_user_interaction_enabled(true);
+
+        front.set_alpha(0.0);
+        assert_eq!(parent_view.hit_test(&point).unwrap(), back);
+        front.set_alpha(1.0);
+
+        // A disabled subview leaves the touch with its superview.
+        child.set_user_interaction_enabled(false);
+        assert_eq!(parent_view.hit_test(&point).unwrap(), front);
+
+        parent_view.set_hidden(true);
+        assert_eq!(parent_view.hit_test(&point), None);
+    }
+
+    custom_view!(
+        PassThroughView subclasses DefaultBehavior
+
+        struct PassThroughBehavior {
+
+        }
+
+        impl Behavior {
+            fn point_inside(&self, point: &Point<i32>) -> bool {
+                // A margin of 10 around the view can also be touched.
+                let size = self.view.upgrade().unwrap().frame().size;
+                Rectangle::new(-10, -10, size.width + 20, size.height + 20).contains_point(point)
+            }
+
+            fn hit_test(&self, point: &Point<i32>) -> Option<View> {
+                // Only subviews are hit; touches elsewhere go to the views
+                // behind.
+                let view = self.view.upgrade()?;
+                self.super_behavior.hit_test(point).filter(|hit| *hit != view)
+            }
+        }
+    );
+
+    #[test]
+    fn test_overridden_hit_test() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let back = View::new(Rectangle::new(0, 0, 200, 200));
+        let overlay = PassThroughView::new_all(Rectangle::new(0, 0, 200, 200));
+        let button = View::new(Rectangle::new(50, 50, 20, 20));
+        overlay.view.add_subview(button.clone());
+        parent_view.add_subview(back.clone());
+        parent_view.add_subview(overlay.view.clone());
+
+        assert_eq!(parent_view.hit_test(&Point::new(150, 150)).unwrap(), back);
+        assert_eq!(parent_view.hit_test(&Point::new(60, 60)).unwrap(), button);
+
+        assert!(overlay.view.point_inside(&Point::new(-5, 205)));
+        assert!(!button.point_inside(&Point::new(-5, -5)));
+    }
+
+    #[test]
+    fn test_hit_test_transformed() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+
+        // Scaled to half its size, it's drawn from 35, 35 to 85, 85.
+        let scaled = View::new(Rectangle::new(10, 10, 100, 100));
+        scaled.set_transform(Transform::scale(0.5, 0.5));
+        parent_view.add_subview(scaled.clone());
+
+        assert_eq!(parent_view.hit_test(&Point::new(20, 20)).unwrap(), parent_view);
+        assert_eq!(parent_view.hit_test(&Point::new(80, 80)).unwrap(), scaled);
+
+        // Rotated a quarter turn, a wide bar becomes a tall one.
+        let rotated = View::new(Rectangle::new(200, 245, 100, 10));
+        rotated.set_transform(Transform::rotation(std::f32::consts::FRAC_PI_2));
+        parent_view.add_subview(rotated.clone());
+
+        assert_eq!(parent_view.hit_test(&Point::new(250, 210)).unwrap(), rotated);
+        assert_eq!(parent_view.hit_test(&Point::new(210, 250)).unwrap(), parent_view);
+
+        // A view scaled to nothing can't be hit.
+        rotated.set_transform(Transform::scale(0.0, 0.0));
+        assert_eq!(parent_view.hit_test(&Point::new(250, 250)).unwrap(), parent_view);
+    }
+
     #[test]
     fn test_set_frame() {
         let frame = Rectangle::new(0, 0, 1000, 1000);
@@ -648,7 +2395,161 @@ mod tests {
         view.set_frame(new_frame.clone());
 
         assert_eq!(view.frame(), new_frame);
-        assert_eq!(view.bounds().size(), &Size::new(1000, 1000));
+        assert_eq!(view.bounds().size(), &Size::new(100, 100));
+    }
+
+    /// A view behavior that records the hooks it's sent.
+    struct RecordingBehavior {
+        view: WeakView,
+        super_behavior: Box<dyn Behavior>,
+        log: Rc<RefCell<Vec<String>>>
+    }
+
+    impl RecordingBehavior {
+        fn new_view(frame: Rectangle<i32, u32>, log: &Rc<RefCell<Vec<String>>>) -> View {
+            let behavior = RecordingBehavior {
+                view: WeakView::none(),
+                super_behavior: Box::new(DefaultBehavior { view: WeakView::none() }),
+                log: log.clone()
+            };
+            View::new_with_behavior(Box::new(behavior), frame, "recording")
+        }
+    }
+
+    impl Behavior for RecordingBehavior {
+        fn super_behavior(&self) -> Option<&Box<dyn Behavior>> {
+            Some(&self.super_behavior)
+        }
+
+        fn mut_super_behavior(&mut self) -> Option<&mut dyn Behavior> {
+            Some(self.super_behavior.as_mut())
+        }
+
+        fn set_view(&mut self, view: WeakView) {
+            self.view = view;
+        }
+
+        fn get_view(&self) -> &WeakView {
+            &self.view
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn layout_subviews(&self) {
+            let size = self.view.upgrade().unwrap().bounds().size;
+            self.log.borrow_mut().push(format!("layout {}x{}", size.width, size.height));
+        }
+
+        fn render_scale_did_change(&self, render_scale: f32) {
+            self.log.borrow_mut().push(format!("scale {}", render_scale));
+        }
+
+        fn set_needs_display_in_rect(&self, rectangle: &Rectangle<i32, u32>) {
+            let Rectangle { origin, size } = rectangle;
+            self.log.borrow_mut().push(format!("dirty {},{} {}x{}", origin.x, origin.y, size.width, size.height));
+            self.super_behavior.set_needs_display_in_rect(rectangle);
+        }
+    }
+
+    #[test]
+    fn test_set_frame_lays_out_subviews() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let view = RecordingBehavior::new_view(Rectangle::new(0, 0, 400, 300), &log);
+
+        // Moving the view doesn't need a layout, resizing it does.
+        view.set_frame(Rectangle::new(10, 10, 400, 300));
+        assert!(log.borrow().is_empty());
+        view.set_frame(Rectangle::new(10, 10, 800, 600));
+        assert_eq!(*log.borrow(), vec!["layout 800x600"]);
+    }
+
+    #[test]
+    fn test_set_needs_display_in_superview() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let view = RecordingBehavior::new_view(Rectangle::new(0, 0, 400, 300), &log);
+        view.set_bounds(Rectangle::new(0, 10, 400, 300));
+        let subview = View::new(Rectangle::new(10, 20, 30, 40));
+        view.add_subview(subview.clone());
+        assert_eq!(*log.borrow(), vec!["dirty 10,10 30x40"]);
+        log.borrow_mut().clear();
+
+        // Both where the subview was and where it is now are drawn again.
+        subview.set_frame(Rectangle::new(100, 20, 30, 40));
+        log.borrow_mut().dedup();
+        assert_eq!(*log.borrow(), vec!["dirty 10,10 30x40", "dirty 100,10 30x40"]);
+        log.borrow_mut().clear();
+
+        // The subview hasn't been drawn yet, so all of it has to be.
+        subview.set_needs_display_in_rect(&Rectangle::new(5, 5, 10, 10));
+        assert_eq!(*log.borrow(), vec!["dirty 100,10 30x40"]);
+        log.borrow_mut().clear();
+
+        // Transformed views can be drawn anywhere in the superview.
+        subview.set_transform(Transform::rotation(45.0));
+        subview.set_alpha(0.5);
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_render_scale_did_change() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let view = View::new(Rectangle::new(0, 0, 400, 300));
+        let container = View::new(Rectangle::new(0, 0, 200, 200));
+        view.add_subview(container.clone());
+        container.add_subview(RecordingBehavior::new_view(Rectangle::new(0, 0, 100, 100), &log));
+        container.add_subview(RecordingBehavior::new_view(Rectangle::new(100, 0, 100, 100), &log));
+
+        view.render_scale_did_change(2.0);
+        assert_eq!(*log.borrow(), vec!["scale 2", "scale 2"]);
+    }
+
+    #[test]
+    fn test_set_frame_autoresizes_subviews() {
+        let view = View::new(Rectangle::new(0, 0, 400, 300));
+        let toolbar = View::new(Rectangle::new(0, 0, 400, 40));
+        toolbar.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth]);
+        let close_button = View::new(Rectangle::new(360, 0, 40, 40));
+        close_button.set_autoresizing_mask(&[AutoresizingMask::FlexibleLeftMargin]);
+        let fixed = View::new(Rectangle::new(10, 50, 40, 40));
+        view.add_subview(toolbar.clone());
+        toolbar.add_subview(close_button.clone());
+        view.add_subview(fixed.clone());
+
+        view.set_frame(Rectangle::new(0, 0, 600, 500));
+
+        assert_eq!(toolbar.frame(), Rectangle::new(0, 0, 600, 40));
+        assert_eq!(close_button.frame(), Rectangle::new(560, 0, 40, 40));
+        assert_eq!(fixed.frame(), Rectangle::new(10, 50, 40, 40));
+    }
+
+    #[test]
+    fn test_layout_margins_autoresize_subviews() {
+        let view = View::new(Rectangle::new(0, 0, 400, 300));
+        let toolbar = View::new(Rectangle::new(0, 0, 400, 40));
+        toolbar.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth]);
+        let corner = View::new(Rectangle::new(360, 260, 40, 40));
+        corner.set_autoresizing_mask(&[AutoresizingMask::FlexibleLeftMargin, AutoresizingMask::FlexibleTopMargin]);
+        let fixed = View::new(Rectangle::new(10, 50, 40, 40));
+        view.add_subview(toolbar.clone());
+        view.add_subview(corner.clone());
+        view.add_subview(fixed.clone());
+
+        // Subviews with masks move inside the margins, others stay put.
+        view.set_layout_margins(EdgeInsets::new(8, 16, 8, 16));
+        assert_eq!(toolbar.frame(), Rectangle::new(16, 8, 368, 40));
+        assert_eq!(corner.frame(), Rectangle::new(344, 252, 40, 40));
+        assert_eq!(fixed.frame(), Rectangle::new(10, 50, 40, 40));
+
+        // Resizing keeps them inside the margins.
+        view.set_frame(Rectangle::new(0, 0, 600, 500));
+        assert_eq!(toolbar.frame(), Rectangle::new(16, 8, 568, 40));
+        assert_eq!(corner.frame(), Rectangle::new(544, 452, 40, 40));
+
+        view.set_layout_margins(EdgeInsets::zero());
+        assert_eq!(toolbar.frame(), Rectangle::new(0, 0, 600, 40));
+        assert_eq!(corner.frame(), Rectangle::new(560, 460, 40, 40));
     }
 
     #[test]
@@ -666,6 +2567,40 @@ mod tests {
         assert_eq!(view.subviews().len(), 0);
     }
 
+    #[test]
+    fn test_subview_order() {
+        let view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let back = View::new(Rectangle::new(0, 0, 100, 100));
+        let front = View::new(Rectangle::new(50, 50, 100, 100));
+        view.add_subview(back.clone());
+        view.add_subview(front.clone());
+
+        // The overlap goes to the view in front.
+        let overlap = Point::new(75, 75);
+        assert_eq!(view.hit_test(&overlap).unwrap(), front);
+
+        view.bring_subview_to_front(&back);
+        assert_eq!(view.subviews(), vec![front.clone(), back.clone()]);
+        assert_eq!(view.hit_test(&overlap).unwrap(), back);
+
+        view.send_subview_to_back(&back);
+        assert_eq!(view.subviews(), vec![back.clone(), front.clone()]);
+
+        view.exchange_subviews(0, 1);
+        assert_eq!(view.subviews(), vec![front.clone(), back.clone()]);
+        view.exchange_subviews(0, 5);
+        assert_eq!(view.subviews(), vec![front.clone(), back.clone()]);
+
+        let middle = View::new(Rectangle::new(0, 0, 10, 10));
+        view.insert_subview_at(middle.clone(), 1);
+        assert_eq!(view.subviews(), vec![front.clone(), middle.clone(), back.clone()]);
+        assert_eq!(middle.superview().upgrade().unwrap(), view);
+
+        // Inserting a subview again moves it.
+        view.insert_subview_at(middle.clone(), 10);
+        assert_eq!(view.subviews(), vec![front, back, middle]);
+    }
+
     #[test]
     fn test_bounds() {
         let frame = Rectangle::new(100, 100, 500, 500);
@@ -679,6 +2614,40 @@ mod tests {
         assert_eq!(view.bounds(), Rectangle::new(10, 10, 100, 100));
     }
 
+    #[test]
+    fn test_alpha() {
+        let view = View::new(Rectangle::new(0, 0, 100, 100));
+        assert_eq!(view.alpha(), 1.0);
+
+        view.set_alpha(0.5);
+        assert_eq!(view.alpha(), 0.5);
+
+        view.set_alpha(-1.0);
+        assert_eq!(view.alpha(), 0.0);
+
+        view.set_alpha(2.0);
+        assert_eq!(view.alpha(), 1.0);
+    }
+
+    #[test]
+    fn test_corner_radius_and_border() {
+        let view = View::new(Rectangle::new(0, 0, 100, 100));
+        assert_eq!(view.corner_radius(), 0.0);
+        assert_eq!(view.border_width(), 0.0);
+        assert!(!view.clips_to_bounds());
+
+        view.set_corner_radius(8.0);
+        view.set_border(2.0, Color::red());
+        view.set_clips_to_bounds(true);
+        assert_eq!(view.corner_radius(), 8.0);
+        assert_eq!(view.border_width(), 2.0);
+        assert_eq!(view.border_color(), Color::red());
+        assert!(view.clips_to_bounds());
+
+        view.set_corner_radius(-1.0);
+        assert_eq!(view.corner_radius(), 0.0);
+    }
+
     #[test]
     fn test_tag() {
         let frame = Rectangle::new(0, 0, 1000, 1000);
@@ -696,4 +2665,74 @@ mod tests {
         let found_view = parent_view.view_with_tag(42);
         assert_eq!(found_view, Some(view));
     }
+
+    #[test]
+    fn test_identifier() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let view = View::new(Rectangle::new(0, 0, 100, 100));
+        let child = View::new(Rectangle::new(0, 0, 10, 10));
+        view.add_subview(child.clone());
+        parent_view.add_subview(view.clone());
+
+        assert_eq!(child.identifier(), None);
+        child.set_identifier(Some("close_button"));
+        assert_eq!(child.identifier(), Some(String::from("close_button")));
+
+        assert_eq!(parent_view.view_with_identifier("close_button"), Some(child.clone()));
+        assert_eq!(view.view_with_identifier("close_button"), Some(child.clone()));
+        assert_eq!(parent_view.view_with_identifier("missing"), None);
+
+        child.set_identifier(None);
+        assert_eq!(parent_view.view_with_identifier("close_button"), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "identifier \"title\"")]
+    fn test_duplicate_identifiers_panic() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let first = View::new(Rectangle::new(0, 0, 10, 10));
+        first.set_identifier(Some("title"));
+        parent_view.add_subview(first);
+
+        let second = View::new(Rectangle::new(0, 0, 10, 10));
+        second.set_identifier(Some("title"));
+        parent_view.add_subview(second);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "identifier \"title\"")]
+    fn test_duplicate_identifiers_inserted_panic() {
+        let parent_view = View::new(Rectangle::new(0, 0, 1000, 1000));
+        let first = View::new(Rectangle::new(0, 0, 10, 10));
+        first.set_identifier(Some("title"));
+        parent_view.add_subview(first);
+
+        let second = View::new(Rectangle::new(0, 0, 10, 10));
+        second.set_identifier(Some("title"));
+        parent_view.insert_subview_at(second, 0);
+    }
+
+    #[test]
+    fn test_recursive_description() {
+        let parent_view = View::new(Rectangle::new(0, 0, 320, 200));
+        let view = View::new(Rectangle::new(16, 16, 288, 24));
+        view.set_identifier(Some("title"));
+        view.add_subview(View::new(Rectangle::new(0, 0, 10, 10)));
+        parent_view.add_subview(view);
+
+        let hidden_view = View::new(Rectangle::new(16, 150, 80, 32));
+        hidden_view.set_tag(3);
+        hidden_view.set_hidden(true);
+        parent_view.add_subview(hidden_view);
+
+        let lines = [
+            "DefaultBehavior (0, 0, 320, 200)",
+            "    DefaultBehavior (16, 16, 288, 24) \"title\"",
+            "        DefaultBehavior (0, 0, 10, 10)",
+            "    DefaultBehavior (16, 150, 80, 32) tag 3 hidden"
+        ];
+        assert_eq!(parent_view.recursive_description(), lines.join("\n") + "\n");
+    }
 }
@@ -1,7 +1,17 @@
-use crate::graphics::{Layer, Rectangle};
+use crate::graphics::{EdgeInsets, Gradient, Layer, Rectangle, Transform};
 use crate::ui::Color;
+use crate::ui::shadow::{Shadow, ShadowCache};
+use crate::ui::view::AutoresizingMask;
+use crate::ui::view::TrackingArea;
 use crate::ui::view::{View, WeakView};
 use crate::ui::gesture::recognizer::Recognizer;
+use crate::ui::menu::Menu;
+use crate::ui::key_command::KeyCommand;
+use crate::ui::appearance::{Appearance, SemanticColor};
+use crate::ui::layout_direction::LayoutDirection;
+use crate::ui::accessibility::AccessibilityRole;
+use crate::platform::cursor::Cursor;
+use crate::ui::view::view::FilesDroppedCallback;
 use std::rc::Rc;
 
 pub(crate) struct ViewInner {
@@ -14,6 +24,11 @@ pub(crate) struct ViewInner {
     /// See `View::view_with_tag`
     pub(crate) tag: u32,
 
+    /// An optional name for the view, unique within its view hierarchy.
+    ///
+    /// See `View::view_with_identifier`
+    pub(crate) identifier: Option<String>,
+
     /// The size and position (within its superview) of this View.
     ///
     /// Used for placing the view in the parent.
@@ -38,6 +53,15 @@ pub(crate) struct ViewInner {
     /// rectangle with a single color - this is that color.
     pub background_color: Color,
 
+    /// The semantic color the background color was left as, so it follows
+    /// the appearance as it changes. `None` once the view is given a
+    /// background color of its own.
+    pub background_color_role: Option<SemanticColor>,
+
+    /// A gradient drawn over the background color, if any, such as for a
+    /// button or a header.
+    pub background_gradient: Option<Gradient>,
+
     /// The actual drawable canvas from the `graphics` library.
     ///
     /// Think of the View as instructions or a template for a picture (this
@@ -66,12 +90,88 @@ pub(crate) struct ViewInner {
     /// Gesture recognizers that are attached to this view.
     pub gesture_recognizers: Vec<Rc<Box<dyn Recognizer>>>,
 
+    /// The parts of the view that tell it as the pointer moves over them.
+    pub tracking_areas: Vec<TrackingArea>,
+
     /// Whether this view is visible or not. When hidden at the next render to
     /// screen, it'll behave the same as if it were not in the view hierarchy at
     /// all.
     pub hidden: bool,
 
+    /// The opacity of the view and its subviews, from 0 (transparent) to 1
+    /// (opaque). The view is drawn as a whole first, and then blended into
+    /// its superview, so overlapping subviews don't show through each other.
+    pub alpha: f32,
+
+    /// A transform applied about the center of the view when it's drawn into
+    /// its superview, such as a rotation or scale. The frame is unchanged.
+    pub transform: Transform,
+
+    /// The radius of the view's rounded corners. The background is always
+    /// rounded; subviews are only cut off at the corners if
+    /// `clips_to_bounds` is set.
+    pub corner_radius: f32,
+
+    /// The width of the border drawn along the inside edge of the view, over
+    /// its subviews.
+    pub border_width: f32,
+
+    pub border_color: Color,
+
+    /// Whether subviews are cut off at the rounded corners of the view.
+    /// Subviews never draw outside of the view's rectangle.
+    pub clips_to_bounds: bool,
+
+    /// The parts of the frame that change when the superview is resized.
+    pub autoresizing_mask: Vec<AutoresizingMask>,
+
+    /// The space to leave between the view's edges and the subviews laid
+    /// out in it.
+    pub layout_margins: EdgeInsets,
+
+    /// The shadow drawn beneath the view, into its superview.
+    pub shadow: Option<Shadow>,
+
+    /// The blurred shadow, kept until the shadow's shape changes.
+    pub(crate) shadow_cache: Option<ShadowCache>,
+
     /// Whether the view accepts user input or not. E.g. touches_began will not
     /// be called if this is `false`.
-    pub user_interaction_enabled: bool
+    pub user_interaction_enabled: bool,
+
+    /// The menu shown when the view is right-clicked or long-pressed.
+    pub context_menu: Option<Menu>,
+
+    /// The shape of the pointer over the view, if set rather than left to
+    /// the view's behavior.
+    pub cursor: Option<Cursor>,
+
+    /// The text shown next to the view once the pointer rests on it.
+    pub tooltip: Option<String>,
+
+    /// Called with the paths of files dropped on the view from other
+    /// applications.
+    pub files_dropped_action: Option<FilesDroppedCallback>,
+
+    /// Keyboard shortcuts handled while the view, or one of its subviews,
+    /// is the first responder.
+    pub key_commands: Vec<KeyCommand>,
+
+    /// The appearance of the view and its subviews, if set rather than
+    /// taken from the superview.
+    pub appearance: Option<Appearance>,
+
+    /// The layout direction of the view and its subviews, if set rather
+    /// than taken from the superview.
+    pub layout_direction: Option<LayoutDirection>,
+
+    /// What the view is read out as by screen readers, its kind of control,
+    /// and what it holds, if set rather than left to the view's behavior.
+    pub accessibility_label: Option<String>,
+    pub accessibility_role: Option<AccessibilityRole>,
+    pub accessibility_value: Option<String>,
+
+    /// Whether the view is read out on its own, if set rather than decided
+    /// by its role.
+    pub is_accessibility_element: Option<bool>
 }
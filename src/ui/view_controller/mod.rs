@@ -1,3 +1,9 @@
 mod view_controller;
 pub use view_controller::ViewControllerBehavior;
 pub use view_controller::ViewController;
+
+pub mod navigation_bar;
+pub use navigation_bar::NavigationBar;
+
+mod navigation_controller;
+pub use navigation_controller::NavigationController;
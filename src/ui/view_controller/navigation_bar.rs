@@ -0,0 +1,112 @@
+use crate::macros::*;
+use crate::ui::view::{AutoresizingMask, DefaultBehavior};
+use crate::ui::{Button, Color, Label};
+use crate::graphics::Rectangle;
+use crate::text::{HorizontalAlignment, VerticalAlignment};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub const NAVIGATION_BAR_HEIGHT: u32 = 44;
+
+/// The width of the back button, and of the space kept for it on either side
+/// of the title so the title stays centered.
+const BACK_BUTTON_WIDTH: u32 = 100;
+
+static BAR_COLOR: Color = Color::new(247, 247, 247, 255);
+
+type BackCallback = Rc<dyn Fn()>;
+
+// The bar along the top of a `NavigationController`, with the title of the
+// top view controller and a button going back to the one below it.
+custom_view!(
+    NavigationBar subclasses DefaultBehavior
+
+    struct NavigationBarBehavior {
+        back_action: RefCell<Option<BackCallback>>
+    }
+
+    impl Self {
+        /// A navigation bar `width` wide, with no title and the back button
+        /// hidden.
+        pub fn new(width: u32) -> NavigationBar {
+            let bar = Self::new_all(Rectangle::new(0, 0, width, NAVIGATION_BAR_HEIGHT), RefCell::new(None));
+            bar.view.set_background_color(BAR_COLOR.clone());
+            bar.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth]);
+
+            let title_width = width.saturating_sub(BACK_BUTTON_WIDTH * 2);
+            let title = Label::new(Rectangle::new(BACK_BUTTON_WIDTH as i32, 0, title_width, NAVIGATION_BAR_HEIGHT), String::new());
+            title.set_text_alignment(HorizontalAlignment::Center);
+            title.set_vertical_alignment(VerticalAlignment::Middle);
+            title.view.set_background_color(Color::clear());
+            title.view.set_user_interaction_enabled(false);
+            title.view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth]);
+            bar.view.add_subview(title.view);
+
+            let weak_bar = bar.view.downgrade();
+            let back = Button::new(Rectangle::new(0, 0, BACK_BUTTON_WIDTH, NAVIGATION_BAR_HEIGHT), "", move || {
+                if let Some(view) = weak_bar.upgrade() {
+                    NavigationBar::from_view(view).go_back();
+                }
+            });
+            back.view.set_hidden(true);
+            bar.view.add_subview(back.view);
+
+            bar
+        }
+
+        fn title_label(&self) -> Label {
+            Label::from_view(self.view.subviews()[0].clone())
+        }
+
+        fn back_button(&self) -> Button {
+            Button::from_view(self.view.subviews()[1].clone())
+        }
+
+        pub fn title(&self) -> String {
+            self.title_label().copy_text()
+        }
+
+        pub fn set_title(&self, title: &str) {
+            self.title_label().set_text(title.to_string());
+        }
+
+        /// The back button's title, with its arrow, or `None` if it's hidden.
+        pub fn back_title(&self) -> Option<String> {
+            let button = self.back_button();
+            if button.view.is_hidden() {
+                None
+            } else {
+                Some(button.title())
+            }
+        }
+
+        /// Shows the back button with `title`, or hides it with `None`.
+        pub fn set_back_title(&self, title: Option<&str>) {
+            let button = self.back_button();
+            match title {
+                Some(title) => {
+                    button.set_title(&format!("‹ {}", title));
+                    button.view.set_hidden(false);
+                },
+                None => button.view.set_hidden(true)
+            }
+        }
+
+        /// Sets what happens when the back button is tapped.
+        pub fn on_back(&self, action: impl Fn() + 'static) {
+            self.behavior().back_action.replace(Some(Rc::new(action)));
+        }
+
+        /// Does what tapping the back button does, if it's shown.
+        pub fn go_back(&self) {
+            if self.back_button().view.is_hidden() {
+                return;
+            }
+
+            let action = self.behavior().back_action.borrow().clone();
+            if let Some(action) = action {
+                action();
+            }
+        }
+    }
+);
@@ -0,0 +1,464 @@
+use crate::ui::view::AutoresizingMask;
+use crate::ui::view_controller::{ViewController, ViewControllerBehavior};
+use crate::ui::view_controller::navigation_bar::{NavigationBar, NAVIGATION_BAR_HEIGHT};
+use crate::ui::animation::{Animation, Easing};
+use crate::ui::{Color, SemanticColor, View};
+use crate::graphics::{Point, Rectangle};
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+const TRANSITION_DURATION: Duration = Duration::from_millis(300);
+
+/// A view controller in a navigation stack, with the view made for it and
+/// its title.
+struct NavigationEntry {
+    controller: ViewController<'static>,
+    view: View,
+    title: String
+}
+
+struct NavigationControllerInner {
+    view: View,
+    navigation_bar: NavigationBar,
+
+    // Holds the view of the top view controller, and during a transition
+    // the one it's replacing.
+    content_view: View,
+
+    stack: RefCell<Vec<NavigationEntry>>,
+
+    // Whether the navigation controller itself is shown, so the view
+    // controllers pushed on it appear.
+    is_visible: Cell<bool>,
+
+    // The animations of the transition running, if any, which is finished
+    // right away when another one starts.
+    transition: RefCell<Vec<Animation>>
+}
+
+/// Manages a stack of view controllers, showing the view of the top one.
+///
+/// Pushing a view controller slides its view in from the right over the
+/// current one, and popping slides it back out. The navigation bar along
+/// the top shows the title of the top view controller and a back button
+/// popping it, and can be hidden.
+///
+/// A navigation controller is itself a `ViewControllerBehavior`, so it can
+/// be a window's view controller, filling the window:
+///
+/// ```ignore
+/// let navigation_controller = NavigationController::new(ViewController::new(Home), "Home");
+/// let window = Window::new("App", frame, ViewController::new(navigation_controller.clone()));
+/// navigation_controller.push(ViewController::new(Settings), "Settings", true);
+/// ```
+///
+/// It can also be put anywhere by adding its `view()`, though the view
+/// controllers on it then only get their appearance callbacks once the
+/// navigation controller's own are called.
+#[derive(Clone)]
+pub struct NavigationController {
+    inner: Rc<NavigationControllerInner>
+}
+
+impl NavigationController {
+    /// A navigation controller with `root` at the bottom of its stack.
+    pub fn new(root: ViewController<'static>, title: &str) -> NavigationController {
+        let frame = Rectangle::new(0, 0, 320, 480);
+        let view = View::new(frame.clone());
+        view.set_background_color(Color::semantic(SemanticColor::Background));
+        view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth, AutoresizingMask::FlexibleHeight]);
+
+        let navigation_bar = NavigationBar::new(frame.size.width);
+        let content_view = View::new(Rectangle::new(0, NAVIGATION_BAR_HEIGHT as i32, frame.size.width, frame.size.height - NAVIGATION_BAR_HEIGHT));
+        content_view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth, AutoresizingMask::FlexibleHeight]);
+        view.add_subview(content_view.clone());
+        view.add_subview(navigation_bar.view.clone());
+
+        let navigation_controller = NavigationController {
+            inner: Rc::new(NavigationControllerInner {
+                view,
+                navigation_bar,
+                content_view,
+                stack: RefCell::new(Vec::new()),
+                is_visible: Cell::new(false),
+                transition: RefCell::new(Vec::new())
+            })
+        };
+
+        let weak_inner = Rc::downgrade(&navigation_controller.inner);
+        navigation_controller.inner.navigation_bar.on_back(move || {
+            if let Some(inner) = Weak::upgrade(&weak_inner) {
+                NavigationController { inner }.pop(true);
+            }
+        });
+
+        let entry = navigation_controller.load(root, title);
+        entry.view.set_frame(navigation_controller.content_frame());
+        navigation_controller.inner.content_view.add_subview(entry.view.clone());
+        navigation_controller.inner.stack.borrow_mut().push(entry);
+        navigation_controller.update_navigation_bar();
+
+        navigation_controller
+    }
+
+    /// The view holding the navigation bar and the top view controller's
+    /// view.
+    pub fn view(&self) -> View {
+        self.inner.view.clone()
+    }
+
+    pub fn navigation_bar(&self) -> NavigationBar {
+        NavigationBar::from_view(self.inner.navigation_bar.view.clone())
+    }
+
+    pub fn is_navigation_bar_hidden(&self) -> bool {
+        self.inner.navigation_bar.view.is_hidden()
+    }
+
+    /// Hides or shows the navigation bar. Without it, the view controllers'
+    /// views fill the navigation controller's view.
+    pub fn set_navigation_bar_hidden(&self, hidden: bool) {
+        self.inner.navigation_bar.view.set_hidden(hidden);
+
+        let size = self.inner.view.frame().size;
+        let top = if hidden { 0 } else { NAVIGATION_BAR_HEIGHT };
+        self.inner.content_view.set_frame(Rectangle::new(0, top as i32, size.width, size.height.saturating_sub(top)));
+    }
+
+    /// How many view controllers are on the stack, including the root.
+    pub fn view_controller_count(&self) -> usize {
+        self.inner.stack.borrow().len()
+    }
+
+    /// The titles of the view controllers on the stack, from the root up.
+    pub fn titles(&self) -> Vec<String> {
+        self.inner.stack.borrow().iter().map(|entry| entry.title.clone()).collect()
+    }
+
+    /// The view made for the top view controller.
+    pub fn top_view(&self) -> View {
+        self.inner.stack.borrow().last().unwrap().view.clone()
+    }
+
+    /// Whether a push or pop is being animated.
+    pub fn is_transitioning(&self) -> bool {
+        self.inner.transition.borrow().iter().any(|animation| animation.is_running())
+    }
+
+    /// Pushes `controller` on the stack, making a view for it and showing
+    /// it over the current top view controller's.
+    pub fn push(&self, controller: ViewController<'static>, title: &str, animated: bool) {
+        self.finish_transition();
+
+        let entry = self.load(controller, title);
+        let width = self.content_frame().size.width as i32;
+        let (outgoing, incoming) = {
+            let stack = self.inner.stack.borrow();
+            (stack.last().unwrap().view.clone(), entry.view.clone())
+        };
+
+        {
+            let stack = self.inner.stack.borrow();
+            self.will_disappear(stack.last().unwrap());
+        }
+        self.will_appear(&entry);
+
+        incoming.set_frame(self.content_frame());
+        self.inner.content_view.add_subview(incoming.clone());
+        self.inner.stack.borrow_mut().push(entry);
+        self.update_navigation_bar();
+
+        let navigation_controller = self.clone();
+        let (outgoing_view, incoming_view) = (outgoing.clone(), incoming.clone());
+        let complete = move || {
+            outgoing.remove_from_superview();
+            outgoing.set_frame(navigation_controller.content_frame());
+            incoming.set_frame(navigation_controller.content_frame());
+
+            let stack = navigation_controller.inner.stack.borrow();
+            let index = stack.iter().position(|entry| entry.view == incoming).unwrap();
+            navigation_controller.did_disappear(&stack[index - 1]);
+            navigation_controller.did_appear(&stack[index]);
+        };
+
+        if animated {
+            self.animate_transition(&incoming_view, width, 0, &outgoing_view, 0, -width / 3, complete);
+        } else {
+            complete();
+        }
+    }
+
+    /// Pops the top view controller off the stack, showing the one below
+    /// it. Returns `false` if only the root view controller is left.
+    pub fn pop(&self, animated: bool) -> bool {
+        self.finish_transition();
+
+        if self.view_controller_count() <= 1 {
+            return false;
+        }
+
+        let outgoing = self.inner.stack.borrow_mut().pop().unwrap();
+        self.reveal(outgoing, animated);
+        true
+    }
+
+    /// Pops every view controller but the root off the stack. Only the
+    /// transition away from the top one is animated. Returns `false` if
+    /// only the root view controller is left.
+    pub fn pop_to_root(&self, animated: bool) -> bool {
+        self.finish_transition();
+
+        if self.view_controller_count() <= 1 {
+            return false;
+        }
+
+        let outgoing = self.inner.stack.borrow_mut().pop().unwrap();
+        self.inner.stack.borrow_mut().truncate(1);
+        self.reveal(outgoing, animated);
+        true
+    }
+
+    /// Slides `outgoing` away, popped off the stack, to show the view
+    /// controller now at the top.
+    fn reveal(&self, outgoing: NavigationEntry, animated: bool) {
+        let width = self.content_frame().size.width as i32;
+        let incoming = self.top_view();
+
+        self.will_disappear(&outgoing);
+        {
+            let stack = self.inner.stack.borrow();
+            self.will_appear(stack.last().unwrap());
+        }
+
+        incoming.set_frame(self.content_frame());
+        self.inner.content_view.insert_subview_at(incoming.clone(), 0);
+        self.update_navigation_bar();
+
+        let navigation_controller = self.clone();
+        let outgoing_view = outgoing.view.clone();
+        let incoming_view = incoming.clone();
+        let complete = move || {
+            outgoing.view.remove_from_superview();
+            incoming.set_frame(navigation_controller.content_frame());
+
+            navigation_controller.did_disappear(&outgoing);
+            let stack = navigation_controller.inner.stack.borrow();
+            navigation_controller.did_appear(stack.last().unwrap());
+        };
+
+        if animated {
+            self.animate_transition(&outgoing_view, 0, width, &incoming_view, -width / 3, 0, complete);
+        } else {
+            complete();
+        }
+    }
+
+    /// Slides `front` from `front_from` to `front_to` across the content,
+    /// and `back` behind it from `back_from` to `back_to`, calling
+    /// `complete` at the end, or when the transition is finished early.
+    #[allow(clippy::too_many_arguments)]
+    fn animate_transition(&self, front: &View, front_from: i32, front_to: i32, back: &View, back_from: i32, back_to: i32, complete: impl FnOnce() + 'static) {
+        let frame = self.content_frame();
+        let at = |x: i32| Rectangle { origin: Point::new(x, 0), size: frame.size.clone() };
+
+        front.set_frame(at(front_from));
+        back.set_frame(at(back_from));
+
+        let back_animation = back.animate(TRANSITION_DURATION, Easing::EaseInOut, |back| back.set_frame(at(back_to)));
+        let front_animation = front.animate(TRANSITION_DURATION, Easing::EaseInOut, |front| front.set_frame(at(front_to)));
+        front_animation.on_complete(move |_| complete());
+
+        // The back view's animation is cancelled first, so the completion
+        // puts both views where they end up.
+        self.inner.transition.replace(vec![back_animation, front_animation]);
+    }
+
+    /// Ends the running transition, if any, putting the views where it
+    /// would have left them.
+    fn finish_transition(&self) {
+        let transition = self.inner.transition.replace(Vec::new());
+        for animation in transition {
+            animation.cancel();
+        }
+    }
+
+    /// The frame of a view controller's view, filling the content view.
+    fn content_frame(&self) -> Rectangle<i32, u32> {
+        let size = self.inner.content_view.frame().size;
+        Rectangle::new(0, 0, size.width, size.height)
+    }
+
+    /// Makes a view for `controller` and loads it.
+    fn load(&self, controller: ViewController<'static>, title: &str) -> NavigationEntry {
+        let view = View::new(self.content_frame());
+        view.set_autoresizing_mask(&[AutoresizingMask::FlexibleWidth, AutoresizingMask::FlexibleHeight]);
+        controller.load_view(view.clone());
+
+        NavigationEntry {
+            controller,
+            view,
+            title: title.to_string()
+        }
+    }
+
+    fn update_navigation_bar(&self) {
+        let stack = self.inner.stack.borrow();
+        let bar = &self.inner.navigation_bar;
+        bar.set_title(&stack.last().unwrap().title);
+
+        let previous = stack.len().checked_sub(2).map(|index| stack[index].title.as_str());
+        bar.set_back_title(previous);
+    }
+
+    fn will_appear(&self, entry: &NavigationEntry) {
+        if self.inner.is_visible.get() {
+            entry.controller.will_appear(entry.view.clone());
+        }
+    }
+
+    fn did_appear(&self, entry: &NavigationEntry) {
+        if self.inner.is_visible.get() {
+            entry.controller.did_appear(entry.view.clone());
+        }
+    }
+
+    fn will_disappear(&self, entry: &NavigationEntry) {
+        if self.inner.is_visible.get() {
+            entry.controller.will_disappear(entry.view.clone());
+        }
+    }
+
+    fn did_disappear(&self, entry: &NavigationEntry) {
+        if self.inner.is_visible.get() {
+            entry.controller.did_disappear(entry.view.clone());
+        }
+    }
+}
+
+impl ViewControllerBehavior for NavigationController {
+    /// Fills `view`, usually the window's, with the navigation controller.
+    fn view_did_load(&self, view: View) {
+        let size = view.frame().size;
+        let navigation_view = self.view();
+        navigation_view.set_frame(Rectangle::new(0, 0, size.width, size.height));
+        view.add_subview(navigation_view);
+    }
+
+    fn view_will_appear(&self, _view: View) {
+        self.inner.is_visible.set(true);
+        let stack = self.inner.stack.borrow();
+        self.will_appear(stack.last().unwrap());
+    }
+
+    fn view_did_appear(&self, _view: View) {
+        let stack = self.inner.stack.borrow();
+        self.did_appear(stack.last().unwrap());
+    }
+
+    fn view_will_disappear(&self, _view: View) {
+        let stack = self.inner.stack.borrow();
+        self.will_disappear(stack.last().unwrap());
+    }
+
+    fn view_did_disappear(&self, _view: View) {
+        {
+            let stack = self.inner.stack.borrow();
+            self.did_disappear(stack.last().unwrap());
+        }
+        self.inner.is_visible.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Logger {
+        name: &'static str,
+        log: Rc<RefCell<Vec<String>>>
+    }
+
+    impl ViewControllerBehavior for Logger {
+        fn view_did_load(&self, _view: View) {
+            self.log.borrow_mut().push(format!("{} did load", self.name));
+        }
+
+        fn view_will_appear(&self, _view: View) {
+            self.log.borrow_mut().push(format!("{} will appear", self.name));
+        }
+
+        fn view_did_appear(&self, _view: View) {
+            self.log.borrow_mut().push(format!("{} did appear", self.name));
+        }
+
+        fn view_will_disappear(&self, _view: View) {
+            self.log.borrow_mut().push(format!("{} will disappear", self.name));
+        }
+
+        fn view_did_disappear(&self, _view: View) {
+            self.log.borrow_mut().push(format!("{} did disappear", self.name));
+        }
+    }
+
+    fn logger(name: &'static str, log: &Rc<RefCell<Vec<String>>>) -> ViewController<'static> {
+        ViewController::new(Logger { name, log: log.clone() })
+    }
+
+    #[test]
+    fn test_push_and_pop() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let navigation_controller = NavigationController::new(logger("home", &log), "Home");
+
+        // Shown as a window's view controller, it fills the window.
+        let window_view = View::new(Rectangle::new(0, 0, 400, 600));
+        let controller = ViewController::new(navigation_controller.clone());
+        controller.window_loaded(window_view.clone());
+        controller.window_set_needs_display(window_view.clone());
+        controller.window_displayed(window_view.clone());
+        assert_eq!(navigation_controller.view().frame(), Rectangle::new(0, 0, 400, 600));
+        assert_eq!(navigation_controller.top_view().frame(), Rectangle::new(0, 0, 400, 600 - NAVIGATION_BAR_HEIGHT));
+
+        navigation_controller.push(logger("settings", &log), "Settings", false);
+        navigation_controller.push(logger("about", &log), "About", false);
+        assert_eq!(navigation_controller.titles(), vec!["Home", "Settings", "About"]);
+        assert_eq!(navigation_controller.navigation_bar().title(), "About");
+        assert_eq!(navigation_controller.navigation_bar().back_title(), Some("‹ Settings".to_string()));
+
+        // Only the top view controller's view is shown.
+        let content_view = navigation_controller.top_view().superview().upgrade().unwrap();
+        assert_eq!(content_view.subviews(), vec![navigation_controller.top_view()]);
+
+        // The back button pops.
+        navigation_controller.navigation_bar().go_back();
+        assert_eq!(navigation_controller.titles(), vec!["Home", "Settings"]);
+        assert!(navigation_controller.pop_to_root(false));
+        assert!(!navigation_controller.pop(false));
+        assert_eq!(navigation_controller.navigation_bar().back_title(), None);
+        assert_eq!(content_view.subviews(), vec![navigation_controller.top_view()]);
+
+        assert_eq!(*log.borrow(), vec![
+            "home did load", "home will appear", "home did appear",
+            "settings did load", "home will disappear", "settings will appear", "home did disappear", "settings did appear",
+            "about did load", "settings will disappear", "about will appear", "settings did disappear", "about did appear",
+            "about will disappear", "settings will appear", "about did disappear", "settings did appear",
+            "settings will disappear", "home will appear", "settings did disappear", "home did appear"
+        ]);
+    }
+
+    #[test]
+    fn test_hidden_navigation_bar() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let navigation_controller = NavigationController::new(logger("home", &log), "Home");
+        navigation_controller.view().set_frame(Rectangle::new(0, 0, 400, 600));
+
+        navigation_controller.set_navigation_bar_hidden(true);
+        assert!(navigation_controller.is_navigation_bar_hidden());
+        navigation_controller.push(logger("settings", &log), "Settings", false);
+        assert_eq!(navigation_controller.top_view().frame(), Rectangle::new(0, 0, 400, 600));
+
+        // Not shown anywhere, the view controllers are loaded but don't
+        // appear.
+        assert_eq!(*log.borrow(), vec!["home did load", "settings did load"]);
+    }
+}
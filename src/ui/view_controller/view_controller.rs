@@ -1,4 +1,7 @@
 use crate::ui::View;
+use crate::ui::press::Press;
+use crate::ui::command::Command;
+use crate::ui::key_command::KeyCommand;
 use std::cell::Cell;
 
 pub trait ViewControllerBehavior {
@@ -7,6 +10,39 @@ pub trait ViewControllerBehavior {
     fn view_will_appear(&self, _view: View) {}
     fn view_did_appear(&self, _view: View) {}
     fn view_did_load(&self, _view: View) {}
+
+    /// Called with key presses no view in the window handled, before the
+    /// window itself handles them, such as Tab moving keyboard focus.
+    /// Return `true` if the press was handled; otherwise it's sent on to
+    /// the application delegate.
+    fn press_began(&self, _press: &Press) -> bool {
+        false
+    }
+
+    /// Called when a press that reached the window is released, whether or
+    /// not `press_began` handled it. Return `true` if the release was
+    /// handled.
+    fn press_ended(&self, _press: &Press) -> bool {
+        false
+    }
+
+    /// Performs a command no view in the window handled, such as from a
+    /// menu. Return `true` if it was handled.
+    fn perform_command(&self, _command: &Command) -> bool {
+        false
+    }
+
+    /// Whether `perform_command` would handle `command` now.
+    fn can_perform_command(&self, _command: &Command) -> bool {
+        false
+    }
+
+    /// Keyboard shortcuts handled anywhere in the controller's window,
+    /// unless a view nearer the first responder handles the same keys. See
+    /// `View::add_key_command`.
+    fn key_commands(&self) -> Vec<KeyCommand> {
+        Vec::new()
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -15,8 +51,8 @@ enum State {
     DidLoad,
     WillAppear,
     DidAppear,
-    // WillDisappear,
-    // DidDisappear
+    WillDisappear,
+    DidDisappear
 }
 
 pub struct ViewController<'a> {
@@ -39,6 +75,13 @@ impl ViewController<'_> {
     /// This is the time for the application itself to start building up the
     /// view heirarchy.
     pub(crate) fn window_loaded(&self, view: View) {
+        self.load_view(view);
+    }
+
+    /// Loads the controller with `view` as its view: the window's for a
+    /// window's controller, or one made for it by a container controller
+    /// such as `NavigationController`.
+    pub(crate) fn load_view(&self, view: View) {
         match self.state.get() {
             State::WillLoad => {
                 self.state.set(State::DidLoad);
@@ -50,6 +93,63 @@ impl ViewController<'_> {
         }
     }
 
+    /// Called by a container controller before it shows the controller's
+    /// view, for the first time or again after hiding it.
+    pub(crate) fn will_appear(&self, view: View) {
+        if let State::DidLoad | State::DidDisappear = self.state.get() {
+            self.state.set(State::WillAppear);
+            self.behavior.view_will_appear(view);
+        }
+    }
+
+    /// Called by a container controller once the controller's view is
+    /// shown, such as at the end of a transition.
+    pub(crate) fn did_appear(&self, view: View) {
+        if let State::WillAppear = self.state.get() {
+            self.state.set(State::DidAppear);
+            self.behavior.view_did_appear(view);
+        }
+    }
+
+    /// Called by a container controller before it hides the controller's
+    /// view, such as when another controller is pushed over it.
+    pub(crate) fn will_disappear(&self, view: View) {
+        if let State::WillAppear | State::DidAppear = self.state.get() {
+            self.state.set(State::WillDisappear);
+            self.behavior.view_will_disappear(view);
+        }
+    }
+
+    /// Called by a container controller once the controller's view is
+    /// hidden.
+    pub(crate) fn did_disappear(&self, view: View) {
+        if let State::WillDisappear = self.state.get() {
+            self.state.set(State::DidDisappear);
+            self.behavior.view_did_disappear(view);
+        }
+    }
+
+    /// Sends a press unhandled by the window's views to the controller.
+    pub(crate) fn press_began(&self, press: &Press) -> bool {
+        self.behavior.press_began(press)
+    }
+
+    pub(crate) fn press_ended(&self, press: &Press) -> bool {
+        self.behavior.press_ended(press)
+    }
+
+    pub(crate) fn perform_command(&self, command: &Command) -> bool {
+        self.behavior.perform_command(command)
+    }
+
+    pub(crate) fn can_perform_command(&self, command: &Command) -> bool {
+        self.behavior.can_perform_command(command)
+    }
+
+    pub(crate) fn key_commands(&self) -> Vec<KeyCommand> {
+        self.behavior.key_commands()
+    }
+
     /// Called by `WindowBehavior.set_needs_display`. I.e. when the window needs
     /// to render.
     ///
@@ -66,12 +166,8 @@ impl ViewController<'_> {
             },
             State::WillAppear => (),
             State::DidAppear => (),
-            // State::WillDisappear => {
-            //     panic!("TODO: what should happen here?")
-            // },
-            // State::DidDisappear => {
-            //     panic!("TODO: what should happen here?")
-            // }
+            State::WillDisappear => (),
+            State::DidDisappear => ()
         }
     }
 
@@ -1,13 +1,28 @@
-use crate::graphics::{Context, Rectangle};
-use crate::ui::{View, WeakView, ViewController};
+use crate::graphics::{Context, Image, ImageCache, Point, Rectangle, RendererKind, Size};
+use crate::ui::{ContextMenu, View, WeakView, ViewController};
+use crate::ui::context_menu;
 use crate::ui::view::{Behavior, DefaultBehavior};
 use crate::ui::application::Application;
 use crate::ui::render;
-use crate::ui::Color;
+use crate::ui::inspector::{self, InspectorOverlay};
+use crate::ui::accessibility::{self, AccessibilityNode, AccessibilityRole};
+use crate::ui::performance_hud::{self, FrameHistory, FrameStatistics};
+use crate::ui::Label;
+use crate::ui::{Color, SemanticColor};
 use crate::ui::timer::Timer;
+use crate::ui::clock;
 use crate::ui::run_loop::RunLoop;
+use crate::ui::press::Press;
+use crate::ui::command::Command;
+use crate::ui::key_command;
+use crate::ui::notification::{self, Notification, NotificationCenter};
+use crate::platform::history::UndoManager;
+#[cfg(all(feature = "accesskit", target_os = "linux"))]
+use crate::platform::accessibility::Adapter as AccessibilityAdapter;
 use std::option::Option;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use crate::ui::key::{Key, KeyCode, ModifierFlag};
 
 pub struct WindowBehavior {
     view: WeakView,
@@ -18,8 +33,81 @@ pub struct WindowBehavior {
     /// The window's first responder. Default to the window itself. Overriden
     /// by a view calling `become_first_responder`.
     first_responder: RefCell<WeakView>,
+
+    /// The undo manager shared by the views in the window that don't have
+    /// their own.
+    undo_manager: Rc<RefCell<UndoManager>>,
+
+    /// Drawn around the first responder to show which view has keyboard
+    /// focus, unless turned off.
+    focus_ring: View,
+    shows_focus_ring: Cell<bool>,
+
+    /// Whether Tab moves keyboard focus through controls too.
+    full_keyboard_access: Cell<bool>,
+
+    /// Whether views that can't be reached with the keyboard are reported,
+    /// with the views reported for the last frame, so each is only
+    /// reported once.
+    audits_keyboard_access: Cell<bool>,
+    unreachable_view_ids: RefCell<Vec<usize>>,
+
+    /// Whether the part of the window drawn again each frame is tinted, to
+    /// debug drawing.
+    shows_dirty_rectangles: Cell<bool>,
+
+    /// Drawn in front of the other views while the inspector is shown, to
+    /// debug layout.
+    inspector: InspectorOverlay,
+    shows_inspector: Cell<bool>,
+
+    /// The frames drawn in the last second, shown by the performance HUD.
+    frame_history: RefCell<FrameHistory>,
+    performance_hud: Label,
+
+    /// Updates the performance HUD while it's shown.
+    performance_hud_timer: RefCell<Option<Timer>>,
+
+    /// Called after the window is resized, once its views are laid out.
+    on_resize: RefCell<Option<ResizeCallback>>,
+
+    /// Called when the accessibility tree changes, with the tree it was
+    /// last called with kept to compare.
+    on_accessibility_tree_change: RefCell<Option<AccessibilityTreeCallback>>,
+    accessibility_tree: RefCell<Option<AccessibilityNode>>,
+
+    /// Sends the accessibility tree on to screen readers, once the window
+    /// is connected to them.
+    #[cfg(all(feature = "accesskit", target_os = "linux"))]
+    accessibility_adapter: RefCell<Option<AccessibilityAdapter>>,
 }
 
+type ResizeCallback = Rc<dyn Fn(&Window, &Size<u32>)>;
+type AccessibilityTreeCallback = Rc<dyn Fn(&Window, &AccessibilityNode)>;
+
+/// How far the focus ring is drawn outside the focused view, and its width.
+const FOCUS_RING_WIDTH: u32 = 2;
+
+/// Setting this environment variable gives every window full keyboard
+/// access. See `Window::set_full_keyboard_access`.
+const FULL_KEYBOARD_ACCESS_VARIABLE: &str = "PELICAN_FULL_KEYBOARD_ACCESS";
+
+/// Setting this environment variable runs the keyboard access audit in
+/// every window. See `Window::set_audits_keyboard_access`.
+const KEYBOARD_AUDIT_VARIABLE: &str = "PELICAN_KEYBOARD_AUDIT";
+
+/// Setting this environment variable shows dirty rectangles in every window.
+/// See `Window::set_shows_dirty_rectangles`.
+const SHOW_DIRTY_RECTANGLES_VARIABLE: &str = "PELICAN_SHOW_DIRTY_RECTS";
+
+/// Setting this environment variable shows the inspector in every window.
+/// See `Window::set_shows_inspector`.
+const SHOW_INSPECTOR_VARIABLE: &str = "PELICAN_SHOW_INSPECTOR";
+
+/// Setting this environment variable shows the performance HUD in every
+/// window. See `Window::set_shows_performance_hud`.
+const SHOW_PERFORMANCE_HUD_VARIABLE: &str = "PELICAN_SHOW_PERFORMANCE_HUD";
+
 pub struct Window {
     pub view: View
 }
@@ -33,24 +121,61 @@ impl std::ops::Deref for Window {
 
 impl Window {
     pub fn new(title: &str, frame: Rectangle<i32, u32>, view_controller: ViewController<'static>) -> Window {
-        let default_behavior = DefaultBehavior {
-            view: WeakView::none()
-        };
+        Window::new_with_renderer(title, frame, view_controller, RendererKind::default())
+    }
 
+    /// A window drawn by a renderer of `renderer_kind`, such as
+    /// `RendererKind::Software` where there's no graphics card. Windows are
+    /// drawn on the graphics card by default.
+    pub fn new_with_renderer(title: &str, frame: Rectangle<i32, u32>, view_controller: ViewController<'static>, renderer_kind: RendererKind) -> Window {
         let context_frame = frame.clone();
 
-        let context = Context::new(
+        let context = Context::new_with_renderer(
             title,
             context_frame.origin,
-            context_frame.size
+            context_frame.size,
+            renderer_kind
         );
 
+        Window::new_with_context(frame, view_controller, context)
+    }
+
+    /// A window drawn into memory rather than on screen, at a scale of 1, so
+    /// it works without a display server, such as in tests driven by a
+    /// `TestDriver`. See `Context::new_headless`.
+    pub fn new_headless(frame: Rectangle<i32, u32>, view_controller: ViewController<'static>) -> Window {
+        let context = Context::new_headless(frame.size.clone(), 1.0);
+        Window::new_with_context(frame, view_controller, context)
+    }
+
+    fn new_with_context(frame: Rectangle<i32, u32>, view_controller: ViewController<'static>, context: Context) -> Window {
+        let default_behavior = DefaultBehavior {
+            view: WeakView::none()
+        };
+
         let window_behavior = WindowBehavior {
             view: WeakView::none(),
             super_behavior: Box::new(default_behavior),
             context: context,
             view_controller: view_controller,
-            first_responder: RefCell::new(WeakView::none())
+            first_responder: RefCell::new(WeakView::none()),
+            undo_manager: Rc::new(RefCell::new(UndoManager::new())),
+            focus_ring: Window::new_focus_ring(),
+            shows_focus_ring: Cell::new(true),
+            full_keyboard_access: Cell::new(std::env::var_os(FULL_KEYBOARD_ACCESS_VARIABLE).is_some() || std::env::var_os(KEYBOARD_AUDIT_VARIABLE).is_some()),
+            audits_keyboard_access: Cell::new(std::env::var_os(KEYBOARD_AUDIT_VARIABLE).is_some()),
+            unreachable_view_ids: RefCell::new(Vec::new()),
+            shows_dirty_rectangles: Cell::new(std::env::var_os(SHOW_DIRTY_RECTANGLES_VARIABLE).is_some()),
+            inspector: InspectorOverlay::new(Rectangle::new(0, 0, frame.size.width, frame.size.height)),
+            shows_inspector: Cell::new(std::env::var_os(SHOW_INSPECTOR_VARIABLE).is_some()),
+            frame_history: RefCell::new(FrameHistory::default()),
+            performance_hud: performance_hud::new_hud(),
+            performance_hud_timer: RefCell::new(None),
+            on_resize: RefCell::new(None),
+            on_accessibility_tree_change: RefCell::new(None),
+            accessibility_tree: RefCell::new(None),
+            #[cfg(all(feature = "accesskit", target_os = "linux"))]
+            accessibility_adapter: RefCell::new(None)
         };
 
         let view = View::new_with_behavior(Box::new(window_behavior), frame, "window");
@@ -71,11 +196,36 @@ impl Window {
         }
 
         window.view.set_hidden(true);
-        window.view.set_background_color(Color::white());
+        window.view.set_background_color(Color::semantic(SemanticColor::Background));
+        window.view.add_gesture_recognizer(Box::new(context_menu::long_press_recognizer()));
+
+        if std::env::var_os(SHOW_PERFORMANCE_HUD_VARIABLE).is_some() {
+            window.set_shows_performance_hud(true);
+        }
+
+        #[cfg(all(feature = "accesskit", target_os = "linux"))]
+        window.connect_accessibility_adapter();
 
         window
     }
 
+    fn new_focus_ring() -> View {
+        let focus_ring = View::new(Rectangle::new(0, 0, 0, 0));
+        focus_ring.set_background_color(Color::clear());
+        focus_ring.set_user_interaction_enabled(false);
+        focus_ring.set_hidden(true);
+
+        // One view for each edge, so the focused view shows through.
+        for _ in 0..4 {
+            let edge = View::new(Rectangle::new(0, 0, 0, 0));
+            edge.set_background_color(Color::new(64, 140, 255, 200));
+            edge.set_user_interaction_enabled(false);
+            focus_ring.add_subview(edge);
+        }
+
+        focus_ring
+    }
+
     pub fn from_view(view: View) -> Window {
         // Downcast the behavior to essentially verify the view is a window.
         let _ = view.behavior.borrow().as_any().downcast_ref::<WindowBehavior>().unwrap();
@@ -83,10 +233,27 @@ impl Window {
         Window { view }
     }
 
+    /// Makes the window the key window, receiving key presses, and shows
+    /// it. Posts `notification::WINDOW_DID_RESIGN_KEY` for the window that
+    /// was key, and `WINDOW_DID_BECOME_KEY` for this one.
     pub fn make_key_and_visible(&self) {
-        let mut application = Application::borrow_mut();
-        application.set_key_window(&self);
+        let previous_key_window = {
+            let mut application = Application::borrow_mut();
+            let previous_key_window = application.get_key_window().upgrade();
+            application.set_key_window(&self);
+            previous_key_window
+        };
         self.set_hidden(false);
+
+        if previous_key_window.as_ref() == Some(&self.view) {
+            return;
+        }
+
+        if let Some(previous_key_window) = previous_key_window {
+            let window = Window::from_view(previous_key_window);
+            NotificationCenter::post(Notification::with_payload(notification::WINDOW_DID_RESIGN_KEY, window));
+        }
+        NotificationCenter::post(Notification::with_payload(notification::WINDOW_DID_BECOME_KEY, self.clone()));
     }
 
     pub fn context(&self) -> Context {
@@ -95,6 +262,17 @@ impl Window {
         behavior.context.clone()
     }
 
+    /// Draws the window now, and returns an image of everything in it as
+    /// shown on screen, at the window's scale. Save it with
+    /// `Image::save_png`, such as for a bug report or documentation.
+    ///
+    /// Only what the window draws is captured: not the title bar or anything
+    /// else the system draws around it, nor the dirty rectangles shown with
+    /// `set_shows_dirty_rectangles`.
+    pub fn capture(&self) -> Image<'static> {
+        render::snapshot(&self.view, &self.context())
+    }
+
     /// Returns the window's first responder.
     ///
     /// If there is no first responder, the window itself is returned.
@@ -110,6 +288,676 @@ impl Window {
         }
     }
 
+    /// Performs the keyboard shortcut triggered by pressing `key`, looking
+    /// from the first responder up through its superviews, then at the
+    /// view controller's. Returns whether there was one, in which case the
+    /// press isn't sent on. The command is performed once the window's
+    /// behavior is no longer borrowed, so it can open windows.
+    pub(crate) fn perform_key_command(&self, key: &Key) -> bool {
+        let key_command = self.first_responder().key_command_matching(key).or_else(|| {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            key_command::matching(&behavior.view_controller.key_commands(), key)
+        });
+
+        match key_command {
+            Some(key_command) => {
+                key_command.perform();
+                true
+            },
+            None => false
+        }
+    }
+
+    /// The view with keyboard focus, if any. Key presses and typing go to
+    /// this view.
+    pub fn focused_view(&self) -> Option<View> {
+        let first_responder = self.first_responder();
+        if first_responder == self.view {
+            None
+        } else {
+            Some(first_responder)
+        }
+    }
+
+    /// Moves keyboard focus to the next view that can take it, or the
+    /// previous when `backwards`, as Tab and Shift+Tab do. See
+    /// `View::focusable_views` for the order.
+    ///
+    /// Returns `false` if there's no view to move to, or the focused view
+    /// refused to resign.
+    pub fn focus_next_view(&self, backwards: bool) -> bool {
+        let focused_view = self.focused_view();
+        match self.view.focusable_view_after(focused_view.as_ref(), backwards) {
+            Some(view) => view.become_first_responder(),
+            None => false
+        }
+    }
+
+    /// Whether a ring is drawn around the focused view.
+    pub fn shows_focus_ring(&self) -> bool {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        behavior.shows_focus_ring.get()
+    }
+
+    /// Sets whether a ring is drawn around the focused view. It's shown by
+    /// default.
+    pub fn set_shows_focus_ring(&self, shows_focus_ring: bool) {
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.shows_focus_ring.set(shows_focus_ring);
+        }
+
+        self.update_focus_ring();
+    }
+
+    /// Whether Tab moves keyboard focus through controls such as buttons,
+    /// as well as text fields.
+    pub fn full_keyboard_access(&self) -> bool {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        behavior.full_keyboard_access.get()
+    }
+
+    /// Sets whether Tab moves keyboard focus through controls, such as
+    /// buttons and table views, as well as text fields, so everything in
+    /// the window can be used without a mouse. The focused button is
+    /// pressed with Space or Return, and the focused table view's selection
+    /// moves with the arrow keys. See `View::is_control`.
+    ///
+    /// It's off unless the `PELICAN_FULL_KEYBOARD_ACCESS` or
+    /// `PELICAN_KEYBOARD_AUDIT` environment variable is set. Turning it off
+    /// takes focus away from a focused control.
+    pub fn set_full_keyboard_access(&self, full_keyboard_access: bool) {
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.full_keyboard_access.set(full_keyboard_access);
+        }
+
+        if let Some(focused_view) = self.focused_view() {
+            if !full_keyboard_access && !focused_view.can_become_first_responder() && focused_view.is_control() {
+                focused_view.resign_first_responder();
+            }
+        }
+    }
+
+    /// The views in the window that could take keyboard focus, but that
+    /// Tab never reaches, such as a text field in a view with user
+    /// interaction disabled. Without full keyboard access, this includes
+    /// every control. Hidden views aren't included.
+    pub fn unreachable_focusable_views(&self) -> Vec<View> {
+        self.view.unreachable_focusable_views(self.full_keyboard_access(), &self.overlay_views())
+    }
+
+    /// Whether the keyboard access audit is running. See
+    /// `set_audits_keyboard_access`.
+    pub fn audits_keyboard_access(&self) -> bool {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        behavior.audits_keyboard_access.get()
+    }
+
+    /// Sets whether to check that everything in the window can be used with
+    /// the keyboard alone. Turning the audit on turns on full keyboard
+    /// access; then, as the window is drawn, a warning is printed for each
+    /// view that becomes one of the `unreachable_focusable_views`.
+    ///
+    /// It's off unless the `PELICAN_KEYBOARD_AUDIT` environment variable is
+    /// set.
+    pub fn set_audits_keyboard_access(&self, audits_keyboard_access: bool) {
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.audits_keyboard_access.set(audits_keyboard_access);
+            behavior.unreachable_view_ids.borrow_mut().clear();
+        }
+
+        if audits_keyboard_access {
+            self.set_full_keyboard_access(true);
+            self.view.set_needs_display();
+        }
+    }
+
+    /// Prints a warning for each view that's become unreachable with the
+    /// keyboard since the last frame, while the audit is running. Called
+    /// before each frame is drawn.
+    pub(crate) fn audit_keyboard_access(&self) {
+        if !self.audits_keyboard_access() {
+            return;
+        }
+
+        let unreachable_views = self.unreachable_focusable_views();
+        let unreachable_view_ids: Vec<usize> = unreachable_views.iter().map(View::id).collect();
+        let reported_view_ids = {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.unreachable_view_ids.replace(unreachable_view_ids)
+        };
+
+        let full_keyboard_access = self.full_keyboard_access();
+        for view in unreachable_views.iter().filter(|view| !reported_view_ids.contains(&view.id())) {
+            let name = match view.identifier() {
+                Some(identifier) => format!("{} {:?}", view.behavior_name(), identifier),
+                None => view.behavior_name()
+            };
+            let reason = if !full_keyboard_access && !view.can_become_first_responder() {
+                "full keyboard access is off"
+            } else {
+                "user interaction is disabled on it or a superview"
+            };
+            println!("Warning: {} can take keyboard focus, but Tab can't reach it, as {}.", name, reason);
+        }
+    }
+
+    /// Whether the part of the window drawn again each frame is tinted red.
+    pub fn shows_dirty_rectangles(&self) -> bool {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        behavior.shows_dirty_rectangles.get()
+    }
+
+    /// Sets whether the part of the window drawn again each frame is tinted
+    /// red, to see what's being drawn. It's off unless the
+    /// `PELICAN_SHOW_DIRTY_RECTS` environment variable is set.
+    pub fn set_shows_dirty_rectangles(&self, shows_dirty_rectangles: bool) {
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.shows_dirty_rectangles.set(shows_dirty_rectangles);
+        }
+
+        self.view.set_needs_display();
+    }
+
+    /// Whether the inspector is drawn over the window's views.
+    pub fn shows_inspector(&self) -> bool {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        behavior.shows_inspector.get()
+    }
+
+    /// Sets whether the inspector is drawn over the window's views, to debug
+    /// layout. It outlines each view, with its identifier or the name of
+    /// its behavior, and tints its layout margins, following the views as
+    /// they change. It's off unless the `PELICAN_SHOW_INSPECTOR`
+    /// environment variable is set.
+    ///
+    /// To print the hierarchy instead, see `View::recursive_description`.
+    pub fn set_shows_inspector(&self, shows_inspector: bool) {
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.shows_inspector.set(shows_inspector);
+        }
+
+        self.update_inspector();
+    }
+
+    /// Shows the window's views in the inspector, in front of them, or
+    /// hides it. Called before each frame is drawn, so the inspector follows
+    /// the views as they change.
+    pub(crate) fn update_inspector(&self) {
+        let overlay = {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.inspector.clone()
+        };
+
+        if !self.shows_inspector() {
+            if self.view.subviews().contains(&overlay.view) {
+                overlay.view.remove_from_superview();
+                self.view.set_needs_display();
+            }
+            return;
+        }
+
+        let size = self.view.frame().size;
+        if overlay.view.frame().size != size {
+            overlay.view.set_frame(Rectangle::new(0, 0, size.width, size.height));
+        }
+
+        // Keep the inspector in front of views added since the last frame.
+        self.bring_overlay_to_front(&overlay.view);
+
+        overlay.update(inspector::inspected_views(&self.view, &self.overlay_views()));
+    }
+
+    /// Whether the performance HUD is shown in the window's top left corner.
+    pub fn shows_performance_hud(&self) -> bool {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        let is_shown = behavior.performance_hud_timer.borrow().is_some();
+        is_shown
+    }
+
+    /// Sets whether the performance HUD is shown in the window's top left
+    /// corner. Twice a second, it shows how many frames were drawn in the
+    /// last second, and for the last frame, how long it took to draw, how
+    /// many layers were drawn again and how much of the window. It's off
+    /// unless the `PELICAN_SHOW_PERFORMANCE_HUD` environment variable is
+    /// set.
+    ///
+    /// Updating the HUD draws a frame itself, so an idle window shows a few
+    /// frames per second rather than none.
+    pub fn set_shows_performance_hud(&self, shows_performance_hud: bool) {
+        if shows_performance_hud == self.shows_performance_hud() {
+            return;
+        }
+
+        let (hud, timer) = {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            (behavior.performance_hud.clone(), behavior.performance_hud_timer.replace(None))
+        };
+
+        if let Some(timer) = timer {
+            timer.invalidate();
+            hud.view.remove_from_superview();
+            self.view.set_needs_display();
+            return;
+        }
+
+        let weak_window = self.view.downgrade();
+        let timer = Timer::new_repeating(performance_hud::UPDATE_INTERVAL, move || {
+            if let Some(view) = weak_window.upgrade() {
+                Window::from_view(view).update_performance_hud();
+            }
+        });
+        RunLoop::borrow().add_timer(timer.clone());
+
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.performance_hud_timer.replace(Some(timer));
+        }
+
+        self.update_performance_hud();
+    }
+
+    /// The measurements of the last frame drawn, if one has been drawn in
+    /// the last second.
+    pub fn frame_statistics(&self) -> Option<FrameStatistics> {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        let statistics = behavior.frame_history.borrow().last(clock::now()).cloned();
+        statistics
+    }
+
+    /// How many frames were drawn in the last second.
+    pub fn frames_per_second(&self) -> usize {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        let frames_per_second = behavior.frame_history.borrow().frames_per_second(clock::now());
+        frames_per_second
+    }
+
+    /// Records the measurements of a frame just drawn.
+    pub(crate) fn record_frame(&self, statistics: FrameStatistics) {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        behavior.frame_history.borrow_mut().record(clock::now(), statistics);
+    }
+
+    /// Shows the latest measurements in the performance HUD, in front of
+    /// the window's views.
+    fn update_performance_hud(&self) {
+        let hud = {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.performance_hud.clone()
+        };
+
+        let summary = performance_hud::summary(
+            self.frames_per_second(),
+            &self.frame_statistics().unwrap_or_default(),
+            &self.view.frame().size
+        );
+        if hud.copy_text() != summary {
+            hud.set_text(summary);
+            hud.fit_to_text();
+        }
+
+        self.bring_overlay_to_front(&hud.view);
+    }
+
+    /// The window's views as screen readers see them: a node for the window,
+    /// with nodes for its labels, buttons, text fields and other
+    /// accessibility elements inside it. Frames are relative to the window.
+    /// Hidden views, and the views the window draws over its own such as
+    /// the focus ring, are left out.
+    ///
+    /// See `View::set_accessibility_label` to change how a view is read out.
+    pub fn accessibility_tree(&self) -> AccessibilityNode {
+        let focused_view = self.focused_view();
+        accessibility::tree(&self.view, &self.overlay_views(), focused_view.as_ref())
+    }
+
+    /// Sets what happens when the window's accessibility tree changes, such
+    /// as passing it on to the platform's accessibility API. It's called
+    /// with the whole tree straight away, and then as the window is drawn,
+    /// whenever the tree is different from the last one.
+    ///
+    /// With the `accesskit` feature, `AccessibilityNode::to_tree_update`
+    /// turns the tree into an update for an AccessKit adapter. On Linux,
+    /// windows are already connected to screen readers this way.
+    pub fn on_accessibility_tree_change(&self, action: impl Fn(&Window, &AccessibilityNode) + 'static) {
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.on_accessibility_tree_change.replace(Some(Rc::new(action)));
+            behavior.accessibility_tree.replace(None);
+        }
+
+        self.update_accessibility_tree();
+    }
+
+    /// Calls the closure set with `on_accessibility_tree_change`, and
+    /// tells screen readers, if the accessibility tree has changed since
+    /// it was last called. Called before each frame is drawn.
+    pub(crate) fn update_accessibility_tree(&self) {
+        let action = {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            let action = behavior.on_accessibility_tree_change.borrow().clone();
+            action
+        };
+        if action.is_none() && !self.is_connected_to_screen_readers() {
+            return;
+        }
+
+        let tree = self.accessibility_tree();
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            if behavior.accessibility_tree.borrow().as_ref() == Some(&tree) {
+                return;
+            }
+            behavior.accessibility_tree.replace(Some(tree.clone()));
+        }
+
+        #[cfg(all(feature = "accesskit", target_os = "linux"))]
+        if self.is_connected_to_screen_readers() {
+            let update = tree.to_tree_update(self.context().render_scale() as f64);
+            self.with_accessibility_adapter(|adapter| adapter.update(update));
+        }
+
+        if let Some(action) = action {
+            action(self, &tree);
+        }
+    }
+
+    /// Connects the window to screen readers, sending them its
+    /// accessibility tree from then on. Headless windows aren't connected.
+    #[cfg(all(feature = "accesskit", target_os = "linux"))]
+    fn connect_accessibility_adapter(&self) {
+        let context = self.context();
+        if context.is_headless() {
+            return;
+        }
+
+        let tree = self.accessibility_tree();
+        let update = tree.to_tree_update(context.render_scale() as f64);
+        let adapter = match AccessibilityAdapter::new(context.id(), update, false) {
+            Some(adapter) => adapter,
+            None => return
+        };
+
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.accessibility_tree.replace(Some(tree));
+            behavior.accessibility_adapter.replace(Some(adapter));
+        }
+
+        self.update_accessibility_screen_frames();
+    }
+
+    /// Calls `action` with the window's connection to screen readers, if
+    /// it's connected.
+    #[cfg(all(feature = "accesskit", target_os = "linux"))]
+    fn with_accessibility_adapter(&self, action: impl FnOnce(&AccessibilityAdapter)) {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        let adapter = behavior.accessibility_adapter.borrow();
+        if let Some(adapter) = adapter.as_ref() {
+            action(adapter);
+        }
+    }
+
+    #[cfg(all(feature = "accesskit", target_os = "linux"))]
+    fn is_connected_to_screen_readers(&self) -> bool {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        let is_connected = behavior.accessibility_adapter.borrow().is_some();
+        is_connected
+    }
+
+    #[cfg(not(all(feature = "accesskit", target_os = "linux")))]
+    fn is_connected_to_screen_readers(&self) -> bool {
+        false
+    }
+
+    /// Tells screen readers where the window is now, after it moved or was
+    /// resized.
+    #[cfg(all(feature = "accesskit", target_os = "linux"))]
+    fn update_accessibility_screen_frames(&self) {
+        if let Some((outer, inner)) = self.context().screen_frames() {
+            self.with_accessibility_adapter(|adapter| adapter.set_screen_frames(&outer, &inner));
+        }
+    }
+
+    /// Called when the platform window gains or loses focus, which screen
+    /// readers are told of with the `accesskit` feature.
+    #[cfg_attr(not(all(feature = "accesskit", target_os = "linux")), allow(unused_variables))]
+    pub(crate) fn did_change_focus(&self, is_focused: bool) {
+        #[cfg(all(feature = "accesskit", target_os = "linux"))]
+        self.with_accessibility_adapter(|adapter| adapter.set_focused(is_focused));
+    }
+
+    /// The views the window draws in front of its own, to show focus and
+    /// for debugging.
+    fn overlay_views(&self) -> Vec<View> {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        vec![behavior.focus_ring.clone(), behavior.inspector.view.clone(), behavior.performance_hud.view.clone()]
+    }
+
+    /// Moves `overlay` in front of the window's views, unless only other
+    /// overlays are in front of it already, so overlays kept in front each
+    /// frame don't keep swapping places.
+    fn bring_overlay_to_front(&self, overlay: &View) {
+        let overlays = self.overlay_views();
+        let subviews = self.view.subviews();
+        let is_in_front = subviews
+            .iter()
+            .position(|view| view == overlay)
+            .is_some_and(|index| subviews[index + 1..].iter().all(|view| overlays.contains(view)));
+
+        if !is_in_front {
+            overlay.remove_from_superview();
+            self.view.add_subview(overlay.clone());
+        }
+    }
+
+    /// Moves the focus ring around the focused view, in front of the other
+    /// views, or hides it.
+    pub(crate) fn update_focus_ring(&self) {
+        let focus_ring = {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.focus_ring.clone()
+        };
+
+        let focused_view = match self.focused_view() {
+            Some(view) if self.shows_focus_ring() => view,
+            _ => {
+                focus_ring.set_hidden(true);
+                return;
+            }
+        };
+
+        let size = focused_view.frame().size;
+        let frame = focused_view.convert_rect_to(&Rectangle::new(0, 0, size.width, size.height), &self.view);
+        let width = frame.size.width + FOCUS_RING_WIDTH * 2;
+        let height = frame.size.height + FOCUS_RING_WIDTH * 2;
+
+        focus_ring.set_frame(Rectangle::new(
+            frame.origin.x - FOCUS_RING_WIDTH as i32,
+            frame.origin.y - FOCUS_RING_WIDTH as i32,
+            width,
+            height
+        ));
+
+        let edges = [
+            Rectangle::new(0, 0, width, FOCUS_RING_WIDTH),
+            Rectangle::new(0, (height - FOCUS_RING_WIDTH) as i32, width, FOCUS_RING_WIDTH),
+            Rectangle::new(0, 0, FOCUS_RING_WIDTH, height),
+            Rectangle::new((width - FOCUS_RING_WIDTH) as i32, 0, FOCUS_RING_WIDTH, height)
+        ];
+        for (edge, frame) in focus_ring.subviews().iter().zip(edges.iter()) {
+            edge.set_frame(frame.clone());
+        }
+
+        // Keep the ring in front of views added since it was last shown.
+        focus_ring.remove_from_superview();
+        self.view.add_subview(focus_ring.clone());
+        focus_ring.set_hidden(false);
+    }
+
+    /// Shows the context menu of the view at `point`, or of its nearest
+    /// superview with one, with its corner at the point. Returns `false` if
+    /// none of them have a menu.
+    pub fn show_context_menu(&self, point: &Point<i32>) -> bool {
+        match self.view.context_menu_at(point) {
+            Some(menu) => {
+                ContextMenu::new(menu).present_at(point, &self.view);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// The title in the window's title bar.
+    pub fn title(&self) -> String {
+        self.context().title()
+    }
+
+    pub fn set_title(&self, title: &str) {
+        self.context().set_title(title);
+    }
+
+    /// The smallest size the user can resize the window to. Zero if there's
+    /// no limit, as there isn't by default.
+    pub fn min_size(&self) -> Size<u32> {
+        self.context().minimum_size()
+    }
+
+    pub fn set_min_size(&self, size: Size<u32>) {
+        self.context().set_minimum_size(&size);
+    }
+
+    /// The largest size the user can resize the window to. Zero if there's
+    /// no limit, as there isn't by default.
+    pub fn max_size(&self) -> Size<u32> {
+        self.context().maximum_size()
+    }
+
+    pub fn set_max_size(&self, size: Size<u32>) {
+        self.context().set_maximum_size(&size);
+    }
+
+    /// Whether the user can resize the window by dragging its edges.
+    pub fn is_resizable(&self) -> bool {
+        self.context().is_resizable()
+    }
+
+    /// Sets whether the user can resize the window. Windows aren't
+    /// resizable by default. See `on_resize`.
+    pub fn set_resizable(&self, resizable: bool) {
+        self.context().set_resizable(resizable);
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.context().is_fullscreen()
+    }
+
+    /// Makes the window fill its screen, or returns it to its size before.
+    /// The window's views are resized either way, as when the user resizes
+    /// the window.
+    pub fn toggle_fullscreen(&self) {
+        let context = self.context();
+        context.set_fullscreen(!context.is_fullscreen());
+    }
+
+    /// Sets what happens after the window is resized, such as by the user
+    /// dragging its edge. It's called with the window's new size, once its
+    /// views have been resized and laid out.
+    pub fn on_resize(&self, action: impl Fn(&Window, &Size<u32>) + 'static) {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        behavior.on_resize.replace(Some(Rc::new(action)));
+    }
+
+    /// Updates the window after the platform window was resized: the
+    /// context takes the new size, and the window's view is resized to it,
+    /// which resizes and lays out its subviews and redraws them.
+    pub(crate) fn did_resize(&self) {
+        let context = self.context();
+        let render_scale = context.render_scale();
+        let size = context.update_size();
+        let frame = self.view.frame();
+        self.view.set_frame(Rectangle { origin: frame.origin, size: size.clone() });
+
+        if context.render_scale() != render_scale {
+            self.view.render_scale_did_change(context.render_scale());
+        }
+
+        // The focused view may have moved.
+        self.update_focus_ring();
+
+        let action = {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            let action = behavior.on_resize.borrow().clone();
+            action
+        };
+
+        #[cfg(all(feature = "accesskit", target_os = "linux"))]
+        self.update_accessibility_screen_frames();
+
+        if let Some(action) = action {
+            action(self, &size);
+        }
+    }
+
+    /// Checks the render scale after the platform window moved, as it
+    /// changes when the window moves between screens of different densities,
+    /// such as from a 1x monitor to a Retina one. The window's views are
+    /// told, and redrawn at the new scale.
+    pub(crate) fn did_move(&self) {
+        let context = self.context();
+        let render_scale = context.render_scale();
+        context.update_size();
+
+        if context.render_scale() != render_scale {
+            self.view.render_scale_did_change(context.render_scale());
+        }
+
+        #[cfg(all(feature = "accesskit", target_os = "linux"))]
+        self.update_accessibility_screen_frames();
+    }
+
+    /// The window's undo manager, used by views in the window that don't
+    /// have their own.
+    pub fn undo_manager(&self) -> Rc<RefCell<UndoManager>> {
+        let behavior = self.view.behavior.borrow();
+        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+        behavior.undo_manager.clone()
+    }
+
     pub(crate) fn replace_first_responder(&self, view: View) -> bool {
         // If there is a first responder, ask whether it wants to resign. If it
         // doesn't, then we can't replace it.
@@ -117,9 +965,20 @@ impl Window {
             return false;
         }
 
-        let behavior = self.view.behavior.borrow();
-        let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
-        behavior.first_responder.replace(view.downgrade());
+        let previous = self.first_responder();
+        if previous == view {
+            return true;
+        }
+
+        {
+            let behavior = self.view.behavior.borrow();
+            let behavior = behavior.as_any().downcast_ref::<WindowBehavior>().unwrap();
+            behavior.first_responder.replace(view.downgrade());
+        }
+
+        previous.behavior.borrow().did_resign_first_responder();
+        view.behavior.borrow().did_become_first_responder();
+        self.update_focus_ring();
         return true;
     }
 }
@@ -149,11 +1008,95 @@ impl Behavior for WindowBehavior {
         true
     }
 
+    fn accessibility_role(&self) -> AccessibilityRole {
+        AccessibilityRole::Window
+    }
+
+    /// Windows are read out as their title.
+    fn accessibility_label(&self) -> Option<String> {
+        Some(self.context.title())
+    }
+
+    fn undo_manager(&self) -> Option<Rc<RefCell<UndoManager>>> {
+        Some(self.undo_manager.clone())
+    }
+
+    /// Presses that reach the window unhandled go to its view controller
+    /// first. Then they can still undo and redo actions in the window's
+    /// undo manager, and Tab moves keyboard focus. Anything else goes to the
+    /// application delegate, at the end of the responder chain.
+    fn press_began(&self, press: &Press) {
+        if self.view_controller.press_began(press) {
+            return;
+        }
+
+        let key = press.key();
+        let modifier_flags = key.modifier_flags();
+
+        if key.key_code() == KeyCode::Tab && !key.has_shortcut_modifier() && !modifier_flags.contains(&ModifierFlag::Alternate) {
+            let window = Window::from_view(self.view.upgrade().unwrap());
+            window.focus_next_view(modifier_flags.contains(&ModifierFlag::Shift));
+        } else if key.is_undo() {
+            self.undo_manager.borrow_mut().undo();
+        } else if key.is_redo() {
+            self.undo_manager.borrow_mut().redo();
+        } else {
+            let delegate = Application::borrow().delegate();
+            if let Some(delegate) = delegate {
+                delegate.press_began(press);
+            }
+        }
+    }
+
+    fn press_ended(&self, press: &Press) {
+        self.view_controller.press_ended(press);
+    }
+
+    /// Commands that reach the window unhandled go to its view controller,
+    /// then the window's undo manager, then the application delegate.
+    fn perform_command(&self, command: &Command) -> bool {
+        if self.view_controller.perform_command(command) || self.super_behavior().unwrap().perform_command(command) {
+            return true;
+        }
+
+        let delegate = Application::borrow().delegate();
+        delegate.is_some_and(|delegate| delegate.perform_command(command))
+    }
+
+    fn can_perform_command(&self, command: &Command) -> bool {
+        if self.view_controller.can_perform_command(command) || self.super_behavior().unwrap().can_perform_command(command) {
+            return true;
+        }
+
+        let delegate = Application::borrow().delegate();
+        delegate.is_some_and(|delegate| delegate.can_perform_command(command))
+    }
+
     /// For the `WindowBehavior` specifically, this will actually add a timer to
     /// the main loop to request a render.
     fn set_needs_display(&self) {
         self.super_behavior().unwrap().set_needs_display();
+        self.schedule_display();
+    }
 
+    /// Like `set_needs_display`, a render is requested, unless one already
+    /// was since the window was last drawn.
+    fn set_needs_display_in_rect(&self, rectangle: &Rectangle<i32, u32>) {
+        let window_view = self.view.upgrade().unwrap();
+        let is_scheduled = window_view.inner_self.borrow().layer.as_ref()
+            .is_some_and(|layer| layer.dirty_rectangle().is_some());
+
+        self.super_behavior().unwrap().set_needs_display_in_rect(rectangle);
+
+        if !is_scheduled {
+            self.schedule_display();
+        }
+    }
+}
+
+impl WindowBehavior {
+    /// Adds a timer to the main loop to render the window.
+    fn schedule_display(&self) {
         let window_view = self.view.upgrade().unwrap();
         {
             let behavior = window_view.behavior.borrow();
@@ -169,6 +1112,14 @@ impl Behavior for WindowBehavior {
     }
 }
 
+impl Drop for WindowBehavior {
+    /// Evicts the images cached for the window's context, which can't be
+    /// drawn with again.
+    fn drop(&mut self) {
+        ImageCache::remove_context(self.context.id());
+    }
+}
+
 impl PartialEq for WindowBehavior {
     fn eq(&self, rhs: &WindowBehavior) -> bool {
         self.view.id() == rhs.view.id()